@@ -1,10 +1,10 @@
 use backoff::{Error as BackoffError, ExponentialBackoff};
-use bdk_electrum::electrum_client::{Client, ConfigBuilder, ElectrumApi, Error};
+use bdk_electrum::electrum_client::{Client, ConfigBuilder, ElectrumApi, Error, Socks5Config};
 use bdk_electrum::BdkElectrumClient;
 use bitcoin::Transaction;
 use futures::future::join_all;
 use once_cell::sync::OnceCell;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use std::time::Instant;
@@ -26,6 +26,9 @@ where
     #[allow(clippy::type_complexity)]
     clients: Arc<RwLock<Vec<Arc<OnceCell<Arc<C>>>>>>,
     next: AtomicUsize,
+    /// How often we switched to the next node because a request failed.
+    /// Shared between all clones of the balancer.
+    failovers: Arc<AtomicU64>,
     config: ElectrumBalancerConfig,
     factory: Arc<dyn ElectrumClientFactory<C> + Send + Sync>,
 }
@@ -130,6 +133,7 @@ where
             urls,
             clients: Arc::new(RwLock::new(clients)),
             next: AtomicUsize::new(0),
+            failovers: Arc::new(AtomicU64::new(0)),
             config,
             factory,
         })
@@ -316,6 +320,7 @@ where
                         Some((current + 1) % num_clients)
                     })
                     .expect("fetch_update should never fail");
+                self.failovers.fetch_add(1, Ordering::Relaxed);
             },
         ) {
             Ok(result) => Ok(result),
@@ -499,6 +504,11 @@ where
         &self.config
     }
 
+    /// Get the number of times a failed request made us switch to the next node
+    pub fn failover_count(&self) -> u64 {
+        self.failovers.load(Ordering::Relaxed)
+    }
+
     /// Populate the transaction cache for all initialized clients.
     pub fn populate_tx_cache(&self, txs: impl IntoIterator<Item = impl Into<Arc<Transaction>>>) {
         // Convert transactions to Arc<Transaction> and collect them since we'll use them for each client
@@ -533,6 +543,7 @@ where
             urls: self.urls.clone(),
             clients: self.clients.clone(),
             next: AtomicUsize::new(self.next.load(Ordering::SeqCst)),
+            failovers: self.failovers.clone(),
             config: self.config.clone(),
             factory: self.factory.clone(),
         }
@@ -567,6 +578,8 @@ pub struct ElectrumBalancerConfig {
     pub request_timeout: u8,
     /// Minimum number of retry attempts across all nodes
     pub min_retries: usize,
+    /// SOCKS5 proxy all connections are made through, e.g. a Tor client
+    pub socks5_proxy: Option<Socks5Config>,
 }
 
 impl Default for ElectrumBalancerConfig {
@@ -574,6 +587,7 @@ impl Default for ElectrumBalancerConfig {
         Self {
             request_timeout: 15,
             min_retries: 15,
+            socks5_proxy: None,
         }
     }
 }
@@ -595,6 +609,7 @@ impl ElectrumClientFactory<BdkElectrumClient<Client>> for BdkElectrumClientFacto
         let client_config = ConfigBuilder::new()
             .timeout(Some(config.request_timeout))
             .retry(0)
+            .socks5(config.socks5_proxy.clone())
             .build();
 
         let client = Client::from_config(url, client_config).map_err(|e| {
@@ -973,6 +988,7 @@ mod tests {
         let config = ElectrumBalancerConfig {
             request_timeout: 5,
             min_retries: 0,
+            socks5_proxy: None,
         };
 
         let balancer = ElectrumBalancer::new_with_config_and_factory(urls, config, factory.clone())
@@ -1000,6 +1016,9 @@ mod tests {
         assert_eq!(factory.get_client(0).unwrap().call_count(), 2); // Called on both attempts
         assert_eq!(factory.get_client(1).unwrap().call_count(), 2); // Called on both attempts after client 0 fails
         assert_eq!(factory.get_client(2).unwrap().call_count(), 0); // Never called
+
+        // Each call failed over from client 0 to client 1 once
+        assert_eq!(balancer.failover_count(), 2);
     }
 
     #[tokio::test]
@@ -1046,6 +1065,7 @@ mod tests {
         let config = ElectrumBalancerConfig {
             request_timeout: 5,
             min_retries: 1,
+            socks5_proxy: None,
         };
 
         let balancer = ElectrumBalancer::new_with_config_and_factory(urls, config, factory.clone())
@@ -1188,6 +1208,7 @@ mod tests {
         let config = ElectrumBalancerConfig {
             request_timeout: 15,
             min_retries: 7,
+            socks5_proxy: None,
         };
 
         let factory = Arc::new(MockElectrumClientFactory::new());
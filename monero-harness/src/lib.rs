@@ -77,6 +77,7 @@ impl<'c> Monero {
             Daemon {
                 address: monerod_url,
                 ssl: false,
+                proxy: None,
             }
         };
 
@@ -6,6 +6,9 @@ pub struct Config {
     pub host: String,
     pub port: u16,
     pub data_dir: PathBuf,
+    /// SOCKS5 proxy (`[username:password@]host:port`) all requests to Monero nodes are made through, e.g. Tor
+    #[serde(default)]
+    pub socks5_proxy: Option<String>,
 }
 
 impl Config {
@@ -14,6 +17,7 @@ impl Config {
             host,
             port,
             data_dir,
+            socks5_proxy: None,
         }
     }
 
@@ -22,6 +26,12 @@ impl Config {
             host,
             port: 0,
             data_dir,
+            socks5_proxy: None,
         }
     }
+
+    pub fn with_socks5_proxy(mut self, socks5_proxy: impl Into<String>) -> Self {
+        self.socks5_proxy = Some(socks5_proxy.into());
+        self
+    }
 }
@@ -39,6 +39,7 @@ use proxy::{proxy_handler, stats_handler};
 #[derive(Clone)]
 pub struct AppState {
     pub node_pool: Arc<NodePool>,
+    pub socks5_proxy: Option<String>,
 }
 
 /// Manages background tasks for the RPC pool
@@ -97,7 +98,10 @@ async fn create_app_with_receiver(
         status_update_handle,
     };
 
-    let app_state = AppState { node_pool };
+    let app_state = AppState {
+        node_pool,
+        socks5_proxy: config.socks5_proxy,
+    };
 
     // Build the app
     let app = Router::new()
@@ -122,7 +126,7 @@ pub async fn create_app_with_data_dir(
     network: Network,
     data_dir: std::path::PathBuf,
 ) -> Result<Router> {
-    let config_with_data_dir = Config::new_with_port(config.host, config.port, data_dir);
+    let config_with_data_dir = Config { data_dir, ..config };
     create_app(config_with_data_dir, network).await
 }
 
@@ -145,7 +149,7 @@ pub async fn run_server_with_data_dir(
     network: Network,
     data_dir: std::path::PathBuf,
 ) -> Result<()> {
-    let config_with_data_dir = Config::new_with_port(config.host, config.port, data_dir);
+    let config_with_data_dir = Config { data_dir, ..config };
     run_server(config_with_data_dir, network).await
 }
 
@@ -163,7 +167,7 @@ pub async fn start_server_with_random_port(
     let host = config.host.clone();
 
     // If port is 0, the system will assign a random available port
-    let config_with_random_port = Config::new_random_port(config.host, config.data_dir);
+    let config_with_random_port = Config { port: 0, ..config };
 
     let (app, status_receiver, pool_handle) =
         create_app_with_receiver(config_with_random_port, network).await?;
@@ -203,6 +207,10 @@ pub async fn start_server_with_random_port_and_data_dir(
     tokio::sync::broadcast::Receiver<PoolStatus>,
     PoolHandle,
 )> {
-    let config_with_data_dir = Config::new_random_port(config.host, data_dir);
+    let config_with_data_dir = Config {
+        port: 0,
+        data_dir,
+        ..config
+    };
     start_server_with_random_port(config_with_data_dir, network).await
 }
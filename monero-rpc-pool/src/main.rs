@@ -47,6 +47,10 @@ struct Args {
     #[arg(short, long)]
     #[arg(help = "Enable verbose logging")]
     verbose: bool,
+
+    #[arg(long)]
+    #[arg(help = "SOCKS5 proxy (host:port) to reach the Monero nodes through, e.g. Tor")]
+    socks5_proxy: Option<String>,
 }
 
 #[tokio::main]
@@ -60,12 +64,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_line_number(true)
         .init();
 
-    let config = Config::new_with_port(
+    let mut config = Config::new_with_port(
         args.host,
         args.port,
         std::env::temp_dir().join("monero-rpc-pool"),
     );
 
+    if let Some(socks5_proxy) = args.socks5_proxy {
+        config = config.with_socks5_proxy(socks5_proxy);
+    }
+
     info!(
         host = config.host,
         port = config.port,
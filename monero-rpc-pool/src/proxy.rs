@@ -65,9 +65,18 @@ async fn raw_http_request(
     method: &str,
     headers: &HeaderMap,
     body: Option<&[u8]>,
+    socks5_proxy: Option<&str>,
 ) -> Result<Response, HandlerError> {
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(30))
+    let mut client_builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(30));
+
+    // Resolve the node's hostname through the proxy as well (`socks5h`) to not leak it
+    if let Some(socks5_proxy) = socks5_proxy {
+        let proxy = reqwest::Proxy::all(format!("socks5h://{}", socks5_proxy))
+            .map_err(|e| HandlerError::RequestError(format!("{:#?}", e)))?;
+        client_builder = client_builder.proxy(proxy);
+    }
+
+    let client = client_builder
         .build()
         .map_err(|e| HandlerError::RequestError(format!("{:#?}", e)))?;
 
@@ -177,10 +186,11 @@ async fn single_raw_request(
     method: &str,
     headers: &HeaderMap,
     body: Option<&[u8]>,
+    socks5_proxy: Option<&str>,
 ) -> Result<(Response, (String, String, i64), f64), HandlerError> {
     let start_time = Instant::now();
 
-    match raw_http_request(node_url.clone(), path, method, headers, body).await {
+    match raw_http_request(node_url.clone(), path, method, headers, body, socks5_proxy).await {
         Ok(response) => {
             let elapsed = start_time.elapsed();
             let latency_ms = elapsed.as_millis() as f64;
@@ -283,7 +293,16 @@ async fn sequential_requests(
             ),
         }
 
-        match single_raw_request(node.clone(), path, method, headers, body).await {
+        match single_raw_request(
+            node.clone(),
+            path,
+            method,
+            headers,
+            body,
+            state.socks5_proxy.as_deref(),
+        )
+        .await
+        {
             Ok((response, winning_node, latency_ms)) => {
                 let (scheme, host, port) = &winning_node;
                 let winning_node_display = format!("{}://{}:{}", scheme, host, port);
@@ -234,6 +234,108 @@ namespace Monero
     {
         v.push_back(s);
     }
+
+    /**
+     * CXX doesn't support overloaded methods, TransactionHistory::transaction is overloaded
+     * with a version that takes the txid.
+     */
+    inline TransactionInfo *transactionHistoryTransaction(const TransactionHistory &history, int index)
+    {
+        return history.transaction(index);
+    }
+
+    inline std::unique_ptr<std::string> transactionInfoHash(const TransactionInfo &tx)
+    {
+        return std::make_unique<std::string>(tx.hash());
+    }
+
+    /**
+     * std::time_t is not supported by CXX.
+     */
+    inline uint64_t transactionInfoTimestamp(const TransactionInfo &tx)
+    {
+        return static_cast<uint64_t>(tx.timestamp());
+    }
+
+    /**
+     * std::set is not supported by CXX, so we copy the indices into a vector.
+     */
+    inline std::unique_ptr<std::vector<uint32_t>> transactionInfoSubaddrIndices(const TransactionInfo &tx)
+    {
+        auto indices = tx.subaddrIndex();
+        return std::make_unique<std::vector<uint32_t>>(indices.begin(), indices.end());
+    }
+
+    /**
+     * CXX doesn't support vectors of pointers, so we access the rows of
+     * Subaddress::getAll by index. The rows are those of the account last passed to
+     * Subaddress::refresh, the position of a row is its address index.
+     */
+    inline size_t subaddressRowCount(const Subaddress &subaddress)
+    {
+        return subaddress.getAll().size();
+    }
+
+    inline std::unique_ptr<std::string> subaddressRowAddress(const Subaddress &subaddress, size_t index)
+    {
+        return std::make_unique<std::string>(subaddress.getAll().at(index)->getAddress());
+    }
+
+    inline std::unique_ptr<std::string> subaddressRowLabel(const Subaddress &subaddress, size_t index)
+    {
+        return std::make_unique<std::string>(subaddress.getAll().at(index)->getLabel());
+    }
+
+    /**
+     * Same as for the subaddress rows, the position of a row is the account index.
+     */
+    inline size_t subaddressAccountRowCount(const SubaddressAccount &account)
+    {
+        return account.getAll().size();
+    }
+
+    inline std::unique_ptr<std::string> subaddressAccountRowAddress(const SubaddressAccount &account, size_t index)
+    {
+        return std::make_unique<std::string>(account.getAll().at(index)->getAddress());
+    }
+
+    inline std::unique_ptr<std::string> subaddressAccountRowLabel(const SubaddressAccount &account, size_t index)
+    {
+        return std::make_unique<std::string>(account.getAll().at(index)->getLabel());
+    }
+
+    inline std::unique_ptr<std::string> walletSecretViewKey(const Wallet &wallet)
+    {
+        return std::make_unique<std::string>(wallet.secretViewKey());
+    }
+
+    inline std::unique_ptr<std::string> unsignedTransactionErrorString(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::string>(tx.errorString());
+    }
+
+    inline std::unique_ptr<std::vector<uint64_t>> unsignedTransactionAmounts(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::vector<uint64_t>>(tx.amount());
+    }
+
+    inline std::unique_ptr<std::vector<uint64_t>> unsignedTransactionFees(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::vector<uint64_t>>(tx.fee());
+    }
+
+    inline std::unique_ptr<std::vector<std::string>> unsignedTransactionRecipients(const UnsignedTransaction &tx)
+    {
+        return std::make_unique<std::vector<std::string>>(tx.recipientAddress());
+    }
+
+    /**
+     * Unlike pending transactions, unsigned transactions are not disposed by the wallet.
+     */
+    inline void disposeUnsignedTransaction(UnsignedTransaction *tx)
+    {
+        delete tx;
+    }
 }
 
 #include "easylogging++.h"
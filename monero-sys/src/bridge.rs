@@ -59,6 +59,21 @@ pub mod ffi {
         /// Can be attached to a wallet and will get notified upon specific events.
         type WalletListener;
 
+        /// The transaction history of a wallet. Owned by the wallet.
+        type TransactionHistory;
+
+        /// A single transaction of the transaction history.
+        type TransactionInfo;
+
+        /// The subaddresses of a single account of a wallet. Owned by the wallet.
+        type Subaddress;
+
+        /// The accounts of a wallet. Owned by the wallet.
+        type SubaddressAccount;
+
+        /// A transaction created by a view-only wallet, to be signed by the full wallet.
+        type UnsignedTransaction;
+
         /// Get the wallet manager.
         fn getWalletManager() -> Result<*mut WalletManager>;
 
@@ -123,6 +138,9 @@ pub mod ffi {
         /// Set the address of the remote node ("daemon").
         fn setDaemonAddress(self: Pin<&mut WalletManager>, address: &CxxString) -> Result<()>;
 
+        /// Set the SOCKS proxy used to connect to the remote node.
+        fn setProxy(self: Pin<&mut WalletManager>, address: &CxxString) -> Result<bool>;
+
         /// Get the path of the wallet.
         fn walletPath(wallet: &Wallet) -> Result<UniquePtr<CxxString>>;
 
@@ -278,6 +296,186 @@ pub mod ffi {
             address: &CxxString,
             sign_with_view_key: bool,
         ) -> Result<UniquePtr<CxxString>>;
+
+        /// Store the wallet to the given path, empty path means the current one.
+        fn store(self: Pin<&mut Wallet>, path: &CxxString) -> Result<bool>;
+
+        /// Get the balance of a single account in atomic units (piconero).
+        fn balance(self: &Wallet, account_index: u32) -> Result<u64>;
+
+        /// Get the unlocked balance of a single account in atomic units (piconero).
+        fn unlockedBalance(self: &Wallet, account_index: u32) -> Result<u64>;
+
+        /// Get the transaction history of the wallet.
+        fn history(self: Pin<&mut Wallet>) -> Result<*mut TransactionHistory>;
+
+        /// Reload the transaction history from the wallet.
+        fn refresh(self: Pin<&mut TransactionHistory>) -> Result<()>;
+
+        /// Get the number of transactions in the history.
+        fn count(self: &TransactionHistory) -> Result<i32>;
+
+        /// Get the transaction at the given index of the history.
+        fn transactionHistoryTransaction(
+            history: &TransactionHistory,
+            index: i32,
+        ) -> Result<*mut TransactionInfo>;
+
+        /// Whether the transaction is incoming (0) or outgoing (1).
+        fn direction(self: &TransactionInfo) -> Result<i32>;
+
+        /// Whether the transaction is still in the mempool.
+        fn isPending(self: &TransactionInfo) -> Result<bool>;
+
+        /// Whether the transaction failed to be published.
+        fn isFailed(self: &TransactionInfo) -> Result<bool>;
+
+        /// The amount of the transaction in atomic units (piconero).
+        fn amount(self: &TransactionInfo) -> Result<u64>;
+
+        /// The fee of the transaction in atomic units (piconero).
+        fn fee(self: &TransactionInfo) -> Result<u64>;
+
+        /// The height of the block the transaction was included in.
+        fn blockHeight(self: &TransactionInfo) -> Result<u64>;
+
+        /// The number of confirmations of the transaction.
+        fn confirmations(self: &TransactionInfo) -> Result<u64>;
+
+        /// The account the transaction belongs to.
+        fn subaddrAccount(self: &TransactionInfo) -> Result<u32>;
+
+        /// Get the hash (txid) of the transaction.
+        fn transactionInfoHash(tx: &TransactionInfo) -> Result<UniquePtr<CxxString>>;
+
+        /// Get the timestamp of the transaction in seconds since the unix epoch.
+        fn transactionInfoTimestamp(tx: &TransactionInfo) -> Result<u64>;
+
+        /// Get the indices of the subaddresses the transaction received to or spent from.
+        fn transactionInfoSubaddrIndices(tx: &TransactionInfo)
+            -> Result<UniquePtr<CxxVector<u32>>>;
+
+        /// Get the subaddresses of the wallet.
+        fn subaddress(self: Pin<&mut Wallet>) -> Result<*mut Subaddress>;
+
+        /// Add a subaddress with a label to an account.
+        fn addRow(self: Pin<&mut Subaddress>, account_index: u32, label: &CxxString) -> Result<()>;
+
+        /// Set the label of a subaddress.
+        fn setLabel(
+            self: Pin<&mut Subaddress>,
+            account_index: u32,
+            address_index: u32,
+            label: &CxxString,
+        ) -> Result<()>;
+
+        /// Reload the subaddresses of an account from the wallet.
+        fn refresh(self: Pin<&mut Subaddress>, account_index: u32) -> Result<()>;
+
+        /// Get the number of loaded subaddresses.
+        fn subaddressRowCount(subaddress: &Subaddress) -> Result<usize>;
+
+        /// Get the address of the subaddress at the given index.
+        fn subaddressRowAddress(
+            subaddress: &Subaddress,
+            index: usize,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Get the label of the subaddress at the given index.
+        fn subaddressRowLabel(
+            subaddress: &Subaddress,
+            index: usize,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Get the accounts of the wallet.
+        fn subaddressAccount(self: Pin<&mut Wallet>) -> Result<*mut SubaddressAccount>;
+
+        /// Add an account with a label.
+        fn addRow(self: Pin<&mut SubaddressAccount>, label: &CxxString) -> Result<()>;
+
+        /// Set the label of an account.
+        fn setLabel(
+            self: Pin<&mut SubaddressAccount>,
+            account_index: u32,
+            label: &CxxString,
+        ) -> Result<()>;
+
+        /// Reload the accounts from the wallet.
+        fn refresh(self: Pin<&mut SubaddressAccount>) -> Result<()>;
+
+        /// Get the number of loaded accounts.
+        fn subaddressAccountRowCount(account: &SubaddressAccount) -> Result<usize>;
+
+        /// Get the primary address of the account at the given index.
+        fn subaddressAccountRowAddress(
+            account: &SubaddressAccount,
+            index: usize,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Get the label of the account at the given index.
+        fn subaddressAccountRowLabel(
+            account: &SubaddressAccount,
+            index: usize,
+        ) -> Result<UniquePtr<CxxString>>;
+
+        /// Check whether the wallet is view-only (has no spend key).
+        fn watchOnly(self: &Wallet) -> Result<bool>;
+
+        /// Get the secret view key of the wallet.
+        fn walletSecretViewKey(wallet: &Wallet) -> Result<UniquePtr<CxxString>>;
+
+        /// Export the outputs of the wallet, such that a full wallet can sign transactions
+        /// spending them.
+        fn exportOutputs(self: Pin<&mut Wallet>, filename: &CxxString, all: bool) -> Result<bool>;
+
+        /// Import outputs exported by a view-only wallet.
+        fn importOutputs(self: Pin<&mut Wallet>, filename: &CxxString) -> Result<bool>;
+
+        /// Export the key images of the wallet, such that a view-only wallet can tell which
+        /// outputs are spent.
+        fn exportKeyImages(self: Pin<&mut Wallet>, filename: &CxxString, all: bool)
+            -> Result<bool>;
+
+        /// Import key images exported by a full wallet.
+        fn importKeyImages(self: Pin<&mut Wallet>, filename: &CxxString) -> Result<bool>;
+
+        /// Load an unsigned transaction created by a view-only wallet.
+        fn loadUnsignedTx(
+            self: Pin<&mut Wallet>,
+            unsigned_filename: &CxxString,
+        ) -> Result<*mut UnsignedTransaction>;
+
+        /// Publish a transaction signed by a full wallet.
+        fn submitTransaction(self: Pin<&mut Wallet>, filename: &CxxString) -> Result<bool>;
+
+        /// Get the status of an unsigned transaction.
+        fn status(self: &UnsignedTransaction) -> Result<i32>;
+
+        /// Get the error string of an unsigned transaction.
+        fn unsignedTransactionErrorString(tx: &UnsignedTransaction)
+            -> Result<UniquePtr<CxxString>>;
+
+        /// Get the amounts sent by the unsigned transaction, one per destination.
+        fn unsignedTransactionAmounts(
+            tx: &UnsignedTransaction,
+        ) -> Result<UniquePtr<CxxVector<u64>>>;
+
+        /// Get the fees of the unsigned transaction, one per transaction.
+        fn unsignedTransactionFees(tx: &UnsignedTransaction) -> Result<UniquePtr<CxxVector<u64>>>;
+
+        /// Get the addresses the unsigned transaction sends to, one per destination.
+        fn unsignedTransactionRecipients(
+            tx: &UnsignedTransaction,
+        ) -> Result<UniquePtr<CxxVector<CxxString>>>;
+
+        /// Get the number of transactions in the unsigned transaction set.
+        fn txCount(self: &UnsignedTransaction) -> Result<u64>;
+
+        /// Sign the unsigned transaction and write it to the given file.
+        fn sign(self: Pin<&mut UnsignedTransaction>, signed_filename: &CxxString) -> Result<bool>;
+
+        /// Dispose of an unsigned transaction object.
+        unsafe fn disposeUnsignedTransaction(tx: *mut UnsignedTransaction) -> Result<()>;
     }
 }
 
@@ -13,7 +13,14 @@
 mod bridge;
 
 use std::{
-    any::Any, cmp::Ordering, fmt::Display, ops::Deref, path::PathBuf, pin::Pin, str::FromStr,
+    any::Any,
+    cmp::Ordering,
+    collections::HashSet,
+    fmt::Display,
+    ops::Deref,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str::FromStr,
     time::Duration,
 };
 
@@ -121,11 +128,75 @@ pub struct TxReceipt {
 pub struct Daemon {
     pub address: String,
     pub ssl: bool,
+    /// SOCKS4a proxy (`host:port`) to connect to the daemon through, e.g. Tor.
+    pub proxy: Option<String>,
 }
 
 /// A wrapper around a pending transaction.
 pub struct PendingTransaction(*mut ffi::PendingTransaction);
 
+/// A transaction from the history of a wallet.
+#[derive(Debug, Clone)]
+pub struct TransactionInfo {
+    pub txid: String,
+    pub direction: TransactionDirection,
+    /// For incoming transactions the amount we received, for outgoing
+    /// transactions the amount we sent (excluding the fee and change).
+    pub amount: monero::Amount,
+    pub fee: monero::Amount,
+    /// `None` while the transaction is in the mempool.
+    pub block_height: Option<u64>,
+    pub confirmations: u64,
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    /// The transaction is in the mempool.
+    pub pending: bool,
+    /// The transaction failed to be published.
+    pub failed: bool,
+    pub account_index: u32,
+    /// The subaddresses (of the account) the transaction received to or spent from.
+    pub subaddress_indices: Vec<u32>,
+}
+
+/// Whether a transaction received or sent funds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// A subaddress of a wallet account.
+#[derive(Debug, Clone)]
+pub struct Subaddress {
+    pub account_index: u32,
+    /// Index 0 is the primary address of the account.
+    pub address_index: u32,
+    pub address: monero::Address,
+    pub label: String,
+}
+
+/// A transaction created by a view-only wallet, as seen by the wallet that signs it.
+#[derive(Debug, Clone)]
+pub struct UnsignedTransfer {
+    /// The (first) destination of each transaction.
+    pub recipients: Vec<monero::Address>,
+    /// The amount sent to each destination, excluding change.
+    pub amounts: Vec<monero::Amount>,
+    /// The fee of each transaction.
+    pub fees: Vec<monero::Amount>,
+}
+
+/// An account of a wallet. Each account has its own subaddresses and balance.
+#[derive(Debug, Clone)]
+pub struct SubaddressAccount {
+    pub index: u32,
+    /// The primary address of the account.
+    pub address: monero::Address,
+    pub label: String,
+    pub balance: monero::Amount,
+    pub unlocked_balance: monero::Amount,
+}
+
 impl WalletHandle {
     /// Open an existing wallet or create a new one, with a random seed.
     pub async fn open_or_create(
@@ -305,7 +376,7 @@ impl WalletHandle {
                         network,
                         &address,
                         view_key,
-                        spend_key,
+                        Some(spend_key),
                         restore_height,
                         background_sync,
                         daemon.clone(),
@@ -328,6 +399,73 @@ impl WalletHandle {
         Ok(wallet)
     }
 
+    /// Open an existing view-only wallet or create a new one from the address and
+    /// the private view key. A view-only wallet can see incoming funds but can't
+    /// spend them, its transactions have to be signed by the full wallet.
+    /// See [`WalletHandle::create_unsigned_transfer`].
+    #[allow(clippy::too_many_arguments)]
+    pub async fn open_or_create_view_only(
+        path: String,
+        password: Option<String>,
+        network: monero::Network,
+        address: monero::Address,
+        view_key: monero::PrivateKey,
+        restore_height: u64,
+        background_sync: bool,
+        daemon: Daemon,
+    ) -> anyhow::Result<Self> {
+        let (call_sender, call_receiver) = unbounded_channel();
+
+        let wallet_name = path
+            .split('/')
+            .last()
+            .map(ToString::to_string)
+            .unwrap_or(path.clone());
+
+        let thread_name = format!("wallet-{}", wallet_name);
+
+        // Capture current dispatcher before spawning
+        let current_dispatcher = tracing::dispatcher::get_default(|d| d.clone());
+
+        std::thread::Builder::new()
+            .name(thread_name)
+            .spawn(move || {
+                // Set the dispatcher for this thread
+                let _guard = tracing::dispatcher::set_default(&current_dispatcher);
+
+                let mut manager = WalletManager::new(daemon.clone(), &wallet_name)
+                    .expect("wallet manager to be created");
+
+                let wallet = manager
+                    .open_or_create_wallet_from_keys(
+                        &path,
+                        password.as_deref(),
+                        network,
+                        &address,
+                        view_key,
+                        None,
+                        restore_height,
+                        background_sync,
+                        daemon.clone(),
+                    )
+                    .expect("view-only wallet to be opened or created from keys");
+
+                let mut wrapped_wallet = Wallet::new(wallet, manager, call_receiver);
+
+                wrapped_wallet.run();
+            })
+            .context("Couldn't start wallet thread")?;
+
+        let wallet = WalletHandle { call_sender };
+        // Make a test call to ensure that the wallet is created.
+        wallet
+            .check_wallet()
+            .await
+            .context("Failed to create view-only wallet")?;
+
+        Ok(wallet)
+    }
+
     /// Execute a function on the wallet thread and return the result.
     /// Necessary because every interaction with the wallet must run on a single thread.
     /// Panics if the channel is closed unexpectedly.
@@ -462,13 +600,166 @@ impl WalletHandle {
         self.call(move |wallet| wallet.total_balance()).await
     }
 
+    /// Get all incoming and outgoing transactions of the wallet, across all accounts.
+    pub async fn history(&self) -> anyhow::Result<Vec<TransactionInfo>> {
+        self.call(move |wallet| wallet.history()).await
+    }
+
+    /// Get the key of a transaction we sent, which proves that we sent it.
+    pub async fn tx_key(&self, txid: &str) -> anyhow::Result<String> {
+        let txid = txid.to_string();
+
+        self.call(move |wallet| wallet.tx_key(&txid)).await
+    }
+
+    /// Get the subaddresses of an account, including its primary address at index 0.
+    pub async fn subaddresses(&self, account_index: u32) -> anyhow::Result<Vec<Subaddress>> {
+        self.call(move |wallet| wallet.subaddresses(account_index))
+            .await
+    }
+
+    /// Create a new subaddress in an account and persist the wallet.
+    pub async fn create_subaddress(
+        &self,
+        account_index: u32,
+        label: &str,
+    ) -> anyhow::Result<Subaddress> {
+        let label = label.to_string();
+
+        self.call(move |wallet| wallet.create_subaddress(account_index, &label))
+            .await
+    }
+
+    /// Set the label of a subaddress and persist the wallet.
+    pub async fn set_subaddress_label(
+        &self,
+        account_index: u32,
+        address_index: u32,
+        label: &str,
+    ) -> anyhow::Result<()> {
+        let label = label.to_string();
+
+        self.call(move |wallet| wallet.set_subaddress_label(account_index, address_index, &label))
+            .await
+    }
+
+    /// Get the accounts of the wallet with their balances.
+    pub async fn accounts(&self) -> anyhow::Result<Vec<SubaddressAccount>> {
+        self.call(move |wallet| wallet.accounts()).await
+    }
+
+    /// Create a new account and persist the wallet.
+    pub async fn create_account(&self, label: &str) -> anyhow::Result<SubaddressAccount> {
+        let label = label.to_string();
+
+        self.call(move |wallet| wallet.create_account(&label)).await
+    }
+
+    /// Check whether the wallet is view-only, i.e. can't sign transactions.
+    pub async fn is_view_only(&self) -> bool {
+        self.call(move |wallet| wallet.is_view_only()).await
+    }
+
+    /// Get the private view key of the wallet.
+    pub async fn secret_view_key(&self) -> anyhow::Result<monero::PrivateKey> {
+        self.call(move |wallet| wallet.secret_view_key()).await
+    }
+
+    /// Create a transfer with a view-only wallet and write the unsigned transaction
+    /// to `unsigned_path`. It has to be signed by the full wallet with
+    /// [`WalletHandle::sign_unsigned_transaction`] and then published with
+    /// [`WalletHandle::submit_signed_transaction`].
+    pub async fn create_unsigned_transfer(
+        &self,
+        address: &monero::Address,
+        amount: monero::Amount,
+        unsigned_path: &Path,
+    ) -> anyhow::Result<()> {
+        let address = *address;
+        let unsigned_path = unsigned_path.to_path_buf();
+
+        self.call(move |wallet| wallet.create_unsigned_transfer(&address, amount, &unsigned_path))
+            .await
+    }
+
+    /// Export the outputs of a view-only wallet, the full wallet needs them
+    /// to sign transactions.
+    pub async fn export_outputs(&self, path: &Path) -> anyhow::Result<()> {
+        let path = path.to_path_buf();
+
+        self.call(move |wallet| wallet.export_outputs(&path)).await
+    }
+
+    /// Import the outputs exported by a view-only wallet.
+    pub async fn import_outputs(&self, path: &Path) -> anyhow::Result<()> {
+        let path = path.to_path_buf();
+
+        self.call(move |wallet| wallet.import_outputs(&path)).await
+    }
+
+    /// Export the key images of the full wallet, the view-only wallet needs them
+    /// to know which of its outputs are spent.
+    pub async fn export_key_images(&self, path: &Path) -> anyhow::Result<()> {
+        let path = path.to_path_buf();
+
+        self.call(move |wallet| wallet.export_key_images(&path))
+            .await
+    }
+
+    /// Import the key images exported by the full wallet.
+    pub async fn import_key_images(&self, path: &Path) -> anyhow::Result<()> {
+        let path = path.to_path_buf();
+
+        self.call(move |wallet| wallet.import_key_images(&path))
+            .await
+    }
+
+    /// Load an unsigned transaction without signing it, to check what it spends.
+    pub async fn inspect_unsigned_transaction(
+        &self,
+        unsigned_path: &Path,
+    ) -> anyhow::Result<UnsignedTransfer> {
+        let unsigned_path = unsigned_path.to_path_buf();
+
+        self.call(move |wallet| wallet.load_unsigned_transaction(&unsigned_path, None))
+            .await
+    }
+
+    /// Sign an unsigned transaction created by a view-only wallet and write it to
+    /// `signed_path`. Only possible with the full wallet.
+    pub async fn sign_unsigned_transaction(
+        &self,
+        unsigned_path: &Path,
+        signed_path: &Path,
+    ) -> anyhow::Result<UnsignedTransfer> {
+        let unsigned_path = unsigned_path.to_path_buf();
+        let signed_path = signed_path.to_path_buf();
+
+        self.call(move |wallet| {
+            wallet.load_unsigned_transaction(&unsigned_path, Some(&signed_path))
+        })
+        .await
+    }
+
+    /// Publish a transaction signed by the full wallet and return the receipts
+    /// of the published transactions.
+    pub async fn submit_signed_transaction(
+        &self,
+        signed_path: &Path,
+    ) -> anyhow::Result<Vec<TxReceipt>> {
+        let signed_path = signed_path.to_path_buf();
+
+        self.call(move |wallet| wallet.submit_signed_transaction(&signed_path))
+            .await
+    }
+
     /// Check if the wallet is synchronized.
     async fn synchronized(&self) -> bool {
         self.call(move |wallet| wallet.synchronized()).await
     }
 
     /// Get the sync progress of the wallet.
-    async fn sync_progress(&self) -> SyncProgress {
+    pub async fn sync_progress(&self) -> SyncProgress {
         self.call(move |wallet| wallet.sync_progress()).await
     }
 
@@ -768,6 +1059,10 @@ impl WalletManager {
 
         manager.set_daemon_address(&daemon.address);
 
+        if let Some(proxy) = &daemon.proxy {
+            manager.set_proxy(proxy)?;
+        }
+
         Ok(manager)
     }
 
@@ -833,12 +1128,12 @@ impl WalletManager {
         network: monero::Network,
         address: &monero::Address,
         view_key: monero::PrivateKey,
-        spend_key: monero::PrivateKey,
+        spend_key: Option<monero::PrivateKey>,
         restore_height: u64,
         background_sync: bool,
         daemon: Daemon,
     ) -> Result<FfiWallet> {
-        tracing::debug!(%path, "Creating wallet from keys");
+        tracing::debug!(%path, view_only = spend_key.is_none(), "Creating wallet from keys");
 
         if self.wallet_exists(path) {
             tracing::info!(wallet=%path, "Wallet already exists, opening it");
@@ -870,7 +1165,8 @@ impl WalletManager {
         let network_type = network.into();
         let_cxx_string!(address = address.to_string());
         let_cxx_string!(view_key = view_key.to_string());
-        let_cxx_string!(spend_key = spend_key.to_string());
+        // An empty spend key creates a view-only wallet
+        let_cxx_string!(spend_key = spend_key.map(|key| key.to_string()).unwrap_or_default());
         let kdf_rounds = Self::DEFAULT_KDF_ROUNDS;
 
         let wallet_pointer = self
@@ -1011,6 +1307,25 @@ impl WalletManager {
             .expect("Shouldn't panic");
     }
 
+    /// Set the proxy used to connect to the remote node ("daemon").
+    fn set_proxy(&mut self, address: &str) -> anyhow::Result<()> {
+        tracing::debug!(%address, "Updating wallet manager's proxy");
+
+        let_cxx_string!(address = address);
+
+        let success = self
+            .inner
+            .pinned()
+            .setProxy(&address)
+            .context("Failed to set proxy: FFI call failed with exception")?;
+
+        if !success {
+            anyhow::bail!("Failed to set proxy of the wallet manager");
+        }
+
+        Ok(())
+    }
+
     /// Check if a wallet exists at the given path.
     pub fn wallet_exists(&mut self, path: &str) -> bool {
         tracing::debug!(%path, "Checking if wallet exists");
@@ -1064,7 +1379,7 @@ impl FfiWallet {
             backoff(None, None),
             || {
                 wallet
-                    .init(&daemon.address, daemon.ssl)
+                    .init(&daemon.address, daemon.ssl, daemon.proxy.as_deref())
                     .context("Failed to initialize wallet")
                     .map_err(backoff::Error::transient)
             },
@@ -1139,13 +1454,19 @@ impl FfiWallet {
 
     /// Initialize the wallet and download initial values from the remote node.
     /// Does not actuallyt sync the wallet, use any of the refresh methods to do that.
-    fn init(&mut self, daemon_address: &str, ssl: bool) -> anyhow::Result<()> {
-        tracing::debug!(%daemon_address, %ssl, "Initializing wallet");
+    /// If a proxy is given, all connections to the remote node are made through it.
+    fn init(
+        &mut self,
+        daemon_address: &str,
+        ssl: bool,
+        proxy_address: Option<&str>,
+    ) -> anyhow::Result<()> {
+        tracing::debug!(%daemon_address, %ssl, ?proxy_address, "Initializing wallet");
 
         let_cxx_string!(daemon_address = daemon_address);
         let_cxx_string!(daemon_username = "");
         let_cxx_string!(daemon_password = "");
-        let_cxx_string!(proxy_address = "");
+        let_cxx_string!(proxy_address = proxy_address.unwrap_or_default());
 
         let raw_wallet = &mut self.inner;
 
@@ -1332,6 +1653,429 @@ impl FfiWallet {
         monero::Amount::from_pico(balance)
     }
 
+    /// Get the key of an outgoing transaction.
+    fn tx_key(&self, txid: &str) -> anyhow::Result<String> {
+        let_cxx_string!(txid = txid);
+
+        Ok(ffi::walletGetTxKey(&self.inner, &txid)
+            .context("Failed to get tx key from wallet: FFI call failed with exception")?
+            .to_string())
+    }
+
+    /// Get the transaction history of the wallet.
+    fn history(&mut self) -> anyhow::Result<Vec<TransactionInfo>> {
+        let history = self
+            .inner
+            .pinned()
+            .history()
+            .context("Failed to get transaction history: FFI call failed with exception")?;
+
+        // Safety: the history is owned by the wallet and lives as long as the wallet
+        let mut history = unsafe { history.as_mut() }
+            .map(|history| unsafe { Pin::new_unchecked(history) })
+            .context("Failed to get transaction history: got null pointer")?;
+
+        history
+            .as_mut()
+            .refresh()
+            .context("Failed to refresh transaction history: FFI call failed with exception")?;
+
+        let count = history
+            .count()
+            .context("Failed to count transactions: FFI call failed with exception")?;
+
+        (0..count)
+            .map(|index| {
+                let tx = ffi::transactionHistoryTransaction(&history, index)
+                    .context("Failed to get transaction: FFI call failed with exception")?;
+                // Safety: the transaction is owned by the history
+                let tx = unsafe { tx.as_ref() }
+                    .context("Failed to get transaction: got null pointer")?;
+
+                TransactionInfo::from_ffi(tx)
+            })
+            .collect()
+    }
+
+    /// Get the subaddresses of an account.
+    fn subaddresses(&mut self, account_index: u32) -> anyhow::Result<Vec<Subaddress>> {
+        let mut subaddress = self.subaddress_table()?;
+
+        subaddress
+            .as_mut()
+            .refresh(account_index)
+            .context("Failed to refresh subaddresses: FFI call failed with exception")?;
+
+        let count = ffi::subaddressRowCount(&subaddress)
+            .context("Failed to count subaddresses: FFI call failed with exception")?;
+
+        (0..count)
+            .map(|index| {
+                let address = ffi::subaddressRowAddress(&subaddress, index)
+                    .context("Failed to get subaddress: FFI call failed with exception")?
+                    .to_string();
+                let label = ffi::subaddressRowLabel(&subaddress, index)
+                    .context("Failed to get subaddress label: FFI call failed with exception")?
+                    .to_string();
+
+                Ok(Subaddress {
+                    account_index,
+                    address_index: index as u32,
+                    address: monero::Address::from_str(&address)
+                        .context("Failed to parse subaddress")?,
+                    label,
+                })
+            })
+            .collect()
+    }
+
+    /// Add a subaddress to an account and return it.
+    fn create_subaddress(&mut self, account_index: u32, label: &str) -> anyhow::Result<Subaddress> {
+        let_cxx_string!(label = label);
+
+        self.subaddress_table()?
+            .addRow(account_index, &label)
+            .context("Failed to create subaddress: FFI call failed with exception")?;
+        self.check_error().context("Failed to create subaddress")?;
+        self.store()?;
+
+        self.subaddresses(account_index)?
+            .pop()
+            .context("Created subaddress is missing")
+    }
+
+    fn set_subaddress_label(
+        &mut self,
+        account_index: u32,
+        address_index: u32,
+        label: &str,
+    ) -> anyhow::Result<()> {
+        let_cxx_string!(label = label);
+
+        self.subaddress_table()?
+            .setLabel(account_index, address_index, &label)
+            .context("Failed to set subaddress label: FFI call failed with exception")?;
+        self.check_error()
+            .context("Failed to set subaddress label")?;
+
+        self.store()
+    }
+
+    /// Get the accounts of the wallet.
+    fn accounts(&mut self) -> anyhow::Result<Vec<SubaddressAccount>> {
+        let mut accounts = self.account_table()?;
+
+        accounts
+            .as_mut()
+            .refresh()
+            .context("Failed to refresh accounts: FFI call failed with exception")?;
+
+        let count = ffi::subaddressAccountRowCount(&accounts)
+            .context("Failed to count accounts: FFI call failed with exception")?;
+
+        let rows = (0..count)
+            .map(|index| {
+                let address = ffi::subaddressAccountRowAddress(&accounts, index)
+                    .context("Failed to get account address: FFI call failed with exception")?
+                    .to_string();
+                let label = ffi::subaddressAccountRowLabel(&accounts, index)
+                    .context("Failed to get account label: FFI call failed with exception")?
+                    .to_string();
+
+                Ok((index as u32, address, label))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(index, address, label)| {
+                let balance = self
+                    .inner
+                    .balance(index)
+                    .context("Failed to get account balance: FFI call failed with exception")?;
+                let unlocked_balance = self.inner.unlockedBalance(index).context(
+                    "Failed to get unlocked account balance: FFI call failed with exception",
+                )?;
+
+                Ok(SubaddressAccount {
+                    index,
+                    address: monero::Address::from_str(&address)
+                        .context("Failed to parse account address")?,
+                    label,
+                    balance: monero::Amount::from_pico(balance),
+                    unlocked_balance: monero::Amount::from_pico(unlocked_balance),
+                })
+            })
+            .collect()
+    }
+
+    /// Add an account to the wallet and return it.
+    fn create_account(&mut self, label: &str) -> anyhow::Result<SubaddressAccount> {
+        let_cxx_string!(label = label);
+
+        self.account_table()?
+            .addRow(&label)
+            .context("Failed to create account: FFI call failed with exception")?;
+        self.check_error().context("Failed to create account")?;
+        self.store()?;
+
+        self.accounts()?.pop().context("Created account is missing")
+    }
+
+    fn is_view_only(&self) -> bool {
+        self.inner
+            .watchOnly()
+            .context("Failed to check whether wallet is view-only: FFI call failed with exception")
+            .expect("Shouldn't panic")
+    }
+
+    fn secret_view_key(&self) -> anyhow::Result<monero::PrivateKey> {
+        let view_key = ffi::walletSecretViewKey(&self.inner)
+            .context("Failed to get view key: FFI call failed with exception")?
+            .to_string();
+
+        monero::PrivateKey::from_str(&view_key).context("Failed to parse view key")
+    }
+
+    /// Create a transfer and write it to `unsigned_path` instead of publishing it.
+    fn create_unsigned_transfer(
+        &mut self,
+        address: &monero::Address,
+        amount: monero::Amount,
+        unsigned_path: &Path,
+    ) -> anyhow::Result<()> {
+        if !self.is_view_only() {
+            bail!("Only view-only wallets create unsigned transactions");
+        }
+
+        let_cxx_string!(address = address.to_string());
+
+        let mut pending_tx = PendingTransaction(
+            ffi::createTransaction(self.inner.pinned(), &address, amount.as_pico())
+                .context("Failed to create transaction: FFI call failed with exception")?,
+        );
+
+        // Committing a transaction of a view-only wallet to a file writes the unsigned transaction
+        let result = pending_tx
+            .check_error()
+            .context("Failed to create transaction")
+            .and_then(|_| {
+                let_cxx_string!(filename = unsigned_path.display().to_string());
+
+                let success = pending_tx.pinned().commit(&filename, true).context(
+                    "Failed to write unsigned transaction: FFI call failed with exception",
+                )?;
+
+                if !success {
+                    pending_tx
+                        .check_error()
+                        .context("Failed to write unsigned transaction")?;
+                    bail!("Failed to write unsigned transaction");
+                }
+
+                Ok(())
+            });
+
+        // Dispose of the transaction to avoid leaking memory.
+        self.dispose_transaction(pending_tx);
+
+        result
+    }
+
+    fn export_outputs(&mut self, path: &Path) -> anyhow::Result<()> {
+        let_cxx_string!(filename = path.display().to_string());
+
+        let success = self
+            .inner
+            .pinned()
+            .exportOutputs(&filename, false)
+            .context("Failed to export outputs: FFI call failed with exception")?;
+
+        self.check_file_operation(success, "Failed to export outputs")
+    }
+
+    fn import_outputs(&mut self, path: &Path) -> anyhow::Result<()> {
+        let_cxx_string!(filename = path.display().to_string());
+
+        let success = self
+            .inner
+            .pinned()
+            .importOutputs(&filename)
+            .context("Failed to import outputs: FFI call failed with exception")?;
+
+        self.check_file_operation(success, "Failed to import outputs")
+    }
+
+    fn export_key_images(&mut self, path: &Path) -> anyhow::Result<()> {
+        let_cxx_string!(filename = path.display().to_string());
+
+        let success = self
+            .inner
+            .pinned()
+            .exportKeyImages(&filename, false)
+            .context("Failed to export key images: FFI call failed with exception")?;
+
+        self.check_file_operation(success, "Failed to export key images")
+    }
+
+    fn import_key_images(&mut self, path: &Path) -> anyhow::Result<()> {
+        let_cxx_string!(filename = path.display().to_string());
+
+        let success = self
+            .inner
+            .pinned()
+            .importKeyImages(&filename)
+            .context("Failed to import key images: FFI call failed with exception")?;
+
+        self.check_file_operation(success, "Failed to import key images")
+    }
+
+    /// Load an unsigned transaction and sign it to `signed_path`, if given.
+    fn load_unsigned_transaction(
+        &mut self,
+        unsigned_path: &Path,
+        signed_path: Option<&Path>,
+    ) -> anyhow::Result<UnsignedTransfer> {
+        let_cxx_string!(filename = unsigned_path.display().to_string());
+
+        let unsigned_tx = self
+            .inner
+            .pinned()
+            .loadUnsignedTx(&filename)
+            .context("Failed to load unsigned transaction: FFI call failed with exception")?;
+
+        // Safety: we own the unsigned transaction until we dispose it below
+        let Some(tx) = (unsafe { unsigned_tx.as_mut() }) else {
+            self.check_error()
+                .context("Failed to load unsigned transaction")?;
+            bail!("Failed to load unsigned transaction, got null pointer");
+        };
+
+        let result = UnsignedTransfer::from_ffi(tx).and_then(|transfer| {
+            if let Some(signed_path) = signed_path {
+                let_cxx_string!(signed_filename = signed_path.display().to_string());
+
+                let mut tx = unsafe { Pin::new_unchecked(&mut *tx) };
+                let success = tx
+                    .as_mut()
+                    .sign(&signed_filename)
+                    .context("Failed to sign transaction: FFI call failed with exception")?;
+
+                if !success {
+                    let error = ffi::unsignedTransactionErrorString(&tx)
+                        .context("Failed to get error string: FFI call failed with exception")?;
+                    bail!("Failed to sign transaction: {}", error);
+                }
+            }
+
+            Ok(transfer)
+        });
+
+        unsafe {
+            ffi::disposeUnsignedTransaction(unsigned_tx)
+                .context("Failed to dispose unsigned transaction: FFI call failed with exception")
+                .expect("Shouldn't panic");
+        }
+
+        result
+    }
+
+    /// Publish a signed transaction. The wallet doesn't tell us the txids, so we
+    /// look for the outgoing transactions that were added to the history.
+    fn submit_signed_transaction(&mut self, signed_path: &Path) -> anyhow::Result<Vec<TxReceipt>> {
+        let known_txids = self
+            .history()?
+            .into_iter()
+            .map(|tx| tx.txid)
+            .collect::<HashSet<_>>();
+
+        let_cxx_string!(filename = signed_path.display().to_string());
+
+        let success = self
+            .inner
+            .pinned()
+            .submitTransaction(&filename)
+            .context("Failed to submit transaction: FFI call failed with exception")?;
+        self.check_file_operation(success, "Failed to submit transaction")?;
+
+        let height = self.blockchain_height();
+
+        self.history()?
+            .into_iter()
+            .filter(|tx| {
+                tx.direction == TransactionDirection::Outgoing && !known_txids.contains(&tx.txid)
+            })
+            .map(|tx| {
+                let_cxx_string!(txid = &tx.txid);
+
+                let tx_key = ffi::walletGetTxKey(&self.inner, &txid)
+                    .context("Failed to get tx key from wallet: FFI call failed with exception")?
+                    .to_string();
+
+                Ok(TxReceipt {
+                    txid: tx.txid,
+                    tx_key,
+                    height,
+                })
+            })
+            .collect()
+    }
+
+    /// Turn the result of a wallet method that reads or writes a file into an error.
+    fn check_file_operation(&self, success: bool, message: &'static str) -> anyhow::Result<()> {
+        if !success {
+            self.check_error().context(message)?;
+            bail!(message);
+        }
+
+        Ok(())
+    }
+
+    /// Get the subaddress table of the wallet.
+    fn subaddress_table(&mut self) -> anyhow::Result<Pin<&mut ffi::Subaddress>> {
+        let subaddress = self
+            .inner
+            .pinned()
+            .subaddress()
+            .context("Failed to get subaddresses: FFI call failed with exception")?;
+
+        // Safety: the table is owned by the wallet and lives as long as the wallet
+        unsafe { subaddress.as_mut() }
+            .map(|subaddress| unsafe { Pin::new_unchecked(subaddress) })
+            .context("Failed to get subaddresses: got null pointer")
+    }
+
+    /// Get the account table of the wallet.
+    fn account_table(&mut self) -> anyhow::Result<Pin<&mut ffi::SubaddressAccount>> {
+        let accounts = self
+            .inner
+            .pinned()
+            .subaddressAccount()
+            .context("Failed to get accounts: FFI call failed with exception")?;
+
+        // Safety: the table is owned by the wallet and lives as long as the wallet
+        unsafe { accounts.as_mut() }
+            .map(|accounts| unsafe { Pin::new_unchecked(accounts) })
+            .context("Failed to get accounts: got null pointer")
+    }
+
+    /// Store the wallet file such that new subaddresses and labels survive a crash.
+    fn store(&mut self) -> anyhow::Result<()> {
+        let_cxx_string!(path = ""); // Empty path means the current path
+
+        let success = self
+            .inner
+            .pinned()
+            .store(&path)
+            .context("Failed to store wallet: FFI call failed with exception")?;
+
+        if !success {
+            self.check_error().context("Failed to store wallet")?;
+            anyhow::bail!("Failed to store wallet");
+        }
+
+        Ok(())
+    }
+
     /// Get the total unlocked balance across all accounts in atomic units.
     fn unlocked_balance(&mut self) -> monero::Amount {
         let balance = self
@@ -1839,6 +2583,79 @@ impl PendingTransaction {
     }
 }
 
+impl TransactionInfo {
+    /// Copy the fields of a transaction from the history.
+    fn from_ffi(tx: &ffi::TransactionInfo) -> anyhow::Result<Self> {
+        const DIRECTION_OUT: i32 = 1;
+
+        let context = "Failed to read transaction: FFI call failed with exception";
+
+        let direction = match tx.direction().context(context)? {
+            DIRECTION_OUT => TransactionDirection::Outgoing,
+            _ => TransactionDirection::Incoming,
+        };
+        let pending = tx.isPending().context(context)?;
+        let block_height = tx.blockHeight().context(context)?;
+
+        Ok(Self {
+            txid: ffi::transactionInfoHash(tx).context(context)?.to_string(),
+            direction,
+            amount: monero::Amount::from_pico(tx.amount().context(context)?),
+            fee: monero::Amount::from_pico(tx.fee().context(context)?),
+            block_height: (!pending && block_height > 0).then_some(block_height),
+            confirmations: tx.confirmations().context(context)?,
+            timestamp: ffi::transactionInfoTimestamp(tx).context(context)?,
+            pending,
+            failed: tx.isFailed().context(context)?,
+            account_index: tx.subaddrAccount().context(context)?,
+            subaddress_indices: ffi::transactionInfoSubaddrIndices(tx)
+                .context(context)?
+                .iter()
+                .copied()
+                .collect(),
+        })
+    }
+}
+
+impl UnsignedTransfer {
+    /// Copy the details of an unsigned transaction, failing if it is invalid.
+    fn from_ffi(tx: &ffi::UnsignedTransaction) -> anyhow::Result<Self> {
+        let context = "Failed to read unsigned transaction: FFI call failed with exception";
+
+        if tx.status().context(context)? != 0 {
+            let error = ffi::unsignedTransactionErrorString(tx).context(context)?;
+            bail!("Invalid unsigned transaction: {}", error);
+        }
+
+        let recipients = ffi::unsignedTransactionRecipients(tx)
+            .context(context)?
+            .iter()
+            .map(|address| {
+                monero::Address::from_str(&address.to_string())
+                    .context("Failed to parse recipient of unsigned transaction")
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let amounts = ffi::unsignedTransactionAmounts(tx)
+            .context(context)?
+            .iter()
+            .map(|amount| monero::Amount::from_pico(*amount))
+            .collect();
+
+        let fees = ffi::unsignedTransactionFees(tx)
+            .context(context)?
+            .iter()
+            .map(|fee| monero::Amount::from_pico(*fee))
+            .collect();
+
+        Ok(Self {
+            recipients,
+            amounts,
+            fees,
+        })
+    }
+}
+
 impl SyncProgress {
     /// Create a new sync progress object.
     fn new(current_block: u64, target_block: u64) -> Self {
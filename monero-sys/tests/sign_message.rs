@@ -13,6 +13,7 @@ async fn test_sign_message() {
     let daemon = Daemon {
         address: PLACEHOLDER_NODE.into(),
         ssl: false,
+        proxy: None,
     };
 
     let wallet_name = "test_signing_wallet";
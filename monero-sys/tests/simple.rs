@@ -18,6 +18,7 @@ async fn main() {
     let daemon = Daemon {
         address: STAGENET_REMOTE_NODE.into(),
         ssl: true,
+        proxy: None,
     };
 
     let wallet_name = "recovered_wallet";
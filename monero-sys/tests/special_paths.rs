@@ -16,6 +16,7 @@ async fn test_wallet_with_special_paths() {
     let daemon = Daemon {
         address: "https://moneronode.org:18081".into(),
         ssl: true,
+        proxy: None,
     };
 
     let futures = special_paths
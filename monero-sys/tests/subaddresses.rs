@@ -0,0 +1,81 @@
+use monero_sys::{Daemon, WalletHandle};
+
+const PLACEHOLDER_NODE: &str = "http://127.0.0.1:18081";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_subaddresses_and_history() {
+    tracing_subscriber::fmt()
+        .with_env_filter("info,test=debug,subaddresses=trace,monero_sys=trace")
+        .with_test_writer()
+        .init();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let daemon = Daemon {
+        address: PLACEHOLDER_NODE.into(),
+        ssl: false,
+        proxy: None,
+    };
+
+    let wallet_path = temp_dir
+        .path()
+        .join("test_subaddress_wallet")
+        .display()
+        .to_string();
+
+    let wallet = WalletHandle::open_or_create(
+        wallet_path.clone(),
+        daemon.clone(),
+        monero::Network::Stagenet,
+        false, // No background sync
+    )
+    .await
+    .expect("Failed to create wallet");
+
+    let main_address = wallet.main_address().await;
+
+    let subaddresses = wallet.subaddresses(0).await.unwrap();
+    assert_eq!(
+        subaddresses.len(),
+        1,
+        "A new wallet only has its primary address"
+    );
+    assert_eq!(subaddresses[0].address, main_address);
+
+    tracing::info!("Creating a labeled subaddress");
+    let subaddress = wallet.create_subaddress(0, "swap").await.unwrap();
+    assert_eq!(subaddress.address_index, 1);
+    assert_eq!(subaddress.label, "swap");
+    assert_ne!(subaddress.address, main_address);
+
+    wallet
+        .set_subaddress_label(0, 1, "relabeled")
+        .await
+        .unwrap();
+
+    tracing::info!("Creating a second account");
+    let account = wallet.create_account("savings").await.unwrap();
+    assert_eq!(account.index, 1);
+    assert_eq!(account.label, "savings");
+    assert_eq!(account.balance, monero::Amount::ZERO);
+    assert_eq!(wallet.accounts().await.unwrap().len(), 2);
+
+    assert!(
+        wallet.history().await.unwrap().is_empty(),
+        "A new wallet has no transactions"
+    );
+
+    // Subaddresses and labels must survive reopening the wallet
+    drop(wallet);
+
+    // Give the wallet thread time to close the wallet
+    tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+    let wallet =
+        WalletHandle::open_or_create(wallet_path, daemon, monero::Network::Stagenet, false)
+            .await
+            .expect("Failed to reopen wallet");
+
+    let subaddresses = wallet.subaddresses(0).await.unwrap();
+    assert_eq!(subaddresses.len(), 2);
+    assert_eq!(subaddresses[1].label, "relabeled");
+}
@@ -0,0 +1,61 @@
+use monero_sys::{Daemon, WalletHandle};
+
+const PLACEHOLDER_NODE: &str = "http://127.0.0.1:18081";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_view_only_wallet() {
+    tracing_subscriber::fmt()
+        .with_env_filter("info,test=debug,view_only=trace,monero_sys=trace")
+        .with_test_writer()
+        .init();
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let daemon = Daemon {
+        address: PLACEHOLDER_NODE.into(),
+        ssl: false,
+        proxy: None,
+    };
+
+    let full_wallet = WalletHandle::open_or_create(
+        temp_dir.path().join("full").display().to_string(),
+        daemon.clone(),
+        monero::Network::Stagenet,
+        false, // No background sync
+    )
+    .await
+    .expect("Failed to create full wallet");
+
+    let address = full_wallet.main_address().await;
+    let view_key = full_wallet.secret_view_key().await.unwrap();
+    assert!(!full_wallet.is_view_only().await);
+
+    tracing::info!("Creating view-only wallet");
+    let view_only_wallet = WalletHandle::open_or_create_view_only(
+        temp_dir.path().join("view-only").display().to_string(),
+        None,
+        monero::Network::Stagenet,
+        address,
+        view_key,
+        0,
+        false,
+        daemon,
+    )
+    .await
+    .expect("Failed to create view-only wallet");
+
+    assert!(view_only_wallet.is_view_only().await);
+    assert_eq!(view_only_wallet.main_address().await, address);
+
+    tracing::info!("Handing the outputs of the view-only wallet to the full wallet");
+    let outputs = temp_dir.path().join("outputs");
+    view_only_wallet.export_outputs(&outputs).await.unwrap();
+    full_wallet.import_outputs(&outputs).await.unwrap();
+
+    tracing::info!("A view-only wallet without funds can't create a transfer");
+    let unsigned = temp_dir.path().join("unsigned");
+    assert!(view_only_wallet
+        .create_unsigned_transfer(&address, monero::Amount::from_pico(1_000_000), &unsigned)
+        .await
+        .is_err());
+    assert!(!unsigned.exists());
+}
@@ -13,6 +13,7 @@ async fn main() {
     let daemon = Daemon {
         address: STAGENET_REMOTE_NODE.into(),
         ssl: true,
+        proxy: None,
     };
 
     {
@@ -269,6 +269,11 @@ export type PendingSeedSelectionApprovalRequest = ApprovalRequest & {
   content: Extract<ApprovalRequest["request_status"], { state: "Pending" }>;
 };
 
+export type PendingUnlockSeedApprovalRequest = ApprovalRequest & {
+  request: Extract<ApprovalRequest["request"], { type: "UnlockSeed" }>;
+  content: Extract<ApprovalRequest["request_status"], { state: "Pending" }>;
+};
+
 export function isPendingLockBitcoinApprovalEvent(
   event: ApprovalRequest,
 ): event is PendingLockBitcoinApprovalRequest {
@@ -289,6 +294,16 @@ export function isPendingSeedSelectionApprovalEvent(
   );
 }
 
+export function isPendingUnlockSeedApprovalEvent(
+  event: ApprovalRequest,
+): event is PendingUnlockSeedApprovalRequest {
+  // Check if the request is an UnlockSeed request and is pending
+  return (
+    event.request.type === "UnlockSeed" &&
+    event.request_status.state === "Pending"
+  );
+}
+
 export function isPendingBackgroundProcess(
   process: TauriBackgroundProgress,
 ): process is TauriBackgroundProgress {
@@ -21,6 +21,7 @@ import "@fontsource/roboto";
 import FeedbackPage from "./pages/feedback/FeedbackPage";
 import IntroductionModal from "./modal/introduction/IntroductionModal";
 import SeedSelectionDialog from "./modal/seed-selection/SeedSelectionDialog";
+import UnlockSeedDialog from "./modal/seed-selection/UnlockSeedDialog";
 
 declare module "@mui/material/styles" {
   interface Theme {
@@ -48,6 +49,7 @@ export default function App() {
         <GlobalSnackbarProvider>
           <IntroductionModal />
           <SeedSelectionDialog />
+          <UnlockSeedDialog />
           <Router>
             <Navigation />
             <InnerContent />
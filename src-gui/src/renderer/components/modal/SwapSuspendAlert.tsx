@@ -12,15 +12,17 @@ import {
   Typography,
 } from "@mui/material";
 import CircleIcon from "@mui/icons-material/Circle";
-import { suspendCurrentSwap } from "renderer/rpc";
+import { suspendSwap } from "renderer/rpc";
 import PromiseInvokeButton from "../PromiseInvokeButton";
 
 type SwapCancelAlertProps = {
+  swapId: string | null;
   open: boolean;
   onClose: () => void;
 };
 
 export default function SwapSuspendAlert({
+  swapId,
   open,
   onClose,
 }: SwapCancelAlertProps) {
@@ -71,7 +73,11 @@ export default function SwapSuspendAlert({
         <PromiseInvokeButton
           color="primary"
           onSuccess={onClose}
-          onInvoke={suspendCurrentSwap}
+          onInvoke={async () => {
+            if (swapId !== null) {
+              await suspendSwap(swapId);
+            }
+          }}
         >
           Suspend
         </PromiseInvokeButton>
@@ -0,0 +1,70 @@
+import {
+  Button,
+  Dialog,
+  DialogActions,
+  DialogContent,
+  DialogTitle,
+  TextField,
+  Typography,
+} from "@mui/material";
+import { useState } from "react";
+import { usePendingUnlockSeedApproval } from "store/hooks";
+import { resolveApproval } from "renderer/rpc";
+
+export default function UnlockSeedDialog() {
+  const pendingApprovals = usePendingUnlockSeedApproval();
+  const [passphrase, setPassphrase] = useState<string>("");
+  const approval = pendingApprovals[0]; // Handle the first pending approval
+
+  const handleUnlock = async () => {
+    if (!approval) return;
+
+    // Clear the field so a wrong passphrase doesn't stay in it for the next attempt
+    const entered = passphrase;
+    setPassphrase("");
+
+    await resolveApproval(approval.request_id, { passphrase: entered });
+  };
+
+  if (!approval) {
+    return null;
+  }
+
+  const wrongPassphrase = approval.request.content.wrong_passphrase;
+
+  return (
+    <Dialog open={true} maxWidth="sm" fullWidth>
+      <DialogTitle>Unlock Seed</DialogTitle>
+      <DialogContent>
+        <Typography variant="body1" sx={{ mb: 2 }}>
+          Your seed is encrypted. Enter your passphrase to unlock it.
+        </Typography>
+
+        <TextField
+          fullWidth
+          autoFocus
+          type="password"
+          label="Passphrase"
+          value={passphrase}
+          onChange={(e) => setPassphrase(e.target.value)}
+          onKeyDown={(e) => {
+            if (e.key === "Enter" && passphrase.length > 0) {
+              handleUnlock();
+            }
+          }}
+          error={wrongPassphrase}
+          helperText={wrongPassphrase ? "Wrong passphrase" : ""}
+        />
+      </DialogContent>
+      <DialogActions>
+        <Button
+          onClick={handleUnlock}
+          variant="contained"
+          disabled={passphrase.length === 0}
+        >
+          Unlock
+        </Button>
+      </DialogActions>
+    </Dialog>
+  );
+}
@@ -38,6 +38,8 @@ import {
   setFiatCurrency,
   setTheme,
   setTorEnabled,
+  setAlwaysTor,
+  setDirectLocalConnections,
   setUseMoneroRpcPool,
   setDonateToDevelopment,
 } from "store/features/settingsSlice";
@@ -91,6 +93,8 @@ export default function SettingsBox() {
             <Table>
               <TableBody>
                 <TorSettings />
+                <AlwaysTorSettings />
+                <DirectLocalConnectionsSettings />
                 <DonationTipSetting />
                 <ElectrumRpcUrlSetting />
                 <MoneroRpcPoolSetting />
@@ -704,7 +708,7 @@ export function TorSettings() {
       <TableCell>
         <SettingLabel
           label="Use Tor"
-          tooltip="Route network traffic through Tor to hide your IP address from the maker."
+          tooltip="Route network traffic through Tor to hide your IP address from the maker, Electrum servers and Monero nodes."
         />
       </TableCell>
 
@@ -715,6 +719,71 @@ export function TorSettings() {
   );
 }
 
+/**
+ * A setting that refuses to connect to blockchain nodes without Tor
+ */
+function AlwaysTorSettings() {
+  const dispatch = useAppDispatch();
+  const torEnabled = useSettings((settings) => settings.enableTor);
+  const alwaysTor = useSettings((settings) => settings.alwaysTor);
+  const handleChange = (event: React.ChangeEvent<HTMLInputElement>) =>
+    dispatch(setAlwaysTor(event.target.checked));
+
+  return (
+    <TableRow>
+      <TableCell>
+        <SettingLabel
+          label="Always use Tor"
+          tooltip="Never connect to Electrum servers and Monero nodes over clearnet. If Tor cannot be started, the wallets are not opened."
+        />
+      </TableCell>
+
+      <TableCell>
+        <Switch
+          checked={torEnabled && alwaysTor}
+          disabled={!torEnabled}
+          onChange={handleChange}
+          color="primary"
+        />
+      </TableCell>
+    </TableRow>
+  );
+}
+
+/**
+ * A setting that reaches local blockchain nodes directly, as Tor cannot reach them
+ */
+function DirectLocalConnectionsSettings() {
+  const dispatch = useAppDispatch();
+  const torEnabled = useSettings((settings) => settings.enableTor);
+  const alwaysTor = useSettings((settings) => settings.alwaysTor);
+  const directLocalConnections = useSettings(
+    (settings) => settings.directLocalConnections,
+  );
+  const handleChange = (event: React.ChangeEvent<HTMLInputElement>) =>
+    dispatch(setDirectLocalConnections(event.target.checked));
+
+  return (
+    <TableRow>
+      <TableCell>
+        <SettingLabel
+          label="Connect to local nodes directly"
+          tooltip="Tor cannot reach Electrum servers and Monero nodes on this computer or your local network. Enable this to connect to them directly. Not available when always using Tor."
+        />
+      </TableCell>
+
+      <TableCell>
+        <Switch
+          checked={torEnabled && !alwaysTor && directLocalConnections}
+          disabled={!torEnabled || alwaysTor}
+          onChange={handleChange}
+          color="primary"
+        />
+      </TableCell>
+    </TableRow>
+  );
+}
+
 /**
  * A setting that allows you to manage rendezvous points for maker discovery
  */
@@ -0,0 +1,31 @@
+import { ButtonProps } from "@mui/material";
+import { revealItemInDir } from "@tauri-apps/plugin-opener";
+import { createDiagnostics } from "renderer/rpc";
+import PromiseInvokeButton from "renderer/components/PromiseInvokeButton";
+
+interface CreateDiagnosticsButtonProps extends ButtonProps {
+  swap_id: string;
+}
+
+// Writes the redacted logs, states, transactions and node connectivity of the
+// swap into one archive (without private keys) and shows it in the file explorer
+export default function CreateDiagnosticsButton({
+  swap_id,
+  ...buttonProps
+}: CreateDiagnosticsButtonProps) {
+  async function handleCreateDiagnostics() {
+    const path = await createDiagnostics(swap_id);
+    await revealItemInDir(path);
+  }
+
+  return (
+    <PromiseInvokeButton
+      onInvoke={handleCreateDiagnostics}
+      tooltipTitle="Bundle everything support needs to look into this swap into one archive. Private keys are left out."
+      displayErrorSnackbar
+      {...buttonProps}
+    >
+      Create Diagnostics
+    </PromiseInvokeButton>
+  );
+}
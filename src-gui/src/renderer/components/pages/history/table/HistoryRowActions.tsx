@@ -12,12 +12,8 @@ import {
   isBobStateNamePossiblyRefundableSwap,
 } from "models/tauriModelExt";
 import PromiseInvokeButton from "renderer/components/PromiseInvokeButton";
-import { resumeSwap, suspendCurrentSwap } from "renderer/rpc";
-import {
-  useIsSpecificSwapRunning,
-  useIsSwapRunning,
-  useIsSwapRunningAndHasFundsLocked,
-} from "store/hooks";
+import { resumeSwap } from "renderer/rpc";
+import { useIsSpecificSwapRunning, useIsSwapRunning } from "store/hooks";
 import { useNavigate } from "react-router-dom";
 
 export function SwapResumeButton({
@@ -30,20 +26,8 @@ export function SwapResumeButton({
   // We cannot resume at all if the swap of this button is already running
   const isAlreadyRunning = useIsSpecificSwapRunning(swap.swap_id);
 
-  // If another swap is running, we can resume but only if no funds have been locked
-  // for that swap. If funds have been locked, we cannot resume. If no funds have been locked,
-  // we suspend the other swap and resume this one.
-  const isAnotherSwapRunningAndHasFundsLocked =
-    useIsSwapRunningAndHasFundsLocked() && !isAlreadyRunning;
-
   async function resume() {
-    // We always suspend the current swap first
-    // If that swap has any funds locked, the button will be disabled
-    // and this function will not be called
-    // If no swap is running, this is a no-op
-    await suspendCurrentSwap();
-
-    // Now resume this swap
+    // Other swaps keep running, several swaps can run at the same time
     await resumeSwap(swap.swap_id);
 
     // Navigate to the swap page
@@ -52,19 +36,13 @@ export function SwapResumeButton({
 
   const tooltipTitle = isAlreadyRunning
     ? "This swap is already running"
-    : isAnotherSwapRunningAndHasFundsLocked
-      ? "Another swap is running. Suspend it first before resuming this one"
-      : undefined;
+    : undefined;
 
   return (
     <PromiseInvokeButton
       variant="contained"
       color="primary"
-      disabled={
-        swap.completed ||
-        isAlreadyRunning ||
-        isAnotherSwapRunningAndHasFundsLocked
-      }
+      disabled={swap.completed || isAlreadyRunning}
       tooltipTitle={tooltipTitle}
       endIcon={<PlayArrowIcon />}
       onInvoke={resume}
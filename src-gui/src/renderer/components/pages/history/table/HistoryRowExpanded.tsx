@@ -20,6 +20,7 @@ import { isTestnet } from "store/config";
 import { getBitcoinTxExplorerUrl } from "utils/conversionUtils";
 import SwapLogFileOpenButton from "./SwapLogFileOpenButton";
 import ExportLogsButton from "./ExportLogsButton";
+import CreateDiagnosticsButton from "./CreateDiagnosticsButton";
 
 export default function HistoryRowExpanded({
   swap,
@@ -45,6 +46,12 @@ export default function HistoryRowExpanded({
               <TableCell>Swap ID</TableCell>
               <TableCell>{swap.swap_id}</TableCell>
             </TableRow>
+            {swap.order_id && (
+              <TableRow>
+                <TableCell>Order ID</TableCell>
+                <TableCell>{swap.order_id}</TableCell>
+              </TableRow>
+            )}
             <TableRow>
               <TableCell>State Name</TableCell>
               <TableCell>{swap.state_name}</TableCell>
@@ -170,6 +177,11 @@ export default function HistoryRowExpanded({
           variant="outlined"
           size="small"
         />
+        <CreateDiagnosticsButton
+          swap_id={swap.swap_id}
+          variant="outlined"
+          size="small"
+        />
       </Box>
     </Box>
   );
@@ -1,6 +1,6 @@
 import { Box, Button } from "@mui/material";
 import { haveFundsBeenLocked } from "models/tauriModelExt";
-import { getCurrentSwapId, suspendCurrentSwap } from "renderer/rpc";
+import { getCurrentSwapIds, suspendSwap } from "renderer/rpc";
 import { swapReset } from "store/features/swapSlice";
 import { useAppDispatch, useAppSelector, useIsSwapRunning } from "store/hooks";
 import { useState } from "react";
@@ -15,15 +15,17 @@ export default function CancelButton() {
   const hasFundsBeenLocked = haveFundsBeenLocked(swap.state?.curr);
 
   async function onCancel() {
-    const swapId = await getCurrentSwapId();
+    const swapId = swap.state?.swapId ?? null;
+    const { swap_ids } = await getCurrentSwapIds();
 
-    if (swapId.swap_id !== null) {
+    // Other swaps may be running at the same time, we only suspend this one
+    if (swapId !== null && swap_ids.includes(swapId)) {
       if (hasFundsBeenLocked && isSwapRunning) {
         setOpenSuspendAlert(true);
         return;
       }
 
-      await suspendCurrentSwap();
+      await suspendSwap(swapId);
     }
 
     dispatch(swapReset());
@@ -32,6 +34,7 @@ export default function CancelButton() {
   return (
     <>
       <SwapSuspendAlert
+        swapId={swap.state?.swapId ?? null}
         open={openSuspendAlert}
         onClose={() => setOpenSuspendAlert(false)}
       />
@@ -17,7 +17,8 @@ export default function MakerOfferItem({
   requestId?: string;
   quoteWithAddress: QuoteWithAddress;
 }) {
-  const { multiaddr, peer_id, quote, version } = quoteWithAddress;
+  const { multiaddr, peer_id, quote, version, reputation, settings } =
+    quoteWithAddress;
 
   return (
     <Paper
@@ -53,6 +54,15 @@ export default function MakerOfferItem({
             gap: 1,
           }}
         >
+          {settings?.label != null && (
+            <Typography
+              variant="body1"
+              sx={{ maxWidth: "200px", fontWeight: "bold" }}
+              noWrap
+            >
+              {settings.label}
+            </Typography>
+          )}
           <Typography variant="body1" sx={{ maxWidth: "200px" }} noWrap>
             {multiaddr}
           </Typography>
@@ -85,6 +95,27 @@ export default function MakerOfferItem({
               }
               size="small"
             />
+            {settings?.status === "Pinned" && (
+              <Chip label="Pinned" color="primary" size="small" />
+            )}
+            {reputation?.score != null && (
+              <Tooltip
+                title={
+                  `${reputation.swaps_redeemed} completed swaps, ` +
+                  `${reputation.refunds_xmr_not_locked} refunded because the ` +
+                  `maker did not lock the Monero, ` +
+                  `${reputation.early_refunds} refunded early. Reachable ` +
+                  `${reputation.times_reachable} out of ` +
+                  `${reputation.times_reachable + reputation.times_unreachable} times.`
+                }
+              >
+                <Chip
+                  label={`Reputation ${reputation.score}/100`}
+                  color={reputation.score < 50 ? "warning" : "default"}
+                  size="small"
+                />
+              </Tooltip>
+            )}
             {isMakerVersionOutdated(version) ? (
               <Tooltip title="Outdated maker version. This may cause issues with the swap.">
                 <Chip
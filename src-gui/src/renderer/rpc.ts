@@ -2,16 +2,26 @@ import { invoke as invokeUnsafe } from "@tauri-apps/api/core";
 import {
   BalanceArgs,
   BalanceResponse,
+  BumpFeeArgs,
+  BumpFeeResponse,
   BuyXmrArgs,
+  FreezeUtxoArgs,
+  GetBitcoinTransactionsArgs,
+  GetBitcoinTransactionsResponse,
+  ListUnspentResponse,
+  SetUtxoLabelArgs,
   BuyXmrResponse,
   GetLogsArgs,
   GetLogsResponse,
   GetSwapInfoResponse,
   ListSellersArgs,
   MoneroRecoveryArgs,
+  RequestEarlyRefundArgs,
   ResumeSwapArgs,
   ResumeSwapResponse,
   SuspendCurrentSwapResponse,
+  SuspendSwapArgs,
+  SuspendSwapResponse,
   WithdrawBtcArgs,
   WithdrawBtcResponse,
   GetSwapInfoArgs,
@@ -24,6 +34,8 @@ import {
   CheckElectrumNodeArgs,
   CheckElectrumNodeResponse,
   GetMoneroAddressesResponse,
+  GetMoneroHistoryResponse,
+  GetMoneroSubaddressesResponse,
   GetDataDirArgs,
   ResolveApprovalArgs,
   ResolveApprovalResponse,
@@ -33,6 +45,20 @@ import {
   LabeledMoneroAddress,
   GetPendingApprovalsArgs,
   GetPendingApprovalsResponse,
+  ExportFormat,
+  ExportHistoryArgs,
+  ExportHistoryResponse,
+  GetSwapTimelineArgs,
+  GetSwapTimelineResponse,
+  CreateDiagnosticsArgs,
+  CreateDiagnosticsResponse,
+  PlaceOrderArgs,
+  PlaceOrderResponse,
+  ListPeersResponse,
+  AddPeerArgs,
+  RemovePeerArgs,
+  BanPeerArgs,
+  SetPeerLabelArgs,
 } from "models/tauriModel";
 import {
   rpcSetBalance,
@@ -164,12 +190,81 @@ export async function getSwapInfo(swapId: string) {
   store.dispatch(rpcSetSwapInfo(response));
 }
 
-export async function withdrawBtc(address: string): Promise<string> {
+export async function bumpFee(swapId: string, feeRate?: number) {
+  return await invoke<BumpFeeArgs, BumpFeeResponse>("bump_fee", {
+    swap_id: swapId,
+    fee_rate: feeRate ?? null,
+  });
+}
+
+export async function getBitcoinTransactions(forceRefresh: boolean) {
+  return await invoke<
+    GetBitcoinTransactionsArgs,
+    GetBitcoinTransactionsResponse
+  >("get_bitcoin_transactions", {
+    force_refresh: forceRefresh,
+  });
+}
+
+export async function listUnspent() {
+  return await invokeNoArgs<ListUnspentResponse>("list_unspent");
+}
+
+export async function setUtxoLabel(outpoint: string, label: string | null) {
+  await invoke<SetUtxoLabelArgs, unknown>("set_utxo_label", {
+    outpoint,
+    label,
+  });
+}
+
+export async function freezeUtxo(outpoint: string, frozen: boolean) {
+  await invoke<FreezeUtxoArgs, unknown>("freeze_utxo", {
+    outpoint,
+    frozen,
+  });
+}
+
+export async function listPeers() {
+  return await invokeNoArgs<ListPeersResponse>("list_peers");
+}
+
+export async function addPeer(multiaddr: string, label: string | null) {
+  await invoke<AddPeerArgs, unknown>("add_peer", {
+    multiaddr,
+    label,
+  });
+}
+
+export async function removePeer(peerId: string) {
+  await invoke<RemovePeerArgs, unknown>("remove_peer", {
+    peer_id: peerId,
+  });
+}
+
+export async function banPeer(peerId: string, banned: boolean) {
+  await invoke<BanPeerArgs, unknown>("ban_peer", {
+    peer_id: peerId,
+    banned,
+  });
+}
+
+export async function setPeerLabel(peerId: string, label: string | null) {
+  await invoke<SetPeerLabelArgs, unknown>("set_peer_label", {
+    peer_id: peerId,
+    label,
+  });
+}
+
+export async function withdrawBtc(
+  address: string,
+  inputs: string[] | null = null,
+): Promise<string> {
   const response = await invoke<WithdrawBtcArgs, WithdrawBtcResponse>(
     "withdraw_btc",
     {
       address,
       amount: null,
+      inputs,
     },
   );
 
@@ -231,17 +326,60 @@ export async function buyXmr(
   });
 }
 
+export async function placeOrder(
+  bitcoin_change_address: string | null,
+  monero_receive_address: string,
+  max_makers: number,
+  btc_amount?: number,
+): Promise<PlaceOrderResponse> {
+  const state = store.getState();
+  const sellers = [
+    ...(state.makers.registry.makers || []),
+    ...state.makers.rendezvous.makers,
+  ].map((maker) => providerToConcatenatedMultiAddr(maker));
+
+  return await invoke<PlaceOrderArgs, PlaceOrderResponse>("place_order", {
+    rendezvous_points: PRESET_RENDEZVOUS_POINTS,
+    sellers,
+    monero_receive_pool: [
+      {
+        address: monero_receive_address,
+        percentage: 1,
+        label: "Your wallet",
+      },
+    ],
+    bitcoin_change_address,
+    btc_amount,
+    max_makers,
+  });
+}
+
 export async function resumeSwap(swapId: string) {
   await invoke<ResumeSwapArgs, ResumeSwapResponse>("resume_swap", {
     swap_id: swapId,
   });
 }
 
+export async function requestEarlyRefund(swapId: string) {
+  await invoke<RequestEarlyRefundArgs, ResumeSwapResponse>(
+    "request_early_refund",
+    {
+      swap_id: swapId,
+    },
+  );
+}
+
 export async function suspendCurrentSwap() {
   await invokeNoArgs<SuspendCurrentSwapResponse>("suspend_current_swap");
 }
 
-export async function getCurrentSwapId() {
+export async function suspendSwap(swapId: string) {
+  await invoke<SuspendSwapArgs, SuspendSwapResponse>("suspend_swap", {
+    swap_id: swapId,
+  });
+}
+
+export async function getCurrentSwapIds() {
   return await invokeNoArgs<GetCurrentSwapResponse>("get_current_swap");
 }
 
@@ -271,6 +409,38 @@ export async function getLogsOfSwap(
   });
 }
 
+/// Export the swap history, optionally only the swaps started between
+/// `from` and `to` (both `YYYY-MM-DD`, inclusive).
+export async function exportHistory(
+  format: ExportFormat,
+  from?: string,
+  to?: string,
+): Promise<ExportHistoryResponse> {
+  return await invoke<ExportHistoryArgs, ExportHistoryResponse>(
+    "export_history",
+    { format, from, to },
+  );
+}
+
+export async function getSwapTimeline(
+  swapId: string,
+): Promise<GetSwapTimelineResponse> {
+  return await invoke<GetSwapTimelineArgs, GetSwapTimelineResponse>(
+    "get_swap_timeline",
+    { swap_id: swapId },
+  );
+}
+
+/// Write everything we know about a swap into one archive for support.
+/// Returns the path of the archive.
+export async function createDiagnostics(swapId: string): Promise<string> {
+  const response = await invoke<
+    CreateDiagnosticsArgs,
+    CreateDiagnosticsResponse
+  >("create_diagnostics", { swap_id: swapId });
+  return response.path;
+}
+
 /// Call the rust backend to redact logs.
 export async function redactLogs(
   logs: (string | CliLog)[],
@@ -294,6 +464,10 @@ export async function initializeContext() {
   const network = getNetwork();
   const testnet = isTestnet();
   const useTor = store.getState().settings.enableTor;
+  const alwaysTor = useTor && store.getState().settings.alwaysTor;
+  // Local nodes can only be reached directly, which always-Tor forbids
+  const directLocalConnections =
+    useTor && !alwaysTor && store.getState().settings.directLocalConnections;
 
   // Get all Bitcoin nodes without checking availability
   // The backend ElectrumBalancer will handle load balancing and failover
@@ -325,6 +499,8 @@ export async function initializeContext() {
     electrum_rpc_urls: bitcoinNodes,
     monero_node_config: moneroNodeConfig,
     use_tor: useTor,
+    always_tor: alwaysTor,
+    direct_local_connections: directLocalConnections,
   };
 
   logger.info("Initializing context with settings", tauriSettings);
@@ -417,6 +593,16 @@ export async function getMoneroAddresses(): Promise<GetMoneroAddressesResponse>
   return await invokeNoArgs<GetMoneroAddressesResponse>("get_monero_addresses");
 }
 
+export async function getMoneroHistory(): Promise<GetMoneroHistoryResponse> {
+  return await invokeNoArgs<GetMoneroHistoryResponse>("get_monero_history");
+}
+
+export async function getMoneroSubaddresses(): Promise<GetMoneroSubaddressesResponse> {
+  return await invokeNoArgs<GetMoneroSubaddressesResponse>(
+    "get_monero_subaddresses",
+  );
+}
+
 export async function getDataDir(): Promise<string> {
   const testnet = isTestnet();
   return await invoke<GetDataDirArgs, string>("get_data_dir", {
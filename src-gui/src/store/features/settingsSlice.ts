@@ -19,6 +19,12 @@ export interface SettingsState {
   fiatCurrency: FiatCurrency;
   /// Whether to enable Tor for p2p connections
   enableTor: boolean;
+  /// Whether to refuse connecting to Electrum servers and Monero nodes if Tor
+  /// cannot be used, instead of falling back to clearnet
+  alwaysTor: boolean;
+  /// Whether to connect to Electrum servers and Monero nodes on this machine or
+  /// the local network directly while using Tor, which cannot reach them
+  directLocalConnections: boolean;
   /// Whether to use the Monero RPC pool for load balancing (true) or custom nodes (false)
   useMoneroRpcPool: boolean;
   userHasSeenIntroduction: boolean;
@@ -126,6 +132,8 @@ const initialState: SettingsState = {
   fetchFiatPrices: false,
   fiatCurrency: FiatCurrency.Usd,
   enableTor: true,
+  alwaysTor: false,
+  directLocalConnections: false,
   useMoneroRpcPool: true, // Default to using RPC pool
   userHasSeenIntroduction: false,
   rendezvousPoints: DEFAULT_RENDEZVOUS_POINTS,
@@ -215,6 +223,12 @@ const alertsSlice = createSlice({
     setTorEnabled(slice, action: PayloadAction<boolean>) {
       slice.enableTor = action.payload;
     },
+    setAlwaysTor(slice, action: PayloadAction<boolean>) {
+      slice.alwaysTor = action.payload;
+    },
+    setDirectLocalConnections(slice, action: PayloadAction<boolean>) {
+      slice.directLocalConnections = action.payload;
+    },
     setUseMoneroRpcPool(slice, action: PayloadAction<boolean>) {
       slice.useMoneroRpcPool = action.payload;
     },
@@ -236,6 +250,8 @@ export const {
   setFetchFiatPrices,
   setFiatCurrency,
   setTorEnabled,
+  setAlwaysTor,
+  setDirectLocalConnections,
   setUseMoneroRpcPool,
   setUserHasSeenIntroduction,
   addRendezvousPoint,
@@ -12,6 +12,8 @@ import {
   isPendingSelectMakerApprovalEvent,
   haveFundsBeenLocked,
   PendingSeedSelectionApprovalRequest,
+  isPendingUnlockSeedApprovalEvent,
+  PendingUnlockSeedApprovalRequest,
 } from "models/tauriModelExt";
 import { TypedUseSelectorHook, useDispatch, useSelector } from "react-redux";
 import type { AppDispatch, RootState } from "renderer/store/storeRenderer";
@@ -217,6 +219,11 @@ export function usePendingSeedSelectionApproval(): PendingSeedSelectionApprovalR
   return approvals.filter((c) => isPendingSeedSelectionApprovalEvent(c));
 }
 
+export function usePendingUnlockSeedApproval(): PendingUnlockSeedApprovalRequest[] {
+  const approvals = usePendingApprovals();
+  return approvals.filter((c) => isPendingUnlockSeedApprovalEvent(c));
+}
+
 /// Returns all the pending background processes
 /// In the format [id, {componentName, {type: "Pending", content: {consumed, total}}}]
 export function usePendingBackgroundProcesses(): [
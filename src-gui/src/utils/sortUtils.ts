@@ -45,10 +45,14 @@ export function sortMakerApprovals(list: SortableQuoteWithAddress[]) {
           (m) => ((m.quote.min_quantity ?? 0) > 0 ? 0 : 1),
           // Prefer approvals over actual quotes
           (m) => (m.request_id ? 0 : 1),
+          // Prefer makers the user pinned
+          (m) => (m.settings?.status === "Pinned" ? 0 : 1),
+          // Prefer makers with a better reputation
+          (m) => m.reputation?.score ?? 50,
           // Prefer makers with a lower price
           (m) => m.quote.price,
         ],
-        ["asc", "asc", "asc", "asc", "asc"],
+        ["asc", "asc", "asc", "asc", "asc", "desc", "asc"],
       )
       // Remove duplicate makers
       .uniqBy((m) => m.peer_id)
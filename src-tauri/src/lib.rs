@@ -7,12 +7,17 @@ use swap::cli::{
     api::{
         data,
         request::{
-            BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, CheckElectrumNodeArgs,
-            CheckElectrumNodeResponse, CheckMoneroNodeArgs, CheckMoneroNodeResponse, CheckSeedArgs,
-            CheckSeedResponse, ExportBitcoinWalletArgs, GetCurrentSwapArgs, GetDataDirArgs,
-            GetHistoryArgs, GetLogsArgs, GetMoneroAddressesArgs, GetPendingApprovalsResponse,
-            GetSwapInfoArgs, GetSwapInfosAllArgs, ListSellersArgs, MoneroRecoveryArgs, RedactArgs,
-            ResolveApprovalArgs, ResumeSwapArgs, SuspendCurrentSwapArgs, WithdrawBtcArgs,
+            AddPeerArgs, BalanceArgs, BanPeerArgs, BumpFeeArgs, BuyXmrArgs, CancelAndRefundArgs,
+            CheckElectrumNodeArgs, CheckElectrumNodeResponse, CheckMoneroNodeArgs,
+            CheckMoneroNodeResponse, CheckSeedArgs, CheckSeedResponse, CreateDiagnosticsArgs,
+            ExportBitcoinWalletArgs, ExportHistoryArgs, FreezeUtxoArgs, GetBitcoinTransactionsArgs,
+            GetCurrentSwapArgs, GetDataDirArgs, GetHistoryArgs, GetLogsArgs,
+            GetMoneroAddressesArgs, GetMoneroHistoryArgs, GetMoneroSubaddressesArgs,
+            GetPendingApprovalsResponse, GetSwapInfoArgs, GetSwapInfosAllArgs, GetSwapTimelineArgs,
+            ListPeersArgs, ListSellersArgs, ListUnspentArgs, MoneroRecoveryArgs, PlaceOrderArgs,
+            RedactArgs, RemovePeerArgs, RequestEarlyRefundArgs, ResolveApprovalArgs,
+            ResumeSwapArgs, SetPeerLabelArgs, SetUtxoLabelArgs, SuspendCurrentSwapArgs,
+            SuspendSwapArgs, WithdrawBtcArgs,
         },
         tauri_bindings::{TauriContextStatusEvent, TauriEmitter, TauriHandle, TauriSettings},
         Context, ContextBuilder,
@@ -178,18 +183,36 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .invoke_handler(tauri::generate_handler![
             get_balance,
+            get_bitcoin_transactions,
             get_monero_addresses,
+            get_monero_history,
+            get_monero_subaddresses,
             get_swap_info,
             get_swap_infos_all,
             withdraw_btc,
+            list_unspent,
+            set_utxo_label,
+            freeze_utxo,
             buy_xmr,
+            place_order,
             resume_swap,
             get_history,
+            export_history,
+            get_swap_timeline,
+            create_diagnostics,
             monero_recovery,
             get_logs,
             list_sellers,
+            list_peers,
+            add_peer,
+            remove_peer,
+            ban_peer,
+            set_peer_label,
             suspend_current_swap,
+            suspend_swap,
             cancel_and_refund,
+            request_early_refund,
+            bump_fee,
             is_context_available,
             initialize_context,
             check_monero_node,
@@ -235,22 +258,40 @@ pub fn run() {
 // The commands are defined using the `tauri_command!` macro.
 // Implementations are handled by the Request trait
 tauri_command!(get_balance, BalanceArgs);
+tauri_command!(get_bitcoin_transactions, GetBitcoinTransactionsArgs);
 tauri_command!(buy_xmr, BuyXmrArgs);
 tauri_command!(resume_swap, ResumeSwapArgs);
 tauri_command!(withdraw_btc, WithdrawBtcArgs);
+tauri_command!(set_utxo_label, SetUtxoLabelArgs);
+tauri_command!(freeze_utxo, FreezeUtxoArgs);
 tauri_command!(monero_recovery, MoneroRecoveryArgs);
 tauri_command!(get_logs, GetLogsArgs);
 tauri_command!(list_sellers, ListSellersArgs);
 tauri_command!(cancel_and_refund, CancelAndRefundArgs);
+tauri_command!(request_early_refund, RequestEarlyRefundArgs);
+tauri_command!(bump_fee, BumpFeeArgs);
 tauri_command!(redact, RedactArgs);
+tauri_command!(export_history, ExportHistoryArgs);
+tauri_command!(get_swap_timeline, GetSwapTimelineArgs);
+tauri_command!(create_diagnostics, CreateDiagnosticsArgs);
+tauri_command!(place_order, PlaceOrderArgs);
+tauri_command!(add_peer, AddPeerArgs);
+tauri_command!(remove_peer, RemovePeerArgs);
+tauri_command!(ban_peer, BanPeerArgs);
+tauri_command!(set_peer_label, SetPeerLabelArgs);
 
 // These commands require no arguments
 tauri_command!(get_wallet_descriptor, ExportBitcoinWalletArgs, no_args);
+tauri_command!(list_unspent, ListUnspentArgs, no_args);
+tauri_command!(list_peers, ListPeersArgs, no_args);
 tauri_command!(suspend_current_swap, SuspendCurrentSwapArgs, no_args);
+tauri_command!(suspend_swap, SuspendSwapArgs);
 tauri_command!(get_swap_info, GetSwapInfoArgs);
 tauri_command!(get_swap_infos_all, GetSwapInfosAllArgs, no_args);
 tauri_command!(get_history, GetHistoryArgs, no_args);
 tauri_command!(get_monero_addresses, GetMoneroAddressesArgs, no_args);
+tauri_command!(get_monero_history, GetMoneroHistoryArgs, no_args);
+tauri_command!(get_monero_subaddresses, GetMoneroSubaddressesArgs, no_args);
 tauri_command!(get_current_swap, GetCurrentSwapArgs, no_args);
 
 /// Here we define Tauri commands whose implementation is not delegated to the Request trait
@@ -422,11 +463,14 @@ async fn initialize_context(
         .with_bitcoin(Bitcoin {
             bitcoin_electrum_rpc_urls: settings.electrum_rpc_urls.clone(),
             bitcoin_target_block: None,
+            bump_fees: false,
         })
         .with_monero(settings.monero_node_config)
         .with_json(false)
         .with_debug(true)
         .with_tor(settings.use_tor)
+        .with_always_tor(settings.always_tor)
+        .with_direct_local_connections(settings.direct_local_connections)
         .with_tauri(tauri_handle.clone())
         .build()
         .await;
@@ -0,0 +1,8 @@
+-- Bob might send us the encrypted signature while we are not yet waiting for it
+-- (e.g. the asb was restarted or the swap has not yet reached the corresponding state).
+-- We store it here such that the swap can pick it up once it needs it.
+CREATE TABLE if NOT EXISTS buffered_encrypted_signatures
+(
+    swap_id             TEXT    PRIMARY KEY NOT NULL,
+    encrypted_signature TEXT                NOT NULL
+);
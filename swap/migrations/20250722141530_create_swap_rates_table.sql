@@ -0,0 +1,8 @@
+-- The rate (market price and spread) we agreed on with Bob during swap setup.
+-- The spread can vary between swaps depending on the pricing policy of the maker.
+CREATE TABLE if NOT EXISTS swap_rates
+(
+    swap_id      TEXT    PRIMARY KEY NOT NULL,
+    market_price INTEGER             NOT NULL,
+    ask_spread   TEXT                NOT NULL
+);
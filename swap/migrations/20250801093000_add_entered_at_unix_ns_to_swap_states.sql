@@ -0,0 +1,6 @@
+-- When the state was entered, in nanoseconds since the unix epoch. Unlike the
+-- human readable `entered_at` it can be compared in queries. States stored before
+-- this column existed are backfilled from `entered_at` when the database is opened.
+ALTER TABLE swap_states ADD COLUMN entered_at_unix_ns INTEGER;
+
+CREATE INDEX IF NOT EXISTS swap_states_entered_at_unix_ns ON swap_states (entered_at_unix_ns);
@@ -0,0 +1,6 @@
+-- Swaps which were started together to split one buy across several makers.
+CREATE TABLE if NOT EXISTS swap_orders
+(
+    swap_id  TEXT PRIMARY KEY NOT NULL,
+    order_id TEXT             NOT NULL
+);
@@ -0,0 +1,7 @@
+-- How often we could (or could not) fetch a quote from a maker when listing sellers.
+CREATE TABLE if NOT EXISTS peer_reachability
+(
+    peer_id           TEXT    PRIMARY KEY NOT NULL,
+    times_reachable   INTEGER             NOT NULL,
+    times_unreachable INTEGER             NOT NULL
+);
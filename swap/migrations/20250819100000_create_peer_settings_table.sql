@@ -0,0 +1,8 @@
+-- Makers the user pinned, banned or labeled.
+-- The status is one of `known`, `pinned` or `banned`.
+CREATE TABLE if NOT EXISTS peer_settings
+(
+    peer_id TEXT PRIMARY KEY NOT NULL,
+    status  TEXT             NOT NULL DEFAULT 'known',
+    label   TEXT
+);
@@ -0,0 +1,6 @@
+-- Backfills of existing rows which ran to completion, so they only run once.
+CREATE TABLE if NOT EXISTS completed_backfills
+(
+    name         TEXT PRIMARY KEY NOT NULL,
+    completed_at TEXT             NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -2,13 +2,21 @@ pub mod command;
 pub mod config;
 mod event_loop;
 mod network;
+mod price_source;
+mod pricing;
 mod rate;
 mod recovery;
+pub mod rpc;
 
-pub use event_loop::{EventLoop, EventLoopHandle, FixedRate, KrakenRate, LatestRate};
+pub use event_loop::{
+    EventLoop, EventLoopHandle, EventLoopService, FixedRate, KrakenRate, LatestRate,
+};
 pub use network::behaviour::{Behaviour, OutEvent};
+pub use network::onion;
 pub use network::rendezvous::RendezvousNode;
 pub use network::transport;
+pub use price_source::{AggregatedRate, PriceFeed, PriceSource};
+pub use pricing::{PricingContext, PricingPolicy, SpreadAdjustment};
 pub use rate::Rate;
 pub use recovery::cancel::cancel;
 pub use recovery::punish::punish;
@@ -1,10 +1,12 @@
 use crate::asb::config::GetDefaults;
 use crate::bitcoin::{bitcoin_address, Amount};
+use crate::common::LogFilter;
 use crate::env;
 use crate::env::GetConfig;
+use crate::protocol::export::{DateRange, ExportFormat};
 use anyhow::Result;
 use bitcoin::address::NetworkUnchecked;
-use bitcoin::Address;
+use bitcoin::{Address, OutPoint};
 use serde::Serialize;
 use std::ffi::OsString;
 use std::path::PathBuf;
@@ -42,10 +44,30 @@ where
             env_config: env_config(testnet),
             cmd: Command::History { only_unfinished },
         },
+        RawCommand::ExportHistory {
+            format,
+            from,
+            to,
+            output,
+        } => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::ExportHistory {
+                format,
+                range: DateRange::parse(from.as_deref(), to.as_deref())?,
+                output,
+            },
+        },
         RawCommand::Logs {
             logs_dir: dir_path,
             swap_id,
             redact,
+            since,
+            until,
+            level,
         } => Arguments {
             testnet,
             json,
@@ -54,11 +76,20 @@ where
             env_config: env_config(testnet),
             cmd: Command::Logs {
                 logs_dir: dir_path,
-                swap_id,
+                filter: LogFilter::parse(
+                    swap_id,
+                    since.as_deref(),
+                    until.as_deref(),
+                    level.as_deref(),
+                )?,
                 redact,
             },
         },
-        RawCommand::WithdrawBtc { amount, address } => Arguments {
+        RawCommand::WithdrawBtc {
+            amount,
+            address,
+            inputs,
+        } => Arguments {
             testnet,
             json,
             trace,
@@ -67,6 +98,42 @@ where
             cmd: Command::WithdrawBtc {
                 amount,
                 address: bitcoin_address::validate(address, testnet)?,
+                inputs: (!inputs.is_empty()).then_some(inputs),
+            },
+        },
+        RawCommand::ListUnspent => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::ListUnspent,
+        },
+        RawCommand::BitcoinTransactions => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::BitcoinTransactions,
+        },
+        RawCommand::LabelUtxo { outpoint, label } => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::LabelUtxo { outpoint, label },
+        },
+        RawCommand::FreezeUtxo { outpoint, unfreeze } => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::FreezeUtxo {
+                outpoint,
+                frozen: !unfreeze,
             },
         },
         RawCommand::Balance => Arguments {
@@ -101,6 +168,32 @@ where
             env_config: env_config(testnet),
             cmd: Command::ExportMoneroWallet,
         },
+        RawCommand::EncryptSeed => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::EncryptSeed,
+        },
+        RawCommand::MoneroSigner {
+            signing_dir,
+            max_amount,
+            max_total_amount,
+            window_hours,
+        } => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::MoneroSigner {
+                signing_dir,
+                max_amount,
+                max_total_amount,
+                window_hours,
+            },
+        },
         RawCommand::ManualRecovery(ManualRecovery::Redeem {
             redeem_params: RecoverCommandParams { swap_id },
             do_not_await_finality,
@@ -146,6 +239,17 @@ where
             env_config: env_config(testnet),
             cmd: Command::Punish { swap_id },
         },
+        RawCommand::ManualRecovery(ManualRecovery::BumpFee {
+            bump_fee_params: RecoverCommandParams { swap_id },
+            fee_rate,
+        }) => Arguments {
+            testnet,
+            json,
+            trace,
+            config_path: config_path(config, testnet)?,
+            env_config: env_config(testnet),
+            cmd: Command::BumpFee { swap_id, fee_rate },
+        },
         RawCommand::ManualRecovery(ManualRecovery::SafelyAbort { swap_id }) => Arguments {
             testnet,
             json,
@@ -206,15 +310,31 @@ pub enum Command {
     History {
         only_unfinished: bool,
     },
+    ExportHistory {
+        format: ExportFormat,
+        range: DateRange,
+        output: Option<PathBuf>,
+    },
     Config,
     Logs {
         logs_dir: Option<PathBuf>,
-        swap_id: Option<Uuid>,
+        filter: LogFilter,
         redact: bool,
     },
     WithdrawBtc {
         amount: Option<Amount>,
         address: Address,
+        inputs: Option<Vec<OutPoint>>,
+    },
+    ListUnspent,
+    BitcoinTransactions,
+    LabelUtxo {
+        outpoint: OutPoint,
+        label: Option<String>,
+    },
+    FreezeUtxo {
+        outpoint: OutPoint,
+        frozen: bool,
     },
     Balance,
     Redeem {
@@ -233,8 +353,19 @@ pub enum Command {
     SafelyAbort {
         swap_id: Uuid,
     },
+    BumpFee {
+        swap_id: Uuid,
+        fee_rate: Option<u64>,
+    },
     ExportBitcoinWallet,
     ExportMoneroWallet,
+    EncryptSeed,
+    MoneroSigner {
+        signing_dir: PathBuf,
+        max_amount: Option<crate::monero::Amount>,
+        max_total_amount: Option<crate::monero::Amount>,
+        window_hours: u64,
+    },
 }
 
 #[derive(structopt::StructOpt, Debug)]
@@ -302,15 +433,58 @@ pub enum RawCommand {
         #[structopt(
             long = "swap-id",
             help = "Filter for logs concerning this swap.",
-            long_help = "This checks whether each logging message contains the swap id. Some messages might be skipped when they don't contain the swap id even though they're relevant."
+            long_help = "This checks whether each logging message contains the swap id. Every message logged while running the swap carries the swap id, the peer id and the state of the swap as fields of its span."
         )]
         swap_id: Option<Uuid>,
+        #[structopt(
+            long = "since",
+            help = "Only print logs written at or after this time, e.g. 2025-06-25T12:00:00Z."
+        )]
+        since: Option<String>,
+        #[structopt(
+            long = "until",
+            help = "Only print logs written at or before this time, e.g. 2025-06-25T13:00:00Z."
+        )]
+        until: Option<String>,
+        #[structopt(
+            long = "level",
+            help = "Only print logs of this level or more severe: error, warn, info, debug or trace."
+        )]
+        level: Option<String>,
     },
     #[structopt(about = "Prints swap-id and the state of each swap ever made.")]
     History {
         #[structopt(long = "only-unfinished", help = "Only print in progress swaps")]
         only_unfinished: bool,
     },
+    #[structopt(
+        about = "Exports amounts, prices, transaction ids, fees and state transitions of all swaps for bookkeeping."
+    )]
+    ExportHistory {
+        #[structopt(
+            long = "format",
+            default_value = "csv",
+            possible_values = &["csv", "json"],
+            help = "The format of the export."
+        )]
+        format: ExportFormat,
+        #[structopt(
+            long = "from",
+            help = "Only export swaps started on or after this day (YYYY-MM-DD, UTC)."
+        )]
+        from: Option<String>,
+        #[structopt(
+            long = "to",
+            help = "Only export swaps started on or before this day (YYYY-MM-DD, UTC)."
+        )]
+        to: Option<String>,
+        #[structopt(
+            long = "output",
+            short = "o",
+            help = "Write the export to this file instead of printing it."
+        )]
+        output: Option<PathBuf>,
+    },
     #[structopt(about = "Prints the current config")]
     Config,
     #[structopt(about = "Allows withdrawing BTC from the internal Bitcoin wallet.")]
@@ -322,6 +496,36 @@ pub enum RawCommand {
         amount: Option<Amount>,
         #[structopt(long = "address", help = "The address to receive the Bitcoin.")]
         address: Address<NetworkUnchecked>,
+        #[structopt(
+            long = "input",
+            help = "Only spend this UTXO (txid:vout). Can be given multiple times."
+        )]
+        inputs: Vec<OutPoint>,
+    },
+    #[structopt(about = "Lists the UTXOs of the internal Bitcoin wallet with their labels.")]
+    ListUnspent,
+    #[structopt(
+        about = "Lists the transactions of the internal Bitcoin wallet and the swaps they belong to."
+    )]
+    BitcoinTransactions,
+    #[structopt(about = "Labels a UTXO of the internal Bitcoin wallet.")]
+    LabelUtxo {
+        #[structopt(long = "outpoint", help = "The UTXO to label (txid:vout).")]
+        outpoint: OutPoint,
+        #[structopt(
+            long = "label",
+            help = "The label to set. If omitted, the current label is removed."
+        )]
+        label: Option<String>,
+    },
+    #[structopt(
+        about = "Freezes a UTXO of the internal Bitcoin wallet such that it is never spent automatically."
+    )]
+    FreezeUtxo {
+        #[structopt(long = "outpoint", help = "The UTXO to freeze (txid:vout).")]
+        outpoint: OutPoint,
+        #[structopt(long = "unfreeze", help = "Unfreeze the UTXO instead.")]
+        unfreeze: bool,
     },
     #[structopt(
         about = "Prints the Bitcoin and Monero balance. Requires the monero-wallet-rpc to be running."
@@ -331,6 +535,39 @@ pub enum RawCommand {
     ExportBitcoinWallet,
     #[structopt(about = "Print the Monero wallet seed and creation height.")]
     ExportMoneroWallet,
+    #[structopt(
+        about = "Encrypts the seed file with a passphrase, which has to be entered whenever the asb starts."
+    )]
+    EncryptSeed,
+    #[structopt(
+        about = "Signs the Monero transfers of an asb running with a view-only wallet (see [monero.offline_signing]). Runs until stopped."
+    )]
+    MoneroSigner {
+        #[structopt(
+            long = "signing-dir",
+            help = "The directory shared with the asb.",
+            parse(from_os_str)
+        )]
+        signing_dir: PathBuf,
+        #[structopt(
+            long = "max-amount",
+            help = "Reject transfers above this amount of XMR.",
+            parse(try_from_str = crate::monero::Amount::parse_monero)
+        )]
+        max_amount: Option<crate::monero::Amount>,
+        #[structopt(
+            long = "max-total-amount",
+            help = "Reject transfers once this amount of XMR was signed within the window.",
+            parse(try_from_str = crate::monero::Amount::parse_monero)
+        )]
+        max_total_amount: Option<crate::monero::Amount>,
+        #[structopt(
+            long = "window-hours",
+            help = "The time window of --max-total-amount in hours.",
+            default_value = "24"
+        )]
+        window_hours: u64,
+    },
     #[structopt(about = "Contains sub-commands for recovering a swap manually.")]
     ManualRecovery(ManualRecovery),
 }
@@ -371,6 +608,19 @@ pub enum ManualRecovery {
         #[structopt(flatten)]
         punish_params: RecoverCommandParams,
     },
+    #[structopt(
+        about = "Bumps the fees of the unconfirmed Bitcoin transactions of a swap by spending our output with a child transaction that pays for the whole package."
+    )]
+    BumpFee {
+        #[structopt(flatten)]
+        bump_fee_params: RecoverCommandParams,
+
+        #[structopt(
+            long = "fee-rate",
+            help = "The fee rate (in sat/vB) to target. Defaults to the current fee estimate."
+        )]
+        fee_rate: Option<u64>,
+    },
     #[structopt(about = "Safely Abort requires the swap to be in a state prior to locking XMR.")]
     SafelyAbort {
         #[structopt(
@@ -399,6 +649,7 @@ mod tests {
     const BITCOIN_MAINNET_ADDRESS: &str = "1KFHE7w8BhaENAswwryaoccDb6qcT6DbYY";
     const BITCOIN_TESTNET_ADDRESS: &str = "tb1qyccwk4yun26708qg5h6g6we8kxln232wclxf5a";
     const SWAP_ID: &str = "ea030832-3be9-454f-bb98-5ea9a788406b";
+    const OUTPOINT: &str = "ea5e3a8d4e5d45d1ba24a6e8e5e4a7c2a9a1aebc2e3f3f1a16d2d5d7cc0a2f9e:1";
 
     #[test]
     fn ensure_start_command_mapping_mainnet() {
@@ -438,6 +689,37 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn ensure_export_history_command_mapping_mainnet() {
+        let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
+        let mainnet_env_config = env::Mainnet::get_config();
+
+        let raw_ars = vec![
+            BINARY_NAME,
+            "export-history",
+            "--format",
+            "json",
+            "--from",
+            "2025-01-01",
+            "--to",
+            "2025-12-31",
+        ];
+        let expected_args = Arguments {
+            testnet: false,
+            json: false,
+            trace: false,
+            config_path: default_mainnet_conf_path,
+            env_config: mainnet_env_config,
+            cmd: Command::ExportHistory {
+                format: ExportFormat::Json,
+                range: DateRange::parse(Some("2025-01-01"), Some("2025-12-31")).unwrap(),
+                output: None,
+            },
+        };
+        let args = parse_args(raw_ars).unwrap();
+        assert_eq!(expected_args, args);
+    }
+
     #[test]
     fn ensure_balance_command_mapping_mainnet() {
         let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
@@ -456,6 +738,24 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn ensure_encrypt_seed_command_mapping_mainnet() {
+        let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
+        let mainnet_env_config = env::Mainnet::get_config();
+
+        let raw_ars = vec![BINARY_NAME, "encrypt-seed"];
+        let expected_args = Arguments {
+            testnet: false,
+            json: false,
+            trace: false,
+            config_path: default_mainnet_conf_path,
+            env_config: mainnet_env_config,
+            cmd: Command::EncryptSeed,
+        };
+        let args = parse_args(raw_ars).unwrap();
+        assert_eq!(expected_args, args);
+    }
+
     #[test]
     fn ensure_withdraw_command_mapping_mainnet() {
         let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
@@ -476,6 +776,62 @@ mod tests {
                 amount: None,
                 address: bitcoin_address::parse_and_validate(BITCOIN_MAINNET_ADDRESS, false)
                     .unwrap(),
+                inputs: None,
+            },
+        };
+        let args = parse_args(raw_ars).unwrap();
+        assert_eq!(expected_args, args);
+    }
+
+    #[test]
+    fn ensure_withdraw_from_inputs_command_mapping_mainnet() {
+        let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
+        let mainnet_env_config = env::Mainnet::get_config();
+        let raw_ars = vec![
+            BINARY_NAME,
+            "withdraw-btc",
+            "--address",
+            BITCOIN_MAINNET_ADDRESS,
+            "--input",
+            OUTPOINT,
+        ];
+        let expected_args = Arguments {
+            testnet: false,
+            json: false,
+            trace: false,
+            config_path: default_mainnet_conf_path,
+            env_config: mainnet_env_config,
+            cmd: Command::WithdrawBtc {
+                amount: None,
+                address: bitcoin_address::parse_and_validate(BITCOIN_MAINNET_ADDRESS, false)
+                    .unwrap(),
+                inputs: Some(vec![OUTPOINT.parse().unwrap()]),
+            },
+        };
+        let args = parse_args(raw_ars).unwrap();
+        assert_eq!(expected_args, args);
+    }
+
+    #[test]
+    fn ensure_freeze_utxo_command_mapping_mainnet() {
+        let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
+        let mainnet_env_config = env::Mainnet::get_config();
+        let raw_ars = vec![
+            BINARY_NAME,
+            "freeze-utxo",
+            "--outpoint",
+            OUTPOINT,
+            "--unfreeze",
+        ];
+        let expected_args = Arguments {
+            testnet: false,
+            json: false,
+            trace: false,
+            config_path: default_mainnet_conf_path,
+            env_config: mainnet_env_config,
+            cmd: Command::FreezeUtxo {
+                outpoint: OUTPOINT.parse().unwrap(),
+                frozen: false,
             },
         };
         let args = parse_args(raw_ars).unwrap();
@@ -508,6 +864,35 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn ensure_bump_fee_command_mapping_mainnet() {
+        let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
+        let mainnet_env_config = env::Mainnet::get_config();
+
+        let raw_ars = vec![
+            BINARY_NAME,
+            "manual-recovery",
+            "bump-fee",
+            "--swap-id",
+            SWAP_ID,
+            "--fee-rate",
+            "25",
+        ];
+        let expected_args = Arguments {
+            testnet: false,
+            json: false,
+            trace: false,
+            config_path: default_mainnet_conf_path,
+            env_config: mainnet_env_config,
+            cmd: Command::BumpFee {
+                swap_id: Uuid::parse_str(SWAP_ID).unwrap(),
+                fee_rate: Some(25),
+            },
+        };
+        let args = parse_args(raw_ars).unwrap();
+        assert_eq!(expected_args, args);
+    }
+
     #[test]
     fn ensure_refund_command_mappin_mainnet() {
         let default_mainnet_conf_path = env::Mainnet::getConfigFileDefaults().unwrap().config_path;
@@ -624,6 +1009,38 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn ensure_export_history_command_mapping_testnet() {
+        let default_testnet_conf_path = env::Testnet::getConfigFileDefaults().unwrap().config_path;
+        let testnet_env_config = env::Testnet::get_config();
+
+        let raw_ars = vec![
+            BINARY_NAME,
+            "--testnet",
+            "export-history",
+            "--format",
+            "json",
+            "--from",
+            "2025-01-01",
+            "--to",
+            "2025-12-31",
+        ];
+        let expected_args = Arguments {
+            testnet: true,
+            json: false,
+            trace: false,
+            config_path: default_testnet_conf_path,
+            env_config: testnet_env_config,
+            cmd: Command::ExportHistory {
+                format: ExportFormat::Json,
+                range: DateRange::parse(Some("2025-01-01"), Some("2025-12-31")).unwrap(),
+                output: None,
+            },
+        };
+        let args = parse_args(raw_ars).unwrap();
+        assert_eq!(expected_args, args);
+    }
+
     #[test]
     fn ensure_balance_command_mapping_testnet() {
         let default_testnet_conf_path = env::Testnet::getConfigFileDefaults().unwrap().config_path;
@@ -661,6 +1078,40 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn ensure_monero_signer_command_mapping_testnet() {
+        let default_testnet_conf_path = env::Testnet::getConfigFileDefaults().unwrap().config_path;
+        let testnet_env_config = env::Testnet::get_config();
+
+        let raw_ars = vec![
+            BINARY_NAME,
+            "--testnet",
+            "monero-signer",
+            "--signing-dir",
+            "/mnt/signing",
+            "--max-amount",
+            "2.5",
+            "--max-total-amount",
+            "10",
+        ];
+        let expected_args = Arguments {
+            testnet: true,
+            json: false,
+            trace: false,
+            config_path: default_testnet_conf_path,
+            env_config: testnet_env_config,
+            cmd: Command::MoneroSigner {
+                signing_dir: PathBuf::from("/mnt/signing"),
+                max_amount: Some(crate::monero::Amount::parse_monero("2.5").unwrap()),
+                max_total_amount: Some(crate::monero::Amount::parse_monero("10").unwrap()),
+                window_hours: 24,
+            },
+        };
+        let args = parse_args(raw_ars).unwrap();
+
+        assert_eq!(expected_args, args);
+    }
+
     #[test]
     fn ensure_withdraw_command_mapping_testnet() {
         let default_testnet_conf_path = env::Testnet::getConfigFileDefaults().unwrap().config_path;
@@ -683,6 +1134,7 @@ mod tests {
                 amount: None,
                 address: bitcoin_address::parse_and_validate(BITCOIN_TESTNET_ADDRESS, true)
                     .unwrap(),
+                inputs: None,
             },
         };
         let args = parse_args(raw_ars).unwrap();
@@ -1,3 +1,4 @@
+use crate::bitcoin::FeeBumpPolicy;
 use crate::env::{Mainnet, Testnet};
 use crate::fs::{ensure_directory_exists, system_config_dir, system_data_dir};
 use anyhow::{bail, Context, Result};
@@ -5,13 +6,17 @@ use config::ConfigError;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::{Input, Select};
 use libp2p::core::Multiaddr;
+use libp2p::PeerId;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use serde_with::{serde_as, DisplayFromStr};
 use std::ffi::OsStr;
 use std::fs;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::time::Duration;
 use url::Url;
 
 pub trait GetDefaults {
@@ -79,6 +84,16 @@ fn default_asb_data_dir() -> Result<PathBuf> {
 const DEFAULT_MIN_BUY_AMOUNT: f64 = 0.002f64;
 const DEFAULT_MAX_BUY_AMOUNT: f64 = 0.02f64;
 const DEFAULT_SPREAD: f64 = 0.02f64;
+const DEFAULT_RPC_PORT: u16 = 9944;
+const DEFAULT_METRICS_PORT: u16 = 9945;
+const DEFAULT_FEE_BUMP_CHECK_INTERVAL_SECS: u64 = 10 * 60;
+const DEFAULT_FEE_BUMP_MAX_FEE_RATE: u64 = 100;
+const DEFAULT_SIGNING_TIMEOUT_SECS: u64 = 5 * 60;
+const DEFAULT_QUOTES_PER_MINUTE: u32 = 30;
+const DEFAULT_SWAP_SETUPS_PER_HOUR: u32 = 10;
+const DEFAULT_MAX_ABANDONED_SETUPS: u32 = 5;
+const DEFAULT_AUTO_BAN_SECS: u64 = 24 * 60 * 60;
+const DEFAULT_ONION_HEALTH_CHECK_INTERVAL_SECS: u64 = 10 * 60;
 
 #[derive(Clone, Debug, serde::Serialize, serde::Deserialize, PartialEq, Eq)]
 #[serde(deny_unknown_fields)]
@@ -89,6 +104,12 @@ pub struct Config {
     pub monero: Monero,
     pub tor: TorConf,
     pub maker: Maker,
+    /// The control-plane server is only started if this section is present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc: Option<Rpc>,
+    /// The Prometheus endpoint is only started if this section is present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<Metrics>,
 }
 
 impl Config {
@@ -134,6 +155,59 @@ pub struct Network {
     pub rendezvous_point: Vec<Multiaddr>,
     #[serde(default, deserialize_with = "addr_list::deserialize")]
     pub external_addresses: Vec<Multiaddr>,
+    #[serde(default)]
+    pub peer_limits: PeerLimits,
+}
+
+/// Limits on what a single peer can ask of us.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PeerLimits {
+    /// Quote requests beyond this are left unanswered
+    #[serde(default = "default_quotes_per_minute")]
+    pub quotes_per_minute: u32,
+    /// Swap setups beyond this fail before we capture a wallet snapshot
+    #[serde(default = "default_swap_setups_per_hour")]
+    pub swap_setups_per_hour: u32,
+    /// Peers which abandon more swap setups than this within `auto_ban_secs` are banned
+    #[serde(default = "default_max_abandoned_setups")]
+    pub max_abandoned_setups: u32,
+    /// How long an automatic ban lasts
+    #[serde(default = "default_auto_ban_secs")]
+    pub auto_ban_secs: u64,
+    /// Peers which may not connect to us at all
+    #[serde_as(as = "Vec<DisplayFromStr>")]
+    #[serde(default)]
+    pub denylist: Vec<PeerId>,
+}
+
+impl Default for PeerLimits {
+    fn default() -> Self {
+        Self {
+            quotes_per_minute: default_quotes_per_minute(),
+            swap_setups_per_hour: default_swap_setups_per_hour(),
+            max_abandoned_setups: default_max_abandoned_setups(),
+            auto_ban_secs: default_auto_ban_secs(),
+            denylist: vec![],
+        }
+    }
+}
+
+fn default_quotes_per_minute() -> u32 {
+    DEFAULT_QUOTES_PER_MINUTE
+}
+
+fn default_swap_setups_per_hour() -> u32 {
+    DEFAULT_SWAP_SETUPS_PER_HOUR
+}
+
+fn default_max_abandoned_setups() -> u32 {
+    DEFAULT_MAX_ABANDONED_SETUPS
+}
+
+fn default_auto_ban_secs() -> u64 {
+    DEFAULT_AUTO_BAN_SECS
 }
 
 mod addr_list {
@@ -229,12 +303,43 @@ pub struct Bitcoin {
     pub network: bitcoin::Network,
     #[serde(default = "default_use_mempool_space_fee_estimation")]
     pub use_mempool_space_fee_estimation: bool,
+    /// Unconfirmed swap transactions are only bumped automatically if this section is present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_bumping: Option<FeeBumping>,
 }
 
 fn default_use_mempool_space_fee_estimation() -> bool {
     true
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeBumping {
+    #[serde(default = "default_fee_bump_check_interval_secs")]
+    pub check_interval_secs: u64,
+    /// We never bump the fee of a transaction beyond this fee rate (in sat/vB)
+    #[serde(default = "default_fee_bump_max_fee_rate")]
+    pub max_fee_rate: u64,
+}
+
+impl FeeBumping {
+    pub fn policy(&self) -> Result<FeeBumpPolicy> {
+        Ok(FeeBumpPolicy {
+            interval: Duration::from_secs(self.check_interval_secs),
+            max_fee_rate: ::bitcoin::FeeRate::from_sat_per_vb(self.max_fee_rate)
+                .context("Maximum fee rate is too high")?,
+        })
+    }
+}
+
+fn default_fee_bump_check_interval_secs() -> u64 {
+    DEFAULT_FEE_BUMP_CHECK_INTERVAL_SECS
+}
+
+fn default_fee_bump_max_fee_rate() -> u64 {
+    DEFAULT_FEE_BUMP_MAX_FEE_RATE
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct Monero {
@@ -244,17 +349,89 @@ pub struct Monero {
     pub network: monero::Network,
     #[serde(default = "default_monero_node_pool")]
     pub monero_node_pool: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub offline_signing: Option<OfflineSigning>,
 }
 
 fn default_monero_node_pool() -> bool {
     false
 }
 
+/// Only keep a view-only Monero wallet on the asb. The Monero lock transactions
+/// are signed by `asb monero-signer` on another machine which holds the spend key.
+/// Both exchange files through the `signing_dir`.
+#[serde_as]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct OfflineSigning {
+    /// The main address of the wallet of the signer.
+    #[serde_as(as = "DisplayFromStr")]
+    pub address: monero::Address,
+    /// The private view key of the wallet of the signer, printed by `asb monero-signer`.
+    #[serde(with = "crate::monero::monero_private_key")]
+    pub view_key: monero::PrivateKey,
+    /// The height to scan from when creating the view-only wallet.
+    #[serde(default)]
+    pub restore_height: u64,
+    /// Authenticates our transfers to the signer, printed by `asb monero-signer`.
+    #[serde_as(as = "DisplayFromStr")]
+    pub manifest_key: crate::monero::offline_signing::ManifestKey,
+    pub signing_dir: PathBuf,
+    /// How long to wait for the signer before retrying to lock the Monero.
+    #[serde(default = "default_signing_timeout_secs")]
+    pub signing_timeout_secs: u64,
+}
+
+fn default_signing_timeout_secs() -> u64 {
+    DEFAULT_SIGNING_TIMEOUT_SECS
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rpc {
+    /// Anyone who can read the cookie file in the data directory can control the asb.
+    /// Only expose this on localhost unless you know what you are doing.
+    #[serde(default = "default_rpc_listen")]
+    pub listen: SocketAddr,
+}
+
+fn default_rpc_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], DEFAULT_RPC_PORT))
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Metrics {
+    /// The `/metrics` endpoint is not authenticated.
+    #[serde(default = "default_metrics_listen")]
+    pub listen: SocketAddr,
+}
+
+fn default_metrics_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], DEFAULT_METRICS_PORT))
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct TorConf {
     pub register_hidden_service: bool,
     pub hidden_service_num_intro_points: u8,
+    /// Only listen on and advertise the onion service and dial all peers through Tor.
+    ///
+    /// The `network.listen` addresses and all clearnet `network.external_addresses` are
+    /// ignored. The key of the onion service is derived from the seed, so the onion address
+    /// stays the same even if the Tor state directory is lost.
+    #[serde(default)]
+    pub onion_only: bool,
+    /// How often we check whether the onion service can be reached through Tor.
+    ///
+    /// If it cannot be reached several times in a row, we publish it again.
+    #[serde(default = "default_onion_health_check_interval_secs")]
+    pub health_check_interval_secs: u64,
+}
+
+fn default_onion_health_check_interval_secs() -> u64 {
+    DEFAULT_ONION_HEALTH_CHECK_INTERVAL_SECS
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -268,6 +445,127 @@ pub struct Maker {
     pub price_ticker_ws_url: Url,
     #[serde(default, with = "crate::bitcoin::address_serde::option")]
     pub external_bitcoin_redeem_address: Option<bitcoin::Address>,
+    /// Price feeds used to determine the market rate.
+    ///
+    /// If omitted, only the Kraken websocket at `price_ticker_ws_url` is used.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price_sources: Option<PriceSources>,
+    /// Rules that widen `ask_spread` depending on inventory, swap size, time of day and volatility.
+    ///
+    /// If omitted, `ask_spread` is applied to every swap.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pricing: Option<Pricing>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PriceSources {
+    pub feeds: Vec<PriceFeed>,
+    /// Prices older than this are not taken into account
+    #[serde(default = "default_max_price_staleness_secs")]
+    pub max_staleness_secs: u64,
+    /// The maximum allowed deviation of a single feed from the median of all feeds, as a factor.
+    /// If any feed deviates further, we refuse to quote.
+    #[serde(default = "default_max_price_deviation")]
+    pub max_deviation: Decimal,
+    /// The minimum number of feeds that must report a recent price for us to quote
+    #[serde(default = "default_min_price_sources")]
+    pub min_sources: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PriceFeed {
+    /// A websocket server that follows the Kraken price ticker protocol
+    Kraken { ws_url: Url },
+    /// A HTTP endpoint that returns JSON. The XMR/BTC price is extracted using `json_pointer`.
+    Rest {
+        url: Url,
+        json_pointer: String,
+        #[serde(default = "default_price_poll_interval_secs")]
+        poll_interval_secs: u64,
+    },
+    /// A local file containing the XMR/BTC price. Use `-` to read prices line by line from stdin.
+    File {
+        path: PathBuf,
+        #[serde(default = "default_price_poll_interval_secs")]
+        poll_interval_secs: u64,
+    },
+}
+
+/// Each rule adds to the configured `ask_spread`. If several tiers of the same kind
+/// match, the largest additional spread of that kind is used.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pricing {
+    /// Charge more once our unreserved Monero balance drops below a threshold
+    #[serde(default)]
+    pub inventory: Vec<InventoryTier>,
+    /// Charge more for large swaps
+    #[serde(default)]
+    pub size: Vec<SizeTier>,
+    /// Charge more during certain hours of the day (UTC)
+    #[serde(default)]
+    pub schedule: Vec<ScheduleWindow>,
+    /// Charge more while the market price is moving quickly
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volatility: Option<Volatility>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct InventoryTier {
+    pub below_xmr: Decimal,
+    pub additional_spread: Decimal,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct SizeTier {
+    #[serde(with = "::bitcoin::amount::serde::as_btc")]
+    pub above_btc: bitcoin::Amount,
+    pub additional_spread: Decimal,
+}
+
+/// A window of hours (UTC). The window wraps around midnight if `end_hour` is smaller than `start_hour`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleWindow {
+    pub start_hour: u8,
+    pub end_hour: u8,
+    pub additional_spread: Decimal,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Volatility {
+    /// The time window over which we observe the market price
+    #[serde(default = "default_volatility_window_secs")]
+    pub window_secs: u64,
+    /// The relative price range ((max - min) / min) within the window is multiplied by this factor
+    pub multiplier: Decimal,
+    /// Upper bound for the additional spread caused by volatility
+    pub max_additional_spread: Decimal,
+}
+
+fn default_volatility_window_secs() -> u64 {
+    60 * 60
+}
+
+fn default_max_price_staleness_secs() -> u64 {
+    10 * 60
+}
+
+fn default_max_price_deviation() -> Decimal {
+    Decimal::new(2, 2)
+}
+
+fn default_min_price_sources() -> usize {
+    1
+}
+
+fn default_price_poll_interval_secs() -> u64 {
+    30
 }
 
 impl Default for TorConf {
@@ -275,6 +573,8 @@ impl Default for TorConf {
         Self {
             register_hidden_service: true,
             hidden_service_num_intro_points: 5,
+            onion_only: false,
+            health_check_interval_secs: DEFAULT_ONION_HEALTH_CHECK_INTERVAL_SECS,
         }
     }
 }
@@ -459,6 +759,7 @@ pub fn query_user_for_initial_config(testnet: bool) -> Result<Config> {
             listen: listen_addresses,
             rendezvous_point: rendezvous_points, // keeping the singular key name for backcompat
             external_addresses: vec![],
+            peer_limits: Default::default(),
         },
         bitcoin: Bitcoin {
             electrum_rpc_urls,
@@ -466,13 +767,17 @@ pub fn query_user_for_initial_config(testnet: bool) -> Result<Config> {
             finality_confirmations: None,
             network: bitcoin_network,
             use_mempool_space_fee_estimation: true,
+            fee_bumping: None,
         },
         monero: Monero {
             daemon_url: monero_daemon_url,
             finality_confirmations: None,
             network: monero_network,
             monero_node_pool: false,
+            offline_signing: None,
         },
+        rpc: None,
+        metrics: None,
         tor: TorConf {
             register_hidden_service,
             ..Default::default()
@@ -483,6 +788,8 @@ pub fn query_user_for_initial_config(testnet: bool) -> Result<Config> {
             ask_spread,
             price_ticker_ws_url: defaults.price_ticker_ws_url,
             external_bitcoin_redeem_address: None,
+            price_sources: None,
+            pricing: None,
         },
     })
 }
@@ -512,18 +819,23 @@ mod tests {
                 finality_confirmations: None,
                 network: bitcoin::Network::Testnet,
                 use_mempool_space_fee_estimation: true,
+                fee_bumping: None,
             },
             network: Network {
                 listen: vec![defaults.listen_address_tcp],
                 rendezvous_point: vec![],
                 external_addresses: vec![],
+                peer_limits: Default::default(),
             },
             monero: Monero {
                 daemon_url: defaults.monero_daemon_address,
                 finality_confirmations: None,
                 network: monero::Network::Stagenet,
                 monero_node_pool: false,
+                offline_signing: None,
             },
+            rpc: None,
+            metrics: None,
             tor: Default::default(),
             maker: Maker {
                 min_buy_btc: bitcoin::Amount::from_btc(DEFAULT_MIN_BUY_AMOUNT).unwrap(),
@@ -531,6 +843,8 @@ mod tests {
                 ask_spread: Decimal::from_f64(DEFAULT_SPREAD).unwrap(),
                 price_ticker_ws_url: defaults.price_ticker_ws_url,
                 external_bitcoin_redeem_address: None,
+                price_sources: None,
+                pricing: None,
             },
         };
 
@@ -558,18 +872,23 @@ mod tests {
                 finality_confirmations: None,
                 network: bitcoin::Network::Bitcoin,
                 use_mempool_space_fee_estimation: true,
+                fee_bumping: None,
             },
             network: Network {
                 listen: vec![defaults.listen_address_tcp],
                 rendezvous_point: vec![],
                 external_addresses: vec![],
+                peer_limits: Default::default(),
             },
             monero: Monero {
                 daemon_url: defaults.monero_daemon_address,
                 finality_confirmations: None,
                 network: monero::Network::Mainnet,
                 monero_node_pool: false,
+                offline_signing: None,
             },
+            rpc: None,
+            metrics: None,
             tor: Default::default(),
             maker: Maker {
                 min_buy_btc: bitcoin::Amount::from_btc(DEFAULT_MIN_BUY_AMOUNT).unwrap(),
@@ -577,6 +896,8 @@ mod tests {
                 ask_spread: Decimal::from_f64(DEFAULT_SPREAD).unwrap(),
                 price_ticker_ws_url: defaults.price_ticker_ws_url,
                 external_bitcoin_redeem_address: None,
+                price_sources: None,
+                pricing: None,
             },
         };
 
@@ -586,6 +907,179 @@ mod tests {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn deserialize_price_sources() {
+        let maker = r#"
+            min_buy_btc = 0.002
+            max_buy_btc = 0.02
+            ask_spread = 0.02
+            price_ticker_ws_url = "wss://ws.kraken.com"
+
+            [price_sources]
+            max_deviation = 0.05
+            min_sources = 2
+
+            [[price_sources.feeds]]
+            type = "kraken"
+            ws_url = "wss://ws.kraken.com"
+
+            [[price_sources.feeds]]
+            type = "rest"
+            url = "https://api.kraken.com/0/public/Ticker?pair=XMRXBT"
+            json_pointer = "/result/XXMRXXBT/a/0"
+
+            [[price_sources.feeds]]
+            type = "file"
+            path = "/tmp/price"
+            poll_interval_secs = 5
+        "#;
+
+        let maker: Maker = toml::from_str(maker).unwrap();
+
+        assert_eq!(
+            maker.price_sources,
+            Some(PriceSources {
+                feeds: vec![
+                    PriceFeed::Kraken {
+                        ws_url: Url::parse("wss://ws.kraken.com").unwrap(),
+                    },
+                    PriceFeed::Rest {
+                        url: Url::parse("https://api.kraken.com/0/public/Ticker?pair=XMRXBT")
+                            .unwrap(),
+                        json_pointer: "/result/XXMRXXBT/a/0".to_string(),
+                        poll_interval_secs: default_price_poll_interval_secs(),
+                    },
+                    PriceFeed::File {
+                        path: PathBuf::from("/tmp/price"),
+                        poll_interval_secs: 5,
+                    },
+                ],
+                max_staleness_secs: default_max_price_staleness_secs(),
+                max_deviation: Decimal::from_f64(0.05).unwrap(),
+                min_sources: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_fee_bumping() {
+        let bitcoin = r#"
+            electrum_rpc_urls = ["tcp://localhost:50001"]
+            target_block = 1
+            network = "Testnet"
+
+            [fee_bumping]
+            max_fee_rate = 50
+        "#;
+
+        let bitcoin: Bitcoin = toml::from_str(bitcoin).unwrap();
+
+        assert_eq!(
+            bitcoin.fee_bumping,
+            Some(FeeBumping {
+                check_interval_secs: DEFAULT_FEE_BUMP_CHECK_INTERVAL_SECS,
+                max_fee_rate: 50,
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_peer_limits() {
+        let network = r#"
+            listen = ["/ip4/0.0.0.0/tcp/9939"]
+
+            [peer_limits]
+            quotes_per_minute = 5
+            denylist = ["12D3KooWCdMKjesXMJz1SiZ7HgotrxuqhQJbP5sgBm2BwP1cqThi"]
+        "#;
+
+        let network: Network = toml::from_str(network).unwrap();
+
+        assert_eq!(
+            network.peer_limits,
+            PeerLimits {
+                quotes_per_minute: 5,
+                denylist: vec!["12D3KooWCdMKjesXMJz1SiZ7HgotrxuqhQJbP5sgBm2BwP1cqThi"
+                    .parse()
+                    .unwrap()],
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_onion_only_tor_conf() {
+        let tor = r#"
+            register_hidden_service = true
+            hidden_service_num_intro_points = 3
+            onion_only = true
+        "#;
+
+        let tor: TorConf = toml::from_str(tor).unwrap();
+
+        assert_eq!(
+            tor,
+            TorConf {
+                hidden_service_num_intro_points: 3,
+                onion_only: true,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_pricing() {
+        let maker = r#"
+            min_buy_btc = 0.002
+            max_buy_btc = 0.02
+            ask_spread = 0.02
+            price_ticker_ws_url = "wss://ws.kraken.com"
+
+            [[pricing.inventory]]
+            below_xmr = 10
+            additional_spread = 0.01
+
+            [[pricing.size]]
+            above_btc = 0.01
+            additional_spread = 0.005
+
+            [[pricing.schedule]]
+            start_hour = 22
+            end_hour = 6
+            additional_spread = 0.005
+
+            [pricing.volatility]
+            multiplier = 1
+            max_additional_spread = 0.05
+        "#;
+
+        let maker: Maker = toml::from_str(maker).unwrap();
+
+        assert_eq!(
+            maker.pricing,
+            Some(Pricing {
+                inventory: vec![InventoryTier {
+                    below_xmr: Decimal::from(10),
+                    additional_spread: Decimal::new(1, 2),
+                }],
+                size: vec![SizeTier {
+                    above_btc: bitcoin::Amount::from_btc(0.01).unwrap(),
+                    additional_spread: Decimal::new(5, 3),
+                }],
+                schedule: vec![ScheduleWindow {
+                    start_hour: 22,
+                    end_hour: 6,
+                    additional_spread: Decimal::new(5, 3),
+                }],
+                volatility: Some(Volatility {
+                    window_secs: default_volatility_window_secs(),
+                    multiplier: Decimal::ONE,
+                    max_additional_spread: Decimal::new(5, 2),
+                }),
+            })
+        );
+    }
+
     #[test]
     #[serial]
     fn env_override() {
@@ -614,18 +1108,23 @@ mod tests {
                 finality_confirmations: None,
                 network: bitcoin::Network::Bitcoin,
                 use_mempool_space_fee_estimation: true,
+                fee_bumping: None,
             },
             network: Network {
                 listen,
                 rendezvous_point: vec![],
                 external_addresses,
+                peer_limits: Default::default(),
             },
             monero: Monero {
                 daemon_url: defaults.monero_daemon_address,
                 finality_confirmations: None,
                 network: monero::Network::Mainnet,
                 monero_node_pool: false,
+                offline_signing: None,
             },
+            rpc: None,
+            metrics: None,
             tor: Default::default(),
             maker: Maker {
                 min_buy_btc: bitcoin::Amount::from_btc(DEFAULT_MIN_BUY_AMOUNT).unwrap(),
@@ -633,6 +1132,8 @@ mod tests {
                 ask_spread: Decimal::from_f64(DEFAULT_SPREAD).unwrap(),
                 price_ticker_ws_url: defaults.price_ticker_ws_url,
                 external_bitcoin_redeem_address: None,
+                price_sources: None,
+                pricing: None,
             },
         };
 
@@ -1,6 +1,12 @@
-use crate::asb::{Behaviour, OutEvent, Rate};
+use crate::asb::onion::OnionService;
+use crate::asb::{Behaviour, OutEvent, PricingContext, PricingPolicy, Rate};
+use crate::common::metrics::METRICS;
+use crate::network::cooperative_early_refund::{self, CooperativeEarlyRefundRejectReason};
 use crate::network::cooperative_xmr_redeem_after_punish::CooperativeXmrRedeemRejectReason;
 use crate::network::cooperative_xmr_redeem_after_punish::Response::{Fullfilled, Rejected};
+#[cfg(feature = "test-utils")]
+use crate::network::fault_injection::MessageFaults;
+use crate::network::fault_injection::{FaultInjector, Message};
 use crate::network::quote::BidQuote;
 use crate::network::swap_setup::alice::WalletSnapshot;
 use crate::network::transfer_proof;
@@ -14,15 +20,16 @@ use futures::future::{BoxFuture, FutureExt};
 use futures::stream::{FuturesUnordered, StreamExt};
 use libp2p::request_response::{OutboundFailure, OutboundRequestId, ResponseChannel};
 use libp2p::swarm::SwarmEvent;
-use libp2p::{PeerId, Swarm};
+use libp2p::{Multiaddr, PeerId, Swarm};
 use moka::future::Cache;
 use monero::Amount;
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 use std::convert::{Infallible, TryInto};
 use std::fmt::Debug;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
 use tokio::sync::{mpsc, oneshot};
 use tokio::time::timeout;
 use uuid::Uuid;
@@ -30,6 +37,10 @@ use uuid::Uuid;
 /// The time-to-live for quotes in the cache
 const QUOTE_CACHE_TTL: Duration = Duration::from_secs(120);
 
+/// How long we wait for a swap to answer an early refund request before rejecting it.
+/// A swap which is in the middle of locking the Monero only answers once it is done.
+const EARLY_REFUND_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
 /// The key for the quote cache
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct QuoteCacheKey {
@@ -48,6 +59,7 @@ where
     monero_wallet: Arc<monero::Wallets>,
     db: Arc<dyn Database + Send + Sync>,
     latest_rate: LR,
+    pricing_policy: PricingPolicy,
     min_buy: bitcoin::Amount,
     max_buy: bitcoin::Amount,
     external_redeem_address: Option<bitcoin::Address>,
@@ -77,6 +89,27 @@ where
     /// 4. Future is removed from this collection
     inflight_encrypted_signatures: FuturesUnordered<BoxFuture<'static, ResponseChannel<()>>>,
 
+    /// Stores where to forward early refund requests from Bob to.
+    /// The corresponding receiver is stored in the EventLoopHandle that is responsible for the swap.
+    ///
+    /// The swap answers a request through the enclosed [`oneshot::Sender`] once it has decided
+    /// not to lock the Monero. If it drops the sender instead, we reject the request.
+    early_refund_requests: HashMap<Uuid, mpsc::UnboundedSender<oneshot::Sender<()>>>,
+
+    /// Early refund requests which we forwarded to the swap and whose answer we are awaiting.
+    /// Resolves to the channel to respond to Bob on, the swap id and whether the swap accepted.
+    #[allow(clippy::type_complexity)]
+    inflight_early_refund_requests: FuturesUnordered<
+        BoxFuture<
+            'static,
+            (
+                ResponseChannel<cooperative_early_refund::Response>,
+                Uuid,
+                bool,
+            ),
+        >,
+    >,
+
     /// Channel for sending transfer proofs to Bobs. The sender is shared with every EventLoopHandle.
     /// The receiver is polled by the event loop to send transfer proofs over the network to Bob.
     ///
@@ -121,6 +154,21 @@ where
     /// 4. The entry is then removed from this map
     inflight_transfer_proofs:
         HashMap<OutboundRequestId, oneshot::Sender<Result<(), OutboundFailure>>>,
+
+    /// Faults to inject into the transfer proofs we send, only used by tests.
+    #[allow(clippy::type_complexity)]
+    transfer_proof_faults: FaultInjector<(
+        PeerId,
+        transfer_proof::Request,
+        oneshot::Sender<Result<(), OutboundFailure>>,
+    )>,
+
+    /// The onion service we listen on, if any. Kept here such that it can be re-published.
+    onion_service: Option<OnionService>,
+
+    /// Requests from the control-plane (see [`EventLoopService`]) for information only the event loop has access to
+    control_requests: mpsc::UnboundedReceiver<ControlRequest>,
+    control_sender: mpsc::UnboundedSender<ControlRequest>,
 }
 
 impl<LR> EventLoop<LR>
@@ -135,6 +183,7 @@ where
         monero_wallet: Arc<monero::Wallets>,
         db: Arc<dyn Database + Send + Sync>,
         latest_rate: LR,
+        pricing_policy: PricingPolicy,
         min_buy: bitcoin::Amount,
         max_buy: bitcoin::Amount,
         external_redeem_address: Option<bitcoin::Address>,
@@ -144,6 +193,7 @@ where
             tokio::sync::mpsc::unbounded_channel();
 
         let quote_cache = Cache::builder().time_to_live(QUOTE_CACHE_TTL).build();
+        let (control_sender, control_requests) = mpsc::unbounded_channel();
 
         let event_loop = EventLoop {
             swarm,
@@ -152,6 +202,7 @@ where
             monero_wallet,
             db,
             latest_rate,
+            pricing_policy,
             swap_sender: swap_channel.sender,
             min_buy,
             max_buy,
@@ -159,23 +210,53 @@ where
             quote_cache,
             recv_encrypted_signature: Default::default(),
             inflight_encrypted_signatures: Default::default(),
+            early_refund_requests: Default::default(),
+            inflight_early_refund_requests: Default::default(),
             outgoing_transfer_proofs_requests,
             outgoing_transfer_proofs_sender,
             buffered_transfer_proofs: Default::default(),
             inflight_transfer_proofs: Default::default(),
+            transfer_proof_faults: Default::default(),
+            onion_service: None,
+            control_requests,
+            control_sender,
         };
         Ok((event_loop, swap_channel.receiver))
     }
 
+    /// Inject the given faults into the transfer proofs we send to Bob.
+    ///
+    /// Only used by tests, see [`fault_injection`](crate::network::fault_injection).
+    #[cfg(feature = "test-utils")]
+    pub fn with_message_faults(mut self, message_faults: MessageFaults) -> Self {
+        self.transfer_proof_faults = FaultInjector::new(message_faults);
+        self
+    }
+
+    /// The onion service the swarm listens on, re-published on request of the health check.
+    pub fn with_onion_service(mut self, onion_service: Option<OnionService>) -> Self {
+        self.onion_service = onion_service;
+        self
+    }
+
     pub fn peer_id(&self) -> PeerId {
         *Swarm::local_peer_id(&self.swarm)
     }
 
+    /// Returns a handle which can be used to query the event loop while it is running.
+    pub fn service(&self) -> EventLoopService {
+        EventLoopService {
+            sender: self.control_sender.clone(),
+        }
+    }
+
     pub async fn run(mut self) {
         // ensure that these streams are NEVER empty, otherwise it will
         // terminate forever.
         self.inflight_encrypted_signatures
             .push(future::pending().boxed());
+        self.inflight_early_refund_requests
+            .push(future::pending().boxed());
 
         let swaps = match self.db.all().await {
             Ok(swaps) => swaps,
@@ -191,31 +272,9 @@ where
             .collect::<Vec<(Uuid, State)>>();
 
         for (swap_id, state) in unfinished_swaps {
-            let peer_id = match self.db.get_peer_id(swap_id).await {
-                Ok(peer_id) => peer_id,
-                Err(_) => {
-                    tracing::warn!(%swap_id, "Resuming swap skipped because no peer-id found for swap in database");
-                    continue;
-                }
-            };
-
-            let handle = self.new_handle(peer_id, swap_id);
-
-            let swap = Swap {
-                event_loop_handle: handle,
-                bitcoin_wallet: self.bitcoin_wallet.clone(),
-                monero_wallet: self.monero_wallet.clone(),
-                env_config: self.env_config,
-                db: self.db.clone(),
-                state: state.try_into().expect("Alice state loaded from db"),
-                swap_id,
-            };
-
-            match self.swap_sender.send(swap).await {
-                Ok(_) => tracing::info!(%swap_id, "Resuming swap"),
-                Err(_) => {
-                    tracing::warn!(%swap_id, "Failed to resume swap because receiver has been dropped")
-                }
+            match self.resume_swap(swap_id, state).await {
+                Ok(()) => tracing::info!(%swap_id, "Resuming swap"),
+                Err(error) => tracing::warn!(%swap_id, "Failed to resume swap: {:#}", error),
             }
         }
 
@@ -223,7 +282,13 @@ where
             tokio::select! {
                 swarm_event = self.swarm.select_next_some() => {
                     match swarm_event {
-                        SwarmEvent::Behaviour(OutEvent::SwapSetupInitiated { mut send_wallet_snapshot }) => {
+                        SwarmEvent::Behaviour(OutEvent::SwapSetupInitiated { peer_id, mut send_wallet_snapshot }) => {
+                            // Dropping the receiver makes the swap setup fail before we capture a wallet snapshot
+                            if !self.swarm.behaviour_mut().peer_guard.allow_swap_setup(peer_id) {
+                                tracing::debug!(%peer_id, "Ignoring swap setup because the peer exceeded its limit");
+                                continue;
+                            }
+
                             let (btc, responder) = match send_wallet_snapshot.recv().await {
                                 Ok((btc, responder)) => (btc, responder),
                                 Err(error) => {
@@ -240,20 +305,45 @@ where
                                 }
                             };
 
-                            // Ignore result, we should never hit this because the receiver will alive as long as the connection is.
-                            let _ = responder.respond(wallet_snapshot);
+                            let additional_ask_spread = match self.additional_ask_spread(btc, wallet_snapshot.unlocked_balance()).await {
+                                Ok(additional_ask_spread) => additional_ask_spread,
+                                Err(error) => {
+                                    tracing::error!("Swap request will be ignored because we were unable to apply our pricing policy: {:#}", error);
+                                    continue;
+                                }
+                            };
+                            let wallet_snapshot = wallet_snapshot.with_additional_ask_spread(additional_ask_spread);
+
+                            // The receiver is alive as long as the connection is
+                            if responder.respond(wallet_snapshot).is_ok() {
+                                self.swarm.behaviour_mut().peer_guard.swap_setup_started(peer_id);
+                            }
                         }
-                        SwarmEvent::Behaviour(OutEvent::SwapSetupCompleted{peer_id, swap_id, state3}) => {
-                            self.handle_execution_setup_done(peer_id, swap_id, state3).await;
+                        SwarmEvent::Behaviour(OutEvent::SwapSetupCompleted{peer_id, swap_id, state3, rate}) => {
+                            self.swarm.behaviour_mut().peer_guard.swap_setup_finished(peer_id);
+                            self.handle_execution_setup_done(peer_id, swap_id, state3, rate).await;
                         }
                         SwarmEvent::Behaviour(OutEvent::SwapDeclined { peer, error }) => {
-                            tracing::warn!(%peer, "Ignoring spot price request: {}", error);
+                            self.swarm.behaviour_mut().peer_guard.swap_setup_finished(peer);
+                            tracing::warn!(peer_id = %peer, "Ignoring spot price request: {}", error);
+                        }
+                        SwarmEvent::Behaviour(OutEvent::SwapSetupFailed { peer, error }) => {
+                            self.swarm.behaviour_mut().peer_guard.swap_setup_failed(peer);
+                            tracing::error!(
+                                %peer,
+                                "Swap setup failed: {:?}", error);
                         }
                         SwarmEvent::Behaviour(OutEvent::QuoteRequested { channel, peer }) => {
+                            // Dropping the channel makes the request fail on the peer's side
+                            if !self.swarm.behaviour_mut().peer_guard.allow_quote(peer) {
+                                tracing::debug!(peer_id = %peer, "Ignoring quote request because the peer exceeded its limit");
+                                continue;
+                            }
+
                             match self.make_quote_or_use_cached(self.min_buy, self.max_buy).await {
                                 Ok(quote_arc) => {
                                     if self.swarm.behaviour_mut().quote.send_response(channel, *quote_arc).is_err() {
-                                        tracing::debug!(%peer, "Failed to respond with quote");
+                                        tracing::debug!(peer_id = %peer, "Failed to respond with quote");
                                     }
                                 }
                                 // The error is already logged in the make_quote_or_use_cached function
@@ -268,13 +358,13 @@ where
                                         .send_response(channel, BidQuote::ZERO)
                                         .is_err()
                                     {
-                                        tracing::debug!(%peer, "Failed to respond with zero quote");
+                                        tracing::debug!(peer_id = %peer, "Failed to respond with zero quote");
                                     }
                                 }
                             }
                         }
                         SwarmEvent::Behaviour(OutEvent::TransferProofAcknowledged { peer, id }) => {
-                            tracing::debug!(%peer, "Bob acknowledged transfer proof");
+                            tracing::debug!(peer_id = %peer, "Bob acknowledged transfer proof");
 
                             if let Some(responder) = self.inflight_transfer_proofs.remove(&id) {
                                 let _ = responder.send(Ok(()));
@@ -316,6 +406,7 @@ where
                                 // Check if we have already processed the encrypted signature
                                 if has_already_processed_enc_sig(&state) {
                                     tracing::warn!(%swap_id, "Received encrypted signature for swap in state {}. We have already processed this encrypted signature. Acknowledging immediately.", state);
+                                    METRICS.record_encrypted_signature_retry();
 
                                     // We push create a future that will resolve immediately, and returns the channel
                                     // This will be resolved in the next iteration of the event loop, and the acknowledgment will be sent to Bob
@@ -330,18 +421,20 @@ where
                             let sender = match self.recv_encrypted_signature.remove(&swap_id) {
                                 Some(sender) => sender,
                                 None => {
-                                    // TODO: Don't just drop encsig if we currently don't have a running swap for it, save in db
-                                    // 1. Save the encrypted signature in the database
-                                    // 2. Acknowledge the receipt of the encrypted signature
-                                    tracing::warn!(%swap_id, "No sender for encrypted signature, maybe already handled?");
+                                    // We do not have a running swap that is waiting for this encrypted signature
+                                    // We save it in the database such that the swap can pick it up once it needs it
+                                    // and acknowledge the receipt immediately so Bob stops retrying
+                                    tracing::info!(%swap_id, "No running swap is waiting for the encrypted signature. Buffering it in the database");
+                                    self.buffer_encrypted_signature(swap_id, msg.tx_redeem_encsig, channel).await;
                                     continue;
                                 }
                             };
 
-                            let mut responder = match sender.send(msg.tx_redeem_encsig).await {
+                            let mut responder = match sender.send(msg.tx_redeem_encsig.clone()).await {
                                 Ok(responder) => responder,
                                 Err(_) => {
-                                    tracing::warn!(%swap_id, "Failed to relay encrypted signature to swap");
+                                    tracing::warn!(%swap_id, "Failed to relay encrypted signature to swap. Buffering it in the database");
+                                    self.buffer_encrypted_signature(swap_id, msg.tx_redeem_encsig, channel).await;
                                     continue;
                                 }
                             };
@@ -404,11 +497,14 @@ where
                             };
 
                             if self.swarm.behaviour_mut().cooperative_xmr_redeem.send_response(channel, Fullfilled { swap_id, s_a: state3.s_a, lock_transfer_proof: transfer_proof }).is_err() {
-                                tracing::error!(peer = %peer, "Failed to respond to cooperative XMR redeem request");
+                                tracing::error!(peer_id = %peer, "Failed to respond to cooperative XMR redeem request");
                                 continue;
                             }
 
-                            tracing::info!(swap_id = %swap_id, peer = %peer, "Fullfilled cooperative XMR redeem request");
+                            tracing::info!(swap_id = %swap_id, peer_id = %peer, "Fullfilled cooperative XMR redeem request");
+                        }
+                        SwarmEvent::Behaviour(OutEvent::CooperativeEarlyRefundRequested { swap_id, channel, peer }) => {
+                            self.handle_early_refund_request(swap_id, peer, channel).await;
                         }
                         SwarmEvent::Behaviour(OutEvent::Rendezvous(libp2p::rendezvous::client::Event::Registered { rendezvous_node, ttl, namespace })) => {
                             tracing::trace!("Successfully registered with rendezvous node: {} with namespace: {} and TTL: {:?}", rendezvous_node, namespace, ttl);
@@ -418,7 +514,7 @@ where
                         }
                         SwarmEvent::Behaviour(OutEvent::OutboundRequestResponseFailure {peer, error, request_id, protocol}) => {
                             tracing::error!(
-                                %peer,
+                                peer_id = %peer,
                                 %request_id,
                                 ?error,
                                 %protocol,
@@ -430,7 +526,7 @@ where
                         }
                         SwarmEvent::Behaviour(OutEvent::InboundRequestResponseFailure {peer, error, request_id, protocol}) => {
                             tracing::error!(
-                                %peer,
+                                peer_id = %peer,
                                 %request_id,
                                 ?error,
                                 %protocol,
@@ -442,17 +538,17 @@ where
                                 "Communication error: {:?}", error);
                         }
                         SwarmEvent::ConnectionEstablished { peer_id: peer, endpoint, .. } => {
-                            tracing::trace!(%peer, address = %endpoint.get_remote_address(), "New connection established");
+                            tracing::trace!(peer_id = %peer, address = %endpoint.get_remote_address(), "New connection established");
 
                             // If we have buffered transfer proofs for this peer, we can now send them
                             if let Some(transfer_proofs) = self.buffered_transfer_proofs.remove(&peer) {
                                 for (transfer_proof, responder) in transfer_proofs {
-                                    tracing::debug!(%peer, "Found buffered transfer proof for peer");
+                                    tracing::debug!(peer_id = %peer, "Found buffered transfer proof for peer");
 
                                     // We have an established connection to the peer, so we can add the transfer proof to the queue
                                     // This is then polled in the next iteration of the event loop, and attempted to be sent to the peer
                                     if let Err(e) = self.outgoing_transfer_proofs_sender.send((peer, transfer_proof, responder)) {
-                                        tracing::error!(%peer, error = ?e, "Failed to forward buffered transfer proof to event loop channel");
+                                        tracing::error!(peer_id = %peer, error = ?e, "Failed to forward buffered transfer proof to event loop channel");
                                     }
                                 }
                             }
@@ -461,10 +557,10 @@ where
                             tracing::trace!(%address, "Failed to set up connection with peer: {:?}", error);
                         }
                         SwarmEvent::ConnectionClosed { peer_id: peer, num_established: 0, endpoint, cause: Some(error), connection_id } => {
-                            tracing::trace!(%peer, address = %endpoint.get_remote_address(), %connection_id, "Lost connection to peer: {:?}", error);
+                            tracing::trace!(peer_id = %peer, address = %endpoint.get_remote_address(), %connection_id, "Lost connection to peer: {:?}", error);
                         }
                         SwarmEvent::ConnectionClosed { peer_id: peer, num_established: 0, endpoint, cause: None, connection_id } => {
-                            tracing::trace!(%peer, address = %endpoint.get_remote_address(), %connection_id,  "Successfully closed connection");
+                            tracing::trace!(peer_id = %peer, address = %endpoint.get_remote_address(), %connection_id,  "Successfully closed connection");
                         }
                         SwarmEvent::NewListenAddr{address, ..} => {
                             let multiaddr = format!("{address}/p2p/{}", self.swarm.local_peer_id());
@@ -473,25 +569,109 @@ where
                         _ => {}
                     }
                 },
-                Some((peer, transfer_proof, responder)) = self.outgoing_transfer_proofs_requests.recv() => {
-                    // If we are not connected to the peer, we buffer the transfer proof
-                    if !self.swarm.behaviour_mut().transfer_proof.is_connected(&peer) {
-                        tracing::warn!(%peer, "No active connection to peer, buffering transfer proof");
-                        self.buffered_transfer_proofs.entry(peer).or_default().push((transfer_proof, responder));
-                        continue;
-                    }
+                Some(request) = self.outgoing_transfer_proofs_requests.recv() => {
+                    let request = self.transfer_proof_faults.intercept(Message::TransferProof, request, |(_, _, responder)| {
+                        let _ = responder.send(Err(OutboundFailure::Timeout));
+                    });
 
-                    // If we are connected to the peer, we attempt to send the transfer proof
-                    let id = self.swarm.behaviour_mut().transfer_proof.send_request(&peer, transfer_proof);
-                    self.inflight_transfer_proofs.insert(id, responder);
+                    if let Some((peer, transfer_proof, responder)) = request {
+                        self.send_transfer_proof(peer, transfer_proof, responder);
+                    }
+                },
+                (peer, transfer_proof, responder) = self.transfer_proof_faults.next_due() => {
+                    self.send_transfer_proof(peer, transfer_proof, responder);
                 },
                 Some(response_channel) = self.inflight_encrypted_signatures.next() => {
                     let _ = self.swarm.behaviour_mut().encrypted_signature.send_response(response_channel, ());
                 }
+                Some((channel, swap_id, accepted)) = self.inflight_early_refund_requests.next() => {
+                    let response = if accepted {
+                        tracing::info!(%swap_id, "Accepted cooperative early refund request, we will not lock the Monero");
+                        cooperative_early_refund::Response::Accepted { swap_id }
+                    } else {
+                        tracing::info!(%swap_id, "Rejecting cooperative early refund request, the swap has progressed too far");
+                        cooperative_early_refund::Response::Rejected { swap_id, reason: CooperativeEarlyRefundRejectReason::SwapInvalidState }
+                    };
+
+                    if self.swarm.behaviour_mut().cooperative_early_refund.send_response(channel, response).is_err() {
+                        tracing::error!(%swap_id, "Failed to respond to cooperative early refund request");
+                    }
+                }
+                Some(request) = self.control_requests.recv() => {
+                    self.handle_control_request(request).await;
+                }
+            }
+        }
+    }
+
+    async fn handle_control_request(&mut self, request: ControlRequest) {
+        match request {
+            ControlRequest::Quote(responder) => {
+                let rate = self
+                    .latest_rate
+                    .latest_rate()
+                    .map_err(|e| anyhow!(e).context("Failed to get latest rate"));
+                let quote = self
+                    .make_quote_or_use_cached(self.min_buy, self.max_buy)
+                    .await
+                    .map_err(|e| anyhow!("{:#}", e));
+
+                let _ = responder.send(rate.and_then(|rate| Ok((*quote?, rate))));
+            }
+            ControlRequest::ConnectedPeers(responder) => {
+                let _ = responder.send(self.swarm.connected_peers().copied().collect());
+            }
+            ControlRequest::SetDenylisted(peer, denylisted, responder) => {
+                self.swarm
+                    .behaviour_mut()
+                    .peer_guard
+                    .set_denylisted(peer, denylisted);
+                let _ = responder.send(());
+            }
+            ControlRequest::RepublishOnionService(responder) => {
+                let result = match self.onion_service.as_mut() {
+                    Some(onion_service) => onion_service.republish(&mut self.swarm).await,
+                    None => Err(anyhow!("We are not listening on an onion service")),
+                };
+
+                let _ = responder.send(result);
+            }
+            ControlRequest::ResumeSwap(swap_id, responder) => {
+                let result = match self.db.get_state(swap_id).await {
+                    Ok(state) if state.swap_finished() => Ok(()),
+                    Ok(state) => self.resume_swap(swap_id, state).await,
+                    Err(error) => Err(error),
+                };
+
+                let _ = responder.send(result);
             }
         }
     }
 
+    /// Hands the swap to the receiver returned by [`EventLoop::new`], which runs it from `state` on.
+    async fn resume_swap(&mut self, swap_id: Uuid, state: State) -> Result<()> {
+        let peer_id = self
+            .db
+            .get_peer_id(swap_id)
+            .await
+            .context("No peer-id found for swap in database")?;
+
+        let swap = Swap {
+            event_loop_handle: self.new_handle(peer_id, swap_id),
+            bitcoin_wallet: self.bitcoin_wallet.clone(),
+            monero_wallet: self.monero_wallet.clone(),
+            env_config: self.env_config,
+            db: self.db.clone(),
+            state: state.try_into()?,
+            swap_id,
+        };
+
+        self.swap_sender
+            .send(swap)
+            .await
+            .map_err(|_| anyhow!("Swap receiver has been dropped"))
+    }
+
     /// Get a quote from the cache or calculate a new one by calling make_quote.
     /// Returns the result wrapped in Arcs for consistent caching.
     async fn make_quote_or_use_cached(
@@ -502,12 +682,14 @@ where
         // We use the min and max buy amounts to create a unique key for the cache
         // Although these values stay constant over the lifetime of an instance of the asb, this might change in the future
         let key = QuoteCacheKey { min_buy, max_buy };
+        let started = Instant::now();
 
         // Check if we have a cached quote
         let maybe_cached_quote = self.quote_cache.get(&key).await;
 
         if let Some(cached_quote_result) = maybe_cached_quote {
             tracing::trace!("Got a request for a quote, using cached value.");
+            METRICS.record_quote(true, cached_quote_result.is_ok(), started.elapsed());
             return cached_quote_result;
         }
 
@@ -530,14 +712,14 @@ where
         };
 
         let monero_wallet = self.monero_wallet.clone();
-        let get_unlocked_balance = || async {
-            unlocked_monero_balance_with_timeout(monero_wallet.main_wallet().await).await
-        };
+        let get_unlocked_balance =
+            || async { unlocked_monero_balance_with_timeout(&monero_wallet).await };
 
         let result = make_quote(
             min_buy,
             max_buy,
             rate,
+            &self.pricing_policy,
             get_unlocked_balance,
             get_reserved_items,
         )
@@ -552,16 +734,61 @@ where
             tracing::warn!(%err, "Failed to make quote. We will retry again later.");
         }
 
+        METRICS.record_quote(false, result.is_ok(), started.elapsed());
+
         // Return the computed quote
         result
     }
 
+    /// Computes the spread we charge on top of our base spread for a swap of `btc`.
+    async fn additional_ask_spread(
+        &self,
+        btc: bitcoin::Amount,
+        unlocked_balance: Amount,
+    ) -> Result<Decimal> {
+        let reserved_amounts: Vec<Amount> = self
+            .db
+            .all()
+            .await
+            .context("Failed to get reserved items")?
+            .into_iter()
+            .filter_map(|(_, state)| match state {
+                State::Alice(state) => Some(state.reserved_monero()),
+                _ => None,
+            })
+            .collect();
+
+        let unreserved_xmr =
+            unreserved_monero_balance(unlocked_balance, reserved_amounts.into_iter());
+
+        let adjustment = self.pricing_policy.adjustment(PricingContext {
+            unreserved_xmr,
+            btc,
+            now: OffsetDateTime::now_utc(),
+        });
+
+        Ok(adjustment.total())
+    }
+
     async fn handle_execution_setup_done(
         &mut self,
         bob_peer_id: PeerId,
         swap_id: Uuid,
         state3: State3,
+        rate: Rate,
     ) {
+        tracing::info!(
+            %swap_id,
+            market_price = %rate.market_price(),
+            effective_spread = %rate.spread(),
+            "Agreed on rate with taker"
+        );
+
+        // The rate is only used for display purposes, so we do not abort the swap if we fail to store it
+        if let Err(error) = self.db.insert_swap_rate(swap_id, rate).await {
+            tracing::warn!(%swap_id, "Unable to save rate in database: {:#}", error);
+        }
+
         let handle = self.new_handle(bob_peer_id, swap_id);
 
         let initial_state = AliceState::Started {
@@ -590,6 +817,175 @@ where
         }
     }
 
+    /// Sends a transfer proof to Bob, or buffers it until we are connected to him.
+    fn send_transfer_proof(
+        &mut self,
+        peer: PeerId,
+        transfer_proof: transfer_proof::Request,
+        responder: oneshot::Sender<Result<(), OutboundFailure>>,
+    ) {
+        // If we are not connected to the peer, we buffer the transfer proof
+        if !self
+            .swarm
+            .behaviour_mut()
+            .transfer_proof
+            .is_connected(&peer)
+        {
+            tracing::warn!(peer_id = %peer, "No active connection to peer, buffering transfer proof");
+            self.buffered_transfer_proofs
+                .entry(peer)
+                .or_default()
+                .push((transfer_proof, responder));
+            return;
+        }
+
+        // If we are connected to the peer, we attempt to send the transfer proof
+        let id = self
+            .swarm
+            .behaviour_mut()
+            .transfer_proof
+            .send_request(&peer, transfer_proof);
+        self.inflight_transfer_proofs.insert(id, responder);
+    }
+
+    /// Saves an [`EncryptedSignature`] in the database and acknowledges its receipt to Bob.
+    ///
+    /// If we fail to save the signature, we do not acknowledge it. Bob will then retry sending it.
+    async fn buffer_encrypted_signature(
+        &mut self,
+        swap_id: Uuid,
+        encrypted_signature: bitcoin::EncryptedSignature,
+        channel: ResponseChannel<()>,
+    ) {
+        if let Err(error) = self
+            .db
+            .insert_buffered_encrypted_signature(swap_id, encrypted_signature)
+            .await
+        {
+            tracing::error!(%swap_id, "Failed to buffer encrypted signature in the database: {:#}", error);
+            return;
+        }
+
+        // The acknowledgment will be sent to Bob in the next iteration of the event loop
+        self.inflight_encrypted_signatures
+            .push(async move { channel }.boxed());
+    }
+
+    async fn handle_early_refund_request(
+        &mut self,
+        swap_id: Uuid,
+        peer: PeerId,
+        channel: ResponseChannel<cooperative_early_refund::Response>,
+    ) {
+        let reject = |swarm: &mut Swarm<Behaviour<LR>>, channel, reason| {
+            tracing::warn!(%swap_id, received_from = %peer, %reason, "Rejecting cooperative early refund request");
+
+            if swarm
+                .behaviour_mut()
+                .cooperative_early_refund
+                .send_response(
+                    channel,
+                    cooperative_early_refund::Response::Rejected { swap_id, reason },
+                )
+                .is_err()
+            {
+                tracing::error!(%swap_id, "Failed to reject cooperative early refund request");
+            }
+        };
+
+        let swap_peer = self.db.get_peer_id(swap_id).await;
+        let swap_state = self.db.get_state(swap_id).await;
+
+        // If we do not find the swap in the database, or we do not have a peer-id for it, reject
+        let (swap_peer, swap_state) = match (swap_peer, swap_state) {
+            (Ok(peer), Ok(state)) => (peer, state),
+            _ => {
+                reject(
+                    &mut self.swarm,
+                    channel,
+                    CooperativeEarlyRefundRejectReason::UnknownSwap,
+                );
+                return;
+            }
+        };
+
+        // If the peer is not the one associated with the swap, reject
+        if swap_peer != peer {
+            reject(
+                &mut self.swarm,
+                channel,
+                CooperativeEarlyRefundRejectReason::MaliciousRequest,
+            );
+            return;
+        }
+
+        match swap_state {
+            // We already decided to refund early, Bob might have missed our response
+            State::Alice(
+                AliceState::BtcEarlyRefundable { .. } | AliceState::BtcEarlyRefunded(_),
+            ) => {
+                if self
+                    .swarm
+                    .behaviour_mut()
+                    .cooperative_early_refund
+                    .send_response(
+                        channel,
+                        cooperative_early_refund::Response::Accepted { swap_id },
+                    )
+                    .is_err()
+                {
+                    tracing::error!(%swap_id, "Failed to respond to cooperative early refund request");
+                }
+            }
+            // We have not locked the Monero yet, ask the swap whether it is still safe to stop.
+            // Only the swap knows whether it is about to publish the Monero lock transaction.
+            State::Alice(
+                AliceState::Started { .. }
+                | AliceState::BtcLockTransactionSeen { .. }
+                | AliceState::BtcLocked { .. },
+            ) => {
+                let Some(sender) = self.early_refund_requests.get(&swap_id) else {
+                    reject(
+                        &mut self.swarm,
+                        channel,
+                        CooperativeEarlyRefundRejectReason::SwapInvalidState,
+                    );
+                    return;
+                };
+
+                let (responder, response) = oneshot::channel();
+
+                if sender.send(responder).is_err() {
+                    // The swap is not running anymore
+                    self.early_refund_requests.remove(&swap_id);
+                    reject(
+                        &mut self.swarm,
+                        channel,
+                        CooperativeEarlyRefundRejectReason::SwapInvalidState,
+                    );
+                    return;
+                }
+
+                self.inflight_early_refund_requests.push(
+                    async move {
+                        let accepted = matches!(
+                            timeout(EARLY_REFUND_REQUEST_TIMEOUT, response).await,
+                            Ok(Ok(()))
+                        );
+
+                        (channel, swap_id, accepted)
+                    }
+                    .boxed(),
+                );
+            }
+            _ => reject(
+                &mut self.swarm,
+                channel,
+                CooperativeEarlyRefundRejectReason::SwapInvalidState,
+            ),
+        }
+    }
+
     /// Create a new [`EventLoopHandle`] that is scoped for communication with
     /// the given peer.
     fn new_handle(&mut self, peer: PeerId, swap_id: Uuid) -> EventLoopHandle {
@@ -605,11 +1001,17 @@ where
 
         let transfer_proof_sender = self.outgoing_transfer_proofs_sender.clone();
 
+        // Early refund requests from Bob are forwarded to the swap through this channel
+        let (early_refund_sender, early_refund_receiver) = mpsc::unbounded_channel();
+        self.early_refund_requests
+            .insert(swap_id, early_refund_sender);
+
         EventLoopHandle {
             swap_id,
             peer,
             recv_encrypted_signature: Some(encrypted_signature_receiver),
             transfer_proof_sender: Some(transfer_proof_sender),
+            early_refund_requests: Mutex::new(early_refund_receiver),
         }
     }
 }
@@ -676,6 +1078,73 @@ impl LatestRate for KrakenRate {
     }
 }
 
+#[derive(Debug)]
+enum ControlRequest {
+    Quote(oneshot::Sender<Result<(BidQuote, Rate)>>),
+    ConnectedPeers(oneshot::Sender<Vec<PeerId>>),
+    SetDenylisted(PeerId, bool, oneshot::Sender<()>),
+    RepublishOnionService(oneshot::Sender<Result<Multiaddr>>),
+    ResumeSwap(Uuid, oneshot::Sender<Result<()>>),
+}
+
+/// Allows querying the [`EventLoop`] while it is running, e.g. from the control-plane server.
+#[derive(Debug, Clone)]
+pub struct EventLoopService {
+    sender: mpsc::UnboundedSender<ControlRequest>,
+}
+
+impl EventLoopService {
+    /// The quote we currently hand out to takers, together with the market rate it is based on.
+    pub async fn quote(&self) -> Result<(BidQuote, Rate)> {
+        let (responder, response) = oneshot::channel();
+        self.sender
+            .send(ControlRequest::Quote(responder))
+            .map_err(|_| anyhow!("Event loop is not running"))?;
+
+        response.await.context("Event loop dropped the request")?
+    }
+
+    /// The peers we currently have at least one open connection to.
+    pub async fn connected_peers(&self) -> Result<Vec<PeerId>> {
+        let (responder, response) = oneshot::channel();
+        self.sender
+            .send(ControlRequest::ConnectedPeers(responder))
+            .map_err(|_| anyhow!("Event loop is not running"))?;
+
+        response.await.context("Event loop dropped the request")
+    }
+
+    /// Adds the peer to the denylist or removes it, until the asb is restarted.
+    pub async fn set_denylisted(&self, peer: PeerId, denylisted: bool) -> Result<()> {
+        let (responder, response) = oneshot::channel();
+        self.sender
+            .send(ControlRequest::SetDenylisted(peer, denylisted, responder))
+            .map_err(|_| anyhow!("Event loop is not running"))?;
+
+        response.await.context("Event loop dropped the request")
+    }
+
+    /// Shuts our onion service down and publishes it again. Returns its onion address.
+    pub async fn republish_onion_service(&self) -> Result<Multiaddr> {
+        let (responder, response) = oneshot::channel();
+        self.sender
+            .send(ControlRequest::RepublishOnionService(responder))
+            .map_err(|_| anyhow!("Event loop is not running"))?;
+
+        response.await.context("Event loop dropped the request")?
+    }
+
+    /// Runs the swap again from the state stored in the database, unless it is finished.
+    pub async fn resume_swap(&self, swap_id: Uuid) -> Result<()> {
+        let (responder, response) = oneshot::channel();
+        self.sender
+            .send(ControlRequest::ResumeSwap(swap_id, responder))
+            .map_err(|_| anyhow!("Event loop is not running"))?;
+
+        response.await.context("Event loop dropped the request")?
+    }
+}
+
 #[derive(Debug)]
 pub struct EventLoopHandle {
     swap_id: Uuid,
@@ -689,9 +1158,16 @@ pub struct EventLoopHandle {
             oneshot::Sender<Result<(), OutboundFailure>>,
         )>,
     >,
+    /// Requests from Bob to refund his Bitcoin early, see [`EventLoop::early_refund_requests`]
+    early_refund_requests: Mutex<mpsc::UnboundedReceiver<oneshot::Sender<()>>>,
 }
 
 impl EventLoopHandle {
+    /// The peer we run the swap with.
+    pub fn peer_id(&self) -> PeerId {
+        self.peer
+    }
+
     fn build_transfer_proof_request(
         &self,
         transfer_proof: monero::TransferProof,
@@ -771,6 +1247,7 @@ impl EventLoopHandle {
                 }
             },
             |e, wait_time: Duration| {
+                METRICS.record_transfer_proof_retry();
                 tracing::warn!(
                     swap_id = %self.swap_id,
                     error = ?e,
@@ -785,6 +1262,44 @@ impl EventLoopHandle {
 
         Ok(())
     }
+
+    /// Wait until Bob asks us to refund his Bitcoin early and accept the request.
+    ///
+    /// Never returns if the event loop is gone.
+    pub async fn accept_early_refund_request(&mut self) {
+        let receiver = self
+            .early_refund_requests
+            .get_mut()
+            .expect("early refund request lock not to be poisoned");
+
+        while let Some(responder) = receiver.recv().await {
+            // The event loop might have given up on the request already
+            if responder.send(()).is_ok() {
+                return;
+            }
+        }
+
+        future::pending().await
+    }
+
+    /// Accept an early refund request if Bob has sent one, without waiting for one.
+    ///
+    /// Returns whether we accepted a request. Once this returns true we must not
+    /// lock the Monero anymore.
+    pub fn try_accept_early_refund_request(&self) -> bool {
+        let mut receiver = self
+            .early_refund_requests
+            .lock()
+            .expect("early refund request lock not to be poisoned");
+
+        while let Ok(responder) = receiver.try_recv() {
+            if responder.send(()).is_ok() {
+                return true;
+            }
+        }
+
+        false
+    }
 }
 
 /// Computes a quote given the provided dependencies
@@ -792,6 +1307,7 @@ pub async fn make_quote<LR, F, Fut, I, Fut2, T>(
     min_buy: bitcoin::Amount,
     max_buy: bitcoin::Amount,
     mut latest_rate: LR,
+    pricing_policy: &PricingPolicy,
     get_unlocked_balance: F,
     get_reserved_items: I,
 ) -> Result<Arc<BidQuote>, Arc<anyhow::Error>>
@@ -803,11 +1319,9 @@ where
     Fut2: futures::Future<Output = Result<Vec<T>, anyhow::Error>>,
     T: ReservesMonero,
 {
-    let ask_price = latest_rate
+    let rate = latest_rate
         .latest_rate()
-        .map_err(|e| Arc::new(anyhow!(e).context("Failed to get latest rate")))?
-        .ask()
-        .map_err(|e| Arc::new(e.context("Failed to compute asking price")))?;
+        .map_err(|e| Arc::new(anyhow!(e).context("Failed to get latest rate")))?;
 
     // Get the unlocked balance
     let unlocked_balance = get_unlocked_balance()
@@ -827,6 +1341,20 @@ where
     let unreserved_xmr_balance =
         unreserved_monero_balance(unlocked_balance, reserved_amounts.into_iter());
 
+    // We quote the price for the largest swap we are willing to do.
+    // Smaller swaps never end up with a larger spread than the one we quote.
+    let rate = pricing_policy.apply(
+        rate,
+        PricingContext {
+            unreserved_xmr: unreserved_xmr_balance,
+            btc: max_buy,
+            now: OffsetDateTime::now_utc(),
+        },
+    );
+    let ask_price = rate
+        .ask()
+        .map_err(|e| Arc::new(e.context("Failed to compute asking price")))?;
+
     let max_bitcoin_for_monero = unreserved_xmr_balance
         .max_bitcoin_for_price(ask_price)
         .ok_or_else(|| {
@@ -837,7 +1365,7 @@ where
             ))
         })?;
 
-    tracing::trace!(%ask_price, %unreserved_xmr_balance, %max_bitcoin_for_monero, "Computed quote");
+    tracing::trace!(%ask_price, effective_spread = %rate.spread(), %unreserved_xmr_balance, %max_bitcoin_for_monero, "Computed quote");
 
     if min_buy > max_bitcoin_for_monero {
         tracing::trace!(
@@ -889,7 +1417,7 @@ pub fn unreserved_monero_balance(
 
 /// Returns the unlocked Monero balance from the wallet
 async fn unlocked_monero_balance_with_timeout(
-    wallet: Arc<monero::Wallet>,
+    wallet: &monero::Wallets,
 ) -> Result<Amount, anyhow::Error> {
     /// This is how long we maximally wait for the wallet operation
     const MONERO_WALLET_OPERATION_TIMEOUT: Duration = Duration::from_secs(10);
@@ -1012,6 +1540,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Ok(balance) },
             || async { Ok(reserved_items) },
         )
@@ -1042,6 +1571,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Ok(balance) },
             || async { Ok(reserved_items) },
         )
@@ -1067,6 +1597,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Ok(balance) },
             || async { Ok(reserved_items) },
         )
@@ -1090,6 +1621,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Ok(balance) },
             || async { Ok(reserved_items) },
         )
@@ -1118,6 +1650,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Ok(balance) },
             || async { Ok(reserved_items) },
         )
@@ -1140,6 +1673,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Err(anyhow::anyhow!("Failed to get balance")) },
             || async { Ok(reserved_items) },
         )
@@ -1164,6 +1698,7 @@ mod tests {
             min_buy,
             max_buy,
             rate.clone(),
+            &PricingPolicy::default(),
             || async { Ok(balance) },
             || async { Ok(reserved_items) },
         )
@@ -1176,6 +1711,44 @@ mod tests {
         assert_eq!(result.max_quantity, max_buy);
     }
 
+    #[tokio::test]
+    async fn test_make_quote_applies_pricing_policy() {
+        let min_buy = bitcoin::Amount::from_sat(100_000);
+        let max_buy = bitcoin::Amount::from_sat(500_000);
+        let rate = FixedRate::default();
+        let balance = Amount::from_monero(1.0).unwrap();
+        let reserved_items: Vec<MockReservedItem> = vec![];
+        let additional_spread = Decimal::new(5, 2);
+        let pricing_policy = PricingPolicy::new(Some(crate::asb::config::Pricing {
+            inventory: vec![crate::asb::config::InventoryTier {
+                below_xmr: Decimal::from(10),
+                additional_spread,
+            }],
+            ..Default::default()
+        }));
+
+        let result = make_quote(
+            min_buy,
+            max_buy,
+            rate.clone(),
+            &pricing_policy,
+            || async { Ok(balance) },
+            || async { Ok(reserved_items) },
+        )
+        .await
+        .unwrap();
+
+        // Our balance is below the inventory threshold, so we should charge the additional spread
+        assert_eq!(
+            result.price,
+            rate.value()
+                .with_additional_spread(additional_spread)
+                .ask()
+                .unwrap()
+        );
+        assert!(result.price > rate.value().ask().unwrap());
+    }
+
     // Mock struct for testing
     #[derive(Debug, Clone)]
     struct MockReservedItem {
@@ -1,4 +1,6 @@
+use crate::asb::config::PeerLimits;
 use crate::asb::event_loop::LatestRate;
+use crate::asb::Rate;
 use crate::env;
 use crate::network::quote::BidQuote;
 use crate::network::rendezvous::XmrBtcNamespace;
@@ -6,7 +8,8 @@ use crate::network::swap_setup::alice;
 use crate::network::swap_setup::alice::WalletSnapshot;
 use crate::network::transport::authenticate_and_multiplex;
 use crate::network::{
-    cooperative_xmr_redeem_after_punish, encrypted_signature, quote, transfer_proof,
+    cooperative_early_refund, cooperative_xmr_redeem_after_punish, encrypted_signature, quote,
+    transfer_proof,
 };
 use crate::protocol::alice::State3;
 use anyhow::{anyhow, Error, Result};
@@ -22,81 +25,413 @@ use std::time::Duration;
 use uuid::Uuid;
 
 pub mod transport {
-    use std::sync::Arc;
+    use std::pin::Pin;
+    use std::sync::{Arc, Mutex, MutexGuard};
+    use std::task::Context;
 
     use arti_client::{config::onion_service::OnionServiceConfigBuilder, TorClient};
+    use libp2p::core::transport::{ListenerId, TransportError, TransportEvent};
     use libp2p::{core::transport::OptionalTransport, dns, identity, tcp, Transport};
-    use libp2p_community_tor::AddressConversion;
+    use libp2p_community_tor::{AddressConversion, TorTransport};
+    use tor_hscrypto::pk::HsIdKeypair;
     use tor_rtcompat::tokio::TokioRustlsRuntime;
 
+    use super::onion::OnionService;
     use super::*;
 
     static ASB_ONION_SERVICE_NICKNAME: &str = "asb";
-    static ASB_ONION_SERVICE_PORT: u16 = 9939;
+    /// The nickname of the onion service whose identity is derived from the seed.
+    ///
+    /// It differs from [`ASB_ONION_SERVICE_NICKNAME`] such that a key which arti generated for
+    /// the latter is never mistaken for the seed-derived one.
+    static ASB_SEED_ONION_SERVICE_NICKNAME: &str = "asb-seed";
 
-    type OnionTransportWithAddresses = (Boxed<(PeerId, StreamMuxerBox)>, Vec<Multiaddr>);
+    type TransportWithOnionService = (Boxed<(PeerId, StreamMuxerBox)>, Option<OnionService>);
 
     /// Creates the libp2p transport for the ASB.
     ///
     /// If you pass in a `None` for `maybe_tor_client`, the ASB will not use Tor at all.
     ///
-    /// If you pass in a `Some(tor_client)`, the ASB will listen on an onion service and return
-    /// the onion address. If it fails to listen on the onion address, it will only use tor for
-    /// dialing and not listening.
+    /// If you pass in a `Some(tor_client)` and `register_hidden_service` is set, an
+    /// [`OnionService`] is returned which has to be launched for the ASB to be reachable over Tor.
+    ///
+    /// If you pass in an `onion_only_identity`, the ASB dials through Tor only and its onion
+    /// service uses that identity instead of one generated by arti.
     pub fn new(
         identity: &identity::Keypair,
         maybe_tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
         register_hidden_service: bool,
         num_intro_points: u8,
-    ) -> Result<OnionTransportWithAddresses> {
-        let (maybe_tor_transport, onion_addresses) = if let Some(tor_client) = maybe_tor_client {
-            let mut tor_transport = libp2p_community_tor::TorTransport::from_client(
+        onion_only_identity: Option<HsIdKeypair>,
+    ) -> Result<TransportWithOnionService> {
+        let onion_only = onion_only_identity.is_some();
+
+        let Some(tor_client) = maybe_tor_client else {
+            if onion_only {
+                return Err(anyhow!("Cannot run onion-only without a Tor client"));
+            }
+
+            let tcp = tcp::tokio::Transport::new(tcp::Config::new().nodelay(true));
+            let tcp_with_dns = dns::tokio::Transport::system(tcp)?;
+
+            return Ok((
+                authenticate_and_multiplex(tcp_with_dns.boxed(), identity)?,
+                None,
+            ));
+        };
+
+        // Without a clearnet transport we have to resolve and dial IP addresses through Tor too
+        let conversion = if onion_only {
+            AddressConversion::IpAndDns
+        } else {
+            AddressConversion::DnsOnly
+        };
+        let tor_transport =
+            SharedTorTransport::new(TorTransport::from_client(tor_client.clone(), conversion));
+
+        let onion_service = register_hidden_service.then(|| {
+            let nickname = if onion_only {
+                ASB_SEED_ONION_SERVICE_NICKNAME
+            } else {
+                ASB_ONION_SERVICE_NICKNAME
+            };
+
+            let config = OnionServiceConfigBuilder::default()
+                .nickname(nickname.parse().expect("Static nickname to be valid"))
+                .num_intro_points(num_intro_points)
+                .build()
+                .expect("We specified a valid nickname");
+
+            OnionService::new(
+                tor_transport.clone(),
                 tor_client,
-                AddressConversion::DnsOnly,
-            );
+                config,
+                onion_only_identity,
+            )
+        });
 
-            let addresses = if register_hidden_service {
-                let onion_service_config = OnionServiceConfigBuilder::default()
-                    .nickname(
-                        ASB_ONION_SERVICE_NICKNAME
-                            .parse()
-                            .expect("Static nickname to be valid"),
-                    )
-                    .num_intro_points(num_intro_points)
-                    .build()
-                    .expect("We specified a valid nickname");
+        let transport = if onion_only {
+            authenticate_and_multiplex(tor_transport.boxed(), identity)?
+        } else {
+            let tcp = OptionalTransport::some(tor_transport)
+                .or_transport(tcp::tokio::Transport::new(tcp::Config::new().nodelay(true)));
+            let tcp_with_dns = dns::tokio::Transport::system(tcp)?;
+
+            authenticate_and_multiplex(tcp_with_dns.boxed(), identity)?
+        };
+
+        Ok((transport, onion_service))
+    }
+
+    /// A [`TorTransport`] which can still be reached after it was handed to the swarm.
+    ///
+    /// We need this to add onion services to the transport while the swarm is running.
+    #[derive(Clone)]
+    pub struct SharedTorTransport(Arc<Mutex<TorTransport>>);
+
+    impl SharedTorTransport {
+        fn new(transport: TorTransport) -> Self {
+            Self(Arc::new(Mutex::new(transport)))
+        }
+
+        pub fn lock(&self) -> MutexGuard<'_, TorTransport> {
+            self.0
+                .lock()
+                .expect("Tor transport mutex not to be poisoned")
+        }
+    }
+
+    impl Transport for SharedTorTransport {
+        type Output = <TorTransport as Transport>::Output;
+        type Error = <TorTransport as Transport>::Error;
+        type ListenerUpgrade = <TorTransport as Transport>::ListenerUpgrade;
+        type Dial = <TorTransport as Transport>::Dial;
+
+        fn listen_on(
+            &mut self,
+            id: ListenerId,
+            addr: Multiaddr,
+        ) -> Result<(), TransportError<Self::Error>> {
+            self.lock().listen_on(id, addr)
+        }
+
+        fn remove_listener(&mut self, id: ListenerId) -> bool {
+            self.lock().remove_listener(id)
+        }
+
+        fn dial(&mut self, addr: Multiaddr) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.lock().dial(addr)
+        }
+
+        fn dial_as_listener(
+            &mut self,
+            addr: Multiaddr,
+        ) -> Result<Self::Dial, TransportError<Self::Error>> {
+            self.lock().dial_as_listener(addr)
+        }
+
+        fn address_translation(
+            &self,
+            listen: &Multiaddr,
+            observed: &Multiaddr,
+        ) -> Option<Multiaddr> {
+            self.lock().address_translation(listen, observed)
+        }
+
+        fn poll(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<TransportEvent<Self::ListenerUpgrade, Self::Error>> {
+            Pin::new(&mut *self.lock()).poll(cx)
+        }
+    }
+}
+
+/// The onion service the asb is reachable at.
+///
+/// arti persists the identity key of an onion service in its keystore and never rotates it, so
+/// the onion address only changes if the keystore is lost. The keys which sign the descriptors
+/// are derived from it and rotated by arti on its own. In onion-only mode the identity is derived
+/// from the seed instead, such that the onion address survives losing the Tor state as well.
+pub mod onion {
+    use super::transport::SharedTorTransport;
+    use super::*;
+    use crate::asb::EventLoopService;
+    use anyhow::{bail, Context};
+    use arti_client::config::onion_service::OnionServiceConfig;
+    use arti_client::TorClient;
+    use libp2p::core::transport::ListenerId;
+    use libp2p::multiaddr::Protocol;
+    use libp2p::swarm::NetworkBehaviour;
+    use libp2p::Swarm;
+    use std::sync::Arc;
+    use tor_hscrypto::pk::{HsId, HsIdKey, HsIdKeypair};
+    use tor_rtcompat::tokio::TokioRustlsRuntime;
+
+    const ONION_SERVICE_PORT: u16 = 9939;
+
+    /// How often we try to add the onion service to the transport. Right after an onion service
+    /// was shut down, arti may not have released its state yet.
+    const LAUNCH_ATTEMPTS: u32 = 5;
+    const LAUNCH_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+    /// How long we wait for a connection to our own onion service.
+    const PROBE_TIMEOUT: Duration = Duration::from_secs(120);
+    /// After this many failed probes in a row, we consider the descriptor stale.
+    const MAX_FAILED_PROBES: u32 = 3;
+
+    #[allow(missing_debug_implementations)]
+    pub struct OnionService {
+        transport: SharedTorTransport,
+        tor_client: Arc<TorClient<TokioRustlsRuntime>>,
+        config: OnionServiceConfig,
+        /// The identity derived from the seed, `None` if arti generates one.
+        identity: Option<HsIdKeypair>,
+        listener: Option<ListenerId>,
+    }
+
+    impl OnionService {
+        pub(super) fn new(
+            transport: SharedTorTransport,
+            tor_client: Arc<TorClient<TokioRustlsRuntime>>,
+            config: OnionServiceConfig,
+            identity: Option<HsIdKeypair>,
+        ) -> Self {
+            Self {
+                transport,
+                tor_client,
+                config,
+                identity,
+                listener: None,
+            }
+        }
+
+        pub fn tor_client(&self) -> Arc<TorClient<TokioRustlsRuntime>> {
+            self.tor_client.clone()
+        }
+
+        /// Publishes the onion service and listens on it. Returns the onion address.
+        pub async fn launch<B>(&mut self, swarm: &mut Swarm<B>) -> Result<Multiaddr>
+        where
+            B: NetworkBehaviour,
+        {
+            let expected_address = self.identity.as_ref().map(onion_address);
 
-                match tor_transport.add_onion_service(onion_service_config, ASB_ONION_SERVICE_PORT)
+            if let Some(identity) = self.identity.take() {
+                // arti only uses an identity we provide if it does not have a key for the
+                // service yet. Launching the service once stores the identity in its keystore.
+                match self
+                    .tor_client
+                    .launch_onion_service_with_hsid(self.config.clone(), identity)
                 {
-                    Ok(addr) => {
-                        tracing::debug!(
-                            %addr,
-                            "Setting up onion service for libp2p to listen on"
-                        );
-                        vec![addr]
+                    Ok(service) => drop(service),
+                    Err(error) => {
+                        tracing::debug!(%error, "Onion service identity is already in the keystore")
                     }
-                    Err(err) => {
-                        tracing::warn!(error=%err, "Failed to listen on onion address");
-                        vec![]
+                }
+            }
+
+            let address = self.add_to_transport().await?;
+
+            if let Some(expected_address) = expected_address {
+                if address != expected_address {
+                    bail!("The Tor keystore holds an onion service key for {} which was not derived from the seed, expected {}. Remove the key from the keystore to use the seed-derived one.", address, expected_address);
+                }
+            }
+
+            self.listen_on(swarm, address)
+        }
+
+        /// Shuts the onion service down and publishes it again, e.g. because its descriptor went
+        /// stale. The onion address stays the same.
+        pub async fn republish<B>(&mut self, swarm: &mut Swarm<B>) -> Result<Multiaddr>
+        where
+            B: NetworkBehaviour,
+        {
+            if let Some(listener) = self.listener.take() {
+                swarm.remove_listener(listener);
+            }
+
+            let address = self.add_to_transport().await?;
+
+            self.listen_on(swarm, address)
+        }
+
+        fn listen_on<B>(&mut self, swarm: &mut Swarm<B>, address: Multiaddr) -> Result<Multiaddr>
+        where
+            B: NetworkBehaviour,
+        {
+            self.listener = Some(
+                swarm
+                    .listen_on(address.clone())
+                    .with_context(|| format!("Failed to listen on onion address {}", address))?,
+            );
+
+            Ok(address)
+        }
+
+        async fn add_to_transport(&mut self) -> Result<Multiaddr> {
+            let mut attempt = 1;
+
+            loop {
+                let result = self
+                    .transport
+                    .lock()
+                    .add_onion_service(self.config.clone(), ONION_SERVICE_PORT);
+
+                match result {
+                    Ok(address) => return Ok(address),
+                    Err(error) if attempt < LAUNCH_ATTEMPTS => {
+                        tracing::debug!(%error, attempt, "Failed to launch onion service, retrying");
+                        attempt += 1;
+                        tokio::time::sleep(LAUNCH_RETRY_DELAY).await;
+                    }
+                    Err(error) => {
+                        return Err(anyhow!(error).context("Failed to launch onion service"))
                     }
                 }
-            } else {
-                vec![]
-            };
+            }
+        }
+    }
 
-            (OptionalTransport::some(tor_transport), addresses)
-        } else {
-            (OptionalTransport::none(), vec![])
+    /// Regularly connects to our own onion service through Tor and has the event loop re-publish
+    /// it if that fails repeatedly, which happens if its descriptor went stale.
+    pub async fn health_check(
+        tor_client: Arc<TorClient<TokioRustlsRuntime>>,
+        address: Multiaddr,
+        interval: Duration,
+        event_loop_service: EventLoopService,
+    ) {
+        let Some(host) = onion_host(&address) else {
+            tracing::warn!(%address, "Not an onion address, not checking the onion service");
+            return;
         };
 
-        let tcp = maybe_tor_transport
-            .or_transport(tcp::tokio::Transport::new(tcp::Config::new().nodelay(true)));
-        let tcp_with_dns = dns::tokio::Transport::system(tcp)?;
+        let mut failed_probes = 0;
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let probe = tokio::time::timeout(
+                PROBE_TIMEOUT,
+                tor_client.connect((host.as_str(), ONION_SERVICE_PORT)),
+            )
+            .await;
+
+            match probe {
+                Ok(Ok(_)) => {
+                    tracing::trace!(%address, "Onion service is reachable");
+                    failed_probes = 0;
+                    continue;
+                }
+                Ok(Err(error)) => {
+                    tracing::debug!(%address, %error, "Failed to connect to our onion service")
+                }
+                Err(_) => tracing::debug!(%address, "Timed out connecting to our onion service"),
+            }
+
+            failed_probes += 1;
+
+            if failed_probes < MAX_FAILED_PROBES {
+                continue;
+            }
+
+            tracing::warn!(%address, failed_probes, "Onion service is unreachable, re-publishing it");
+            failed_probes = 0;
+
+            if let Err(error) = event_loop_service.republish_onion_service().await {
+                tracing::error!("Failed to re-publish onion service: {:#}", error);
+            }
+        }
+    }
+
+    /// The onion address of the service with the given identity.
+    fn onion_address(identity: &HsIdKeypair) -> Multiaddr {
+        let hs_id = HsId::from(HsIdKey::from(identity));
+        let onion = hs_id.to_string();
+        let onion = onion.trim_end_matches(".onion");
 
-        Ok((
-            authenticate_and_multiplex(tcp_with_dns.boxed(), identity)?,
-            onion_addresses,
-        ))
+        format!("/onion3/{}:{}", onion, ONION_SERVICE_PORT)
+            .parse()
+            .expect("HsId to be a valid onion3 address")
+    }
+
+    /// The host name of an `/onion3/` address, e.g. `<base32>.onion`.
+    fn onion_host(address: &Multiaddr) -> Option<String> {
+        address.iter().find_map(|protocol| match protocol {
+            Protocol::Onion3(onion) => {
+                let host = data_encoding::BASE32.encode(onion.hash()).to_lowercase();
+                Some(format!("{}.onion", host))
+            }
+            _ => None,
+        })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::seed::Seed;
+
+        #[test]
+        fn onion_address_of_the_seed_identity_has_a_host_we_can_connect_to() {
+            let seed = Seed::from(*b"this string is exactly 32 bytes!");
+            let address = onion_address(&seed.derive_onion_service_identity());
+
+            let host = onion_host(&address).unwrap();
+
+            assert!(host.ends_with(".onion"));
+            assert_eq!(host.len(), 56 + ".onion".len());
+            assert!(address
+                .to_string()
+                .contains(host.trim_end_matches(".onion")));
+        }
+
+        #[test]
+        fn clearnet_addresses_have_no_onion_host() {
+            let address: Multiaddr = "/ip4/127.0.0.1/tcp/9939".parse().unwrap();
+
+            assert_eq!(onion_host(&address), None);
+        }
     }
 }
 
@@ -113,17 +448,24 @@ pub mod behaviour {
     #[derive(Debug)]
     pub enum OutEvent {
         SwapSetupInitiated {
+            peer_id: PeerId,
             send_wallet_snapshot: bmrng::RequestReceiver<bitcoin::Amount, WalletSnapshot>,
         },
         SwapSetupCompleted {
             peer_id: PeerId,
             swap_id: Uuid,
             state3: State3,
+            rate: Rate,
         },
         SwapDeclined {
             peer: PeerId,
             error: alice::Error,
         },
+        /// The swap setup failed for a reason other than us declining it
+        SwapSetupFailed {
+            peer: PeerId,
+            error: Error,
+        },
         QuoteRequested {
             channel: ResponseChannel<BidQuote>,
             peer: PeerId,
@@ -142,6 +484,11 @@ pub mod behaviour {
             swap_id: Uuid,
             peer: PeerId,
         },
+        CooperativeEarlyRefundRequested {
+            channel: ResponseChannel<cooperative_early_refund::Response>,
+            swap_id: Uuid,
+            peer: PeerId,
+        },
         Rendezvous(libp2p::rendezvous::client::Event),
         OutboundRequestResponseFailure {
             peer: PeerId,
@@ -192,8 +539,10 @@ pub mod behaviour {
         pub swap_setup: alice::Behaviour<LR>,
         pub transfer_proof: transfer_proof::Behaviour,
         pub cooperative_xmr_redeem: cooperative_xmr_redeem_after_punish::Behaviour,
+        pub cooperative_early_refund: cooperative_early_refund::Behaviour,
         pub encrypted_signature: encrypted_signature::Behaviour,
         pub identify: identify::Behaviour,
+        pub peer_guard: peer_guard::Behaviour,
 
         /// Ping behaviour that ensures that the underlying network connection
         /// is still alive. If the ping fails a connection close event
@@ -205,6 +554,7 @@ pub mod behaviour {
     where
         LR: LatestRate + Send + 'static,
     {
+        #[allow(clippy::too_many_arguments)]
         pub fn new(
             min_buy: bitcoin::Amount,
             max_buy: bitcoin::Amount,
@@ -213,6 +563,7 @@ pub mod behaviour {
             env_config: env::Config,
             identify_params: (identity::Keypair, XmrBtcNamespace),
             rendezvous_nodes: Vec<RendezvousNode>,
+            peer_limits: &PeerLimits,
         ) -> Self {
             let (identity, namespace) = identify_params;
             let agent_version = format!("asb/{} ({})", env!("CARGO_PKG_VERSION"), namespace);
@@ -242,8 +593,10 @@ pub mod behaviour {
                 transfer_proof: transfer_proof::alice(),
                 encrypted_signature: encrypted_signature::alice(),
                 cooperative_xmr_redeem: cooperative_xmr_redeem_after_punish::alice(),
+                cooperative_early_refund: cooperative_early_refund::alice(),
                 ping: ping::Behaviour::new(pingConfig),
                 identify: identify::Behaviour::new(identifyConfig),
+                peer_guard: peer_guard::Behaviour::new(peer_limits),
             }
         }
     }
@@ -260,6 +613,12 @@ pub mod behaviour {
         }
     }
 
+    impl From<()> for OutEvent {
+        fn from(_: ()) -> Self {
+            OutEvent::Other
+        }
+    }
+
     impl From<libp2p::rendezvous::client::Event> for OutEvent {
         fn from(event: libp2p::rendezvous::client::Event) -> Self {
             OutEvent::Rendezvous(event)
@@ -269,6 +628,7 @@ pub mod behaviour {
 
 pub mod rendezvous {
     use super::*;
+    use crate::common::metrics::METRICS;
     use libp2p::identity;
     use libp2p::rendezvous::client::RegisterError;
     use libp2p::swarm::dial_opts::DialOpts;
@@ -318,6 +678,8 @@ pub mod rendezvous {
             namespace: XmrBtcNamespace,
             registration_ttl: Option<u64>,
         ) -> Self {
+            METRICS.set_rendezvous_registered(peer_id, false);
+
             Self {
                 address: address.to_owned(),
                 connection_status: ConnectionStatus::Disconnected,
@@ -333,6 +695,10 @@ pub mod rendezvous {
         }
 
         fn set_registration(&mut self, status: RegistrationStatus) {
+            METRICS.set_rendezvous_registered(
+                self.peer_id,
+                matches!(status, RegistrationStatus::Registered { .. }),
+            );
             self.registration_status = status;
         }
     }
@@ -739,3 +1105,353 @@ pub mod rendezvous {
         }
     }
 }
+
+/// Protects the asb against peers which flood it with quote requests or swap setups.
+///
+/// Every peer may only request a limited number of quotes and start a limited number of swap
+/// setups within a time window. Peers which abandon too many swap setups after we captured a
+/// wallet snapshot for them are banned for a while. Banned peers are neither quoted nor allowed to
+/// set up swaps, but stay connected such that swaps they already started can finish.
+///
+/// Peers on the operator's denylist cannot connect to us at all.
+pub mod peer_guard {
+    use super::*;
+    use libp2p::swarm::{
+        dummy, CloseConnection, ConnectionDenied, ConnectionId, FromSwarm, THandler,
+        THandlerInEvent, THandlerOutEvent, ToSwarm,
+    };
+    use std::collections::{HashMap, HashSet, VecDeque};
+    use std::task::Context;
+    use std::time::Instant;
+
+    #[derive(thiserror::Error, Debug, Clone, Copy)]
+    #[error("Peer {0} is on the denylist")]
+    pub struct Denylisted(PeerId);
+
+    /// How many events we allow per peer within a fixed window of time.
+    struct RateLimit {
+        max: u32,
+        window: Duration,
+        /// When the current window of each peer started and how many events it saw so far
+        windows: HashMap<PeerId, (Instant, u32)>,
+    }
+
+    impl RateLimit {
+        fn new(max: u32, window: Duration) -> Self {
+            Self {
+                max,
+                window,
+                windows: HashMap::new(),
+            }
+        }
+
+        /// Counts the event and returns whether the peer stayed within the limit.
+        fn allow(&mut self, peer: PeerId, now: Instant) -> bool {
+            let (started, count) = self.windows.entry(peer).or_insert((now, 0));
+
+            if now.duration_since(*started) >= self.window {
+                *started = now;
+                *count = 0;
+            }
+
+            if *count >= self.max {
+                return false;
+            }
+
+            *count += 1;
+            true
+        }
+
+        fn forget(&mut self, peer: &PeerId) {
+            self.windows.remove(peer);
+        }
+
+        /// Drops the windows which are over, they would be reset on the next event anyway.
+        fn prune(&mut self, now: Instant) {
+            let window = self.window;
+            self.windows
+                .retain(|_, (started, _)| now.duration_since(*started) < window);
+        }
+    }
+
+    pub struct Behaviour {
+        quotes: RateLimit,
+        swap_setups: RateLimit,
+        abandoned_setups: RateLimit,
+        ban_duration: Duration,
+        denylist: HashSet<PeerId>,
+        banned_until: HashMap<PeerId, Instant>,
+        /// Swap setups for which we handed out a wallet snapshot, by peer
+        pending_setups: HashMap<PeerId, u32>,
+        to_disconnect: VecDeque<PeerId>,
+    }
+
+    impl Behaviour {
+        pub fn new(limits: &PeerLimits) -> Self {
+            let ban_duration = Duration::from_secs(limits.auto_ban_secs);
+
+            Self {
+                quotes: RateLimit::new(limits.quotes_per_minute, Duration::from_secs(60)),
+                swap_setups: RateLimit::new(limits.swap_setups_per_hour, Duration::from_secs(3600)),
+                abandoned_setups: RateLimit::new(limits.max_abandoned_setups, ban_duration),
+                ban_duration,
+                denylist: limits.denylist.iter().copied().collect(),
+                banned_until: HashMap::new(),
+                pending_setups: HashMap::new(),
+                to_disconnect: VecDeque::new(),
+            }
+        }
+
+        /// Whether we should answer a quote request of the peer.
+        pub fn allow_quote(&mut self, peer: PeerId) -> bool {
+            self.allow_quote_at(peer, Instant::now())
+        }
+
+        /// Whether we should capture a wallet snapshot for a swap setup of the peer.
+        pub fn allow_swap_setup(&mut self, peer: PeerId) -> bool {
+            self.allow_swap_setup_at(peer, Instant::now())
+        }
+
+        /// We handed out a wallet snapshot for a swap setup of the peer.
+        pub fn swap_setup_started(&mut self, peer: PeerId) {
+            *self.pending_setups.entry(peer).or_default() += 1;
+        }
+
+        /// A swap setup of the peer completed or we declined it.
+        pub fn swap_setup_finished(&mut self, peer: PeerId) {
+            self.take_pending_setup(peer);
+        }
+
+        /// A swap setup of the peer failed for a reason other than us declining it.
+        ///
+        /// Only setups we already captured a wallet snapshot for count as abandoned. So do such
+        /// setups which are still running when the peer disconnects.
+        pub fn swap_setup_failed(&mut self, peer: PeerId) {
+            self.swap_setup_failed_at(peer, Instant::now())
+        }
+
+        /// Adds the peer to the denylist and disconnects it, or removes it from the denylist
+        /// and lifts any automatic ban.
+        pub fn set_denylisted(&mut self, peer: PeerId, denylisted: bool) {
+            if denylisted {
+                if self.denylist.insert(peer) {
+                    tracing::info!(%peer, "Added peer to the denylist");
+                    self.to_disconnect.push_back(peer);
+                }
+            } else {
+                self.denylist.remove(&peer);
+                self.banned_until.remove(&peer);
+                self.abandoned_setups.forget(&peer);
+                tracing::info!(%peer, "Removed peer from the denylist");
+            }
+        }
+
+        fn allow_quote_at(&mut self, peer: PeerId, now: Instant) -> bool {
+            !self.is_banned(peer, now) && self.quotes.allow(peer, now)
+        }
+
+        fn allow_swap_setup_at(&mut self, peer: PeerId, now: Instant) -> bool {
+            !self.is_banned(peer, now) && self.swap_setups.allow(peer, now)
+        }
+
+        fn swap_setup_failed_at(&mut self, peer: PeerId, now: Instant) {
+            if self.take_pending_setup(peer) {
+                self.record_abandoned_setup(peer, now);
+            }
+        }
+
+        fn record_abandoned_setup(&mut self, peer: PeerId, now: Instant) {
+            if !self.abandoned_setups.allow(peer, now) {
+                tracing::warn!(
+                    %peer,
+                    ban_secs = self.ban_duration.as_secs(),
+                    "Banning peer because it abandoned too many swap setups"
+                );
+                self.banned_until.insert(peer, now + self.ban_duration);
+                self.abandoned_setups.forget(&peer);
+            }
+        }
+
+        /// Returns whether we handed out a wallet snapshot for a setup of the peer.
+        fn take_pending_setup(&mut self, peer: PeerId) -> bool {
+            match self.pending_setups.get_mut(&peer) {
+                Some(pending) if *pending > 1 => {
+                    *pending -= 1;
+                    true
+                }
+                Some(_) => {
+                    self.pending_setups.remove(&peer);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        fn is_banned(&mut self, peer: PeerId, now: Instant) -> bool {
+            if self.denylist.contains(&peer) {
+                return true;
+            }
+
+            match self.banned_until.get(&peer) {
+                Some(until) if *until > now => true,
+                Some(_) => {
+                    self.banned_until.remove(&peer);
+                    false
+                }
+                None => false,
+            }
+        }
+
+        fn deny_if_denylisted(&self, peer: PeerId) -> Result<(), ConnectionDenied> {
+            if self.denylist.contains(&peer) {
+                return Err(ConnectionDenied::new(Denylisted(peer)));
+            }
+
+            Ok(())
+        }
+    }
+
+    impl NetworkBehaviour for Behaviour {
+        type ConnectionHandler = dummy::ConnectionHandler;
+        type ToSwarm = ();
+
+        fn handle_established_inbound_connection(
+            &mut self,
+            _connection_id: ConnectionId,
+            peer: PeerId,
+            _local_addr: &Multiaddr,
+            _remote_addr: &Multiaddr,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            self.deny_if_denylisted(peer)?;
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn handle_established_outbound_connection(
+            &mut self,
+            _connection_id: ConnectionId,
+            peer: PeerId,
+            _addr: &Multiaddr,
+            _role_override: libp2p::core::Endpoint,
+        ) -> Result<THandler<Self>, ConnectionDenied> {
+            self.deny_if_denylisted(peer)?;
+            Ok(dummy::ConnectionHandler)
+        }
+
+        fn on_swarm_event(&mut self, event: FromSwarm<'_>) {
+            if let FromSwarm::ConnectionClosed(closed) = event {
+                if closed.remaining_established == 0 {
+                    let now = Instant::now();
+
+                    // The setups still running on the connection are dropped with it
+                    let pending = self.pending_setups.remove(&closed.peer_id).unwrap_or(0);
+                    for _ in 0..pending {
+                        self.record_abandoned_setup(closed.peer_id, now);
+                    }
+
+                    self.quotes.prune(now);
+                    self.swap_setups.prune(now);
+                    self.abandoned_setups.prune(now);
+                }
+            }
+        }
+
+        fn on_connection_handler_event(
+            &mut self,
+            _peer_id: PeerId,
+            _connection_id: ConnectionId,
+            _event: THandlerOutEvent<Self>,
+        ) {
+            unreachable!("The peer guard dummy connection handler does not produce any events");
+        }
+
+        fn poll(&mut self, _: &mut Context<'_>) -> Poll<ToSwarm<(), THandlerInEvent<Self>>> {
+            if let Some(peer_id) = self.to_disconnect.pop_front() {
+                return Poll::Ready(ToSwarm::CloseConnection {
+                    peer_id,
+                    connection: CloseConnection::All,
+                });
+            }
+
+            Poll::Pending
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn limits() -> PeerLimits {
+            PeerLimits {
+                quotes_per_minute: 2,
+                swap_setups_per_hour: 1,
+                max_abandoned_setups: 1,
+                auto_ban_secs: 600,
+                denylist: vec![],
+            }
+        }
+
+        #[test]
+        fn limits_are_per_peer_and_reset_after_the_window() {
+            let mut guard = Behaviour::new(&limits());
+            let (alice, bob) = (PeerId::random(), PeerId::random());
+            let now = Instant::now();
+
+            assert!(guard.allow_quote_at(alice, now));
+            assert!(guard.allow_quote_at(alice, now));
+            assert!(!guard.allow_quote_at(alice, now));
+            assert!(guard.allow_quote_at(bob, now));
+
+            assert!(guard.allow_quote_at(alice, now + Duration::from_secs(60)));
+        }
+
+        #[test]
+        fn peers_abandoning_too_many_setups_are_banned_for_a_while() {
+            let mut guard = Behaviour::new(&limits());
+            let peer = PeerId::random();
+            let now = Instant::now();
+
+            for _ in 0..2 {
+                guard.swap_setup_started(peer);
+                guard.swap_setup_failed_at(peer, now);
+            }
+
+            assert!(!guard.allow_quote_at(peer, now));
+            assert!(!guard.allow_swap_setup_at(peer, now));
+            assert!(guard.allow_quote_at(peer, now + Duration::from_secs(600)));
+        }
+
+        #[test]
+        fn setups_we_never_captured_a_snapshot_for_are_not_abandoned() {
+            let mut guard = Behaviour::new(&limits());
+            let peer = PeerId::random();
+            let now = Instant::now();
+
+            for _ in 0..3 {
+                guard.swap_setup_failed_at(peer, now);
+            }
+            guard.swap_setup_started(peer);
+            guard.swap_setup_finished(peer);
+            guard.swap_setup_failed_at(peer, now);
+
+            assert!(guard.allow_quote_at(peer, now));
+        }
+
+        #[test]
+        fn denylisted_peers_are_never_served() {
+            let peer = PeerId::random();
+            let mut guard = Behaviour::new(&PeerLimits {
+                denylist: vec![peer],
+                ..limits()
+            });
+            let now = Instant::now();
+
+            assert!(!guard.allow_quote_at(peer, now));
+            assert!(guard.deny_if_denylisted(peer).is_err());
+
+            guard.set_denylisted(peer, false);
+
+            assert!(guard.allow_quote_at(peer, now));
+            assert!(guard.deny_if_denylisted(peer).is_ok());
+        }
+    }
+}
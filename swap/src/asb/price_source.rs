@@ -0,0 +1,659 @@
+//! Price sources used by the asb to determine the XMR/BTC market rate.
+//!
+//! Every configured [`PriceSource`] is polled in the background and publishes
+//! its latest price into a [`PriceFeed`]. The [`AggregatedRate`] combines all
+//! feeds into a single [`Rate`] by taking the median of all recent prices. If
+//! the feeds disagree too much, no rate is produced and we refuse to quote.
+use crate::asb::config::{self, Maker};
+use crate::asb::{LatestRate, Rate};
+use crate::{bitcoin, kraken};
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncBufReadExt, BufReader, Lines, Stdin};
+use tokio::sync::watch;
+use tokio::time::{Interval, MissedTickBehavior};
+use url::Url;
+
+/// How long we wait before asking a source again after it failed.
+const RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A source of XMR/BTC prices.
+#[async_trait]
+pub trait PriceSource: Send + 'static {
+    /// A human readable name used in logs and errors
+    fn name(&self) -> String;
+
+    /// Waits for the next price (the price of 1 XMR in BTC) reported by this source.
+    ///
+    /// Sources may report an unchanged price again to signal that they are still
+    /// alive, e.g. on a heartbeat. Only the time since the last report counts
+    /// towards staleness.
+    async fn next_price(&mut self) -> Result<bitcoin::Amount>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdate {
+    pub ask: bitcoin::Amount,
+    /// When the source last reported this price.
+    pub received_at: Instant,
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Price is not yet available")]
+    NotYetAvailable,
+    #[error("All price sources are stale, none reported a price within the last {max_staleness_secs} seconds")]
+    Stale { max_staleness_secs: u64 },
+    #[error("Only {fresh} price sources reported a recent price but we require {required}")]
+    NotEnoughSources { fresh: usize, required: usize },
+    #[error("Price sources disagree: {feed} reports {price} which deviates by {deviation} from the median {median} (allowed deviation is {max_deviation})")]
+    SourcesDisagree {
+        feed: String,
+        price: bitcoin::Amount,
+        median: bitcoin::Amount,
+        deviation: Decimal,
+        max_deviation: Decimal,
+    },
+}
+
+/// The latest price published by a [`PriceSource`] that is polled in the background.
+#[derive(Clone, Debug)]
+pub struct PriceFeed {
+    name: String,
+    latest: watch::Receiver<Result<PriceUpdate, Error>>,
+}
+
+impl PriceFeed {
+    /// Spawns a task which continuously polls the given source.
+    pub fn spawn(mut source: impl PriceSource) -> Self {
+        let name = source.name();
+        let (sender, latest) = watch::channel(Err(Error::NotYetAvailable));
+
+        let task_name = name.clone();
+        tokio::spawn(async move {
+            loop {
+                match source.next_price().await {
+                    Ok(ask) => {
+                        let update = PriceUpdate {
+                            ask,
+                            received_at: Instant::now(),
+                        };
+
+                        tracing::trace!(source = %task_name, %ask, "Received price update");
+
+                        if sender.send(Ok(update)).is_err() {
+                            // Nobody is interested in this feed anymore
+                            return;
+                        }
+                    }
+                    Err(error) => {
+                        // We keep the last known price. It will be ignored once it becomes stale.
+                        tracing::warn!(source = %task_name, "Failed to get price from price source, retrying in {} seconds: {:#}", RETRY_INTERVAL.as_secs(), error);
+
+                        if sender.is_closed() {
+                            return;
+                        }
+
+                        tokio::time::sleep(RETRY_INTERVAL).await;
+                    }
+                }
+            }
+        });
+
+        Self { name, latest }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn latest_update(&self) -> Result<PriceUpdate, Error> {
+        self.latest.borrow().clone()
+    }
+}
+
+/// Produces [`Rate`]s from the median of multiple [`PriceFeed`]s and a configured spread.
+#[derive(Clone, Debug)]
+pub struct AggregatedRate {
+    feeds: Vec<PriceFeed>,
+    ask_spread: Decimal,
+    /// Prices older than this are ignored. `None` means prices never become stale.
+    max_staleness: Option<Duration>,
+    max_deviation: Decimal,
+    min_sources: usize,
+}
+
+impl AggregatedRate {
+    pub fn new(
+        feeds: Vec<PriceFeed>,
+        ask_spread: Decimal,
+        max_staleness: Option<Duration>,
+        max_deviation: Decimal,
+        min_sources: usize,
+    ) -> Self {
+        Self {
+            feeds,
+            ask_spread,
+            max_staleness,
+            max_deviation,
+            min_sources: min_sources.max(1),
+        }
+    }
+
+    /// Connects to all price sources configured in the `[maker]` section.
+    ///
+    /// If no price sources are configured, we only use the Kraken websocket
+    /// at `price_ticker_ws_url` and never consider its price stale.
+    pub fn from_config(maker: &Maker) -> Result<Self> {
+        let Some(price_sources) = &maker.price_sources else {
+            let kraken = KrakenSource::connect(maker.price_ticker_ws_url.clone())?;
+
+            return Ok(Self::new(
+                vec![PriceFeed::spawn(kraken)],
+                maker.ask_spread,
+                None,
+                Decimal::ZERO,
+                1,
+            ));
+        };
+
+        if price_sources.feeds.is_empty() {
+            bail!("At least one price feed has to be configured in `maker.price_sources.feeds`");
+        }
+
+        if price_sources.min_sources > price_sources.feeds.len() {
+            bail!(
+                "`maker.price_sources.min_sources` ({}) is larger than the number of configured feeds ({})",
+                price_sources.min_sources,
+                price_sources.feeds.len()
+            );
+        }
+
+        let feeds = price_sources
+            .feeds
+            .iter()
+            .map(|feed| {
+                Ok(match feed {
+                    config::PriceFeed::Kraken { ws_url } => {
+                        PriceFeed::spawn(KrakenSource::connect(ws_url.clone())?)
+                    }
+                    config::PriceFeed::Rest {
+                        url,
+                        json_pointer,
+                        poll_interval_secs,
+                    } => PriceFeed::spawn(RestSource::new(
+                        url.clone(),
+                        json_pointer.clone(),
+                        Duration::from_secs(*poll_interval_secs),
+                    )),
+                    config::PriceFeed::File { path, .. } if path.as_os_str() == "-" => {
+                        PriceFeed::spawn(StdinSource::new())
+                    }
+                    config::PriceFeed::File {
+                        path,
+                        poll_interval_secs,
+                    } => PriceFeed::spawn(FileSource::new(
+                        path.clone(),
+                        Duration::from_secs(*poll_interval_secs),
+                    )),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        tracing::info!(
+            feeds = ?feeds.iter().map(PriceFeed::name).collect::<Vec<_>>(),
+            "Connected to price sources"
+        );
+
+        Ok(Self::new(
+            feeds,
+            maker.ask_spread,
+            Some(Duration::from_secs(price_sources.max_staleness_secs)),
+            price_sources.max_deviation,
+            price_sources.min_sources,
+        ))
+    }
+
+    /// Computes the median price of all feeds which reported a recent price.
+    ///
+    /// Fails if too few feeds are available or if any feed deviates too far from the median.
+    fn median_price(&self) -> Result<bitcoin::Amount, Error> {
+        self.median_price_at(Instant::now())
+    }
+
+    /// Like [`AggregatedRate::median_price`], with the age of the prices taken relative to `now`.
+    fn median_price_at(&self, now: Instant) -> Result<bitcoin::Amount, Error> {
+        let mut stale = 0;
+        let mut prices = self
+            .feeds
+            .iter()
+            .filter_map(|feed| match feed.latest_update() {
+                Ok(update) => match self.max_staleness {
+                    Some(max_staleness)
+                        if now.saturating_duration_since(update.received_at) > max_staleness =>
+                    {
+                        tracing::debug!(source = %feed.name(), "Ignoring stale price");
+                        stale += 1;
+                        None
+                    }
+                    _ => Some((feed.name(), update.ask)),
+                },
+                Err(error) => {
+                    tracing::debug!(source = %feed.name(), %error, "Price source has no price");
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if prices.len() < self.min_sources {
+            return Err(match (prices.len(), self.max_staleness) {
+                (0, Some(max_staleness)) if stale > 0 => Error::Stale {
+                    max_staleness_secs: max_staleness.as_secs(),
+                },
+                (0, _) if self.min_sources == 1 => Error::NotYetAvailable,
+                (fresh, _) => Error::NotEnoughSources {
+                    fresh,
+                    required: self.min_sources,
+                },
+            });
+        }
+
+        prices.sort_by_key(|(_, price)| *price);
+
+        let middle = prices.len() / 2;
+        let median = if prices.len() % 2 == 0 {
+            let sum = prices[middle - 1].1.to_sat() + prices[middle].1.to_sat();
+            bitcoin::Amount::from_sat(sum / 2)
+        } else {
+            prices[middle].1
+        };
+
+        // A single feed can never disagree with itself
+        if prices.len() > 1 {
+            let median_sats = Decimal::from(median.to_sat());
+
+            for (feed, price) in prices.iter() {
+                let deviation = (Decimal::from(price.to_sat()) - median_sats).abs()
+                    / median_sats.max(Decimal::ONE);
+
+                if deviation > self.max_deviation {
+                    return Err(Error::SourcesDisagree {
+                        feed: feed.to_string(),
+                        price: *price,
+                        median,
+                        deviation: deviation.round_dp(4),
+                        max_deviation: self.max_deviation,
+                    });
+                }
+            }
+        }
+
+        Ok(median)
+    }
+}
+
+impl LatestRate for AggregatedRate {
+    type Error = Error;
+
+    fn latest_rate(&mut self) -> Result<Rate, Self::Error> {
+        let median = self.median_price()?;
+
+        Ok(Rate::new(median, self.ask_spread))
+    }
+}
+
+/// Prices from a websocket server following the Kraken price ticker protocol.
+pub struct KrakenSource {
+    ws_url: Url,
+    price_updates: kraken::PriceUpdates,
+}
+
+impl KrakenSource {
+    pub fn connect(ws_url: Url) -> Result<Self> {
+        let price_updates = kraken::connect(ws_url.clone())?;
+
+        Ok(Self {
+            ws_url,
+            price_updates,
+        })
+    }
+}
+
+#[async_trait]
+impl PriceSource for KrakenSource {
+    fn name(&self) -> String {
+        format!("kraken ({})", self.ws_url)
+    }
+
+    async fn next_price(&mut self) -> Result<bitcoin::Amount> {
+        let update = self.price_updates.wait_for_next_update().await??;
+
+        Ok(update.ask)
+    }
+}
+
+/// Prices from a HTTP endpoint returning JSON.
+///
+/// The price is extracted from the response using a JSON pointer (RFC 6901),
+/// e.g. `/result/XXMRXXBT/a/0` for the Kraken REST ticker.
+pub struct RestSource {
+    url: Url,
+    json_pointer: String,
+    client: reqwest::Client,
+    interval: Interval,
+}
+
+impl RestSource {
+    pub fn new(url: Url, json_pointer: String, poll_interval: Duration) -> Self {
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Self {
+            url,
+            json_pointer,
+            client: reqwest::Client::new(),
+            interval,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for RestSource {
+    fn name(&self) -> String {
+        format!("rest ({})", self.url)
+    }
+
+    async fn next_price(&mut self) -> Result<bitcoin::Amount> {
+        self.interval.tick().await;
+
+        let response: Value = self
+            .client
+            .get(self.url.clone())
+            .send()
+            .await
+            .context("Failed to request price")?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse response as JSON")?;
+
+        let value = response
+            .pointer(&self.json_pointer)
+            .with_context(|| format!("Response contains no value at {}", self.json_pointer))?;
+
+        parse_price(value)
+    }
+}
+
+/// Prices from a local file. Intended for testing.
+///
+/// The file has to contain the price of 1 XMR in BTC. A new price is only
+/// reported once the file was modified, so an untouched file becomes stale.
+pub struct FileSource {
+    path: PathBuf,
+    interval: Interval,
+    last_modified: Option<SystemTime>,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf, poll_interval: Duration) -> Self {
+        let mut interval = tokio::time::interval(poll_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        Self {
+            path,
+            interval,
+            last_modified: None,
+        }
+    }
+}
+
+#[async_trait]
+impl PriceSource for FileSource {
+    fn name(&self) -> String {
+        format!("file ({})", self.path.display())
+    }
+
+    async fn next_price(&mut self) -> Result<bitcoin::Amount> {
+        loop {
+            self.interval.tick().await;
+
+            let modified = tokio::fs::metadata(&self.path)
+                .await
+                .and_then(|metadata| metadata.modified())
+                .with_context(|| format!("Failed to read {}", self.path.display()))?;
+
+            if self.last_modified == Some(modified) {
+                continue;
+            }
+
+            let content = tokio::fs::read_to_string(&self.path)
+                .await
+                .with_context(|| format!("Failed to read {}", self.path.display()))?;
+            self.last_modified = Some(modified);
+
+            return parse_price(&Value::String(content.trim().to_string()));
+        }
+    }
+}
+
+/// Prices read line by line from stdin. Intended for testing.
+pub struct StdinSource {
+    lines: Lines<BufReader<Stdin>>,
+}
+
+impl StdinSource {
+    pub fn new() -> Self {
+        Self {
+            lines: BufReader::new(tokio::io::stdin()).lines(),
+        }
+    }
+}
+
+impl Default for StdinSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PriceSource for StdinSource {
+    fn name(&self) -> String {
+        "stdin".to_string()
+    }
+
+    async fn next_price(&mut self) -> Result<bitcoin::Amount> {
+        loop {
+            let Some(line) = self.lines.next_line().await? else {
+                // Stdin was closed, we will never receive another price
+                return std::future::pending().await;
+            };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            return parse_price(&Value::String(line.trim().to_string()));
+        }
+    }
+}
+
+/// Parses the price of 1 XMR denominated in BTC from a JSON string or number.
+fn parse_price(value: &Value) -> Result<bitcoin::Amount> {
+    let price = match value {
+        Value::String(price) => {
+            bitcoin::Amount::from_str_in(price, ::bitcoin::Denomination::Bitcoin)
+                .with_context(|| format!("Failed to parse price \"{}\"", price))?
+        }
+        Value::Number(price) => {
+            let price = price.as_f64().context("Price is not a valid number")?;
+            bitcoin::Amount::from_btc(price).context("Failed to parse price")?
+        }
+        value => bail!("Expected price to be a string or number, got {}", value),
+    };
+
+    if price == bitcoin::Amount::ZERO {
+        bail!("Price source reported a price of zero");
+    }
+
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(
+        name: &str,
+        ask: Option<bitcoin::Amount>,
+    ) -> (PriceFeed, watch::Sender<Result<PriceUpdate, Error>>) {
+        let update = match ask {
+            Some(ask) => Ok(PriceUpdate {
+                ask,
+                received_at: Instant::now(),
+            }),
+            None => Err(Error::NotYetAvailable),
+        };
+        let (sender, latest) = watch::channel(update);
+
+        (
+            PriceFeed {
+                name: name.to_string(),
+                latest,
+            },
+            sender,
+        )
+    }
+
+    fn sats(sats: u64) -> Option<bitcoin::Amount> {
+        Some(bitcoin::Amount::from_sat(sats))
+    }
+
+    fn rate(feeds: Vec<PriceFeed>, min_sources: usize) -> AggregatedRate {
+        AggregatedRate::new(
+            feeds,
+            Decimal::ZERO,
+            Some(Duration::from_secs(60)),
+            Decimal::new(2, 2),
+            min_sources,
+        )
+    }
+
+    #[test]
+    fn uses_median_of_odd_number_of_feeds() {
+        let (a, _a) = feed("a", sats(100_000));
+        let (b, _b) = feed("b", sats(101_000));
+        let (c, _c) = feed("c", sats(100_500));
+
+        let median = rate(vec![a, b, c], 1).median_price().unwrap();
+
+        assert_eq!(median, bitcoin::Amount::from_sat(100_500));
+    }
+
+    #[test]
+    fn uses_average_of_middle_feeds_for_even_number_of_feeds() {
+        let (a, _a) = feed("a", sats(100_000));
+        let (b, _b) = feed("b", sats(101_000));
+
+        let median = rate(vec![a, b], 1).median_price().unwrap();
+
+        assert_eq!(median, bitcoin::Amount::from_sat(100_500));
+    }
+
+    #[test]
+    fn refuses_rate_if_feeds_disagree() {
+        let (a, _a) = feed("a", sats(100_000));
+        let (b, _b) = feed("b", sats(100_000));
+        let (c, _c) = feed("c", sats(150_000));
+
+        let error = rate(vec![a, b, c], 1).latest_rate().unwrap_err();
+
+        assert!(matches!(error, Error::SourcesDisagree { feed, .. } if feed == "c"));
+    }
+
+    #[test]
+    fn ignores_feeds_without_price() {
+        let (a, _a) = feed("a", sats(100_000));
+        let (b, _b) = feed("b", None);
+
+        let median = rate(vec![a, b], 1).median_price().unwrap();
+
+        assert_eq!(median, bitcoin::Amount::from_sat(100_000));
+    }
+
+    #[test]
+    fn refuses_rate_if_not_enough_feeds_are_available() {
+        let (a, _a) = feed("a", sats(100_000));
+        let (b, _b) = feed("b", None);
+
+        let error = rate(vec![a, b], 2).latest_rate().unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::NotEnoughSources {
+                fresh: 1,
+                required: 2
+            }
+        ));
+    }
+
+    /// Two minutes from now, when the prices the feeds report now are stale.
+    fn two_minutes_later() -> Instant {
+        Instant::now() + Duration::from_secs(120)
+    }
+
+    #[test]
+    fn ignores_stale_prices() {
+        let (a, _a) = feed("a", sats(100_000));
+
+        let error = rate(vec![a], 1)
+            .median_price_at(two_minutes_later())
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            Error::Stale {
+                max_staleness_secs: 60
+            }
+        ));
+    }
+
+    #[test]
+    fn reports_stale_prices_even_if_more_sources_are_required() {
+        let (a, _a) = feed("a", sats(100_000));
+        let (b, _b) = feed("b", None);
+
+        let error = rate(vec![a, b], 2)
+            .median_price_at(two_minutes_later())
+            .unwrap_err();
+
+        assert!(matches!(error, Error::Stale { .. }));
+    }
+
+    #[test]
+    fn has_no_price_before_any_source_reported() {
+        let (a, _a) = feed("a", None);
+
+        let error = rate(vec![a], 1).latest_rate().unwrap_err();
+
+        assert!(matches!(error, Error::NotYetAvailable));
+    }
+
+    #[test]
+    fn parses_prices_from_strings_and_numbers() {
+        assert_eq!(
+            parse_price(&Value::String("0.00440700".to_string())).unwrap(),
+            bitcoin::Amount::from_sat(440_700)
+        );
+        assert_eq!(
+            parse_price(&serde_json::json!(0.004407)).unwrap(),
+            bitcoin::Amount::from_sat(440_700)
+        );
+        assert!(parse_price(&Value::String("0".to_string())).is_err());
+        assert!(parse_price(&Value::Null).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+use crate::asb::config;
+use crate::asb::Rate;
+use crate::{bitcoin, monero};
+use rust_decimal::Decimal;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+
+/// Decides how much spread we charge on top of the configured `ask_spread`.
+///
+/// The policy is consulted whenever we compute a quote and when Bob requests a swap.
+/// It is cheap to clone; all clones share the same price history.
+#[derive(Clone, Debug, Default)]
+pub struct PricingPolicy {
+    config: config::Pricing,
+    /// Market prices we have observed, used to estimate volatility
+    price_history: Arc<Mutex<VecDeque<(Instant, bitcoin::Amount)>>>,
+}
+
+/// The inputs of the pricing policy.
+#[derive(Clone, Copy, Debug)]
+pub struct PricingContext {
+    /// Our Monero balance that is not yet reserved for other swaps
+    pub unreserved_xmr: monero::Amount,
+    /// The amount of Bitcoin Bob wants to swap
+    pub btc: bitcoin::Amount,
+    pub now: OffsetDateTime,
+}
+
+/// The additional spread charged by each rule of the pricing policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SpreadAdjustment {
+    pub inventory: Decimal,
+    pub size: Decimal,
+    pub schedule: Decimal,
+    pub volatility: Decimal,
+}
+
+impl SpreadAdjustment {
+    pub fn total(&self) -> Decimal {
+        self.inventory + self.size + self.schedule + self.volatility
+    }
+}
+
+impl fmt::Display for SpreadAdjustment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "inventory: {}, size: {}, schedule: {}, volatility: {}",
+            self.inventory, self.size, self.schedule, self.volatility
+        )
+    }
+}
+
+impl PricingPolicy {
+    pub fn new(config: Option<config::Pricing>) -> Self {
+        Self {
+            config: config.unwrap_or_default(),
+            price_history: Default::default(),
+        }
+    }
+
+    /// Applies the policy to the given rate and returns the rate including the additional spread.
+    pub fn apply(&self, rate: Rate, context: PricingContext) -> Rate {
+        self.record_market_price(rate.market_price(), Instant::now());
+
+        let adjustment = self.adjustment(context);
+        let rate = rate.with_additional_spread(adjustment.total());
+
+        tracing::debug!(
+            effective_spread = %rate.spread(),
+            %adjustment,
+            unreserved_xmr = %context.unreserved_xmr,
+            btc = %context.btc,
+            "Applied pricing policy"
+        );
+
+        rate
+    }
+
+    /// Computes the additional spread for the given context without recording a price.
+    pub fn adjustment(&self, context: PricingContext) -> SpreadAdjustment {
+        SpreadAdjustment {
+            inventory: self.inventory_spread(context.unreserved_xmr),
+            size: self.size_spread(context.btc),
+            schedule: self.schedule_spread(context.now),
+            volatility: self.volatility_spread(Instant::now()),
+        }
+    }
+
+    pub fn record_market_price(&self, price: bitcoin::Amount, at: Instant) {
+        let Some(volatility) = &self.config.volatility else {
+            return;
+        };
+
+        let mut history = self
+            .price_history
+            .lock()
+            .expect("price history mutex not to be poisoned");
+
+        history.push_back((at, price));
+        prune(
+            &mut history,
+            at,
+            Duration::from_secs(volatility.window_secs),
+        );
+    }
+
+    fn inventory_spread(&self, unreserved_xmr: monero::Amount) -> Decimal {
+        let unreserved_xmr = unreserved_xmr.as_piconero_decimal()
+            / Decimal::from(monero::Amount::ONE_XMR.as_piconero());
+
+        max_spread(
+            self.config
+                .inventory
+                .iter()
+                .filter(|tier| unreserved_xmr < tier.below_xmr)
+                .map(|tier| tier.additional_spread),
+        )
+    }
+
+    fn size_spread(&self, btc: bitcoin::Amount) -> Decimal {
+        max_spread(
+            self.config
+                .size
+                .iter()
+                .filter(|tier| btc > tier.above_btc)
+                .map(|tier| tier.additional_spread),
+        )
+    }
+
+    fn schedule_spread(&self, now: OffsetDateTime) -> Decimal {
+        let hour = now.to_offset(time::UtcOffset::UTC).hour();
+
+        max_spread(
+            self.config
+                .schedule
+                .iter()
+                .filter(|window| is_within(hour, window.start_hour, window.end_hour))
+                .map(|window| window.additional_spread),
+        )
+    }
+
+    fn volatility_spread(&self, now: Instant) -> Decimal {
+        let Some(volatility) = &self.config.volatility else {
+            return Decimal::ZERO;
+        };
+
+        let mut history = self
+            .price_history
+            .lock()
+            .expect("price history mutex not to be poisoned");
+        prune(
+            &mut history,
+            now,
+            Duration::from_secs(volatility.window_secs),
+        );
+
+        let min = history.iter().map(|(_, price)| *price).min();
+        let max = history.iter().map(|(_, price)| *price).max();
+
+        let (Some(min), Some(max)) = (min, max) else {
+            return Decimal::ZERO;
+        };
+
+        if min == bitcoin::Amount::ZERO {
+            return Decimal::ZERO;
+        }
+
+        let range = Decimal::from(max.to_sat() - min.to_sat()) / Decimal::from(min.to_sat());
+
+        (range * volatility.multiplier).min(volatility.max_additional_spread)
+    }
+}
+
+fn max_spread(spreads: impl Iterator<Item = Decimal>) -> Decimal {
+    spreads.max().unwrap_or(Decimal::ZERO)
+}
+
+/// Checks whether `hour` lies within `[start, end)`, wrapping around midnight if `end < start`.
+fn is_within(hour: u8, start: u8, end: u8) -> bool {
+    if start <= end {
+        start <= hour && hour < end
+    } else {
+        hour >= start || hour < end
+    }
+}
+
+fn prune(history: &mut VecDeque<(Instant, bitcoin::Amount)>, now: Instant, window: Duration) {
+    while let Some((at, _)) = history.front() {
+        if now.saturating_duration_since(*at) <= window {
+            break;
+        }
+
+        history.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asb::config::{InventoryTier, ScheduleWindow, SizeTier, Volatility};
+
+    const ONE_PERCENT: Decimal = Decimal::from_parts(1, 0, 0, false, 2);
+    const TWO_PERCENT: Decimal = Decimal::from_parts(2, 0, 0, false, 2);
+
+    fn context(unreserved_xmr: f64, btc: f64, now: OffsetDateTime) -> PricingContext {
+        PricingContext {
+            unreserved_xmr: monero::Amount::from_monero(unreserved_xmr).unwrap(),
+            btc: bitcoin::Amount::from_btc(btc).unwrap(),
+            now,
+        }
+    }
+
+    #[test]
+    fn default_policy_does_not_change_rate() {
+        let policy = PricingPolicy::default();
+        let rate = Rate::new(bitcoin::Amount::from_sat(1_000), ONE_PERCENT);
+
+        let adjusted = policy.apply(rate, context(1.0, 0.1, OffsetDateTime::now_utc()));
+
+        assert_eq!(adjusted, rate);
+    }
+
+    #[test]
+    fn uses_largest_matching_inventory_tier() {
+        let policy = PricingPolicy::new(Some(config::Pricing {
+            inventory: vec![
+                InventoryTier {
+                    below_xmr: Decimal::from(10),
+                    additional_spread: ONE_PERCENT,
+                },
+                InventoryTier {
+                    below_xmr: Decimal::from(5),
+                    additional_spread: TWO_PERCENT,
+                },
+            ],
+            ..Default::default()
+        }));
+        let now = OffsetDateTime::now_utc();
+
+        assert_eq!(
+            policy.adjustment(context(20.0, 0.1, now)).total(),
+            Decimal::ZERO
+        );
+        assert_eq!(
+            policy.adjustment(context(8.0, 0.1, now)).total(),
+            ONE_PERCENT
+        );
+        assert_eq!(
+            policy.adjustment(context(1.0, 0.1, now)).total(),
+            TWO_PERCENT
+        );
+    }
+
+    #[test]
+    fn adds_spread_for_large_swaps() {
+        let policy = PricingPolicy::new(Some(config::Pricing {
+            size: vec![SizeTier {
+                above_btc: bitcoin::Amount::from_btc(0.5).unwrap(),
+                additional_spread: ONE_PERCENT,
+            }],
+            ..Default::default()
+        }));
+        let now = OffsetDateTime::now_utc();
+
+        assert_eq!(
+            policy.adjustment(context(1.0, 0.5, now)).size,
+            Decimal::ZERO
+        );
+        assert_eq!(policy.adjustment(context(1.0, 0.6, now)).size, ONE_PERCENT);
+    }
+
+    #[test]
+    fn schedule_window_wraps_around_midnight() {
+        let policy = PricingPolicy::new(Some(config::Pricing {
+            schedule: vec![ScheduleWindow {
+                start_hour: 22,
+                end_hour: 6,
+                additional_spread: ONE_PERCENT,
+            }],
+            ..Default::default()
+        }));
+
+        // 2025-01-01 00:00 UTC
+        let midnight = 1_735_689_600;
+        let at = |hour: i64| {
+            let now = OffsetDateTime::from_unix_timestamp(midnight + hour * 3600).unwrap();
+            policy.adjustment(context(1.0, 0.1, now)).schedule
+        };
+
+        assert_eq!(at(23), ONE_PERCENT);
+        assert_eq!(at(3), ONE_PERCENT);
+        assert_eq!(at(6), Decimal::ZERO);
+        assert_eq!(at(12), Decimal::ZERO);
+    }
+
+    #[test]
+    fn volatility_spread_is_capped() {
+        let policy = PricingPolicy::new(Some(config::Pricing {
+            volatility: Some(Volatility {
+                window_secs: 60,
+                multiplier: Decimal::ONE,
+                max_additional_spread: TWO_PERCENT,
+            }),
+            ..Default::default()
+        }));
+        let now = Instant::now();
+
+        policy.record_market_price(bitcoin::Amount::from_sat(1_000), now);
+        policy.record_market_price(bitcoin::Amount::from_sat(1_010), now);
+        assert_eq!(policy.volatility_spread(now), ONE_PERCENT);
+
+        policy.record_market_price(bitcoin::Amount::from_sat(1_500), now);
+        assert_eq!(policy.volatility_spread(now), TWO_PERCENT);
+    }
+}
@@ -25,6 +25,24 @@ impl Rate {
         Self { ask, ask_spread }
     }
 
+    /// The market asking price, without any spread applied.
+    pub fn market_price(&self) -> bitcoin::Amount {
+        self.ask
+    }
+
+    /// The spread which is applied to the market asking price.
+    pub fn spread(&self) -> Decimal {
+        self.ask_spread
+    }
+
+    /// Returns a new rate with `additional_spread` added on top of the current spread.
+    pub fn with_additional_spread(&self, additional_spread: Decimal) -> Self {
+        Self {
+            ask: self.ask,
+            ask_spread: self.ask_spread + additional_spread,
+        }
+    }
+
     /// Computes the asking price at which we are willing to sell 1 XMR.
     ///
     /// This applies the spread to the market asking price.
@@ -108,6 +126,16 @@ mod tests {
         assert_eq!(amount.to_sat(), 102);
     }
 
+    #[test]
+    fn additional_spread_is_added_to_base_spread() {
+        let asking_price = bitcoin::Amount::from_sat(100);
+        let rate = Rate::new(asking_price, TWO_PERCENT).with_additional_spread(TWO_PERCENT);
+
+        assert_eq!(rate.market_price(), asking_price);
+        assert_eq!(rate.spread(), TWO_PERCENT + TWO_PERCENT);
+        assert_eq!(rate.ask().unwrap().to_sat(), 104);
+    }
+
     #[test]
     fn given_spread_of_two_percent_when_caluclating_sell_quote_factor_between_should_be_two_percent(
     ) {
@@ -9,7 +9,17 @@ use uuid::Uuid;
 pub async fn cancel(
     swap_id: Uuid,
     bitcoin_wallet: Arc<Wallet>,
-    db: Arc<dyn Database>,
+    db: Arc<dyn Database + Send + Sync>,
+) -> Result<(Txid, AliceState)> {
+    cancel_stopping_swap(swap_id, bitcoin_wallet, db, || {}).await
+}
+
+/// Like [`cancel`], but calls `stop_swap` right before publishing the cancel transaction.
+pub(crate) async fn cancel_stopping_swap(
+    swap_id: Uuid,
+    bitcoin_wallet: Arc<Wallet>,
+    db: Arc<dyn Database + Send + Sync>,
+    stop_swap: impl FnOnce(),
 ) -> Result<(Txid, AliceState)> {
     let state = db.get_state(swap_id).await?.try_into()?;
 
@@ -44,6 +54,8 @@ pub async fn cancel(
         | AliceState::SafelyAborted => bail!("Swap is in state {} which is not cancelable", state),
     };
 
+    stop_swap();
+
     let txid = match state3.submit_tx_cancel(bitcoin_wallet.as_ref()).await {
         Ok(txid) => txid,
         Err(err) => {
@@ -15,7 +15,17 @@ pub enum Error {
 pub async fn punish(
     swap_id: Uuid,
     bitcoin_wallet: Arc<bitcoin::Wallet>,
-    db: Arc<dyn Database>,
+    db: Arc<dyn Database + Send + Sync>,
+) -> Result<(Txid, AliceState)> {
+    punish_stopping_swap(swap_id, bitcoin_wallet, db, || {}).await
+}
+
+/// Like [`punish`], but calls `stop_swap` right before publishing the punish transaction.
+pub(crate) async fn punish_stopping_swap(
+    swap_id: Uuid,
+    bitcoin_wallet: Arc<bitcoin::Wallet>,
+    db: Arc<dyn Database + Send + Sync>,
+    stop_swap: impl FnOnce(),
 ) -> Result<(Txid, AliceState)> {
     let state = db.get_state(swap_id).await?.try_into()?;
 
@@ -47,6 +57,7 @@ pub async fn punish(
 
     tracing::info!(%swap_id, "Trying to manually punish swap");
 
+    stop_swap();
     let txid = state3.punish_btc(&bitcoin_wallet).await?;
 
     let state = AliceState::BtcPunished {
@@ -24,8 +24,20 @@ impl Finality {
 pub async fn redeem(
     swap_id: Uuid,
     bitcoin_wallet: Arc<Wallet>,
-    db: Arc<dyn Database>,
+    db: Arc<dyn Database + Send + Sync>,
     finality: Finality,
+) -> Result<(Txid, AliceState)> {
+    redeem_stopping_swap(swap_id, bitcoin_wallet, db, finality, || {}).await
+}
+
+/// Like [`redeem`], but calls `stop_swap` right before publishing the redeem transaction or,
+/// if it was already published, before waiting for it.
+pub(crate) async fn redeem_stopping_swap(
+    swap_id: Uuid,
+    bitcoin_wallet: Arc<Wallet>,
+    db: Arc<dyn Database + Send + Sync>,
+    finality: Finality,
+    stop_swap: impl FnOnce(),
 ) -> Result<(Txid, AliceState)> {
     let state = db.get_state(swap_id).await?.try_into()?;
 
@@ -39,6 +51,7 @@ pub async fn redeem(
             tracing::info!(%swap_id, "Trying to redeem swap");
 
             let redeem_tx = state3.signed_redeem_transaction(*encrypted_signature)?;
+            stop_swap();
             let (txid, subscription) = bitcoin_wallet.broadcast(redeem_tx, "redeem").await?;
 
             subscription.wait_until_seen().await?;
@@ -60,6 +73,7 @@ pub async fn redeem(
             Ok((txid, state))
         }
         AliceState::BtcRedeemTransactionPublished { state3, .. } => {
+            stop_swap();
             let subscription = bitcoin_wallet.subscribe_to(state3.tx_redeem()).await;
 
             if let Finality::Await = finality {
@@ -29,7 +29,19 @@ pub async fn refund(
     swap_id: Uuid,
     bitcoin_wallet: Arc<bitcoin::Wallet>,
     monero_wallet: Arc<monero::Wallets>,
-    db: Arc<dyn Database>,
+    db: Arc<dyn Database + Send + Sync>,
+) -> Result<AliceState> {
+    refund_stopping_swap(swap_id, bitcoin_wallet, monero_wallet, db, || {}).await
+}
+
+/// Like [`refund`], but calls `stop_swap` once we found Bob's refund transaction, right before
+/// refunding the Monero.
+pub(crate) async fn refund_stopping_swap(
+    swap_id: Uuid,
+    bitcoin_wallet: Arc<bitcoin::Wallet>,
+    monero_wallet: Arc<monero::Wallets>,
+    db: Arc<dyn Database + Send + Sync>,
+    stop_swap: impl FnOnce(),
 ) -> Result<AliceState> {
     let state = db.get_state(swap_id).await?.try_into()?;
 
@@ -75,6 +87,8 @@ pub async fn refund(
         bail!(Error::RefundTransactionNotPublishedYet(bob_peer_id),);
     };
 
+    stop_swap();
+
     retry(
         "Refund Monero",
         || async {
@@ -5,13 +5,26 @@ use std::convert::TryInto;
 use std::sync::Arc;
 use uuid::Uuid;
 
-pub async fn safely_abort(swap_id: Uuid, db: Arc<dyn Database>) -> Result<AliceState> {
+pub async fn safely_abort(
+    swap_id: Uuid,
+    db: Arc<dyn Database + Send + Sync>,
+) -> Result<AliceState> {
+    safely_abort_stopping_swap(swap_id, db, || {}).await
+}
+
+/// Like [`safely_abort`], but calls `stop_swap` right before storing the aborted state.
+pub(crate) async fn safely_abort_stopping_swap(
+    swap_id: Uuid,
+    db: Arc<dyn Database + Send + Sync>,
+    stop_swap: impl FnOnce(),
+) -> Result<AliceState> {
     let state = db.get_state(swap_id).await?.try_into()?;
 
     match state {
         AliceState::Started { .. }
         | AliceState::BtcLockTransactionSeen { .. }
         | AliceState::BtcLocked { .. } => {
+            stop_swap();
             let state = AliceState::SafelyAborted;
 
             db.insert_latest_state(swap_id, state.clone().into())
@@ -0,0 +1,759 @@
+//! Authenticated JSON-RPC server which lets operators inspect and manage a running asb.
+//!
+//! The server speaks JSON-RPC 2.0 over HTTP `POST /`. Every request must carry the token from the
+//! cookie file (written to the data directory on startup) as a bearer token in the
+//! `Authorization` header.
+use crate::asb::recovery::cancel::cancel_stopping_swap;
+use crate::asb::recovery::punish::punish_stopping_swap;
+use crate::asb::recovery::redeem::redeem_stopping_swap;
+use crate::asb::recovery::refund::refund_stopping_swap;
+use crate::asb::recovery::safely_abort::safely_abort_stopping_swap;
+use crate::asb::{EventLoopService, Finality};
+use crate::bitcoin::{bitcoin_address, ExpiredTimelocks};
+use crate::protocol::alice::swap::is_complete;
+use crate::protocol::alice::AliceState;
+use crate::protocol::{Database, State};
+use crate::{bitcoin, env, monero};
+use ::bitcoin::address::NetworkUnchecked;
+use anyhow::{bail, Context, Result};
+use axum::extract::State as AxumState;
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::post;
+use axum::{Json, Router};
+use libp2p::PeerId;
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use subtle::ConstantTimeEq;
+use tokio::task::{AbortHandle, JoinHandle};
+use uuid::Uuid;
+
+/// Name of the file (in the data directory) that holds the token clients have to authenticate with
+pub const COOKIE_FILE_NAME: &str = "rpc.cookie";
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// Keeps track of the swaps that are currently being executed by this asb.
+///
+/// Manual recovery actions stop the running swap right before they publish
+/// anything, such that the swap and the recovery action do not race each other.
+#[derive(Clone, Debug, Default)]
+pub struct RunningSwaps {
+    inner: Arc<Mutex<HashMap<Uuid, AbortHandle>>>,
+}
+
+impl RunningSwaps {
+    pub fn insert(&self, swap_id: Uuid, handle: AbortHandle) {
+        self.lock().insert(swap_id, handle);
+    }
+
+    pub fn remove(&self, swap_id: Uuid) {
+        self.lock().remove(&swap_id);
+    }
+
+    pub fn contains(&self, swap_id: Uuid) -> bool {
+        self.lock()
+            .get(&swap_id)
+            .is_some_and(|handle| !handle.is_finished())
+    }
+
+    /// Stops the swap if it is currently running. Returns whether a running swap was stopped.
+    pub fn abort(&self, swap_id: Uuid) -> bool {
+        match self.lock().remove(&swap_id) {
+            Some(handle) if !handle.is_finished() => {
+                handle.abort();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, AbortHandle>> {
+        self.inner
+            .lock()
+            .expect("running swaps mutex not to be poisoned")
+    }
+}
+
+/// Everything the RPC methods need access to.
+#[derive(Clone)]
+pub struct RpcContext {
+    pub env_config: env::Config,
+    pub bitcoin_wallet: Arc<bitcoin::Wallet>,
+    pub monero_wallet: Arc<monero::Wallets>,
+    pub db: Arc<dyn Database + Send + Sync>,
+    pub event_loop: EventLoopService,
+    pub running_swaps: RunningSwaps,
+}
+
+#[derive(Clone)]
+struct ServerState {
+    context: RpcContext,
+    token: Arc<String>,
+}
+
+/// Starts the RPC server in the background.
+///
+/// A fresh token is generated on every start and written to `<data_dir>/rpc.cookie`.
+pub async fn serve(
+    listen: SocketAddr,
+    data_dir: &Path,
+    context: RpcContext,
+) -> Result<JoinHandle<()>> {
+    let token = generate_token();
+    let cookie_path = write_cookie(data_dir, &token)?;
+
+    let state = ServerState {
+        context,
+        token: Arc::new(token),
+    };
+
+    let app = Router::new().route("/", post(handle)).with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind RPC server to {}", listen))?;
+
+    tracing::info!(
+        address = %listen,
+        cookie = %cookie_path.display(),
+        "RPC server listening"
+    );
+
+    Ok(tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, app).await {
+            tracing::error!("RPC server stopped: {:#}", error);
+        }
+    }))
+}
+
+fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+fn write_cookie(data_dir: &Path, token: &str) -> Result<PathBuf> {
+    let path = data_dir.join(COOKIE_FILE_NAME);
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    // Only the operator should be able to read the token
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(&path)
+        .with_context(|| format!("Failed to create RPC cookie file at {}", path.display()))?;
+    std::io::Write::write_all(&mut file, token.as_bytes())
+        .context("Failed to write RPC cookie file")?;
+
+    Ok(path)
+}
+
+fn is_authorized(headers: &HeaderMap, token: &str) -> bool {
+    headers
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        // Constant time such that the time we take does not reveal how much of the token was right
+        .is_some_and(|provided| provided.trim().as_bytes().ct_eq(token.as_bytes()).into())
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    jsonrpc: String,
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(error: anyhow::Error) -> Self {
+        Self::new(SERVER_ERROR, format!("{:#}", error))
+    }
+}
+
+async fn handle(
+    AxumState(state): AxumState<ServerState>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, Json<Value>) {
+    if !is_authorized(&headers, &state.token) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "missing or invalid bearer token" })),
+        );
+    }
+
+    let request: Request = match serde_json::from_str(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return (
+                StatusCode::OK,
+                Json(response(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, error.to_string())),
+                )),
+            )
+        }
+    };
+
+    if request.jsonrpc != "2.0" {
+        return (
+            StatusCode::OK,
+            Json(response(
+                request.id,
+                Err(RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"")),
+            )),
+        );
+    }
+
+    tracing::debug!(method = %request.method, "Received RPC request");
+
+    let result = dispatch(&state.context, &request.method, request.params).await;
+
+    if let Err(error) = &result {
+        tracing::warn!(method = %request.method, "RPC request failed: {}", error.message);
+    }
+
+    (StatusCode::OK, Json(response(request.id, result)))
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": error.code, "message": error.message }
+        }),
+    }
+}
+
+fn params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    // Methods without parameters may omit them entirely
+    let params = if params.is_null() { json!({}) } else { params };
+
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SwapIdParams {
+    swap_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RedeemParams {
+    swap_id: Uuid,
+    #[serde(default)]
+    do_not_await_finality: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BumpFeeParams {
+    swap_id: Uuid,
+    /// In sat/vB, defaults to the current fee estimate
+    #[serde(default)]
+    fee_rate: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct WithdrawBtcParams {
+    address: ::bitcoin::Address<NetworkUnchecked>,
+    /// Omit to withdraw the whole balance
+    #[serde(default, with = "::bitcoin::amount::serde::as_btc::opt")]
+    amount: Option<bitcoin::Amount>,
+    /// Only spend these UTXOs
+    #[serde(default)]
+    inputs: Option<Vec<bitcoin::OutPoint>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LabelUtxoParams {
+    outpoint: bitcoin::OutPoint,
+    /// Omit to remove the label
+    #[serde(default)]
+    label: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FreezeUtxoParams {
+    outpoint: bitcoin::OutPoint,
+    frozen: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DenylistPeerParams {
+    peer_id: PeerId,
+    denylisted: bool,
+}
+
+async fn dispatch(context: &RpcContext, method: &str, raw: Value) -> Result<Value, RpcError> {
+    match method {
+        "get_balance" => {
+            let NoParams {} = params(raw)?;
+            Ok(get_balance(context).await?)
+        }
+        "get_swaps" => {
+            let NoParams {} = params(raw)?;
+            Ok(get_swaps(context).await?)
+        }
+        "get_swap" => {
+            let SwapIdParams { swap_id } = params(raw)?;
+            Ok(get_swap(context, swap_id).await?)
+        }
+        "get_quote" => {
+            let NoParams {} = params(raw)?;
+            Ok(get_quote(context).await?)
+        }
+        "get_peers" => {
+            let NoParams {} = params(raw)?;
+            Ok(get_peers(context).await?)
+        }
+        "denylist_peer" => {
+            let DenylistPeerParams {
+                peer_id,
+                denylisted,
+            } = params(raw)?;
+            context
+                .event_loop
+                .set_denylisted(peer_id, denylisted)
+                .await?;
+            Ok(json!({ "peer_id": peer_id.to_string(), "denylisted": denylisted }))
+        }
+        "withdraw_btc" => {
+            let WithdrawBtcParams {
+                address,
+                amount,
+                inputs,
+            } = params(raw)?;
+            Ok(withdraw_btc(context, address, amount, inputs).await?)
+        }
+        "list_unspent" => {
+            let NoParams {} = params(raw)?;
+            let utxos = context.bitcoin_wallet.list_unspent().await?;
+            Ok(json!(utxos))
+        }
+        "get_bitcoin_transactions" => {
+            let NoParams {} = params(raw)?;
+            let mut transactions = context.bitcoin_wallet.transaction_history().await?;
+            bitcoin::link_to_swaps(&mut transactions, context.db.as_ref()).await?;
+            Ok(json!(transactions))
+        }
+        "label_utxo" => {
+            let LabelUtxoParams { outpoint, label } = params(raw)?;
+            context
+                .bitcoin_wallet
+                .set_utxo_label(outpoint, label.clone())
+                .await?;
+            Ok(json!({ "outpoint": outpoint, "label": label }))
+        }
+        "freeze_utxo" => {
+            let FreezeUtxoParams { outpoint, frozen } = params(raw)?;
+            context
+                .bitcoin_wallet
+                .set_utxo_frozen(outpoint, frozen)
+                .await?;
+            Ok(json!({ "outpoint": outpoint, "frozen": frozen }))
+        }
+        "cancel" => {
+            let SwapIdParams { swap_id } = params(raw)?;
+            ensure_timelock_expired(context, swap_id, Timelock::Cancel).await?;
+            let stopper = SwapStopper::new(context, swap_id);
+            let result = cancel_stopping_swap(
+                swap_id,
+                context.bitcoin_wallet.clone(),
+                context.db.clone(),
+                || stopper.stop(),
+            )
+            .await;
+            let (txid, state) = stopper.resume_on_error(result).await?;
+            Ok(json!({ "txid": txid.to_string(), "state": state.to_string() }))
+        }
+        "refund" => {
+            let SwapIdParams { swap_id } = params(raw)?;
+            let stopper = SwapStopper::new(context, swap_id);
+            let result = refund_stopping_swap(
+                swap_id,
+                context.bitcoin_wallet.clone(),
+                context.monero_wallet.clone(),
+                context.db.clone(),
+                || stopper.stop(),
+            )
+            .await;
+            let state = stopper.resume_on_error(result).await?;
+            Ok(json!({ "state": state.to_string() }))
+        }
+        "punish" => {
+            let SwapIdParams { swap_id } = params(raw)?;
+            ensure_timelock_expired(context, swap_id, Timelock::Punish).await?;
+            let stopper = SwapStopper::new(context, swap_id);
+            let result = punish_stopping_swap(
+                swap_id,
+                context.bitcoin_wallet.clone(),
+                context.db.clone(),
+                || stopper.stop(),
+            )
+            .await;
+            let (txid, state) = stopper.resume_on_error(result).await?;
+            Ok(json!({ "txid": txid.to_string(), "state": state.to_string() }))
+        }
+        "redeem" => {
+            let RedeemParams {
+                swap_id,
+                do_not_await_finality,
+            } = params(raw)?;
+            let stopper = SwapStopper::new(context, swap_id);
+            let result = redeem_stopping_swap(
+                swap_id,
+                context.bitcoin_wallet.clone(),
+                context.db.clone(),
+                Finality::from_bool(do_not_await_finality),
+                || stopper.stop(),
+            )
+            .await;
+            let (txid, state) = stopper.resume_on_error(result).await?;
+            Ok(json!({ "txid": txid.to_string(), "state": state.to_string() }))
+        }
+        "safely_abort" => {
+            let SwapIdParams { swap_id } = params(raw)?;
+            let stopper = SwapStopper::new(context, swap_id);
+            let result =
+                safely_abort_stopping_swap(swap_id, context.db.clone(), || stopper.stop()).await;
+            let state = stopper.resume_on_error(result).await?;
+            Ok(json!({ "state": state.to_string() }))
+        }
+        "bump_fee" => {
+            // Bumping fees does not interfere with the running swap, so we leave it alone
+            let BumpFeeParams { swap_id, fee_rate } = params(raw)?;
+            let fee_rate = fee_rate
+                .map(|sat_per_vb| {
+                    ::bitcoin::FeeRate::from_sat_per_vb(sat_per_vb)
+                        .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Fee rate is too high"))
+                })
+                .transpose()?;
+            let reports = bitcoin::bump_swap(
+                swap_id,
+                &context.bitcoin_wallet,
+                context.db.as_ref(),
+                fee_rate,
+            )
+            .await
+            .map_err(RpcError::from)?;
+            Ok(json!({ "transactions": reports }))
+        }
+        method => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {}", method),
+        )),
+    }
+}
+
+/// Stops the swap task for a recovery action, right before the action publishes anything.
+///
+/// If the action fails after it stopped the swap, the swap is resumed such that it does not
+/// stay stopped until the asb is restarted.
+struct SwapStopper<'a> {
+    context: &'a RpcContext,
+    swap_id: Uuid,
+    stopped: AtomicBool,
+}
+
+impl<'a> SwapStopper<'a> {
+    fn new(context: &'a RpcContext, swap_id: Uuid) -> Self {
+        Self {
+            context,
+            swap_id,
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    fn stop(&self) {
+        if self.context.running_swaps.abort(self.swap_id) {
+            self.stopped.store(true, Ordering::SeqCst);
+            tracing::info!(swap_id = %self.swap_id, "Stopped running swap for manual recovery");
+        }
+    }
+
+    async fn resume_on_error<T>(self, result: Result<T>) -> Result<T, RpcError> {
+        if result.is_err() && self.stopped.load(Ordering::SeqCst) {
+            match self.context.event_loop.resume_swap(self.swap_id).await {
+                Ok(()) => {
+                    tracing::info!(swap_id = %self.swap_id, "Resumed swap after manual recovery failed")
+                }
+                Err(error) => {
+                    tracing::error!(swap_id = %self.swap_id, "Failed to resume swap after manual recovery failed: {:#}", error)
+                }
+            }
+        }
+
+        result.map_err(RpcError::from)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Timelock {
+    Cancel,
+    Punish,
+}
+
+/// Fails if `timelock` of the swap has not expired yet, such that we do not stop the running
+/// swap for a recovery action the Bitcoin network would reject anyway.
+async fn ensure_timelock_expired(
+    context: &RpcContext,
+    swap_id: Uuid,
+    timelock: Timelock,
+) -> Result<(), RpcError> {
+    let state: AliceState = context
+        .db
+        .get_state(swap_id)
+        .await
+        .context("Swap not found")?
+        .try_into()
+        .map_err(anyhow::Error::from)?;
+
+    // Swaps without Bob's lock transaction are rejected by the recovery action itself
+    let Some(state3) = state.state3() else {
+        return Ok(());
+    };
+
+    let expired = state3
+        .expired_timelocks(&context.bitcoin_wallet)
+        .await
+        .context("Failed to check the timelocks of the swap")?;
+
+    match (timelock, expired) {
+        (Timelock::Cancel, expired) if expired.cancel_timelock_expired() => Ok(()),
+        (Timelock::Punish, ExpiredTimelocks::Punish) => Ok(()),
+        (Timelock::Cancel, _) => Err(RpcError::new(
+            SERVER_ERROR,
+            format!(
+                "The cancel timelock of swap {} has not expired yet",
+                swap_id
+            ),
+        )),
+        (Timelock::Punish, _) => Err(RpcError::new(
+            SERVER_ERROR,
+            format!(
+                "The punish timelock of swap {} has not expired yet",
+                swap_id
+            ),
+        )),
+    }
+}
+
+async fn get_balance(context: &RpcContext) -> Result<Value> {
+    // The running asb only syncs the scripts of its swaps, so we sync the whole wallet here
+    context.bitcoin_wallet.sync().await?;
+    let bitcoin_balance = context.bitcoin_wallet.balance().await?;
+
+    let monero_total: monero::Amount = context.monero_wallet.total_balance().await.into();
+    let monero_unlocked: monero::Amount = context.monero_wallet.unlocked_balance().await.into();
+    let monero_address = context.monero_wallet.main_address().await;
+
+    Ok(json!({
+        "bitcoin": bitcoin_balance.to_sat(),
+        "monero_total": monero_total.as_piconero(),
+        "monero_unlocked": monero_unlocked.as_piconero(),
+        "monero_address": monero_address.to_string(),
+    }))
+}
+
+async fn get_swaps(context: &RpcContext) -> Result<Value> {
+    let mut swaps = Vec::new();
+
+    for (swap_id, state) in context.db.all().await? {
+        let State::Alice(state) = state else {
+            continue;
+        };
+
+        if is_complete(&state) {
+            continue;
+        }
+
+        swaps.push(swap_summary(context, swap_id, &state).await?);
+    }
+
+    Ok(Value::Array(swaps))
+}
+
+async fn get_swap(context: &RpcContext, swap_id: Uuid) -> Result<Value> {
+    let state: AliceState = context
+        .db
+        .get_state(swap_id)
+        .await
+        .context("Swap not found")?
+        .try_into()?;
+
+    swap_summary(context, swap_id, &state).await
+}
+
+async fn swap_summary(context: &RpcContext, swap_id: Uuid, state: &AliceState) -> Result<Value> {
+    let start_date = context.db.get_swap_start_date(swap_id).await?;
+    let peer_id = context.db.get_peer_id(swap_id).await.ok();
+    let rate = context.db.get_swap_rate(swap_id).await?;
+
+    Ok(json!({
+        "swap_id": swap_id,
+        "start_date": start_date,
+        "state": state.to_string(),
+        "completed": is_complete(state),
+        "running": context.running_swaps.contains(swap_id),
+        "peer_id": peer_id.map(|peer_id| peer_id.to_string()),
+        "market_price": rate.map(|rate| rate.market_price().to_sat()),
+        "spread": rate.map(|rate| rate.spread()),
+    }))
+}
+
+async fn get_quote(context: &RpcContext) -> Result<Value> {
+    let (quote, rate) = context.event_loop.quote().await?;
+
+    Ok(json!({
+        "price": quote.price.to_sat(),
+        "min_quantity": quote.min_quantity.to_sat(),
+        "max_quantity": quote.max_quantity.to_sat(),
+        "market_price": rate.market_price().to_sat(),
+        "base_spread": rate.spread(),
+    }))
+}
+
+async fn get_peers(context: &RpcContext) -> Result<Value> {
+    let peers = context.event_loop.connected_peers().await?;
+
+    Ok(json!(peers
+        .into_iter()
+        .map(|peer| peer.to_string())
+        .collect::<Vec<_>>()))
+}
+
+async fn withdraw_btc(
+    context: &RpcContext,
+    address: ::bitcoin::Address<NetworkUnchecked>,
+    amount: Option<bitcoin::Amount>,
+    inputs: Option<Vec<bitcoin::OutPoint>>,
+) -> Result<Value> {
+    let address = bitcoin_address::validate_network(address, context.env_config.bitcoin_network)?;
+
+    if amount == Some(bitcoin::Amount::ZERO) {
+        bail!("Amount must be greater than zero");
+    }
+
+    context.bitcoin_wallet.sync().await?;
+
+    let psbt = match amount {
+        Some(amount) => {
+            context
+                .bitcoin_wallet
+                .send_to_address_dynamic_fee(address, amount, None, inputs.as_deref())
+                .await?
+        }
+        None => {
+            context
+                .bitcoin_wallet
+                .sweep_balance_to_address_dynamic_fee(address, inputs.as_deref())
+                .await?
+        }
+    };
+
+    let signed_tx = context.bitcoin_wallet.sign_and_finalize(psbt).await?;
+    let (txid, _) = context
+        .bitcoin_wallet
+        .broadcast(signed_tx, "withdraw")
+        .await?;
+
+    tracing::info!(%txid, "Published withdrawal transaction requested via RPC");
+
+    Ok(json!({ "txid": txid.to_string() }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn only_accepts_matching_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer wrong"),
+        );
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secre"),
+        );
+        assert!(!is_authorized(&headers, "secret"));
+
+        headers.insert(
+            axum::http::header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        assert!(is_authorized(&headers, "secret"));
+    }
+
+    #[test]
+    fn missing_params_are_treated_as_empty() {
+        assert!(params::<NoParams>(Value::Null).is_ok());
+        assert!(params::<NoParams>(json!({ "unexpected": 1 })).is_err());
+
+        let error = params::<SwapIdParams>(Value::Null).unwrap_err();
+        assert_eq!(error.code, INVALID_PARAMS);
+    }
+
+    #[test]
+    fn cookie_contains_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let token = generate_token();
+
+        let path = write_cookie(dir.path(), &token).unwrap();
+
+        assert_eq!(std::fs::read_to_string(path).unwrap(), token);
+    }
+}
@@ -14,6 +14,7 @@
 
 use anyhow::{bail, Context, Result};
 use comfy_table::Table;
+use libp2p::multiaddr::Protocol;
 use libp2p::Swarm;
 use monero_sys::Daemon;
 use rust_decimal::prelude::FromPrimitive;
@@ -21,13 +22,19 @@ use rust_decimal::Decimal;
 use std::convert::TryInto;
 use std::env;
 use std::sync::Arc;
+use std::time::Duration;
 use structopt::clap;
 use structopt::clap::ErrorKind;
 use swap::asb::command::{parse_args, Arguments, Command};
 use swap::asb::config::{
     initial_setup, query_user_for_initial_config, read_config, Config, ConfigNotInitialized,
 };
-use swap::asb::{cancel, punish, redeem, refund, safely_abort, EventLoop, Finality, KrakenRate};
+use swap::asb::rpc::{self, RpcContext, RunningSwaps};
+use swap::asb::{
+    cancel, onion, punish, redeem, refund, safely_abort, AggregatedRate, EventLoop, Finality,
+    PricingPolicy,
+};
+use swap::common::metrics;
 use swap::common::tor::init_tor_client;
 use swap::common::tracing_util::Format;
 use swap::common::{self, get_logs, warn_if_outdated};
@@ -36,9 +43,12 @@ use swap::network::rendezvous::XmrBtcNamespace;
 use swap::network::swarm;
 use swap::protocol::alice::swap::is_complete;
 use swap::protocol::alice::{run, AliceState};
-use swap::protocol::{Database, State};
+use swap::protocol::export;
+use swap::protocol::timeline;
+use swap::protocol::{Database, State, StateTransition};
 use swap::seed::Seed;
-use swap::{bitcoin, kraken, monero};
+use swap::{bitcoin, monero};
+use time::OffsetDateTime;
 use tracing_subscriber::filter::LevelFilter;
 use uuid::Uuid;
 
@@ -53,7 +63,11 @@ impl IntoDaemon for url::Url {
         let address = self.to_string();
         let ssl = self.scheme() == "https";
 
-        Ok(Daemon { address, ssl })
+        Ok(Daemon {
+            address,
+            ssl,
+            proxy: None,
+        })
     }
 }
 
@@ -62,7 +76,11 @@ impl IntoDaemon for monero_rpc_pool::ServerInfo {
         let address = format!("http://{}:{}", self.host, self.port);
         let ssl = false; // Pool server always uses HTTP locally
 
-        Ok(Daemon { address, ssl })
+        Ok(Daemon {
+            address,
+            ssl,
+            proxy: None,
+        })
     }
 }
 
@@ -132,9 +150,20 @@ pub async fn main() -> Result<()> {
         ));
     }
 
+    // Encrypting the seed must not ask for the passphrase of an already encrypted seed
+    if cmd == Command::EncryptSeed {
+        let seed_file = Seed::encrypt_file(&config.data.dir)?;
+        println!(
+            "Encrypted the seed file {}, its passphrase has to be entered whenever the asb starts",
+            seed_file.display()
+        );
+
+        return Ok(());
+    }
+
     let seed = Seed::from_file_or_generate(&config.data.dir, None)
         .await
-        .expect("Could not retrieve/initialize seed");
+        .context("Could not retrieve/initialize seed")?;
 
     let db_file = config.data.dir.join("sqlite");
 
@@ -158,14 +187,12 @@ pub async fn main() -> Result<()> {
 
             // Initialize Monero wallet
             let monero_wallet = init_monero_wallet(&config, env_config).await?;
-            let monero_address = monero_wallet.main_wallet().await.main_address().await;
+            let monero_address = monero_wallet.main_address().await;
             tracing::info!(%monero_address, "Monero wallet address");
 
             // Check Monero balance
-            let wallet = monero_wallet.main_wallet().await;
-
-            let total = wallet.total_balance().await.as_pico();
-            let unlocked = wallet.unlocked_balance().await.as_pico();
+            let total = monero_wallet.total_balance().await.as_pico();
+            let unlocked = monero_wallet.unlocked_balance().await.as_pico();
 
             match (total, unlocked) {
                 (0, _) => {
@@ -189,24 +216,29 @@ pub async fn main() -> Result<()> {
             }
 
             // Initialize Bitcoin wallet
-            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, false).await?;
+            let bitcoin_wallet =
+                Arc::new(init_bitcoin_wallet(&config, &seed, env_config, false).await?);
             let bitcoin_balance = bitcoin_wallet.balance().await?;
             tracing::info!(%bitcoin_balance, "Bitcoin wallet balance");
 
-            // Connect to Kraken
-            let kraken_price_updates = kraken::connect(config.maker.price_ticker_ws_url.clone())?;
-
-            let kraken_rate = KrakenRate::new(config.maker.ask_spread, kraken_price_updates);
+            // Connect to the price sources
+            let latest_rate = AggregatedRate::from_config(&config.maker)
+                .context("Failed to connect to price sources")?;
             let namespace = XmrBtcNamespace::from_is_testnet(testnet);
 
+            let onion_only = config.tor.onion_only;
+            if onion_only && !config.tor.register_hidden_service {
+                bail!("`tor.onion_only` requires `tor.register_hidden_service` to be enabled");
+            }
+
             // Initialize Tor client
             let tor_client = init_tor_client(&config.data.dir, None).await?.into();
 
-            let (mut swarm, onion_addresses) = swarm::asb(
+            let (mut swarm, mut onion_service) = swarm::asb(
                 &seed,
                 config.maker.min_buy_btc,
                 config.maker.max_buy_btc,
-                kraken_rate.clone(),
+                latest_rate.clone(),
                 resume_only,
                 env_config,
                 namespace,
@@ -214,25 +246,34 @@ pub async fn main() -> Result<()> {
                 tor_client,
                 config.tor.register_hidden_service,
                 config.tor.hidden_service_num_intro_points,
+                onion_only,
+                &config.network.peer_limits,
             )?;
 
-            for listen in config.network.listen.clone() {
-                if let Err(e) = Swarm::listen_on(&mut swarm, listen.clone()) {
-                    tracing::warn!("Failed to listen on network interface {}: {}. Consider removing it from the config.", listen, e);
+            if onion_only {
+                tracing::info!("Running onion-only, not listening on any network interface");
+            } else {
+                for listen in config.network.listen.clone() {
+                    if let Err(e) = Swarm::listen_on(&mut swarm, listen.clone()) {
+                        tracing::warn!("Failed to listen on network interface {}: {}. Consider removing it from the config.", listen, e);
+                    }
                 }
             }
 
-            for onion_address in onion_addresses {
-                match swarm.listen_on(onion_address.clone()) {
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to listen on onion address {}: {}",
-                            onion_address,
-                            e
-                        );
+            let mut onion_address = None;
+            if let Some(service) = onion_service.as_mut() {
+                match service.launch(&mut swarm).await {
+                    Ok(address) => {
+                        tracing::info!(%address, "Listening on onion service");
+                        swarm.add_external_address(address.clone());
+                        onion_address = Some((address, service.tor_client()));
+                    }
+                    Err(error) if onion_only => {
+                        return Err(error.context("Failed to launch onion service"));
                     }
-                    _ => {
-                        swarm.add_external_address(onion_address);
+                    Err(error) => {
+                        tracing::warn!("Failed to listen on onion service: {:#}", error);
+                        onion_service = None;
                     }
                 }
             }
@@ -240,27 +281,86 @@ pub async fn main() -> Result<()> {
             tracing::info!(peer_id = %swarm.local_peer_id(), "Network layer initialized");
 
             for external_address in config.network.external_addresses {
+                let is_onion = external_address
+                    .iter()
+                    .any(|protocol| matches!(protocol, Protocol::Onion3(_)));
+                if onion_only && !is_onion {
+                    tracing::warn!(%external_address, "Not advertising clearnet address because we are running onion-only");
+                    continue;
+                }
+
                 swarm.add_external_address(external_address);
             }
 
             let (event_loop, mut swap_receiver) = EventLoop::new(
                 swarm,
                 env_config,
-                Arc::new(bitcoin_wallet),
+                bitcoin_wallet.clone(),
                 monero_wallet.clone(),
-                db,
-                kraken_rate.clone(),
+                db.clone(),
+                latest_rate.clone(),
+                PricingPolicy::new(config.maker.pricing.clone()),
                 config.maker.min_buy_btc,
                 config.maker.max_buy_btc,
                 config.maker.external_bitcoin_redeem_address,
             )
             .unwrap();
+            let event_loop = event_loop.with_onion_service(onion_service);
+
+            if let Some((address, tor_client)) = onion_address {
+                tokio::spawn(onion::health_check(
+                    tor_client,
+                    address,
+                    Duration::from_secs(config.tor.health_check_interval_secs),
+                    event_loop.service(),
+                ));
+            }
+
+            let running_swaps = RunningSwaps::default();
+
+            if let Some(rpc) = &config.rpc {
+                rpc::serve(
+                    rpc.listen,
+                    &config.data.dir,
+                    RpcContext {
+                        env_config,
+                        bitcoin_wallet: bitcoin_wallet.clone(),
+                        monero_wallet: monero_wallet.clone(),
+                        db: db.clone(),
+                        event_loop: event_loop.service(),
+                        running_swaps: running_swaps.clone(),
+                    },
+                )
+                .await
+                .context("Failed to start RPC server")?;
+            }
+
+            if let Some(metrics) = &config.metrics {
+                metrics::serve(metrics.listen)
+                    .await
+                    .context("Failed to start metrics endpoint")?;
+
+                tokio::spawn(metrics::collect(
+                    Some(bitcoin_wallet.clone()),
+                    Some(monero_wallet.clone()),
+                    Some(db.clone()),
+                ));
+            }
+
+            if let Some(fee_bumping) = &config.bitcoin.fee_bumping {
+                tokio::spawn(bitcoin::fee_bump::run(
+                    bitcoin_wallet.clone(),
+                    db.clone(),
+                    fee_bumping.policy()?,
+                ));
+            }
 
             tokio::spawn(async move {
                 while let Some(swap) = swap_receiver.recv().await {
-                    let rate = kraken_rate.clone();
-                    tokio::spawn(async move {
-                        let swap_id = swap.swap_id;
+                    let rate = latest_rate.clone();
+                    let swap_id = swap.swap_id;
+                    let running = running_swaps.clone();
+                    let handle = tokio::spawn(async move {
                         match run(swap, rate).await {
                             Ok(state) => {
                                 tracing::debug!(%swap_id, final_state=%state, "Swap completed")
@@ -269,7 +369,9 @@ pub async fn main() -> Result<()> {
                                 tracing::error!(%swap_id, "Swap failed: {:#}", error)
                             }
                         }
+                        running.remove(swap_id);
                     });
+                    running_swaps.insert(swap_id, handle.abort_handle());
                 }
             });
 
@@ -284,9 +386,13 @@ pub async fn main() -> Result<()> {
                 "Start Date",
                 "State",
                 "Bitcoin Lock TxId",
+                "BTC Lock Seen",
+                "XMR Locked",
+                "Duration",
                 "BTC Amount",
                 "XMR Amount",
                 "Exchange Rate",
+                "Spread",
                 "Taker Peer ID",
                 "Completed",
             ]);
@@ -319,35 +425,63 @@ pub async fn main() -> Result<()> {
                 println!("{}", table);
             }
         }
+        Command::ExportHistory {
+            format,
+            range,
+            output,
+        } => {
+            let db = open_db(db_file, AccessMode::ReadOnly, None).await?;
+
+            let swaps = export::swap_records(db.as_ref(), range).await?;
+            let content = export::render(&swaps, format)?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, content).with_context(|| {
+                        format!("Failed to write the export to {}", path.display())
+                    })?;
+                    tracing::info!(
+                        swaps = swaps.len(),
+                        path = %path.display(),
+                        "Exported swap history"
+                    );
+                }
+                None => print!("{}", content),
+            }
+        }
         Command::Config => {
             let config_json = serde_json::to_string_pretty(&config)?;
             println!("{}", config_json);
         }
         Command::Logs {
             logs_dir,
-            swap_id,
+            filter,
             redact,
         } => {
             let dir = logs_dir.unwrap_or(config.data.dir.join("logs"));
 
-            let log_messages = get_logs(dir, swap_id, redact).await?;
+            let log_messages = get_logs(dir, filter, redact).await?;
 
             for msg in log_messages {
                 println!("{msg}");
             }
         }
-        Command::WithdrawBtc { amount, address } => {
+        Command::WithdrawBtc {
+            amount,
+            address,
+            inputs,
+        } => {
             let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
 
             let withdraw_tx_unsigned = match amount {
                 Some(amount) => {
                     bitcoin_wallet
-                        .send_to_address_dynamic_fee(address, amount, None)
+                        .send_to_address_dynamic_fee(address, amount, None, inputs.as_deref())
                         .await?
                 }
                 None => {
                     bitcoin_wallet
-                        .sweep_balance_to_address_dynamic_fee(address)
+                        .sweep_balance_to_address_dynamic_fee(address, inputs.as_deref())
                         .await?
                 }
             };
@@ -358,9 +492,61 @@ pub async fn main() -> Result<()> {
 
             bitcoin_wallet.broadcast(signed_tx, "withdraw").await?;
         }
+        Command::ListUnspent => {
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
+
+            for utxo in bitcoin_wallet.list_unspent().await? {
+                tracing::info!(
+                    outpoint = %utxo.outpoint,
+                    amount = %utxo.amount,
+                    confirmations = utxo.confirmations,
+                    address = %utxo.address,
+                    label = utxo.label.as_deref().unwrap_or(""),
+                    frozen = utxo.frozen,
+                    "Unspent output",
+                );
+            }
+        }
+        Command::BitcoinTransactions => {
+            let db = open_db(db_file, AccessMode::ReadOnly, None).await?;
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
+
+            let mut transactions = bitcoin_wallet.transaction_history().await?;
+            swap::bitcoin::link_to_swaps(&mut transactions, db.as_ref()).await?;
+
+            for transaction in transactions {
+                tracing::info!(
+                    txid = %transaction.txid,
+                    direction = ?transaction.direction,
+                    amount = %transaction.amount,
+                    fee = ?transaction.fee,
+                    confirmation_height = ?transaction.confirmation_height,
+                    timestamp = ?transaction.timestamp,
+                    swap_id = ?transaction.swap_id,
+                    swap_transaction = transaction.swap_transaction.as_deref().unwrap_or(""),
+                    "Wallet transaction",
+                );
+            }
+        }
+        Command::LabelUtxo { outpoint, label } => {
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
+
+            bitcoin_wallet
+                .set_utxo_label(outpoint, label.clone())
+                .await?;
+
+            tracing::info!(%outpoint, ?label, "Updated label of unspent output");
+        }
+        Command::FreezeUtxo { outpoint, frozen } => {
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
+
+            bitcoin_wallet.set_utxo_frozen(outpoint, frozen).await?;
+
+            tracing::info!(%outpoint, frozen, "Updated unspent output");
+        }
         Command::Balance => {
             let monero_wallet = init_monero_wallet(&config, env_config).await?;
-            let monero_balance = monero_wallet.main_wallet().await.total_balance().await;
+            let monero_balance = monero_wallet.total_balance().await;
             tracing::info!(%monero_balance);
 
             let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
@@ -396,6 +582,24 @@ pub async fn main() -> Result<()> {
 
             tracing::info!("Punish transaction successfully published with id {}", txid);
         }
+        Command::BumpFee { swap_id, fee_rate } => {
+            let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
+
+            let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, true).await?;
+
+            let fee_rate = fee_rate
+                .map(|sat_per_vb| {
+                    ::bitcoin::FeeRate::from_sat_per_vb(sat_per_vb).context("Fee rate is too high")
+                })
+                .transpose()?;
+
+            let reports =
+                bitcoin::bump_swap(swap_id, &bitcoin_wallet, db.as_ref(), fee_rate).await?;
+
+            for report in reports {
+                tracing::info!("{}", report);
+            }
+        }
         Command::SafelyAbort { swap_id } => {
             let db = open_db(db_file, AccessMode::ReadWrite, None).await?;
 
@@ -421,6 +625,7 @@ pub async fn main() -> Result<()> {
 
             tracing::info!("Redeem transaction successfully published with id {}", txid);
         }
+        Command::EncryptSeed => unreachable!("The seed is encrypted before it is read"),
         Command::ExportBitcoinWallet => {
             let bitcoin_wallet = init_bitcoin_wallet(&config, &seed, env_config, false).await?;
             let wallet_export = bitcoin_wallet.wallet_export("asb").await?;
@@ -428,7 +633,7 @@ pub async fn main() -> Result<()> {
         }
         Command::ExportMoneroWallet => {
             let monero_wallet = init_monero_wallet(&config, env_config).await?;
-            let main_wallet = monero_wallet.main_wallet().await;
+            let main_wallet = monero_wallet.main_wallet().await?;
 
             let seed = main_wallet.seed().await;
             let creation_height = main_wallet.creation_height().await;
@@ -436,6 +641,40 @@ pub async fn main() -> Result<()> {
             println!("Seed          : {seed}");
             println!("Restore height: {creation_height}");
         }
+        Command::MoneroSigner {
+            signing_dir,
+            max_amount,
+            max_total_amount,
+            window_hours,
+        } => {
+            if config.monero.offline_signing.is_some() {
+                bail!("The signer needs the full Monero wallet, remove [monero.offline_signing] from its config");
+            }
+
+            let policy = monero::offline_signing::SigningPolicy::new(
+                &config.data.dir.join("monero-signer"),
+                max_amount.map(Into::into),
+                max_total_amount.map(|max_total| monero::offline_signing::SigningLimit {
+                    max_total: max_total.into(),
+                    window: Duration::from_secs(window_hours * 60 * 60),
+                }),
+            )?;
+
+            let monero_wallet = init_monero_wallet(&config, env_config).await?;
+            let main_wallet = monero_wallet.main_wallet().await?;
+
+            // The asb needs these to create its view-only wallet
+            println!("Address       : {}", main_wallet.main_address().await);
+            println!("View key      : {}", main_wallet.secret_view_key().await?);
+            println!("Restore height: {}", main_wallet.creation_height().await);
+            println!("Manifest key  : {}", policy.manifest_key());
+
+            let signing_dir = monero::offline_signing::SigningDir::new(signing_dir)?;
+
+            tracing::info!(dir=%signing_dir.path().display(), "Waiting for Monero transfers to sign");
+
+            monero::offline_signing::run_signer(&main_wallet, &signing_dir, &policy).await?;
+        }
     }
 
     Ok(())
@@ -520,16 +759,44 @@ async fn init_monero_wallet(
             .context("Failed to convert daemon URL to Daemon")?
     };
 
-    let manager = monero::Wallets::new(
-        config.data.dir.join("monero/wallets"),
-        DEFAULT_WALLET_NAME.to_string(),
-        daemon,
-        env_config.monero_network,
-        false,
-        None,
-    )
-    .await
-    .context("Failed to initialize Monero wallets")?;
+    let manager = match &config.monero.offline_signing {
+        Some(offline_signing) => {
+            tracing::info!(
+                signing_dir=%offline_signing.signing_dir.display(),
+                "Using a view-only Monero wallet, transfers are signed offline"
+            );
+
+            let offline_signer = monero::offline_signing::OfflineSigner::new(
+                offline_signing.signing_dir.clone(),
+                Duration::from_secs(offline_signing.signing_timeout_secs),
+                offline_signing.manifest_key.clone(),
+            )?;
+
+            monero::Wallets::new_view_only(
+                config.data.dir.join("monero/wallets"),
+                format!("{}-view-only", DEFAULT_WALLET_NAME),
+                daemon,
+                env_config.monero_network,
+                offline_signing.address,
+                offline_signing.view_key,
+                offline_signing.restore_height,
+                offline_signer,
+                None,
+            )
+            .await
+            .context("Failed to initialize view-only Monero wallets")?
+        }
+        None => monero::Wallets::new(
+            config.data.dir.join("monero/wallets"),
+            DEFAULT_WALLET_NAME.to_string(),
+            daemon,
+            env_config.monero_network,
+            false,
+            None,
+        )
+        .await
+        .context("Failed to initialize Monero wallets")?,
+    };
 
     Ok(Arc::new(manager))
 }
@@ -541,9 +808,13 @@ struct SwapDetails {
     start_date: String,
     state: String,
     btc_lock_txid: String,
+    btc_lock_seen_at: String,
+    xmr_locked_at: String,
+    duration: String,
     btc_amount: String,
     xmr_amount: String,
     exchange_rate: String,
+    spread: String,
     peer_id: String,
     completed: bool,
 }
@@ -556,33 +827,76 @@ impl SwapDetails {
     ) -> Result<Self> {
         let completed = is_complete(&latest_state);
 
-        let all_states = db.get_states(swap_id).await?;
-        let state3 = all_states
+        let transitions = db.get_state_transitions(swap_id).await?;
+        let state3 = transitions
             .iter()
-            .find_map(|s| match s {
+            .find_map(|transition| match &transition.state {
                 State::Alice(AliceState::BtcLockTransactionSeen { state3 }) => Some(state3),
                 _ => None,
             })
             .context("Failed to get \"BtcLockTransactionSeen\" state")?;
 
+        let btc_lock_seen_at = Self::format_entered_at(&transitions, |state| {
+            matches!(
+                state,
+                State::Alice(AliceState::BtcLockTransactionSeen { .. })
+            )
+        })?;
+        let xmr_locked_at = Self::format_entered_at(&transitions, |state| {
+            matches!(state, State::Alice(AliceState::XmrLocked { .. }))
+        })?;
+        // Ongoing swaps are measured until now
+        let duration = match (transitions.first(), transitions.last()) {
+            (Some(first), Some(last)) => {
+                let until = if completed {
+                    last.entered_at
+                } else {
+                    OffsetDateTime::now_utc()
+                };
+                timeline::format_duration_ms(timeline::duration_ms(first.entered_at, until))
+            }
+            _ => "-".to_string(),
+        };
+
         let exchange_rate = Self::calculate_exchange_rate(state3.btc, state3.xmr)?;
         let start_date = db.get_swap_start_date(swap_id).await?;
         let btc_lock_txid = state3.tx_lock.txid();
         let peer_id = db.get_peer_id(swap_id).await?;
+        // Swaps started before we recorded the rate do not have a spread stored
+        let spread = db
+            .get_swap_rate(swap_id)
+            .await?
+            .map(|rate| format!("{}%", (rate.spread() * Decimal::ONE_HUNDRED).normalize()))
+            .unwrap_or_else(|| "-".to_string());
 
         Ok(Self {
             swap_id: swap_id.to_string(),
             start_date: start_date.to_string(),
             state: latest_state.to_string(),
             btc_lock_txid: btc_lock_txid.to_string(),
+            btc_lock_seen_at,
+            xmr_locked_at,
+            duration,
             btc_amount: state3.btc.to_string(),
             xmr_amount: state3.xmr.to_string(),
             exchange_rate,
+            spread,
             peer_id: peer_id.to_string(),
             completed,
         })
     }
 
+    /// When the swap first entered a state matching `predicate`, `-` if it never did.
+    fn format_entered_at(
+        transitions: &[StateTransition],
+        predicate: impl Fn(&State) -> bool,
+    ) -> Result<String> {
+        match timeline::first_entered_at(transitions, predicate) {
+            Some(entered_at) => timeline::format_timestamp(entered_at),
+            None => Ok("-".to_string()),
+        }
+    }
+
     fn calculate_exchange_rate(btc: bitcoin::Amount, xmr: monero::Amount) -> Result<String> {
         let btc_decimal = Decimal::from_f64(btc.to_btc())
             .ok_or_else(|| anyhow::anyhow!("Failed to convert BTC amount to Decimal"))?;
@@ -602,9 +916,13 @@ impl SwapDetails {
             self.start_date.clone(),
             self.state.clone(),
             self.btc_lock_txid.clone(),
+            self.btc_lock_seen_at.clone(),
+            self.xmr_locked_at.clone(),
+            self.duration.clone(),
             self.btc_amount.clone(),
             self.xmr_amount.clone(),
             self.exchange_rate.clone(),
+            self.spread.clone(),
             self.peer_id.clone(),
             self.completed.to_string(),
         ]
@@ -616,9 +934,13 @@ impl SwapDetails {
             swap_start_date = %self.start_date,
             latest_state = %self.state,
             btc_lock_txid = %self.btc_lock_txid,
+            btc_lock_seen_at = %self.btc_lock_seen_at,
+            xmr_locked_at = %self.xmr_locked_at,
+            duration = %self.duration,
             btc_amount = %self.btc_amount,
             xmr_amount = %self.xmr_amount,
             exchange_rate = %self.exchange_rate,
+            spread = %self.spread,
             taker_peer_id = %self.peer_id,
             completed = self.completed,
             "Found swap in database"
@@ -1,7 +1,12 @@
+pub mod fee_bump;
+#[cfg(feature = "test-utils")]
+pub mod simulated;
 pub mod wallet;
 
 mod cancel;
+mod coin_control;
 mod early_refund;
+mod history;
 mod lock;
 mod punish;
 mod redeem;
@@ -9,7 +14,12 @@ mod refund;
 mod timelocks;
 
 pub use crate::bitcoin::cancel::{CancelTimelock, PunishTimelock, TxCancel};
+pub use crate::bitcoin::coin_control::Utxo;
 pub use crate::bitcoin::early_refund::TxEarlyRefund;
+pub use crate::bitcoin::fee_bump::{
+    bump_swap, bump_transactions, BumpOutcome, BumpReport, FeeBumpPolicy, SwapTransaction,
+};
+pub use crate::bitcoin::history::{link_to_swaps, TransactionDirection, WalletTransaction};
 pub use crate::bitcoin::lock::TxLock;
 pub use crate::bitcoin::punish::TxPunish;
 pub use crate::bitcoin::redeem::TxRedeem;
@@ -17,7 +27,7 @@ pub use crate::bitcoin::refund::TxRefund;
 pub use crate::bitcoin::timelocks::{BlockHeight, ExpiredTimelocks};
 pub use ::bitcoin::amount::Amount;
 pub use ::bitcoin::psbt::Psbt as PartiallySignedTransaction;
-pub use ::bitcoin::{Address, AddressType, Network, Transaction, Txid};
+pub use ::bitcoin::{Address, AddressType, Network, OutPoint, Transaction, Txid};
 pub use ecdsa_fun::adaptor::EncryptedSignature;
 pub use ecdsa_fun::fun::Scalar;
 pub use ecdsa_fun::Signature;
@@ -663,6 +673,7 @@ mod tests {
             spending_fee,
             spending_fee,
             tx_lock_fee,
+            None,
         );
 
         let message0 = bob_state0.next_message();
@@ -768,6 +779,7 @@ mod tests {
             spending_fee,
             spending_fee,
             spending_fee,
+            None,
         );
 
         // Complete the state machine up to State3
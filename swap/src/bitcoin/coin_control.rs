@@ -0,0 +1,208 @@
+//! Labels and freezes for the UTXOs of our Bitcoin wallet.
+//!
+//! bdk knows about neither, so we keep them in a small JSON file next to the
+//! wallet database. Frozen UTXOs are never selected automatically, they can
+//! only be spent by selecting them explicitly after unfreezing them.
+
+use crate::bitcoin::Amount;
+use anyhow::{Context, Result};
+use bitcoin::OutPoint;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use typeshare::typeshare;
+
+/// An unspent output of our wallet.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Utxo {
+    #[typeshare(serialized_as = "string")]
+    pub outpoint: OutPoint,
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub amount: Amount,
+    /// Zero if the UTXO is still unconfirmed.
+    pub confirmations: u32,
+    pub address: String,
+    pub label: Option<String>,
+    pub frozen: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UtxoInfo {
+    pub label: Option<String>,
+    pub frozen: bool,
+}
+
+/// The format of a single UTXO in the coin control file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    outpoint: OutPoint,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(default)]
+    frozen: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct CoinControl {
+    /// Where we persist labels and freezes, `None` keeps them in memory only.
+    path: Option<PathBuf>,
+    utxos: BTreeMap<OutPoint, UtxoInfo>,
+}
+
+impl CoinControl {
+    pub const FILE_NAME: &'static str = "coin-control.json";
+
+    /// Loads the coin control file at `path`, starting out empty if it does not exist yet.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let utxos = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<Vec<Entry>>(&bytes)
+                .with_context(|| format!("Failed to parse coin control file {}", path.display()))?
+                .into_iter()
+                .map(|entry| {
+                    let info = UtxoInfo {
+                        label: entry.label,
+                        frozen: entry.frozen,
+                    };
+
+                    (entry.outpoint, info)
+                })
+                .collect(),
+            Err(error) if error.kind() == ErrorKind::NotFound => BTreeMap::new(),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!("Failed to read coin control file {}", path.display())
+                })
+            }
+        };
+
+        Ok(Self {
+            path: Some(path),
+            utxos,
+        })
+    }
+
+    pub fn get(&self, outpoint: &OutPoint) -> UtxoInfo {
+        self.utxos.get(outpoint).cloned().unwrap_or_default()
+    }
+
+    pub fn is_frozen(&self, outpoint: &OutPoint) -> bool {
+        self.get(outpoint).frozen
+    }
+
+    pub fn frozen(&self) -> Vec<OutPoint> {
+        self.utxos
+            .iter()
+            .filter(|(_, info)| info.frozen)
+            .map(|(outpoint, _)| *outpoint)
+            .collect()
+    }
+
+    /// Sets the label of a UTXO. An empty label removes it.
+    pub fn set_label(&mut self, outpoint: OutPoint, label: Option<String>) -> Result<()> {
+        let label = label
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty());
+
+        self.update(outpoint, |info| info.label = label)
+    }
+
+    pub fn set_frozen(&mut self, outpoint: OutPoint, frozen: bool) -> Result<()> {
+        self.update(outpoint, |info| info.frozen = frozen)
+    }
+
+    fn update(&mut self, outpoint: OutPoint, change: impl FnOnce(&mut UtxoInfo)) -> Result<()> {
+        let mut info = self.get(&outpoint);
+        change(&mut info);
+
+        if info == UtxoInfo::default() {
+            self.utxos.remove(&outpoint);
+        } else {
+            self.utxos.insert(outpoint, info);
+        }
+
+        self.persist()
+    }
+
+    fn persist(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let entries = self
+            .utxos
+            .iter()
+            .map(|(outpoint, info)| Entry {
+                outpoint: *outpoint,
+                label: info.label.clone(),
+                frozen: info.frozen,
+            })
+            .collect::<Vec<_>>();
+
+        // Write to a temporary file first such that we never leave a half written file behind
+        let temp_path = path.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(&entries)?)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        std::fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to write coin control file {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn outpoint(vout: u32) -> OutPoint {
+        OutPoint::from_str(&format!(
+            "0000000000000000000000000000000000000000000000000000000000000001:{}",
+            vout
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn labels_and_freezes_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CoinControl::FILE_NAME);
+
+        let mut coin_control = CoinControl::load(&path).unwrap();
+        coin_control
+            .set_label(outpoint(0), Some("swap proceeds".to_string()))
+            .unwrap();
+        coin_control.set_frozen(outpoint(1), true).unwrap();
+
+        let coin_control = CoinControl::load(&path).unwrap();
+
+        assert_eq!(
+            coin_control.get(&outpoint(0)),
+            UtxoInfo {
+                label: Some("swap proceeds".to_string()),
+                frozen: false,
+            }
+        );
+        assert_eq!(coin_control.frozen(), vec![outpoint(1)]);
+    }
+
+    #[test]
+    fn clearing_label_and_freeze_forgets_utxo() {
+        let mut coin_control = CoinControl::default();
+
+        coin_control
+            .set_label(outpoint(0), Some("kyc".to_string()))
+            .unwrap();
+        coin_control.set_frozen(outpoint(0), true).unwrap();
+        coin_control
+            .set_label(outpoint(0), Some("  ".to_string()))
+            .unwrap();
+        coin_control.set_frozen(outpoint(0), false).unwrap();
+
+        assert!(coin_control.utxos.is_empty());
+    }
+}
@@ -0,0 +1,449 @@
+//! Fee bumping for the Bitcoin transactions of a swap.
+//!
+//! Apart from the lock transaction, all swap transactions are signed by both
+//! parties and their signatures commit to the fee. We therefore cannot replace
+//! them on our own. Instead we spend the output that pays to our wallet with a
+//! child transaction which pays for the whole unconfirmed package
+//! (child-pays-for-parent). If we already published such a child, we replace
+//! it with one that pays more (replace-by-fee).
+//!
+//! The lock transaction is only bumped through its change output. It is funded
+//! by our wallet, but replacing it (RBF) would change its txid and therefore
+//! invalidate the presigned cancel, refund and punish transactions which spend
+//! it.
+//!
+//! We also do not re-sign the presigned transactions at a higher fee rate. The
+//! signatures of the counterparty (and the encrypted signatures of the redeem
+//! and refund transactions) commit to the fee, so a new variant would require
+//! both parties to sign it. The swap setup protocol only exchanges signatures
+//! for a single fee rate, so this is not possible without extending it. A
+//! transaction without an output to our wallet (e.g. the cancel transaction)
+//! can therefore only be bumped together with a descendant that pays to us.
+
+use crate::bitcoin::wallet::{ScriptStatus, Watchable};
+use crate::bitcoin::{Amount, Transaction, Txid, Wallet};
+use crate::protocol::Database;
+use anyhow::{Context, Result};
+use bdk_wallet::bitcoin::{FeeRate, ScriptBuf};
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use typeshare::typeshare;
+use uuid::Uuid;
+
+/// The fee rate increment (in sat/vB) a replacement has to pay on top of the
+/// transaction it replaces (BIP125 rule 4).
+const INCREMENTAL_RELAY_FEE_SAT_VB: u64 = 1;
+
+/// A Bitcoin transaction of a swap we may have to get confirmed.
+#[derive(Debug, Clone)]
+pub struct SwapTransaction {
+    pub kind: &'static str,
+    pub txid: Txid,
+    script: ScriptBuf,
+}
+
+impl SwapTransaction {
+    pub fn new(kind: &'static str, tx: &impl Watchable) -> Self {
+        Self {
+            kind,
+            txid: tx.id(),
+            script: tx.script(),
+        }
+    }
+}
+
+impl Watchable for SwapTransaction {
+    fn id(&self) -> Txid {
+        self.txid
+    }
+
+    fn script(&self) -> ScriptBuf {
+        self.script.clone()
+    }
+}
+
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", content = "content")]
+pub enum BumpOutcome {
+    /// The transaction is already confirmed.
+    Confirmed,
+    /// The transaction has not been published (yet).
+    NotPublished,
+    /// The unconfirmed package already pays at least the target fee rate.
+    Sufficient {
+        #[typeshare(serialized_as = "number")]
+        fee_rate_sat_vb: u64,
+    },
+    /// We published a child transaction to bump the fee of the package.
+    Bumped {
+        #[typeshare(serialized_as = "string")]
+        child_txid: Txid,
+        #[typeshare(serialized_as = "number")]
+        fee_rate_sat_vb: u64,
+    },
+    /// We cannot bump the fee of this transaction ourselves.
+    NotBumpable { reason: String },
+}
+
+#[typeshare]
+#[derive(Debug, Clone, Serialize)]
+pub struct BumpReport {
+    /// Which transaction of the swap this is, e.g. `lock` or `cancel`.
+    #[typeshare(serialized_as = "string")]
+    pub kind: &'static str,
+    #[typeshare(serialized_as = "string")]
+    pub txid: Txid,
+    pub outcome: BumpOutcome,
+}
+
+impl std::fmt::Display for BumpReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} transaction {}: ", self.kind, self.txid)?;
+
+        match &self.outcome {
+            BumpOutcome::Confirmed => write!(f, "confirmed"),
+            BumpOutcome::NotPublished => write!(f, "not published"),
+            BumpOutcome::Sufficient { fee_rate_sat_vb } => {
+                write!(f, "already pays {} sat/vB", fee_rate_sat_vb)
+            }
+            BumpOutcome::Bumped {
+                child_txid,
+                fee_rate_sat_vb,
+            } => write!(
+                f,
+                "bumped to {} sat/vB with child transaction {}",
+                fee_rate_sat_vb, child_txid
+            ),
+            BumpOutcome::NotBumpable { reason } => write!(f, "cannot be bumped: {}", reason),
+        }
+    }
+}
+
+/// Governs how the fees of pending swap transactions are bumped automatically.
+#[derive(Debug, Clone, Copy)]
+pub struct FeeBumpPolicy {
+    /// How often we check whether a transaction needs a fee bump.
+    pub interval: Duration,
+    /// We never bump the fee of a package beyond this fee rate.
+    pub max_fee_rate: FeeRate,
+}
+
+impl Default for FeeBumpPolicy {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(10 * 60),
+            max_fee_rate: FeeRate::from_sat_per_vb_unchecked(100),
+        }
+    }
+}
+
+impl FeeBumpPolicy {
+    fn target_fee_rate(&self, estimate: FeeRate) -> FeeRate {
+        estimate.min(self.max_fee_rate)
+    }
+}
+
+/// The virtual size and fee of a set of unconfirmed transactions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Package {
+    vsize: u64,
+    fee: Amount,
+}
+
+impl Package {
+    async fn of(wallet: &Wallet, tx: &Transaction) -> Result<Self> {
+        let mut input_value = Amount::ZERO;
+
+        for input in &tx.input {
+            let previous_output = input.previous_output;
+            let previous_tx = wallet
+                .get_raw_transaction(previous_output.txid)
+                .await?
+                .with_context(|| format!("Could not find transaction {}", previous_output.txid))?;
+            let txout = previous_tx
+                .output
+                .get(previous_output.vout as usize)
+                .with_context(|| format!("Transaction has no output {}", previous_output))?;
+
+            input_value += txout.value;
+        }
+
+        let output_value = tx.output.iter().map(|output| output.value).sum();
+
+        Ok(Self {
+            vsize: tx.vsize() as u64,
+            fee: input_value
+                .checked_sub(output_value)
+                .context("Transaction spends more than its inputs")?,
+        })
+    }
+
+    fn fee_rate_sat_vb(&self) -> u64 {
+        self.fee.to_sat() / self.vsize.max(1)
+    }
+
+    fn pays(&self, fee_rate: FeeRate) -> bool {
+        fee_rate
+            .fee_vb(self.vsize)
+            .is_some_and(|required| self.fee >= required)
+    }
+}
+
+impl std::ops::Add for Package {
+    type Output = Package;
+
+    fn add(self, other: Package) -> Package {
+        Package {
+            vsize: self.vsize + other.vsize,
+            fee: self.fee + other.fee,
+        }
+    }
+}
+
+/// Makes sure the unconfirmed `transactions` pay at least `fee_rate`.
+///
+/// The transactions are expected in order, i.e. each transaction may only
+/// spend the ones before it. Unconfirmed transactions without an output to our
+/// wallet are bumped together with their descendants.
+pub async fn bump_transactions(
+    wallet: &Wallet,
+    transactions: Vec<(SwapTransaction, ScriptStatus)>,
+    fee_rate: FeeRate,
+) -> Result<Vec<BumpReport>> {
+    let mut ancestors = Package::default();
+    let mut reports = Vec::new();
+
+    for (transaction, status) in transactions {
+        let outcome = match status {
+            ScriptStatus::Confirmed(_) => {
+                ancestors = Package::default();
+                BumpOutcome::Confirmed
+            }
+            ScriptStatus::Unseen => BumpOutcome::NotPublished,
+            ScriptStatus::Retrying => BumpOutcome::NotBumpable {
+                reason: "could not determine the status of the transaction".to_string(),
+            },
+            ScriptStatus::InMempool => {
+                let tx = wallet
+                    .get_raw_transaction(transaction.txid)
+                    .await?
+                    .with_context(|| format!("Could not find transaction {}", transaction.txid))?;
+
+                let package = ancestors + Package::of(wallet, &tx).await?;
+                ancestors = package;
+
+                bump_package(wallet, &tx, package, fee_rate).await?
+            }
+        };
+
+        reports.push(BumpReport {
+            kind: transaction.kind,
+            txid: transaction.txid,
+            outcome,
+        });
+    }
+
+    Ok(reports)
+}
+
+async fn bump_package(
+    wallet: &Wallet,
+    tx: &Transaction,
+    package: Package,
+    fee_rate: FeeRate,
+) -> Result<BumpOutcome> {
+    let txid = tx.compute_txid();
+
+    let child = wallet.unconfirmed_child_of(txid).await;
+    let with_child = match &child {
+        Some((child, fee)) => {
+            package
+                + Package {
+                    vsize: child.vsize() as u64,
+                    fee: *fee,
+                }
+        }
+        None => package,
+    };
+
+    if with_child.pays(fee_rate) {
+        return Ok(BumpOutcome::Sufficient {
+            fee_rate_sat_vb: with_child.fee_rate_sat_vb(),
+        });
+    }
+
+    let bump = match child {
+        // Replace our previous child with one paying more
+        Some((child, old_fee)) => {
+            let child_vsize = child.vsize() as u64;
+            let required = fee_rate
+                .fee_vb(package.vsize + child_vsize)
+                .context("Fee overflow")?
+                .checked_sub(package.fee)
+                .unwrap_or(Amount::ZERO);
+            let minimum = old_fee + Amount::from_sat(INCREMENTAL_RELAY_FEE_SAT_VB * child_vsize);
+
+            wallet
+                .bump_fee(child.compute_txid(), required.max(minimum))
+                .await
+        }
+        None => wallet.cpfp(tx, package.vsize, package.fee, fee_rate).await,
+    };
+
+    let bump = match bump {
+        Ok(bump) => bump,
+        Err(error) => {
+            return Ok(BumpOutcome::NotBumpable {
+                reason: format!("{:#}", error),
+            })
+        }
+    };
+
+    let bumped = package + Package::of(wallet, &bump).await?;
+
+    let (child_txid, _) = wallet.broadcast(bump, "fee bump").await?;
+
+    Ok(BumpOutcome::Bumped {
+        child_txid,
+        fee_rate_sat_vb: bumped.fee_rate_sat_vb(),
+    })
+}
+
+/// Bumps the fees of the pending Bitcoin transactions of a swap.
+///
+/// If no `fee_rate` is given, we target the current fee estimate.
+pub async fn bump_swap(
+    swap_id: Uuid,
+    wallet: &Wallet,
+    db: &(dyn Database + Send + Sync),
+    fee_rate: Option<FeeRate>,
+) -> Result<Vec<BumpReport>> {
+    let state = db.get_state(swap_id).await?;
+    let transactions = state.transactions_to_confirm()?;
+
+    if transactions.is_empty() {
+        anyhow::bail!(
+            "Swap {} has no pending Bitcoin transaction whose fee could be bumped",
+            swap_id
+        );
+    }
+
+    let fee_rate = match fee_rate {
+        Some(fee_rate) => fee_rate,
+        None => wallet.current_fee_rate().await?,
+    };
+
+    let statuses = statuses_of(wallet, transactions).await?;
+
+    bump_transactions(wallet, statuses, fee_rate).await
+}
+
+/// Periodically bumps the fees of the pending Bitcoin transactions of all swaps.
+pub async fn run(wallet: Arc<Wallet>, db: Arc<dyn Database + Send + Sync>, policy: FeeBumpPolicy) {
+    loop {
+        if let Err(error) = bump_all(&wallet, db.as_ref(), policy).await {
+            tracing::warn!(%error, "Failed to bump fees of pending swap transactions");
+        }
+
+        tokio::time::sleep(policy.interval).await;
+    }
+}
+
+async fn bump_all(
+    wallet: &Wallet,
+    db: &(dyn Database + Send + Sync),
+    policy: FeeBumpPolicy,
+) -> Result<()> {
+    let fee_rate = policy.target_fee_rate(wallet.current_fee_rate().await?);
+
+    for (swap_id, state) in db.all().await? {
+        let transactions = match state.transactions_to_confirm() {
+            Ok(transactions) if !transactions.is_empty() => transactions,
+            Ok(_) => continue,
+            Err(error) => {
+                tracing::warn!(%swap_id, ?error, "Failed to construct swap transactions");
+                continue;
+            }
+        };
+
+        let reports = match statuses_of(wallet, transactions).await {
+            Ok(statuses) => bump_transactions(wallet, statuses, fee_rate).await,
+            Err(error) => Err(error),
+        };
+
+        match reports {
+            Ok(reports) => {
+                for report in reports {
+                    match report.outcome {
+                        BumpOutcome::Bumped { .. } => {
+                            tracing::info!(%swap_id, "Bumped fee of {}", report)
+                        }
+                        BumpOutcome::NotBumpable { .. } => {
+                            tracing::debug!(%swap_id, "Did not bump fee of {}", report)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Err(error) => {
+                tracing::warn!(%swap_id, ?error, "Failed to bump fees of swap transactions")
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn statuses_of(
+    wallet: &Wallet,
+    transactions: Vec<SwapTransaction>,
+) -> Result<Vec<(SwapTransaction, ScriptStatus)>> {
+    let mut statuses = Vec::with_capacity(transactions.len());
+
+    for transaction in transactions {
+        let status = wallet.status_of_script(&transaction).await?;
+        statuses.push((transaction, status));
+    }
+
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_fee_rate_is_capped_by_policy() {
+        let policy = FeeBumpPolicy {
+            max_fee_rate: FeeRate::from_sat_per_vb_unchecked(50),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            policy.target_fee_rate(FeeRate::from_sat_per_vb_unchecked(20)),
+            FeeRate::from_sat_per_vb_unchecked(20)
+        );
+        assert_eq!(
+            policy.target_fee_rate(FeeRate::from_sat_per_vb_unchecked(200)),
+            FeeRate::from_sat_per_vb_unchecked(50)
+        );
+    }
+
+    #[test]
+    fn package_includes_ancestors() {
+        let parent = Package {
+            vsize: 200,
+            fee: Amount::from_sat(200),
+        };
+        let child = Package {
+            vsize: 100,
+            fee: Amount::from_sat(2_800),
+        };
+
+        assert!(!parent.pays(FeeRate::from_sat_per_vb_unchecked(10)));
+        assert!((parent + child).pays(FeeRate::from_sat_per_vb_unchecked(10)));
+        assert_eq!((parent + child).fee_rate_sat_vb(), 10);
+    }
+}
@@ -0,0 +1,155 @@
+//! The transaction history of our Bitcoin wallet.
+
+use crate::bitcoin::{Amount, Txid};
+use crate::protocol::Database;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use typeshare::typeshare;
+use uuid::Uuid;
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionDirection {
+    /// Funds were sent to our wallet from elsewhere.
+    Incoming,
+    /// Funds left our wallet.
+    Outgoing,
+    /// All outputs pay back to our wallet, we only paid the fee.
+    SelfTransfer,
+}
+
+/// A transaction which touched our Bitcoin wallet.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletTransaction {
+    #[typeshare(serialized_as = "string")]
+    pub txid: Txid,
+    pub direction: TransactionDirection,
+    /// The amount that was received by or left the wallet, excluding the fee.
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub amount: Amount,
+    /// Only known if all inputs of the transaction are known to the wallet.
+    #[typeshare(serialized_as = "number")]
+    #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub fee: Option<Amount>,
+    /// `None` while the transaction is unconfirmed.
+    pub confirmation_height: Option<u32>,
+    /// The time of the block that confirmed the transaction, in seconds since the unix epoch.
+    #[typeshare(serialized_as = "number")]
+    pub timestamp: Option<u64>,
+    /// The swap this transaction belongs to, if any.
+    #[typeshare(serialized_as = "Option<string>")]
+    pub swap_id: Option<Uuid>,
+    /// Which transaction of the swap this is, e.g. `lock` or `refund`.
+    pub swap_transaction: Option<String>,
+}
+
+impl WalletTransaction {
+    /// Derives the direction and the amount of a transaction from what it
+    /// spent from and paid to our wallet.
+    pub fn new(
+        txid: Txid,
+        sent: Amount,
+        received: Amount,
+        fee: Option<Amount>,
+        confirmation_height: Option<u32>,
+        timestamp: Option<u64>,
+    ) -> Self {
+        let (direction, amount) = if sent == Amount::ZERO {
+            (TransactionDirection::Incoming, received)
+        } else {
+            // We paid the fee if we spent anything
+            let left_wallet = sent
+                .checked_sub(received)
+                .and_then(|left| left.checked_sub(fee.unwrap_or(Amount::ZERO)))
+                .unwrap_or(Amount::ZERO);
+
+            if left_wallet == Amount::ZERO {
+                (TransactionDirection::SelfTransfer, received)
+            } else {
+                (TransactionDirection::Outgoing, left_wallet)
+            }
+        };
+
+        Self {
+            txid,
+            direction,
+            amount,
+            fee,
+            confirmation_height,
+            timestamp,
+            swap_id: None,
+            swap_transaction: None,
+        }
+    }
+}
+
+/// Links each transaction to the swap it belongs to by comparing its txid
+/// with the transactions that can be constructed from the swap states in
+/// the database.
+pub async fn link_to_swaps(
+    transactions: &mut [WalletTransaction],
+    db: &(dyn Database + Send + Sync),
+) -> Result<()> {
+    let mut swap_transactions = HashMap::new();
+
+    for (swap_id, _) in db.all().await? {
+        for state in db.get_states(swap_id).await? {
+            match state.swap_transactions() {
+                Ok(transactions) => {
+                    for transaction in transactions {
+                        swap_transactions.insert(transaction.txid, (swap_id, transaction.kind));
+                    }
+                }
+                Err(error) => {
+                    tracing::warn!(%swap_id, "Failed to construct swap transactions: {:#}", error);
+                }
+            }
+        }
+    }
+
+    for transaction in transactions {
+        if let Some((swap_id, kind)) = swap_transactions.get(&transaction.txid) {
+            transaction.swap_id = Some(*swap_id);
+            transaction.swap_transaction = Some(kind.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bitcoin::hashes::Hash;
+
+    fn transaction(sent: u64, received: u64, fee: Option<u64>) -> WalletTransaction {
+        WalletTransaction::new(
+            Txid::all_zeros(),
+            Amount::from_sat(sent),
+            Amount::from_sat(received),
+            fee.map(Amount::from_sat),
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn direction_and_amount_exclude_the_fee() {
+        let deposit = transaction(0, 50_000, None);
+        assert_eq!(deposit.direction, TransactionDirection::Incoming);
+        assert_eq!(deposit.amount, Amount::from_sat(50_000));
+
+        // Spent 100k, 39k change back, 1k fee
+        let withdrawal = transaction(100_000, 39_000, Some(1_000));
+        assert_eq!(withdrawal.direction, TransactionDirection::Outgoing);
+        assert_eq!(withdrawal.amount, Amount::from_sat(60_000));
+
+        let consolidation = transaction(100_000, 99_000, Some(1_000));
+        assert_eq!(consolidation.direction, TransactionDirection::SelfTransfer);
+        assert_eq!(consolidation.amount, Amount::from_sat(99_000));
+    }
+}
@@ -32,6 +32,7 @@ impl TxLock {
         A: PublicKey,
         B: PublicKey,
         change: bitcoin::Address,
+        inputs: Option<&[OutPoint]>,
     ) -> Result<Self> {
         let lock_output_descriptor = build_shared_output_descriptor(A.0, B.0)?;
         let address = lock_output_descriptor
@@ -39,7 +40,7 @@ impl TxLock {
             .expect("can derive address from descriptor");
 
         let psbt = wallet
-            .send_to_address(address, amount, spending_fee, Some(change))
+            .send_to_address_from(address, amount, spending_fee, Some(change), inputs)
             .await?;
 
         Ok(Self {
@@ -294,7 +295,7 @@ mod tests {
         spending_fee: Amount,
     ) -> PartiallySignedTransaction {
         let change = wallet.new_address().await.unwrap();
-        TxLock::new(wallet, amount, spending_fee, A, B, change)
+        TxLock::new(wallet, amount, spending_fee, A, B, change, None)
             .await
             .unwrap()
             .into()
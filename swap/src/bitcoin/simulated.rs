@@ -0,0 +1,1082 @@
+//! An in-process Bitcoin blockchain for tests.
+//!
+//! [`Blockchain`] keeps its blocks and mempool in memory and serves them over the
+//! Electrum protocol on a local port, such that a regular [`Wallet`](super::Wallet)
+//! can be pointed at [`Blockchain::electrum_url`].
+//!
+//! Broadcast transactions are checked for double spends, missing inputs, fees and
+//! timelocks, but not for valid scripts or signatures. Blocks are only produced
+//! when asked to ([`Blockchain::mine`]) or by the miner started with
+//! [`Blockchain::start_mining`].
+
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use bitcoin::blockdata::constants::genesis_block;
+use bitcoin::consensus::encode::{deserialize, serialize_hex};
+use bitcoin::hashes::{sha256, sha256d, Hash};
+use bitcoin::script::Builder;
+use bitcoin::{
+    absolute, block, relative, transaction, Address, Amount, Block, BlockHash, CompactTarget,
+    FeeRate, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxMerkleNode, TxOut, Txid,
+    Witness,
+};
+use serde_json::{json, Value};
+use tokio_util::task::AbortOnDropHandle;
+
+/// The reward of every block, all of it goes to the faucet.
+const BLOCK_REWARD: Amount = Amount::from_sat(50 * 100_000_000);
+
+/// Maximum number of headers returned by `blockchain.block.headers`.
+const MAX_HEADERS: usize = 2016;
+
+/// Error codes of Bitcoin Core, see [`RpcErrorCode`](super::RpcErrorCode).
+const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+const RPC_VERIFY_ERROR: i64 = -25;
+const RPC_VERIFY_REJECTED: i64 = -26;
+const RPC_VERIFY_ALREADY_IN_CHAIN: i64 = -27;
+
+/// A Bitcoin blockchain which is kept in memory and served over the Electrum protocol.
+#[derive(Clone)]
+pub struct Blockchain {
+    chain: Arc<Mutex<Chain>>,
+    electrum_url: String,
+}
+
+struct Chain {
+    /// Index 0 is the genesis block.
+    blocks: Vec<Block>,
+    /// In the order the transactions were accepted, parents always come first.
+    mempool: Vec<Transaction>,
+    /// Returned by `blockchain.estimatefee`.
+    fee_rate: FeeRate,
+    /// Returned by `blockchain.relayfee`, broadcast transactions must pay at least this.
+    min_relay_fee: FeeRate,
+    /// If set, all broadcasts are rejected with this message.
+    broadcast_rejection: Option<String>,
+    /// Makes the coinbase of every block unique, even across reorgs.
+    coinbase_counter: i64,
+    /// The background miner does not produce blocks before this instant.
+    stalled_until: Option<Instant>,
+}
+
+/// A JSON-RPC error, sent to the client the way electrs forwards errors of bitcoind.
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl Blockchain {
+    /// Create a blockchain with only the genesis block of `network` and start
+    /// serving it on a random local port.
+    pub fn new(network: Network) -> Result<Self> {
+        let chain = Arc::new(Mutex::new(Chain {
+            blocks: vec![genesis_block(network)],
+            mempool: Vec::new(),
+            fee_rate: FeeRate::from_sat_per_vb_unchecked(2),
+            min_relay_fee: FeeRate::from_sat_per_vb_unchecked(1),
+            broadcast_rejection: None,
+            coinbase_counter: 0,
+            stalled_until: None,
+        }));
+
+        let listener =
+            TcpListener::bind("127.0.0.1:0").context("Failed to bind simulated Electrum server")?;
+        let electrum_url = format!("tcp://{}", listener.local_addr()?);
+
+        let server_chain = chain.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let chain = server_chain.clone();
+                std::thread::spawn(move || serve(stream, chain));
+            }
+        });
+
+        Ok(Self {
+            chain,
+            electrum_url,
+        })
+    }
+
+    /// The URL to pass to the wallet as Electrum server.
+    pub fn electrum_url(&self) -> String {
+        self.electrum_url.clone()
+    }
+
+    /// The height of the tip.
+    pub fn height(&self) -> u32 {
+        self.lock().height()
+    }
+
+    /// Mine `blocks` blocks. The first one contains all transactions in the mempool.
+    pub fn mine(&self, blocks: u32) -> Vec<BlockHash> {
+        let mut chain = self.lock();
+
+        (0..blocks)
+            .map(|_| {
+                let transactions = std::mem::take(&mut chain.mempool);
+                chain.mine_block(transactions)
+            })
+            .collect()
+    }
+
+    /// Mine a block every `interval` until the returned handle is dropped.
+    pub fn start_mining(&self, interval: Duration) -> AbortOnDropHandle<()> {
+        let blockchain = self.clone();
+
+        AbortOnDropHandle::new(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                if !blockchain.is_stalled() {
+                    blockchain.mine(1);
+                }
+            }
+        }))
+    }
+
+    /// Stop the miner started with [`Blockchain::start_mining`] for `duration`.
+    ///
+    /// Blocks can still be mined explicitly with [`Blockchain::mine`].
+    pub fn stall(&self, duration: Duration) {
+        self.lock().stalled_until = Some(Instant::now() + duration);
+    }
+
+    fn is_stalled(&self) -> bool {
+        self.lock()
+            .stalled_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Replace the last `depth` blocks with a longer chain of empty blocks.
+    ///
+    /// The transactions of the replaced blocks go back to the mempool.
+    pub fn reorg(&self, depth: u32) {
+        let mut chain = self.lock();
+
+        // Never replace the genesis block
+        let depth = depth.min(chain.height()) as usize;
+        let fork_point = chain.blocks.len() - depth;
+
+        let mut transactions: Vec<Transaction> = chain
+            .blocks
+            .drain(fork_point..)
+            .flat_map(|block| block.txdata.into_iter().skip(1))
+            .collect();
+        transactions.append(&mut chain.mempool);
+        chain.mempool = transactions;
+
+        for _ in 0..=depth {
+            chain.mine_block(vec![]);
+        }
+
+        chain.drop_invalid_mempool_transactions();
+    }
+
+    /// Drop a transaction from the mempool, as if it was never broadcast.
+    ///
+    /// Transactions spending its outputs are dropped as well. Returns whether the
+    /// transaction was in the mempool.
+    pub fn evict(&self, txid: Txid) -> bool {
+        let mut chain = self.lock();
+
+        let found = chain.remove_from_mempool(&HashSet::from([txid]));
+        chain.drop_invalid_mempool_transactions();
+
+        found
+    }
+
+    /// Send `amount` from the faucet to `address` and mine a block to confirm it.
+    ///
+    /// The faucet receives all block rewards, we mine more blocks if it doesn't
+    /// have enough.
+    pub fn fund(&self, address: &Address, amount: Amount) -> Txid {
+        let mut chain = self.lock();
+        let faucet = faucet_script();
+
+        let (inputs, total) = loop {
+            let utxos = chain.utxos(false);
+
+            let inputs: Vec<(OutPoint, Amount)> = utxos
+                .into_iter()
+                .filter(|(_, output)| output.script_pubkey == faucet)
+                .map(|(outpoint, output)| (outpoint, output.value))
+                .collect();
+            let total = inputs.iter().map(|(_, value)| *value).sum::<Amount>();
+
+            if total >= amount {
+                break (inputs, total);
+            }
+
+            chain.mine_block(vec![]);
+        };
+
+        let mut output = vec![TxOut {
+            value: amount,
+            script_pubkey: address.script_pubkey(),
+        }];
+        if total > amount {
+            output.push(TxOut {
+                value: total - amount,
+                script_pubkey: faucet,
+            });
+        }
+
+        let transaction = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: inputs
+                .into_iter()
+                .map(|(previous_output, _)| TxIn {
+                    previous_output,
+                    script_sig: ScriptBuf::new(),
+                    sequence: Sequence::MAX,
+                    witness: Witness::new(),
+                })
+                .collect(),
+            output,
+        };
+        let txid = transaction.compute_txid();
+
+        let mut transactions = std::mem::take(&mut chain.mempool);
+        transactions.push(transaction);
+        chain.mine_block(transactions);
+
+        txid
+    }
+
+    /// The confirmed and unconfirmed balance of `address`.
+    pub fn balance(&self, address: &Address) -> Amount {
+        let script = address.script_pubkey();
+
+        self.lock()
+            .utxos(true)
+            .into_iter()
+            .filter(|(_, output)| output.script_pubkey == script)
+            .map(|(_, output)| output.value)
+            .sum()
+    }
+
+    /// The number of confirmations of a transaction.
+    ///
+    /// `Some(0)` if it is in the mempool, `None` if we don't know it.
+    pub fn confirmations(&self, txid: Txid) -> Option<u32> {
+        let chain = self.lock();
+
+        if chain.mempool.iter().any(|tx| tx.compute_txid() == txid) {
+            return Some(0);
+        }
+
+        chain
+            .block_height_of(txid)
+            .map(|height| chain.height() - height + 1)
+    }
+
+    /// The transactions in the mempool.
+    pub fn mempool(&self) -> Vec<Transaction> {
+        self.lock().mempool.clone()
+    }
+
+    /// Get a confirmed or unconfirmed transaction.
+    pub fn transaction(&self, txid: Txid) -> Option<Transaction> {
+        self.lock().transactions().remove(&txid).cloned()
+    }
+
+    /// Set the fee rate the server estimates for all targets.
+    pub fn set_fee_rate(&self, fee_rate: FeeRate) {
+        self.lock().fee_rate = fee_rate;
+    }
+
+    /// Set the fee rate broadcast transactions must pay at least.
+    pub fn set_min_relay_fee(&self, fee_rate: FeeRate) {
+        self.lock().min_relay_fee = fee_rate;
+    }
+
+    /// Reject all broadcasts with `message` until this is called with `None`.
+    pub fn reject_broadcasts(&self, message: Option<String>) {
+        self.lock().broadcast_rejection = message;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Chain> {
+        self.chain
+            .lock()
+            .expect("simulated Bitcoin blockchain lock not to be poisoned")
+    }
+}
+
+impl Chain {
+    fn height(&self) -> u32 {
+        (self.blocks.len() - 1) as u32
+    }
+
+    fn mine_block(&mut self, transactions: Vec<Transaction>) -> BlockHash {
+        let height = self.blocks.len() as i64;
+        self.coinbase_counter += 1;
+
+        let coinbase = Transaction {
+            version: transaction::Version::ONE,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Builder::new()
+                    .push_int(height)
+                    .push_int(self.coinbase_counter)
+                    .into_script(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: BLOCK_REWARD,
+                script_pubkey: faucet_script(),
+            }],
+        };
+
+        let previous = &self.blocks[self.blocks.len() - 1].header;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as u32);
+
+        let mut block = Block {
+            header: block::Header {
+                version: block::Version::TWO,
+                prev_blockhash: previous.block_hash(),
+                merkle_root: TxMerkleNode::all_zeros(),
+                time: now.max(previous.time + 1),
+                bits: CompactTarget::from_consensus(0x207f_ffff),
+                nonce: 0,
+            },
+            txdata: std::iter::once(coinbase).chain(transactions).collect(),
+        };
+        block.header.merkle_root = block
+            .compute_merkle_root()
+            .expect("block to contain a coinbase");
+
+        let hash = block.block_hash();
+        self.blocks.push(block);
+
+        hash
+    }
+
+    /// All confirmed and unconfirmed transactions by txid.
+    fn transactions(&self) -> HashMap<Txid, &Transaction> {
+        self.blocks
+            .iter()
+            .flat_map(|block| block.txdata.iter())
+            .chain(self.mempool.iter())
+            .map(|tx| (tx.compute_txid(), tx))
+            .collect()
+    }
+
+    fn block_height_of(&self, txid: Txid) -> Option<u32> {
+        self.blocks
+            .iter()
+            .position(|block| block.txdata.iter().any(|tx| tx.compute_txid() == txid))
+            .map(|height| height as u32)
+    }
+
+    /// The unspent outputs of the chain, including the mempool if `include_mempool`.
+    ///
+    /// Outputs spent in the mempool are never included.
+    fn utxos(&self, include_mempool: bool) -> HashMap<OutPoint, TxOut> {
+        let mut utxos = HashMap::new();
+        let confirmed = self.blocks.iter().flat_map(|block| block.txdata.iter());
+
+        for (tx, in_mempool) in confirmed
+            .map(|tx| (tx, false))
+            .chain(self.mempool.iter().map(|tx| (tx, true)))
+        {
+            for input in &tx.input {
+                utxos.remove(&input.previous_output);
+            }
+
+            if in_mempool && !include_mempool {
+                continue;
+            }
+
+            let txid = tx.compute_txid();
+            for (vout, output) in tx.output.iter().enumerate() {
+                utxos.insert(OutPoint::new(txid, vout as u32), output.clone());
+            }
+        }
+
+        utxos
+    }
+
+    /// Remove transactions and their descendants from the mempool.
+    fn remove_from_mempool(&mut self, txids: &HashSet<Txid>) -> bool {
+        let mut removed = txids.clone();
+        let before = self.mempool.len();
+
+        self.mempool.retain(|tx| {
+            let txid = tx.compute_txid();
+            let remove = removed.contains(&txid)
+                || tx
+                    .input
+                    .iter()
+                    .any(|input| removed.contains(&input.previous_output.txid));
+
+            if remove {
+                removed.insert(txid);
+            }
+
+            !remove
+        });
+
+        self.mempool.len() < before
+    }
+
+    /// Drop mempool transactions whose inputs no longer exist or are spent twice.
+    fn drop_invalid_mempool_transactions(&mut self) {
+        let mempool = std::mem::take(&mut self.mempool);
+
+        for tx in mempool {
+            if self.check_inputs(&tx).is_ok() {
+                self.mempool.push(tx);
+            }
+        }
+    }
+
+    /// Check that all inputs of `tx` are unspent and their timelocks expired.
+    /// Returns the fee `tx` pays.
+    fn check_inputs(&self, tx: &Transaction) -> Result<Amount, RpcError> {
+        let utxos = self.utxos(true);
+        let next_height = self.height() + 1;
+
+        let mut input_value = Amount::ZERO;
+        for input in &tx.input {
+            let output = utxos
+                .get(&input.previous_output)
+                .ok_or_else(|| RpcError::new(RPC_VERIFY_ERROR, "bad-txns-inputs-missingorspent"))?;
+            input_value += output.value;
+
+            if let Some(relative::LockTime::Blocks(blocks)) = relative_lock_time(tx, input) {
+                let confirmations = self
+                    .block_height_of(input.previous_output.txid)
+                    .map_or(0, |height| next_height - height);
+
+                if confirmations < u32::from(blocks.value()) {
+                    return Err(RpcError::new(RPC_VERIFY_REJECTED, "non-BIP68-final"));
+                }
+            }
+        }
+
+        if let absolute::LockTime::Blocks(height) = tx.lock_time {
+            let enabled = tx.input.iter().any(|input| input.sequence != Sequence::MAX);
+
+            if enabled && height.to_consensus_u32() >= next_height {
+                return Err(RpcError::new(RPC_VERIFY_REJECTED, "non-final"));
+            }
+        }
+
+        let output_value = tx.output.iter().map(|output| output.value).sum::<Amount>();
+
+        input_value
+            .checked_sub(output_value)
+            .ok_or_else(|| RpcError::new(RPC_VERIFY_REJECTED, "bad-txns-in-belowout"))
+    }
+
+    /// Accept a transaction into the mempool, replacing conflicting transactions
+    /// if it pays a higher fee.
+    fn broadcast(&mut self, tx: Transaction) -> Result<Txid, RpcError> {
+        let txid = tx.compute_txid();
+
+        if let Some(message) = &self.broadcast_rejection {
+            return Err(RpcError::new(RPC_VERIFY_REJECTED, message));
+        }
+
+        if self.block_height_of(txid).is_some() {
+            return Err(RpcError::new(
+                RPC_VERIFY_ALREADY_IN_CHAIN,
+                "Transaction already in block chain",
+            ));
+        }
+
+        if self
+            .mempool
+            .iter()
+            .any(|other| other.compute_txid() == txid)
+        {
+            return Ok(txid);
+        }
+
+        let spent: HashSet<OutPoint> = tx.input.iter().map(|input| input.previous_output).collect();
+        let conflicts: Vec<Transaction> = self
+            .mempool
+            .iter()
+            .filter(|other| {
+                other
+                    .input
+                    .iter()
+                    .any(|input| spent.contains(&input.previous_output))
+            })
+            .cloned()
+            .collect();
+
+        // Check the replacement against the mempool without the transactions it replaces
+        let mempool = self.mempool.clone();
+        self.remove_from_mempool(&conflicts.iter().map(Transaction::compute_txid).collect());
+
+        let result = self.check_inputs(&tx).and_then(|fee| {
+            let min_fee = self
+                .min_relay_fee
+                .fee_wu(tx.weight())
+                .unwrap_or(Amount::MAX_MONEY);
+            if fee < min_fee {
+                return Err(RpcError::new(RPC_VERIFY_REJECTED, "min relay fee not met"));
+            }
+
+            for conflict in &conflicts {
+                let conflict_fee = self.fee(conflict).unwrap_or(Amount::ZERO);
+                if fee <= conflict_fee {
+                    return Err(RpcError::new(
+                        RPC_VERIFY_REJECTED,
+                        "insufficient fee, rejecting replacement",
+                    ));
+                }
+            }
+
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {
+                self.mempool.push(tx);
+                Ok(txid)
+            }
+            Err(error) => {
+                self.mempool = mempool;
+                Err(error)
+            }
+        }
+    }
+
+    /// The fee a transaction pays, `None` for coinbases.
+    fn fee(&self, tx: &Transaction) -> Option<Amount> {
+        if tx.is_coinbase() {
+            return None;
+        }
+
+        let transactions = self.transactions();
+        let input_value = tx
+            .input
+            .iter()
+            .map(|input| {
+                transactions
+                    .get(&input.previous_output.txid)
+                    .and_then(|previous| previous.output.get(input.previous_output.vout as usize))
+                    .map(|output| output.value)
+            })
+            .sum::<Option<Amount>>()?;
+        let output_value = tx.output.iter().map(|output| output.value).sum::<Amount>();
+
+        input_value.checked_sub(output_value)
+    }
+
+    /// The history of a script hash, as `(txid, height)` where the height is 0 for
+    /// unconfirmed transactions and -1 if they have unconfirmed parents.
+    fn history(&self, script_hash: &str) -> Vec<(Txid, i64)> {
+        let transactions = self.transactions();
+        let touches = |tx: &Transaction| {
+            tx.output
+                .iter()
+                .map(|output| &output.script_pubkey)
+                .chain(tx.input.iter().filter_map(|input| {
+                    transactions
+                        .get(&input.previous_output.txid)
+                        .and_then(|previous| {
+                            previous.output.get(input.previous_output.vout as usize)
+                        })
+                        .map(|output| &output.script_pubkey)
+                }))
+                .any(|script| electrum_script_hash(script) == script_hash)
+        };
+
+        let mut history = Vec::new();
+
+        for (height, block) in self.blocks.iter().enumerate() {
+            for tx in block.txdata.iter().filter(|tx| touches(tx)) {
+                history.push((tx.compute_txid(), height as i64));
+            }
+        }
+
+        let mempool_txids: HashSet<Txid> =
+            self.mempool.iter().map(Transaction::compute_txid).collect();
+
+        for tx in self.mempool.iter().filter(|tx| touches(tx)) {
+            let has_unconfirmed_parents = tx
+                .input
+                .iter()
+                .any(|input| mempool_txids.contains(&input.previous_output.txid));
+
+            history.push((
+                tx.compute_txid(),
+                if has_unconfirmed_parents { -1 } else { 0 },
+            ));
+        }
+
+        history
+    }
+
+    /// The merkle branch of a transaction in the block at `height`, in the format
+    /// of `blockchain.transaction.get_merkle`.
+    fn merkle_branch(&self, txid: Txid, height: u32) -> Option<(Vec<String>, usize)> {
+        let block = self.blocks.get(height as usize)?;
+        let position = block
+            .txdata
+            .iter()
+            .position(|tx| tx.compute_txid() == txid)?;
+
+        let mut level: Vec<sha256d::Hash> = block
+            .txdata
+            .iter()
+            .map(|tx| tx.compute_txid().to_raw_hash())
+            .collect();
+        let mut index = position;
+        let mut branch = Vec::new();
+
+        while level.len() > 1 {
+            if level.len() % 2 == 1 {
+                level.push(level[level.len() - 1]);
+            }
+
+            branch.push(level[index ^ 1].to_string());
+
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let mut bytes = pair[0].to_byte_array().to_vec();
+                    bytes.extend_from_slice(&pair[1].to_byte_array());
+                    sha256d::Hash::hash(&bytes)
+                })
+                .collect();
+            index /= 2;
+        }
+
+        Some((branch, position))
+    }
+
+    /// Handle a single Electrum request.
+    fn handle(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        let param_str = |index: usize| {
+            params
+                .get(index)
+                .and_then(Value::as_str)
+                .ok_or_else(|| RpcError::new(RPC_VERIFY_ERROR, "missing parameter"))
+        };
+        let param_u64 = |index: usize| {
+            params
+                .get(index)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| RpcError::new(RPC_VERIFY_ERROR, "missing parameter"))
+        };
+        let param_txid = |index: usize| {
+            param_str(index)?
+                .parse::<Txid>()
+                .map_err(|_| RpcError::new(RPC_VERIFY_ERROR, "invalid txid"))
+        };
+
+        let result = match method {
+            "server.version" => json!(["simulated-electrum", "1.4"]),
+            "server.banner" => json!("Simulated Electrum server"),
+            "server.ping" => Value::Null,
+            "server.features" => json!({
+                "genesis_hash": self.blocks[0].block_hash().to_string(),
+                "hash_function": "sha256",
+                "protocol_min": "1.4",
+                "protocol_max": "1.4",
+                "server_version": "simulated-electrum",
+                "hosts": {},
+                "pruning": null,
+            }),
+            "blockchain.headers.subscribe" => json!({
+                "height": self.height(),
+                "hex": serialize_hex(&self.blocks[self.blocks.len() - 1].header),
+            }),
+            "blockchain.block.header" => {
+                let block = self.blocks.get(param_u64(0)? as usize).ok_or_else(|| {
+                    RpcError::new(RPC_INVALID_ADDRESS_OR_KEY, "height out of range")
+                })?;
+
+                json!(serialize_hex(&block.header))
+            }
+            "blockchain.block.headers" => {
+                let start = param_u64(0)? as usize;
+                let count = (param_u64(1)? as usize).min(MAX_HEADERS);
+                let headers: Vec<String> = self
+                    .blocks
+                    .iter()
+                    .skip(start)
+                    .take(count)
+                    .map(|block| serialize_hex(&block.header))
+                    .collect();
+
+                json!({
+                    "count": headers.len(),
+                    "hex": headers.concat(),
+                    "max": MAX_HEADERS,
+                })
+            }
+            "blockchain.estimatefee" => json!(btc_per_kvb(self.fee_rate)),
+            "blockchain.relayfee" => json!(btc_per_kvb(self.min_relay_fee)),
+            "mempool.get_fee_histogram" => {
+                let mut histogram: Vec<(f64, u64)> = self
+                    .mempool
+                    .iter()
+                    .filter_map(|tx| {
+                        let fee = self.fee(tx)?;
+                        let vsize = tx.vsize() as u64;
+
+                        Some((fee.to_sat() as f64 / vsize as f64, vsize))
+                    })
+                    .collect();
+                histogram.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+                json!(histogram)
+            }
+            "blockchain.scripthash.get_history" => {
+                let history: Vec<Value> = self
+                    .history(param_str(0)?)
+                    .into_iter()
+                    .map(|(txid, height)| json!({"tx_hash": txid.to_string(), "height": height}))
+                    .collect();
+
+                json!(history)
+            }
+            "blockchain.scripthash.subscribe" => {
+                let history = self.history(param_str(0)?);
+
+                if history.is_empty() {
+                    Value::Null
+                } else {
+                    let status: String = history
+                        .iter()
+                        .map(|(txid, height)| format!("{}:{}:", txid, height))
+                        .collect();
+
+                    json!(sha256::Hash::hash(status.as_bytes()).to_string())
+                }
+            }
+            "blockchain.scripthash.unsubscribe" => json!(true),
+            "blockchain.scripthash.get_balance" | "blockchain.scripthash.listunspent" => {
+                let script_hash = param_str(0)?;
+                let confirmed = self.utxos(false);
+                let unspent: Vec<(OutPoint, TxOut, i64)> = self
+                    .utxos(true)
+                    .into_iter()
+                    .filter(|(_, output)| {
+                        electrum_script_hash(&output.script_pubkey) == script_hash
+                    })
+                    .map(|(outpoint, output)| {
+                        let height = match confirmed.contains_key(&outpoint) {
+                            true => self.block_height_of(outpoint.txid).map_or(0, i64::from),
+                            false => 0,
+                        };
+                        (outpoint, output, height)
+                    })
+                    .collect();
+
+                if method == "blockchain.scripthash.get_balance" {
+                    let (confirmed, unconfirmed): (Vec<_>, Vec<_>) =
+                        unspent.iter().partition(|(_, _, height)| *height > 0);
+                    let sum = |outputs: Vec<&(OutPoint, TxOut, i64)>| {
+                        outputs
+                            .iter()
+                            .map(|(_, output, _)| output.value.to_sat())
+                            .sum::<u64>()
+                    };
+
+                    json!({"confirmed": sum(confirmed), "unconfirmed": sum(unconfirmed)})
+                } else {
+                    let unspent: Vec<Value> = unspent
+                        .into_iter()
+                        .map(|(outpoint, output, height)| {
+                            json!({
+                                "tx_hash": outpoint.txid.to_string(),
+                                "tx_pos": outpoint.vout,
+                                "height": height,
+                                "value": output.value.to_sat(),
+                            })
+                        })
+                        .collect();
+
+                    json!(unspent)
+                }
+            }
+            "blockchain.transaction.get" => {
+                if params.get(1).and_then(Value::as_bool) == Some(true) {
+                    return Err(RpcError::new(
+                        RPC_VERIFY_ERROR,
+                        "verbose transactions are not supported",
+                    ));
+                }
+
+                let txid = param_txid(0)?;
+                let transactions = self.transactions();
+                let tx = transactions.get(&txid).ok_or_else(|| {
+                    RpcError::new(
+                        RPC_INVALID_ADDRESS_OR_KEY,
+                        "No such mempool or blockchain transaction",
+                    )
+                })?;
+
+                json!(serialize_hex(*tx))
+            }
+            "blockchain.transaction.broadcast" => {
+                let bytes = hex::decode(param_str(0)?)
+                    .map_err(|_| RpcError::new(RPC_VERIFY_ERROR, "TX decode failed"))?;
+                let tx: Transaction = deserialize(&bytes)
+                    .map_err(|_| RpcError::new(RPC_VERIFY_ERROR, "TX decode failed"))?;
+
+                json!(self.broadcast(tx)?.to_string())
+            }
+            "blockchain.transaction.get_merkle" => {
+                let txid = param_txid(0)?;
+                let height = param_u64(1)? as u32;
+                let (merkle, pos) = self.merkle_branch(txid, height).ok_or_else(|| {
+                    RpcError::new(RPC_INVALID_ADDRESS_OR_KEY, "Transaction not found in block")
+                })?;
+
+                json!({"block_height": height, "merkle": merkle, "pos": pos})
+            }
+            method => {
+                return Err(RpcError::new(
+                    RPC_VERIFY_ERROR,
+                    &format!("unknown method {}", method),
+                ))
+            }
+        };
+
+        Ok(result)
+    }
+}
+
+impl RpcError {
+    fn new(code: i64, message: &str) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Serve Electrum requests on `stream` until the client disconnects.
+fn serve(stream: TcpStream, chain: Arc<Mutex<Chain>>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+        let Ok(request) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+
+        let response = {
+            let mut chain = chain
+                .lock()
+                .expect("simulated Bitcoin blockchain lock not to be poisoned");
+
+            match request {
+                Value::Array(requests) => Value::Array(
+                    requests
+                        .iter()
+                        .map(|request| respond(&mut chain, request))
+                        .collect(),
+                ),
+                request => respond(&mut chain, &request),
+            }
+        };
+
+        if writeln!(writer, "{}", response).is_err() {
+            return;
+        }
+    }
+}
+
+fn respond(chain: &mut Chain, request: &Value) -> Value {
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = request
+        .get("params")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    match chain.handle(method, &params) {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => {
+            // Mimic electrs, which forwards the errors of bitcoind as a string
+            let daemon_error = json!({"code": error.code, "message": error.message});
+
+            json!({"jsonrpc": "2.0", "id": id, "error": format!("daemon error: {}", daemon_error)})
+        }
+    }
+}
+
+/// The script all block rewards are paid to. Anyone can spend it.
+fn faucet_script() -> ScriptBuf {
+    Builder::new()
+        .push_opcode(bitcoin::opcodes::OP_TRUE)
+        .into_script()
+}
+
+/// The relative timelock of an input, if the transaction enforces it (BIP68).
+fn relative_lock_time(tx: &Transaction, input: &TxIn) -> Option<relative::LockTime> {
+    if tx.version < transaction::Version::TWO {
+        return None;
+    }
+
+    input.sequence.to_relative_lock_time()
+}
+
+/// The hash Electrum servers index scripts by: the reversed sha256 of the script.
+fn electrum_script_hash(script: &ScriptBuf) -> String {
+    let mut hash = sha256::Hash::hash(script.as_bytes()).to_byte_array();
+    hash.reverse();
+
+    hex::encode(hash)
+}
+
+fn btc_per_kvb(fee_rate: FeeRate) -> f64 {
+    (fee_rate.to_sat_per_kwu() * 4) as f64 / Amount::ONE_BTC.to_sat() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_electrum::electrum_client::{self, ElectrumApi};
+    use bitcoin::key::{CompressedPublicKey, Secp256k1};
+    use bitcoin::secp256k1::SecretKey;
+
+    fn new_address() -> Address {
+        let secp = Secp256k1::new();
+        let secret_key = SecretKey::new(&mut rand::thread_rng());
+        let public_key = CompressedPublicKey(secret_key.public_key(&secp));
+
+        Address::p2wpkh(&public_key, Network::Regtest)
+    }
+
+    /// Spend the first output of `previous` to `address`, paying `fee`.
+    fn spend(previous: &Transaction, address: &Address, fee: Amount) -> Transaction {
+        Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::new(previous.compute_txid(), 0),
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                witness: Witness::new(),
+            }],
+            output: vec![TxOut {
+                value: previous.output[0].value - fee,
+                script_pubkey: address.script_pubkey(),
+            }],
+        }
+    }
+
+    #[test]
+    fn serves_funded_transactions_with_valid_merkle_proofs() {
+        let blockchain = Blockchain::new(Network::Regtest).unwrap();
+        let client = electrum_client::Client::new(&blockchain.electrum_url()).unwrap();
+        let address = new_address();
+
+        let txid = blockchain.fund(&address, Amount::from_sat(100_000));
+        blockchain.mine(2);
+
+        let tip = client.block_headers_subscribe().unwrap();
+        assert_eq!(tip.height, blockchain.height() as usize);
+
+        let history = client.script_get_history(&address.script_pubkey()).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].tx_hash, txid);
+        assert_eq!(blockchain.confirmations(txid), Some(3));
+
+        let height = history[0].height as usize;
+        let merkle = client.transaction_get_merkle(&txid, height).unwrap();
+        let header = client.block_header(height).unwrap();
+        assert!(electrum_client::utils::validate_merkle_proof(
+            &txid,
+            &header.merkle_root,
+            &merkle
+        ));
+
+        let tx = client.transaction_get(&txid).unwrap();
+        assert_eq!(tx.compute_txid(), txid);
+        assert_eq!(blockchain.balance(&address), Amount::from_sat(100_000));
+    }
+
+    #[test]
+    fn replacement_needs_higher_fee() {
+        let blockchain = Blockchain::new(Network::Regtest).unwrap();
+        let client = electrum_client::Client::new(&blockchain.electrum_url()).unwrap();
+        let address = new_address();
+
+        let txid = blockchain.fund(&address, Amount::from_sat(100_000));
+        let funding = blockchain.transaction(txid).unwrap();
+
+        let original = spend(&funding, &address, Amount::from_sat(1_000));
+        client.transaction_broadcast(&original).unwrap();
+
+        let cheaper = spend(&funding, &new_address(), Amount::from_sat(500));
+        assert!(client.transaction_broadcast(&cheaper).is_err());
+
+        let replacement = spend(&funding, &new_address(), Amount::from_sat(2_000));
+        client.transaction_broadcast(&replacement).unwrap();
+
+        assert_eq!(blockchain.confirmations(original.compute_txid()), None);
+        assert_eq!(
+            blockchain.confirmations(replacement.compute_txid()),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn enforces_relative_timelocks() {
+        let blockchain = Blockchain::new(Network::Regtest).unwrap();
+        let address = new_address();
+
+        let txid = blockchain.fund(&address, Amount::from_sat(100_000));
+        let funding = blockchain.transaction(txid).unwrap();
+
+        let mut timelocked = spend(&funding, &address, Amount::from_sat(1_000));
+        timelocked.input[0].sequence = Sequence::from_height(3);
+
+        assert!(blockchain.lock().broadcast(timelocked.clone()).is_err());
+
+        blockchain.mine(2);
+        assert!(blockchain.lock().broadcast(timelocked).is_ok());
+    }
+
+    #[test]
+    fn reorg_returns_transactions_to_mempool() {
+        let blockchain = Blockchain::new(Network::Regtest).unwrap();
+        let address = new_address();
+
+        let txid = blockchain.fund(&address, Amount::from_sat(100_000));
+        let funding = blockchain.transaction(txid).unwrap();
+
+        let spending = spend(&funding, &address, Amount::from_sat(1_000));
+        blockchain.lock().broadcast(spending.clone()).unwrap();
+        let replaced = blockchain.mine(1);
+        let height = blockchain.height();
+
+        blockchain.reorg(1);
+
+        assert_eq!(blockchain.height(), height + 1);
+        assert_ne!(
+            blockchain.lock().blocks[height as usize].block_hash(),
+            replaced[0]
+        );
+        assert_eq!(blockchain.confirmations(spending.compute_txid()), Some(0));
+
+        assert!(blockchain.evict(spending.compute_txid()));
+        assert_eq!(blockchain.confirmations(spending.compute_txid()), None);
+    }
+}
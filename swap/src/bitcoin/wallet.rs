@@ -1,8 +1,13 @@
-use crate::bitcoin::{parse_rpc_error_code, Address, Amount, RpcErrorCode, Transaction};
+use crate::bitcoin::coin_control::CoinControl;
+use crate::bitcoin::{
+    parse_rpc_error_code, Address, Amount, OutPoint, RpcErrorCode, Transaction, Utxo,
+    WalletTransaction,
+};
 use crate::cli::api::tauri_bindings::{
     TauriBackgroundProgress, TauriBitcoinFullScanProgress, TauriBitcoinSyncProgress, TauriEmitter,
     TauriHandle,
 };
+use crate::common::tor::SocksProxy;
 use crate::seed::Seed;
 use anyhow::{anyhow, bail, Context, Result};
 use bdk_chain::spk_client::{SyncRequest, SyncRequestBuilder};
@@ -16,6 +21,7 @@ use bdk_wallet::rusqlite::Connection;
 use bdk_wallet::template::{Bip84, DescriptorTemplate};
 use bdk_wallet::KeychainKind;
 use bdk_wallet::SignOptions;
+use bdk_wallet::TxBuilder;
 use bdk_wallet::WalletPersister;
 use bdk_wallet::{Balance, PersistedWallet};
 use bitcoin::bip32::Xpriv;
@@ -42,7 +48,7 @@ use tracing::{debug_span, Instrument};
 use super::bitcoin_address::revalidate_network;
 use super::BlockHeight;
 use derive_builder::Builder;
-use electrum_pool::ElectrumBalancer;
+use electrum_pool::{ElectrumBalancer, ElectrumBalancerConfig};
 use moka;
 
 /// We allow transaction fees of up to 20% of the transferred amount to ensure
@@ -83,6 +89,8 @@ pub struct Wallet<Persister = Connection, C = Client> {
     target_block: u32,
     /// The Tauri handle
     tauri_handle: Option<TauriHandle>,
+    /// Labels and freezes of our UTXOs.
+    coin_control: Arc<SyncMutex<CoinControl>>,
 }
 
 /// This is our wrapper around a bdk electrum client.
@@ -100,6 +108,8 @@ pub struct Client {
     sync_interval: Duration,
     /// The height of the latest block we know about.
     latest_block_height: BlockHeight,
+    /// The SOCKS5 proxy all connections to the outside world are made through.
+    socks5_proxy: Option<SocksProxy>,
 }
 
 /// Holds the configuration parameters for creating a Bitcoin wallet.
@@ -128,6 +138,9 @@ pub struct WalletConfig {
     tauri_handle: Option<TauriHandle>,
     #[builder(default = "true")]
     use_mempool_space_fee_estimation: bool,
+    /// Route the Electrum and mempool.space traffic through this SOCKS5 proxy (e.g. Tor).
+    #[builder(default)]
+    socks5_proxy: Option<SocksProxy>,
 }
 
 impl WalletBuilder {
@@ -139,9 +152,13 @@ impl WalletBuilder {
             .validate_config()
             .map_err(|e| anyhow!("Builder validation failed: {e}"))?;
 
-        let client = Client::new(&config.electrum_rpc_urls, config.sync_interval)
-            .await
-            .context("Failed to create Electrum client")?;
+        let client = Client::new(
+            &config.electrum_rpc_urls,
+            config.sync_interval,
+            config.socks5_proxy,
+        )
+        .await
+        .context("Failed to create Electrum client")?;
 
         match &config.persister {
             PersisterConfig::SqliteFile { data_dir } => {
@@ -166,7 +183,9 @@ impl WalletBuilder {
                     ))
                 };
 
-                if wallet_exists {
+                let coin_control_path = wallet_dir.join(CoinControl::FILE_NAME);
+
+                let wallet = if wallet_exists {
                     let connection = open_connection()?;
 
                     Wallet::create_existing(
@@ -203,7 +222,9 @@ impl WalletBuilder {
                     )
                     .await
                     .context("Failed to create new wallet")
-                }
+                }?;
+
+                wallet.with_coin_control(coin_control_path)
             }
             PersisterConfig::InMemorySqlite => {
                 let xprivkey = config
@@ -454,7 +475,7 @@ impl Wallet {
         let wallet_exists = wallet_path.exists();
 
         // Connect to the electrum server.
-        let client = Client::new(electrum_rpc_urls, sync_interval).await?;
+        let client = Client::new(electrum_rpc_urls, sync_interval, None).await?;
 
         // Make sure the wallet directory exists.
         tokio::fs::create_dir_all(&wallet_dir).await?;
@@ -463,7 +484,7 @@ impl Wallet {
             || Connection::open(&wallet_path).context("Failed to open SQLite database");
 
         // If the new Bitcoin wallet (> 1.0.0 bdk) already exists, we open it
-        if wallet_exists {
+        let wallet = if wallet_exists {
             Self::create_existing(
                 xprivkey,
                 network,
@@ -492,7 +513,9 @@ impl Wallet {
                 true, // default to true for mempool space fee estimation
             )
             .await
-        }
+        }?;
+
+        wallet.with_coin_control(wallet_dir.join(CoinControl::FILE_NAME))
     }
 
     /// Create a new wallet, persisted to an in-memory sqlite database.
@@ -510,7 +533,7 @@ impl Wallet {
         Self::create_new(
             seed.derive_extended_private_key(network)?,
             network,
-            Client::new(electrum_rpc_urls, sync_interval)
+            Client::new(electrum_rpc_urls, sync_interval, None)
                 .await
                 .expect("Failed to create electrum client"),
             || {
@@ -624,7 +647,7 @@ impl Wallet {
 
         // Create the mempool client
         let mempool_client = if use_mempool_space_fee_estimation {
-            mempool_client::MempoolClient::new(network).inspect_err(|e| {
+            mempool_client::MempoolClient::new(network, client.socks5_proxy.as_ref()).inspect_err(|e| {
                 tracing::warn!("Failed to create mempool client: {:?}. We will only use the Electrum server for fee estimation.", e);
             }).ok()
         } else {
@@ -646,6 +669,7 @@ impl Wallet {
             network,
             finality_confirmations,
             target_block,
+            coin_control: CoinControl::default().into_arc_mutex_sync(),
         })
     }
 
@@ -685,7 +709,7 @@ impl Wallet {
 
         // Create the mempool client with caching
         let cached_mempool_fee_estimator = if use_mempool_space_fee_estimation {
-            mempool_client::MempoolClient::new(network).inspect_err(|e| {
+            mempool_client::MempoolClient::new(network, client.socks5_proxy.as_ref()).inspect_err(|e| {
                 tracing::warn!("Failed to create mempool client: {:?}. We will only use the Electrum server for fee estimation.", e);
             }).ok().map(CachedFeeEstimator::new)
         } else {
@@ -705,6 +729,7 @@ impl Wallet {
             network,
             finality_confirmations,
             target_block,
+            coin_control: CoinControl::default().into_arc_mutex_sync(),
         };
 
         Ok(wallet)
@@ -1150,6 +1175,13 @@ impl Wallet {
 
         Ok(fee)
     }
+
+    /// Get the electrum balancer used by this wallet.
+    ///
+    /// Useful to inspect the balancer without locking the client on every access.
+    pub async fn electrum_balancer(&self) -> Arc<ElectrumBalancer> {
+        self.electrum_client.lock().await.inner.clone()
+    }
 }
 
 // These are the methods that are always available, regardless of the persister.
@@ -1171,6 +1203,17 @@ impl<T, C> Wallet<T, C> {
     pub fn target_block(&self) -> u32 {
         self.target_block
     }
+
+    /// Persist UTXO labels and freezes in the file at `path`, loading the
+    /// ones we already have.
+    fn with_coin_control(self, path: impl AsRef<Path>) -> Result<Self> {
+        let coin_control = CoinControl::load(path)?;
+
+        Ok(Self {
+            coin_control: coin_control.into_arc_mutex_sync(),
+            ..self
+        })
+    }
 }
 
 impl<Persister, C> Wallet<Persister, C>
@@ -1317,6 +1360,143 @@ where
         }
     }
 
+    /// Returns the fee rate we currently need to get a transaction confirmed within
+    /// our target block, but never less than the minimum relay fee.
+    pub async fn current_fee_rate(&self) -> Result<FeeRate> {
+        let fee_rate = self.combined_fee_rate().await?;
+        let min_relay_fee = self.combined_min_relay_fee().await?;
+
+        Ok(std::cmp::max(fee_rate, min_relay_fee))
+    }
+
+    /// Builds and signs a child transaction that spends our output of the unconfirmed `parent`
+    /// back to ourselves (child-pays-for-parent).
+    ///
+    /// `package_vsize` and `package_fee` describe the parent and all of its unconfirmed
+    /// ancestors. The child pays enough such that the whole package reaches `fee_rate`.
+    pub async fn cpfp(
+        &self,
+        parent: &Transaction,
+        package_vsize: u64,
+        package_fee: Amount,
+        fee_rate: FeeRate,
+    ) -> Result<Transaction> {
+        let mut wallet = self.wallet.lock().await;
+
+        let vout = parent
+            .output
+            .iter()
+            .position(|output| wallet.is_mine(output.script_pubkey.clone()))
+            .context("Transaction has no output that pays to our wallet")?;
+        let outpoint = bitcoin::OutPoint {
+            txid: parent.compute_txid(),
+            vout: u32::try_from(vout)?,
+        };
+
+        // The parent might have been published by our counterparty, in which case
+        // the wallet may not know about it yet
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("time went backwards")
+            .as_secs();
+        wallet.apply_unconfirmed_txs(vec![(parent.clone(), timestamp)]);
+
+        let drain_script = wallet
+            .next_unused_address(KeychainKind::Internal)
+            .script_pubkey();
+
+        let build_child = |wallet: &mut PersistedWallet<Persister>, fee: Option<Amount>| {
+            let mut tx_builder = wallet.build_tx();
+            tx_builder.add_utxo(outpoint)?;
+            tx_builder.manually_selected_only();
+            tx_builder.drain_to(drain_script.clone());
+
+            match fee {
+                Some(fee) => tx_builder.fee_absolute(fee),
+                None => tx_builder.fee_rate(fee_rate),
+            };
+
+            let mut psbt = tx_builder
+                .finish()
+                .context("Our output is too small to pay for the fee bump")?;
+
+            if !wallet.sign(&mut psbt, SignOptions::default())? {
+                bail!("PSBT is not finalized")
+            }
+
+            anyhow::Ok(psbt.extract_tx()?)
+        };
+
+        // We first build the child at the target fee rate to learn its size including the witness.
+        // The signature may be one byte larger the second time around, hence the extra vbyte.
+        let child_vsize = build_child(&mut *wallet, None)?.vsize() as u64 + 1;
+
+        let child_fee = fee_rate
+            .fee_vb(package_vsize + child_vsize)
+            .context("Fee overflow")?
+            .checked_sub(package_fee)
+            .unwrap_or(Amount::ZERO)
+            .max(fee_rate.fee_vb(child_vsize).context("Fee overflow")?);
+
+        let child = build_child(&mut *wallet, Some(child_fee))?;
+
+        let mut persister = self.persister.lock().await;
+        wallet.persist(&mut persister)?;
+
+        Ok(child)
+    }
+
+    /// Replaces an unconfirmed transaction funded entirely by our wallet with one paying `fee`.
+    ///
+    /// Frozen UTXOs are never added to pay for the higher fee.
+    pub async fn bump_fee(&self, txid: Txid, fee: Amount) -> Result<Transaction> {
+        let mut wallet = self.wallet.lock().await;
+
+        let mut tx_builder = wallet
+            .build_fee_bump(txid)
+            .with_context(|| format!("Cannot replace transaction {}", txid))?;
+        self.apply_coin_control(&mut tx_builder, None)?;
+        tx_builder.fee_absolute(fee);
+
+        let mut psbt = tx_builder.finish()?;
+
+        if !wallet.sign(&mut psbt, SignOptions::default())? {
+            bail!("PSBT is not finalized")
+        }
+
+        Ok(psbt.extract_tx()?)
+    }
+
+    /// Finds an unconfirmed transaction of ours which spends an output of `txid`
+    /// and is funded entirely by our wallet (e.g. a child created by [`Wallet::cpfp`]).
+    ///
+    /// Returns the transaction together with the fee it pays.
+    pub async fn unconfirmed_child_of(&self, txid: Txid) -> Option<(Arc<Transaction>, Amount)> {
+        let wallet = self.wallet.lock().await;
+
+        wallet
+            .transactions()
+            .filter(|tx| !tx.chain_position.is_confirmed())
+            .map(|tx| tx.tx_node.tx.clone())
+            .filter(|tx| {
+                tx.input
+                    .iter()
+                    .any(|input| input.previous_output.txid == txid)
+            })
+            .filter(|tx| {
+                tx.input.iter().all(|input| {
+                    wallet
+                        .tx_graph()
+                        .get_txout(input.previous_output)
+                        .is_some_and(|txout| wallet.is_mine(txout.script_pubkey.clone()))
+                })
+            })
+            .find_map(|tx| {
+                let fee = wallet.calculate_fee(&tx).ok()?;
+                Some((tx, fee))
+            })
+    }
+
     pub async fn sign_and_finalize(&self, mut psbt: bitcoin::psbt::Psbt) -> Result<Transaction> {
         // Acquire the wallet lock once here for efficiency within the non-finalized block
         let wallet_guard = self.wallet.lock().await;
@@ -1344,6 +1524,188 @@ where
         Ok(self.wallet.lock().await.balance())
     }
 
+    /// Returns all transactions which touched the wallet, unconfirmed ones
+    /// first and then newest first.
+    ///
+    /// Use [`crate::bitcoin::link_to_swaps`] to find out which swap they belong to.
+    pub async fn transaction_history(&self) -> Result<Vec<WalletTransaction>> {
+        let wallet = self.wallet.lock().await;
+
+        let mut transactions = wallet
+            .transactions()
+            .map(|tx| {
+                let (sent, received) = wallet.sent_and_received(&tx.tx_node.tx);
+                // Fails for incoming transactions whose inputs we don't know
+                let fee = wallet.calculate_fee(&tx.tx_node.tx).ok();
+                let confirmation_height = tx.chain_position.confirmation_height_upper_bound();
+                let timestamp = confirmation_height.and_then(|height| {
+                    tx.tx_node
+                        .anchors
+                        .iter()
+                        .find(|anchor| anchor.block_id.height == height)
+                        .map(|anchor| anchor.confirmation_time)
+                });
+
+                WalletTransaction::new(
+                    tx.tx_node.txid,
+                    sent,
+                    received,
+                    fee,
+                    confirmation_height,
+                    timestamp,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        // Unconfirmed transactions don't have a height yet, they are the newest
+        transactions
+            .sort_by_key(|tx| std::cmp::Reverse(tx.confirmation_height.unwrap_or(u32::MAX)));
+
+        Ok(transactions)
+    }
+
+    /// Returns the UTXOs of the wallet together with their labels and
+    /// whether they are frozen.
+    pub async fn list_unspent(&self) -> Result<Vec<Utxo>> {
+        let wallet = self.wallet.lock().await;
+        let coin_control = self.coin_control()?;
+        let tip = wallet.latest_checkpoint().height();
+
+        let mut utxos = wallet
+            .list_unspent()
+            .map(|output| {
+                let confirmations = output
+                    .chain_position
+                    .confirmation_height_upper_bound()
+                    .map(|height| tip.saturating_sub(height) + 1)
+                    .unwrap_or(0);
+                let address = Address::from_script(&output.txout.script_pubkey, self.network)
+                    .map(|address| address.to_string())
+                    .unwrap_or_default();
+                let info = coin_control.get(&output.outpoint);
+
+                Utxo {
+                    outpoint: output.outpoint,
+                    amount: output.txout.value,
+                    confirmations,
+                    address,
+                    label: info.label,
+                    frozen: info.frozen,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Oldest first, unconfirmed last
+        utxos.sort_by_key(|utxo| std::cmp::Reverse(utxo.confirmations));
+
+        Ok(utxos)
+    }
+
+    /// Sets the label of one of our UTXOs. `None` removes the label.
+    pub async fn set_utxo_label(&self, outpoint: OutPoint, label: Option<String>) -> Result<()> {
+        self.ensure_own_utxo(outpoint).await?;
+        self.coin_control()?.set_label(outpoint, label)
+    }
+
+    /// Freezes or unfreezes one of our UTXOs.
+    ///
+    /// Frozen UTXOs are ignored by coin selection and cannot be spent
+    /// until they are unfrozen.
+    pub async fn set_utxo_frozen(&self, outpoint: OutPoint, frozen: bool) -> Result<()> {
+        self.ensure_own_utxo(outpoint).await?;
+        self.coin_control()?.set_frozen(outpoint, frozen)
+    }
+
+    /// Returns the balance we can spend from the given `inputs`, or from all
+    /// UTXOs that are not frozen if no inputs are given.
+    ///
+    /// Fails if one of the `inputs` is frozen or not one of our UTXOs.
+    pub async fn spendable_balance(&self, inputs: Option<&[OutPoint]>) -> Result<Amount> {
+        let utxos = self.list_unspent().await?;
+
+        let Some(inputs) = inputs else {
+            return Ok(utxos
+                .iter()
+                .filter(|utxo| !utxo.frozen)
+                .map(|utxo| utxo.amount)
+                .sum());
+        };
+
+        inputs.iter().try_fold(Amount::ZERO, |total, outpoint| {
+            let utxo = utxos
+                .iter()
+                .find(|utxo| utxo.outpoint == *outpoint)
+                .with_context(|| format!("{} is not an unspent output of our wallet", outpoint))?;
+
+            if utxo.frozen {
+                bail!("{} is frozen, unfreeze it before spending it", outpoint);
+            }
+
+            Ok(total + utxo.amount)
+        })
+    }
+
+    async fn ensure_own_utxo(&self, outpoint: OutPoint) -> Result<()> {
+        let wallet = self.wallet.lock().await;
+
+        match wallet.get_utxo(outpoint) {
+            Some(_) => Ok(()),
+            None => bail!("{} is not an unspent output of our wallet", outpoint),
+        }
+    }
+
+    fn coin_control(&self) -> Result<std::sync::MutexGuard<'_, CoinControl>> {
+        self.coin_control
+            .lock()
+            .map_err(|_| anyhow!("Coin control mutex was poisoned"))
+    }
+
+    /// Restricts coin selection to the given `inputs`, or to all UTXOs that
+    /// are not frozen if no inputs are given.
+    fn apply_coin_control<Cs>(
+        &self,
+        tx_builder: &mut TxBuilder<'_, Cs>,
+        inputs: Option<&[OutPoint]>,
+    ) -> Result<()> {
+        let coin_control = self.coin_control()?;
+
+        match inputs {
+            Some([]) => bail!("At least one input has to be selected"),
+            Some(inputs) => {
+                if let Some(frozen) = inputs.iter().find(|input| coin_control.is_frozen(input)) {
+                    bail!("{} is frozen, unfreeze it before spending it", frozen);
+                }
+
+                tx_builder
+                    .add_utxos(inputs)
+                    .context("Selected input is not an unspent output of our wallet")?;
+                tx_builder.manually_selected_only();
+            }
+            None => {
+                tx_builder.unspendable(coin_control.frozen());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Spends all of the given `inputs`, or all UTXOs that are not frozen if
+    /// no inputs are given.
+    fn drain_with_coin_control<Cs>(
+        &self,
+        tx_builder: &mut TxBuilder<'_, Cs>,
+        inputs: Option<&[OutPoint]>,
+    ) -> Result<()> {
+        self.apply_coin_control(tx_builder, inputs)?;
+
+        // Manually selected inputs are always spent in full
+        if inputs.is_none() {
+            tx_builder.drain_wallet();
+        }
+
+        Ok(())
+    }
+
     /// Reveals the next address from the wallet.
     pub async fn new_address(&self) -> Result<Address> {
         let mut wallet = self.wallet.lock().await;
@@ -1369,6 +1731,7 @@ where
         address: Address,
         amount: Amount,
         change_override: Option<Address>,
+        inputs: Option<&[OutPoint]>,
     ) -> Result<PartiallySignedTransaction> {
         // Check address and change address for network equality.
         let address = revalidate_network(address, self.network)?;
@@ -1391,6 +1754,7 @@ where
 
             tx_builder.add_recipient(script.clone(), amount);
             tx_builder.fee_absolute(Amount::ZERO);
+            self.apply_coin_control(&mut tx_builder, inputs)?;
 
             tx_builder.finish()?
         };
@@ -1398,7 +1762,7 @@ where
         let weight = psbt.unsigned_tx.weight();
         let fee = self.estimate_fee(weight, Some(amount)).await?;
 
-        self.send_to_address(address, amount, fee, change_override)
+        self.send_to_address_from(address, amount, fee, change_override, inputs)
             .await
     }
 
@@ -1407,13 +1771,19 @@ where
     ///
     /// The fee is calculated based on the weight of the transaction
     /// and the state of the current mempool.
+    ///
+    /// If `inputs` are given, only those UTXOs are swept.
     pub async fn sweep_balance_to_address_dynamic_fee(
         &self,
         address: Address,
+        inputs: Option<&[OutPoint]>,
     ) -> Result<PartiallySignedTransaction> {
-        let (max_giveable, fee) = self.max_giveable(address.script_pubkey().len()).await?;
+        let (max_giveable, fee) = self
+            .max_giveable_from(address.script_pubkey().len(), inputs)
+            .await?;
 
-        self.send_to_address(address, max_giveable, fee, None).await
+        self.send_to_address_from(address, max_giveable, fee, None, inputs)
+            .await
     }
 
     /// Builds a partially signed transaction that sends
@@ -1428,6 +1798,22 @@ where
         amount: Amount,
         spending_fee: Amount,
         change_override: Option<Address>,
+    ) -> Result<PartiallySignedTransaction> {
+        self.send_to_address_from(address, amount, spending_fee, change_override, None)
+            .await
+    }
+
+    /// Like [`Wallet::send_to_address`] but only spends the given `inputs`.
+    ///
+    /// If no inputs are given, coin selection picks from all UTXOs that are
+    /// not frozen.
+    pub async fn send_to_address_from(
+        &self,
+        address: Address,
+        amount: Amount,
+        spending_fee: Amount,
+        change_override: Option<Address>,
+        inputs: Option<&[OutPoint]>,
     ) -> Result<PartiallySignedTransaction> {
         // Check address and change address for network equality.
         let address = revalidate_network(address, self.network)?;
@@ -1445,6 +1831,7 @@ where
         let mut tx_builder = wallet.build_tx();
         tx_builder.add_recipient(script.clone(), amount);
         tx_builder.fee_absolute(spending_fee);
+        self.apply_coin_control(&mut tx_builder, inputs)?;
 
         let mut psbt = tx_builder.finish()?;
 
@@ -1478,6 +1865,73 @@ where
         Ok(psbt)
     }
 
+    /// Builds and signs a transaction which pays each of the given `amounts` to a
+    /// fresh address of our wallet, at the current fee rate.
+    ///
+    /// This splits our funds into outputs which can then be spent independently,
+    /// e.g. by swaps which run at the same time. Any change goes back to the wallet.
+    ///
+    /// Returns the transaction together with the outpoints of the new outputs,
+    /// in the order of `amounts`.
+    pub async fn split_into_outputs(
+        &self,
+        amounts: &[Amount],
+        inputs: Option<&[OutPoint]>,
+    ) -> Result<(Transaction, Vec<OutPoint>)> {
+        let fee_rate = self.current_fee_rate().await?;
+
+        let mut wallet = self.wallet.lock().await;
+
+        let scripts = amounts
+            .iter()
+            .map(|_| {
+                wallet
+                    .reveal_next_address(KeychainKind::External)
+                    .script_pubkey()
+            })
+            .collect::<Vec<_>>();
+
+        let mut tx_builder = wallet.build_tx();
+        for (script, amount) in scripts.iter().zip(amounts) {
+            tx_builder.add_recipient(script.clone(), *amount);
+        }
+        tx_builder.fee_rate(fee_rate);
+        self.apply_coin_control(&mut tx_builder, inputs)?;
+
+        let mut psbt = tx_builder
+            .finish()
+            .context("Not enough funds to split into the requested outputs")?;
+
+        if !wallet.sign(&mut psbt, SignOptions::default())? {
+            bail!("PSBT is not finalized")
+        }
+
+        let transaction = psbt.extract_tx()?;
+        let txid = transaction.compute_txid();
+
+        // The outputs are shuffled when the transaction is built
+        let outpoints = scripts
+            .iter()
+            .map(|script| {
+                let vout = transaction
+                    .output
+                    .iter()
+                    .position(|output| output.script_pubkey == *script)
+                    .context("Transaction does not pay to one of the requested outputs")?;
+
+                Ok(OutPoint {
+                    txid,
+                    vout: u32::try_from(vout)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut persister = self.persister.lock().await;
+        wallet.persist(&mut persister)?;
+
+        Ok((transaction, outpoints))
+    }
+
     /// Calculates the maximum "giveable" amount of this wallet.
     ///
     /// We define this as the maximum amount we can pay to a single output,
@@ -1486,6 +1940,17 @@ where
     ///
     /// Returns a tuple of (max_giveable_amount, spending_fee).
     pub async fn max_giveable(&self, locking_script_size: usize) -> Result<(Amount, Amount)> {
+        self.max_giveable_from(locking_script_size, None).await
+    }
+
+    /// Like [`Wallet::max_giveable`] but only considers the given `inputs`.
+    ///
+    /// If no inputs are given, all UTXOs that are not frozen are considered.
+    pub async fn max_giveable_from(
+        &self,
+        locking_script_size: usize,
+        inputs: Option<&[OutPoint]>,
+    ) -> Result<(Amount, Amount)> {
         let mut wallet = self.wallet.lock().await;
 
         // Construct a dummy drain transaction
@@ -1495,7 +1960,7 @@ where
 
         tx_builder.drain_to(dummy_script.clone());
         tx_builder.fee_absolute(Amount::ZERO);
-        tx_builder.drain_wallet();
+        self.drain_with_coin_control(&mut tx_builder, inputs)?;
 
         // The weight WILL NOT change, even if we change the fee
         // because we are draining the wallet (using all inputs) and
@@ -1569,7 +2034,7 @@ where
 
                 tx_builder.drain_to(dummy_script.clone());
                 tx_builder.fee_absolute(Amount::ZERO);
-                tx_builder.drain_wallet();
+                self.drain_with_coin_control(&mut tx_builder, inputs)?;
 
                 tx_builder
                     .add_foreign_utxo(
@@ -1649,8 +2114,19 @@ where
 
 impl Client {
     /// Create a new client with multiple electrum servers for load balancing.
-    pub async fn new(electrum_rpc_urls: &[String], sync_interval: Duration) -> Result<Self> {
-        let balancer = ElectrumBalancer::new(electrum_rpc_urls.to_vec()).await?;
+    ///
+    /// If a SOCKS5 proxy is given, all connections to the servers are made through it.
+    pub async fn new(
+        electrum_rpc_urls: &[String],
+        sync_interval: Duration,
+        socks5_proxy: Option<SocksProxy>,
+    ) -> Result<Self> {
+        let config = ElectrumBalancerConfig {
+            socks5_proxy: socks5_proxy.as_ref().map(SocksProxy::socks5_config),
+            ..Default::default()
+        };
+        let balancer =
+            ElectrumBalancer::new_with_config(electrum_rpc_urls.to_vec(), config).await?;
 
         Ok(Self {
             inner: Arc::new(balancer),
@@ -1661,6 +2137,7 @@ impl Client {
             sync_interval,
             latest_block_height: BlockHeight::from(0),
             subscriptions: Default::default(),
+            socks5_proxy,
         })
     }
 
@@ -2342,6 +2819,11 @@ fn trace_status_change(txid: Txid, old: Option<ScriptStatus>, new: ScriptStatus)
 }
 
 impl Subscription {
+    /// The most recent status we have seen for the transaction.
+    pub fn status(&self) -> ScriptStatus {
+        *self.receiver.borrow()
+    }
+
     pub async fn wait_until_final(&self) -> Result<()> {
         let conf_target = self.finality_confirmations;
         let txid = self.txid;
@@ -2535,6 +3017,7 @@ mod mempool_client {
     static BASE_URL: &str = "https://mempool.space";
 
     use super::EstimateFeeRate;
+    use crate::common::tor::SocksProxy;
     use anyhow::{bail, Context, Result};
     use bitcoin::{FeeRate, Network};
     use serde::Deserialize;
@@ -2559,7 +3042,7 @@ mod mempool_client {
     }
 
     impl MempoolClient {
-        pub fn new(network: Network) -> Result<Self> {
+        pub fn new(network: Network, socks5_proxy: Option<&SocksProxy>) -> Result<Self> {
             let base_url = match network {
                 Network::Bitcoin => BASE_URL.to_string(),
                 Network::Testnet => format!("{}/testnet", BASE_URL),
@@ -2567,8 +3050,16 @@ mod mempool_client {
                 _ => bail!("mempool.space fee estimation unsupported for network"),
             };
 
-            let client = reqwest::Client::builder()
-                .timeout(HTTP_TIMEOUT)
+            let mut builder = reqwest::Client::builder().timeout(HTTP_TIMEOUT);
+
+            if let Some(proxy) = socks5_proxy {
+                builder = builder.proxy(
+                    reqwest::Proxy::all(proxy.url())
+                        .context("Failed to configure SOCKS5 proxy for mempool.space")?,
+                );
+            }
+
+            let client = builder
                 .build()
                 .context("Failed to build mempool.space HTTP client")?;
 
@@ -2958,6 +3449,7 @@ impl TestWalletBuilder {
             network: Network::Regtest,
             finality_confirmations: 1,
             target_block: 1,
+            coin_control: CoinControl::default().into_arc_mutex_sync(),
         };
 
         let mut locked_wallet = wallet.wallet.try_lock().unwrap();
@@ -3289,6 +3781,7 @@ mod tests {
                 A,
                 B,
                 change,
+                None,
             )
             .await
             .unwrap();
@@ -3334,6 +3827,108 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn cpfp_child_pays_for_the_whole_package() {
+        let wallet = TestWalletBuilder::new(50_000).build().await;
+
+        let parent_fee = Amount::from_sat(200);
+        let psbt = wallet
+            .send_to_address(
+                wallet.new_address().await.unwrap(),
+                Amount::from_sat(10_000),
+                parent_fee,
+                None,
+            )
+            .await
+            .unwrap();
+        let parent = wallet.sign_and_finalize(psbt).await.unwrap();
+        let parent_vsize = parent.vsize() as u64;
+
+        let fee_rate = FeeRate::from_sat_per_vb(10).unwrap();
+        let child = wallet
+            .cpfp(&parent, parent_vsize, parent_fee, fee_rate)
+            .await
+            .unwrap();
+
+        assert_eq!(child.input.len(), 1);
+        assert_eq!(child.input[0].previous_output.txid, parent.compute_txid());
+
+        let spent = parent.output[child.input[0].previous_output.vout as usize].value;
+        let change: Amount = child.output.iter().map(|output| output.value).sum();
+        let child_fee = spent - change;
+        let package_vsize = parent_vsize + child.vsize() as u64;
+
+        assert!(parent_fee + child_fee >= fee_rate.fee_vb(package_vsize).unwrap());
+    }
+
+    #[tokio::test]
+    async fn transaction_history_contains_deposits() {
+        let wallet = TestWalletBuilder::new(50_000).build().await;
+
+        let history = wallet.transaction_history().await.unwrap();
+
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            history[0].direction,
+            crate::bitcoin::TransactionDirection::Incoming
+        );
+        assert_eq!(history[0].amount, Amount::from_sat(50_000));
+        assert!(history[0].confirmation_height.is_some());
+    }
+
+    #[tokio::test]
+    async fn coin_selection_respects_frozen_and_selected_utxos() {
+        let wallet = TestWalletBuilder::new(50_000)
+            .with_zero_fees()
+            .with_num_utxos(3)
+            .build()
+            .await;
+
+        let utxos = wallet.list_unspent().await.unwrap();
+        assert_eq!(utxos.len(), 3);
+        assert!(utxos.iter().all(|utxo| utxo.confirmations > 0));
+
+        let frozen = utxos[0].outpoint;
+        wallet.set_utxo_frozen(frozen, true).await.unwrap();
+
+        let (amount, _fee) = wallet.max_giveable(TxLock::script_size()).await.unwrap();
+        assert!(amount > Amount::from_sat(50_000) && amount <= Amount::from_sat(100_000));
+        assert_eq!(
+            wallet.spendable_balance(None).await.unwrap(),
+            Amount::from_sat(100_000)
+        );
+        assert!(wallet.spendable_balance(Some(&[frozen])).await.is_err());
+
+        let selected = utxos[1].outpoint;
+        let psbt = wallet
+            .send_to_address_from(
+                wallet.new_address().await.unwrap(),
+                Amount::from_sat(10_000),
+                Amount::ZERO,
+                None,
+                Some(&[selected]),
+            )
+            .await
+            .unwrap();
+        let inputs = psbt
+            .unsigned_tx
+            .input
+            .iter()
+            .map(|input| input.previous_output)
+            .collect::<Vec<_>>();
+        assert_eq!(inputs, vec![selected]);
+
+        let psbt = wallet
+            .send_to_address(
+                wallet.new_address().await.unwrap(),
+                Amount::from_sat(120_000),
+                Amount::ZERO,
+                None,
+            )
+            .await;
+        assert!(psbt.is_err(), "frozen UTXO must not be selected");
+    }
+
     #[test]
     fn printing_status_change_doesnt_spam_on_same_status() {
         let writer = capture_logs(LevelFilter::TRACE);
@@ -3395,7 +3990,7 @@ TRACE swap::bitcoin::wallet: Bitcoin transaction status changed txid=00000000000
                     .await;
 
                 let (amount, spending_fee) = wallet.max_giveable(TxLock::script_size()).await.unwrap();
-                let psbt: PartiallySignedTransaction = TxLock::new(&wallet, amount, spending_fee, PublicKey::from(alice), PublicKey::from(bob), wallet.new_address().await.unwrap()).await.unwrap().into();
+                let psbt: PartiallySignedTransaction = TxLock::new(&wallet, amount, spending_fee, PublicKey::from(alice), PublicKey::from(bob), wallet.new_address().await.unwrap(), None).await.unwrap().into();
                 let result = wallet.sign_and_finalize(psbt).await;
 
                 result.expect("transaction to be signed");
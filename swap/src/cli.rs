@@ -2,14 +2,18 @@ pub mod api;
 mod behaviour;
 pub mod cancel_and_refund;
 pub mod command;
+pub mod diagnostics;
 mod event_loop;
 mod list_sellers;
+pub mod order;
+pub mod peers;
+pub mod reputation;
 pub mod transport;
 pub mod watcher;
 
 pub use behaviour::{Behaviour, OutEvent};
 pub use cancel_and_refund::{cancel, cancel_and_refund, refund};
-pub use event_loop::{EventLoop, EventLoopHandle};
+pub use event_loop::{EventLoop, EventLoopHandle, EventLoopService};
 pub use list_sellers::{list_sellers, SellerStatus};
 
 #[cfg(test)]
@@ -154,6 +158,8 @@ mod tests {
                 peer_id: asb_peer_id,
                 quote: static_quote,
                 version: Version::parse("1.0.0").unwrap(),
+                reputation: Default::default(),
+                settings: Default::default(),
             }),
         }
     }
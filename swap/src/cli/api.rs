@@ -2,21 +2,28 @@ pub mod request;
 pub mod tauri_bindings;
 
 use crate::cli::command::{Bitcoin, Monero};
-use crate::common::tor::init_tor_client;
+use crate::cli::{self, EventLoop, EventLoopHandle, EventLoopService};
+use crate::common::metrics;
+use crate::common::tor::{
+    init_tor_client, start_node_proxy, start_socks_proxy, LocalConnections, SocksProxy,
+};
 use crate::common::tracing_util::Format;
 use crate::database::{open_db, AccessMode};
 use crate::env::{Config as EnvConfig, GetConfig, Mainnet, Testnet};
 use crate::fs::system_data_dir;
 use crate::monero::Wallets;
 use crate::network::rendezvous::XmrBtcNamespace;
+use crate::network::swarm;
 use crate::protocol::Database;
 use crate::seed::Seed;
 use crate::{bitcoin, common, monero};
 use anyhow::{bail, Context as AnyContext, Error, Result};
 use arti_client::TorClient;
 use futures::future::try_join_all;
+use libp2p::{Multiaddr, PeerId};
 use std::fmt;
 use std::future::Future;
+use std::net::SocketAddr;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Once};
 use tauri_bindings::{
@@ -73,77 +80,108 @@ impl PendingTaskList {
     }
 }
 
-/// The `SwapLock` manages the state of the current swap, ensuring that only one swap can be active at a time.
+/// The `SwapLock` manages the state of the currently running swaps, ensuring that the same swap
+/// is never run twice at the same time. Different swaps can run concurrently.
 /// It includes:
-/// - A lock for the current swap (`current_swap`)
+/// - A lock for the current swaps (`current_swaps`)
 /// - A broadcast channel for suspension signals (`suspension_trigger`)
 ///
 /// The `SwapLock` provides methods to acquire and release the swap lock, and to listen for suspension signals.
 /// This ensures that swap operations do not overlap and can be safely suspended if needed.
 pub struct SwapLock {
-    current_swap: RwLock<Option<Uuid>>,
-    suspension_trigger: Sender<()>,
+    current_swaps: RwLock<Vec<Uuid>>,
+    /// Carries the swap which should be suspended, `None` suspends all of them.
+    suspension_trigger: Sender<Option<Uuid>>,
 }
 
 impl SwapLock {
     pub fn new() -> Self {
         let (suspension_trigger, _) = broadcast::channel(10);
         SwapLock {
-            current_swap: RwLock::new(None),
+            current_swaps: RwLock::new(Vec::new()),
             suspension_trigger,
         }
     }
 
-    pub async fn listen_for_swap_force_suspension(&self) -> Result<(), Error> {
+    /// Resolves once the given swap, or all swaps, are asked to suspend.
+    pub async fn listen_for_swap_force_suspension(&self, swap_id: Uuid) -> Result<(), Error> {
         let mut listener = self.suspension_trigger.subscribe();
-        let event = listener.recv().await;
-        match event {
-            Ok(_) => Ok(()),
-            Err(e) => {
-                tracing::error!("Error receiving swap suspension signal: {}", e);
-                bail!(e)
+        loop {
+            match listener.recv().await {
+                Ok(None) => return Ok(()),
+                Ok(Some(suspended)) if suspended == swap_id => return Ok(()),
+                Ok(Some(_)) => continue,
+                Err(e) => {
+                    tracing::error!("Error receiving swap suspension signal: {}", e);
+                    bail!(e)
+                }
             }
         }
     }
 
     pub async fn acquire_swap_lock(&self, swap_id: Uuid) -> Result<(), Error> {
-        let mut current_swap = self.current_swap.write().await;
-        if current_swap.is_some() {
-            bail!("There already exists an active swap lock");
+        self.acquire_swap_locks(&[swap_id]).await
+    }
+
+    /// Acquires the lock for several swaps at once, e.g. the swaps of an order.
+    ///
+    /// Fails without acquiring any of them if one is already running.
+    /// Each of them releases its lock on its own via [`SwapLock::release_swap_lock`].
+    pub async fn acquire_swap_locks(&self, swap_ids: &[Uuid]) -> Result<(), Error> {
+        let mut current_swaps = self.current_swaps.write().await;
+        if let Some(running) = swap_ids.iter().find(|id| current_swaps.contains(id)) {
+            bail!("Swap {} is already running", running);
         }
 
-        tracing::debug!(swap_id = %swap_id, "Acquiring swap lock");
-        *current_swap = Some(swap_id);
+        tracing::debug!(swap_ids = ?swap_ids, "Acquiring swap lock");
+        current_swaps.extend_from_slice(swap_ids);
         Ok(())
     }
 
-    pub async fn get_current_swap_id(&self) -> Option<Uuid> {
-        *self.current_swap.read().await
+    pub async fn get_current_swap_ids(&self) -> Vec<Uuid> {
+        self.current_swaps.read().await.clone()
+    }
+
+    pub async fn is_running(&self, swap_id: Uuid) -> bool {
+        self.current_swaps.read().await.contains(&swap_id)
     }
 
     /// Sends a signal to suspend all ongoing swap processes.
     ///
     /// This function performs the following steps:
-    /// 1. Triggers the suspension by sending a unit `()` signal to all listeners via `self.suspension_trigger`.
-    /// 2. Polls the `current_swap` state every 50 milliseconds to check if it has been set to `None`, indicating that the swap processes have been suspended and the lock released.
-    /// 3. If the lock is not released within 10 seconds, the function returns an error.
+    /// 1. Triggers the suspension by sending a `None` signal to all listeners via `self.suspension_trigger`.
+    /// 2. Polls the `current_swaps` state every 50 milliseconds to check if it is empty, indicating that the swap processes have been suspended and their locks released.
+    /// 3. If the locks are not released within 10 seconds, the function returns an error.
     ///
     /// If we send a suspend signal while no swap is in progress, the function will not fail, but will return immediately.
     ///
     /// # Returns
-    /// - `Ok(())` if the swap lock is successfully released.
-    /// - `Err(Error)` if the function times out waiting for the swap lock to be released.
+    /// - `Ok(())` if the swap locks are successfully released.
+    /// - `Err(Error)` if the function times out waiting for the swap locks to be released.
     ///
     /// # Notes
     /// The 50ms polling interval is considered negligible overhead compared to the typical time required to suspend ongoing swap processes.
     pub async fn send_suspend_signal(&self) -> Result<(), Error> {
+        let _ = self.suspension_trigger.send(None)?;
+
+        self.wait_for_release(|current_swaps| current_swaps.is_empty())
+            .await
+    }
+
+    /// Like [`SwapLock::send_suspend_signal`] but only suspends the given swap, the others keep running.
+    pub async fn send_suspend_signal_to(&self, swap_id: Uuid) -> Result<(), Error> {
+        let _ = self.suspension_trigger.send(Some(swap_id))?;
+
+        self.wait_for_release(|current_swaps| !current_swaps.contains(&swap_id))
+            .await
+    }
+
+    async fn wait_for_release(&self, released: impl Fn(&[Uuid]) -> bool) -> Result<(), Error> {
         const TIMEOUT: u64 = 10_000;
         const INTERVAL: u64 = 50;
 
-        let _ = self.suspension_trigger.send(())?;
-
         for _ in 0..(TIMEOUT / INTERVAL) {
-            if self.get_current_swap_id().await.is_none() {
+            if released(&self.current_swaps.read().await) {
                 return Ok(());
             }
             tokio::time::sleep(tokio::time::Duration::from_millis(INTERVAL)).await;
@@ -152,17 +190,15 @@ impl SwapLock {
         bail!("Timed out waiting for swap lock to be released");
     }
 
-    pub async fn release_swap_lock(&self) -> Result<Uuid, Error> {
-        let mut current_swap = self.current_swap.write().await;
-        if let Some(swap_id) = current_swap.as_ref() {
-            tracing::debug!(swap_id = %swap_id, "Releasing swap lock");
-
-            let prev_swap_id = *swap_id;
-            *current_swap = None;
-            drop(current_swap);
-            Ok(prev_swap_id)
-        } else {
-            bail!("There is no current swap lock to release");
+    pub async fn release_swap_lock(&self, swap_id: Uuid) -> Result<(), Error> {
+        let mut current_swaps = self.current_swaps.write().await;
+        match current_swaps.iter().position(|current| *current == swap_id) {
+            Some(index) => {
+                tracing::debug!(swap_id = %swap_id, "Releasing swap lock");
+                current_swaps.remove(index);
+                Ok(())
+            }
+            None => bail!("There is no swap lock for swap {} to release", swap_id),
         }
     }
 }
@@ -189,6 +225,10 @@ pub struct Context {
     bitcoin_wallet: Option<Arc<bitcoin::Wallet>>,
     monero_manager: Option<Arc<monero::Wallets>>,
     tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
+    /// The SOCKS proxy which connects through the Tor client.
+    tor_socks_proxy: Option<SocksProxy>,
+    /// The event loop all swaps run on, started with the first swap and again whenever it stopped.
+    swap_event_loop: Arc<TokioMutex<Option<EventLoopService>>>,
     #[allow(dead_code)]
     monero_rpc_pool_handle: Option<Arc<monero_rpc_pool::PoolHandle>>,
 }
@@ -203,6 +243,8 @@ pub struct ContextBuilder {
     debug: bool,
     json: bool,
     tor: bool,
+    always_tor: bool,
+    direct_local_connections: bool,
     tauri_handle: Option<TauriHandle>,
 }
 
@@ -226,6 +268,8 @@ impl ContextBuilder {
             debug: false,
             json: false,
             tor: false,
+            always_tor: false,
+            direct_local_connections: false,
             tauri_handle: None,
         }
     }
@@ -279,8 +323,30 @@ impl ContextBuilder {
         self
     }
 
+    /// Whether to refuse reaching Electrum servers and Monero nodes without Tor (default false)
+    ///
+    /// Implies `with_tor(true)`. If the Tor client cannot be bootstrapped, building the
+    /// Context fails instead of falling back to clearnet.
+    pub fn with_always_tor(mut self, always_tor: bool) -> Self {
+        self.always_tor = always_tor;
+        self
+    }
+
+    /// Whether to reach Electrum servers and Monero nodes on this machine or the local
+    /// network directly while using Tor, which cannot reach them (default false)
+    ///
+    /// Cannot be combined with `with_always_tor(true)`.
+    pub fn with_direct_local_connections(mut self, direct_local_connections: bool) -> Self {
+        self.direct_local_connections = direct_local_connections;
+        self
+    }
+
     /// Takes the builder, initializes the context by initializing the wallets and other components and returns the Context.
     pub async fn build(self) -> Result<Context> {
+        if self.always_tor && self.direct_local_connections {
+            bail!("Direct connections to local nodes cannot be enabled when always using Tor");
+        }
+
         let data_dir = &data::data_dir_from(self.data, self.is_testnet)?;
 
         // Initialize logging
@@ -336,6 +402,43 @@ impl ContextBuilder {
         database_progress_handle.finish();
 
         let tauri_handle = &self.tauri_handle.clone();
+        let bump_fees = self
+            .bitcoin
+            .as_ref()
+            .is_some_and(|bitcoin| bitcoin.bump_fees);
+
+        // We bootstrap Tor before opening the wallets such that they can reach the
+        // Electrum servers and Monero nodes through it
+        let tor = if self.tor || self.always_tor {
+            match init_tor_client(data_dir, tauri_handle.clone()).await {
+                Ok(tor_client) => Some(tor_client),
+                Err(err) if self.always_tor => {
+                    return Err(Error::new(err)
+                        .context("Failed to create Tor client. Refusing to continue without Tor"));
+                }
+                Err(err) => {
+                    tracing::warn!(%err, "Failed to create Tor client. We will continue without Tor");
+                    None
+                }
+            }
+        } else {
+            tracing::warn!("Internal Tor client not enabled, skipping initialization");
+            None
+        };
+
+        let local_connections = match self.direct_local_connections {
+            true => LocalConnections::Direct,
+            false => LocalConnections::Refuse,
+        };
+
+        let tor_socks_proxy = match &tor {
+            Some(tor_client) => Some(
+                start_socks_proxy(tor_client.clone(), local_connections)
+                    .await
+                    .context("Failed to start SOCKS proxy for the Tor client")?,
+            ),
+            None => None,
+        };
 
         let initialize_bitcoin_wallet = async {
             match self.bitcoin {
@@ -354,6 +457,7 @@ impl ContextBuilder {
                         data_dir,
                         env_config,
                         target_block,
+                        tor_socks_proxy.clone(),
                         self.tauri_handle.clone(),
                     )
                     .await?;
@@ -379,15 +483,23 @@ impl ContextBuilder {
 
                     // If we are instructed to use a pool, we start it and use it
                     // Otherwise we use the single node address provided by the user
-                    let (monero_node_address, rpc_pool_handle) = match monero_config {
+                    //
+                    // The pool makes its requests through Tor itself, the wallet only
+                    // needs the proxy to connect to a single node
+                    let (node_address, node_proxy, rpc_pool_handle) = match monero_config {
                         MoneroNodeConfig::Pool => {
                             // Start RPC pool and use it
                             let (server_info, mut status_receiver, pool_handle) =
                                 monero_rpc_pool::start_server_with_random_port(
-                                    monero_rpc_pool::config::Config::new_random_port(
-                                        "127.0.0.1".to_string(),
-                                        data_dir.join("monero-rpc-pool"),
-                                    ),
+                                    monero_rpc_pool::config::Config {
+                                        socks5_proxy: tor_socks_proxy
+                                            .as_ref()
+                                            .map(SocksProxy::authority),
+                                        ..monero_rpc_pool::config::Config::new_random_port(
+                                            "127.0.0.1".to_string(),
+                                            data_dir.join("monero-rpc-pool"),
+                                        )
+                                    },
                                     match self.is_testnet {
                                         true => crate::monero::Network::Stagenet,
                                         false => crate::monero::Network::Mainnet,
@@ -409,14 +521,30 @@ impl ContextBuilder {
                                 });
                             }
 
-                            (rpc_url, Some(Arc::new(pool_handle)))
+                            (rpc_url, None, Some(Arc::new(pool_handle)))
+                        }
+                        MoneroNodeConfig::SingleNode { url } => {
+                            let proxy = match &tor {
+                                Some(tor_client) => Some(
+                                    start_monero_node_proxy(
+                                        tor_client.clone(),
+                                        &url,
+                                        local_connections,
+                                    )
+                                    .await?
+                                    .to_string(),
+                                ),
+                                None => None,
+                            };
+
+                            (url, proxy, None)
                         }
-                        MoneroNodeConfig::SingleNode { url } => (url, None),
                     };
 
                     let wallets = init_monero_wallet(
                         data_dir.as_path(),
-                        monero_node_address,
+                        node_address,
+                        node_proxy,
                         env_config,
                         tauri_handle.clone(),
                     )
@@ -430,28 +558,8 @@ impl ContextBuilder {
             }
         };
 
-        let initialize_tor_client = async {
-            // Don't init a tor client unless we should use it.
-            if !self.tor {
-                tracing::warn!("Internal Tor client not enabled, skipping initialization");
-                return Ok(None);
-            }
-
-            let maybe_tor_client = init_tor_client(data_dir, tauri_handle.clone())
-                .await
-                .inspect_err(|err| {
-                    tracing::warn!(%err, "Failed to create Tor client. We will continue without Tor");
-                })
-                .ok();
-
-            Ok(maybe_tor_client)
-        };
-
-        let (bitcoin_wallet, (monero_manager, monero_rpc_pool_handle), tor) = tokio::try_join!(
-            initialize_bitcoin_wallet,
-            initialize_monero_wallet,
-            initialize_tor_client,
-        )?;
+        let (bitcoin_wallet, (monero_manager, monero_rpc_pool_handle)) =
+            tokio::try_join!(initialize_bitcoin_wallet, initialize_monero_wallet)?;
 
         // If we have a bitcoin wallet and a tauri handle, we start a background task
         if let Some(wallet) = bitcoin_wallet.clone() {
@@ -466,6 +574,24 @@ impl ContextBuilder {
             }
         }
 
+        if let Some(wallet) = bitcoin_wallet.clone() {
+            if bump_fees {
+                tokio::spawn(bitcoin::fee_bump::run(
+                    wallet,
+                    db.clone(),
+                    bitcoin::FeeBumpPolicy::default(),
+                ));
+            }
+        }
+
+        if metrics::is_enabled() {
+            tokio::spawn(metrics::collect(
+                bitcoin_wallet.clone(),
+                monero_manager.clone(),
+                None,
+            ));
+        }
+
         tauri_handle.emit_context_init_progress_event(TauriContextStatusEvent::Available);
 
         let context = Context {
@@ -485,6 +611,8 @@ impl ContextBuilder {
             tasks,
             tauri_handle: self.tauri_handle,
             tor_client: tor,
+            tor_socks_proxy,
+            swap_event_loop: Default::default(),
             monero_rpc_pool_handle,
         };
 
@@ -519,10 +647,67 @@ impl Context {
             tasks: PendingTaskList::default().into(),
             tauri_handle: None,
             tor_client: None,
+            tor_socks_proxy: None,
+            swap_event_loop: Default::default(),
             monero_rpc_pool_handle: None,
         }
     }
 
+    /// Adds the swap to the event loop which all swaps share and returns the
+    /// [`EventLoopHandle`] the swap talks to it through.
+    ///
+    /// The event loop is started if it is not running, either because this is the first swap or
+    /// because it stopped once its last swap was done or it failed.
+    pub(crate) async fn new_swap_event_loop_handle(
+        &self,
+        swap_id: Uuid,
+        alice_peer_id: PeerId,
+        alice_addresses: Vec<Multiaddr>,
+    ) -> Result<EventLoopHandle> {
+        let mut swap_event_loop = self.swap_event_loop.lock().await;
+
+        if let Some(service) = swap_event_loop.as_ref() {
+            match service.new_handle(swap_id, alice_peer_id, alice_addresses.clone()) {
+                Ok(handle) => return Ok(handle),
+                Err(_) => tracing::debug!("The event loop stopped, starting a new one"),
+            }
+        }
+
+        let service = self.start_swap_event_loop().await?;
+        let handle = service.new_handle(swap_id, alice_peer_id, alice_addresses)?;
+        *swap_event_loop = Some(service);
+
+        Ok(handle)
+    }
+
+    async fn start_swap_event_loop(&self) -> Result<EventLoopService> {
+        let identity = self
+            .config
+            .seed
+            .as_ref()
+            .context("Could not get seed")?
+            .derive_libp2p_identity();
+        let bitcoin_wallet = self
+            .bitcoin_wallet
+            .clone()
+            .context("Could not get Bitcoin wallet")?;
+
+        let behaviour = cli::Behaviour::new(
+            self.config.env_config,
+            bitcoin_wallet,
+            (identity.clone(), self.config.namespace),
+        );
+        let swarm = swarm::cli(identity, self.tor_client.clone(), behaviour).await?;
+
+        tracing::debug!(peer_id = %swarm.local_peer_id(), "Network layer initialized");
+
+        let event_loop = EventLoop::new(swarm, self.db.clone());
+        let service = event_loop.service();
+        self.tasks.spawn(event_loop.run()).await;
+
+        Ok(service)
+    }
+
     pub fn cleanup(&self) -> Result<()> {
         // TODO: close all monero wallets
 
@@ -550,6 +735,7 @@ async fn init_bitcoin_wallet(
     data_dir: &Path,
     env_config: EnvConfig,
     bitcoin_target_block: u16,
+    socks5_proxy: Option<SocksProxy>,
     tauri_handle_option: Option<TauriHandle>,
 ) -> Result<bitcoin::Wallet<bdk_wallet::rusqlite::Connection, bitcoin::wallet::Client>> {
     let mut builder = bitcoin::wallet::WalletBuilder::default()
@@ -567,6 +753,10 @@ async fn init_bitcoin_wallet(
         builder = builder.tauri_handle(handle.clone());
     }
 
+    if let Some(proxy) = socks5_proxy {
+        builder = builder.socks5_proxy(proxy);
+    }
+
     let wallet = builder
         .build()
         .await
@@ -575,9 +765,39 @@ async fn init_bitcoin_wallet(
     Ok(wallet)
 }
 
+/// Starts a SOCKS proxy through the Tor client which only reaches the Monero node at `url`.
+///
+/// The SOCKS client of the Monero wallet cannot authenticate to our main proxy.
+async fn start_monero_node_proxy(
+    tor_client: Arc<TorClient<TokioRustlsRuntime>>,
+    url: &str,
+    local_connections: LocalConnections,
+) -> Result<SocketAddr> {
+    let parsed = match url.contains("://") {
+        true => url::Url::parse(url),
+        false => url::Url::parse(&format!("http://{}", url)),
+    }
+    .with_context(|| format!("Failed to parse Monero node address {}", url))?;
+
+    let host = parsed
+        .host_str()
+        .context("Monero node address has no host")?
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let port = parsed
+        .port_or_known_default()
+        .context("Monero node address has no port")?;
+
+    start_node_proxy(tor_client, host, port, local_connections)
+        .await
+        .context("Failed to start SOCKS proxy for the Monero node")
+}
+
 async fn init_monero_wallet(
     data_dir: &Path,
     monero_daemon_address: String,
+    monero_daemon_proxy: Option<String>,
     env_config: EnvConfig,
     tauri_handle: Option<TauriHandle>,
 ) -> Result<Arc<Wallets>> {
@@ -587,6 +807,7 @@ async fn init_monero_wallet(
     let daemon = monero_sys::Daemon {
         address: monero_daemon_address,
         ssl: false,
+        proxy: monero_daemon_proxy,
     };
 
     // This is the name of a wallet we only use for blockchain monitoring
@@ -724,3 +945,53 @@ pub mod api_test {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs until the swap is asked to suspend, like the swaps spawned by the API.
+    async fn spawn_swap(swap_lock: Arc<SwapLock>, swap_id: Uuid) -> JoinHandle<()> {
+        swap_lock.acquire_swap_lock(swap_id).await.unwrap();
+
+        let swap = tokio::spawn(async move {
+            swap_lock
+                .listen_for_swap_force_suspension(swap_id)
+                .await
+                .unwrap();
+            swap_lock.release_swap_lock(swap_id).await.unwrap();
+        });
+
+        // Let the swap subscribe to the suspension signals
+        tokio::task::yield_now().await;
+
+        swap
+    }
+
+    #[tokio::test]
+    async fn swap_lock_runs_different_swaps_concurrently() {
+        let swap_lock = Arc::new(SwapLock::new());
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        let first_swap = spawn_swap(swap_lock.clone(), first).await;
+        let second_swap = spawn_swap(swap_lock.clone(), second).await;
+
+        assert!(swap_lock.acquire_swap_lock(first).await.is_err());
+        assert!(swap_lock
+            .acquire_swap_locks(&[Uuid::new_v4(), second])
+            .await
+            .is_err());
+        assert_eq!(swap_lock.get_current_swap_ids().await, vec![first, second]);
+
+        swap_lock.send_suspend_signal_to(first).await.unwrap();
+
+        first_swap.await.unwrap();
+        assert!(!second_swap.is_finished());
+        assert!(!swap_lock.is_running(first).await);
+        assert!(swap_lock.is_running(second).await);
+
+        swap_lock.send_suspend_signal().await.unwrap();
+
+        assert!(swap_lock.get_current_swap_ids().await.is_empty());
+    }
+}
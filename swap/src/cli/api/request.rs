@@ -2,22 +2,28 @@ use super::tauri_bindings::TauriHandle;
 use crate::bitcoin::{wallet, CancelTimelock, ExpiredTimelocks, PunishTimelock};
 use crate::cli::api::tauri_bindings::{SelectMakerDetails, TauriEmitter, TauriSwapProgressEvent};
 use crate::cli::api::Context;
+use crate::cli::diagnostics::{self, Connectivity, NodeStatus, VersionInfo};
 use crate::cli::list_sellers::{list_sellers_init, QuoteWithAddress, UnreachableSeller};
-use crate::cli::{list_sellers as list_sellers_impl, EventLoop, SellerStatus};
-use crate::common::{get_logs, redact};
+use crate::cli::peers;
+use crate::cli::reputation::{self, Reputation};
+use crate::cli::{list_sellers as list_sellers_impl, order, SellerStatus};
+use crate::common::tor::SocksProxy;
+use crate::common::{get_logs, redact, LogFilter};
+use crate::database::{PeerSettings, PeerStatus};
 use crate::libp2p_ext::MultiAddrExt;
 use crate::monero::wallet_rpc::MoneroDaemon;
 use crate::monero::MoneroAddressPool;
 use crate::network::quote::BidQuote;
 use crate::network::rendezvous::XmrBtcNamespace;
-use crate::network::swarm;
 use crate::protocol::bob::{BobState, Swap};
+use crate::protocol::export::{self, DateRange, ExportFormat, SwapRecord};
+use crate::protocol::timeline::{self, TimelineEntry};
 use crate::protocol::{bob, Database, State};
 use crate::{bitcoin, cli, monero};
 use ::bitcoin::address::NetworkUnchecked;
 use ::bitcoin::Txid;
 use ::monero::Network;
-use anyhow::{bail, Context as AnyContext, Result};
+use anyhow::{anyhow, bail, Context as AnyContext, Result};
 use arti_client::TorClient;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
@@ -27,15 +33,17 @@ use monero_seed::{Language, Seed as MoneroSeed};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryInto;
 use std::future::Future;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use time::OffsetDateTime;
 use tokio_util::task::AbortOnDropHandle;
 use tor_rtcompat::tokio::TokioRustlsRuntime;
-use tracing::debug_span;
+use tracing::info_span;
 use tracing::Instrument;
 use tracing::Span;
 use typeshare::typeshare;
@@ -53,8 +61,11 @@ pub trait Request {
 }
 
 /// This generates a tracing span which is attached to all logs caused by a swap
+///
+/// It is an info span such that the swap id is attached to the logs even if debug logs are
+/// disabled.
 fn get_swap_tracing_span(swap_id: Uuid) -> Span {
-    debug_span!("swap", swap_id = %swap_id)
+    info_span!("swap", swap_id = %swap_id)
 }
 
 // BuyXmr
@@ -68,6 +79,10 @@ pub struct BuyXmrArgs {
     #[typeshare(serialized_as = "Option<string>")]
     pub bitcoin_change_address: Option<bitcoin::Address<NetworkUnchecked>>,
     pub monero_receive_pool: MoneroAddressPool,
+    /// Only fund the Bitcoin lock transaction from these UTXOs.
+    #[typeshare(serialized_as = "Option<Vec<string>>")]
+    #[serde(default)]
+    pub bitcoin_inputs: Option<Vec<bitcoin::OutPoint>>,
 }
 
 #[typeshare]
@@ -89,6 +104,62 @@ impl Request for BuyXmrArgs {
     }
 }
 
+// PlaceOrder
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PlaceOrderArgs {
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub rendezvous_points: Vec<Multiaddr>,
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub sellers: Vec<Multiaddr>,
+    #[typeshare(serialized_as = "Option<string>")]
+    pub bitcoin_change_address: Option<bitcoin::Address<NetworkUnchecked>>,
+    pub monero_receive_pool: MoneroAddressPool,
+    /// Only fund the order from these UTXOs.
+    #[typeshare(serialized_as = "Option<Vec<string>>")]
+    #[serde(default)]
+    pub bitcoin_inputs: Option<Vec<bitcoin::OutPoint>>,
+    /// Spend at most this amount on the order, including the fees of the lock transactions.
+    /// Defaults to everything the wallet can spend.
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(default, with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub btc_amount: Option<bitcoin::Amount>,
+    /// Split the order across at most this many makers.
+    pub max_makers: u8,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PlaceOrderResponse {
+    #[typeshare(serialized_as = "string")]
+    pub order_id: Uuid,
+    /// The transaction which split our funds into one output per swap.
+    #[typeshare(serialized_as = "string")]
+    pub funding_txid: Txid,
+    pub swaps: Vec<OrderSwap>,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrderSwap {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    #[typeshare(serialized_as = "string")]
+    pub seller_peer_id: PeerId,
+    pub quote: BidQuote,
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub btc_amount: bitcoin::Amount,
+}
+
+impl Request for PlaceOrderArgs {
+    type Response = PlaceOrderResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        place_order(self, ctx).await
+    }
+}
+
 // ResumeSwap
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -131,6 +202,24 @@ impl Request for CancelAndRefundArgs {
     }
 }
 
+// RequestEarlyRefund
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RequestEarlyRefundArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+impl Request for RequestEarlyRefundArgs {
+    type Response = ResumeSwapResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let swap_span = get_swap_tracing_span(self.swap_id);
+
+        request_early_refund(self, ctx).instrument(swap_span).await
+    }
+}
+
 // MoneroRecovery
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -157,6 +246,10 @@ pub struct WithdrawBtcArgs {
     #[typeshare(serialized_as = "string")]
     #[serde(with = "crate::bitcoin::address_serde")]
     pub address: bitcoin::Address,
+    /// Only spend these UTXOs. If the amount is omitted, all of them are swept.
+    #[typeshare(serialized_as = "Option<Vec<string>>")]
+    #[serde(default)]
+    pub inputs: Option<Vec<bitcoin::OutPoint>>,
 }
 
 #[typeshare]
@@ -176,6 +269,89 @@ impl Request for WithdrawBtcArgs {
     }
 }
 
+// ListUnspent
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListUnspentArgs;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ListUnspentResponse {
+    pub utxos: Vec<bitcoin::Utxo>,
+}
+
+impl Request for ListUnspentArgs {
+    type Response = ListUnspentResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        list_unspent(ctx).await
+    }
+}
+
+// SetUtxoLabel
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SetUtxoLabelArgs {
+    #[typeshare(serialized_as = "string")]
+    pub outpoint: bitcoin::OutPoint,
+    /// The new label. Omitting it removes the current label.
+    pub label: Option<String>,
+}
+
+impl Request for SetUtxoLabelArgs {
+    type Response = serde_json::Value;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        set_utxo_label(self, ctx).await
+    }
+}
+
+// FreezeUtxo
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct FreezeUtxoArgs {
+    #[typeshare(serialized_as = "string")]
+    pub outpoint: bitcoin::OutPoint,
+    pub frozen: bool,
+}
+
+impl Request for FreezeUtxoArgs {
+    type Response = serde_json::Value;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        freeze_utxo(self, ctx).await
+    }
+}
+
+// BumpFee
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BumpFeeArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    /// The fee rate (in sat/vB) the unconfirmed transactions should reach.
+    /// Defaults to the current fee estimate.
+    #[typeshare(serialized_as = "number")]
+    #[serde(default)]
+    pub fee_rate: Option<u64>,
+}
+
+#[typeshare]
+#[derive(Debug, Serialize)]
+pub struct BumpFeeResponse {
+    pub transactions: Vec<bitcoin::BumpReport>,
+}
+
+impl Request for BumpFeeArgs {
+    type Response = BumpFeeResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        let swap_span = get_swap_tracing_span(self.swap_id);
+
+        bump_fee(self, ctx).instrument(swap_span).await
+    }
+}
+
 // ListSellers
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -200,6 +376,104 @@ impl Request for ListSellersArgs {
     }
 }
 
+// ListPeers
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ListPeersArgs;
+
+#[typeshare]
+#[derive(Debug, Serialize)]
+pub struct PeerInfo {
+    #[typeshare(serialized_as = "string")]
+    pub peer_id: PeerId,
+    pub addresses: Vec<String>,
+    pub settings: PeerSettings,
+    pub reputation: Reputation,
+}
+
+#[typeshare]
+#[derive(Debug, Serialize)]
+pub struct ListPeersResponse {
+    pub peers: Vec<PeerInfo>,
+}
+
+impl Request for ListPeersArgs {
+    type Response = ListPeersResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        list_peers(ctx).await
+    }
+}
+
+// AddPeer
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct AddPeerArgs {
+    /// The address of the peer. Must contain its peer ID.
+    #[typeshare(serialized_as = "string")]
+    pub multiaddr: Multiaddr,
+    pub label: Option<String>,
+}
+
+impl Request for AddPeerArgs {
+    type Response = serde_json::Value;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        add_peer(self, ctx).await
+    }
+}
+
+// RemovePeer
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct RemovePeerArgs {
+    #[typeshare(serialized_as = "string")]
+    pub peer_id: PeerId,
+}
+
+impl Request for RemovePeerArgs {
+    type Response = serde_json::Value;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        remove_peer(self, ctx).await
+    }
+}
+
+// BanPeer
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BanPeerArgs {
+    #[typeshare(serialized_as = "string")]
+    pub peer_id: PeerId,
+    pub banned: bool,
+}
+
+impl Request for BanPeerArgs {
+    type Response = serde_json::Value;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        ban_peer(self, ctx).await
+    }
+}
+
+// SetPeerLabel
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SetPeerLabelArgs {
+    #[typeshare(serialized_as = "string")]
+    pub peer_id: PeerId,
+    /// The new label. Omitting it removes the current label.
+    pub label: Option<String>,
+}
+
+impl Request for SetPeerLabelArgs {
+    type Response = serde_json::Value;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        set_peer_label(self, ctx).await
+    }
+}
+
 // GetSwapInfo
 #[typeshare]
 #[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -239,6 +513,10 @@ pub struct GetSwapInfoResponse {
     pub punish_timelock: PunishTimelock,
     pub timelock: Option<ExpiredTimelocks>,
     pub monero_receive_pool: MoneroAddressPool,
+    pub timeline: Vec<TimelineEntry>,
+    /// Set if the swap is part of an order which was split across several makers.
+    #[typeshare(serialized_as = "Option<string>")]
+    pub order_id: Option<Uuid>,
 }
 
 impl Request for GetSwapInfoArgs {
@@ -272,6 +550,65 @@ impl Request for BalanceArgs {
     }
 }
 
+// GetBitcoinTransactions
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GetBitcoinTransactionsArgs {
+    pub force_refresh: bool,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetBitcoinTransactionsResponse {
+    pub transactions: Vec<bitcoin::WalletTransaction>,
+}
+
+impl Request for GetBitcoinTransactionsArgs {
+    type Response = GetBitcoinTransactionsResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        get_bitcoin_transactions(self, ctx).await
+    }
+}
+
+// GetMoneroHistory
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GetMoneroHistoryArgs;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetMoneroHistoryResponse {
+    pub transfers: Vec<monero::MoneroTransfer>,
+}
+
+impl Request for GetMoneroHistoryArgs {
+    type Response = GetMoneroHistoryResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        get_monero_history(ctx).await
+    }
+}
+
+// GetMoneroSubaddresses
+#[typeshare]
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GetMoneroSubaddressesArgs;
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct GetMoneroSubaddressesResponse {
+    pub subaddresses: Vec<monero::MoneroSubaddress>,
+}
+
+impl Request for GetMoneroSubaddressesArgs {
+    type Response = GetMoneroSubaddressesResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        get_monero_subaddresses(ctx).await
+    }
+}
+
 // GetHistory
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug)]
@@ -283,6 +620,9 @@ pub struct GetHistoryEntry {
     #[typeshare(serialized_as = "string")]
     swap_id: Uuid,
     state: String,
+    /// Set if the swap is part of an order which was split across several makers.
+    #[typeshare(serialized_as = "Option<string>")]
+    order_id: Option<Uuid>,
 }
 
 #[typeshare]
@@ -299,6 +639,84 @@ impl Request for GetHistoryArgs {
     }
 }
 
+// ExportHistory
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportHistoryArgs {
+    pub format: ExportFormat,
+    /// Only export swaps started on or after this day (`YYYY-MM-DD`, UTC).
+    pub from: Option<String>,
+    /// Only export swaps started on or before this day (`YYYY-MM-DD`, UTC).
+    pub to: Option<String>,
+}
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct ExportHistoryResponse {
+    pub swaps: Vec<SwapRecord>,
+    /// The swaps rendered in the requested format.
+    pub content: String,
+}
+
+impl Request for ExportHistoryArgs {
+    type Response = ExportHistoryResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        export_history(self, ctx).await
+    }
+}
+
+// GetSwapTimeline
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetSwapTimelineArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct GetSwapTimelineResponse {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    pub timeline: Vec<TimelineEntry>,
+}
+
+impl Request for GetSwapTimelineArgs {
+    type Response = GetSwapTimelineResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        get_swap_timeline(self, ctx).await
+    }
+}
+
+// CreateDiagnostics
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateDiagnosticsArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    /// Where to write the archive, defaults to the `diagnostics` folder of the data directory.
+    #[typeshare(serialized_as = "Option<string>")]
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+}
+
+#[typeshare]
+#[derive(Serialize, Debug)]
+pub struct CreateDiagnosticsResponse {
+    /// The path of the archive.
+    pub path: String,
+}
+
+impl Request for CreateDiagnosticsArgs {
+    type Response = CreateDiagnosticsResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        create_diagnostics(self, ctx).await
+    }
+}
+
 // Additional structs
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -308,16 +726,16 @@ pub struct AliceAddress {
     pub addresses: Vec<String>,
 }
 
-// Suspend current swap
+// Suspend all running swaps
 #[derive(Debug, Deserialize)]
 pub struct SuspendCurrentSwapArgs;
 
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SuspendCurrentSwapResponse {
-    // If no swap was running, we still return Ok(...) but this is set to None
-    #[typeshare(serialized_as = "Option<string>")]
-    pub swap_id: Option<Uuid>,
+    // If no swap was running, we still return Ok(...) but this is empty
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub swap_ids: Vec<Uuid>,
 }
 
 impl Request for SuspendCurrentSwapArgs {
@@ -328,6 +746,29 @@ impl Request for SuspendCurrentSwapArgs {
     }
 }
 
+// Suspend one running swap, the others keep running
+#[typeshare]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuspendSwapArgs {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+}
+
+#[typeshare]
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SuspendSwapResponse {
+    // Whether the swap was running
+    pub suspended: bool,
+}
+
+impl Request for SuspendSwapArgs {
+    type Response = SuspendSwapResponse;
+
+    async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
+        suspend_swap(self, ctx).await
+    }
+}
+
 #[typeshare]
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetCurrentSwapArgs;
@@ -335,8 +776,9 @@ pub struct GetCurrentSwapArgs;
 #[typeshare]
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetCurrentSwapResponse {
-    #[typeshare(serialized_as = "Option<string>")]
-    pub swap_id: Option<Uuid>,
+    // All swaps which are currently running
+    #[typeshare(serialized_as = "Vec<string>")]
+    pub swap_ids: Vec<Uuid>,
 }
 
 impl Request for GetCurrentSwapArgs {
@@ -405,6 +847,15 @@ pub struct GetLogsArgs {
     pub redact: bool,
     #[typeshare(serialized_as = "Option<string>")]
     pub logs_dir: Option<PathBuf>,
+    /// Only return logs written at or after this time (RFC 3339, e.g. `2025-06-25T12:00:00Z`).
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only return logs written at or before this time (RFC 3339).
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Only return logs of this level or more severe, e.g. `warn`.
+    #[serde(default)]
+    pub level: Option<String>,
 }
 
 #[typeshare]
@@ -418,7 +869,13 @@ impl Request for GetLogsArgs {
 
     async fn request(self, ctx: Arc<Context>) -> Result<Self::Response> {
         let dir = self.logs_dir.unwrap_or(ctx.config.data_dir.join("logs"));
-        let logs = get_logs(dir, self.swap_id, self.redact).await?;
+        let filter = LogFilter::parse(
+            self.swap_id,
+            self.since.as_deref(),
+            self.until.as_deref(),
+            self.level.as_deref(),
+        )?;
+        let logs = get_logs(dir, filter, self.redact).await?;
 
         for msg in &logs {
             println!("{msg}");
@@ -473,18 +930,30 @@ impl Request for GetMoneroAddressesArgs {
 
 #[tracing::instrument(fields(method = "suspend_current_swap"), skip(context))]
 pub async fn suspend_current_swap(context: Arc<Context>) -> Result<SuspendCurrentSwapResponse> {
-    let swap_id = context.swap_lock.get_current_swap_id().await;
+    let swap_ids = context.swap_lock.get_current_swap_ids().await;
 
-    if let Some(id_value) = swap_id {
+    // If no swap was running, we still return Ok(...) with no swaps
+    if !swap_ids.is_empty() {
         context.swap_lock.send_suspend_signal().await?;
+    }
 
-        Ok(SuspendCurrentSwapResponse {
-            swap_id: Some(id_value),
-        })
-    } else {
-        // If no swap was running, we still return Ok(...) with None
-        Ok(SuspendCurrentSwapResponse { swap_id: None })
+    Ok(SuspendCurrentSwapResponse { swap_ids })
+}
+
+#[tracing::instrument(fields(method = "suspend_swap"), skip(context))]
+pub async fn suspend_swap(
+    suspend_swap: SuspendSwapArgs,
+    context: Arc<Context>,
+) -> Result<SuspendSwapResponse> {
+    let SuspendSwapArgs { swap_id } = suspend_swap;
+
+    if !context.swap_lock.is_running(swap_id).await {
+        return Ok(SuspendSwapResponse { suspended: false });
     }
+
+    context.swap_lock.send_suspend_signal_to(swap_id).await?;
+
+    Ok(SuspendSwapResponse { suspended: true })
 }
 
 #[tracing::instrument(fields(method = "get_swap_infos_all"), skip(context))]
@@ -582,6 +1051,10 @@ pub async fn get_swap_info(
 
     let monero_receive_pool = context.db.get_monero_address_pool(args.swap_id).await?;
 
+    let transitions = context.db.get_state_transitions(args.swap_id).await?;
+    let timeline = timeline::timeline(&transitions, OffsetDateTime::now_utc())?;
+    let order_id = context.db.get_swap_order(args.swap_id).await?;
+
     Ok(GetSwapInfoResponse {
         swap_id: args.swap_id,
         seller: AliceAddress {
@@ -602,6 +1075,8 @@ pub async fn get_swap_info(
         punish_timelock,
         timelock,
         monero_receive_pool,
+        timeline,
+        order_id,
     })
 }
 
@@ -618,6 +1093,7 @@ pub async fn buy_xmr(
         sellers,
         bitcoin_change_address,
         monero_receive_pool,
+        bitcoin_inputs,
     } = buy_xmr;
 
     monero_receive_pool.assert_network(context.config.env_config.monero_network)?;
@@ -630,6 +1106,14 @@ pub async fn buy_xmr(
             .expect("Could not find Bitcoin wallet"),
     );
 
+    // Fail early if the user selected inputs we cannot spend
+    if let Some(inputs) = &bitcoin_inputs {
+        bitcoin_wallet
+            .spendable_balance(Some(inputs))
+            .await
+            .context("Cannot fund the swap from the selected Bitcoin inputs")?;
+    }
+
     let bitcoin_change_address = match bitcoin_change_address {
         Some(addr) => addr
             .require_network(bitcoin_wallet.network())
@@ -646,14 +1130,6 @@ pub async fn buy_xmr(
         }
     };
 
-    let monero_wallet = Arc::clone(
-        context
-            .monero_manager
-            .as_ref()
-            .context("Could not get Monero wallet")?,
-    );
-
-    let env_config = context.config.env_config;
     let seed = context.config.seed.clone().context("Could not get seed")?;
 
     // Prepare variables for the quote fetching process
@@ -669,8 +1145,6 @@ pub async fn buy_xmr(
 
     let bitcoin_wallet_for_closures = Arc::clone(&bitcoin_wallet);
 
-    // Clone bitcoin_change_address before moving it in the emit call
-    let bitcoin_change_address_for_spawn = bitcoin_change_address.clone();
     let rendezvous_points_clone = rendezvous_points.clone();
     let sellers_clone = sellers.clone();
 
@@ -704,16 +1178,20 @@ pub async fn buy_xmr(
             bitcoin_wallet.new_address(),
             {
                 let wallet = Arc::clone(&bitcoin_wallet_for_closures);
+                let inputs = bitcoin_inputs.clone();
                 move || {
                     let w = wallet.clone();
-                    async move { w.balance().await }
+                    let inputs = inputs.clone();
+                    async move { w.spendable_balance(inputs.as_deref()).await }
                 }
             },
             {
                 let wallet = Arc::clone(&bitcoin_wallet_for_closures);
+                let inputs = bitcoin_inputs.clone();
                 move || {
                     let w = wallet.clone();
-                    async move { w.max_giveable(address_len).await }
+                    let inputs = inputs.clone();
+                    async move { w.max_giveable_from(address_len, inputs.as_deref()).await }
                 }
             },
             {
@@ -740,53 +1218,294 @@ pub async fn buy_xmr(
         ) => {
             result?
         }
-        _ = context.swap_lock.listen_for_swap_force_suspension() => {
-            context.swap_lock.release_swap_lock().await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
+        _ = context.swap_lock.listen_for_swap_force_suspension(swap_id) => {
+            context.swap_lock.release_swap_lock(swap_id).await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
             context.tauri_handle.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::Released);
             bail!("Shutdown signal received");
         },
     };
 
-    // Insert the peer_id into the database
-    context.db.insert_peer_id(swap_id, seller_peer_id).await?;
+    spawn_swap(
+        context,
+        swap_id,
+        seller_peer_id,
+        seller_multiaddr,
+        quote,
+        monero_receive_pool,
+        bitcoin_change_address,
+        tx_lock_amount,
+        tx_lock_fee,
+        bitcoin_inputs,
+    )
+    .await?;
 
-    context
-        .db
-        .insert_address(seller_peer_id, seller_multiaddr.clone())
-        .await?;
+    Ok(BuyXmrResponse { swap_id, quote })
+}
 
-    let behaviour = cli::Behaviour::new(
-        seller_peer_id,
-        env_config,
-        bitcoin_wallet.clone(),
-        (seed.derive_libp2p_identity(), context.config.namespace),
+#[tracing::instrument(fields(method = "place_order"), skip(context))]
+pub async fn place_order(
+    place_order: PlaceOrderArgs,
+    context: Arc<Context>,
+) -> Result<PlaceOrderResponse> {
+    let PlaceOrderArgs {
+        rendezvous_points,
+        sellers,
+        bitcoin_change_address,
+        monero_receive_pool,
+        bitcoin_inputs,
+        btc_amount,
+        max_makers,
+    } = place_order;
+
+    monero_receive_pool.assert_network(context.config.env_config.monero_network)?;
+    monero_receive_pool.assert_sum_to_one()?;
+
+    if max_makers == 0 {
+        bail!("An order needs at least one maker");
+    }
+
+    let bitcoin_wallet = Arc::clone(
+        context
+            .bitcoin_wallet
+            .as_ref()
+            .context("Could not get Bitcoin wallet")?,
     );
+    let seed = context.config.seed.clone().context("Could not get seed")?;
 
-    let mut swarm = swarm::cli(
-        seed.derive_libp2p_identity(),
+    let bitcoin_change_address = match bitcoin_change_address {
+        Some(addr) => addr
+            .require_network(bitcoin_wallet.network())
+            .context("Address is not on the correct network")?,
+        None => bitcoin_wallet.new_address().await?,
+    };
+
+    bitcoin_wallet.sync().await?;
+
+    let address_len = bitcoin_wallet.new_address().await?.script_pubkey().len();
+    let (max_giveable, _) = bitcoin_wallet
+        .max_giveable_from(address_len, bitcoin_inputs.as_deref())
+        .await?;
+
+    if max_giveable == bitcoin::Amount::ZERO {
+        bail!("Deposit Bitcoin into the internal wallet before placing an order");
+    }
+
+    let budget = btc_amount.map_or(max_giveable, |amount| amount.min(max_giveable));
+
+    // Every swap pays for its own lock transaction and for its output of the funding transaction
+    let reserve = bitcoin_wallet
+        .estimate_fee(bitcoin::TxLock::weight(), Some(budget))
+        .await?
+        + bitcoin_wallet
+            .estimate_fee(order::SPLIT_OUTPUT_WEIGHT, None)
+            .await?;
+
+    let rendezvous_nodes = rendezvous_points
+        .iter()
+        .filter_map(|rendezvous_point| rendezvous_point.split_peer_id())
+        .collect();
+    let fetch_sellers = list_sellers_init(
+        rendezvous_nodes,
+        context.config.namespace,
         context.tor_client.clone(),
-        behaviour,
+        seed.derive_libp2p_identity(),
+        Some(context.db.clone()),
+        context.tauri_handle.clone(),
+        None,
+        sellers,
     )
     .await?;
 
-    swarm.add_peer_address(seller_peer_id, seller_multiaddr.clone());
+    let online_sellers = fetch_sellers()
+        .await
+        .into_iter()
+        .filter_map(|seller| match seller {
+            SellerStatus::Online(quote_with_address)
+                if !quote_with_address.reputation.is_blocklisted() =>
+            {
+                Some(quote_with_address)
+            }
+            SellerStatus::Online(_) | SellerStatus::Unreachable(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    let parts = order::allocate(online_sellers, budget, reserve, usize::from(max_makers));
+
+    if parts.is_empty() {
+        bail!(
+            "None of the makers can take an order of {} (the fees of the swaps excluded)",
+            budget
+        );
+    }
+
+    let order_id = Uuid::new_v4();
+    let swap_ids = parts.iter().map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+
+    // Acquire the locks before we touch our funds such that the swaps cannot be interrupted
+    context.swap_lock.acquire_swap_locks(&swap_ids).await?;
+
+    let funding = async {
+        let mut tx_lock_fees = Vec::new();
+        for part in &parts {
+            tx_lock_fees.push(
+                bitcoin_wallet
+                    .estimate_fee(bitcoin::TxLock::weight(), Some(part.btc_amount))
+                    .await?,
+            );
+        }
+
+        let amounts = parts
+            .iter()
+            .zip(&tx_lock_fees)
+            .map(|(part, tx_lock_fee)| part.btc_amount + *tx_lock_fee)
+            .collect::<Vec<_>>();
+
+        let (transaction, outpoints) = bitcoin_wallet
+            .split_into_outputs(&amounts, bitcoin_inputs.as_deref())
+            .await?;
+        let (funding_txid, _) = bitcoin_wallet
+            .broadcast(transaction, "order funding")
+            .await?;
+
+        anyhow::Ok((funding_txid, outpoints, tx_lock_fees))
+    }
+    .await;
+
+    let (funding_txid, outpoints, tx_lock_fees) = match funding {
+        Ok(funding) => funding,
+        Err(error) => {
+            for swap_id in &swap_ids {
+                context.swap_lock.release_swap_lock(*swap_id).await?;
+            }
+
+            return Err(error.context("Failed to fund the order"));
+        }
+    };
+
+    tracing::info!(%order_id, %funding_txid, swaps = parts.len(), "Funded order");
+
+    // From here on we must not return early, the caller needs to learn about the swaps we started
+    let mut swaps = Vec::new();
+
+    for (((part, swap_id), outpoint), tx_lock_fee) in parts
+        .into_iter()
+        .zip(swap_ids)
+        .zip(outpoints)
+        .zip(tx_lock_fees)
+    {
+        let seller = part.seller;
+
+        let started = async {
+            context.db.insert_swap_order(swap_id, order_id).await?;
+
+            tracing::info!(
+                %order_id,
+                seller = %seller.peer_id,
+                btc_amount = %part.btc_amount,
+                price = %seller.quote.price,
+                "Starting swap of order"
+            );
+
+            spawn_swap(
+                context.clone(),
+                swap_id,
+                seller.peer_id,
+                seller.multiaddr.clone(),
+                seller.quote,
+                monero_receive_pool.clone(),
+                bitcoin_change_address.clone(),
+                part.btc_amount,
+                tx_lock_fee,
+                Some(vec![outpoint]),
+            )
+            .await
+        }
+        .instrument(get_swap_tracing_span(swap_id))
+        .await;
+
+        // The output we reserved for the swap stays in our wallet
+        if let Err(error) = started {
+            tracing::error!(%order_id, %swap_id, "Failed to start swap of order: {:#}", error);
+            if let Err(error) = context.swap_lock.release_swap_lock(swap_id).await {
+                tracing::error!(%order_id, %swap_id, "Failed to release swap lock: {:#}", error);
+            }
+            continue;
+        }
+
+        swaps.push(OrderSwap {
+            swap_id,
+            seller_peer_id: seller.peer_id,
+            quote: seller.quote,
+            btc_amount: part.btc_amount,
+        });
+    }
+
+    if swaps.is_empty() {
+        bail!(
+            "Failed to start any swap of order {}. The Bitcoin of funding transaction {} stays in our wallet.",
+            order_id,
+            funding_txid
+        );
+    }
+
+    Ok(PlaceOrderResponse {
+        order_id,
+        funding_txid,
+        swaps,
+    })
+}
+
+/// Connects to the seller and runs the swap in the background until it completes or is suspended.
+///
+/// The caller must hold the swap lock for `swap_id`, it is released once the swap stops.
+#[allow(clippy::too_many_arguments)]
+async fn spawn_swap(
+    context: Arc<Context>,
+    swap_id: Uuid,
+    seller_peer_id: PeerId,
+    seller_multiaddr: Multiaddr,
+    quote: BidQuote,
+    monero_receive_pool: MoneroAddressPool,
+    bitcoin_change_address: bitcoin::Address,
+    tx_lock_amount: bitcoin::Amount,
+    tx_lock_fee: bitcoin::Amount,
+    bitcoin_inputs: Option<Vec<bitcoin::OutPoint>>,
+) -> Result<()> {
+    let bitcoin_wallet = Arc::clone(
+        context
+            .bitcoin_wallet
+            .as_ref()
+            .context("Could not get Bitcoin wallet")?,
+    );
+    let monero_wallet = Arc::clone(
+        context
+            .monero_manager
+            .as_ref()
+            .context("Could not get Monero wallet")?,
+    );
+    let env_config = context.config.env_config;
+
+    // Insert the peer_id into the database
+    context.db.insert_peer_id(swap_id, seller_peer_id).await?;
 
     context
         .db
-        .insert_monero_address_pool(swap_id, monero_receive_pool.clone())
+        .insert_address(seller_peer_id, seller_multiaddr.clone())
         .await?;
 
-    tracing::debug!(peer_id = %swarm.local_peer_id(), "Network layer initialized");
+    context
+        .db
+        .insert_monero_address_pool(swap_id, monero_receive_pool.clone())
+        .await?;
 
-    context.tauri_handle.emit_swap_progress_event(
-        swap_id,
-        TauriSwapProgressEvent::ReceivedQuote(quote.clone()),
-    );
+    context
+        .tauri_handle
+        .emit_swap_progress_event(swap_id, TauriSwapProgressEvent::ReceivedQuote(quote));
 
-    // Now create the event loop we use for the swap
-    let (event_loop, event_loop_handle) =
-        EventLoop::new(swap_id, swarm, seller_peer_id, context.db.clone())?;
-    let event_loop = tokio::spawn(event_loop.run().in_current_span());
+    // Add the swap to the event loop which all swaps share
+    let event_loop_handle = context
+        .new_swap_event_loop_handle(swap_id, seller_peer_id, vec![seller_multiaddr])
+        .await?;
 
     context
         .tauri_handle
@@ -795,25 +1514,15 @@ pub async fn buy_xmr(
     context.tasks.clone().spawn(async move {
         tokio::select! {
             biased;
-            _ = context.swap_lock.listen_for_swap_force_suspension() => {
+            _ = context.swap_lock.listen_for_swap_force_suspension(swap_id) => {
                 tracing::debug!("Shutdown signal received, exiting");
-                context.swap_lock.release_swap_lock().await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
+                context.swap_lock.release_swap_lock(swap_id).await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
 
                 context.tauri_handle.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::Released);
 
                 bail!("Shutdown signal received");
             },
 
-            event_loop_result = event_loop => {
-                match event_loop_result {
-                    Ok(_) => {
-                        tracing::debug!(%swap_id, "EventLoop completed")
-                    }
-                    Err(error) => {
-                        tracing::error!(%swap_id, "EventLoop failed: {:#}", error)
-                    }
-                }
-            },
             swap_result = async {
                 let swap = Swap::new(
                     Arc::clone(&context.db),
@@ -823,9 +1532,10 @@ pub async fn buy_xmr(
                     env_config,
                     event_loop_handle,
                     monero_receive_pool.clone(),
-                    bitcoin_change_address_for_spawn,
+                    bitcoin_change_address,
                     tx_lock_amount,
-                    tx_lock_fee
+                    tx_lock_fee,
+                    bitcoin_inputs,
                 ).with_event_emitter(context.tauri_handle.clone());
 
                 bob::run(swap).await
@@ -844,7 +1554,7 @@ pub async fn buy_xmr(
 
         context
             .swap_lock
-            .release_swap_lock()
+            .release_swap_lock(swap_id)
             .await
             .expect("Could not release swap lock");
 
@@ -853,7 +1563,7 @@ pub async fn buy_xmr(
         Ok::<_, anyhow::Error>(())
     }.in_current_span()).await;
 
-    Ok(BuyXmrResponse { swap_id, quote })
+    Ok(())
 }
 
 #[tracing::instrument(fields(method = "resume_swap"), skip(context))]
@@ -864,63 +1574,47 @@ pub async fn resume_swap(
     let ResumeSwapArgs { swap_id } = resume;
 
     let seller_peer_id = context.db.get_peer_id(swap_id).await?;
-    let seller_addresses = context.db.get_addresses(seller_peer_id).await?;
-
-    let seed = context
-        .config
-        .seed
-        .as_ref()
-        .context("Could not get seed")?
-        .derive_libp2p_identity();
-
-    let behaviour = cli::Behaviour::new(
-        seller_peer_id,
-        context.config.env_config,
-        Arc::clone(
-            context
-                .bitcoin_wallet
-                .as_ref()
-                .context("Could not get Bitcoin wallet")?,
-        ),
-        (seed.clone(), context.config.namespace),
-    );
-    let mut swarm = swarm::cli(seed.clone(), context.tor_client.clone(), behaviour).await?;
-    let our_peer_id = swarm.local_peer_id();
-
-    tracing::debug!(peer_id = %our_peer_id, "Network layer initialized");
+    let seller_addresses = context.db.get_addresses(seller_peer_id).await?;
 
-    // Fetch the seller's addresses from the database and add them to the swarm
-    for seller_address in seller_addresses {
-        swarm.add_peer_address(seller_peer_id, seller_address);
-    }
+    // Acquire the lock before we add the swap to the event loop, the swap might already be running
+    context.swap_lock.acquire_swap_lock(swap_id).await?;
 
-    let (event_loop, event_loop_handle) =
-        EventLoop::new(swap_id, swarm, seller_peer_id, context.db.clone())?;
+    let swap = async {
+        let event_loop_handle = context
+            .new_swap_event_loop_handle(swap_id, seller_peer_id, seller_addresses)
+            .await?;
 
-    let monero_receive_pool = context.db.get_monero_address_pool(swap_id).await?;
+        let monero_receive_pool = context.db.get_monero_address_pool(swap_id).await?;
 
-    let swap = Swap::from_db(
-        Arc::clone(&context.db),
-        swap_id,
-        Arc::clone(
+        Swap::from_db(
+            Arc::clone(&context.db),
+            swap_id,
+            Arc::clone(
+                context
+                    .bitcoin_wallet
+                    .as_ref()
+                    .context("Could not get Bitcoin wallet")?,
+            ),
             context
-                .bitcoin_wallet
+                .monero_manager
                 .as_ref()
-                .context("Could not get Bitcoin wallet")?,
-        ),
-        context
-            .monero_manager
-            .as_ref()
-            .context("Could not get Monero wallet manager")?
-            .clone(),
-        context.config.env_config,
-        event_loop_handle,
-        monero_receive_pool,
-    )
-    .await?
-    .with_event_emitter(context.tauri_handle.clone());
+                .context("Could not get Monero wallet manager")?
+                .clone(),
+            context.config.env_config,
+            event_loop_handle,
+            monero_receive_pool,
+        )
+        .await
+    }
+    .await;
 
-    context.swap_lock.acquire_swap_lock(swap_id).await?;
+    let swap = match swap {
+        Ok(swap) => swap.with_event_emitter(context.tauri_handle.clone()),
+        Err(error) => {
+            context.swap_lock.release_swap_lock(swap_id).await?;
+            return Err(error);
+        }
+    };
 
     context
         .tauri_handle
@@ -928,28 +1622,17 @@ pub async fn resume_swap(
 
     context.tasks.clone().spawn(
         async move {
-            let handle = tokio::spawn(event_loop.run().in_current_span());
             tokio::select! {
                 biased;
-                _ = context.swap_lock.listen_for_swap_force_suspension() => {
+                _ = context.swap_lock.listen_for_swap_force_suspension(swap_id) => {
                      tracing::debug!("Shutdown signal received, exiting");
-                    context.swap_lock.release_swap_lock().await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
+                    context.swap_lock.release_swap_lock(swap_id).await.expect("Shutdown signal received but failed to release swap lock. The swap process has been terminated but the swap lock is still active.");
 
                     context.tauri_handle.emit_swap_progress_event(swap_id, TauriSwapProgressEvent::Released);
 
                     bail!("Shutdown signal received");
                 },
 
-                event_loop_result = handle => {
-                    match event_loop_result {
-                        Ok(_) => {
-                            tracing::debug!(%swap_id, "EventLoop completed during swap resume")
-                        }
-                        Err(error) => {
-                            tracing::error!(%swap_id, "EventLoop failed during swap resume: {:#}", error)
-                        }
-                    }
-                },
                 swap_result = bob::run(swap) => {
                     match swap_result {
                         Ok(state) => {
@@ -964,7 +1647,7 @@ pub async fn resume_swap(
             }
             context
                 .swap_lock
-                .release_swap_lock()
+                .release_swap_lock(swap_id)
                 .await
                 .expect("Could not release swap lock");
 
@@ -998,7 +1681,7 @@ pub async fn cancel_and_refund(
 
     context
         .swap_lock
-        .release_swap_lock()
+        .release_swap_lock(swap_id)
         .await
         .expect("Could not release swap lock");
 
@@ -1013,6 +1696,97 @@ pub async fn cancel_and_refund(
     })
 }
 
+/// Ask the maker to refund our Bitcoin early, before they lock the Monero.
+///
+/// The swap is resumed with the request. Whether the maker agrees or not, we keep
+/// watching for the Monero lock and the cancel timelock as usual.
+#[tracing::instrument(fields(method = "request_early_refund"), skip(context))]
+pub async fn request_early_refund(
+    request_early_refund: RequestEarlyRefundArgs,
+    context: Arc<Context>,
+) -> Result<ResumeSwapResponse> {
+    let RequestEarlyRefundArgs { swap_id } = request_early_refund;
+
+    let not_refundable = |state: &BobState| {
+        anyhow!(
+            "Cannot request an early refund for swap {} because it is in state {}. Only swaps waiting for the Monero to be locked can be refunded early.",
+            swap_id,
+            state
+        )
+    };
+
+    // Check before we suspend the swap such that we do not stop a swap we cannot refund early
+    let state: BobState = context.db.get_state(swap_id).await?.try_into()?;
+    if !matches!(state, BobState::BtcLocked { .. }) {
+        return Err(not_refundable(&state));
+    }
+
+    // The swap might be running, waiting for the Monero to be locked
+    let suspended = context.swap_lock.is_running(swap_id).await;
+    if suspended {
+        context.swap_lock.send_suspend_signal_to(swap_id).await?;
+    }
+
+    // The swap might have progressed before it was suspended
+    let state: BobState = context.db.get_state(swap_id).await?.try_into()?;
+
+    let BobState::BtcLocked {
+        state3,
+        monero_wallet_restore_blockheight,
+    } = state
+    else {
+        let error = not_refundable(&state);
+
+        if suspended {
+            resume_swap(ResumeSwapArgs { swap_id }, context)
+                .await
+                .context("Failed to resume the swap")?;
+        }
+
+        return Err(error);
+    };
+
+    context
+        .db
+        .insert_latest_state(
+            swap_id,
+            BobState::BtcEarlyRefundRequested {
+                state3,
+                monero_wallet_restore_blockheight,
+            }
+            .into(),
+        )
+        .await?;
+
+    resume_swap(ResumeSwapArgs { swap_id }, context).await
+}
+
+#[tracing::instrument(fields(method = "bump_fee"), skip(context))]
+pub async fn bump_fee(bump_fee: BumpFeeArgs, context: Arc<Context>) -> Result<BumpFeeResponse> {
+    let BumpFeeArgs { swap_id, fee_rate } = bump_fee;
+    let bitcoin_wallet = context
+        .bitcoin_wallet
+        .as_ref()
+        .context("Could not get Bitcoin wallet")?;
+
+    let fee_rate = fee_rate
+        .map(|sat_per_vb| {
+            ::bitcoin::FeeRate::from_sat_per_vb(sat_per_vb).context("Fee rate is too high")
+        })
+        .transpose()?;
+
+    let reports =
+        bitcoin::bump_swap(swap_id, bitcoin_wallet, context.db.as_ref(), fee_rate).await?;
+
+    for report in &reports {
+        tracing::info!(%swap_id, "{}", report);
+    }
+
+    Ok(BumpFeeResponse {
+        transactions: reports,
+    })
+}
+
 #[tracing::instrument(fields(method = "get_history"), skip(context))]
 pub async fn get_history(context: Arc<Context>) -> Result<GetHistoryResponse> {
     let swaps = context.db.all().await?;
@@ -1022,12 +1796,176 @@ pub async fn get_history(context: Arc<Context>) -> Result<GetHistoryResponse> {
         vec.push(GetHistoryEntry {
             swap_id,
             state: state.to_string(),
+            order_id: context.db.get_swap_order(swap_id).await?,
         })
     }
 
     Ok(GetHistoryResponse { swaps: vec })
 }
 
+#[tracing::instrument(fields(method = "export_history"), skip(context))]
+pub async fn export_history(
+    args: ExportHistoryArgs,
+    context: Arc<Context>,
+) -> Result<ExportHistoryResponse> {
+    let range = DateRange::parse(args.from.as_deref(), args.to.as_deref())?;
+
+    let swaps = export::swap_records(context.db.as_ref(), range).await?;
+    let content = export::render(&swaps, args.format)?;
+
+    tracing::debug!(swaps = swaps.len(), format = %args.format, "Exported swap history");
+
+    Ok(ExportHistoryResponse { swaps, content })
+}
+
+#[tracing::instrument(fields(method = "get_swap_timeline"), skip(context))]
+pub async fn get_swap_timeline(
+    args: GetSwapTimelineArgs,
+    context: Arc<Context>,
+) -> Result<GetSwapTimelineResponse> {
+    let transitions = context.db.get_state_transitions(args.swap_id).await?;
+
+    if transitions.is_empty() {
+        bail!("Swap {} does not exist", args.swap_id);
+    }
+
+    let timeline = timeline::timeline(&transitions, OffsetDateTime::now_utc())?;
+
+    for entry in &timeline {
+        tracing::info!(
+            state = %entry.state,
+            entered_at = %entry.entered_at,
+            duration = %entry
+                .duration_ms
+                .map(timeline::format_duration_ms)
+                .unwrap_or_else(|| "-".to_string()),
+            "Swap state"
+        );
+    }
+
+    Ok(GetSwapTimelineResponse {
+        swap_id: args.swap_id,
+        timeline,
+    })
+}
+
+#[tracing::instrument(fields(method = "create_diagnostics"), skip(context))]
+pub async fn create_diagnostics(
+    args: CreateDiagnosticsArgs,
+    context: Arc<Context>,
+) -> Result<CreateDiagnosticsResponse> {
+    let CreateDiagnosticsArgs { swap_id, output } = args;
+
+    let transitions = context.db.get_state_transitions(swap_id).await?;
+
+    if transitions.is_empty() {
+        bail!("Swap {} does not exist", swap_id);
+    }
+
+    let logs = get_logs(
+        context.config.data_dir.join("logs"),
+        LogFilter {
+            swap_id: Some(swap_id),
+            ..Default::default()
+        },
+        true,
+    )
+    .await?;
+
+    // The swap info contains the timelock status but cannot be assembled for
+    // swaps which never completed the setup, we include why instead
+    let swap_info = match get_swap_info(GetSwapInfoArgs { swap_id }, context.clone()).await {
+        Ok(swap_info) => serde_json::to_value(swap_info)?,
+        Err(error) => json!({ "error": format!("{:#}", error) }),
+    };
+
+    let transactions = match &context.bitcoin_wallet {
+        Some(bitcoin_wallet) => diagnostics::transactions(bitcoin_wallet, &transitions).await,
+        None => Vec::new(),
+    };
+
+    let files = [
+        ("logs.txt", logs.join("\n").into_bytes()),
+        ("swap.json", serde_json::to_vec_pretty(&swap_info)?),
+        (
+            "states.json",
+            serde_json::to_vec_pretty(&diagnostics::states(&transitions)?)?,
+        ),
+        (
+            "transactions.json",
+            serde_json::to_vec_pretty(&transactions)?,
+        ),
+        (
+            "connectivity.json",
+            serde_json::to_vec_pretty(&check_connectivity(&context).await)?,
+        ),
+        (
+            "version.json",
+            serde_json::to_vec_pretty(&VersionInfo::current(context.config.is_testnet))?,
+        ),
+    ];
+
+    let path = output.unwrap_or_else(|| {
+        context
+            .config
+            .data_dir
+            .join("diagnostics")
+            .join(format!("diagnostics-{}.zip", swap_id))
+    });
+    diagnostics::write_archive(&path, &files)?;
+
+    tracing::info!(path = %path.display(), "Wrote diagnostics archive");
+
+    Ok(CreateDiagnosticsResponse {
+        path: path.display().to_string(),
+    })
+}
+
+/// Whether we can reach the Electrum servers and the Monero node we are configured with.
+async fn check_connectivity(context: &Context) -> Connectivity {
+    let mut connectivity = Connectivity {
+        tor: context.tor_client.is_some(),
+        ..Default::default()
+    };
+
+    if let Some(bitcoin_wallet) = &context.bitcoin_wallet {
+        let urls = bitcoin_wallet.electrum_balancer().await.urls().clone();
+
+        for url in urls {
+            let available = CheckElectrumNodeArgs { url: url.clone() }
+                .request_with_proxy(context.tor_socks_proxy.clone())
+                .await
+                .is_ok_and(|response| response.available);
+
+            connectivity.electrum.push(NodeStatus { url, available });
+        }
+    }
+
+    if let Some(monero_manager) = &context.monero_manager {
+        let address = &monero_manager.daemon().address;
+        let url = match address.contains("://") {
+            true => address.clone(),
+            false => format!("http://{}", address),
+        };
+        let network = match context.config.is_testnet {
+            true => "testnet",
+            false => "mainnet",
+        };
+
+        let available = CheckMoneroNodeArgs {
+            url: url.clone(),
+            network: network.to_string(),
+        }
+        .request_with_proxy(context.tor_socks_proxy.clone())
+        .await
+        .is_ok_and(|response| response.available);
+
+        connectivity.monero = Some(NodeStatus { url, available });
+    }
+
+    connectivity
+}
+
 #[tracing::instrument(fields(method = "get_config"), skip(context))]
 pub async fn get_config(context: Arc<Context>) -> Result<serde_json::Value> {
     let data_dir_display = context.config.data_dir.display();
@@ -1052,7 +1990,11 @@ pub async fn withdraw_btc(
     withdraw_btc: WithdrawBtcArgs,
     context: Arc<Context>,
 ) -> Result<WithdrawBtcResponse> {
-    let WithdrawBtcArgs { address, amount } = withdraw_btc;
+    let WithdrawBtcArgs {
+        address,
+        amount,
+        inputs,
+    } = withdraw_btc;
     let bitcoin_wallet = context
         .bitcoin_wallet
         .as_ref()
@@ -1061,18 +2003,18 @@ pub async fn withdraw_btc(
     let (withdraw_tx_unsigned, amount) = match amount {
         Some(amount) => {
             let withdraw_tx_unsigned = bitcoin_wallet
-                .send_to_address_dynamic_fee(address, amount, None)
+                .send_to_address_dynamic_fee(address, amount, None, inputs.as_deref())
                 .await?;
 
             (withdraw_tx_unsigned, amount)
         }
         None => {
             let (max_giveable, spending_fee) = bitcoin_wallet
-                .max_giveable(address.script_pubkey().len())
+                .max_giveable_from(address.script_pubkey().len(), inputs.as_deref())
                 .await?;
 
             let withdraw_tx_unsigned = bitcoin_wallet
-                .send_to_address(address, max_giveable, spending_fee, None)
+                .send_to_address_from(address, max_giveable, spending_fee, None, inputs.as_deref())
                 .await?;
 
             (withdraw_tx_unsigned, max_giveable)
@@ -1095,6 +2037,138 @@ pub async fn withdraw_btc(
     })
 }
 
+#[tracing::instrument(fields(method = "get_bitcoin_transactions"), skip(context))]
+pub async fn get_bitcoin_transactions(
+    get_bitcoin_transactions: GetBitcoinTransactionsArgs,
+    context: Arc<Context>,
+) -> Result<GetBitcoinTransactionsResponse> {
+    let GetBitcoinTransactionsArgs { force_refresh } = get_bitcoin_transactions;
+    let bitcoin_wallet = context
+        .bitcoin_wallet
+        .as_ref()
+        .context("Could not get Bitcoin wallet")?;
+
+    if force_refresh {
+        bitcoin_wallet.sync().await?;
+    }
+
+    let mut transactions = bitcoin_wallet.transaction_history().await?;
+    bitcoin::link_to_swaps(&mut transactions, context.db.as_ref()).await?;
+
+    for transaction in &transactions {
+        tracing::info!(
+            txid = %transaction.txid,
+            direction = ?transaction.direction,
+            amount = %transaction.amount,
+            fee = ?transaction.fee,
+            confirmation_height = ?transaction.confirmation_height,
+            swap_id = ?transaction.swap_id,
+            swap_transaction = ?transaction.swap_transaction,
+            "Bitcoin transaction",
+        );
+    }
+
+    Ok(GetBitcoinTransactionsResponse { transactions })
+}
+
+#[tracing::instrument(fields(method = "get_monero_history"), skip(context))]
+pub async fn get_monero_history(context: Arc<Context>) -> Result<GetMoneroHistoryResponse> {
+    let monero_wallet = context
+        .monero_manager
+        .as_ref()
+        .context("Could not get Monero wallet")?;
+
+    let transfers = monero_wallet.transfers().await?;
+
+    Ok(GetMoneroHistoryResponse { transfers })
+}
+
+#[tracing::instrument(fields(method = "get_monero_subaddresses"), skip(context))]
+pub async fn get_monero_subaddresses(
+    context: Arc<Context>,
+) -> Result<GetMoneroSubaddressesResponse> {
+    let monero_wallet = context
+        .monero_manager
+        .as_ref()
+        .context("Could not get Monero wallet")?;
+
+    let subaddresses = monero_wallet.subaddresses().await?;
+
+    Ok(GetMoneroSubaddressesResponse { subaddresses })
+}
+
+#[tracing::instrument(fields(method = "list_unspent"), skip(context))]
+pub async fn list_unspent(context: Arc<Context>) -> Result<ListUnspentResponse> {
+    let bitcoin_wallet = context
+        .bitcoin_wallet
+        .as_ref()
+        .context("Could not get Bitcoin wallet")?;
+
+    bitcoin_wallet.sync().await?;
+    let utxos = bitcoin_wallet.list_unspent().await?;
+
+    for utxo in &utxos {
+        tracing::info!(
+            outpoint = %utxo.outpoint,
+            amount = %utxo.amount,
+            confirmations = utxo.confirmations,
+            label = utxo.label.as_deref().unwrap_or(""),
+            frozen = utxo.frozen,
+            "Unspent output",
+        );
+    }
+
+    Ok(ListUnspentResponse { utxos })
+}
+
+#[tracing::instrument(fields(method = "set_utxo_label"), skip(context))]
+pub async fn set_utxo_label(
+    set_utxo_label: SetUtxoLabelArgs,
+    context: Arc<Context>,
+) -> Result<serde_json::Value> {
+    let SetUtxoLabelArgs { outpoint, label } = set_utxo_label;
+    let bitcoin_wallet = context
+        .bitcoin_wallet
+        .as_ref()
+        .context("Could not get Bitcoin wallet")?;
+
+    bitcoin_wallet
+        .set_utxo_label(outpoint, label.clone())
+        .await?;
+
+    tracing::info!(%outpoint, ?label, "Updated label of unspent output");
+
+    Ok(json!({
+        "outpoint": outpoint,
+        "label": label,
+    }))
+}
+
+#[tracing::instrument(fields(method = "freeze_utxo"), skip(context))]
+pub async fn freeze_utxo(
+    freeze_utxo: FreezeUtxoArgs,
+    context: Arc<Context>,
+) -> Result<serde_json::Value> {
+    let FreezeUtxoArgs { outpoint, frozen } = freeze_utxo;
+    let bitcoin_wallet = context
+        .bitcoin_wallet
+        .as_ref()
+        .context("Could not get Bitcoin wallet")?;
+
+    bitcoin_wallet.set_utxo_frozen(outpoint, frozen).await?;
+
+    if frozen {
+        tracing::info!(%outpoint, "Froze unspent output, it will not be spent until unfrozen");
+    } else {
+        tracing::info!(%outpoint, "Unfroze unspent output");
+    }
+
+    Ok(json!({
+        "outpoint": outpoint,
+        "frozen": frozen,
+    }))
+}
+
 #[tracing::instrument(fields(method = "get_balance"), skip(context))]
 pub async fn get_balance(balance: BalanceArgs, context: Arc<Context>) -> Result<BalanceResponse> {
     let BalanceArgs { force_refresh } = balance;
@@ -1161,6 +2235,7 @@ pub async fn list_sellers(
                 multiaddr,
                 peer_id,
                 version,
+                reputation,
             }) => {
                 tracing::trace!(
                     status = "Online",
@@ -1170,6 +2245,7 @@ pub async fn list_sellers(
                     address = %multiaddr.clone().to_string(),
                     peer_id = %peer_id,
                     version = %version,
+                    score = ?reputation.score,
                     "Fetched peer status"
                 );
 
@@ -1194,6 +2270,133 @@ pub async fn list_sellers(
     Ok(ListSellersResponse { sellers })
 }
 
+#[tracing::instrument(fields(method = "list_peers"), skip(context))]
+pub async fn list_peers(context: Arc<Context>) -> Result<ListPeersResponse> {
+    let mut addresses = context
+        .db
+        .get_all_peer_addresses()
+        .await?
+        .into_iter()
+        .collect::<HashMap<_, _>>();
+    let mut settings = peers::load(context.db.as_ref()).await?;
+    let reputations = reputation::load(context.db.as_ref()).await?;
+
+    let peer_ids = addresses
+        .keys()
+        .chain(settings.keys())
+        .copied()
+        .collect::<HashSet<_>>();
+
+    let mut peers = peer_ids
+        .into_iter()
+        .map(|peer_id| PeerInfo {
+            peer_id,
+            addresses: addresses
+                .remove(&peer_id)
+                .unwrap_or_default()
+                .iter()
+                .map(|address| address.to_string())
+                .collect(),
+            settings: settings.remove(&peer_id).unwrap_or_default(),
+            reputation: reputations.get(&peer_id).copied().unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    peers.sort_by_key(|peer| (peer.settings.status, peer.peer_id));
+
+    for peer in &peers {
+        tracing::info!(
+            peer_id = %peer.peer_id,
+            status = %peer.settings.status,
+            label = peer.settings.label.as_deref().unwrap_or(""),
+            score = ?peer.reputation.score,
+            addresses = ?peer.addresses,
+            "Known peer",
+        );
+    }
+
+    Ok(ListPeersResponse { peers })
+}
+
+#[tracing::instrument(fields(method = "add_peer"), skip(context))]
+pub async fn add_peer(add_peer: AddPeerArgs, context: Arc<Context>) -> Result<serde_json::Value> {
+    let AddPeerArgs { multiaddr, label } = add_peer;
+    let (peer_id, address) = multiaddr
+        .split_peer_id()
+        .context("The address of the peer must contain its peer ID, i.e. `/p2p/`")?;
+
+    context.db.insert_address(peer_id, address).await?;
+    context
+        .db
+        .insert_peer_status(peer_id, PeerStatus::Pinned)
+        .await?;
+    if label.is_some() {
+        context.db.insert_peer_label(peer_id, label.clone()).await?;
+    }
+
+    tracing::info!(%peer_id, ?label, "Pinned peer, it will be preferred over other makers");
+
+    Ok(json!({
+        "peer_id": peer_id.to_string(),
+        "label": label,
+    }))
+}
+
+#[tracing::instrument(fields(method = "remove_peer"), skip(context))]
+pub async fn remove_peer(
+    remove_peer: RemovePeerArgs,
+    context: Arc<Context>,
+) -> Result<serde_json::Value> {
+    let RemovePeerArgs { peer_id } = remove_peer;
+
+    context.db.remove_peer(peer_id).await?;
+
+    tracing::info!(%peer_id, "Removed peer and its addresses, settings and label");
+
+    Ok(json!({
+        "peer_id": peer_id.to_string(),
+    }))
+}
+
+#[tracing::instrument(fields(method = "ban_peer"), skip(context))]
+pub async fn ban_peer(ban_peer: BanPeerArgs, context: Arc<Context>) -> Result<serde_json::Value> {
+    let BanPeerArgs { peer_id, banned } = ban_peer;
+    let status = if banned {
+        PeerStatus::Banned
+    } else {
+        PeerStatus::Known
+    };
+
+    context.db.insert_peer_status(peer_id, status).await?;
+
+    if banned {
+        tracing::info!(%peer_id, "Banned peer, it will not be contacted anymore");
+    } else {
+        tracing::info!(%peer_id, "Unbanned peer");
+    }
+
+    Ok(json!({
+        "peer_id": peer_id.to_string(),
+        "status": status,
+    }))
+}
+
+#[tracing::instrument(fields(method = "set_peer_label"), skip(context))]
+pub async fn set_peer_label(
+    set_peer_label: SetPeerLabelArgs,
+    context: Arc<Context>,
+) -> Result<serde_json::Value> {
+    let SetPeerLabelArgs { peer_id, label } = set_peer_label;
+
+    context.db.insert_peer_label(peer_id, label.clone()).await?;
+
+    tracing::info!(%peer_id, ?label, "Updated label of peer");
+
+    Ok(json!({
+        "peer_id": peer_id.to_string(),
+        "label": label,
+    }))
+}
+
 #[tracing::instrument(fields(method = "export_bitcoin_wallet"), skip(context))]
 pub async fn export_bitcoin_wallet(context: Arc<Context>) -> Result<serde_json::Value> {
     let bitcoin_wallet = context
@@ -1244,8 +2447,8 @@ pub async fn monero_recovery(
 
 #[tracing::instrument(fields(method = "get_current_swap"), skip(context))]
 pub async fn get_current_swap(context: Arc<Context>) -> Result<GetCurrentSwapResponse> {
-    let swap_id = context.swap_lock.get_current_swap_id().await;
-    Ok(GetCurrentSwapResponse { swap_id })
+    let swap_ids = context.swap_lock.get_current_swap_ids().await;
+    Ok(GetCurrentSwapResponse { swap_ids })
 }
 
 pub async fn resolve_approval_request(
@@ -1423,8 +2626,10 @@ where
         );
 
         // Iterate through quotes and find ones that match the balance and max_giveable
+        // Makers which repeatedly failed to lock the Monero are not offered
         let matching_quotes = success_quotes
             .iter()
+            .filter(|quote_with_address| !quote_with_address.reputation.is_blocklisted())
             .filter_map(|quote_with_address| {
                 let quote = quote_with_address.quote;
 
@@ -1556,6 +2761,14 @@ struct UnknownMoneroNetwork(String);
 
 impl CheckMoneroNodeArgs {
     pub async fn request(self) -> Result<CheckMoneroNodeResponse> {
+        self.request_with_proxy(None).await
+    }
+
+    /// Check the node through a SOCKS5 proxy, e.g. the one of our Tor client.
+    pub async fn request_with_proxy(
+        self,
+        socks5_proxy: Option<SocksProxy>,
+    ) -> Result<CheckMoneroNodeResponse> {
         let url = self.url.clone();
         let network_str = self.network.clone();
 
@@ -1579,7 +2792,15 @@ impl CheckMoneroNodeArgs {
             return Ok(CheckMoneroNodeResponse { available: false });
         };
 
-        match monero_daemon.is_available(&CLIENT).await {
+        let client = match socks5_proxy {
+            Some(proxy) => reqwest::Client::builder()
+                .timeout(Duration::from_secs(5))
+                .proxy(reqwest::Proxy::all(proxy.url())?)
+                .build()?,
+            None => CLIENT.clone(),
+        };
+
+        match monero_daemon.is_available(&client).await {
             Ok(available) => Ok(CheckMoneroNodeResponse { available }),
             Err(e) => {
                 tracing::error!(
@@ -1610,13 +2831,26 @@ pub struct CheckElectrumNodeResponse {
 
 impl CheckElectrumNodeArgs {
     pub async fn request(self) -> Result<CheckElectrumNodeResponse> {
+        self.request_with_proxy(None).await
+    }
+
+    /// Check the node through a SOCKS5 proxy, e.g. the one of our Tor client.
+    pub async fn request_with_proxy(
+        self,
+        socks5_proxy: Option<SocksProxy>,
+    ) -> Result<CheckElectrumNodeResponse> {
         // Check if the URL is valid
         let Ok(url) = Url::parse(&self.url) else {
             return Ok(CheckElectrumNodeResponse { available: false });
         };
 
         // Check if the node is available
-        let res = wallet::Client::new(&[url.as_str().to_string()], Duration::from_secs(60)).await;
+        let res = wallet::Client::new(
+            &[url.as_str().to_string()],
+            Duration::from_secs(60),
+            socks5_proxy,
+        )
+        .await;
 
         Ok(CheckElectrumNodeResponse {
             available: res.is_ok(),
@@ -70,6 +70,20 @@ pub enum SeedChoice {
     FromSeed { seed: String },
 }
 
+#[typeshare]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnlockSeedDetails {
+    /// Whether the passphrase the user entered before was wrong.
+    pub wrong_passphrase: bool,
+}
+
+/// The passphrase which unlocks an encrypted seed file.
+#[typeshare]
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SeedPassphrase {
+    pub passphrase: String,
+}
+
 #[typeshare]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ApprovalRequest {
@@ -92,6 +106,8 @@ pub enum ApprovalRequestType {
     /// Request seed selection from user.
     /// User can choose between random seed or provide their own.
     SeedSelection,
+    /// Request the passphrase of the encrypted seed file from the user.
+    UnlockSeed(UnlockSeedDetails),
 }
 
 #[typeshare]
@@ -267,11 +283,15 @@ impl TauriHandle {
                 .map_err(|e| anyhow!("Failed to acquire approval lock: {}", e))?;
 
             if let Some(_pending) = map.remove(&request_id) {
-                let status = match &maybe_response {
-                    Some(_) => RequestStatus::Resolved {
+                let status = match (&maybe_response, &request.request) {
+                    // The passphrase must neither be logged nor sent back to the frontend
+                    (Some(_), ApprovalRequestType::UnlockSeed(_)) => RequestStatus::Resolved {
+                        approve_input: serde_json::Value::Null,
+                    },
+                    (Some(_), _) => RequestStatus::Resolved {
                         approve_input: unparsed_response.unwrap_or(serde_json::Value::Bool(false)),
                     },
-                    None => RequestStatus::Rejected,
+                    (None, _) => RequestStatus::Rejected,
                 };
 
                 // Set the status and emit the event
@@ -353,6 +373,7 @@ impl Display for ApprovalRequest {
             ApprovalRequestType::LockBitcoin(..) => write!(f, "LockBitcoin()"),
             ApprovalRequestType::SelectMaker(..) => write!(f, "SelectMaker()"),
             ApprovalRequestType::SeedSelection => write!(f, "SeedSelection()"),
+            ApprovalRequestType::UnlockSeed(..) => write!(f, "UnlockSeed()"),
         }
     }
 }
@@ -373,6 +394,8 @@ pub trait TauriEmitter {
 
     async fn request_seed_selection(&self) -> Result<SeedChoice>;
 
+    async fn request_seed_passphrase(&self, details: UnlockSeedDetails) -> Result<SeedPassphrase>;
+
     fn emit_tauri_event<S: Serialize + Clone>(&self, event: &str, payload: S) -> Result<()>;
 
     fn emit_unified_event(&self, event: TauriEvent) {
@@ -472,6 +495,11 @@ impl TauriEmitter for TauriHandle {
             .await
     }
 
+    async fn request_seed_passphrase(&self, details: UnlockSeedDetails) -> Result<SeedPassphrase> {
+        self.request_approval(ApprovalRequestType::UnlockSeed(details), None)
+            .await
+    }
+
     fn emit_tauri_event<S: Serialize + Clone>(&self, event: &str, payload: S) -> Result<()> {
         self.emit_tauri_event(event, payload)
     }
@@ -541,6 +569,13 @@ impl TauriEmitter for Option<TauriHandle> {
         }
     }
 
+    async fn request_seed_passphrase(&self, details: UnlockSeedDetails) -> Result<SeedPassphrase> {
+        match self {
+            Some(tauri) => tauri.request_seed_passphrase(details).await,
+            None => bail!("No Tauri handle available"),
+        }
+    }
+
     fn new_background_process<T: Clone>(
         &self,
         component: fn(PendingCompleted<T>) -> TauriBackgroundProgress,
@@ -880,6 +915,13 @@ pub struct TauriSettings {
     pub electrum_rpc_urls: Vec<String>,
     /// Whether to initialize and use a tor client.
     pub use_tor: bool,
+    /// Whether to refuse to connect to Electrum servers and Monero nodes without Tor.
+    #[serde(default)]
+    pub always_tor: bool,
+    /// Whether to connect to Electrum servers and Monero nodes on this machine or the
+    /// local network directly, as Tor cannot reach them.
+    #[serde(default)]
+    pub direct_local_connections: bool,
 }
 
 #[typeshare]
@@ -4,7 +4,8 @@ use crate::network::quote::BidQuote;
 use crate::network::rendezvous::XmrBtcNamespace;
 use crate::network::swap_setup::bob;
 use crate::network::{
-    cooperative_xmr_redeem_after_punish, encrypted_signature, quote, redial, transfer_proof,
+    cooperative_early_refund, cooperative_xmr_redeem_after_punish, encrypted_signature, quote,
+    redial, transfer_proof,
 };
 use crate::protocol::bob::State2;
 use crate::{bitcoin, env};
@@ -23,7 +24,10 @@ pub enum OutEvent {
         id: OutboundRequestId,
         response: BidQuote,
     },
-    SwapSetupCompleted(Box<Result<State2>>),
+    SwapSetupCompleted {
+        swap_id: uuid::Uuid,
+        result: Box<Result<State2>>,
+    },
     TransferProofReceived {
         msg: Box<transfer_proof::Request>,
         channel: ResponseChannel<()>,
@@ -43,6 +47,10 @@ pub enum OutEvent {
         reason: CooperativeXmrRedeemRejectReason,
         swap_id: uuid::Uuid,
     },
+    CooperativeEarlyRefundResponse {
+        id: OutboundRequestId,
+        response: cooperative_early_refund::Response,
+    },
     Failure {
         peer: PeerId,
         error: Error,
@@ -89,6 +97,7 @@ pub struct Behaviour {
     pub swap_setup: bob::Behaviour,
     pub transfer_proof: transfer_proof::Behaviour,
     pub cooperative_xmr_redeem: cooperative_xmr_redeem_after_punish::Behaviour,
+    pub cooperative_early_refund: cooperative_early_refund::Behaviour,
     pub encrypted_signature: encrypted_signature::Behaviour,
     pub redial: redial::Behaviour,
     pub identify: identify::Behaviour,
@@ -101,7 +110,6 @@ pub struct Behaviour {
 
 impl Behaviour {
     pub fn new(
-        alice: PeerId,
         env_config: env::Config,
         bitcoin_wallet: Arc<bitcoin::Wallet>,
         identify_params: (identity::Keypair, XmrBtcNamespace),
@@ -120,11 +128,8 @@ impl Behaviour {
             transfer_proof: transfer_proof::bob(),
             encrypted_signature: encrypted_signature::bob(),
             cooperative_xmr_redeem: cooperative_xmr_redeem_after_punish::bob(),
-            redial: redial::Behaviour::new(
-                alice,
-                Duration::from_secs(2),
-                Duration::from_secs(5 * 60),
-            ),
+            cooperative_early_refund: cooperative_early_refund::bob(),
+            redial: redial::Behaviour::new(Duration::from_secs(2), Duration::from_secs(5 * 60)),
             ping: ping::Behaviour::new(pingConfig),
             identify: identify::Behaviour::new(identifyConfig),
         }
@@ -34,6 +34,10 @@ pub async fn cancel(
         BobState::BtcLocked {
             state3,
             monero_wallet_restore_blockheight,
+        }
+        | BobState::BtcEarlyRefundRequested {
+            state3,
+            monero_wallet_restore_blockheight,
         } => state3.cancel(monero_wallet_restore_blockheight),
         BobState::XmrLockProofReceived {
             state,
@@ -133,6 +137,10 @@ pub async fn refund(
         BobState::BtcLocked {
             state3,
             monero_wallet_restore_blockheight,
+        }
+        | BobState::BtcEarlyRefundRequested {
+            state3,
+            monero_wallet_restore_blockheight,
         } => state3.cancel(monero_wallet_restore_blockheight),
         BobState::XmrLockProofReceived {
             state,
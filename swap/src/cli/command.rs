@@ -1,15 +1,23 @@
 use crate::bitcoin::{bitcoin_address, Amount};
 use crate::cli::api::request::{
-    BalanceArgs, BuyXmrArgs, CancelAndRefundArgs, ExportBitcoinWalletArgs, GetConfigArgs,
-    GetHistoryArgs, ListSellersArgs, MoneroRecoveryArgs, Request, ResumeSwapArgs, WithdrawBtcArgs,
+    AddPeerArgs, BalanceArgs, BanPeerArgs, BumpFeeArgs, BuyXmrArgs, CancelAndRefundArgs,
+    CreateDiagnosticsArgs, ExportBitcoinWalletArgs, ExportHistoryArgs, FreezeUtxoArgs,
+    GetBitcoinTransactionsArgs, GetConfigArgs, GetHistoryArgs, GetSwapTimelineArgs, ListPeersArgs,
+    ListSellersArgs, ListUnspentArgs, MoneroRecoveryArgs, PlaceOrderArgs, RemovePeerArgs, Request,
+    RequestEarlyRefundArgs, ResumeSwapArgs, SetPeerLabelArgs, SetUtxoLabelArgs, WithdrawBtcArgs,
 };
 use crate::cli::api::Context;
+use crate::common::metrics;
 use crate::monero::monero_address;
 use crate::monero::{self, MoneroAddressPool};
-use anyhow::Result;
+use crate::protocol::export::ExportFormat;
+use crate::seed::Seed;
+use anyhow::{Context as _, Result};
 use bitcoin::address::NetworkUnchecked;
 use libp2p::core::Multiaddr;
+use libp2p::PeerId;
 use std::ffi::OsString;
+use std::net::SocketAddr;
 use std::path::PathBuf;
 use std::sync::Arc;
 use structopt::{clap, StructOpt};
@@ -17,7 +25,7 @@ use url::Url;
 use uuid::Uuid;
 
 use super::api::request::GetLogsArgs;
-use super::api::ContextBuilder;
+use super::api::{data, ContextBuilder};
 
 // See: https://1209k.com/bitcoin-eye/ele.php?chain=btc
 const DEFAULT_ELECTRUM_RPC_URL: &str = "ssl://blockstream.info:700";
@@ -59,11 +67,17 @@ where
     let json = args.json;
     let is_testnet = args.testnet;
     let data = args.data;
+
+    if let Some(listen) = args.metrics_listen {
+        metrics::serve(listen).await?;
+    }
+
     let result: Result<Arc<Context>> = match args.cmd {
         CliCommand::BuyXmr {
             seller: Seller { seller },
             bitcoin,
             bitcoin_change_address,
+            bitcoin_inputs,
             monero,
             monero_receive_address,
             tor,
@@ -79,6 +93,8 @@ where
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_tor(tor.enable_tor)
+                    .with_always_tor(tor.always_tor)
+                    .with_direct_local_connections(tor.direct_local_connections)
                     .with_bitcoin(bitcoin)
                     .with_monero(monero)
                     .with_data_dir(data)
@@ -93,6 +109,55 @@ where
                 sellers: vec![seller],
                 bitcoin_change_address,
                 monero_receive_pool,
+                bitcoin_inputs: (!bitcoin_inputs.is_empty()).then_some(bitcoin_inputs),
+            }
+            .request(context.clone())
+            .await?;
+
+            Ok(context)
+        }
+        CliCommand::PlaceOrder {
+            rendezvous_points,
+            sellers,
+            btc_amount,
+            max_makers,
+            bitcoin,
+            bitcoin_change_address,
+            bitcoin_inputs,
+            monero,
+            monero_receive_address,
+            tor,
+        } => {
+            let monero_receive_pool: MoneroAddressPool =
+                monero_address::validate_is_testnet(monero_receive_address, is_testnet)?.into();
+
+            let bitcoin_change_address = bitcoin_change_address
+                .map(|address| bitcoin_address::validate(address, is_testnet))
+                .transpose()?
+                .map(|address| address.into_unchecked());
+
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_tor(tor.enable_tor)
+                    .with_always_tor(tor.always_tor)
+                    .with_direct_local_connections(tor.direct_local_connections)
+                    .with_bitcoin(bitcoin)
+                    .with_monero(monero)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            PlaceOrderArgs {
+                rendezvous_points,
+                sellers,
+                bitcoin_change_address,
+                monero_receive_pool,
+                bitcoin_inputs: (!bitcoin_inputs.is_empty()).then_some(bitcoin_inputs),
+                btc_amount,
+                max_makers,
             }
             .request(context.clone())
             .await?;
@@ -113,10 +178,93 @@ where
 
             Ok(context)
         }
+        CliCommand::ExportHistory {
+            format,
+            from,
+            to,
+            output,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            let export = ExportHistoryArgs { format, from, to }
+                .request(context.clone())
+                .await?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, export.content).with_context(|| {
+                        format!("Failed to write the export to {}", path.display())
+                    })?;
+                    tracing::info!(
+                        swaps = export.swaps.len(),
+                        path = %path.display(),
+                        "Exported swap history"
+                    );
+                }
+                None => print!("{}", export.content),
+            }
+
+            Ok(context)
+        }
+        CliCommand::Timeline {
+            swap_id: SwapId { swap_id },
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            GetSwapTimelineArgs { swap_id }
+                .request(context.clone())
+                .await?;
+
+            Ok(context)
+        }
+        CliCommand::Diagnostics {
+            swap_id: SwapId { swap_id },
+            output,
+            bitcoin,
+            monero,
+            tor,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_tor(tor.enable_tor)
+                    .with_always_tor(tor.always_tor)
+                    .with_direct_local_connections(tor.direct_local_connections)
+                    .with_bitcoin(bitcoin)
+                    .with_monero(monero)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            CreateDiagnosticsArgs { swap_id, output }
+                .request(context.clone())
+                .await?;
+
+            Ok(context)
+        }
         CliCommand::Logs {
             logs_dir,
             redact,
             swap_id,
+            since,
+            until,
+            level,
         } => {
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
@@ -131,6 +279,9 @@ where
                 logs_dir,
                 redact,
                 swap_id,
+                since,
+                until,
+                level,
             }
             .request(context.clone())
             .await?;
@@ -151,6 +302,17 @@ where
 
             Ok(context)
         }
+        CliCommand::EncryptSeed => {
+            let data_dir = data::data_dir_from(data, is_testnet)?;
+            let seed_file = Seed::encrypt_file(&data_dir)?;
+
+            return Ok(ParseResult::PrintAndExitZero {
+                message: format!(
+                    "Encrypted the seed file {}, you will be asked for the passphrase whenever the seed is needed",
+                    seed_file.display()
+                ),
+            });
+        }
         CliCommand::Balance { bitcoin } => {
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
@@ -174,6 +336,7 @@ where
             bitcoin,
             amount,
             address,
+            inputs,
         } => {
             let address = bitcoin_address::validate(address, is_testnet)?;
 
@@ -187,12 +350,95 @@ where
                     .await?,
             );
 
-            WithdrawBtcArgs { amount, address }
+            WithdrawBtcArgs {
+                amount,
+                address,
+                inputs: (!inputs.is_empty()).then_some(inputs),
+            }
+            .request(context.clone())
+            .await?;
+
+            Ok(context)
+        }
+        CliCommand::BitcoinTransactions { bitcoin } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_bitcoin(bitcoin)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            GetBitcoinTransactionsArgs {
+                force_refresh: true,
+            }
+            .request(context.clone())
+            .await?;
+
+            Ok(context)
+        }
+        CliCommand::ListUnspent { bitcoin } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_bitcoin(bitcoin)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            ListUnspentArgs.request(context.clone()).await?;
+
+            Ok(context)
+        }
+        CliCommand::LabelUtxo {
+            bitcoin,
+            outpoint,
+            label,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_bitcoin(bitcoin)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            SetUtxoLabelArgs { outpoint, label }
                 .request(context.clone())
                 .await?;
 
             Ok(context)
         }
+        CliCommand::FreezeUtxo {
+            bitcoin,
+            outpoint,
+            unfreeze,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_bitcoin(bitcoin)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            FreezeUtxoArgs {
+                outpoint,
+                frozen: !unfreeze,
+            }
+            .request(context.clone())
+            .await?;
+
+            Ok(context)
+        }
         CliCommand::Resume {
             swap_id: SwapId { swap_id },
             bitcoin,
@@ -202,6 +448,8 @@ where
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_tor(tor.enable_tor)
+                    .with_always_tor(tor.always_tor)
+                    .with_direct_local_connections(tor.direct_local_connections)
                     .with_bitcoin(bitcoin)
                     .with_monero(monero)
                     .with_data_dir(data)
@@ -215,6 +463,32 @@ where
 
             Ok(context)
         }
+        CliCommand::EarlyRefund {
+            swap_id: SwapId { swap_id },
+            bitcoin,
+            monero,
+            tor,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_tor(tor.enable_tor)
+                    .with_always_tor(tor.always_tor)
+                    .with_direct_local_connections(tor.direct_local_connections)
+                    .with_bitcoin(bitcoin)
+                    .with_monero(monero)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            RequestEarlyRefundArgs { swap_id }
+                .request(context.clone())
+                .await?;
+
+            Ok(context)
+        }
         CliCommand::CancelAndRefund {
             swap_id: SwapId { swap_id },
             bitcoin,
@@ -235,6 +509,27 @@ where
 
             Ok(context)
         }
+        CliCommand::BumpFee {
+            swap_id: SwapId { swap_id },
+            fee_rate,
+            bitcoin,
+        } => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_bitcoin(bitcoin)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            BumpFeeArgs { swap_id, fee_rate }
+                .request(context.clone())
+                .await?;
+
+            Ok(context)
+        }
         CliCommand::ListSellers {
             rendezvous_point,
             tor,
@@ -242,6 +537,8 @@ where
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
                     .with_tor(tor.enable_tor)
+                    .with_always_tor(tor.always_tor)
+                    .with_direct_local_connections(tor.direct_local_connections)
                     .with_data_dir(data)
                     .with_debug(debug)
                     .with_json(json)
@@ -257,6 +554,48 @@ where
 
             Ok(context)
         }
+        CliCommand::Peers(command) => {
+            let context = Arc::new(
+                ContextBuilder::new(is_testnet)
+                    .with_data_dir(data)
+                    .with_debug(debug)
+                    .with_json(json)
+                    .build()
+                    .await?,
+            );
+
+            match command {
+                Peers::List => {
+                    ListPeersArgs.request(context.clone()).await?;
+                }
+                Peers::Add { peer, label } => {
+                    AddPeerArgs {
+                        multiaddr: peer,
+                        label,
+                    }
+                    .request(context.clone())
+                    .await?;
+                }
+                Peers::Remove { peer_id } => {
+                    RemovePeerArgs { peer_id }.request(context.clone()).await?;
+                }
+                Peers::Ban { peer_id, unban } => {
+                    BanPeerArgs {
+                        peer_id,
+                        banned: !unban,
+                    }
+                    .request(context.clone())
+                    .await?;
+                }
+                Peers::Label { peer_id, label } => {
+                    SetPeerLabelArgs { peer_id, label }
+                        .request(context.clone())
+                        .await?;
+                }
+            }
+
+            Ok(context)
+        }
         CliCommand::ExportBitcoinWallet { bitcoin } => {
             let context = Arc::new(
                 ContextBuilder::new(is_testnet)
@@ -328,6 +667,12 @@ struct Arguments {
     )]
     json: bool,
 
+    #[structopt(
+        long = "metrics-listen",
+        help = "Serve Prometheus metrics on /metrics at this address, e.g. 127.0.0.1:9946"
+    )]
+    metrics_listen: Option<SocketAddr>,
+
     #[structopt(subcommand)]
     cmd: CliCommand,
 }
@@ -349,6 +694,68 @@ enum CliCommand {
         )]
         bitcoin_change_address: Option<bitcoin::Address<NetworkUnchecked>>,
 
+        #[structopt(
+            long = "input",
+            help = "Only fund the swap from this UTXO (txid:vout). Can be given multiple times."
+        )]
+        bitcoin_inputs: Vec<bitcoin::OutPoint>,
+
+        #[structopt(flatten)]
+        monero: Monero,
+
+        #[structopt(long = "receive-address",
+            help = "The monero address where you would like to receive monero",
+            parse(try_from_str = monero_address::parse)
+        )]
+        monero_receive_address: monero::Address,
+
+        #[structopt(flatten)]
+        tor: Tor,
+    },
+    /// Split a large buy across several makers, cheapest first, and run one
+    /// swap with each of them
+    PlaceOrder {
+        #[structopt(
+            long = "rendezvous-point",
+            help = "Address of a rendezvous point to discover makers at. Can be given multiple times."
+        )]
+        rendezvous_points: Vec<Multiaddr>,
+
+        #[structopt(
+            long = "seller",
+            help = "Address of a maker to consider. Must include a peer ID part, i.e. `/p2p/`. Can be given multiple times."
+        )]
+        sellers: Vec<Multiaddr>,
+
+        #[structopt(
+            long = "amount",
+            help = "Spend at most this amount, including the fees of the swaps. If omitted, everything the wallet can spend is used."
+        )]
+        btc_amount: Option<Amount>,
+
+        #[structopt(
+            long = "max-makers",
+            default_value = "3",
+            help = "Split the order across at most this many makers."
+        )]
+        max_makers: u8,
+
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(
+            long = "change-address",
+            help = "The bitcoin address where any form of change or excess funds should be sent to. If omitted they will be sent to the internal wallet.",
+            parse(try_from_str = bitcoin_address::parse)
+        )]
+        bitcoin_change_address: Option<bitcoin::Address<NetworkUnchecked>>,
+
+        #[structopt(
+            long = "input",
+            help = "Only fund the order from this UTXO (txid:vout). Can be given multiple times."
+        )]
+        bitcoin_inputs: Vec<bitcoin::OutPoint>,
+
         #[structopt(flatten)]
         monero: Monero,
 
@@ -363,6 +770,60 @@ enum CliCommand {
     },
     /// Show a list of past, ongoing and completed swaps
     History,
+    /// Export the swap history for bookkeeping, including amounts, prices,
+    /// transaction ids, fees and the time of every state transition
+    ExportHistory {
+        #[structopt(
+            long = "format",
+            default_value = "csv",
+            possible_values = &["csv", "json"],
+            help = "The format of the export."
+        )]
+        format: ExportFormat,
+        #[structopt(
+            long = "from",
+            help = "Only export swaps started on or after this day (YYYY-MM-DD, UTC)."
+        )]
+        from: Option<String>,
+        #[structopt(
+            long = "to",
+            help = "Only export swaps started on or before this day (YYYY-MM-DD, UTC)."
+        )]
+        to: Option<String>,
+        #[structopt(
+            long = "output",
+            short = "o",
+            help = "Write the export to this file instead of printing it."
+        )]
+        output: Option<PathBuf>,
+    },
+    /// Show when a swap entered each of its states and how long it stayed there
+    Timeline {
+        #[structopt(flatten)]
+        swap_id: SwapId,
+    },
+    /// Write the logs, states, transactions and node connectivity of a swap to one archive
+    /// which can be shared with support. Private keys are left out and the logs are redacted.
+    Diagnostics {
+        #[structopt(flatten)]
+        swap_id: SwapId,
+
+        #[structopt(
+            long = "output",
+            short = "o",
+            help = "Write the archive to this file instead of the diagnostics folder of the data directory."
+        )]
+        output: Option<PathBuf>,
+
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(flatten)]
+        monero: Monero,
+
+        #[structopt(flatten)]
+        tor: Tor,
+    },
     /// Output all logging messages that have been issued.
     Logs {
         #[structopt(
@@ -378,12 +839,30 @@ enum CliCommand {
         #[structopt(
             long = "swap-id",
             help = "Filter for logs concerning this swap.",
-            long_help = "This checks whether each logging message contains the swap id. Some messages might be skipped when they don't contain the swap id even though they're relevant."
+            long_help = "This checks whether each logging message contains the swap id. Every message logged while running the swap carries the swap id, the peer id and the state of the swap as fields of its span."
         )]
         swap_id: Option<Uuid>,
+        #[structopt(
+            long = "since",
+            help = "Only print logs written at or after this time, e.g. 2025-06-25T12:00:00Z."
+        )]
+        since: Option<String>,
+        #[structopt(
+            long = "until",
+            help = "Only print logs written at or before this time, e.g. 2025-06-25T13:00:00Z."
+        )]
+        until: Option<String>,
+        #[structopt(
+            long = "level",
+            help = "Only print logs of this level or more severe: error, warn, info, debug or trace."
+        )]
+        level: Option<String>,
     },
     #[structopt(about = "Prints the current config")]
     Config,
+    /// Encrypt the seed file with a passphrase. The passphrase is asked for whenever the seed is
+    /// needed, e.g. when starting a swap. Keep it safe, the seed cannot be recovered without it.
+    EncryptSeed,
     #[structopt(about = "Allows withdrawing BTC from the internal Bitcoin wallet.")]
     WithdrawBtc {
         #[structopt(flatten)]
@@ -400,6 +879,51 @@ enum CliCommand {
             parse(try_from_str = bitcoin_address::parse)
         )]
         address: bitcoin::Address<NetworkUnchecked>,
+
+        #[structopt(
+            long = "input",
+            help = "Only spend this UTXO (txid:vout). Can be given multiple times."
+        )]
+        inputs: Vec<bitcoin::OutPoint>,
+    },
+    #[structopt(
+        about = "Lists the transactions of the internal Bitcoin wallet and the swaps they belong to."
+    )]
+    BitcoinTransactions {
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+    },
+    #[structopt(about = "Lists the UTXOs of the internal Bitcoin wallet with their labels.")]
+    ListUnspent {
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+    },
+    #[structopt(about = "Labels a UTXO of the internal Bitcoin wallet.")]
+    LabelUtxo {
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(long = "outpoint", help = "The UTXO to label (txid:vout).")]
+        outpoint: bitcoin::OutPoint,
+
+        #[structopt(
+            long = "label",
+            help = "The label to set. If omitted, the current label is removed."
+        )]
+        label: Option<String>,
+    },
+    #[structopt(
+        about = "Freezes a UTXO of the internal Bitcoin wallet such that it is never spent automatically."
+    )]
+    FreezeUtxo {
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(long = "outpoint", help = "The UTXO to freeze (txid:vout).")]
+        outpoint: bitcoin::OutPoint,
+
+        #[structopt(long = "unfreeze", help = "Unfreeze the UTXO instead.")]
+        unfreeze: bool,
     },
     #[structopt(about = "Prints the Bitcoin balance.")]
     Balance {
@@ -420,6 +944,20 @@ enum CliCommand {
         #[structopt(flatten)]
         tor: Tor,
     },
+    /// Ask the maker to refund the Bitcoin of a swap early, before they lock the Monero
+    EarlyRefund {
+        #[structopt(flatten)]
+        swap_id: SwapId,
+
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+
+        #[structopt(flatten)]
+        monero: Monero,
+
+        #[structopt(flatten)]
+        tor: Tor,
+    },
     /// Force the submission of the cancel and refund transactions of a swap
     #[structopt(aliases = &["cancel", "refund"])]
     CancelAndRefund {
@@ -429,6 +967,20 @@ enum CliCommand {
         #[structopt(flatten)]
         bitcoin: Bitcoin,
     },
+    /// Bump the fees of the unconfirmed Bitcoin transactions of a swap
+    BumpFee {
+        #[structopt(flatten)]
+        swap_id: SwapId,
+
+        #[structopt(
+            long = "fee-rate",
+            help = "The fee rate (in sat/vB) to target. Defaults to the current fee estimate"
+        )]
+        fee_rate: Option<u64>,
+
+        #[structopt(flatten)]
+        bitcoin: Bitcoin,
+    },
     /// Discover and list sellers (i.e. ASB providers)
     ListSellers {
         #[structopt(
@@ -440,6 +992,8 @@ enum CliCommand {
         #[structopt(flatten)]
         tor: Tor,
     },
+    /// Pin, ban and label sellers
+    Peers(Peers),
     /// Print the internal bitcoin wallet descriptor
     ExportBitcoinWallet {
         #[structopt(flatten)]
@@ -454,6 +1008,47 @@ enum CliCommand {
     },
 }
 
+#[derive(structopt::StructOpt, Debug)]
+enum Peers {
+    /// List the known sellers with their addresses, status, label and reputation
+    List,
+    /// Pin a seller such that it is preferred over all others
+    Add {
+        #[structopt(
+            long,
+            help = "The seller's address. Must include a peer ID part, i.e. `/p2p/`"
+        )]
+        peer: Multiaddr,
+
+        #[structopt(long = "label", help = "A name for the seller.")]
+        label: Option<String>,
+    },
+    /// Forget a seller's addresses and settings. This also lifts a ban.
+    Remove {
+        #[structopt(long = "peer-id", help = "The peer ID of the seller.")]
+        peer_id: PeerId,
+    },
+    /// Ban a seller such that it is never contacted again
+    Ban {
+        #[structopt(long = "peer-id", help = "The peer ID of the seller.")]
+        peer_id: PeerId,
+
+        #[structopt(long = "unban", help = "Lift the ban instead.")]
+        unban: bool,
+    },
+    /// Give a seller a label
+    Label {
+        #[structopt(long = "peer-id", help = "The peer ID of the seller.")]
+        peer_id: PeerId,
+
+        #[structopt(
+            long = "label",
+            help = "The label to set. If omitted, the current label is removed."
+        )]
+        label: Option<String>,
+    },
+}
+
 #[derive(structopt::StructOpt, Debug)]
 pub struct Monero {
     #[structopt(
@@ -473,6 +1068,12 @@ pub struct Bitcoin {
         help = "Estimate Bitcoin fees such that transactions are confirmed within the specified number of blocks"
     )]
     pub bitcoin_target_block: Option<u16>,
+
+    #[structopt(
+        long = "bump-fees",
+        help = "Periodically bump the fees of unconfirmed swap transactions that pay less than the current fee estimate"
+    )]
+    pub bump_fees: bool,
 }
 
 impl Bitcoin {
@@ -501,9 +1102,19 @@ impl Bitcoin {
 pub struct Tor {
     #[structopt(
         long = "enable-tor",
-        help = "Bootstrap a tor client and use it for all libp2p connections"
+        help = "Bootstrap a tor client and use it for all libp2p, Electrum and Monero connections"
     )]
     pub enable_tor: bool,
+    #[structopt(
+        long = "always-tor",
+        help = "Like --enable-tor, but refuse to reach Electrum and Monero nodes without Tor"
+    )]
+    pub always_tor: bool,
+    #[structopt(
+        long = "direct-local-connections",
+        help = "With --enable-tor, reach Electrum and Monero nodes on this machine or the local network directly, as Tor cannot reach them. Not allowed with --always-tor"
+    )]
+    pub direct_local_connections: bool,
 }
 
 #[derive(structopt::StructOpt, Debug)]
@@ -0,0 +1,496 @@
+//! Everything we know about a swap, bundled into one archive for support.
+//!
+//! The archive is meant to be shared, so it must not allow anyone to take our
+//! funds: private keys are removed from the stored states and the logs are
+//! redacted.
+
+use crate::bitcoin::{self, wallet::ScriptStatus};
+use crate::database;
+use crate::protocol::timeline::format_timestamp;
+use crate::protocol::{State, StateTransition};
+use ::bitcoin::Txid;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+use strum::VariantNames;
+
+/// Fields of the stored states which are kept as they are.
+///
+/// This is an allow-list so that a field added to a state later on is left out
+/// until someone made sure it does not hold a private key.
+const PUBLIC_FIELDS: &[&str] = &[
+    "btc",
+    "btc_amount",
+    "xmr",
+    "cancel_timelock",
+    "punish_timelock",
+    "change_address",
+    "refund_address",
+    "redeem_address",
+    "punish_address",
+    "min_monero_confirmations",
+    "monero_wallet_restore_blockheight",
+    "swap_id",
+    "tx_lock",
+    "tx_lock_id",
+    "tx_lock_fee",
+    "tx_lock_inputs",
+    "tx_redeem_fee",
+    "tx_punish_fee",
+    "tx_refund_fee",
+    "tx_cancel_fee",
+    "tx_hash",
+];
+
+/// Fields of the stored states which hold other states or a transfer proof, of
+/// which only the public fields are kept.
+const NESTED_FIELDS: &[&str] = &[
+    "state",
+    "state2",
+    "state3",
+    "state4",
+    "state5",
+    "state6",
+    "transfer_proof",
+    "lock_transfer_proof",
+];
+
+/// Names of the enum variants the stored states are made of.
+const STATE_VARIANTS: &[&[&str]] = &[
+    database::Swap::VARIANTS,
+    database::Alice::VARIANTS,
+    database::AliceEndState::VARIANTS,
+    database::Bob::VARIANTS,
+    database::BobEndState::VARIANTS,
+];
+
+/// Keeps only the public fields of a stored state, no matter how deeply nested.
+pub fn remove_secrets(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|name, value| {
+                if PUBLIC_FIELDS.contains(&name.as_str()) {
+                    return true;
+                }
+
+                let is_variant = STATE_VARIANTS
+                    .iter()
+                    .any(|variants| variants.contains(&name.as_str()));
+                if is_variant || NESTED_FIELDS.contains(&name.as_str()) {
+                    remove_secrets(value);
+                    return true;
+                }
+
+                false
+            });
+        }
+        Value::Array(values) => values.iter_mut().for_each(remove_secrets),
+        _ => {}
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StateEntry {
+    pub state: String,
+    /// RFC 3339 in UTC.
+    pub entered_at: String,
+    /// The state as we stored it, without private keys.
+    pub details: Value,
+}
+
+/// Every state the swap went through, in the order they were entered.
+pub fn states(transitions: &[StateTransition]) -> Result<Vec<StateEntry>> {
+    transitions
+        .iter()
+        .map(|transition| {
+            let mut details = serde_json::to_value(database::Swap::from(transition.state.clone()))?;
+            remove_secrets(&mut details);
+
+            Ok(StateEntry {
+                state: transition.state.to_string(),
+                entered_at: format_timestamp(transition.entered_at)?,
+                details,
+            })
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransactionStatus {
+    pub kind: &'static str,
+    pub txid: Txid,
+    pub status: String,
+    pub confirmations: Option<u32>,
+}
+
+/// The Bitcoin transactions of any state the swap went through and whether they
+/// have been published or confirmed.
+pub async fn transactions(
+    bitcoin_wallet: &bitcoin::Wallet,
+    transitions: &[StateTransition],
+) -> Vec<TransactionStatus> {
+    let mut seen = HashSet::new();
+    let mut statuses = Vec::new();
+
+    for transition in transitions {
+        let State::Bob(state) = &transition.state else {
+            continue;
+        };
+
+        let transactions = match state.swap_transactions() {
+            Ok(transactions) => transactions,
+            Err(error) => {
+                tracing::debug!(
+                    %state,
+                    error = %format!("{:#}", error),
+                    "Failed to construct the transactions of a state"
+                );
+                continue;
+            }
+        };
+
+        for transaction in transactions {
+            if !seen.insert(transaction.txid) {
+                continue;
+            }
+
+            let status = bitcoin_wallet.status_of_script(&transaction).await;
+            let (status, confirmations) = match status {
+                Ok(status @ ScriptStatus::Confirmed(inner)) => {
+                    (status.to_string(), Some(inner.confirmations()))
+                }
+                Ok(status) => (status.to_string(), None),
+                Err(error) => (format!("unknown: {:#}", error), None),
+            };
+
+            statuses.push(TransactionStatus {
+                kind: transaction.kind,
+                txid: transaction.txid,
+                status,
+                confirmations,
+            });
+        }
+    }
+
+    statuses
+}
+
+#[derive(Debug, Serialize)]
+pub struct NodeStatus {
+    pub url: String,
+    pub available: bool,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct Connectivity {
+    pub tor: bool,
+    pub electrum: Vec<NodeStatus>,
+    pub monero: Option<NodeStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub testnet: bool,
+}
+
+impl VersionInfo {
+    pub fn current(testnet: bool) -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            testnet,
+        }
+    }
+}
+
+/// Writes `files` (name and content) into a zip archive at `path`, replacing any
+/// existing file.
+pub fn write_archive(path: &Path, files: &[(&str, Vec<u8>)]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory {}", dir.display()))?;
+    }
+
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut archive = zip::ZipWriter::new(file);
+
+    for (name, content) in files {
+        archive.start_file(*name, zip::write::FileOptions::default())?;
+        archive.write_all(content)?;
+    }
+
+    archive.finish()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{Alice, AliceEndState, Bob, BobEndState, Swap};
+    use crate::monero::{self, BlockHeight, TransferProof, TxHash};
+    use crate::protocol::fixtures;
+    use ::bitcoin::address::NetworkUnchecked;
+    use rand::rngs::OsRng;
+    use serde_json::json;
+    use std::io::Read;
+
+    /// Fields of the stored states which hold private keys.
+    const SECRET_FIELDS: &[&str] = &["a", "b", "s_a", "s_b", "v", "v_a", "spend_key", "tx_key"];
+
+    /// Every variant of the stored states, built from a real swap so that all
+    /// private keys are set.
+    async fn all_stored_states() -> Vec<Swap> {
+        let (alice, state2) = fixtures::swap_setup_completed().await;
+        let (state3, _) = state2.clone().lock_btc().await.unwrap();
+        let height = BlockHeight { height: 1 };
+        let proof = TransferProof::new(
+            TxHash("abcd".to_string()),
+            monero::PrivateKey::from_scalar(monero::Scalar::random(&mut OsRng)),
+        );
+        let state4 = state3.clone().xmr_locked(height, proof.clone());
+        let state6 = state4.clone().cancel();
+        let state5 = state6.attempt_cooperative_redeem(alice.s_a, proof.clone());
+        let tx_lock_id = state3.tx_lock_id();
+
+        let bob = vec![
+            Bob::Started {
+                btc_amount: alice.btc,
+                change_address: "bcrt1q08pfqpsyrt7acllzyjm8q5qsz5capvyahm49rw"
+                    .parse::<::bitcoin::Address<NetworkUnchecked>>()
+                    .unwrap()
+                    .assume_checked(),
+                tx_lock_fee: alice.tx_refund_fee,
+                tx_lock_inputs: None,
+            },
+            Bob::ExecutionSetupDone { state2 },
+            Bob::BtcLocked {
+                state3: state3.clone(),
+                monero_wallet_restore_blockheight: height,
+            },
+            Bob::BtcEarlyRefundRequested {
+                state3: state3.clone(),
+                monero_wallet_restore_blockheight: height,
+            },
+            Bob::XmrLockProofReceived {
+                state: state3,
+                lock_transfer_proof: proof.clone(),
+                monero_wallet_restore_blockheight: height,
+            },
+            Bob::XmrLocked {
+                state4: state4.clone(),
+            },
+            Bob::EncSigSent {
+                state4: state4.clone(),
+            },
+            Bob::BtcPunished {
+                state: state6.clone(),
+                tx_lock_id,
+            },
+            Bob::BtcRedeemed(state5),
+            Bob::CancelTimelockExpired(state6.clone()),
+            Bob::BtcCancelled(state6.clone()),
+            Bob::BtcRefundPublished(state6.clone()),
+            Bob::BtcEarlyRefundPublished(state6.clone()),
+            Bob::Done(BobEndState::SafelyAborted),
+            Bob::Done(BobEndState::XmrRedeemed { tx_lock_id }),
+            Bob::Done(BobEndState::BtcRefunded(Box::new(state6.clone()))),
+            Bob::Done(BobEndState::BtcEarlyRefunded(Box::new(state6))),
+        ];
+
+        let state3 = alice;
+        let transfer_proof = proof;
+        let monero_wallet_restore_blockheight = height;
+        let alice = vec![
+            Alice::Started {
+                state3: state3.clone(),
+            },
+            Alice::BtcLockTransactionSeen {
+                state3: state3.clone(),
+            },
+            Alice::BtcLocked {
+                state3: state3.clone(),
+            },
+            Alice::XmrLockTransactionSent {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+            },
+            Alice::XmrLocked {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+            },
+            Alice::XmrLockTransferProofSent {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+            },
+            Alice::EncSigLearned {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                encrypted_signature: state4.tx_redeem_encsig(),
+                state3: state3.clone(),
+            },
+            Alice::BtcRedeemTransactionPublished {
+                state3: state3.clone(),
+                transfer_proof: transfer_proof.clone(),
+            },
+            Alice::CancelTimelockExpired {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+            },
+            Alice::BtcCancelled {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+            },
+            Alice::BtcPunishable {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+            },
+            Alice::BtcEarlyRefundable {
+                state3: state3.clone(),
+            },
+            Alice::BtcRefunded {
+                monero_wallet_restore_blockheight,
+                transfer_proof: transfer_proof.clone(),
+                state3: state3.clone(),
+                spend_key: monero::PrivateKey::from_scalar(state3.s_a),
+            },
+            Alice::Done(AliceEndState::SafelyAborted),
+            Alice::Done(AliceEndState::BtcRedeemed),
+            Alice::Done(AliceEndState::XmrRefunded),
+            Alice::Done(AliceEndState::BtcEarlyRefunded {
+                state3: state3.clone(),
+            }),
+            Alice::Done(AliceEndState::BtcPunished {
+                state3,
+                transfer_proof,
+            }),
+        ];
+
+        bob.into_iter()
+            .map(Swap::Bob)
+            .chain(alice.into_iter().map(Swap::Alice))
+            .collect()
+    }
+
+    /// Every value stored under one of the [`SECRET_FIELDS`], serialized.
+    fn secrets(value: &Value) -> Vec<String> {
+        match value {
+            Value::Object(fields) => fields
+                .iter()
+                .flat_map(|(name, value)| {
+                    if SECRET_FIELDS.contains(&name.as_str()) {
+                        vec![value.to_string()]
+                    } else {
+                        secrets(value)
+                    }
+                })
+                .collect(),
+            Value::Array(values) => values.iter().flat_map(secrets).collect(),
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn removes_nested_secrets() {
+        let mut state = json!({
+            "Bob": {
+                "BtcLocked": {
+                    "state3": {
+                        "b": "secret",
+                        "s_b": "secret",
+                        "v": "secret",
+                        "xmr": 1000,
+                        "tx_lock": { "inner": "psbt" },
+                        "added_later": "secret"
+                    },
+                    "lock_transfer_proof": { "tx_key": "secret", "tx_hash": "abcd" }
+                }
+            }
+        });
+
+        remove_secrets(&mut state);
+
+        assert_eq!(
+            state,
+            json!({
+                "Bob": {
+                    "BtcLocked": {
+                        "state3": {
+                            "xmr": 1000,
+                            "tx_lock": { "inner": "psbt" }
+                        },
+                        "lock_transfer_proof": { "tx_hash": "abcd" }
+                    }
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn no_private_key_of_any_stored_state_survives() {
+        for state in all_stored_states().await {
+            let stored = serde_json::to_value(&state).unwrap();
+            let secrets = secrets(&stored);
+
+            let mut details = stored.clone();
+            remove_secrets(&mut details);
+            let details = details.to_string();
+
+            if stored.to_string().contains("tx_lock") {
+                assert!(
+                    details.contains("tx_lock"),
+                    "public fields of {} were removed",
+                    stored
+                );
+            }
+            for secret in secrets {
+                assert!(
+                    !details.contains(&secret),
+                    "private key {} survived in {}",
+                    secret,
+                    details
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn archive_contains_all_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("diagnostics").join("swap.zip");
+
+        write_archive(
+            &path,
+            &[
+                ("logs.txt", b"a log line".to_vec()),
+                ("version.json", b"{}".to_vec()),
+            ],
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut logs = String::new();
+        archive
+            .by_name("logs.txt")
+            .unwrap()
+            .read_to_string(&mut logs)
+            .unwrap();
+
+        assert_eq!(archive.len(), 2);
+        assert_eq!(logs, "a log line");
+    }
+}
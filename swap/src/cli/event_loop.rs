@@ -1,45 +1,61 @@
 use crate::bitcoin::EncryptedSignature;
 use crate::cli::behaviour::{Behaviour, OutEvent};
+use crate::common::metrics::METRICS;
 use crate::monero;
+use crate::network::cooperative_early_refund;
 use crate::network::cooperative_xmr_redeem_after_punish::{self, Request, Response};
 use crate::network::encrypted_signature;
+#[cfg(feature = "test-utils")]
+use crate::network::fault_injection::MessageFaults;
+use crate::network::fault_injection::{FaultInjector, Message};
 use crate::network::quote::BidQuote;
 use crate::network::swap_setup::bob::NewSwap;
 use crate::protocol::bob::swap::has_already_processed_transfer_proof;
 use crate::protocol::bob::{BobState, State2};
 use crate::protocol::Database;
 use anyhow::{anyhow, Context, Result};
-use futures::future::{BoxFuture, OptionFuture};
+use bmrng::RequestReceiverStream;
+use futures::future::{self, BoxFuture};
+use futures::stream::{self, BoxStream, FuturesUnordered, SelectAll};
 use futures::{FutureExt, StreamExt};
 use libp2p::request_response::{OutboundFailure, OutboundRequestId, ResponseChannel};
 use libp2p::swarm::dial_opts::DialOpts;
 use libp2p::swarm::SwarmEvent;
-use libp2p::{PeerId, Swarm};
+use libp2p::{Multiaddr, PeerId, Swarm};
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::mpsc;
 use uuid::Uuid;
 
 static REQUEST_RESPONSE_PROTOCOL_TIMEOUT: Duration = Duration::from_secs(60);
 static EXECUTION_SETUP_PROTOCOL_TIMEOUT: Duration = Duration::from_secs(120);
 
+/// Runs the network layer of all swaps on one swarm.
+///
+/// Every swap talks to the event loop through its own [`EventLoopHandle`], which is created by
+/// [`EventLoopService::new_handle`]. The event loop stops once the last of its swaps is done.
 #[allow(missing_debug_implementations)]
 pub struct EventLoop {
-    swap_id: Uuid,
     swarm: libp2p::Swarm<Behaviour>,
-    alice_peer_id: PeerId,
     db: Arc<dyn Database + Send + Sync>,
 
-    // These streams represents outgoing requests that we have to make
+    /// The swaps which currently run on this event loop.
+    ///
+    /// For each of them we store the sender we use to relay incoming transfer proofs to its
+    /// EventLoopHandle. The corresponding receiver is stored in the EventLoopHandle.
+    swaps: HashMap<Uuid, (RunningSwap, bmrng::RequestSender<monero::TransferProof, ()>)>,
+
+    /// How many swaps have been added so far, see [`RunningSwap::registration`].
+    registrations: u64,
+
+    /// Swaps to add to the event loop, see [`EventLoopService::new_handle`].
+    new_swaps: mpsc::UnboundedReceiver<NewSwapChannels>,
+    new_swaps_sender: mpsc::UnboundedSender<NewSwapChannels>,
+
+    // These streams represents outgoing requests that the swaps want us to make
     // These are essentially queues of requests that we will send to Alice once we are connected to her.
-    quote_requests: bmrng::RequestReceiverStream<(), Result<BidQuote, OutboundFailure>>,
-    cooperative_xmr_redeem_requests: bmrng::RequestReceiverStream<
-        (),
-        Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>,
-    >,
-    encrypted_signatures_requests:
-        bmrng::RequestReceiverStream<EncryptedSignature, Result<(), OutboundFailure>>,
-    execution_setup_requests: bmrng::RequestReceiverStream<NewSwap, Result<State2>>,
+    requests: SelectAll<BoxStream<'static, (RunningSwap, SwapRequest)>>,
 
     // These represents requests that are currently in-flight.
     // Meaning that we have sent them to Alice, but we have not yet received a response.
@@ -49,80 +65,169 @@ pub struct EventLoop {
         HashMap<OutboundRequestId, bmrng::Responder<Result<BidQuote, OutboundFailure>>>,
     inflight_encrypted_signature_requests:
         HashMap<OutboundRequestId, bmrng::Responder<Result<(), OutboundFailure>>>,
-    inflight_swap_setup: Option<bmrng::Responder<Result<State2>>>,
+    inflight_swap_setups: HashMap<Uuid, bmrng::Responder<Result<State2>>>,
     inflight_cooperative_xmr_redeem_requests: HashMap<
         OutboundRequestId,
         bmrng::Responder<Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>>,
     >,
+    inflight_cooperative_early_refund_requests: HashMap<
+        OutboundRequestId,
+        bmrng::Responder<Result<cooperative_early_refund::Response, OutboundFailure>>,
+    >,
 
-    /// The sender we will use to relay incoming transfer proofs to the EventLoopHandle
-    /// The corresponding receiver is stored in the EventLoopHandle
-    transfer_proof_sender: bmrng::RequestSender<monero::TransferProof, ()>,
+    /// Swap setups we "buffer" until we are connected to Alice, because the protocol does not
+    /// dial Alice itself (unlike request-response).
+    #[allow(clippy::type_complexity)]
+    pending_swap_setups: HashMap<PeerId, Vec<(NewSwap, bmrng::Responder<Result<State2>>)>>,
 
-    /// The future representing the successful handling of an incoming transfer
-    /// proof.
+    /// The futures representing the handling of incoming transfer proofs.
     ///
-    /// Once we've sent a transfer proof to the ongoing swap, this future waits
-    /// until the swap took it "out" of the `EventLoopHandle`. As this future
+    /// Each future passes a transfer proof on to an ongoing swap and waits
+    /// until the swap took it "out" of its `EventLoopHandle`. As the future
     /// resolves, we use the `ResponseChannel` returned from it to send an ACK
-    /// to Alice that we have successfully processed the transfer proof.
-    pending_transfer_proof: OptionFuture<BoxFuture<'static, ResponseChannel<()>>>,
+    /// to Alice that we have successfully processed the transfer proof. It
+    /// resolves to `None` if the swap is gone, Alice will send the proof again.
+    pending_transfer_proofs: FuturesUnordered<BoxFuture<'static, Option<ResponseChannel<()>>>>,
+
+    /// Faults to inject into the requests we send, only used by tests.
+    request_faults: FaultInjector<OutgoingRequest>,
 }
 
-impl EventLoop {
-    pub fn new(
-        swap_id: Uuid,
-        swarm: Swarm<Behaviour>,
-        alice_peer_id: PeerId,
-        db: Arc<dyn Database + Send + Sync>,
-    ) -> Result<(Self, EventLoopHandle)> {
-        // We still use a timeout here, because this protocol does not dial Alice itself
-        // and we want to fail if we cannot reach Alice
-        let (execution_setup_sender, execution_setup_receiver) =
-            bmrng::channel_with_timeout(1, EXECUTION_SETUP_PROTOCOL_TIMEOUT);
+/// A swap which runs on the [`EventLoop`].
+#[derive(Debug, Clone, Copy)]
+struct RunningSwap {
+    swap_id: Uuid,
+    alice_peer_id: PeerId,
+    /// Tells apart the handles of the same swap, e.g. if it is resumed while the handle of its
+    /// previous run is still being dropped.
+    registration: u64,
+}
 
-        // It is okay to not have a timeout here, as timeouts are enforced by the request-response protocol
-        let (transfer_proof_sender, transfer_proof_receiver) = bmrng::channel(1);
-        let (encrypted_signature_sender, encrypted_signature_receiver) = bmrng::channel(1);
-        let (quote_sender, quote_receiver) = bmrng::channel(1);
-        let (cooperative_xmr_redeem_sender, cooperative_xmr_redeem_receiver) = bmrng::channel(1);
+/// The event loop's ends of the channels of a new [`EventLoopHandle`].
+struct NewSwapChannels {
+    swap_id: Uuid,
+    alice_peer_id: PeerId,
+    alice_addresses: Vec<Multiaddr>,
+    transfer_proof_sender: bmrng::RequestSender<monero::TransferProof, ()>,
+    quote_requests: bmrng::RequestReceiver<(), Result<BidQuote, OutboundFailure>>,
+    encrypted_signature_requests:
+        bmrng::RequestReceiver<EncryptedSignature, Result<(), OutboundFailure>>,
+    cooperative_xmr_redeem_requests: bmrng::RequestReceiver<
+        (),
+        Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>,
+    >,
+    cooperative_early_refund_requests:
+        bmrng::RequestReceiver<(), Result<cooperative_early_refund::Response, OutboundFailure>>,
+    execution_setup_requests: bmrng::RequestReceiver<NewSwap, Result<State2>>,
+}
 
-        let event_loop = EventLoop {
-            swap_id,
+/// A request which a swap sent through its [`EventLoopHandle`].
+enum SwapRequest {
+    Quote(bmrng::Responder<Result<BidQuote, OutboundFailure>>),
+    EncryptedSignature(
+        EncryptedSignature,
+        bmrng::Responder<Result<(), OutboundFailure>>,
+    ),
+    CooperativeXmrRedeem(
+        bmrng::Responder<Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>>,
+    ),
+    CooperativeEarlyRefund(
+        bmrng::Responder<Result<cooperative_early_refund::Response, OutboundFailure>>,
+    ),
+    SwapSetup(NewSwap, bmrng::Responder<Result<State2>>),
+    /// The [`EventLoopHandle`] of the swap was dropped, the swap is not running anymore.
+    HandleDropped,
+}
+
+/// A request to Alice which faults can be injected into, see [`EventLoop::request_faults`].
+enum OutgoingRequest {
+    SwapSetup(RunningSwap, NewSwap, bmrng::Responder<Result<State2>>),
+    EncryptedSignature(
+        RunningSwap,
+        EncryptedSignature,
+        bmrng::Responder<Result<(), OutboundFailure>>,
+    ),
+    CooperativeEarlyRefund(
+        RunningSwap,
+        bmrng::Responder<Result<cooperative_early_refund::Response, OutboundFailure>>,
+    ),
+    CooperativeXmrRedeem(
+        RunningSwap,
+        bmrng::Responder<Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>>,
+    ),
+}
+
+impl OutgoingRequest {
+    fn message(&self) -> Message {
+        match self {
+            OutgoingRequest::SwapSetup(..) => Message::SwapSetup,
+            OutgoingRequest::EncryptedSignature(..) => Message::EncryptedSignature,
+            OutgoingRequest::CooperativeEarlyRefund(..) => Message::CooperativeEarlyRefund,
+            OutgoingRequest::CooperativeXmrRedeem(..) => Message::CooperativeXmrRedeem,
+        }
+    }
+
+    /// The request got lost on its way to Alice, the swap observes a timeout.
+    fn lose(self) {
+        match self {
+            OutgoingRequest::SwapSetup(_, _, responder) => {
+                let _ = responder.respond(Err(anyhow!("The swap setup request was lost")));
+            }
+            OutgoingRequest::EncryptedSignature(_, _, responder) => {
+                let _ = responder.respond(Err(OutboundFailure::Timeout));
+            }
+            OutgoingRequest::CooperativeEarlyRefund(_, responder) => {
+                let _ = responder.respond(Err(OutboundFailure::Timeout));
+            }
+            OutgoingRequest::CooperativeXmrRedeem(_, responder) => {
+                let _ = responder.respond(Err(OutboundFailure::Timeout));
+            }
+        }
+    }
+}
+
+impl EventLoop {
+    pub fn new(swarm: Swarm<Behaviour>, db: Arc<dyn Database + Send + Sync>) -> Self {
+        let (new_swaps_sender, new_swaps) = mpsc::unbounded_channel();
+
+        EventLoop {
             swarm,
-            alice_peer_id,
-            execution_setup_requests: execution_setup_receiver.into(),
-            transfer_proof_sender,
-            encrypted_signatures_requests: encrypted_signature_receiver.into(),
-            cooperative_xmr_redeem_requests: cooperative_xmr_redeem_receiver.into(),
-            quote_requests: quote_receiver.into(),
+            db,
+            swaps: HashMap::default(),
+            registrations: 0,
+            new_swaps,
+            new_swaps_sender,
+            requests: SelectAll::new(),
             inflight_quote_requests: HashMap::default(),
-            inflight_swap_setup: None,
+            inflight_swap_setups: HashMap::default(),
             inflight_encrypted_signature_requests: HashMap::default(),
             inflight_cooperative_xmr_redeem_requests: HashMap::default(),
-            pending_transfer_proof: OptionFuture::from(None),
-            db,
-        };
+            inflight_cooperative_early_refund_requests: HashMap::default(),
+            pending_swap_setups: HashMap::default(),
+            pending_transfer_proofs: FuturesUnordered::new(),
+            request_faults: FaultInjector::default(),
+        }
+    }
 
-        let handle = EventLoopHandle {
-            execution_setup_sender,
-            transfer_proof_receiver,
-            encrypted_signature_sender,
-            cooperative_xmr_redeem_sender,
-            quote_sender,
-        };
+    /// Returns a handle which is used to add swaps to the event loop while it is running.
+    pub fn service(&self) -> EventLoopService {
+        EventLoopService {
+            sender: self.new_swaps_sender.clone(),
+        }
+    }
 
-        Ok((event_loop, handle))
+    /// Inject the given faults into the requests we send to Alice.
+    ///
+    /// Only used by tests, see [`fault_injection`](crate::network::fault_injection).
+    #[cfg(feature = "test-utils")]
+    pub fn with_message_faults(mut self, message_faults: MessageFaults) -> Self {
+        self.request_faults = FaultInjector::new(message_faults);
+        self
     }
 
     pub async fn run(mut self) {
-        match self.swarm.dial(DialOpts::from(self.alice_peer_id)) {
-            Ok(()) => {}
-            Err(e) => {
-                tracing::error!("Failed to initiate dial to Alice: {:?}", e);
-                return;
-            }
-        }
+        // Ensure that these streams are never empty, otherwise they would terminate
+        self.pending_transfer_proofs.push(future::pending().boxed());
 
         loop {
             // Note: We are making very elaborate use of `select!` macro's feature here. Make sure to read the documentation thoroughly: https://docs.rs/tokio/1.4.0/tokio/macro.select.html
@@ -134,21 +239,21 @@ impl EventLoop {
                                 let _ = responder.respond(Ok(response));
                             }
                         }
-                        SwarmEvent::Behaviour(OutEvent::SwapSetupCompleted(response)) => {
-                            if let Some(responder) = self.inflight_swap_setup.take() {
-                                let _ = responder.respond(*response);
+                        SwarmEvent::Behaviour(OutEvent::SwapSetupCompleted { swap_id, result }) => {
+                            if let Some(responder) = self.inflight_swap_setups.remove(&swap_id) {
+                                let _ = responder.respond(*result);
                             }
                         }
                         SwarmEvent::Behaviour(OutEvent::TransferProofReceived { msg, channel, peer }) => {
                             let swap_id = msg.swap_id;
 
-                            if swap_id == self.swap_id {
-                                if peer != self.alice_peer_id {
+                            if let Some((swap, transfer_proof_sender)) = self.swaps.get(&swap_id) {
+                                if peer != swap.alice_peer_id {
                                     tracing::warn!(
                                                 %swap_id,
                                                 "Ignoring malicious transfer proof from {}, expected to receive it from {}",
                                                 peer,
-                                                self.alice_peer_id);
+                                                swap.alice_peer_id);
                                             continue;
                                 }
 
@@ -160,61 +265,65 @@ impl EventLoop {
                                         .expect("Bobs database only contains Bob states");
 
                                     if has_already_processed_transfer_proof(&state) {
-                                        tracing::warn!("Received transfer proof for swap {} but we are already in state {}. Acknowledging immediately. Alice most likely did not receive the acknowledgment when we sent it before", swap_id, state);
+                                        tracing::warn!(%swap_id, peer_id = %peer, %state, "Received transfer proof but we already processed it. Acknowledging immediately. Alice most likely did not receive the acknowledgment when we sent it before");
+                                        METRICS.record_transfer_proof_retry();
 
-                                        // We set this to a future that will resolve immediately, and returns the channel
+                                        // We add a future that will resolve immediately, and returns the channel
                                         // This will be resolved in the next iteration of the event loop, and a response will be sent to Alice
-                                        self.pending_transfer_proof = OptionFuture::from(Some(async move {
-                                            channel
-                                        }.boxed()));
+                                        self.pending_transfer_proofs.push(async move {
+                                            Some(channel)
+                                        }.boxed());
 
                                         continue;
                                     }
                                 }
 
-                                let mut responder = match self.transfer_proof_sender.send(msg.tx_lock_proof).await {
-                                    Ok(responder) => responder,
-                                    Err(e) => {
-                                        tracing::warn!("Failed to pass on transfer proof: {:#}", e);
-                                        continue;
-                                    }
-                                };
+                                // Passing the transfer proof on waits until the swap has room for it, which
+                                // must not hold up the other swaps
+                                let transfer_proof_sender = transfer_proof_sender.clone();
+                                self.pending_transfer_proofs.push(async move {
+                                    let mut responder = match transfer_proof_sender.send(msg.tx_lock_proof).await {
+                                        Ok(responder) => responder,
+                                        Err(e) => {
+                                            tracing::warn!(%swap_id, peer_id = %peer, "Failed to pass on transfer proof: {:#}", e);
+                                            return None;
+                                        }
+                                    };
 
-                                self.pending_transfer_proof = OptionFuture::from(Some(async move {
                                     let _ = responder.recv().await;
 
-                                    channel
-                                }.boxed()));
-                            }else {
+                                    Some(channel)
+                                }.boxed());
+                            } else {
                                 // Check if the transfer proof is sent from the correct peer and if we have a record of the swap
                                 match self.db.get_peer_id(swap_id).await {
                                     // We have a record of the swap
                                     Ok(buffer_swap_alice_peer_id) => {
-                                        if buffer_swap_alice_peer_id == self.alice_peer_id {
+                                        if buffer_swap_alice_peer_id == peer {
                                             // Save transfer proof in the database such that we can process it later when we resume the swap
                                             match self.db.insert_buffered_transfer_proof(swap_id, msg.tx_lock_proof).await {
                                                 Ok(_) => {
-                                                    tracing::info!("Received transfer proof for swap {} while running swap {}. Buffering this transfer proof in the database for later retrieval", swap_id, self.swap_id);
+                                                    tracing::info!(%swap_id, peer_id = %peer, "Received transfer proof while the swap is not running. Buffering this transfer proof in the database for later retrieval");
                                                     let _ = self.swarm.behaviour_mut().transfer_proof.send_response(channel, ());
                                                 }
                                                 Err(e) => {
-                                                    tracing::error!("Failed to buffer transfer proof for swap {}: {:#}", swap_id, e);
+                                                    tracing::error!(%swap_id, peer_id = %peer, "Failed to buffer transfer proof: {:#}", e);
                                                 }
                                             };
                                         }else {
                                             tracing::warn!(
                                                 %swap_id,
                                                 "Ignoring malicious transfer proof from {}, expected to receive it from {}",
-                                                self.swap_id,
+                                                peer,
                                                 buffer_swap_alice_peer_id);
                                         }
                                     },
                                     // We do not have a record of the swap or an error occurred while retrieving the peer id of Alice
                                     Err(e) => {
                                         if let Some(sqlx::Error::RowNotFound) = e.downcast_ref::<sqlx::Error>() {
-                                            tracing::warn!("Ignoring transfer proof for swap {} while running swap {}. We do not have a record of this swap", swap_id, self.swap_id);
+                                            tracing::warn!(%swap_id, peer_id = %peer, "Ignoring transfer proof. We do not have a record of this swap");
                                         } else {
-                                            tracing::error!("Ignoring transfer proof for swap {} while running swap {}. Failed to retrieve the peer id of Alice for the corresponding swap: {:#}", swap_id, self.swap_id, e);
+                                            tracing::error!(%swap_id, peer_id = %peer, "Ignoring transfer proof. Failed to retrieve the peer id of Alice for the corresponding swap: {:#}", e);
                                         }
                                     }
                                 }
@@ -235,38 +344,46 @@ impl EventLoop {
                                 let _ = responder.respond(Ok(Response::Rejected { reason, swap_id }));
                             }
                         }
+                        SwarmEvent::Behaviour(OutEvent::CooperativeEarlyRefundResponse { id, response }) => {
+                            if let Some(responder) = self.inflight_cooperative_early_refund_requests.remove(&id) {
+                                let _ = responder.respond(Ok(response));
+                            }
+                        }
                         SwarmEvent::Behaviour(OutEvent::Failure { peer, error }) => {
-                            tracing::warn!(%peer, err = ?error, "Communication error");
-                            return;
+                            // Other swaps might still be running with this or other peers, so we keep going
+                            tracing::warn!(peer_id = %peer, err = ?error, "Communication error");
                         }
-                        SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } if peer_id == self.alice_peer_id => {
-                            tracing::info!(peer_id = %endpoint.get_remote_address(), "Connected to Alice");
+                        SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } if self.is_alice(&peer_id) => {
+                            tracing::info!(%peer_id, address = %endpoint.get_remote_address(), "Connected to Alice");
+
+                            for (swap, responder) in self.pending_swap_setups.remove(&peer_id).unwrap_or_default() {
+                                self.start_swap_setup(peer_id, swap, responder).await;
+                            }
                         }
-                        SwarmEvent::Dialing { peer_id: Some(alice_peer_id), connection_id } if alice_peer_id == self.alice_peer_id => {
-                            tracing::debug!(%alice_peer_id, %connection_id, "Dialing Alice");
+                        SwarmEvent::Dialing { peer_id: Some(alice_peer_id), connection_id } if self.is_alice(&alice_peer_id) => {
+                            tracing::debug!(peer_id = %alice_peer_id, %connection_id, "Dialing Alice");
                         }
-                        SwarmEvent::ConnectionClosed { peer_id, endpoint, num_established, cause: Some(error), connection_id } if peer_id == self.alice_peer_id && num_established == 0 => {
-                            tracing::warn!(peer_id = %endpoint.get_remote_address(), cause = ?error, %connection_id, "Lost connection to Alice");
+                        SwarmEvent::ConnectionClosed { peer_id, endpoint, num_established, cause: Some(error), connection_id } if self.is_alice(&peer_id) && num_established == 0 => {
+                            tracing::warn!(%peer_id, address = %endpoint.get_remote_address(), cause = ?error, %connection_id, "Lost connection to Alice");
 
-                            if let Some(duration) = self.swarm.behaviour_mut().redial.until_next_redial() {
+                            if let Some(duration) = self.swarm.behaviour_mut().redial.until_next_redial(&peer_id) {
                                 tracing::info!(seconds_until_next_redial = %duration.as_secs(), "Waiting for next redial attempt");
                             }
                         }
-                        SwarmEvent::ConnectionClosed { peer_id, num_established, cause: None, .. } if peer_id == self.alice_peer_id && num_established == 0 => {
+                        SwarmEvent::ConnectionClosed { peer_id, num_established, cause: None, .. } if self.is_alice(&peer_id) && num_established == 0 => {
                             // no error means the disconnection was requested
-                            tracing::info!("Successfully closed connection to Alice");
-                            return;
+                            tracing::info!(%peer_id, "Successfully closed connection to Alice");
                         }
-                        SwarmEvent::OutgoingConnectionError { peer_id: Some(alice_peer_id),  error, connection_id } if alice_peer_id == self.alice_peer_id => {
-                            tracing::warn!(%alice_peer_id, %connection_id, ?error, "Failed to connect to Alice");
+                        SwarmEvent::OutgoingConnectionError { peer_id: Some(alice_peer_id),  error, connection_id } if self.is_alice(&alice_peer_id) => {
+                            tracing::warn!(peer_id = %alice_peer_id, %connection_id, ?error, "Failed to connect to Alice");
 
-                            if let Some(duration) = self.swarm.behaviour_mut().redial.until_next_redial() {
+                            if let Some(duration) = self.swarm.behaviour_mut().redial.until_next_redial(&alice_peer_id) {
                                 tracing::info!(seconds_until_next_redial = %duration.as_secs(), "Waiting for next redial attempt");
                             }
                         }
                         SwarmEvent::Behaviour(OutEvent::OutboundRequestResponseFailure {peer, error, request_id, protocol}) => {
                             tracing::error!(
-                                %peer,
+                                peer_id = %peer,
                                 %request_id,
                                 ?error,
                                 %protocol,
@@ -292,10 +409,16 @@ impl EventLoop {
                                 let _ = responder.respond(Err(error));
                                 continue;
                             }
+
+                            // Check for cooperative early refund requests
+                            if let Some(responder) = self.inflight_cooperative_early_refund_requests.remove(&request_id) {
+                                let _ = responder.respond(Err(error));
+                                continue;
+                            }
                         }
                         SwarmEvent::Behaviour(OutEvent::InboundRequestResponseFailure {peer, error, request_id, protocol}) => {
                             tracing::error!(
-                                %peer,
+                                peer_id = %peer,
                                 %request_id,
                                 ?error,
                                 %protocol,
@@ -305,59 +428,333 @@ impl EventLoop {
                     }
                 },
 
-                // Handle to-be-sent outgoing requests for all our network protocols.
-                Some(((), responder)) = self.quote_requests.next().fuse() => {
-                    let id = self.swarm.behaviour_mut().quote.send_request(&self.alice_peer_id, ());
-                    self.inflight_quote_requests.insert(id, responder);
-                },
-                Some((tx_redeem_encsig, responder)) = self.encrypted_signatures_requests.next().fuse() => {
-                    let request = encrypted_signature::Request {
-                        swap_id: self.swap_id,
-                        tx_redeem_encsig
-                    };
-
-                    let id = self.swarm.behaviour_mut().encrypted_signature.send_request(&self.alice_peer_id, request);
-                    self.inflight_encrypted_signature_requests.insert(id, responder);
-                },
-                Some((_, responder)) = self.cooperative_xmr_redeem_requests.next().fuse() => {
-                    let id = self.swarm.behaviour_mut().cooperative_xmr_redeem.send_request(&self.alice_peer_id, Request {
-                        swap_id: self.swap_id
-                    });
-                    self.inflight_cooperative_xmr_redeem_requests.insert(id, responder);
+                Some(new_swap) = self.new_swaps.recv() => {
+                    self.add_swap(new_swap);
                 },
 
-                // We use `self.is_connected_to_alice` as a guard to "buffer" requests until we are connected.
-                // because the protocol does not dial Alice itself
-                // (unlike request-response above)
-                Some((swap, responder)) = self.execution_setup_requests.next().fuse(), if self.is_connected_to_alice() => {
-                    self.swarm.behaviour_mut().swap_setup.start(self.alice_peer_id, swap).await;
-                    self.inflight_swap_setup = Some(responder);
+                // Handle to-be-sent outgoing requests of all swaps for all our network protocols.
+                Some((swap, request)) = self.requests.next() => {
+                    match request {
+                        SwapRequest::Quote(responder) => {
+                            let id = self.swarm.behaviour_mut().quote.send_request(&swap.alice_peer_id, ());
+                            self.inflight_quote_requests.insert(id, responder);
+                        }
+                        SwapRequest::EncryptedSignature(tx_redeem_encsig, responder) => {
+                            self.send_with_faults(OutgoingRequest::EncryptedSignature(swap, tx_redeem_encsig, responder)).await;
+                        }
+                        SwapRequest::CooperativeXmrRedeem(responder) => {
+                            self.send_with_faults(OutgoingRequest::CooperativeXmrRedeem(swap, responder)).await;
+                        }
+                        SwapRequest::CooperativeEarlyRefund(responder) => {
+                            self.send_with_faults(OutgoingRequest::CooperativeEarlyRefund(swap, responder)).await;
+                        }
+                        SwapRequest::SwapSetup(new_swap, responder) => {
+                            self.send_with_faults(OutgoingRequest::SwapSetup(swap, new_swap, responder)).await;
+                        }
+                        SwapRequest::HandleDropped => {
+                            self.remove_swap(swap);
+
+                            if self.stop_if_idle() {
+                                tracing::debug!("All swaps are done, stopping the event loop");
+                                return;
+                            }
+                        }
+                    }
+                },
+                request = self.request_faults.next_due() => {
+                    self.send_request(request).await;
                 },
 
-                // Send an acknowledgement to Alice once the EventLoopHandle has processed a received transfer proof
-                // We use `self.is_connected_to_alice` as a guard to "buffer" requests until we are connected.
-                //
-                // Why do we do this here but not for the other request-response channels?
-                // This is the only request, we don't have a retry mechanism for. We lazily send this.
-                Some(response_channel) = &mut self.pending_transfer_proof, if self.is_connected_to_alice() => {
+                // Send an acknowledgement to Alice once an EventLoopHandle has processed a received transfer proof
+                Some(Some(response_channel)) = self.pending_transfer_proofs.next() => {
                     if self.swarm.behaviour_mut().transfer_proof.send_response(response_channel, ()).is_err() {
                         tracing::warn!("Failed to send acknowledgment to Alice that we have received the transfer proof");
                     } else {
                         tracing::info!("Sent acknowledgment to Alice that we have received the transfer proof");
-                        self.pending_transfer_proof = OptionFuture::from(None);
                     }
                 },
             }
         }
     }
 
-    fn is_connected_to_alice(&self) -> bool {
-        self.swarm.is_connected(&self.alice_peer_id)
+    /// Whether Alice of any of the running swaps is this peer.
+    fn is_alice(&self, peer: &PeerId) -> bool {
+        self.swaps
+            .values()
+            .any(|(swap, _)| swap.alice_peer_id == *peer)
+    }
+
+    fn add_swap(&mut self, new_swap: NewSwapChannels) {
+        let NewSwapChannels {
+            swap_id,
+            alice_peer_id,
+            alice_addresses,
+            transfer_proof_sender,
+            quote_requests,
+            encrypted_signature_requests,
+            cooperative_xmr_redeem_requests,
+            cooperative_early_refund_requests,
+            execution_setup_requests,
+        } = new_swap;
+
+        self.registrations += 1;
+        let swap = RunningSwap {
+            swap_id,
+            alice_peer_id,
+            registration: self.registrations,
+        };
+
+        for address in alice_addresses {
+            self.swarm.add_peer_address(alice_peer_id, address);
+        }
+
+        self.swarm.behaviour_mut().redial.add_peer(alice_peer_id);
+
+        if !self.swarm.is_connected(&alice_peer_id) {
+            if let Err(e) = self.swarm.dial(DialOpts::from(alice_peer_id)) {
+                tracing::error!(%swap_id, "Failed to initiate dial to Alice: {:?}", e);
+            }
+        }
+
+        // Once the EventLoopHandle is dropped, all of these streams end and we remove the swap
+        let requests = stream::select_all([
+            RequestReceiverStream::from(quote_requests)
+                .map(|((), responder)| SwapRequest::Quote(responder))
+                .boxed(),
+            RequestReceiverStream::from(encrypted_signature_requests)
+                .map(|(tx_redeem_encsig, responder)| {
+                    SwapRequest::EncryptedSignature(tx_redeem_encsig, responder)
+                })
+                .boxed(),
+            RequestReceiverStream::from(cooperative_xmr_redeem_requests)
+                .map(|((), responder)| SwapRequest::CooperativeXmrRedeem(responder))
+                .boxed(),
+            RequestReceiverStream::from(cooperative_early_refund_requests)
+                .map(|((), responder)| SwapRequest::CooperativeEarlyRefund(responder))
+                .boxed(),
+            RequestReceiverStream::from(execution_setup_requests)
+                .map(|(new_swap, responder)| SwapRequest::SwapSetup(new_swap, responder))
+                .boxed(),
+        ])
+        .chain(stream::once(future::ready(SwapRequest::HandleDropped)))
+        .map(move |request| (swap, request))
+        .boxed();
+
+        self.swaps.insert(swap_id, (swap, transfer_proof_sender));
+        self.requests.push(requests);
+
+        tracing::debug!(%swap_id, peer_id = %alice_peer_id, "Added swap to the event loop");
+    }
+
+    fn remove_swap(&mut self, swap: RunningSwap) {
+        // The swap might have been added again in the meantime
+        match self.swaps.get(&swap.swap_id) {
+            Some((running, _)) if running.registration == swap.registration => {}
+            _ => return,
+        }
+
+        self.swaps.remove(&swap.swap_id);
+        self.inflight_swap_setups.remove(&swap.swap_id);
+
+        // Stop re-dialling Alice unless another swap still needs her
+        if !self.is_alice(&swap.alice_peer_id) {
+            self.swarm
+                .behaviour_mut()
+                .redial
+                .remove_peer(&swap.alice_peer_id);
+            self.pending_swap_setups.remove(&swap.alice_peer_id);
+        }
+
+        tracing::debug!(swap_id = %swap.swap_id, "Removed swap from the event loop");
+    }
+
+    /// Stop taking new swaps once the last swap is done, the next swap starts a new event loop.
+    ///
+    /// Swaps which were added in the meantime are still run. Returns whether we are done.
+    fn stop_if_idle(&mut self) -> bool {
+        if !self.swaps.is_empty() {
+            return false;
+        }
+
+        self.new_swaps.close();
+        while let Ok(new_swap) = self.new_swaps.try_recv() {
+            self.add_swap(new_swap);
+        }
+
+        self.swaps.is_empty()
+    }
+
+    /// Send `request` to Alice, unless a fault is injected into it.
+    async fn send_with_faults(&mut self, request: OutgoingRequest) {
+        let message = request.message();
+
+        if let Some(request) =
+            self.request_faults
+                .intercept(message, request, OutgoingRequest::lose)
+        {
+            self.send_request(request).await;
+        }
+    }
+
+    async fn send_request(&mut self, request: OutgoingRequest) {
+        match request {
+            // We "buffer" swap setups until we are connected to Alice
+            // because the protocol does not dial Alice itself
+            // (unlike request-response)
+            OutgoingRequest::SwapSetup(swap, new_swap, responder) => {
+                if self.swarm.is_connected(&swap.alice_peer_id) {
+                    self.start_swap_setup(swap.alice_peer_id, new_swap, responder)
+                        .await;
+                } else {
+                    self.pending_swap_setups
+                        .entry(swap.alice_peer_id)
+                        .or_default()
+                        .push((new_swap, responder));
+                }
+            }
+            OutgoingRequest::EncryptedSignature(swap, tx_redeem_encsig, responder) => {
+                self.send_encrypted_signature(swap, tx_redeem_encsig, responder);
+            }
+            OutgoingRequest::CooperativeEarlyRefund(swap, responder) => {
+                let id = self
+                    .swarm
+                    .behaviour_mut()
+                    .cooperative_early_refund
+                    .send_request(
+                        &swap.alice_peer_id,
+                        cooperative_early_refund::Request {
+                            swap_id: swap.swap_id,
+                        },
+                    );
+                self.inflight_cooperative_early_refund_requests
+                    .insert(id, responder);
+            }
+            OutgoingRequest::CooperativeXmrRedeem(swap, responder) => {
+                self.send_cooperative_xmr_redeem_request(swap, responder);
+            }
+        }
+    }
+
+    async fn start_swap_setup(
+        &mut self,
+        alice_peer_id: PeerId,
+        new_swap: NewSwap,
+        responder: bmrng::Responder<Result<State2>>,
+    ) {
+        let swap_id = new_swap.swap_id;
+
+        self.swarm
+            .behaviour_mut()
+            .swap_setup
+            .start(alice_peer_id, new_swap)
+            .await;
+        self.inflight_swap_setups.insert(swap_id, responder);
+    }
+
+    fn send_encrypted_signature(
+        &mut self,
+        swap: RunningSwap,
+        tx_redeem_encsig: EncryptedSignature,
+        responder: bmrng::Responder<Result<(), OutboundFailure>>,
+    ) {
+        let request = encrypted_signature::Request {
+            swap_id: swap.swap_id,
+            tx_redeem_encsig,
+        };
+
+        let id = self
+            .swarm
+            .behaviour_mut()
+            .encrypted_signature
+            .send_request(&swap.alice_peer_id, request);
+        self.inflight_encrypted_signature_requests
+            .insert(id, responder);
+    }
+
+    fn send_cooperative_xmr_redeem_request(
+        &mut self,
+        swap: RunningSwap,
+        responder: bmrng::Responder<
+            Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>,
+        >,
+    ) {
+        let id = self
+            .swarm
+            .behaviour_mut()
+            .cooperative_xmr_redeem
+            .send_request(
+                &swap.alice_peer_id,
+                Request {
+                    swap_id: swap.swap_id,
+                },
+            );
+        self.inflight_cooperative_xmr_redeem_requests
+            .insert(id, responder);
+    }
+}
+
+/// Adds swaps to a running [`EventLoop`].
+#[derive(Debug, Clone)]
+pub struct EventLoopService {
+    sender: mpsc::UnboundedSender<NewSwapChannels>,
+}
+
+impl EventLoopService {
+    /// Create the [`EventLoopHandle`] through which a swap with the given Alice talks to the
+    /// event loop. We dial Alice right away.
+    ///
+    /// The swap is removed from the event loop once the handle is dropped. Fails if the event
+    /// loop is not running anymore.
+    pub fn new_handle(
+        &self,
+        swap_id: Uuid,
+        alice_peer_id: PeerId,
+        alice_addresses: Vec<Multiaddr>,
+    ) -> Result<EventLoopHandle> {
+        // We still use a timeout here, because this protocol does not dial Alice itself
+        // and we want to fail if we cannot reach Alice
+        let (execution_setup_sender, execution_setup_receiver) =
+            bmrng::channel_with_timeout(1, EXECUTION_SETUP_PROTOCOL_TIMEOUT);
+
+        // It is okay to not have a timeout here, as timeouts are enforced by the request-response protocol
+        let (transfer_proof_sender, transfer_proof_receiver) = bmrng::channel(1);
+        let (encrypted_signature_sender, encrypted_signature_receiver) = bmrng::channel(1);
+        let (quote_sender, quote_receiver) = bmrng::channel(1);
+        let (cooperative_xmr_redeem_sender, cooperative_xmr_redeem_receiver) = bmrng::channel(1);
+        let (cooperative_early_refund_sender, cooperative_early_refund_receiver) =
+            bmrng::channel(1);
+
+        self.sender
+            .send(NewSwapChannels {
+                swap_id,
+                alice_peer_id,
+                alice_addresses,
+                transfer_proof_sender,
+                quote_requests: quote_receiver,
+                encrypted_signature_requests: encrypted_signature_receiver,
+                cooperative_xmr_redeem_requests: cooperative_xmr_redeem_receiver,
+                cooperative_early_refund_requests: cooperative_early_refund_receiver,
+                execution_setup_requests: execution_setup_receiver,
+            })
+            .map_err(|_| anyhow!("Event loop is not running"))?;
+
+        Ok(EventLoopHandle {
+            alice_peer_id,
+            execution_setup_sender,
+            transfer_proof_receiver,
+            encrypted_signature_sender,
+            cooperative_xmr_redeem_sender,
+            cooperative_early_refund_sender,
+            quote_sender,
+        })
     }
 }
 
 #[derive(Debug)]
 pub struct EventLoopHandle {
+    /// The Alice we run the swap with.
+    alice_peer_id: PeerId,
+
     /// When a NewSwap object is sent into this channel, the EventLoop will:
     /// 1. Trigger the swap setup protocol with Alice to negotiate the swap parameters
     /// 2. Return the resulting State2 if successful
@@ -391,9 +788,21 @@ pub struct EventLoopHandle {
         (),
         Result<cooperative_xmr_redeem_after_punish::Response, OutboundFailure>,
     >,
+
+    /// When a () is sent into this channel, the EventLoop will:
+    /// 1. Ask Alice to refund our Bitcoin early instead of locking the Monero
+    /// 2. Return her response (Accepted or Rejected), if the network request is successful
+    /// 3. Return an OutboundFailure error if the network request fails
+    cooperative_early_refund_sender:
+        bmrng::RequestSender<(), Result<cooperative_early_refund::Response, OutboundFailure>>,
 }
 
 impl EventLoopHandle {
+    /// The Alice we run the swap with.
+    pub fn peer_id(&self) -> PeerId {
+        self.alice_peer_id
+    }
+
     fn create_retry_config(max_elapsed_time: Duration) -> backoff::ExponentialBackoff {
         backoff::ExponentialBackoffBuilder::new()
             .with_max_elapsed_time(max_elapsed_time.into())
@@ -500,6 +909,34 @@ impl EventLoopHandle {
         .context("Failed to request cooperative XMR redeem after retries")
     }
 
+    pub async fn request_cooperative_early_refund(
+        &mut self,
+    ) -> Result<cooperative_early_refund::Response> {
+        tracing::debug!("Requesting cooperative early refund");
+
+        let backoff = Self::create_retry_config(REQUEST_RESPONSE_PROTOCOL_TIMEOUT);
+
+        backoff::future::retry_notify(backoff, || async {
+            match self.cooperative_early_refund_sender.send_receive(()).await {
+                Ok(Ok(response)) => Ok(response),
+                Ok(Err(err)) => {
+                    Err(backoff::Error::transient(anyhow!(err).context("A network error occurred while requesting cooperative early refund")))
+                }
+                Err(_) => {
+                    unreachable!("We initiate the cooperative early refund channel without a timeout and store both the sender and receiver in the same struct, so this should never happen");
+                }
+            }
+        }, |err, wait_time: Duration| {
+            tracing::warn!(
+                error = ?err,
+                "Failed to request cooperative early refund. We will retry in {} seconds",
+                wait_time.as_secs()
+            )
+        })
+        .await
+        .context("Failed to request cooperative early refund after retries")
+    }
+
     pub async fn send_encrypted_signature(
         &mut self,
         tx_redeem_encsig: EncryptedSignature,
@@ -523,6 +960,7 @@ impl EventLoopHandle {
                 }
             }
         }, |err, wait_time: Duration| {
+            METRICS.record_encrypted_signature_retry();
             tracing::warn!(
                 error = ?err,
                 "Failed to send encrypted signature. We will retry in {} seconds",
@@ -2,6 +2,9 @@ use crate::cli::api::tauri_bindings::{
     ListSellersProgress, TauriBackgroundProgress, TauriBackgroundProgressHandle, TauriEmitter,
     TauriHandle,
 };
+use crate::cli::peers;
+use crate::cli::reputation::{self, Reputation};
+use crate::database::PeerSettings;
 use crate::libp2p_ext::MultiAddrExt;
 use crate::network::quote::BidQuote;
 use crate::network::rendezvous::XmrBtcNamespace;
@@ -34,6 +37,8 @@ fn build_identify_config(identity: identity::Keypair) -> identify::Config {
 }
 
 /// Returns a function that when called will return sorted list of sellers, with [Online](Status::Online) listed first.
+/// Online sellers are ranked by whether the user pinned them and by their [reputation](Reputation).
+/// Sellers the user banned are never contacted.
 ///
 /// First uses the rendezvous node to discover peers in the given namespace,
 /// then fetches a quote from each peer that was discovered. If fetching a quote
@@ -42,6 +47,8 @@ fn build_identify_config(identity: identity::Keypair) -> identify::Config {
 ///
 /// If a database is provided, it will be used to get the list of peers that
 /// have already been discovered previously and attempt to fetch a quote from them.
+/// Whether a seller was reachable is recorded in the database and the sellers are
+/// annotated with their reputation from our past swaps.
 pub async fn list_sellers_init(
     rendezvous_points: Vec<(PeerId, Multiaddr)>,
     namespace: XmrBtcNamespace,
@@ -95,7 +102,7 @@ pub async fn list_sellers_init(
                 .unwrap();
 
             // Get peers from the database, add them to the dial queue
-            let mut external_dial_queue = match db {
+            let mut external_dial_queue = match &db {
                 Some(db) => match db.get_all_peer_addresses().await {
                     Ok(peers) => VecDeque::from(peers),
                     Err(err) => {
@@ -106,6 +113,22 @@ pub async fn list_sellers_init(
                 None => VecDeque::new(),
             };
 
+            let reputations = match &db {
+                Some(db) => reputation::load(db.as_ref()).await.unwrap_or_else(|err| {
+                    tracing::warn!(%err, "Failed to load the reputation of makers");
+                    HashMap::new()
+                }),
+                None => HashMap::new(),
+            };
+
+            let peer_settings = match &db {
+                Some(db) => peers::load(db.as_ref()).await.unwrap_or_else(|err| {
+                    tracing::warn!(%err, "Failed to load the settings of peers");
+                    HashMap::new()
+                }),
+                None => HashMap::new(),
+            };
+
             // Get peers the user has manually passed in, add them to the dial queue
             for seller_addr in sellers {
                 if let Some((peer_id, multiaddr)) = seller_addr.split_peer_id() {
@@ -118,10 +141,27 @@ pub async fn list_sellers_init(
                 rendezvous_points,
                 namespace,
                 external_dial_queue,
+                reputations,
+                peer_settings,
                 tauri_handle,
             );
 
-            event_loop.run(sender).await
+            let sellers = event_loop.run(sender).await;
+
+            if let Some(db) = &db {
+                for seller in &sellers {
+                    let (peer_id, reachable) = match seller {
+                        SellerStatus::Online(quote) => (quote.peer_id, true),
+                        SellerStatus::Unreachable(seller) => (seller.peer_id, false),
+                    };
+
+                    if let Err(err) = db.insert_peer_reachability(peer_id, reachable).await {
+                        tracing::warn!(%peer_id, %err, "Failed to record whether the peer was reachable");
+                    }
+                }
+            }
+
+            sellers
         })
             as std::pin::Pin<
                 Box<dyn std::future::Future<Output = Vec<SellerStatus>> + Send + 'static>,
@@ -180,6 +220,14 @@ pub struct QuoteWithAddress {
     #[serde_as(as = "DisplayFromStr")]
     #[typeshare(serialized_as = "string")]
     pub version: Version,
+
+    /// How the seller behaved in our past swaps with it
+    #[serde(default)]
+    pub reputation: Reputation,
+
+    /// Whether the user pinned the seller and the label they gave it
+    #[serde(default)]
+    pub settings: PeerSettings,
 }
 
 #[typeshare]
@@ -561,6 +609,12 @@ struct EventLoop {
 
     /// Background progress handle for UI updates
     progress_handle: Option<TauriBackgroundProgressHandle<ListSellersProgress>>,
+
+    /// The reputation of the peers we already know
+    reputations: HashMap<PeerId, Reputation>,
+
+    /// The peers the user pinned, banned or labeled
+    peer_settings: HashMap<PeerId, PeerSettings>,
 }
 
 impl EventLoop {
@@ -569,6 +623,8 @@ impl EventLoop {
         rendezvous_points: Vec<(PeerId, Multiaddr)>,
         namespace: XmrBtcNamespace,
         dial_queue: VecDeque<(PeerId, Vec<Multiaddr>)>,
+        reputations: HashMap<PeerId, Reputation>,
+        peer_settings: HashMap<PeerId, PeerSettings>,
         tauri_handle: Option<TauriHandle>,
     ) -> Self {
         let progress_handle =
@@ -583,9 +639,19 @@ impl EventLoop {
             peer_states: Default::default(),
             to_request_quote: dial_queue,
             progress_handle: Some(progress_handle),
+            reputations,
+            peer_settings,
         }
     }
 
+    fn reputation(&self, peer_id: &PeerId) -> Reputation {
+        self.reputations.get(peer_id).copied().unwrap_or_default()
+    }
+
+    fn settings(&self, peer_id: &PeerId) -> PeerSettings {
+        self.peer_settings.get(peer_id).cloned().unwrap_or_default()
+    }
+
     fn is_rendezvous_point(&self, peer_id: &PeerId) -> bool {
         self.rendezvous_points
             .iter()
@@ -675,6 +741,8 @@ impl EventLoop {
                     multiaddr: reachable_addresses[0].clone(),
                     quote: *quote,
                     version: version.clone(),
+                    reputation: self.reputation(peer_id),
+                    settings: self.settings(peer_id),
                 })),
                 PeerState::Failed { peer_id, .. } => {
                     Some(SellerStatus::Unreachable(UnreachableSeller {
@@ -685,7 +753,7 @@ impl EventLoop {
             })
             .collect();
 
-        sellers.sort();
+        sort_sellers(&mut sellers);
         sellers
     }
 
@@ -739,6 +807,12 @@ impl EventLoop {
                         continue;
                     }
 
+                    // The user does not want us to contact this peer
+                    if self.settings(&peer_id).is_banned() {
+                        tracing::trace!(%peer_id, "Skipping banned peer");
+                        continue;
+                    }
+
                     // Initialize peer state
                     self.peer_states.insert(peer_id, PeerState::new(peer_id));
 
@@ -931,6 +1005,8 @@ impl EventLoop {
                         multiaddr: reachable_addresses[0].clone(),
                         quote: *quote,
                         version: version.clone(),
+                        reputation: self.reputation(peer_id),
+                        settings: self.settings(peer_id),
                     })),
                     PeerState::Failed {
                         peer_id,
@@ -949,7 +1025,7 @@ impl EventLoop {
 
             match all_quotes_fetched {
                 Ok(mut sellers) => {
-                    sellers.sort();
+                    sort_sellers(&mut sellers);
                     if let Some(ref progress_handle) = self.progress_handle {
                         progress_handle.finish();
                     }
@@ -964,6 +1040,22 @@ impl EventLoop {
 #[derive(Debug)]
 struct StillPending {}
 
+/// Sorts the [Online](SellerStatus::Online) sellers first, pinned ones and then those
+/// with the best reputation at the top. Sellers we know nothing about rank in the middle.
+fn sort_sellers(sellers: &mut [SellerStatus]) {
+    const UNKNOWN_SCORE: u8 = 50;
+
+    sellers.sort();
+    sellers.sort_by_key(|seller| match seller {
+        SellerStatus::Online(quote) => (
+            0,
+            !quote.settings.is_pinned(),
+            std::cmp::Reverse(quote.reputation.score.unwrap_or(UNKNOWN_SCORE)),
+        ),
+        SellerStatus::Unreachable(_) => (1, true, std::cmp::Reverse(0)),
+    });
+}
+
 impl From<rendezvous::client::Event> for OutEvent {
     fn from(event: rendezvous::client::Event) -> Self {
         OutEvent::Rendezvous(event)
@@ -991,6 +1083,7 @@ impl From<ping::Event> for OutEvent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::PeerStatus;
     use anyhow::anyhow;
 
     // Helper function to create a test multiaddr
@@ -1609,6 +1702,8 @@ mod tests {
                     max_quantity: Default::default(),
                 },
                 version: Version::parse("1.0.0").unwrap(), // Fixed: Use valid semver
+                reputation: Default::default(),
+                settings: Default::default(),
             }),
         ];
 
@@ -1619,4 +1714,50 @@ mod tests {
         assert!(matches!(list[1], SellerStatus::Unreachable(_)));
         assert!(matches!(list[2], SellerStatus::Unreachable(_)));
     }
+
+    #[test]
+    fn online_sellers_are_ranked_by_pin_and_reputation() {
+        let seller = |score: Option<u8>, status: PeerStatus| {
+            SellerStatus::Online(QuoteWithAddress {
+                multiaddr: "/ip4/127.0.0.1/tcp/5678".parse().unwrap(),
+                peer_id: PeerId::random(),
+                quote: BidQuote {
+                    price: Default::default(),
+                    min_quantity: Default::default(),
+                    max_quantity: Default::default(),
+                },
+                version: Version::parse("1.0.0").unwrap(),
+                reputation: Reputation {
+                    score,
+                    ..Default::default()
+                },
+                settings: PeerSettings {
+                    status,
+                    label: None,
+                },
+            })
+        };
+        let score = |seller: &SellerStatus| match seller {
+            SellerStatus::Online(quote) => quote.reputation.score,
+            SellerStatus::Unreachable(_) => None,
+        };
+
+        let mut list = vec![
+            SellerStatus::Unreachable(UnreachableSeller {
+                peer_id: PeerId::random(),
+            }),
+            seller(Some(20), PeerStatus::Known),
+            seller(None, PeerStatus::Known),
+            seller(Some(90), PeerStatus::Known),
+            seller(Some(10), PeerStatus::Pinned),
+        ];
+
+        sort_sellers(&mut list);
+
+        assert_eq!(
+            list.iter().map(score).collect::<Vec<_>>(),
+            vec![Some(10), Some(90), None, Some(20), None]
+        );
+        assert!(matches!(list[4], SellerStatus::Unreachable(_)));
+    }
 }
@@ -0,0 +1,213 @@
+//! Splitting one large buy across several makers.
+//!
+//! A single maker often cannot take a large buy because of its `max_quantity`,
+//! or only at a worse price than others. An order fills the buy from the
+//! makers the user pinned and then from the cheapest ones, and runs one swap
+//! per maker.
+
+use crate::bitcoin;
+use crate::cli::list_sellers::QuoteWithAddress;
+use std::collections::HashSet;
+
+/// The weight an additional P2WPKH output adds to the transaction which splits our
+/// funds into one output per swap.
+pub const SPLIT_OUTPUT_WEIGHT: ::bitcoin::Weight = ::bitcoin::Weight::from_wu(31 * 4);
+
+/// The part of an order which is swapped with one maker.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderPart {
+    pub seller: QuoteWithAddress,
+    /// The amount which is locked in the swap with this maker.
+    pub btc_amount: bitcoin::Amount,
+}
+
+/// Splits `budget` across the `sellers`, makers the user pinned first and then the
+/// cheapest ones.
+///
+/// Every part costs `reserve` on top of the amount it swaps, e.g. for the fee of its
+/// lock transaction. Makers whose minimum cannot be met with what is left are skipped
+/// and at most `max_parts` makers are used. Whatever cannot be allocated stays in the
+/// wallet.
+pub fn allocate(
+    mut sellers: Vec<QuoteWithAddress>,
+    budget: bitcoin::Amount,
+    reserve: bitcoin::Amount,
+    max_parts: usize,
+) -> Vec<OrderPart> {
+    // Pinned makers come first. Among equally priced makers, prefer the ones which can take more
+    sellers.sort_by(|a, b| {
+        b.settings
+            .is_pinned()
+            .cmp(&a.settings.is_pinned())
+            .then(a.quote.price.cmp(&b.quote.price))
+            .then(b.quote.max_quantity.cmp(&a.quote.max_quantity))
+    });
+
+    let mut parts = Vec::new();
+    let mut used_peers = HashSet::new();
+    let mut remaining = budget;
+
+    for seller in sellers {
+        if parts.len() >= max_parts {
+            break;
+        }
+
+        let quote = seller.quote;
+
+        if quote.max_quantity == bitcoin::Amount::ZERO || used_peers.contains(&seller.peer_id) {
+            continue;
+        }
+
+        let Some(available) = remaining.checked_sub(reserve) else {
+            break;
+        };
+
+        if available < quote.min_quantity || available == bitcoin::Amount::ZERO {
+            continue;
+        }
+
+        let btc_amount = available.min(quote.max_quantity);
+
+        remaining -= btc_amount + reserve;
+        used_peers.insert(seller.peer_id);
+        parts.push(OrderPart { seller, btc_amount });
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::PeerStatus;
+    use crate::network::quote::BidQuote;
+    use libp2p::PeerId;
+    use semver::Version;
+
+    fn seller(price: u64, min_quantity: u64, max_quantity: u64) -> QuoteWithAddress {
+        QuoteWithAddress {
+            multiaddr: "/ip4/127.0.0.1/tcp/9939".parse().unwrap(),
+            peer_id: PeerId::random(),
+            quote: BidQuote {
+                price: bitcoin::Amount::from_sat(price),
+                min_quantity: bitcoin::Amount::from_sat(min_quantity),
+                max_quantity: bitcoin::Amount::from_sat(max_quantity),
+            },
+            version: Version::new(2, 3, 1),
+            reputation: Default::default(),
+            settings: Default::default(),
+        }
+    }
+
+    fn amounts(parts: &[OrderPart]) -> Vec<(u64, u64)> {
+        parts
+            .iter()
+            .map(|part| (part.seller.quote.price.to_sat(), part.btc_amount.to_sat()))
+            .collect()
+    }
+
+    #[test]
+    fn fills_from_the_cheapest_maker_first() {
+        let sellers = vec![
+            seller(600, 0, 1_000),
+            seller(500, 0, 1_000),
+            seller(700, 0, 10_000),
+        ];
+
+        let parts = allocate(
+            sellers,
+            bitcoin::Amount::from_sat(2_530),
+            bitcoin::Amount::from_sat(10),
+            3,
+        );
+
+        assert_eq!(
+            amounts(&parts),
+            vec![(500, 1_000), (600, 1_000), (700, 500)]
+        );
+    }
+
+    #[test]
+    fn skips_makers_whose_minimum_cannot_be_met() {
+        let sellers = vec![
+            seller(500, 0, 1_000),
+            seller(600, 5_000, 10_000),
+            seller(700, 0, 10_000),
+        ];
+
+        let parts = allocate(
+            sellers,
+            bitcoin::Amount::from_sat(3_000),
+            bitcoin::Amount::ZERO,
+            3,
+        );
+
+        assert_eq!(amounts(&parts), vec![(500, 1_000), (700, 2_000)]);
+    }
+
+    #[test]
+    fn uses_at_most_max_parts_makers() {
+        let sellers = vec![
+            seller(500, 0, 1_000),
+            seller(600, 0, 1_000),
+            seller(700, 0, 1_000),
+        ];
+
+        let parts = allocate(
+            sellers,
+            bitcoin::Amount::from_sat(10_000),
+            bitcoin::Amount::ZERO,
+            2,
+        );
+
+        assert_eq!(amounts(&parts), vec![(500, 1_000), (600, 1_000)]);
+    }
+
+    #[test]
+    fn ignores_makers_without_liquidity_and_duplicate_peers() {
+        let cheap = seller(500, 0, 1_000);
+        let sellers = vec![
+            cheap.clone(),
+            cheap,
+            seller(400, 0, 0),
+            seller(600, 0, 1_000),
+        ];
+
+        let parts = allocate(
+            sellers,
+            bitcoin::Amount::from_sat(10_000),
+            bitcoin::Amount::ZERO,
+            3,
+        );
+
+        assert_eq!(amounts(&parts), vec![(500, 1_000), (600, 1_000)]);
+    }
+
+    #[test]
+    fn pinned_makers_are_preferred_over_cheaper_ones() {
+        let mut pinned = seller(700, 0, 1_000);
+        pinned.settings.status = PeerStatus::Pinned;
+        let sellers = vec![seller(500, 0, 1_000), pinned];
+
+        let parts = allocate(
+            sellers,
+            bitcoin::Amount::from_sat(1_500),
+            bitcoin::Amount::ZERO,
+            3,
+        );
+
+        assert_eq!(amounts(&parts), vec![(700, 1_000), (500, 500)]);
+    }
+
+    #[test]
+    fn nothing_is_allocated_if_the_budget_does_not_cover_the_reserve() {
+        let parts = allocate(
+            vec![seller(500, 0, 1_000)],
+            bitcoin::Amount::from_sat(10),
+            bitcoin::Amount::from_sat(10),
+            3,
+        );
+
+        assert!(parts.is_empty());
+    }
+}
@@ -0,0 +1,15 @@
+//! Makers the user pinned, banned or labeled.
+//!
+//! Banned makers are never contacted when listing sellers. Pinned makers are
+//! ranked before all others and preferred when splitting an order.
+
+use crate::database::PeerSettings;
+use crate::protocol::Database;
+use anyhow::Result;
+use libp2p::PeerId;
+use std::collections::HashMap;
+
+/// The settings of every peer the user pinned, banned or labeled.
+pub async fn load(db: &(dyn Database + Send + Sync)) -> Result<HashMap<PeerId, PeerSettings>> {
+    Ok(db.get_all_peer_settings().await?.into_iter().collect())
+}
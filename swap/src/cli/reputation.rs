@@ -0,0 +1,310 @@
+//! How makers behaved towards us in the past.
+//!
+//! The outcome of every swap is derived from the states we stored for it. How
+//! often a maker was reachable is recorded whenever we list sellers.
+
+use crate::protocol::bob::BobState;
+use crate::protocol::timeline::{duration_ms, first_entered_at};
+use crate::protocol::{Database, PeerReachability, State, StateTransition};
+use anyhow::Result;
+use libp2p::PeerId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use typeshare::typeshare;
+
+/// Makers which failed to lock the Monero in at least this many of our swaps, and
+/// in more swaps than they completed, are not offered for new swaps anymore.
+const BLOCKLIST_MIN_XMR_NOT_LOCKED: u32 = 2;
+
+#[typeshare]
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Ord, PartialOrd,
+)]
+pub struct Reputation {
+    /// Swaps in which we redeemed the Monero.
+    pub swaps_redeemed: u32,
+    /// Swaps we had to refund because the maker never locked the Monero.
+    pub refunds_xmr_not_locked: u32,
+    /// Swaps the maker refunded early instead of locking the Monero.
+    pub early_refunds: u32,
+    /// The average time between our Bitcoin lock and the maker's Monero lock.
+    #[typeshare(serialized_as = "Option<number>")]
+    pub avg_time_to_xmr_lock_secs: Option<u64>,
+    /// How often we could fetch a quote from the maker.
+    pub times_reachable: u32,
+    /// How often we could not fetch a quote from the maker.
+    pub times_unreachable: u32,
+    /// From 0 (worst) to 100 (best), `None` if we know nothing about the maker.
+    pub score: Option<u8>,
+}
+
+impl Reputation {
+    /// Whether the maker failed us often enough to not swap with it anymore.
+    pub fn is_blocklisted(&self) -> bool {
+        self.refunds_xmr_not_locked >= BLOCKLIST_MIN_XMR_NOT_LOCKED
+            && self.refunds_xmr_not_locked > self.swaps_redeemed
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapOutcome {
+    Redeemed,
+    RefundedXmrNotLocked,
+    EarlyRefunded,
+}
+
+/// The outcome of a finished swap, `None` if the swap is ongoing or its outcome
+/// says nothing about the maker (e.g. we aborted it, asked for the early refund
+/// ourselves or got punished).
+pub fn outcome(transitions: &[StateTransition]) -> Option<SwapOutcome> {
+    let xmr_locked = xmr_locked_at(transitions).is_some();
+    let early_refund_requested = transitions.iter().any(|transition| {
+        matches!(
+            transition.state,
+            State::Bob(BobState::BtcEarlyRefundRequested { .. })
+        )
+    });
+
+    if early_refund_requested {
+        return None;
+    }
+
+    match &transitions.last()?.state {
+        State::Bob(BobState::XmrRedeemed { .. }) => Some(SwapOutcome::Redeemed),
+        State::Bob(BobState::BtcEarlyRefunded(..)) => Some(SwapOutcome::EarlyRefunded),
+        State::Bob(BobState::BtcRefunded(..)) if !xmr_locked => {
+            Some(SwapOutcome::RefundedXmrNotLocked)
+        }
+        _ => None,
+    }
+}
+
+/// How long it took the maker to lock the Monero after we locked the Bitcoin.
+pub fn time_to_xmr_lock_ms(transitions: &[StateTransition]) -> Option<u64> {
+    let btc_locked_at = first_entered_at(transitions, |state| {
+        matches!(state, State::Bob(BobState::BtcLocked { .. }))
+    })?;
+
+    Some(duration_ms(btc_locked_at, xmr_locked_at(transitions)?))
+}
+
+fn xmr_locked_at(transitions: &[StateTransition]) -> Option<time::OffsetDateTime> {
+    first_entered_at(transitions, |state| {
+        matches!(state, State::Bob(BobState::XmrLockProofReceived { .. }))
+    })
+}
+
+/// What we know about a maker, collected swap by swap.
+#[derive(Debug, Default)]
+struct History {
+    outcomes: Vec<SwapOutcome>,
+    times_to_xmr_lock_ms: Vec<u64>,
+    reachability: PeerReachability,
+}
+
+impl History {
+    fn add_swap(&mut self, transitions: &[StateTransition]) {
+        self.outcomes.extend(outcome(transitions));
+        self.times_to_xmr_lock_ms
+            .extend(time_to_xmr_lock_ms(transitions));
+    }
+
+    fn reputation(&self) -> Reputation {
+        let count = |outcome| {
+            let count = self.outcomes.iter().filter(|o| **o == outcome).count();
+            u32::try_from(count).unwrap_or(u32::MAX)
+        };
+
+        let avg_time_to_xmr_lock_secs = match self.times_to_xmr_lock_ms.len() {
+            0 => None,
+            len => Some(self.times_to_xmr_lock_ms.iter().sum::<u64>() / len as u64 / 1000),
+        };
+
+        let mut reputation = Reputation {
+            swaps_redeemed: count(SwapOutcome::Redeemed),
+            refunds_xmr_not_locked: count(SwapOutcome::RefundedXmrNotLocked),
+            early_refunds: count(SwapOutcome::EarlyRefunded),
+            avg_time_to_xmr_lock_secs,
+            times_reachable: self.reachability.reachable,
+            times_unreachable: self.reachability.unreachable,
+            score: None,
+        };
+        reputation.score = score(&reputation);
+
+        reputation
+    }
+}
+
+/// Scores a maker from 0 to 100.
+///
+/// How our swaps went weighs three times as much as how often the maker was
+/// reachable. An early refund counts as half a failed swap because our Bitcoin is
+/// not locked until the timelock expires. Both ratios start out at one half such
+/// that a single swap or dial does not decide the score.
+pub fn score(reputation: &Reputation) -> Option<u8> {
+    let ratio = |good: f64, bad: f64| (good + 1.0) / (good + bad + 2.0);
+
+    let swaps =
+        reputation.swaps_redeemed + reputation.refunds_xmr_not_locked + reputation.early_refunds;
+    let dials = reputation.times_reachable + reputation.times_unreachable;
+
+    let swap_ratio = ratio(
+        f64::from(reputation.swaps_redeemed),
+        f64::from(reputation.refunds_xmr_not_locked) + f64::from(reputation.early_refunds) / 2.0,
+    );
+    let reachability_ratio = ratio(
+        f64::from(reputation.times_reachable),
+        f64::from(reputation.times_unreachable),
+    );
+
+    let score = match (swaps, dials) {
+        (0, 0) => return None,
+        (0, _) => reachability_ratio,
+        (_, 0) => swap_ratio,
+        _ => (3.0 * swap_ratio + reachability_ratio) / 4.0,
+    };
+
+    Some((score * 100.0).round() as u8)
+}
+
+/// The reputation of every maker we swapped with or tried to fetch a quote from.
+pub async fn load(db: &(dyn Database + Send + Sync)) -> Result<HashMap<PeerId, Reputation>> {
+    let mut histories: HashMap<PeerId, History> = HashMap::new();
+
+    for (swap_id, transitions) in db.get_all_state_transitions().await? {
+        if !matches!(
+            transitions.last().map(|transition| &transition.state),
+            Some(State::Bob(_))
+        ) {
+            continue;
+        }
+
+        let peer_id = match db.get_peer_id(swap_id).await {
+            Ok(peer_id) => peer_id,
+            Err(error) => {
+                tracing::warn!(%swap_id, ?error, "Failed to get the maker of the swap, ignoring it for reputation");
+                continue;
+            }
+        };
+
+        histories.entry(peer_id).or_default().add_swap(&transitions);
+    }
+
+    for (peer_id, reachability) in db.get_all_peer_reachability().await? {
+        histories.entry(peer_id).or_default().reachability = reachability;
+    }
+
+    Ok(histories
+        .into_iter()
+        .map(|(peer_id, history)| (peer_id, history.reputation()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::monero;
+    use time::OffsetDateTime;
+
+    fn transition(state: BobState, unix_secs: i64) -> StateTransition {
+        StateTransition {
+            state: state.into(),
+            entered_at: OffsetDateTime::from_unix_timestamp(unix_secs).unwrap(),
+        }
+    }
+
+    fn reputation(
+        redeemed: u32,
+        xmr_not_locked: u32,
+        reachable: u32,
+        unreachable: u32,
+    ) -> Reputation {
+        Reputation {
+            swaps_redeemed: redeemed,
+            refunds_xmr_not_locked: xmr_not_locked,
+            times_reachable: reachable,
+            times_unreachable: unreachable,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_outcomes_caused_by_the_maker_are_counted() {
+        let redeemed = BobState::XmrRedeemed {
+            tx_lock_id: "0000000000000000000000000000000000000000000000000000000000000000"
+                .parse()
+                .unwrap(),
+        };
+
+        assert_eq!(
+            outcome(&[transition(redeemed, 0)]),
+            Some(SwapOutcome::Redeemed)
+        );
+        assert_eq!(outcome(&[transition(BobState::SafelyAborted, 0)]), None);
+        assert_eq!(outcome(&[]), None);
+    }
+
+    #[tokio::test]
+    async fn early_refunds_we_asked_for_are_not_counted() {
+        let (_, state3) = crate::protocol::fixtures::btc_locked().await;
+        let restore_blockheight = monero::BlockHeight { height: 0 };
+        let early_refunded = BobState::BtcEarlyRefunded(state3.cancel(restore_blockheight));
+
+        assert_eq!(
+            outcome(&[
+                transition(
+                    BobState::BtcLocked {
+                        state3: state3.clone(),
+                        monero_wallet_restore_blockheight: restore_blockheight,
+                    },
+                    0
+                ),
+                transition(early_refunded.clone(), 1),
+            ]),
+            Some(SwapOutcome::EarlyRefunded)
+        );
+        assert_eq!(
+            outcome(&[
+                transition(
+                    BobState::BtcEarlyRefundRequested {
+                        state3,
+                        monero_wallet_restore_blockheight: restore_blockheight,
+                    },
+                    0
+                ),
+                transition(early_refunded, 1),
+            ]),
+            None
+        );
+    }
+
+    #[test]
+    fn unknown_makers_have_no_score() {
+        assert_eq!(score(&Reputation::default()), None);
+    }
+
+    #[test]
+    fn swaps_weigh_more_than_reachability() {
+        assert_eq!(score(&reputation(0, 0, 10, 0)), Some(92));
+        assert_eq!(score(&reputation(8, 0, 0, 0)), Some(90));
+        assert_eq!(score(&reputation(0, 2, 10, 0)), Some(42));
+        assert_eq!(score(&reputation(4, 0, 0, 10)), Some(65));
+    }
+
+    #[test]
+    fn early_refunds_count_half() {
+        let early_refunds = Reputation {
+            early_refunds: 2,
+            ..Default::default()
+        };
+
+        assert_eq!(score(&early_refunds), score(&reputation(0, 1, 0, 0)));
+    }
+
+    #[test]
+    fn makers_which_repeatedly_did_not_lock_the_monero_are_blocklisted() {
+        assert!(!reputation(0, 1, 0, 0).is_blocklisted());
+        assert!(reputation(0, 2, 0, 0).is_blocklisted());
+        assert!(!reputation(3, 2, 0, 0).is_blocklisted());
+    }
+}
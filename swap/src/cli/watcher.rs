@@ -114,14 +114,12 @@ impl Watcher {
 
                 // If the swap is already running, we can skip the refund
                 // The refund will be handled by the state machine
-                if let Some(current_swap_id) = self.swap_lock.get_current_swap_id().await {
-                    if current_swap_id == swap_id {
-                        continue;
-                    }
+                if self.swap_lock.is_running(swap_id).await {
+                    continue;
                 }
 
                 if let Err(e) = self.swap_lock.acquire_swap_lock(swap_id).await {
-                    tracing::error!(%e, %swap_id, "Watcher failed to refund a swap in the background because another swap is already running");
+                    tracing::error!(%e, %swap_id, "Watcher failed to refund a swap in the background because it was started in the meantime");
                     continue;
                 }
 
@@ -145,7 +143,7 @@ impl Watcher {
                 background_process_handle.finish();
 
                 // We have to release the swap lock when we are done
-                self.swap_lock.release_swap_lock().await?;
+                self.swap_lock.release_swap_lock(swap_id).await?;
             }
         }
 
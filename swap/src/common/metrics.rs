@@ -0,0 +1,381 @@
+//! Prometheus metrics for the asb and the cli.
+//!
+//! Metrics are always recorded into a process wide registry, which is cheap. They are only
+//! exported if the operator opts in by starting the `/metrics` endpoint with [`serve`].
+use crate::protocol::{Database, State};
+use crate::{bitcoin, monero};
+use anyhow::{Context, Result};
+use axum::http::header::CONTENT_TYPE;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use once_cell::sync::Lazy;
+use prometheus_client::encoding::EncodeLabelSet;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::family::Family;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::metrics::histogram::{exponential_buckets, Histogram};
+use prometheus_client::registry::Registry;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// How often we refresh the metrics which have to be polled (balances, sync state, database)
+const COLLECT_INTERVAL: Duration = Duration::from_secs(15);
+
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// The process wide metrics.
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::new);
+
+/// Set once the `/metrics` endpoint is running, such that we only poll for metrics if somebody
+/// can actually read them.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct QuoteLabels {
+    /// Either `cache` or `computed`
+    pub source: &'static str,
+    /// Either `success` or `failure`
+    pub result: &'static str,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct SwapSetupLabels {
+    pub outcome: &'static str,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct StateLabels {
+    pub state: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct MessageLabels {
+    pub message: &'static str,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+pub struct RendezvousLabels {
+    pub rendezvous_node: String,
+}
+
+pub struct Metrics {
+    registry: Registry,
+    /// Quotes served to peers
+    pub quotes: Family<QuoteLabels, Counter>,
+    /// Time it took to serve a quote, including cache hits
+    pub quote_latency: Histogram,
+    /// Swap setups we took part in, by outcome
+    pub swap_setups: Family<SwapSetupLabels, Counter>,
+    /// Swaps in our database, by state
+    pub swaps: Family<StateLabels, Gauge>,
+    /// Retries while sending a message to our counterparty (or receiving it again)
+    pub message_retries: Family<MessageLabels, Counter>,
+    /// How often we switched to another Electrum server because a request failed
+    pub electrum_failovers: Counter,
+    /// How many blocks the Monero wallet is behind the daemon
+    pub monero_sync_lag: Gauge,
+    /// Whether we are currently registered at a rendezvous node (1) or not (0)
+    pub rendezvous_registered: Family<RendezvousLabels, Gauge>,
+    /// The states we have exported a swap count for, such that we can reset them to zero
+    known_states: Mutex<HashSet<String>>,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let mut registry = Registry::with_prefix("swap");
+
+        let quotes = Family::<QuoteLabels, Counter>::default();
+        registry.register("quotes", "Quotes served to peers", quotes.clone());
+
+        let quote_latency = Histogram::new(exponential_buckets(0.001, 2.0, 14));
+        registry.register(
+            "quote_latency_seconds",
+            "Time it took to serve a quote",
+            quote_latency.clone(),
+        );
+
+        let swap_setups = Family::<SwapSetupLabels, Counter>::default();
+        registry.register("swap_setups", "Swap setups by outcome", swap_setups.clone());
+
+        let swaps = Family::<StateLabels, Gauge>::default();
+        registry.register("swaps", "Swaps in the database by state", swaps.clone());
+
+        let message_retries = Family::<MessageLabels, Counter>::default();
+        registry.register(
+            "message_retries",
+            "Retries of protocol messages exchanged with the counterparty",
+            message_retries.clone(),
+        );
+
+        let electrum_failovers = Counter::default();
+        registry.register(
+            "electrum_failovers",
+            "Switches to another Electrum server because a request failed",
+            electrum_failovers.clone(),
+        );
+
+        let monero_sync_lag = Gauge::default();
+        registry.register(
+            "monero_sync_lag_blocks",
+            "Number of blocks the Monero wallet is behind the daemon",
+            monero_sync_lag.clone(),
+        );
+
+        let rendezvous_registered = Family::<RendezvousLabels, Gauge>::default();
+        registry.register(
+            "rendezvous_registered",
+            "Whether we are registered at the rendezvous node",
+            rendezvous_registered.clone(),
+        );
+
+        Self {
+            registry,
+            quotes,
+            quote_latency,
+            swap_setups,
+            swaps,
+            message_retries,
+            electrum_failovers,
+            monero_sync_lag,
+            rendezvous_registered,
+            known_states: Default::default(),
+        }
+    }
+
+    pub fn record_quote(&self, cached: bool, success: bool, latency: Duration) {
+        let labels = QuoteLabels {
+            source: if cached { "cache" } else { "computed" },
+            result: if success { "success" } else { "failure" },
+        };
+
+        self.quotes.get_or_create(&labels).inc();
+        self.quote_latency.observe(latency.as_secs_f64());
+    }
+
+    pub fn record_swap_setup(&self, outcome: &'static str) {
+        self.swap_setups
+            .get_or_create(&SwapSetupLabels { outcome })
+            .inc();
+    }
+
+    pub fn record_transfer_proof_retry(&self) {
+        self.record_message_retry("transfer_proof");
+    }
+
+    pub fn record_encrypted_signature_retry(&self) {
+        self.record_message_retry("encrypted_signature");
+    }
+
+    pub fn set_rendezvous_registered(&self, rendezvous_node: impl ToString, registered: bool) {
+        self.rendezvous_registered
+            .get_or_create(&RendezvousLabels {
+                rendezvous_node: rendezvous_node.to_string(),
+            })
+            .set(registered as i64);
+    }
+
+    /// Replaces the per-state swap counts with the given ones.
+    pub fn set_swaps_by_state(&self, counts: HashMap<String, i64>) {
+        let mut known_states = self
+            .known_states
+            .lock()
+            .expect("known states mutex not to be poisoned");
+
+        for state in known_states.iter() {
+            if !counts.contains_key(state) {
+                self.swaps
+                    .get_or_create(&StateLabels {
+                        state: state.clone(),
+                    })
+                    .set(0);
+            }
+        }
+
+        for (state, count) in counts {
+            self.swaps
+                .get_or_create(&StateLabels {
+                    state: state.clone(),
+                })
+                .set(count);
+            known_states.insert(state);
+        }
+    }
+
+    /// Brings the failover counter up to date with the total reported by the balancer.
+    pub fn set_electrum_failovers(&self, total: u64) {
+        self.electrum_failovers
+            .inc_by(total.saturating_sub(self.electrum_failovers.get()));
+    }
+
+    /// Encodes all metrics in the OpenMetrics text format.
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = String::new();
+        prometheus_client::encoding::text::encode(&mut buffer, &self.registry)
+            .context("Failed to encode metrics")?;
+
+        Ok(buffer)
+    }
+
+    fn record_message_retry(&self, message: &'static str) {
+        self.message_retries
+            .get_or_create(&MessageLabels { message })
+            .inc();
+    }
+}
+
+/// Returns whether the `/metrics` endpoint was started in this process.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Starts serving the metrics on `GET /metrics` at the given address.
+pub async fn serve(listen: SocketAddr) -> Result<JoinHandle<()>> {
+    let app = Router::new().route("/metrics", get(handle));
+
+    let listener = tokio::net::TcpListener::bind(listen)
+        .await
+        .with_context(|| format!("Failed to bind metrics endpoint to {}", listen))?;
+
+    ENABLED.store(true, Ordering::Relaxed);
+
+    tracing::info!(address = %listen, "Serving Prometheus metrics on /metrics");
+
+    Ok(tokio::spawn(async move {
+        if let Err(error) = axum::serve(listener, app).await {
+            tracing::error!("Metrics endpoint stopped: {:#}", error);
+        }
+    }))
+}
+
+async fn handle() -> impl IntoResponse {
+    match METRICS.encode() {
+        Ok(body) => (
+            StatusCode::OK,
+            [(CONTENT_TYPE, OPENMETRICS_CONTENT_TYPE)],
+            body,
+        )
+            .into_response(),
+        Err(error) => {
+            tracing::error!("{:#}", error);
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Periodically refreshes the metrics which cannot be recorded where they happen.
+///
+/// Any of the sources may be omitted if it is not available in this process.
+pub async fn collect(
+    bitcoin_wallet: Option<Arc<bitcoin::Wallet>>,
+    monero_wallets: Option<Arc<monero::Wallets>>,
+    db: Option<Arc<dyn Database + Send + Sync>>,
+) {
+    let electrum = match &bitcoin_wallet {
+        Some(wallet) => Some(wallet.electrum_balancer().await),
+        None => None,
+    };
+
+    let mut interval = tokio::time::interval(COLLECT_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Some(electrum) = &electrum {
+            METRICS.set_electrum_failovers(electrum.failover_count());
+        }
+
+        if let Some(monero_wallets) = &monero_wallets {
+            let progress = monero_wallets.sync_progress().await;
+
+            // The target is zero if we cannot reach the daemon, in which case we don't know the lag
+            if progress.target_block > 0 {
+                let lag = progress.target_block.saturating_sub(progress.current_block);
+                METRICS.monero_sync_lag.set(lag as i64);
+            }
+        }
+
+        if let Some(db) = &db {
+            match db.all().await {
+                Ok(swaps) => METRICS.set_swaps_by_state(count_alice_states(swaps)),
+                Err(error) => tracing::debug!("Failed to collect swap metrics: {:#}", error),
+            }
+        }
+    }
+}
+
+fn count_alice_states(swaps: Vec<(uuid::Uuid, State)>) -> HashMap<String, i64> {
+    let mut counts = HashMap::new();
+
+    for (_, state) in swaps {
+        if let State::Alice(state) = state {
+            *counts.entry(state.to_string()).or_default() += 1;
+        }
+    }
+
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::alice::AliceState;
+
+    #[test]
+    fn encodes_recorded_metrics() {
+        let metrics = Metrics::new();
+
+        metrics.record_quote(false, true, Duration::from_millis(5));
+        metrics.record_swap_setup("amount_below_minimum");
+        metrics.record_transfer_proof_retry();
+        metrics.set_electrum_failovers(3);
+        metrics.set_electrum_failovers(5);
+        metrics.set_rendezvous_registered("12D3KooWExample", true);
+
+        let encoded = metrics.encode().unwrap();
+
+        assert!(encoded.contains(r#"swap_quotes_total{source="computed",result="success"} 1"#));
+        assert!(encoded.contains(r#"swap_swap_setups_total{outcome="amount_below_minimum"} 1"#));
+        assert!(encoded.contains(r#"swap_message_retries_total{message="transfer_proof"} 1"#));
+        assert!(encoded.contains("swap_electrum_failovers_total 5"));
+        assert!(
+            encoded.contains(r#"swap_rendezvous_registered{rendezvous_node="12D3KooWExample"} 1"#)
+        );
+    }
+
+    #[test]
+    fn counts_only_alice_states() {
+        let swaps = vec![
+            (uuid::Uuid::new_v4(), State::Alice(AliceState::BtcRedeemed)),
+            (uuid::Uuid::new_v4(), State::Alice(AliceState::BtcRedeemed)),
+            (
+                uuid::Uuid::new_v4(),
+                State::Alice(AliceState::SafelyAborted),
+            ),
+        ];
+
+        let counts = count_alice_states(swaps);
+
+        assert_eq!(counts.get("btc is redeemed"), Some(&2));
+        assert_eq!(counts.get("safely aborted"), Some(&1));
+        assert_eq!(counts.len(), 2);
+    }
+
+    #[test]
+    fn resets_states_without_swaps_to_zero() {
+        let metrics = Metrics::new();
+
+        metrics.set_swaps_by_state(HashMap::from([("started".to_string(), 2)]));
+        metrics.set_swaps_by_state(HashMap::from([("btc is redeemed".to_string(), 1)]));
+
+        let encoded = metrics.encode().unwrap();
+
+        assert!(encoded.contains(r#"swap_swaps{state="started"} 0"#));
+        assert!(encoded.contains(r#"swap_swaps{state="btc is redeemed"} 1"#));
+    }
+}
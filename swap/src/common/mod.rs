@@ -1,12 +1,18 @@
+pub mod metrics;
 pub mod tor;
 pub mod tracing_util;
 
-use anyhow::anyhow;
+use anyhow::{anyhow, bail, Context};
+use serde::Deserialize;
+use std::str::FromStr;
 use std::{collections::HashMap, future::Future, path::PathBuf, time::Duration};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
 use tokio::{
     fs::{read_dir, File},
     io::{AsyncBufReadExt, BufReader},
 };
+use tracing::Level;
 use uuid::Uuid;
 
 const LATEST_RELEASE_URL: &str = "https://github.com/UnstoppableSwap/core/releases/latest";
@@ -112,51 +118,158 @@ macro_rules! regex_find_placeholders {
     }};
 }
 
+/// Which log messages [`get_logs`] returns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LogFilter {
+    /// Only messages concerning this swap.
+    pub swap_id: Option<Uuid>,
+    /// Only messages logged at or after this time.
+    pub since: Option<OffsetDateTime>,
+    /// Only messages logged at or before this time.
+    pub until: Option<OffsetDateTime>,
+    /// Only messages of this level or more severe.
+    pub level: Option<Level>,
+}
+
+impl LogFilter {
+    /// Parse the bounds given as RFC 3339 timestamps and the level given by its name, e.g. `warn`.
+    pub fn parse(
+        swap_id: Option<Uuid>,
+        since: Option<&str>,
+        until: Option<&str>,
+        level: Option<&str>,
+    ) -> anyhow::Result<Self> {
+        let parse_time = |timestamp: &str| {
+            OffsetDateTime::parse(timestamp, &Rfc3339).with_context(|| {
+                format!(
+                    "Invalid time `{}`, expected e.g. 2025-06-25T12:00:00Z",
+                    timestamp
+                )
+            })
+        };
+
+        let filter = Self {
+            swap_id,
+            since: since.map(parse_time).transpose()?,
+            until: until.map(parse_time).transpose()?,
+            level: level
+                .map(|level| {
+                    Level::from_str(level).map_err(|_| {
+                        anyhow!(
+                            "Invalid log level `{}`, expected one of error, warn, info, debug or trace",
+                            level
+                        )
+                    })
+                })
+                .transpose()?,
+        };
+
+        if let (Some(since), Some(until)) = (filter.since, filter.until) {
+            if since > until {
+                bail!(
+                    "The start of the time range ({}) is after its end ({})",
+                    since,
+                    until
+                );
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// Whether the log line passes the filter.
+    ///
+    /// Lines which are not JSON log messages only pass if we do not filter by time or level.
+    pub fn matches(&self, line: &str) -> bool {
+        if let Some(swap_id) = self.swap_id {
+            // The swap id is a field of the swap's span, and therefore part of every message
+            // logged within it
+            if !line.contains(&swap_id.to_string()) {
+                return false;
+            }
+        }
+
+        if self.since.is_none() && self.until.is_none() && self.level.is_none() {
+            return true;
+        }
+
+        let Ok(message) = serde_json::from_str::<LogLine>(line) else {
+            return false;
+        };
+
+        let in_range = |timestamp: OffsetDateTime| {
+            self.since.is_none_or(|since| since <= timestamp)
+                && self.until.is_none_or(|until| timestamp <= until)
+        };
+        let time_matches = (self.since.is_none() && self.until.is_none())
+            || message
+                .timestamp
+                .and_then(|timestamp| OffsetDateTime::parse(&timestamp, &Rfc3339).ok())
+                .is_some_and(in_range);
+
+        // More verbose levels compare as greater
+        let level_matches = self.level.is_none_or(|max_level| {
+            message
+                .level
+                .and_then(|level| Level::from_str(&level).ok())
+                .is_some_and(|level| level <= max_level)
+        });
+
+        time_matches && level_matches
+    }
+}
+
+/// The parts of a JSON log message we filter on.
+#[derive(Deserialize)]
+struct LogLine {
+    timestamp: Option<String>,
+    level: Option<String>,
+}
+
 /// Print the logs from the specified logs or from the default location
 /// to the specified path or the terminal.
 ///
-/// If specified, filter by swap id or redact addresses.
+/// Only messages which pass the filter are returned, optionally with addresses redacted. Log files
+/// are read in the order of their names, which for rotated files is the order they were written in.
 pub async fn get_logs(
     logs_dir: PathBuf,
-    swap_id: Option<Uuid>,
+    filter: LogFilter,
     redact_addresses: bool,
 ) -> anyhow::Result<Vec<String>> {
     tracing::debug!("reading logfiles from {}", logs_dir.display());
 
-    // get all files in the directory
-    let mut log_files = read_dir(&logs_dir).await?;
+    // get all .log files in the directory
+    let mut entries = read_dir(&logs_dir).await?;
+    let mut log_files = Vec::new();
 
-    let mut log_messages = Vec::new();
-    // when we redact we need to store the placeholder
-    let mut placeholders = HashMap::new();
-
-    // print all lines from every log file. TODO: sort files by date?
-    while let Some(entry) = log_files.next_entry().await? {
-        // get the file path
+    while let Some(entry) = entries.next_entry().await? {
         let file_path = entry.path();
 
-        // filter for .log files
-        let file_name = file_path
+        let is_log_file = file_path
             .file_name()
             .and_then(|name| name.to_str())
-            .unwrap_or("");
+            .is_some_and(|name| name.ends_with(".log"));
 
-        if !file_name.ends_with(".log") {
-            continue;
+        if is_log_file {
+            log_files.push(file_path);
         }
+    }
+
+    log_files.sort();
 
+    let mut log_messages = Vec::new();
+    // when we redact we need to store the placeholder
+    let mut placeholders = HashMap::new();
+
+    for file_path in log_files {
         // use BufReader to stay easy on memory and then read line by line
         let buf_reader = BufReader::new(File::open(&file_path).await?);
         let mut lines = buf_reader.lines();
 
         // print each line, redacted if the flag is set
         while let Some(line) = lines.next_line().await? {
-            // if we should filter by swap id, check if the line contains it
-            if let Some(swap_id) = swap_id {
-                // we only want lines which contain the swap id
-                if !line.contains(&swap_id.to_string()) {
-                    continue;
-                }
+            if !filter.matches(&line) {
+                continue;
             }
 
             // redact if necessary
@@ -238,3 +351,59 @@ pub fn redact_with(input: &str, replacements: &mut HashMap<String, String>) -> S
 
     redacted
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SWAP_ID: &str = "a9165a1e-d26d-4b56-bf6d-ca9658825c44";
+
+    fn line(timestamp: &str, level: &str) -> String {
+        format!(
+            r#"{{"timestamp":"{}","level":"{}","fields":{{"message":"Advancing state"}},"span":{{"swap_id":"{}","name":"swap"}}}}"#,
+            timestamp, level, SWAP_ID
+        )
+    }
+
+    #[test]
+    fn filters_by_time_range_and_level() {
+        let filter = LogFilter::parse(
+            None,
+            Some("2025-06-25T12:00:00Z"),
+            Some("2025-06-25T13:00:00Z"),
+            Some("info"),
+        )
+        .unwrap();
+
+        assert!(filter.matches(&line("2025-06-25T12:30:00.123456Z", "INFO")));
+        assert!(filter.matches(&line("2025-06-25T12:30:00Z", "ERROR")));
+        assert!(!filter.matches(&line("2025-06-25T12:30:00Z", "DEBUG")));
+        assert!(!filter.matches(&line("2025-06-25T11:59:59Z", "INFO")));
+        assert!(!filter.matches(&line("2025-06-25T13:00:01Z", "INFO")));
+        assert!(!filter.matches("not a json log message"));
+    }
+
+    #[test]
+    fn filters_by_swap_id() {
+        let filter = LogFilter {
+            swap_id: Some(SWAP_ID.parse().unwrap()),
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&line("2025-06-25T12:30:00Z", "INFO")));
+        assert!(!filter.matches(r#"{"level":"INFO","fields":{"message":"Resuming swap"}}"#));
+    }
+
+    #[test]
+    fn rejects_invalid_bounds() {
+        assert!(LogFilter::parse(None, Some("yesterday"), None, None).is_err());
+        assert!(LogFilter::parse(None, None, None, Some("loud")).is_err());
+        assert!(LogFilter::parse(
+            None,
+            Some("2025-06-25T13:00:00Z"),
+            Some("2025-06-25T12:00:00Z"),
+            None
+        )
+        .is_err());
+    }
+}
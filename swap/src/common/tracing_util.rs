@@ -94,6 +94,9 @@ pub fn init(
         .with_file(true)
         .with_line_number(true)
         .json()
+        // The swap_id, peer_id and state fields of the swap span correlate the messages of a swap
+        .with_current_span(true)
+        .with_span_list(true)
         .with_filter(env_filter_with_info_crates(
             level_filter,
             OUR_CRATES.clone(),
@@ -111,6 +114,8 @@ pub fn init(
         .with_file(true)
         .with_line_number(true)
         .json()
+        .with_current_span(true)
+        .with_span_list(true)
         .with_filter(env_filter_with_all_crates(
             LevelFilter::TRACE,
             OUR_CRATES.clone(),
@@ -1,5 +1,6 @@
-pub use alice::Alice;
-pub use bob::Bob;
+pub use alice::{Alice, AliceEndState};
+pub use bob::{Bob, BobEndState};
+pub use peer_settings::{PeerSettings, PeerStatus};
 pub use sqlite::SqliteDatabase;
 
 use crate::cli::api::tauri_bindings::TauriHandle;
@@ -13,9 +14,10 @@ use std::sync::Arc;
 
 mod alice;
 mod bob;
+mod peer_settings;
 mod sqlite;
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, strum::VariantNames)]
 pub enum Swap {
     Alice(Alice),
     Bob(Bob),
@@ -10,7 +10,7 @@ use std::fmt;
 // Large enum variant is fine because this is only used for database
 // and is dropped once written in DB.
 #[allow(clippy::large_enum_variant)]
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, strum::VariantNames)]
 pub enum Alice {
     Started {
         state3: alice::State3,
@@ -74,7 +74,7 @@ pub enum Alice {
     Done(AliceEndState),
 }
 
-#[derive(Clone, strum::Display, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, strum::Display, strum::VariantNames, Debug, Deserialize, Serialize, PartialEq)]
 pub enum AliceEndState {
     SafelyAborted,
     BtcRedeemed,
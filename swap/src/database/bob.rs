@@ -5,7 +5,7 @@ use crate::protocol::bob::BobState;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, strum::VariantNames)]
 pub enum Bob {
     Started {
         #[serde(with = "::bitcoin::amount::serde::as_sat")]
@@ -13,6 +13,8 @@ pub enum Bob {
         #[serde(with = "crate::bitcoin::address_serde")]
         change_address: bitcoin::Address,
         tx_lock_fee: bitcoin::Amount,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tx_lock_inputs: Option<Vec<bitcoin::OutPoint>>,
     },
     ExecutionSetupDone {
         state2: bob::State2,
@@ -21,6 +23,10 @@ pub enum Bob {
         state3: bob::State3,
         monero_wallet_restore_blockheight: BlockHeight,
     },
+    BtcEarlyRefundRequested {
+        state3: bob::State3,
+        monero_wallet_restore_blockheight: BlockHeight,
+    },
     XmrLockProofReceived {
         state: bob::State3,
         lock_transfer_proof: TransferProof,
@@ -44,7 +50,7 @@ pub enum Bob {
     Done(BobEndState),
 }
 
-#[derive(Clone, strum::Display, Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Clone, strum::Display, strum::VariantNames, Debug, Deserialize, Serialize, PartialEq)]
 pub enum BobEndState {
     SafelyAborted,
     XmrRedeemed { tx_lock_id: bitcoin::Txid },
@@ -59,10 +65,12 @@ impl From<BobState> for Bob {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                tx_lock_inputs,
             } => Bob::Started {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                tx_lock_inputs,
             },
             BobState::SwapSetupCompleted(state2) => Bob::ExecutionSetupDone { state2 },
             BobState::BtcLocked {
@@ -72,6 +80,13 @@ impl From<BobState> for Bob {
                 state3,
                 monero_wallet_restore_blockheight,
             },
+            BobState::BtcEarlyRefundRequested {
+                state3,
+                monero_wallet_restore_blockheight,
+            } => Bob::BtcEarlyRefundRequested {
+                state3,
+                monero_wallet_restore_blockheight,
+            },
             BobState::XmrLockProofReceived {
                 state,
                 lock_transfer_proof,
@@ -108,10 +123,12 @@ impl From<Bob> for BobState {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                tx_lock_inputs,
             } => BobState::Started {
                 btc_amount,
                 change_address,
                 tx_lock_fee,
+                tx_lock_inputs,
             },
             Bob::ExecutionSetupDone { state2 } => BobState::SwapSetupCompleted(state2),
             Bob::BtcLocked {
@@ -121,6 +138,13 @@ impl From<Bob> for BobState {
                 state3,
                 monero_wallet_restore_blockheight,
             },
+            Bob::BtcEarlyRefundRequested {
+                state3,
+                monero_wallet_restore_blockheight,
+            } => BobState::BtcEarlyRefundRequested {
+                state3,
+                monero_wallet_restore_blockheight,
+            },
             Bob::XmrLockProofReceived {
                 state,
                 lock_transfer_proof,
@@ -154,6 +178,7 @@ impl fmt::Display for Bob {
             Bob::Started { .. } => write!(f, "Started"),
             Bob::ExecutionSetupDone { .. } => f.write_str("Execution setup done"),
             Bob::BtcLocked { .. } => f.write_str("Bitcoin locked"),
+            Bob::BtcEarlyRefundRequested { .. } => f.write_str("Bitcoin early refund requested"),
             Bob::XmrLockProofReceived { .. } => {
                 f.write_str("XMR lock transaction transfer proof received")
             }
@@ -0,0 +1,78 @@
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use typeshare::typeshare;
+
+#[typeshare]
+#[derive(
+    Debug, Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Ord, PartialOrd,
+)]
+pub enum PeerStatus {
+    /// We know the peer but the user did not express a preference
+    #[default]
+    Known,
+    Pinned,
+    Banned,
+}
+
+impl PeerStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PeerStatus::Known => "known",
+            PeerStatus::Pinned => "pinned",
+            PeerStatus::Banned => "banned",
+        }
+    }
+}
+
+impl fmt::Display for PeerStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PeerStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Ok(match s {
+            "known" => PeerStatus::Known,
+            "pinned" => PeerStatus::Pinned,
+            "banned" => PeerStatus::Banned,
+            other => bail!("Unknown peer status `{}`", other),
+        })
+    }
+}
+
+#[typeshare]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Ord, PartialOrd)]
+pub struct PeerSettings {
+    pub status: PeerStatus,
+    /// A name the user gave the peer
+    pub label: Option<String>,
+}
+
+impl PeerSettings {
+    pub fn is_pinned(&self) -> bool {
+        self.status == PeerStatus::Pinned
+    }
+
+    pub fn is_banned(&self) -> bool {
+        self.status == PeerStatus::Banned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_roundtrips_through_its_database_representation() {
+        for status in [PeerStatus::Known, PeerStatus::Pinned, PeerStatus::Banned] {
+            assert_eq!(status.to_string().parse::<PeerStatus>().unwrap(), status);
+        }
+
+        assert!("trusted".parse::<PeerStatus>().is_err());
+    }
+}
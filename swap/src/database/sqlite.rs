@@ -1,10 +1,12 @@
+use crate::asb::Rate;
+use crate::bitcoin::EncryptedSignature;
 use crate::cli::api::tauri_bindings::TauriEmitter;
 use crate::cli::api::tauri_bindings::TauriHandle;
-use crate::database::Swap;
+use crate::database::{PeerSettings, PeerStatus, Swap};
 use crate::monero::LabeledMoneroAddress;
 use crate::monero::MoneroAddressPool;
 use crate::monero::TransferProof;
-use crate::protocol::{Database, State};
+use crate::protocol::{Database, PeerReachability, State, StateTransition};
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
 use libp2p::{Multiaddr, PeerId};
@@ -12,6 +14,7 @@ use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 use sqlx::sqlite::{Sqlite, SqliteConnectOptions};
 use sqlx::{ConnectOptions, Pool, SqlitePool};
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::str::FromStr;
 use time::OffsetDateTime;
@@ -19,6 +22,9 @@ use uuid::Uuid;
 
 use super::AccessMode;
 
+/// Name under which the backfill of `swap_states.entered_at_unix_ns` is recorded as completed.
+const ENTERED_AT_UNIX_NS_BACKFILL: &str = "entered_at_unix_ns";
+
 pub struct SqliteDatabase {
     pool: Pool<Sqlite>,
     tauri_handle: Option<TauriHandle>,
@@ -59,6 +65,91 @@ impl SqliteDatabase {
             .set_ignore_missing(true)
             .run(&self.pool)
             .await?;
+
+        if !self.backfill_completed(ENTERED_AT_UNIX_NS_BACKFILL).await? {
+            self.backfill_entered_at_unix_ns().await?;
+        }
+
+        Ok(())
+    }
+
+    async fn backfill_completed(&self, name: &str) -> Result<bool> {
+        let row = sqlx::query!(
+            r#"
+            SELECT name
+            FROM completed_backfills
+            WHERE name = ?
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Fill in `entered_at_unix_ns` for the states stored before the column existed.
+    ///
+    /// States whose `entered_at` cannot be parsed are left as they are. The backfill
+    /// is recorded as completed, new states always have the column set.
+    async fn backfill_entered_at_unix_ns(&self) -> Result<()> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, entered_at
+            FROM swap_states
+            WHERE entered_at_unix_ns IS NULL
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transaction = self.pool.begin().await?;
+        let mut backfilled = 0;
+
+        for row in &rows {
+            let entered_at_unix_ns = match parse_entered_at(&row.entered_at).and_then(unix_ns) {
+                Ok(entered_at_unix_ns) => entered_at_unix_ns,
+                Err(error) => {
+                    tracing::warn!(id = row.id, %error, "Cannot backfill the timestamp of a state");
+                    continue;
+                }
+            };
+
+            sqlx::query!(
+                r#"
+                UPDATE swap_states
+                SET entered_at_unix_ns = ?
+                WHERE id = ?
+                "#,
+                entered_at_unix_ns,
+                row.id
+            )
+            .execute(&mut *transaction)
+            .await?;
+
+            backfilled += 1;
+        }
+
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO completed_backfills (name)
+            VALUES (?)
+            "#,
+            ENTERED_AT_UNIX_NS_BACKFILL
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        if !rows.is_empty() {
+            tracing::info!(
+                backfilled,
+                total = rows.len(),
+                "Backfilled the timestamps of swap states"
+            );
+        }
+
         Ok(())
     }
 }
@@ -273,6 +364,147 @@ impl Database for SqliteDatabase {
         Ok(peer_map.into_iter().collect())
     }
 
+    async fn insert_peer_reachability(&self, peer_id: PeerId, reachable: bool) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let (times_reachable, times_unreachable) = if reachable { (1, 0) } else { (0, 1) };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO peer_reachability (
+                peer_id,
+                times_reachable,
+                times_unreachable
+                ) VALUES (?, ?, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET
+                times_reachable = times_reachable + excluded.times_reachable,
+                times_unreachable = times_unreachable + excluded.times_unreachable;
+        "#,
+            peer_id,
+            times_reachable,
+            times_unreachable
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_all_peer_reachability(&self) -> Result<Vec<(PeerId, PeerReachability)>> {
+        let rows = sqlx::query!(
+            r#"
+           SELECT peer_id, times_reachable, times_unreachable
+           FROM peer_reachability
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let peer_id = PeerId::from_str(&row.peer_id)?;
+                let reachability = PeerReachability {
+                    reachable: u32::try_from(row.times_reachable)?,
+                    unreachable: u32::try_from(row.times_unreachable)?,
+                };
+
+                Ok((peer_id, reachability))
+            })
+            .collect()
+    }
+
+    async fn insert_peer_status(&self, peer_id: PeerId, status: PeerStatus) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let status = status.to_string();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO peer_settings (
+                peer_id,
+                status
+                ) VALUES (?, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET status = excluded.status;
+        "#,
+            peer_id,
+            status
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_peer_label(&self, peer_id: PeerId, label: Option<String>) -> Result<()> {
+        let peer_id = peer_id.to_string();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO peer_settings (
+                peer_id,
+                label
+                ) VALUES (?, ?)
+            ON CONFLICT(peer_id) DO UPDATE SET label = excluded.label;
+        "#,
+            peer_id,
+            label
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_all_peer_settings(&self) -> Result<Vec<(PeerId, PeerSettings)>> {
+        let rows = sqlx::query!(
+            r#"
+           SELECT peer_id, status, label
+           FROM peer_settings
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let peer_id = PeerId::from_str(&row.peer_id)?;
+                let settings = PeerSettings {
+                    status: PeerStatus::from_str(&row.status)?,
+                    label: row.label,
+                };
+
+                Ok((peer_id, settings))
+            })
+            .collect()
+    }
+
+    async fn remove_peer(&self, peer_id: PeerId) -> Result<()> {
+        let peer_id = peer_id.to_string();
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM peer_addresses
+            WHERE peer_id = ?
+        "#,
+            peer_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            DELETE FROM peer_settings
+            WHERE peer_id = ?
+        "#,
+            peer_id
+        )
+        .execute(&mut *transaction)
+        .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
     async fn get_swap_start_date(&self, swap_id: Uuid) -> Result<String> {
         let swap_id = swap_id.to_string();
 
@@ -295,6 +527,7 @@ impl Database for SqliteDatabase {
         let entered_at = OffsetDateTime::now_utc();
 
         let swap = serde_json::to_string(&Swap::from(state))?;
+        let entered_at_unix_ns = unix_ns(entered_at)?;
         let entered_at = entered_at.to_string();
         let swap_id_str = swap_id.to_string();
 
@@ -303,11 +536,13 @@ impl Database for SqliteDatabase {
             insert into swap_states (
                 swap_id,
                 entered_at,
+                entered_at_unix_ns,
                 state
-                ) values (?, ?, ?);
+                ) values (?, ?, ?, ?);
         "#,
             swap_id_str,
             entered_at,
+            entered_at_unix_ns,
             swap
         )
         .execute(&self.pool)
@@ -420,6 +655,98 @@ impl Database for SqliteDatabase {
         result
     }
 
+    async fn get_state_transitions(&self, swap_id: Uuid) -> Result<Vec<StateTransition>> {
+        let swap_id = swap_id.to_string();
+
+        let rows = sqlx::query!(
+            r#"
+           SELECT state, entered_at, entered_at_unix_ns
+           FROM swap_states
+           WHERE swap_id = ?
+           ORDER BY id ASC
+        "#,
+            swap_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| state_transition(&row.state, &row.entered_at, row.entered_at_unix_ns))
+            .collect()
+    }
+
+    async fn get_state_transitions_between(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<(Uuid, StateTransition)>> {
+        let from = unix_ns(from)?;
+        let to = unix_ns(to)?;
+
+        let rows = sqlx::query!(
+            r#"
+           SELECT swap_id, state, entered_at, entered_at_unix_ns
+           FROM swap_states
+           WHERE entered_at_unix_ns BETWEEN ? AND ?
+           ORDER BY entered_at_unix_ns ASC, id ASC
+        "#,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let swap_id = Uuid::from_str(&row.swap_id)?;
+                let transition =
+                    state_transition(&row.state, &row.entered_at, row.entered_at_unix_ns)?;
+
+                Ok((swap_id, transition))
+            })
+            .collect()
+    }
+
+    async fn get_all_state_transitions(&self) -> Result<HashMap<Uuid, Vec<StateTransition>>> {
+        let rows = sqlx::query!(
+            r#"
+           SELECT swap_id, state, entered_at, entered_at_unix_ns
+           FROM swap_states
+           ORDER BY id ASC
+        "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut transitions: HashMap<Uuid, Vec<StateTransition>> = HashMap::new();
+        let mut unreadable = HashSet::new();
+
+        for row in &rows {
+            let swap_id = match Uuid::from_str(&row.swap_id) {
+                Ok(swap_id) => swap_id,
+                Err(error) => {
+                    tracing::warn!(swap_id = %row.swap_id, %error, "Failed to parse swap id, skipping the state");
+                    continue;
+                }
+            };
+
+            if unreadable.contains(&swap_id) {
+                continue;
+            }
+
+            match state_transition(&row.state, &row.entered_at, row.entered_at_unix_ns) {
+                Ok(transition) => transitions.entry(swap_id).or_default().push(transition),
+                Err(error) => {
+                    tracing::warn!(%swap_id, "Failed to read a state of the swap, skipping the swap: {:#}", error);
+                    transitions.remove(&swap_id);
+                    unreadable.insert(swap_id);
+                }
+            }
+        }
+
+        Ok(transitions)
+    }
+
     async fn insert_buffered_transfer_proof(
         &self,
         swap_id: Uuid,
@@ -467,6 +794,197 @@ impl Database for SqliteDatabase {
 
         Ok(Some(proof))
     }
+
+    async fn insert_buffered_encrypted_signature(
+        &self,
+        swap_id: Uuid,
+        encrypted_signature: EncryptedSignature,
+    ) -> Result<()> {
+        let swap_id = swap_id.to_string();
+        let encrypted_signature = serde_json::to_string(&encrypted_signature)?;
+
+        // Bob retries sending the encrypted signature until we acknowledge it
+        // We might therefore receive (and store) the same signature multiple times
+        sqlx::query!(
+            r#"
+            INSERT OR REPLACE INTO buffered_encrypted_signatures (
+                swap_id,
+                encrypted_signature
+                ) VALUES (?, ?);
+        "#,
+            swap_id,
+            encrypted_signature
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_buffered_encrypted_signature(
+        &self,
+        swap_id: Uuid,
+    ) -> Result<Option<EncryptedSignature>> {
+        let swap_id = swap_id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+           SELECT encrypted_signature
+           FROM buffered_encrypted_signatures
+           WHERE swap_id = ?
+            "#,
+            swap_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        if row.is_empty() {
+            return Ok(None);
+        }
+
+        let encrypted_signature_str = &row[0].encrypted_signature;
+        let encrypted_signature = serde_json::from_str(encrypted_signature_str)?;
+
+        Ok(Some(encrypted_signature))
+    }
+
+    async fn remove_buffered_encrypted_signature(&self, swap_id: Uuid) -> Result<()> {
+        let swap_id = swap_id.to_string();
+
+        sqlx::query!(
+            r#"
+            DELETE FROM buffered_encrypted_signatures
+            WHERE swap_id = ?
+        "#,
+            swap_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn insert_swap_rate(&self, swap_id: Uuid, rate: Rate) -> Result<()> {
+        let swap_id = swap_id.to_string();
+        let market_price = i64::try_from(rate.market_price().to_sat())
+            .context("Market price does not fit into an i64")?;
+        let ask_spread = rate.spread().to_string();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO swap_rates (
+                swap_id,
+                market_price,
+                ask_spread
+                ) VALUES (?, ?, ?);
+        "#,
+            swap_id,
+            market_price,
+            ask_spread
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_swap_rate(&self, swap_id: Uuid) -> Result<Option<Rate>> {
+        let swap_id = swap_id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+           SELECT market_price, ask_spread
+           FROM swap_rates
+           WHERE swap_id = ?
+            "#,
+            swap_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let market_price = crate::bitcoin::Amount::from_sat(
+            u64::try_from(row.market_price).context("Stored market price is negative")?,
+        );
+        let ask_spread =
+            Decimal::from_str(&row.ask_spread).context("Failed to parse stored ask spread")?;
+
+        Ok(Some(Rate::new(market_price, ask_spread)))
+    }
+
+    async fn insert_swap_order(&self, swap_id: Uuid, order_id: Uuid) -> Result<()> {
+        let swap_id = swap_id.to_string();
+        let order_id = order_id.to_string();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO swap_orders (
+                swap_id,
+                order_id
+                ) VALUES (?, ?);
+        "#,
+            swap_id,
+            order_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_swap_order(&self, swap_id: Uuid) -> Result<Option<Uuid>> {
+        let swap_id = swap_id.to_string();
+
+        let row = sqlx::query!(
+            r#"
+           SELECT order_id
+           FROM swap_orders
+           WHERE swap_id = ?
+            "#,
+            swap_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| Uuid::from_str(&row.order_id).context("Failed to parse stored order id"))
+            .transpose()
+    }
+}
+
+fn state_transition(
+    state: &str,
+    entered_at: &str,
+    entered_at_unix_ns: Option<i64>,
+) -> Result<StateTransition> {
+    let state = State::from(serde_json::from_str::<Swap>(state)?);
+    let entered_at = match entered_at_unix_ns {
+        Some(unix_ns) => OffsetDateTime::from_unix_timestamp_nanos(i128::from(unix_ns))?,
+        None => parse_entered_at(entered_at)?,
+    };
+
+    Ok(StateTransition { state, entered_at })
+}
+
+fn unix_ns(timestamp: OffsetDateTime) -> Result<i64> {
+    i64::try_from(timestamp.unix_timestamp_nanos())
+        .with_context(|| format!("Timestamp {} is out of range", timestamp))
+}
+
+/// Parse the `entered_at` column of `swap_states`.
+///
+/// The column holds the [`Display`](std::fmt::Display) representation of an
+/// [`OffsetDateTime`], e.g. `2025-07-22 14:15:30.123456 +00:00:00`.
+fn parse_entered_at(entered_at: &str) -> Result<OffsetDateTime> {
+    let format = time::format_description::parse(concat!(
+        "[year]-[month]-[day] [hour padding:none]:[minute]:[second].[subsecond] ",
+        "[offset_hour sign:mandatory]:[offset_minute]:[offset_second]"
+    ))?;
+
+    OffsetDateTime::parse(entered_at, &format)
+        .with_context(|| format!("Failed to parse state timestamp `{}`", entered_at))
 }
 
 #[cfg(test)]
@@ -527,6 +1045,183 @@ mod tests {
         assert!(!latest_loaded.contains(&(swap_id_1, state_1)));
     }
 
+    #[tokio::test]
+    async fn test_load_state_transitions() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let swap_id = Uuid::new_v4();
+        let before = OffsetDateTime::now_utc();
+
+        db.insert_latest_state(swap_id, State::Alice(AliceState::SafelyAborted))
+            .await?;
+        db.insert_latest_state(swap_id, State::Alice(AliceState::BtcRedeemed))
+            .await?;
+        db.insert_latest_state(Uuid::new_v4(), State::Bob(BobState::SafelyAborted))
+            .await?;
+
+        let transitions = db.get_state_transitions(swap_id).await?;
+
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(
+            transitions[0].state,
+            State::Alice(AliceState::SafelyAborted)
+        );
+        assert_eq!(transitions[1].state, State::Alice(AliceState::BtcRedeemed));
+        assert!(before <= transitions[0].entered_at);
+        assert!(transitions[0].entered_at <= transitions[1].entered_at);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_all_state_transitions() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let swap_id_1 = Uuid::new_v4();
+        let swap_id_2 = Uuid::new_v4();
+        let unreadable_swap_id = Uuid::new_v4();
+
+        db.insert_latest_state(swap_id_1, State::Bob(BobState::SafelyAborted))
+            .await?;
+        db.insert_latest_state(swap_id_2, State::Alice(AliceState::SafelyAborted))
+            .await?;
+        db.insert_latest_state(swap_id_2, State::Alice(AliceState::BtcRedeemed))
+            .await?;
+        db.insert_latest_state(unreadable_swap_id, State::Bob(BobState::SafelyAborted))
+            .await?;
+
+        sqlx::query("INSERT INTO swap_states (swap_id, entered_at, state) VALUES (?, ?, ?)")
+            .bind(unreadable_swap_id.to_string())
+            .bind("2025-07-22 4:05:30.0 +00:00:00")
+            .bind("not a state")
+            .execute(&db.pool)
+            .await?;
+
+        let transitions = db.get_all_state_transitions().await?;
+
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[&swap_id_1].len(), 1);
+        assert_eq!(
+            transitions[&swap_id_2]
+                .iter()
+                .map(|transition| &transition.state)
+                .collect::<Vec<_>>(),
+            vec![
+                &State::Alice(AliceState::SafelyAborted),
+                &State::Alice(AliceState::BtcRedeemed)
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_load_state_transitions_between() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let swap_id_1 = Uuid::new_v4();
+        let swap_id_2 = Uuid::new_v4();
+
+        db.insert_latest_state(swap_id_1, State::Alice(AliceState::SafelyAborted))
+            .await?;
+        let from = OffsetDateTime::now_utc();
+        db.insert_latest_state(swap_id_2, State::Bob(BobState::SafelyAborted))
+            .await?;
+        db.insert_latest_state(swap_id_1, State::Alice(AliceState::BtcRedeemed))
+            .await?;
+        let to = OffsetDateTime::now_utc();
+
+        let transitions = db.get_state_transitions_between(from, to).await?;
+
+        assert_eq!(transitions.len(), 2);
+        assert_eq!(transitions[0].0, swap_id_2);
+        assert_eq!(transitions[1].0, swap_id_1);
+        assert_eq!(
+            transitions[1].1.state,
+            State::Alice(AliceState::BtcRedeemed)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backfill_entered_at_unix_ns() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let swap_id = Uuid::new_v4();
+        let state = serde_json::to_string(&Swap::from(State::Bob(BobState::SafelyAborted)))?;
+
+        // States stored before the column existed only have the textual timestamp
+        sqlx::query(
+            "INSERT INTO swap_states (swap_id, entered_at, state) VALUES (?, ?, ?), (?, ?, ?)",
+        )
+        .bind(swap_id.to_string())
+        .bind("2025-07-22 4:05:30.0 +00:00:00")
+        .bind(&state)
+        .bind(swap_id.to_string())
+        .bind("not a timestamp")
+        .bind(&state)
+        .execute(&db.pool)
+        .await?;
+
+        db.backfill_entered_at_unix_ns().await?;
+
+        let backfilled: Vec<Option<i64>> = sqlx::query_scalar(
+            "SELECT entered_at_unix_ns FROM swap_states WHERE swap_id = ? ORDER BY id ASC",
+        )
+        .bind(swap_id.to_string())
+        .fetch_all(&db.pool)
+        .await?;
+
+        assert_eq!(backfilled, vec![Some(1_753_157_130_000_000_000), None]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_backfill_entered_at_unix_ns_runs_once() -> Result<()> {
+        let mut db = setup_test_db().await?;
+
+        let swap_id = Uuid::new_v4();
+        let state = serde_json::to_string(&Swap::from(State::Bob(BobState::SafelyAborted)))?;
+
+        sqlx::query("INSERT INTO swap_states (swap_id, entered_at, state) VALUES (?, ?, ?)")
+            .bind(swap_id.to_string())
+            .bind("2025-07-22 4:05:30.0 +00:00:00")
+            .bind(&state)
+            .execute(&db.pool)
+            .await?;
+
+        // Opening the database again does not scan the states again
+        db.run_migrations().await?;
+
+        let entered_at_unix_ns: Option<i64> =
+            sqlx::query_scalar("SELECT entered_at_unix_ns FROM swap_states WHERE swap_id = ?")
+                .bind(swap_id.to_string())
+                .fetch_one(&db.pool)
+                .await?;
+
+        assert_eq!(entered_at_unix_ns, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_entered_at() {
+        let entered_at = OffsetDateTime::now_utc();
+
+        assert_eq!(
+            parse_entered_at(&entered_at.to_string()).unwrap(),
+            entered_at
+        );
+        assert_eq!(
+            parse_entered_at("2025-07-22 4:05:30.0 +00:00:00")
+                .unwrap()
+                .unix_timestamp(),
+            1753157130
+        );
+    }
+
     #[tokio::test]
     async fn test_insert_and_load_monero_address_pool() -> Result<()> {
         use crate::monero::{LabeledMoneroAddress, MoneroAddressPool};
@@ -621,6 +1316,118 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_insert_and_load_swap_order() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let order_id = Uuid::new_v4();
+        let swap_id = Uuid::new_v4();
+
+        db.insert_swap_order(swap_id, order_id).await?;
+
+        assert_eq!(db.get_swap_order(swap_id).await?, Some(order_id));
+        assert_eq!(db.get_swap_order(Uuid::new_v4()).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_load_and_remove_buffered_encrypted_signature() -> Result<()> {
+        use ::bitcoin::hashes::Hash;
+
+        let db = setup_test_db().await?;
+
+        let swap_id = Uuid::new_v4();
+        let secret_key = crate::bitcoin::SecretKey::new_random(&mut rand::thread_rng());
+        let encrypted_signature = secret_key.encsign(
+            secret_key.public(),
+            ::bitcoin::sighash::SegwitV0Sighash::all_zeros(),
+        );
+
+        // Bob retries sending the signature until we acknowledge it
+        db.insert_buffered_encrypted_signature(swap_id, encrypted_signature.clone())
+            .await?;
+        db.insert_buffered_encrypted_signature(swap_id, encrypted_signature.clone())
+            .await?;
+
+        let loaded = db
+            .get_buffered_encrypted_signature(swap_id)
+            .await?
+            .expect("buffered encrypted signature to be stored");
+        assert_eq!(
+            serde_json::to_value(&loaded)?,
+            serde_json::to_value(&encrypted_signature)?
+        );
+        assert!(db
+            .get_buffered_encrypted_signature(Uuid::new_v4())
+            .await?
+            .is_none());
+
+        db.remove_buffered_encrypted_signature(swap_id).await?;
+
+        assert!(db
+            .get_buffered_encrypted_signature(swap_id)
+            .await?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_load_peer_reachability() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let peer_id = PeerId::random();
+
+        db.insert_peer_reachability(peer_id, true).await?;
+        db.insert_peer_reachability(peer_id, true).await?;
+        db.insert_peer_reachability(peer_id, false).await?;
+
+        assert_eq!(
+            db.get_all_peer_reachability().await?,
+            vec![(
+                peer_id,
+                PeerReachability {
+                    reachable: 2,
+                    unreachable: 1,
+                }
+            )]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_load_and_remove_peer_settings() -> Result<()> {
+        let db = setup_test_db().await?;
+
+        let peer_id = PeerId::random();
+        db.insert_address(peer_id, "/ip4/127.0.0.1".parse()?)
+            .await?;
+
+        db.insert_peer_label(peer_id, Some("my maker".to_string()))
+            .await?;
+        db.insert_peer_status(peer_id, PeerStatus::Banned).await?;
+
+        assert_eq!(
+            db.get_all_peer_settings().await?,
+            vec![(
+                peer_id,
+                PeerSettings {
+                    status: PeerStatus::Banned,
+                    label: Some("my maker".to_string()),
+                }
+            )]
+        );
+
+        db.remove_peer(peer_id).await?;
+
+        assert!(db.get_all_peer_settings().await?.is_empty());
+        assert!(db.get_addresses(peer_id).await?.is_empty());
+
+        Ok(())
+    }
+
     async fn setup_test_db() -> Result<SqliteDatabase> {
         let dir: TempDir = tempdir().unwrap();
         let temp_db = dir.path().join("tempdb");
@@ -11,6 +11,10 @@ use url::Url;
 ///
 /// If the connection fails, it will automatically be re-established.
 ///
+/// Kraken only sends ticker updates when there are trades. In between it sends
+/// heartbeats, for which we publish the last price again. Receivers can
+/// therefore tell a quiet market apart from a dead connection.
+///
 /// price_ticker_ws_url must point to a websocket server that follows the kraken
 /// price ticker protocol
 /// See: https://docs.kraken.com/websockets/
@@ -35,14 +39,22 @@ pub fn connect(price_ticker_ws_url: Url) -> Result<PriceUpdates> {
                 async move {
                     let mut stream = connection::new(price_ticker_ws_url).await?;
 
-                    while let Some(update) = stream.try_next().await.map_err(to_backoff)? {
-                        let send_result = price_update.send(Ok(update));
-
-                        if send_result.is_err() {
+                    while let Some(message) = stream.try_next().await.map_err(to_backoff)? {
+                        if price_update.is_closed() {
                             return Err(backoff::Error::Permanent(anyhow!(
                                 "receiver disconnected"
                             )));
                         }
+
+                        match message {
+                            connection::Message::Ticker(update) => {
+                                let _ = price_update.send(Ok(update));
+                            }
+                            // Only re-publish if we already know a price
+                            connection::Message::Heartbeat => {
+                                price_update.send_if_modified(|update| update.is_ok());
+                            }
+                        }
                     }
 
                     Err(backoff::Error::transient(anyhow!("stream ended")))
@@ -120,16 +132,24 @@ fn to_backoff(e: connection::Error) -> backoff::Error<anyhow::Error> {
 /// Kraken websocket connection module.
 ///
 /// Responsible for establishing a connection to the Kraken websocket API and
-/// transforming the received websocket frames into a stream of rate updates.
-/// The connection may fail in which case it is simply terminated and the stream
-/// ends.
+/// transforming the received websocket frames into a stream of rate updates
+/// and heartbeats. The connection may fail in which case it is simply
+/// terminated and the stream ends.
 mod connection {
     use super::*;
     use crate::kraken::wire;
     use futures::stream::BoxStream;
     use tokio_tungstenite::tungstenite;
 
-    pub async fn new(ws_url: Url) -> Result<BoxStream<'static, Result<wire::PriceUpdate, Error>>> {
+    /// A message from the Kraken websocket API that we are interested in.
+    #[derive(Clone, Debug)]
+    pub enum Message {
+        Ticker(wire::PriceUpdate),
+        /// Sent about once per second if there were no other messages.
+        Heartbeat,
+    }
+
+    pub async fn new(ws_url: Url) -> Result<BoxStream<'static, Result<Message, Error>>> {
         let (mut rate_stream, _) = tokio_tungstenite::connect_async(ws_url)
             .await
             .context("Failed to connect to Kraken websocket API")?;
@@ -143,12 +163,12 @@ mod connection {
         Ok(stream)
     }
 
-    /// Parse a websocket message into a [`wire::PriceUpdate`].
+    /// Parse a websocket message into a [`Message`].
     ///
-    /// Messages which are not actually ticker updates are ignored and result in
-    /// `None` being returned. In the context of a [`TryStream`], these will
-    /// simply be filtered out.
-    async fn parse_message(msg: tungstenite::Message) -> Result<Option<wire::PriceUpdate>, Error> {
+    /// Messages which are neither ticker updates nor heartbeats are ignored and
+    /// result in `None` being returned. In the context of a [`TryStream`], these
+    /// will simply be filtered out.
+    async fn parse_message(msg: tungstenite::Message) -> Result<Option<Message>, Error> {
         let msg = match msg {
             tungstenite::Message::Text(msg) => msg,
             tungstenite::Message::Close(close_frame) => {
@@ -186,7 +206,7 @@ mod connection {
                 return Ok(None);
             }
             Ok(wire::Event::Heartbeat) => {
-                return Ok(None);
+                return Ok(Some(Message::Heartbeat));
             }
             // if the message is not an event, it is a ticker update or an unknown event
             Err(_) => match serde_json::from_str::<wire::PriceUpdate>(&msg) {
@@ -198,7 +218,7 @@ mod connection {
             },
         };
 
-        Ok(Some(update))
+        Ok(Some(Message::Ticker(update)))
     }
 
     #[derive(Debug, thiserror::Error)]
@@ -330,6 +350,15 @@ mod wire {
             assert_eq!(event, Event::SubscriptionStatus)
         }
 
+        #[test]
+        fn can_deserialize_heartbeat_event() {
+            let event = r#"{"event":"heartbeat"}"#;
+
+            let event = serde_json::from_str::<Event>(event).unwrap();
+
+            assert_eq!(event, Event::Heartbeat)
+        }
+
         #[test]
         fn deserialize_ticker_update() {
             let message = r#"[980,{"a":["0.00440700",7,"7.35318535"],"b":["0.00440200",7,"7.57416678"],"c":["0.00440700","0.22579000"],"v":["273.75489000","4049.91233351"],"p":["0.00446205","0.00441699"],"t":[123,1310],"l":["0.00439400","0.00429900"],"h":["0.00450000","0.00450000"],"o":["0.00449100","0.00433700"]},"ticker","XMR/XBT"]"#;
@@ -1,10 +1,16 @@
+pub mod offline_signing;
+#[cfg(feature = "test-utils")]
+pub mod simulated;
 pub mod wallet;
 pub mod wallet_rpc;
 
 pub use ::monero::network::Network;
 pub use ::monero::{Address, PrivateKey, PublicKey};
 pub use curve25519_dalek::scalar::Scalar;
-pub use wallet::{Daemon, Wallet, Wallets, WatchRequest};
+pub use wallet::{
+    Daemon, MoneroSubaddress, MoneroTransfer, MoneroTransferDirection, Wallet, Wallets,
+    WatchRequest,
+};
 
 use crate::bitcoin;
 use anyhow::{bail, Result};
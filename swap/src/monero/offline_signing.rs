@@ -0,0 +1,933 @@
+//! Signing the Monero transfers of a view-only wallet on another machine.
+//!
+//! The asb only keeps a view-only wallet and exchanges files with the signer
+//! (`asb monero-signer`), which holds the full wallet, through a shared directory:
+//!
+//! 1. asb: exports its outputs to `<id>.outputs` and writes the transfer to `<id>.unsigned`
+//! 2. signer: imports the outputs, signs the transfer to `<id>.signed` and exports its
+//!    key images to `<id>.key-images`
+//! 3. asb: imports the key images, publishes the transfer and records it in `<id>.receipt.json`
+//!
+//! Anyone who can write to the signing directory could drop a transfer there. The
+//! asb therefore writes `<id>.manifest.json` next to each transfer, which names the
+//! recipient and amount and is authenticated with a [`ManifestKey`] only the asb and
+//! the signer know. The signer only signs transfers which match their manifest, and
+//! at most a configured total within a time window, which it tracks in its own
+//! directory.
+//!
+//! Files are written under a temporary name and renamed once complete, such that
+//! neither side ever reads a partially written file. The receipt makes retries
+//! idempotent, we never create a second transfer for the same id.
+//!
+//! Until the key images of a transfer are imported, the view-only wallet does not
+//! know which outputs the transfer spends. We therefore only handle one transfer
+//! at a time, otherwise two transfers could spend the same outputs. Before
+//! publishing, we record the outgoing transactions we already know in
+//! `<id>.submitting`. If we crash (or publishing fails) afterwards, we look for
+//! the transfer in the wallet history instead of publishing it again. A signed
+//! transfer which could not be published is removed, such that the next attempt
+//! creates it again from our current outputs.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use hmac::{Hmac, Mac};
+use monero::Address;
+use monero_sys::{TransactionDirection, TxReceipt, UnsignedTransfer};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::Wallet;
+
+const OUTPUTS: &str = "outputs";
+const UNSIGNED: &str = "unsigned";
+const SIGNED: &str = "signed";
+const KEY_IMAGES: &str = "key-images";
+const RECEIPT: &str = "receipt.json";
+const REJECTED: &str = "rejected";
+const SUBMITTING: &str = "submitting";
+const MANIFEST: &str = "manifest.json";
+
+/// The files the signer keeps in its own directory, never in the signing directory.
+const MANIFEST_KEY_FILE: &str = "manifest.key";
+const SIGNED_TRANSFERS_FILE: &str = "signed-transfers.json";
+
+/// How often we look for new files in the signing directory.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The directory shared by the asb and the signer.
+#[derive(Debug, Clone)]
+pub struct SigningDir {
+    path: PathBuf,
+}
+
+/// What we store about a published transfer.
+#[derive(Debug, Serialize, Deserialize)]
+struct Receipt {
+    txid: String,
+    tx_key: String,
+    height: u64,
+}
+
+/// The outgoing transactions of the wallet before we published a transfer.
+#[derive(Debug, Serialize, Deserialize)]
+struct Submission {
+    known_txids: HashSet<String>,
+}
+
+/// Authenticates the manifests of the transfers the asb asks the signer to sign.
+///
+/// Generated by the signer and configured on the asb, displayed as hex.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ManifestKey([u8; 32]);
+
+/// What the asb asks the signer to sign for a transfer.
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    recipient: String,
+    /// In piconero.
+    amount: u64,
+    /// Hex encoded HMAC-SHA256 of the id, recipient and amount.
+    mac: String,
+}
+
+/// A transfer the signer signed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SignedTransfer {
+    id: Uuid,
+    /// In piconero.
+    amount: u64,
+    /// Seconds since the Unix epoch.
+    signed_at: u64,
+}
+
+/// Limits the total the signer signs within a time window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningLimit {
+    pub max_total: monero::Amount,
+    pub window: Duration,
+}
+
+/// Decides which transfers the signer signs.
+#[derive(Debug)]
+pub struct SigningPolicy {
+    manifest_key: ManifestKey,
+    max_amount: Option<monero::Amount>,
+    limit: Option<SigningLimit>,
+    signed_transfers: PathBuf,
+}
+
+/// Creates transfers with the view-only wallet and waits for the signer to sign them.
+#[derive(Debug, Clone)]
+pub struct OfflineSigner {
+    dir: SigningDir,
+    timeout: Duration,
+    manifest_key: ManifestKey,
+    /// Held from creating a transfer until it is published.
+    transfer_lock: Arc<Mutex<()>>,
+}
+
+impl SigningDir {
+    pub fn new(path: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create signing directory {}", path.display()))?;
+
+        Ok(Self { path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn file(&self, id: Uuid, kind: &str) -> PathBuf {
+        self.path.join(format!("{}.{}", id, kind))
+    }
+
+    fn temp_file(&self, id: Uuid, kind: &str) -> PathBuf {
+        self.path.join(format!("{}.{}.tmp", id, kind))
+    }
+
+    /// The transfers which are waiting for the signer.
+    fn unsigned(&self) -> Result<Vec<Uuid>> {
+        let mut ids = Vec::new();
+
+        for entry in std::fs::read_dir(&self.path)
+            .with_context(|| format!("Failed to read signing directory {}", self.path.display()))?
+        {
+            let file_name = entry?.file_name();
+            let Some(id) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(&format!(".{}", UNSIGNED)))
+                .and_then(|id| id.parse::<Uuid>().ok())
+            else {
+                continue;
+            };
+
+            if !self.file(id, SIGNED).exists() && !self.file(id, REJECTED).exists() {
+                ids.push(id);
+            }
+        }
+
+        Ok(ids)
+    }
+
+    fn load_receipt(&self, id: Uuid) -> Result<Option<TxReceipt>> {
+        let path = self.file(id, RECEIPT);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let receipt: Receipt = serde_json::from_slice(&std::fs::read(&path)?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        Ok(Some(TxReceipt {
+            txid: receipt.txid,
+            tx_key: receipt.tx_key,
+            height: receipt.height,
+        }))
+    }
+
+    fn save_receipt(&self, id: Uuid, receipt: &TxReceipt) -> Result<()> {
+        let receipt = Receipt {
+            txid: receipt.txid.clone(),
+            tx_key: receipt.tx_key.clone(),
+            height: receipt.height,
+        };
+
+        let temp_path = self.temp_file(id, RECEIPT);
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(&receipt)?)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        rename(&temp_path, &self.file(id, RECEIPT))
+    }
+
+    fn load_submission(&self, id: Uuid) -> Result<Option<Submission>> {
+        let path = self.file(id, SUBMITTING);
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let submission = serde_json::from_slice(&std::fs::read(&path)?)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        Ok(Some(submission))
+    }
+
+    fn save_submission(&self, id: Uuid, submission: &Submission) -> Result<()> {
+        let temp_path = self.temp_file(id, SUBMITTING);
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(submission)?)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        rename(&temp_path, &self.file(id, SUBMITTING))
+    }
+
+    /// Removes everything we and the signer created for a transfer, such that it
+    /// is created again from scratch.
+    fn remove_transfer(&self, id: Uuid) -> Result<()> {
+        for kind in [OUTPUTS, MANIFEST, UNSIGNED, SIGNED, KEY_IMAGES, SUBMITTING] {
+            let path = self.file(id, kind);
+
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => {
+                    return Err(error)
+                        .with_context(|| format!("Failed to remove {}", path.display()))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ManifestKey {
+    /// Loads the key from `path`, generating it on first use.
+    pub fn load_or_generate(path: &Path) -> Result<Self> {
+        if path.exists() {
+            return std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+                .trim()
+                .parse()
+                .with_context(|| format!("Failed to parse manifest key in {}", path.display()));
+        }
+
+        let mut key = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        let key = Self(key);
+
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+
+        // Whoever knows the key can ask the signer to sign transfers
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let mut file = options
+            .open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        std::io::Write::write_all(&mut file, key.to_string().as_bytes())
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(key)
+    }
+
+    fn mac(&self, id: Uuid, recipient: &str, amount: u64) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.0).expect("HMAC accepts keys of any length");
+        mac.update(format!("{}:{}:{}", id, recipient, amount).as_bytes());
+        mac
+    }
+}
+
+impl fmt::Display for ManifestKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for ManifestKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ManifestKey(..)")
+    }
+}
+
+impl FromStr for ManifestKey {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let key = hex::decode(s).context("Manifest key is not hex encoded")?;
+        let key = key
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Manifest key must be 32 bytes"))?;
+
+        Ok(Self(key))
+    }
+}
+
+impl Manifest {
+    fn new(key: &ManifestKey, id: Uuid, recipient: &Address, amount: monero::Amount) -> Self {
+        let recipient = recipient.to_string();
+        let amount = amount.as_pico();
+        let mac = hex::encode(key.mac(id, &recipient, amount).finalize().into_bytes());
+
+        Self {
+            recipient,
+            amount,
+            mac,
+        }
+    }
+
+    /// The recipient and amount, if the manifest was written for transfer `id` by
+    /// someone who knows `key`.
+    fn verify(&self, key: &ManifestKey, id: Uuid) -> Result<(Address, monero::Amount)> {
+        let mac = hex::decode(&self.mac).context("Manifest MAC is not hex encoded")?;
+
+        key.mac(id, &self.recipient, self.amount)
+            .verify_slice(&mac)
+            .context("Manifest was not written by the asb")?;
+
+        let recipient = self
+            .recipient
+            .parse()
+            .context("Failed to parse recipient of manifest")?;
+
+        Ok((recipient, monero::Amount::from_pico(self.amount)))
+    }
+}
+
+impl SigningPolicy {
+    /// Keeps the manifest key and the signed transfers in `signer_dir`, which must
+    /// not be the signing directory.
+    pub fn new(
+        signer_dir: &Path,
+        max_amount: Option<monero::Amount>,
+        limit: Option<SigningLimit>,
+    ) -> Result<Self> {
+        std::fs::create_dir_all(signer_dir)
+            .with_context(|| format!("Failed to create {}", signer_dir.display()))?;
+
+        Ok(Self {
+            manifest_key: ManifestKey::load_or_generate(&signer_dir.join(MANIFEST_KEY_FILE))?,
+            max_amount,
+            limit,
+            signed_transfers: signer_dir.join(SIGNED_TRANSFERS_FILE),
+        })
+    }
+
+    pub fn manifest_key(&self) -> &ManifestKey {
+        &self.manifest_key
+    }
+
+    /// Checks that we may sign `transfer` as transfer `id` at `now` (in seconds
+    /// since the Unix epoch) and returns its amount.
+    fn check(
+        &self,
+        id: Uuid,
+        manifest: &Manifest,
+        transfer: &UnsignedTransfer,
+        now: u64,
+    ) -> Result<monero::Amount> {
+        let (recipient, expected_amount) = manifest.verify(&self.manifest_key, id)?;
+
+        // A transfer may be split into multiple transactions, all to the same recipient
+        if transfer.recipients.is_empty()
+            || transfer
+                .recipients
+                .iter()
+                .any(|address| *address != recipient)
+        {
+            bail!(
+                "Transfer pays {:?} instead of {} as stated in its manifest",
+                transfer.recipients,
+                recipient
+            );
+        }
+
+        let amount = sum(&transfer.amounts);
+        if amount != expected_amount {
+            bail!(
+                "Transfer of {} does not match the amount of {} stated in its manifest",
+                amount,
+                expected_amount
+            );
+        }
+
+        if let Some(max_amount) = self.max_amount {
+            if amount > max_amount {
+                bail!(
+                    "Transfer of {} exceeds the maximum amount of {}",
+                    amount,
+                    max_amount
+                );
+            }
+        }
+
+        if let Some(limit) = self.limit {
+            let since = now.saturating_sub(limit.window.as_secs());
+            let signed = self
+                .load_signed_transfers()?
+                .iter()
+                .filter(|transfer| transfer.signed_at > since)
+                .fold(monero::Amount::ZERO, |total, transfer| {
+                    total + monero::Amount::from_pico(transfer.amount)
+                });
+
+            if signed + amount > limit.max_total {
+                bail!(
+                    "Transfer of {} exceeds the limit of {} within {} hours, {} were already signed",
+                    amount,
+                    limit.max_total,
+                    limit.window.as_secs() / 3600,
+                    signed
+                );
+            }
+        }
+
+        Ok(amount)
+    }
+
+    /// Remembers that we signed transfer `id` at `now`, forgetting transfers
+    /// which left the time window.
+    fn record(&self, id: Uuid, amount: monero::Amount, now: u64) -> Result<()> {
+        let window = self.limit.map_or(0, |limit| limit.window.as_secs());
+
+        let mut transfers = self.load_signed_transfers()?;
+        transfers.retain(|transfer| transfer.signed_at > now.saturating_sub(window));
+        transfers.push(SignedTransfer {
+            id,
+            amount: amount.as_pico(),
+            signed_at: now,
+        });
+
+        let temp_path = self.signed_transfers.with_extension("json.tmp");
+        std::fs::write(&temp_path, serde_json::to_vec_pretty(&transfers)?)
+            .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+        rename(&temp_path, &self.signed_transfers)
+    }
+
+    fn load_signed_transfers(&self) -> Result<Vec<SignedTransfer>> {
+        if !self.signed_transfers.exists() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_slice(&std::fs::read(&self.signed_transfers)?)
+            .with_context(|| format!("Failed to parse {}", self.signed_transfers.display()))
+    }
+}
+
+impl OfflineSigner {
+    pub fn new(dir: PathBuf, timeout: Duration, manifest_key: ManifestKey) -> Result<Self> {
+        Ok(Self {
+            dir: SigningDir::new(dir)?,
+            timeout,
+            manifest_key,
+            transfer_lock: Default::default(),
+        })
+    }
+
+    /// Transfer `amount` to `address` from the view-only `wallet`.
+    ///
+    /// Waits at most for the configured timeout for the signer, plus the time other
+    /// transfers take to be signed and published. Calling this again with the same
+    /// `id` continues where we left off.
+    pub async fn transfer(
+        &self,
+        wallet: &Wallet,
+        id: Uuid,
+        address: &Address,
+        amount: monero::Amount,
+    ) -> Result<TxReceipt> {
+        let _transfer_lock = self.transfer_lock.lock().await;
+
+        if let Some(receipt) = self.dir.load_receipt(id)? {
+            tracing::info!(%id, txid=%receipt.txid, "Monero transfer was already published");
+            return Ok(receipt);
+        }
+
+        if let Some(receipt) = self.find_published(wallet, id, amount).await? {
+            tracing::info!(%id, txid=%receipt.txid, "Found published Monero transfer in the wallet history");
+            self.dir.save_receipt(id, &receipt)?;
+            return Ok(receipt);
+        }
+
+        let unsigned = self.dir.file(id, UNSIGNED);
+
+        if !unsigned.exists() {
+            // The signer needs our outputs before it can sign the transfer
+            let outputs = self.dir.temp_file(id, OUTPUTS);
+            wallet.export_outputs(&outputs).await?;
+            rename(&outputs, &self.dir.file(id, OUTPUTS))?;
+
+            let manifest = Manifest::new(&self.manifest_key, id, address, amount);
+            let temp_manifest = self.dir.temp_file(id, MANIFEST);
+            std::fs::write(&temp_manifest, serde_json::to_vec_pretty(&manifest)?)
+                .with_context(|| format!("Failed to write {}", temp_manifest.display()))?;
+            rename(&temp_manifest, &self.dir.file(id, MANIFEST))?;
+
+            let temp_unsigned = self.dir.temp_file(id, UNSIGNED);
+            wallet
+                .create_unsigned_transfer(address, amount, &temp_unsigned)
+                .await?;
+            rename(&temp_unsigned, &unsigned)?;
+
+            tracing::info!(%id, %address, %amount, file=%unsigned.display(), "Created unsigned Monero transfer, waiting for the signer");
+        }
+
+        let signed = self.dir.file(id, SIGNED);
+        let rejected = self.dir.file(id, REJECTED);
+
+        tokio::time::timeout(self.timeout, async {
+            while !signed.exists() && !rejected.exists() {
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        })
+        .await
+        .with_context(|| {
+            format!(
+                "The signer did not sign {} within {} seconds",
+                unsigned.display(),
+                self.timeout.as_secs()
+            )
+        })?;
+
+        if rejected.exists() {
+            let reason = std::fs::read_to_string(&rejected).unwrap_or_default();
+            bail!("The signer rejected the Monero transfer: {}", reason.trim());
+        }
+
+        // Without the key images our balance ignores spent outputs, this is not fatal though
+        if let Err(error) = wallet
+            .import_key_images(&self.dir.file(id, KEY_IMAGES))
+            .await
+        {
+            tracing::warn!(%id, "Failed to import key images from the signer: {:#}", error);
+        }
+
+        let known_txids = wallet
+            .history()
+            .await?
+            .into_iter()
+            .map(|tx| tx.txid)
+            .collect();
+        self.dir.save_submission(id, &Submission { known_txids })?;
+
+        let receipt = match wallet.submit_signed_transaction(&signed).await {
+            Ok(receipts) => {
+                let mut receipts = receipts.into_iter();
+
+                let receipt = receipts.next().context(
+                    "Published Monero transfer but could not find it in the wallet history",
+                )?;
+
+                if receipts.next().is_some() {
+                    tracing::warn!(%id, "Monero transfer was split into multiple transactions, only tracking the first one");
+                }
+
+                receipt
+            }
+            Err(error) => match self.find_published(wallet, id, amount).await? {
+                Some(receipt) => receipt,
+                None => {
+                    // E.g. another transfer spent the same outputs in the meantime
+                    self.dir.remove_transfer(id)?;
+
+                    return Err(error.context(
+                        "Failed to publish the signed Monero transfer, it will be created again on the next attempt",
+                    ));
+                }
+            },
+        };
+
+        self.dir.save_receipt(id, &receipt)?;
+
+        tracing::info!(%id, txid=%receipt.txid, "Published signed Monero transfer");
+
+        Ok(receipt)
+    }
+
+    /// Looks for an outgoing transfer of `amount` which was added to the wallet
+    /// history after we started to publish the transfer `id`.
+    async fn find_published(
+        &self,
+        wallet: &Wallet,
+        id: Uuid,
+        amount: monero::Amount,
+    ) -> Result<Option<TxReceipt>> {
+        let Some(submission) = self.dir.load_submission(id)? else {
+            return Ok(None);
+        };
+
+        let Some(tx) = wallet.history().await?.into_iter().find(|tx| {
+            tx.direction == TransactionDirection::Outgoing
+                && !tx.failed
+                && tx.amount == amount
+                && !submission.known_txids.contains(&tx.txid)
+        }) else {
+            return Ok(None);
+        };
+
+        let tx_key = wallet.tx_key(&tx.txid).await?;
+        let height = match tx.block_height {
+            Some(height) => height,
+            None => wallet.blockchain_height().await?,
+        };
+
+        Ok(Some(TxReceipt {
+            txid: tx.txid,
+            tx_key,
+            height,
+        }))
+    }
+}
+
+/// Sign the transfers which are dropped into `dir` with the full `wallet`, until
+/// the future is dropped.
+///
+/// Transfers the `policy` does not allow are rejected, the asb gives up on them.
+pub async fn run_signer(wallet: &Wallet, dir: &SigningDir, policy: &SigningPolicy) -> Result<()> {
+    if wallet.is_view_only().await {
+        bail!("The signer needs the full wallet, not a view-only wallet");
+    }
+
+    loop {
+        for id in dir.unsigned()? {
+            if let Err(error) = sign(wallet, dir, id, policy).await {
+                tracing::error!(%id, "Rejecting Monero transfer: {:#}", error);
+
+                std::fs::write(dir.file(id, REJECTED), format!("{:#}", error))
+                    .context("Failed to reject Monero transfer")?;
+            }
+        }
+
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+async fn sign(wallet: &Wallet, dir: &SigningDir, id: Uuid, policy: &SigningPolicy) -> Result<()> {
+    let unsigned = dir.file(id, UNSIGNED);
+
+    let manifest_path = dir.file(id, MANIFEST);
+    let manifest: Manifest = serde_json::from_slice(
+        &std::fs::read(&manifest_path)
+            .with_context(|| format!("Failed to read {}", manifest_path.display()))?,
+    )
+    .with_context(|| format!("Failed to parse {}", manifest_path.display()))?;
+
+    wallet
+        .import_outputs(&dir.file(id, OUTPUTS))
+        .await
+        .context("Failed to import outputs of the view-only wallet")?;
+
+    let transfer = wallet.inspect_unsigned_transaction(&unsigned).await?;
+
+    let now = unix_time();
+    let amount = policy.check(id, &manifest, &transfer, now)?;
+    let fee = sum(&transfer.fees);
+
+    tracing::info!(%id, recipients=?transfer.recipients, %amount, %fee, "Signing Monero transfer");
+
+    let temp_signed = dir.temp_file(id, SIGNED);
+    wallet
+        .sign_unsigned_transaction(&unsigned, &temp_signed)
+        .await?;
+
+    // The key images must be in place before the asb sees the signed transfer
+    let temp_key_images = dir.temp_file(id, KEY_IMAGES);
+    wallet.export_key_images(&temp_key_images).await?;
+    rename(&temp_key_images, &dir.file(id, KEY_IMAGES))?;
+
+    // Count the transfer against the limit before the asb can publish it
+    policy.record(id, amount, now)?;
+    rename(&temp_signed, &dir.file(id, SIGNED))?;
+
+    tracing::info!(%id, "Signed Monero transfer");
+
+    Ok(())
+}
+
+fn sum(amounts: &[monero::Amount]) -> monero::Amount {
+    amounts
+        .iter()
+        .fold(monero::Amount::ZERO, |total, amount| total + *amount)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+fn rename(from: &Path, to: &Path) -> Result<()> {
+    std::fs::rename(from, to)
+        .with_context(|| format!("Failed to move {} to {}", from.display(), to.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_unsigned_transfers_without_answer_are_pending() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = SigningDir::new(temp_dir.path().join("signing")).unwrap();
+
+        let pending = Uuid::new_v4();
+        let signed = Uuid::new_v4();
+        let rejected = Uuid::new_v4();
+        let in_progress = Uuid::new_v4();
+
+        for id in [pending, signed, rejected] {
+            std::fs::write(dir.file(id, UNSIGNED), b"").unwrap();
+        }
+        std::fs::write(dir.file(signed, SIGNED), b"").unwrap();
+        std::fs::write(dir.file(rejected, REJECTED), b"").unwrap();
+        std::fs::write(dir.temp_file(in_progress, UNSIGNED), b"").unwrap();
+        std::fs::write(temp_dir.path().join("signing/notes.unsigned"), b"").unwrap();
+
+        assert_eq!(dir.unsigned().unwrap(), vec![pending]);
+    }
+
+    #[test]
+    fn receipt_survives_reload() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = SigningDir::new(temp_dir.path().to_path_buf()).unwrap();
+        let id = Uuid::new_v4();
+
+        assert!(dir.load_receipt(id).unwrap().is_none());
+
+        dir.save_receipt(
+            id,
+            &TxReceipt {
+                txid: "txid".to_string(),
+                tx_key: "tx_key".to_string(),
+                height: 42,
+            },
+        )
+        .unwrap();
+
+        let receipt = dir.load_receipt(id).unwrap().unwrap();
+        assert_eq!(receipt.txid, "txid");
+        assert_eq!(receipt.tx_key, "tx_key");
+        assert_eq!(receipt.height, 42);
+    }
+
+    #[test]
+    fn stale_transfer_files_are_removed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = SigningDir::new(temp_dir.path().to_path_buf()).unwrap();
+        let id = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        for kind in [OUTPUTS, MANIFEST, UNSIGNED, SIGNED, KEY_IMAGES] {
+            std::fs::write(dir.file(id, kind), b"").unwrap();
+            std::fs::write(dir.file(other, kind), b"").unwrap();
+        }
+        std::fs::write(dir.file(id, REJECTED), b"").unwrap();
+        dir.save_submission(
+            id,
+            &Submission {
+                known_txids: HashSet::from(["txid".to_string()]),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            dir.load_submission(id).unwrap().unwrap().known_txids,
+            HashSet::from(["txid".to_string()])
+        );
+
+        dir.remove_transfer(id).unwrap();
+        // Removing it twice is fine, e.g. if we crashed while removing it
+        dir.remove_transfer(id).unwrap();
+
+        for kind in [OUTPUTS, MANIFEST, UNSIGNED, SIGNED, KEY_IMAGES, SUBMITTING] {
+            assert!(!dir.file(id, kind).exists(), "{} was not removed", kind);
+            assert!(kind == SUBMITTING || dir.file(other, kind).exists());
+        }
+        assert!(dir.file(id, REJECTED).exists());
+        assert!(dir.load_submission(id).unwrap().is_none());
+    }
+
+    const RECIPIENT: &str = "44Ato7HveWidJYUAVw5QffEcEtSH1DwzSP3FPPkHxNAS4LX9CqgucphTisH978FLHE34YNEx7FcbBfQLQUU8m3NUC4VqsRa";
+    const OTHER_RECIPIENT: &str = "4AdUndXHHZ6cfufTMvppY6JwXNouMBzSkbLYfpAV5Usx3skxNgYeYTRj5UzqtReoS44qo9mtmXCqY45DJ852K5Jv2684Rge";
+
+    fn xmr(amount: &str) -> monero::Amount {
+        monero::Amount::from_str_in(amount, monero::Denomination::Monero).unwrap()
+    }
+
+    fn transfer_to(recipient: &str, amounts: &[monero::Amount]) -> UnsignedTransfer {
+        UnsignedTransfer {
+            recipients: vec![recipient.parse().unwrap(); amounts.len()],
+            amounts: amounts.to_vec(),
+            fees: vec![xmr("0.0001"); amounts.len()],
+        }
+    }
+
+    fn manifest(policy: &SigningPolicy, id: Uuid, amount: &str) -> Manifest {
+        Manifest::new(
+            policy.manifest_key(),
+            id,
+            &RECIPIENT.parse().unwrap(),
+            xmr(amount),
+        )
+    }
+
+    #[test]
+    fn manifest_key_survives_reload() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join(MANIFEST_KEY_FILE);
+
+        let key = ManifestKey::load_or_generate(&path).unwrap();
+
+        assert_eq!(ManifestKey::load_or_generate(&path).unwrap(), key);
+        assert_eq!(key.to_string().parse::<ManifestKey>().unwrap(), key);
+        assert!(ManifestKey::load_or_generate(&temp_dir.path().join("other.key")).unwrap() != key);
+    }
+
+    #[test]
+    fn only_transfers_matching_their_manifest_are_signed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let policy = SigningPolicy::new(temp_dir.path(), None, None).unwrap();
+        let id = Uuid::new_v4();
+        let manifest = manifest(&policy, id, "1");
+
+        assert_eq!(
+            policy
+                .check(id, &manifest, &transfer_to(RECIPIENT, &[xmr("1")]), 0)
+                .unwrap(),
+            xmr("1")
+        );
+        // Split into multiple transactions
+        assert_eq!(
+            policy
+                .check(
+                    id,
+                    &manifest,
+                    &transfer_to(RECIPIENT, &[xmr("0.4"), xmr("0.6")]),
+                    0
+                )
+                .unwrap(),
+            xmr("1")
+        );
+
+        let other_recipient = transfer_to(OTHER_RECIPIENT, &[xmr("1")]);
+        let other_amount = transfer_to(RECIPIENT, &[xmr("2")]);
+        let no_recipient = transfer_to(RECIPIENT, &[]);
+        for transfer in [other_recipient, other_amount, no_recipient] {
+            assert!(policy.check(id, &manifest, &transfer, 0).is_err());
+        }
+
+        // The manifest of another transfer
+        assert!(policy
+            .check(
+                Uuid::new_v4(),
+                &manifest,
+                &transfer_to(RECIPIENT, &[xmr("1")]),
+                0
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn manifests_without_the_key_are_rejected() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let policy = SigningPolicy::new(&temp_dir.path().join("signer"), None, None).unwrap();
+        let attacker = SigningPolicy::new(&temp_dir.path().join("attacker"), None, None).unwrap();
+        let id = Uuid::new_v4();
+        let transfer = transfer_to(OTHER_RECIPIENT, &[xmr("1")]);
+
+        let forged = Manifest::new(
+            attacker.manifest_key(),
+            id,
+            &OTHER_RECIPIENT.parse().unwrap(),
+            xmr("1"),
+        );
+        assert!(policy.check(id, &forged, &transfer, 0).is_err());
+
+        let mut altered = manifest(&policy, id, "1");
+        altered.recipient = OTHER_RECIPIENT.to_string();
+        assert!(policy.check(id, &altered, &transfer, 0).is_err());
+    }
+
+    #[test]
+    fn signed_total_is_limited_within_the_window() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let limit = SigningLimit {
+            max_total: xmr("3"),
+            window: Duration::from_secs(3600),
+        };
+        let policy = SigningPolicy::new(temp_dir.path(), Some(xmr("2")), Some(limit)).unwrap();
+        let sign_at = |amount: &str, now: u64| {
+            let id = Uuid::new_v4();
+            let amount = policy.check(
+                id,
+                &manifest(&policy, id, amount),
+                &transfer_to(RECIPIENT, &[xmr(amount)]),
+                now,
+            )?;
+            policy.record(id, amount, now)
+        };
+
+        assert!(sign_at("2.5", 0).is_err(), "exceeds the maximum amount");
+        sign_at("2", 1000).unwrap();
+        sign_at("1", 2000).unwrap();
+        assert!(sign_at("0.1", 3000).is_err(), "exceeds the limit");
+
+        // The first transfer left the window
+        sign_at("2", 4601).unwrap();
+        assert!(sign_at("0.1", 4602).is_err(), "exceeds the limit");
+        assert_eq!(policy.load_signed_transfers().unwrap().len(), 2);
+    }
+}
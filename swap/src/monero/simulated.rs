@@ -0,0 +1,744 @@
+//! An in-process Monero blockchain for tests.
+//!
+//! [`Blockchain`] only tracks what the swap observes: which address received how
+//! much in which transaction, the transaction keys and the confirmations. There
+//! are no ring signatures or key images, a transfer simply spends the unlocked
+//! outputs of the sender.
+//!
+//! Blocks are only produced when asked to ([`Blockchain::mine`]) or by the miner
+//! started with [`Blockchain::start_mining`]. Use [`Wallets::simulated`](super::Wallets::simulated)
+//! to run the swap protocol against it.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{bail, Context, Result};
+use monero::{Address, Amount, Network, PrivateKey, PublicKey};
+use monero_sys::{Subaddress, TransactionDirection, TransactionInfo, TxReceipt};
+use rand::RngCore;
+use tokio_util::task::AbortOnDropHandle;
+
+use super::Scalar;
+
+/// Outputs can be spent once they have this many confirmations.
+pub const UNLOCK_CONFIRMATIONS: u64 = 10;
+
+/// Every transfer pays this fee (in piconero).
+const FEE: u64 = 30_000_000;
+
+/// How often we check the blockchain while waiting for confirmations.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A Monero blockchain which is kept in memory.
+#[derive(Clone, Default)]
+pub struct Blockchain {
+    chain: Arc<Mutex<Chain>>,
+}
+
+/// The main wallet of a participant, see [`Blockchain::wallet`].
+#[derive(Clone)]
+pub struct Wallet {
+    blockchain: Blockchain,
+    network: Network,
+    /// Index 0 is the main address.
+    subaddresses: Arc<Mutex<Vec<Subaddress>>>,
+}
+
+#[derive(Default)]
+struct Chain {
+    /// The number of blocks.
+    height: u64,
+    /// Confirmed transfers and the ones in the mempool, in the order they were published.
+    transfers: Vec<Transfer>,
+    /// The background miner does not produce blocks before this instant.
+    stalled_until: Option<Instant>,
+}
+
+struct Transfer {
+    txid: String,
+    tx_key: PrivateKey,
+    inputs: Vec<OutputId>,
+    outputs: Vec<Output>,
+    fee: Amount,
+    /// The height of the block the transfer is in, `None` while it is in the mempool.
+    block_height: Option<u64>,
+    timestamp: u64,
+}
+
+#[derive(Clone, PartialEq)]
+struct OutputId {
+    txid: String,
+    index: usize,
+}
+
+#[derive(Clone)]
+struct Output {
+    address: Address,
+    amount: Amount,
+}
+
+impl Blockchain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of blocks.
+    pub fn height(&self) -> u64 {
+        self.lock().height
+    }
+
+    /// Mine `blocks` blocks. The first one confirms all transfers in the mempool.
+    pub fn mine(&self, blocks: u64) {
+        let mut chain = self.lock();
+
+        if blocks == 0 {
+            return;
+        }
+
+        let height = chain.height;
+        for transfer in chain
+            .transfers
+            .iter_mut()
+            .filter(|transfer| transfer.block_height.is_none())
+        {
+            transfer.block_height = Some(height);
+        }
+
+        chain.height += blocks;
+    }
+
+    /// Mine a block every `interval` until the returned handle is dropped.
+    pub fn start_mining(&self, interval: Duration) -> AbortOnDropHandle<()> {
+        let blockchain = self.clone();
+
+        AbortOnDropHandle::new(tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                if !blockchain.is_stalled() {
+                    blockchain.mine(1);
+                }
+            }
+        }))
+    }
+
+    /// Stop the miner started with [`Blockchain::start_mining`] for `duration`.
+    ///
+    /// Blocks can still be mined explicitly with [`Blockchain::mine`].
+    pub fn stall(&self, duration: Duration) {
+        self.lock().stalled_until = Some(Instant::now() + duration);
+    }
+
+    fn is_stalled(&self) -> bool {
+        self.lock()
+            .stalled_until
+            .is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Replace the last `depth` blocks with a longer chain of empty blocks.
+    ///
+    /// The transfers of the replaced blocks go back to the mempool.
+    pub fn reorg(&self, depth: u64) {
+        let mut chain = self.lock();
+
+        let fork_height = chain.height.saturating_sub(depth);
+        for transfer in chain.transfers.iter_mut() {
+            if transfer.block_height >= Some(fork_height) {
+                transfer.block_height = None;
+            }
+        }
+
+        chain.height += 1;
+    }
+
+    /// Drop a transfer from the mempool, as if it was never published.
+    ///
+    /// Transfers spending its outputs are dropped as well. Returns whether the
+    /// transfer was in the mempool.
+    pub fn evict(&self, txid: &str) -> bool {
+        let mut chain = self.lock();
+
+        let mut evicted = vec![txid.to_string()];
+        let mut found = false;
+
+        while let Some(txid) = evicted.pop() {
+            let Some(position) = chain
+                .transfers
+                .iter()
+                .position(|transfer| transfer.txid == txid && transfer.block_height.is_none())
+            else {
+                continue;
+            };
+
+            found = true;
+            chain.transfers.remove(position);
+
+            evicted.extend(
+                chain
+                    .transfers
+                    .iter()
+                    .filter(|transfer| transfer.inputs.iter().any(|input| input.txid == txid))
+                    .map(|transfer| transfer.txid.clone()),
+            );
+        }
+
+        found
+    }
+
+    /// Send `amount` out of thin air to `address` and mine until it is unlocked.
+    pub fn fund(&self, address: &Address, amount: Amount) -> String {
+        let txid = {
+            let mut chain = self.lock();
+            let transfer = Transfer::new(
+                vec![],
+                vec![Output {
+                    address: *address,
+                    amount,
+                }],
+                Amount::ZERO,
+            );
+            let txid = transfer.txid.clone();
+            chain.transfers.push(transfer);
+            txid
+        };
+
+        self.mine(UNLOCK_CONFIRMATIONS);
+
+        txid
+    }
+
+    /// The balance of `address`, including unconfirmed transfers.
+    pub fn balance(&self, address: &Address) -> Amount {
+        self.lock()
+            .unspent(|owner| same_keys(owner, address), false)
+            .into_iter()
+            .fold(Amount::ZERO, |total, (_, output)| total + output.amount)
+    }
+
+    /// The number of confirmations of a transfer, `None` if we don't know it.
+    pub fn confirmations(&self, txid: &str) -> Option<u64> {
+        let chain = self.lock();
+
+        chain
+            .transfer(txid)
+            .map(|transfer| chain.confirmations(transfer.block_height))
+    }
+
+    /// Create a wallet with a random main address.
+    pub fn wallet(&self, network: Network) -> Wallet {
+        let main_address = Address::standard(network, random_public_key(), random_public_key());
+
+        Wallet {
+            blockchain: self.clone(),
+            network,
+            subaddresses: Arc::new(Mutex::new(vec![Subaddress {
+                account_index: 0,
+                address_index: 0,
+                address: main_address,
+                label: String::new(),
+            }])),
+        }
+    }
+
+    /// Send all unlocked funds of `from`, minus the fee, to `addresses`.
+    ///
+    /// The funds are split according to `percentages`, the last address gets the remainder.
+    pub fn sweep(
+        &self,
+        from: &Address,
+        addresses: &[Address],
+        percentages: &[f64],
+    ) -> Result<Vec<TxReceipt>> {
+        if addresses.is_empty() || addresses.len() != percentages.len() {
+            bail!("Need one percentage for each address to sweep to");
+        }
+
+        let mut chain = self.lock();
+
+        let inputs = chain.unspent(|owner| same_keys(owner, from), true);
+        let total = inputs
+            .iter()
+            .fold(Amount::ZERO, |total, (_, output)| total + output.amount);
+
+        let Some(available) = total.checked_sub(Amount::from_pico(FEE)) else {
+            bail!("Not enough unlocked funds to sweep {}", from);
+        };
+
+        let mut outputs = Vec::new();
+        let mut remaining = available;
+        for (index, (address, percentage)) in addresses.iter().zip(percentages).enumerate() {
+            let amount = if index == addresses.len() - 1 {
+                remaining
+            } else {
+                Amount::from_pico((available.as_pico() as f64 * percentage / 100.0).floor() as u64)
+            };
+
+            remaining = remaining
+                .checked_sub(amount)
+                .context("Percentages of the sweep add up to more than 100")?;
+            outputs.push(Output {
+                address: *address,
+                amount,
+            });
+        }
+
+        Ok(vec![chain.publish(
+            inputs.into_iter().map(|(id, _)| id).collect(),
+            outputs,
+        )])
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Chain> {
+        self.chain
+            .lock()
+            .expect("simulated Monero blockchain lock not to be poisoned")
+    }
+}
+
+impl Wallet {
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    pub fn main_address(&self) -> Address {
+        self.subaddresses()[0].address
+    }
+
+    pub fn subaddresses(&self) -> Vec<Subaddress> {
+        self.subaddresses
+            .lock()
+            .expect("subaddress lock not to be poisoned")
+            .clone()
+    }
+
+    pub fn create_subaddress(&self, label: &str) -> Subaddress {
+        let mut subaddresses = self
+            .subaddresses
+            .lock()
+            .expect("subaddress lock not to be poisoned");
+
+        let subaddress = Subaddress {
+            account_index: 0,
+            address_index: subaddresses.len() as u32,
+            address: Address::subaddress(self.network, random_public_key(), random_public_key()),
+            label: label.to_string(),
+        };
+        subaddresses.push(subaddress.clone());
+
+        subaddress
+    }
+
+    pub fn blockchain_height(&self) -> u64 {
+        self.blockchain.height()
+    }
+
+    pub fn total_balance(&self) -> Amount {
+        self.balance(false)
+    }
+
+    pub fn unlocked_balance(&self) -> Amount {
+        self.balance(true)
+    }
+
+    /// Transfer `amount` to `address`, the change goes back to the main address.
+    pub fn transfer(&self, address: &Address, amount: Amount) -> Result<TxReceipt> {
+        let subaddresses = self.subaddresses();
+        let mut chain = self.blockchain.lock();
+
+        let needed = amount + Amount::from_pico(FEE);
+        let mut inputs = Vec::new();
+        let mut total = Amount::ZERO;
+
+        for (id, output) in chain.unspent(|owner| owns(&subaddresses, owner), true) {
+            if total >= needed {
+                break;
+            }
+
+            total += output.amount;
+            inputs.push(id);
+        }
+
+        if total < needed {
+            bail!(
+                "Not enough unlocked balance to transfer {}, have {}",
+                amount,
+                total
+            );
+        }
+
+        let mut outputs = vec![Output {
+            address: *address,
+            amount,
+        }];
+        if total > needed {
+            outputs.push(Output {
+                address: subaddresses[0].address,
+                amount: total - needed,
+            });
+        }
+
+        Ok(chain.publish(inputs, outputs))
+    }
+
+    /// The transfers which received to or spent from this wallet.
+    pub fn history(&self) -> Vec<TransactionInfo> {
+        let subaddresses = self.subaddresses();
+        let chain = self.blockchain.lock();
+
+        let indices = |addresses: &mut dyn Iterator<Item = &Address>| {
+            let mut indices = addresses
+                .filter_map(|address| {
+                    subaddresses
+                        .iter()
+                        .find(|subaddress| same_keys(&subaddress.address, address))
+                        .map(|subaddress| subaddress.address_index)
+                })
+                .collect::<Vec<_>>();
+            indices.sort_unstable();
+            indices.dedup();
+            indices
+        };
+
+        chain
+            .transfers
+            .iter()
+            .filter_map(|transfer| {
+                let spent_from = indices(
+                    &mut transfer
+                        .inputs
+                        .iter()
+                        .filter_map(|input| chain.output(input))
+                        .map(|output| &output.address),
+                );
+                let received_to =
+                    indices(&mut transfer.outputs.iter().map(|output| &output.address));
+
+                let (direction, amount, subaddress_indices) = if !spent_from.is_empty() {
+                    let sent = transfer
+                        .outputs
+                        .iter()
+                        .filter(|output| !owns(&subaddresses, &output.address))
+                        .fold(Amount::ZERO, |total, output| total + output.amount);
+                    (TransactionDirection::Outgoing, sent, spent_from)
+                } else if !received_to.is_empty() {
+                    let received = transfer
+                        .outputs
+                        .iter()
+                        .filter(|output| owns(&subaddresses, &output.address))
+                        .fold(Amount::ZERO, |total, output| total + output.amount);
+                    (TransactionDirection::Incoming, received, received_to)
+                } else {
+                    return None;
+                };
+
+                Some(TransactionInfo {
+                    txid: transfer.txid.clone(),
+                    direction,
+                    amount,
+                    fee: transfer.fee,
+                    block_height: transfer.block_height,
+                    confirmations: chain.confirmations(transfer.block_height),
+                    timestamp: transfer.timestamp,
+                    pending: transfer.block_height.is_none(),
+                    failed: false,
+                    account_index: 0,
+                    subaddress_indices,
+                })
+            })
+            .collect()
+    }
+
+    /// Wait until the transfer `txid` sent `expected_amount` to `address` and has
+    /// `confirmations` confirmations.
+    ///
+    /// Behaves like the real wallet: we keep waiting while we don't know the
+    /// transfer and fail if it sent a different amount (or `tx_key` is wrong).
+    pub async fn wait_until_confirmed(
+        &self,
+        txid: &str,
+        tx_key: PrivateKey,
+        address: &Address,
+        expected_amount: Amount,
+        confirmations: u64,
+        listener: Option<impl Fn((u64, u64)) + Send + 'static>,
+    ) -> Result<()> {
+        loop {
+            let status = {
+                let chain = self.blockchain.lock();
+
+                chain.transfer(txid).map(|transfer| {
+                    let received = match transfer.tx_key == tx_key {
+                        true => transfer
+                            .outputs
+                            .iter()
+                            .filter(|output| same_keys(&output.address, address))
+                            .fold(Amount::ZERO, |total, output| total + output.amount),
+                        false => Amount::ZERO,
+                    };
+
+                    (received, chain.confirmations(transfer.block_height))
+                })
+            };
+
+            if let Some((received, current_confirmations)) = status {
+                if received != expected_amount {
+                    bail!(
+                        "Transaction received amount mismatch: expected {}, got {}",
+                        expected_amount,
+                        received
+                    );
+                }
+
+                if let Some(listener) = &listener {
+                    listener((current_confirmations, confirmations));
+                }
+
+                if current_confirmations >= confirmations {
+                    return Ok(());
+                }
+            }
+
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Sweep the wallet with the given keys, like the lock wallet of a swap.
+    pub fn sweep_from_keys(
+        &self,
+        spend_key: PrivateKey,
+        view_key: PrivateKey,
+        addresses: &[Address],
+        percentages: &[f64],
+    ) -> Result<Vec<TxReceipt>> {
+        let from = Address::standard(
+            self.network,
+            PublicKey::from_private_key(&spend_key),
+            PublicKey::from_private_key(&view_key),
+        );
+
+        self.blockchain.sweep(&from, addresses, percentages)
+    }
+
+    fn balance(&self, unlocked_only: bool) -> Amount {
+        let subaddresses = self.subaddresses();
+
+        self.blockchain
+            .lock()
+            .unspent(|owner| owns(&subaddresses, owner), unlocked_only)
+            .into_iter()
+            .fold(Amount::ZERO, |total, (_, output)| total + output.amount)
+    }
+}
+
+impl Chain {
+    fn confirmations(&self, block_height: Option<u64>) -> u64 {
+        block_height.map_or(0, |block_height| self.height - block_height)
+    }
+
+    fn transfer(&self, txid: &str) -> Option<&Transfer> {
+        self.transfers.iter().find(|transfer| transfer.txid == txid)
+    }
+
+    fn output(&self, id: &OutputId) -> Option<&Output> {
+        self.transfer(&id.txid)
+            .and_then(|transfer| transfer.outputs.get(id.index))
+    }
+
+    fn is_spent(&self, id: &OutputId) -> bool {
+        self.transfers
+            .iter()
+            .any(|transfer| transfer.inputs.contains(id))
+    }
+
+    /// The outputs of the addresses matching `owner` which are not spent yet.
+    fn unspent(
+        &self,
+        owner: impl Fn(&Address) -> bool,
+        unlocked_only: bool,
+    ) -> Vec<(OutputId, Output)> {
+        self.transfers
+            .iter()
+            .filter(|transfer| {
+                !unlocked_only || self.confirmations(transfer.block_height) >= UNLOCK_CONFIRMATIONS
+            })
+            .flat_map(|transfer| {
+                transfer.outputs.iter().enumerate().map(|(index, output)| {
+                    (
+                        OutputId {
+                            txid: transfer.txid.clone(),
+                            index,
+                        },
+                        output.clone(),
+                    )
+                })
+            })
+            .filter(|(id, output)| owner(&output.address) && !self.is_spent(id))
+            .collect()
+    }
+
+    /// Put a transfer into the mempool.
+    fn publish(&mut self, inputs: Vec<OutputId>, outputs: Vec<Output>) -> TxReceipt {
+        let transfer = Transfer::new(inputs, outputs, Amount::from_pico(FEE));
+
+        let receipt = TxReceipt {
+            txid: transfer.txid.clone(),
+            tx_key: transfer.tx_key.to_string(),
+            height: self.height,
+        };
+        self.transfers.push(transfer);
+
+        receipt
+    }
+}
+
+impl Transfer {
+    fn new(inputs: Vec<OutputId>, outputs: Vec<Output>, fee: Amount) -> Self {
+        let mut txid = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut txid);
+
+        Self {
+            txid: hex::encode(txid),
+            tx_key: random_private_key(),
+            inputs,
+            outputs,
+            fee,
+            block_height: None,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs()),
+        }
+    }
+}
+
+/// Whether the addresses have the same keys, regardless of the network.
+fn same_keys(a: &Address, b: &Address) -> bool {
+    a.public_spend == b.public_spend && a.public_view == b.public_view
+}
+
+fn owns(subaddresses: &[Subaddress], address: &Address) -> bool {
+    subaddresses
+        .iter()
+        .any(|subaddress| same_keys(&subaddress.address, address))
+}
+
+fn random_private_key() -> PrivateKey {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+
+    PrivateKey::from_scalar(Scalar::from_bytes_mod_order(bytes))
+}
+
+fn random_public_key() -> PublicKey {
+    PublicKey::from_private_key(&random_private_key())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn xmr(amount: f64) -> Amount {
+        Amount::from_xmr(amount).unwrap()
+    }
+
+    #[test]
+    fn funds_are_spendable_after_unlock_confirmations() {
+        let blockchain = Blockchain::new();
+        let alice = blockchain.wallet(Network::Mainnet);
+        let bob = blockchain.wallet(Network::Mainnet);
+
+        blockchain.fund(&alice.main_address(), xmr(1.0));
+        assert_eq!(alice.unlocked_balance(), xmr(1.0));
+
+        let receipt = alice.transfer(&bob.main_address(), xmr(0.4)).unwrap();
+        assert_eq!(bob.total_balance(), xmr(0.4));
+        assert_eq!(bob.unlocked_balance(), Amount::ZERO);
+        assert_eq!(alice.total_balance(), xmr(0.6) - Amount::from_pico(FEE));
+
+        blockchain.mine(UNLOCK_CONFIRMATIONS - 1);
+        assert_eq!(blockchain.confirmations(&receipt.txid), Some(9));
+        assert_eq!(bob.unlocked_balance(), Amount::ZERO);
+
+        blockchain.mine(1);
+        assert_eq!(bob.unlocked_balance(), xmr(0.4));
+    }
+
+    #[test]
+    fn reorg_and_eviction_unconfirm_transfers() {
+        let blockchain = Blockchain::new();
+        let alice = blockchain.wallet(Network::Mainnet);
+        let bob = blockchain.wallet(Network::Mainnet);
+
+        blockchain.fund(&alice.main_address(), xmr(1.0));
+        let receipt = alice.transfer(&bob.main_address(), xmr(0.4)).unwrap();
+        blockchain.mine(2);
+
+        blockchain.reorg(2);
+        assert_eq!(blockchain.confirmations(&receipt.txid), Some(0));
+
+        assert!(blockchain.evict(&receipt.txid));
+        assert_eq!(blockchain.confirmations(&receipt.txid), None);
+        assert_eq!(bob.total_balance(), Amount::ZERO);
+        assert_eq!(alice.total_balance(), xmr(1.0));
+    }
+
+    #[test]
+    fn sweep_splits_by_percentages() {
+        let blockchain = Blockchain::new();
+        let wallet = blockchain.wallet(Network::Mainnet);
+        let spend_key = random_private_key();
+        let view_key = random_private_key();
+        let lock_address = Address::standard(
+            Network::Mainnet,
+            PublicKey::from_private_key(&spend_key),
+            PublicKey::from_private_key(&view_key),
+        );
+        let first = wallet.create_subaddress("first").address;
+        let second = wallet.create_subaddress("second").address;
+
+        blockchain.fund(&lock_address, xmr(1.0) + Amount::from_pico(FEE));
+        wallet
+            .sweep_from_keys(spend_key, view_key, &[first, second], &[25.0, 75.0])
+            .unwrap();
+
+        assert_eq!(blockchain.balance(&lock_address), Amount::ZERO);
+        assert_eq!(blockchain.balance(&first), xmr(0.25));
+        assert_eq!(blockchain.balance(&second), xmr(0.75));
+        assert_eq!(wallet.total_balance(), xmr(1.0));
+    }
+
+    #[tokio::test]
+    async fn wait_until_confirmed_checks_amount() {
+        let blockchain = Blockchain::new();
+        let alice = blockchain.wallet(Network::Mainnet);
+        let bob = blockchain.wallet(Network::Mainnet);
+
+        blockchain.fund(&alice.main_address(), xmr(1.0));
+        let receipt = alice.transfer(&bob.main_address(), xmr(0.4)).unwrap();
+        let tx_key: PrivateKey = receipt.tx_key.parse().unwrap();
+
+        let error = bob
+            .wait_until_confirmed(
+                &receipt.txid,
+                tx_key,
+                &bob.main_address(),
+                xmr(0.5),
+                1,
+                None::<fn((u64, u64))>,
+            )
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("mismatch"));
+
+        let _miner = blockchain.start_mining(Duration::from_millis(10));
+        bob.wait_until_confirmed(
+            &receipt.txid,
+            tx_key,
+            &bob.main_address(),
+            xmr(0.4),
+            3,
+            None::<fn((u64, u64))>,
+        )
+        .await
+        .unwrap();
+    }
+}
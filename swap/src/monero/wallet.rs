@@ -5,16 +5,29 @@
 //!  - wait for transactions to be confirmed
 //!  - send money from one wallet to another.
 
-use std::{path::PathBuf, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use anyhow::{Context, Result};
 use monero::{Address, Network};
 pub use monero_sys::{Daemon, WalletHandle as Wallet};
+use monero_sys::{Subaddress, SyncProgress, TransactionDirection, TransactionInfo, TxReceipt};
+use serde::{Deserialize, Serialize};
+use typeshare::typeshare;
 use uuid::Uuid;
 
 use crate::cli::api::tauri_bindings::TauriHandle;
 
-use super::{BlockHeight, TransferProof, TxHash};
+use super::offline_signing::OfflineSigner;
+#[cfg(feature = "test-utils")]
+use super::simulated;
+use super::{Amount, BlockHeight, TransferProof, TxHash};
+
+/// We only use the first account of the main wallet.
+const MAIN_ACCOUNT_INDEX: u32 = 0;
+
+/// Subaddresses of the main wallet which receive the Monero of a swap are
+/// labeled with this prefix followed by the swap id.
+const SWAP_SUBADDRESS_LABEL_PREFIX: &str = "swap:";
 
 /// Entrance point to the Monero blockchain.
 /// You can use this struct to open specific wallets and monitor the blockchain.
@@ -26,16 +39,26 @@ pub struct Wallets {
     /// The monero node we connect to.
     daemon: Daemon,
     /// Keep the main wallet open and synced.
-    main_wallet: Arc<Wallet>,
+    main_wallet: MainWallet,
     /// Since Network::Regtest isn't a thing we have to use an extra flag.
     /// When we're in regtest mode, we need to unplug some safty nets to make the wallet work.
     regtest: bool,
+    /// Set if the main wallet is view-only and its transfers are signed elsewhere.
+    offline_signer: Option<OfflineSigner>,
     /// A handle we use to send status updates to the UI i.e. when
     /// waiting for a transaction to be confirmed.
     #[expect(dead_code)]
     tauri_handle: Option<TauriHandle>,
 }
 
+/// The main wallet is either a real wallet or one on a simulated blockchain (for tests).
+#[derive(Clone)]
+enum MainWallet {
+    Wallet2(Arc<Wallet>),
+    #[cfg(feature = "test-utils")]
+    Simulated(simulated::Wallet),
+}
+
 /// A request to watch for a transfer.
 pub struct WatchRequest {
     pub public_view_key: super::PublicViewKey,
@@ -48,6 +71,54 @@ pub struct WatchRequest {
     pub confirmation_target: u64,
 }
 
+/// A transfer from or to the main wallet.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoneroTransfer {
+    pub txid: String,
+    pub direction: MoneroTransferDirection,
+    /// The amount that was received or sent, excluding the fee and change.
+    pub amount: Amount,
+    pub fee: Amount,
+    /// `None` while the transfer is in the mempool.
+    #[typeshare(serialized_as = "number")]
+    pub block_height: Option<u64>,
+    #[typeshare(serialized_as = "number")]
+    pub confirmations: u64,
+    /// Seconds since the unix epoch.
+    #[typeshare(serialized_as = "number")]
+    pub timestamp: u64,
+    pub pending: bool,
+    pub failed: bool,
+    /// The subaddresses of the main account the transfer received to or spent from.
+    pub subaddress_indices: Vec<u32>,
+    /// The swap whose subaddress received this transfer, if any.
+    #[typeshare(serialized_as = "Option<string>")]
+    pub swap_id: Option<Uuid>,
+}
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoneroTransferDirection {
+    Incoming,
+    Outgoing,
+}
+
+/// A subaddress of the main account of the main wallet.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MoneroSubaddress {
+    /// Index 0 is the main address.
+    pub address_index: u32,
+    #[typeshare(serialized_as = "string")]
+    pub address: Address,
+    pub label: String,
+    /// The swap this subaddress was created for, if any.
+    #[typeshare(serialized_as = "Option<string>")]
+    pub swap_id: Option<Uuid>,
+}
+
 /// Transfer a specified amount of money to a specified address.
 pub struct TransferRequest {
     pub public_spend_key: monero::PublicKey,
@@ -83,7 +154,7 @@ impl Wallets {
             main_wallet.unsafe_prepare_for_regtest().await;
         }
 
-        let main_wallet = Arc::new(main_wallet);
+        let main_wallet = MainWallet::Wallet2(Arc::new(main_wallet));
 
         let wallets = Self {
             wallet_dir,
@@ -91,14 +162,77 @@ impl Wallets {
             daemon,
             main_wallet,
             regtest,
+            offline_signer: None,
+            tauri_handle,
+        };
+
+        Ok(wallets)
+    }
+
+    /// Create a new `Wallets` instance whose main wallet is view-only.
+    ///
+    /// The main wallet can watch the balance but can't spend it, transfers are
+    /// handed to the `offline_signer` instead.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn new_view_only(
+        wallet_dir: PathBuf,
+        main_wallet_name: String,
+        daemon: Daemon,
+        network: Network,
+        address: Address,
+        view_key: monero::PrivateKey,
+        restore_height: u64,
+        offline_signer: OfflineSigner,
+        tauri_handle: Option<TauriHandle>,
+    ) -> Result<Self> {
+        let main_wallet = Wallet::open_or_create_view_only(
+            wallet_dir.join(&main_wallet_name).display().to_string(),
+            None,
+            network,
+            address,
+            view_key,
+            restore_height,
+            true,
+            daemon.clone(),
+        )
+        .await
+        .context("Failed to open view-only main wallet")?;
+
+        let wallets = Self {
+            wallet_dir,
+            network,
+            daemon,
+            main_wallet: MainWallet::Wallet2(Arc::new(main_wallet)),
+            regtest: false,
+            offline_signer: Some(offline_signer),
             tauri_handle,
         };
 
         Ok(wallets)
     }
 
+    /// Create a new `Wallets` instance on a [simulated blockchain](simulated::Blockchain).
+    ///
+    /// Meant for tests, there is no daemon and no wallet files. Use
+    /// [`Wallets::sweep_swap_wallet`] instead of [`Wallets::swap_wallet`].
+    #[cfg(feature = "test-utils")]
+    pub fn simulated(main_wallet: simulated::Wallet) -> Self {
+        Self {
+            wallet_dir: PathBuf::new(),
+            network: main_wallet.network(),
+            daemon: Daemon::default(),
+            main_wallet: MainWallet::Simulated(main_wallet),
+            regtest: false,
+            offline_signer: None,
+            tauri_handle: None,
+        }
+    }
+
     /// Open the lock wallet of a specific swap.
     /// Used to redeem (Bob) or refund (Alice) the Monero.
+    ///
+    /// Not supported if the main wallet is [simulated](Wallets::simulated), there are no
+    /// wallet files. Use [`Wallets::sweep_swap_wallet`] instead.
     pub async fn swap_wallet(
         &self,
         swap_id: Uuid,
@@ -158,18 +292,219 @@ impl Wallets {
         Ok(Arc::new(wallet))
     }
 
+    /// Sweep the lock wallet of a specific swap to `addresses`, split by `percentages`.
+    /// Used to redeem (Bob) or refund (Alice) the Monero.
+    pub async fn sweep_swap_wallet(
+        &self,
+        swap_id: Uuid,
+        spend_key: monero::PrivateKey,
+        view_key: super::PrivateViewKey,
+        tx_lock_id: TxHash,
+        addresses: &[Address],
+        percentages: &[f64],
+    ) -> Result<Vec<TxReceipt>> {
+        #[cfg(feature = "test-utils")]
+        if let MainWallet::Simulated(wallet) = &self.main_wallet {
+            return wallet.sweep_from_keys(spend_key, view_key.into(), addresses, percentages);
+        }
+
+        tracing::debug!(%swap_id, "Opening temporary Monero wallet from keys");
+        let wallet = self
+            .swap_wallet(swap_id, spend_key, view_key, tx_lock_id)
+            .await
+            .context(format!("Failed to open/create swap wallet `{}`", swap_id))?;
+
+        // Update blockheight to ensure that the wallet knows the funds are unlocked
+        tracing::debug!(%swap_id, "Updating temporary Monero wallet's blockheight");
+        let _ = wallet
+            .blockchain_height()
+            .await
+            .context("Couldn't get Monero blockheight")?;
+
+        match addresses {
+            [address] => wallet.sweep(address).await,
+            _ => wallet.sweep_multi(addresses, percentages).await,
+        }
+    }
+
     /// Get the main wallet (specified when initializing the `Wallets` instance).
-    pub async fn main_wallet(&self) -> Arc<Wallet> {
-        self.main_wallet.clone()
+    ///
+    /// Fails if the main wallet is [simulated](Wallets::simulated), prefer the
+    /// methods on `Wallets` where possible.
+    pub async fn main_wallet(&self) -> Result<Arc<Wallet>> {
+        match &self.main_wallet {
+            MainWallet::Wallet2(wallet) => Ok(wallet.clone()),
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(_) => {
+                anyhow::bail!("The simulated main wallet is not a wallet2")
+            }
+        }
+    }
+
+    /// Get the main address of the main wallet.
+    pub async fn main_address(&self) -> Address {
+        self.main_wallet.main_address().await
+    }
+
+    /// The monero node the wallets connect to.
+    pub fn daemon(&self) -> &Daemon {
+        &self.daemon
+    }
+
+    /// Wait until the main wallet is synced with the daemon.
+    ///
+    /// Returns immediately if the main wallet is [simulated](Wallets::simulated).
+    pub async fn wait_until_synced(
+        &self,
+        listener: Option<impl Fn(SyncProgress) + Send + 'static>,
+    ) -> Result<()> {
+        match &self.main_wallet {
+            MainWallet::Wallet2(wallet) => wallet.wait_until_synced(listener).await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(_) => Ok(()),
+        }
+    }
+
+    /// How far the main wallet is synced.
+    ///
+    /// A [simulated](Wallets::simulated) main wallet is always synced.
+    pub async fn sync_progress(&self) -> SyncProgress {
+        match &self.main_wallet {
+            MainWallet::Wallet2(wallet) => wallet.sync_progress().await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => SyncProgress {
+                current_block: wallet.blockchain_height(),
+                target_block: wallet.blockchain_height(),
+            },
+        }
+    }
+
+    /// Get the unlocked balance of the main wallet.
+    pub async fn unlocked_balance(&self) -> monero::Amount {
+        self.main_wallet.unlocked_balance().await
+    }
+
+    /// Get the total balance of the main wallet, including locked funds.
+    pub async fn total_balance(&self) -> monero::Amount {
+        self.main_wallet.total_balance().await
+    }
+
+    /// Lock the Monero of a swap by transferring `amount` from the main wallet to `address`.
+    ///
+    /// If the main wallet is view-only this waits for the offline signer, which
+    /// may time out. Retrying with the same swap id never creates a second transfer.
+    pub async fn lock_transfer(
+        &self,
+        swap_id: Uuid,
+        address: &Address,
+        amount: monero::Amount,
+    ) -> Result<TxReceipt> {
+        match (&self.offline_signer, &self.main_wallet) {
+            (Some(offline_signer), MainWallet::Wallet2(main_wallet)) => {
+                offline_signer
+                    .transfer(main_wallet, swap_id, address, amount)
+                    .await
+            }
+            _ => self.main_wallet.transfer(address, amount).await,
+        }
+    }
+
+    /// Get the subaddress of the main wallet which receives the Monero of a swap.
+    ///
+    /// The subaddress is created on first use and labeled with the swap id, such
+    /// that we can tell which transfers belong to which swap.
+    pub async fn swap_subaddress(&self, swap_id: Uuid) -> Result<Address> {
+        let label = swap_subaddress_label(swap_id);
+
+        let existing = self
+            .main_wallet
+            .subaddresses(MAIN_ACCOUNT_INDEX)
+            .await
+            .context("Failed to list subaddresses")?
+            .into_iter()
+            .find(|subaddress| subaddress.label == label);
+
+        if let Some(subaddress) = existing {
+            return Ok(subaddress.address);
+        }
+
+        let subaddress = self
+            .main_wallet
+            .create_subaddress(MAIN_ACCOUNT_INDEX, &label)
+            .await
+            .context("Failed to create subaddress")?;
+
+        tracing::debug!(%swap_id, address=%subaddress.address, index=subaddress.address_index, "Created Monero subaddress for swap");
+
+        Ok(subaddress.address)
+    }
+
+    /// Get the address to receive the Monero of a swap into the main wallet.
+    ///
+    /// This is the subaddress of the swap, falls back to the main address if we
+    /// fail to create it. Receiving funds must never fail because of a label.
+    pub async fn swap_receive_address(&self, swap_id: Uuid) -> Address {
+        match self.swap_subaddress(swap_id).await {
+            Ok(address) => address,
+            Err(error) => {
+                tracing::warn!(%swap_id, "Failed to get Monero subaddress for swap, using the main address instead: {:#}", error);
+                self.main_wallet.main_address().await
+            }
+        }
+    }
+
+    /// List the subaddresses of the main wallet.
+    pub async fn subaddresses(&self) -> Result<Vec<MoneroSubaddress>> {
+        let subaddresses = self
+            .main_wallet
+            .subaddresses(MAIN_ACCOUNT_INDEX)
+            .await
+            .context("Failed to list subaddresses")?
+            .into_iter()
+            .map(|subaddress| MoneroSubaddress {
+                address_index: subaddress.address_index,
+                address: subaddress.address,
+                swap_id: parse_swap_subaddress_label(&subaddress.label),
+                label: subaddress.label,
+            })
+            .collect();
+
+        Ok(subaddresses)
+    }
+
+    /// List the incoming and outgoing transfers of the main wallet, newest first.
+    ///
+    /// Transfers received to the subaddress of a swap are linked to that swap.
+    pub async fn transfers(&self) -> Result<Vec<MoneroTransfer>> {
+        let swap_subaddresses: HashMap<u32, Uuid> = self
+            .subaddresses()
+            .await?
+            .into_iter()
+            .filter_map(|subaddress| Some((subaddress.address_index, subaddress.swap_id?)))
+            .collect();
+
+        let mut transfers = self
+            .main_wallet
+            .history()
+            .await
+            .context("Failed to get transaction history")?
+            .into_iter()
+            .filter(|tx| tx.account_index == MAIN_ACCOUNT_INDEX)
+            .map(|tx| MoneroTransfer::new(tx, &swap_subaddresses))
+            .collect::<Vec<_>>();
+
+        // Unconfirmed transfers first, then by descending height
+        transfers
+            .sort_by_key(|transfer| std::cmp::Reverse(transfer.block_height.unwrap_or(u64::MAX)));
+
+        Ok(transfers)
     }
 
     /// Get the current blockchain height.
     /// May fail if not connected to a daemon.
     pub async fn blockchain_height(&self) -> Result<BlockHeight> {
-        let wallet = self.main_wallet().await;
-
         Ok(BlockHeight {
-            height: wallet.blockchain_height().await.context(
+            height: self.main_wallet.blockchain_height().await.context(
                 "Failed to get blockchain height: wallet manager not connected to daemon",
             )?,
         })
@@ -185,15 +520,13 @@ impl Wallets {
         watch_request: WatchRequest,
         listener: Option<impl Fn((u64, u64)) + Send + 'static>,
     ) -> Result<()> {
-        let wallet = self.main_wallet().await;
-
         let address = Address::standard(
             self.network,
             watch_request.public_spend_key,
             watch_request.public_view_key.0,
         );
 
-        wallet
+        self.main_wallet
             .wait_until_confirmed(
                 watch_request.transfer_proof.tx_hash.0.clone(),
                 watch_request.transfer_proof.tx_key,
@@ -218,6 +551,153 @@ impl Wallets {
     }
 }
 
+impl MainWallet {
+    async fn main_address(&self) -> Address {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.main_address().await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => wallet.main_address(),
+        }
+    }
+
+    async fn blockchain_height(&self) -> Result<u64> {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.blockchain_height().await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => Ok(wallet.blockchain_height()),
+        }
+    }
+
+    async fn unlocked_balance(&self) -> monero::Amount {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.unlocked_balance().await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => wallet.unlocked_balance(),
+        }
+    }
+
+    async fn total_balance(&self) -> monero::Amount {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.total_balance().await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => wallet.total_balance(),
+        }
+    }
+
+    async fn transfer(&self, address: &Address, amount: monero::Amount) -> Result<TxReceipt> {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.transfer(address, amount).await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => wallet.transfer(address, amount),
+        }
+    }
+
+    async fn history(&self) -> Result<Vec<TransactionInfo>> {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.history().await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => Ok(wallet.history()),
+        }
+    }
+
+    async fn subaddresses(&self, account_index: u32) -> Result<Vec<Subaddress>> {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.subaddresses(account_index).await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => Ok(wallet.subaddresses()),
+        }
+    }
+
+    async fn create_subaddress(&self, account_index: u32, label: &str) -> Result<Subaddress> {
+        match self {
+            MainWallet::Wallet2(wallet) => wallet.create_subaddress(account_index, label).await,
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => Ok(wallet.create_subaddress(label)),
+        }
+    }
+
+    async fn wait_until_confirmed(
+        &self,
+        txid: String,
+        tx_key: monero::PrivateKey,
+        address: &Address,
+        expected_amount: monero::Amount,
+        confirmations: u64,
+        listener: Option<impl Fn((u64, u64)) + Send + 'static>,
+    ) -> Result<()> {
+        match self {
+            MainWallet::Wallet2(wallet) => {
+                wallet
+                    .wait_until_confirmed(
+                        txid,
+                        tx_key,
+                        address,
+                        expected_amount,
+                        confirmations,
+                        listener,
+                    )
+                    .await
+            }
+            #[cfg(feature = "test-utils")]
+            MainWallet::Simulated(wallet) => {
+                wallet
+                    .wait_until_confirmed(
+                        &txid,
+                        tx_key,
+                        address,
+                        expected_amount,
+                        confirmations,
+                        listener,
+                    )
+                    .await
+            }
+        }
+    }
+}
+
+impl MoneroTransfer {
+    fn new(tx: TransactionInfo, swap_subaddresses: &HashMap<u32, Uuid>) -> Self {
+        let direction = match tx.direction {
+            TransactionDirection::Incoming => MoneroTransferDirection::Incoming,
+            TransactionDirection::Outgoing => MoneroTransferDirection::Outgoing,
+        };
+
+        // Only incoming transfers are to a subaddress of the swap
+        let swap_id = match direction {
+            MoneroTransferDirection::Incoming => tx
+                .subaddress_indices
+                .iter()
+                .find_map(|index| swap_subaddresses.get(index))
+                .copied(),
+            MoneroTransferDirection::Outgoing => None,
+        };
+
+        Self {
+            txid: tx.txid,
+            direction,
+            amount: tx.amount.into(),
+            fee: tx.fee.into(),
+            block_height: tx.block_height,
+            confirmations: tx.confirmations,
+            timestamp: tx.timestamp,
+            pending: tx.pending,
+            failed: tx.failed,
+            subaddress_indices: tx.subaddress_indices,
+            swap_id,
+        }
+    }
+}
+
+fn swap_subaddress_label(swap_id: Uuid) -> String {
+    format!("{}{}", SWAP_SUBADDRESS_LABEL_PREFIX, swap_id)
+}
+
+fn parse_swap_subaddress_label(label: &str) -> Option<Uuid> {
+    label
+        .strip_prefix(SWAP_SUBADDRESS_LABEL_PREFIX)
+        .and_then(|swap_id| swap_id.parse().ok())
+}
+
 impl TransferRequest {
     pub fn address_and_amount(&self, network: Network) -> (Address, monero::Amount) {
         (
@@ -233,6 +713,67 @@ pub fn no_listener<T>() -> Option<impl Fn(T) + Send + 'static> {
     Some(|_| {})
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transaction(
+        direction: TransactionDirection,
+        subaddress_indices: Vec<u32>,
+    ) -> TransactionInfo {
+        TransactionInfo {
+            txid: "txid".to_string(),
+            direction,
+            amount: monero::Amount::from_pico(1_000),
+            fee: monero::Amount::from_pico(10),
+            block_height: Some(100),
+            confirmations: 10,
+            timestamp: 0,
+            pending: false,
+            failed: false,
+            account_index: MAIN_ACCOUNT_INDEX,
+            subaddress_indices,
+        }
+    }
+
+    #[test]
+    fn swap_subaddress_label_roundtrip() {
+        let swap_id = Uuid::new_v4();
+
+        assert_eq!(
+            parse_swap_subaddress_label(&swap_subaddress_label(swap_id)),
+            Some(swap_id)
+        );
+        assert_eq!(parse_swap_subaddress_label("Primary account"), None);
+        assert_eq!(parse_swap_subaddress_label("swap:not-a-uuid"), None);
+    }
+
+    #[test]
+    fn only_incoming_transfers_to_swap_subaddress_are_linked() {
+        let swap_id = Uuid::new_v4();
+        let swap_subaddresses = HashMap::from([(3, swap_id)]);
+
+        let redeemed = MoneroTransfer::new(
+            transaction(TransactionDirection::Incoming, vec![3]),
+            &swap_subaddresses,
+        );
+        assert_eq!(redeemed.swap_id, Some(swap_id));
+        assert_eq!(redeemed.direction, MoneroTransferDirection::Incoming);
+
+        let deposit = MoneroTransfer::new(
+            transaction(TransactionDirection::Incoming, vec![0]),
+            &swap_subaddresses,
+        );
+        assert_eq!(deposit.swap_id, None);
+
+        let spent = MoneroTransfer::new(
+            transaction(TransactionDirection::Outgoing, vec![3]),
+            &swap_subaddresses,
+        );
+        assert_eq!(spent.swap_id, None);
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
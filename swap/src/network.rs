@@ -1,7 +1,9 @@
 mod impl_from_rr_event;
 
+pub mod cooperative_early_refund;
 pub mod cooperative_xmr_redeem_after_punish;
 pub mod encrypted_signature;
+pub mod fault_injection;
 pub mod quote;
 pub mod redial;
 pub mod rendezvous;
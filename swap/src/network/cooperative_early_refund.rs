@@ -0,0 +1,101 @@
+use crate::{asb, cli};
+use libp2p::request_response::ProtocolSupport;
+use libp2p::{request_response, PeerId, StreamProtocol};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+const PROTOCOL: &str = "/comit/xmr/btc/cooperative_early_refund/1.0.0";
+type OutEvent = request_response::Event<Request, Response>;
+type Message = request_response::Message<Request, Response>;
+
+pub type Behaviour = request_response::cbor::Behaviour<Request, Response>;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CooperativeEarlyRefundProtocol;
+
+impl AsRef<str> for CooperativeEarlyRefundProtocol {
+    fn as_ref(&self) -> &str {
+        PROTOCOL
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone, Serialize, Deserialize)]
+pub enum CooperativeEarlyRefundRejectReason {
+    #[error("Alice does not have a record of the swap")]
+    UnknownSwap,
+    #[error("Alice rejected the request because it deemed it malicious")]
+    MaliciousRequest,
+    #[error("Alice has already locked the Monero or is about to lock it")]
+    SwapInvalidState,
+}
+
+/// Bob asks Alice to publish the early refund transaction, before she locks the Monero.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Request {
+    pub swap_id: Uuid,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Response {
+    /// Alice will not lock the Monero and publishes the early refund transaction.
+    Accepted { swap_id: Uuid },
+    Rejected {
+        swap_id: Uuid,
+        reason: CooperativeEarlyRefundRejectReason,
+    },
+}
+
+pub fn alice() -> Behaviour {
+    Behaviour::new(
+        vec![(
+            StreamProtocol::new(CooperativeEarlyRefundProtocol.as_ref()),
+            ProtocolSupport::Inbound,
+        )],
+        request_response::Config::default().with_request_timeout(Duration::from_secs(60)),
+    )
+}
+
+pub fn bob() -> Behaviour {
+    Behaviour::new(
+        vec![(
+            StreamProtocol::new(CooperativeEarlyRefundProtocol.as_ref()),
+            ProtocolSupport::Outbound,
+        )],
+        request_response::Config::default().with_request_timeout(Duration::from_secs(60)),
+    )
+}
+
+impl From<(PeerId, Message)> for asb::OutEvent {
+    fn from((peer, message): (PeerId, Message)) -> Self {
+        match message {
+            Message::Request {
+                request, channel, ..
+            } => Self::CooperativeEarlyRefundRequested {
+                swap_id: request.swap_id,
+                channel,
+                peer,
+            },
+            Message::Response { .. } => Self::unexpected_response(peer),
+        }
+    }
+}
+
+crate::impl_from_rr_event!(OutEvent, asb::OutEvent, PROTOCOL);
+
+impl From<(PeerId, Message)> for cli::OutEvent {
+    fn from((peer, message): (PeerId, Message)) -> Self {
+        match message {
+            Message::Request { .. } => Self::unexpected_request(peer),
+            Message::Response {
+                response,
+                request_id,
+            } => Self::CooperativeEarlyRefundResponse {
+                id: request_id,
+                response,
+            },
+        }
+    }
+}
+
+crate::impl_from_rr_event!(OutEvent, cli::OutEvent, PROTOCOL);
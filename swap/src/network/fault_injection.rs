@@ -0,0 +1,241 @@
+//! Deliberately lose or hold back messages of the swap protocol.
+//!
+//! Used by tests to check that a swap recovers from an unreliable network. The
+//! event loops pass every request they are about to send through a
+//! [`FaultInjector`], which consults the [`MessageFaults`] injected by the test.
+//! Without any injected faults, messages are sent as usual.
+//!
+//! Faults can only be injected with the `test-utils` feature. Without it a
+//! [`FaultInjector`] hands back every request right away.
+
+#[cfg(feature = "test-utils")]
+use futures::future::{BoxFuture, FutureExt};
+#[cfg(feature = "test-utils")]
+use futures::stream::{FuturesUnordered, StreamExt};
+#[cfg(feature = "test-utils")]
+use std::collections::HashMap;
+#[cfg(not(feature = "test-utils"))]
+use std::marker::PhantomData;
+#[cfg(feature = "test-utils")]
+use std::sync::{Arc, Mutex, MutexGuard};
+#[cfg(feature = "test-utils")]
+use std::time::Duration;
+
+/// The messages faults can be injected into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Message {
+    /// Bob starting the swap setup with Alice.
+    SwapSetup,
+    /// Alice sending the Monero lock transfer proof to Bob.
+    TransferProof,
+    /// Bob sending the encrypted signature of the redeem transaction to Alice.
+    EncryptedSignature,
+    /// Bob asking Alice to refund his Bitcoin before she locks the Monero.
+    CooperativeEarlyRefund,
+    /// Bob requesting the cooperative redeem of the Monero after he was punished.
+    CooperativeXmrRedeem,
+}
+
+#[cfg(feature = "test-utils")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The message is lost, the sender observes a timeout.
+    Drop,
+    /// The message is sent after the given delay.
+    Delay(Duration),
+}
+
+/// Faults to inject into outgoing messages, shared between the event loop and the test.
+#[cfg(feature = "test-utils")]
+#[derive(Debug, Clone, Default)]
+pub struct MessageFaults {
+    /// The fault and the number of messages it still applies to.
+    faults: Arc<Mutex<HashMap<Message, (Fault, u32)>>>,
+}
+
+#[cfg(feature = "test-utils")]
+impl MessageFaults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply `fault` to the next `times` messages of kind `message`.
+    ///
+    /// Replaces a fault previously injected for the same kind of message.
+    pub fn inject(&self, message: Message, fault: Fault, times: u32) {
+        if times == 0 {
+            self.lock().remove(&message);
+            return;
+        }
+
+        self.lock().insert(message, (fault, times));
+    }
+
+    /// Send all messages as usual again.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the fault to apply to the next `message`, if any.
+    fn next(&self, message: Message) -> Option<Fault> {
+        let mut faults = self.lock();
+        let (fault, remaining) = faults.get_mut(&message)?;
+        let fault = *fault;
+
+        *remaining -= 1;
+        if *remaining == 0 {
+            faults.remove(&message);
+        }
+
+        tracing::warn!(?message, ?fault, "Injecting fault into outgoing message");
+
+        Some(fault)
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<Message, (Fault, u32)>> {
+        self.faults
+            .lock()
+            .expect("no other thread to panic while holding the lock")
+    }
+}
+
+/// Applies the [`MessageFaults`] to the requests of an event loop and holds back the delayed ones.
+pub(crate) struct FaultInjector<T> {
+    #[cfg(feature = "test-utils")]
+    faults: MessageFaults,
+    #[cfg(feature = "test-utils")]
+    delayed: FuturesUnordered<BoxFuture<'static, T>>,
+    #[cfg(not(feature = "test-utils"))]
+    _request: PhantomData<T>,
+}
+
+impl<T: Send + 'static> FaultInjector<T> {
+    #[cfg(feature = "test-utils")]
+    pub fn new(faults: MessageFaults) -> Self {
+        Self {
+            faults,
+            delayed: FuturesUnordered::new(),
+        }
+    }
+
+    /// Called by the event loops before sending `request` of kind `message`.
+    ///
+    /// Returns the request if it is to be sent right away. Lost requests are passed
+    /// to `lose`, delayed ones are returned by [`FaultInjector::next_due`] later.
+    #[cfg_attr(not(feature = "test-utils"), allow(unused_variables))]
+    pub fn intercept(&mut self, message: Message, request: T, lose: impl FnOnce(T)) -> Option<T> {
+        #[cfg(feature = "test-utils")]
+        match self.faults.next(message) {
+            Some(Fault::Drop) => {
+                lose(request);
+                return None;
+            }
+            Some(Fault::Delay(delay)) => {
+                self.delayed.push(
+                    async move {
+                        tokio::time::sleep(delay).await;
+
+                        request
+                    }
+                    .boxed(),
+                );
+                return None;
+            }
+            None => {}
+        }
+
+        Some(request)
+    }
+
+    /// Resolves with the next delayed request once it is due to be sent.
+    ///
+    /// Never resolves if there is no delayed request.
+    pub async fn next_due(&mut self) -> T {
+        #[cfg(feature = "test-utils")]
+        if let Some(request) = self.delayed.next().await {
+            return request;
+        }
+
+        futures::future::pending().await
+    }
+}
+
+impl<T> Default for FaultInjector<T> {
+    fn default() -> Self {
+        Self {
+            #[cfg(feature = "test-utils")]
+            faults: MessageFaults::default(),
+            #[cfg(feature = "test-utils")]
+            delayed: FuturesUnordered::new(),
+            #[cfg(not(feature = "test-utils"))]
+            _request: PhantomData,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "test-utils"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fault_applies_to_the_given_number_of_messages() {
+        let faults = MessageFaults::new();
+        faults.inject(Message::EncryptedSignature, Fault::Drop, 2);
+
+        assert_eq!(faults.next(Message::TransferProof), None);
+        assert_eq!(faults.next(Message::EncryptedSignature), Some(Fault::Drop));
+        assert_eq!(faults.next(Message::EncryptedSignature), Some(Fault::Drop));
+        assert_eq!(faults.next(Message::EncryptedSignature), None);
+    }
+
+    #[test]
+    fn clones_share_the_injected_faults() {
+        let faults = MessageFaults::new();
+        let event_loop_faults = faults.clone();
+
+        faults.inject(
+            Message::TransferProof,
+            Fault::Delay(Duration::from_secs(1)),
+            1,
+        );
+
+        assert_eq!(
+            event_loop_faults.next(Message::TransferProof),
+            Some(Fault::Delay(Duration::from_secs(1)))
+        );
+
+        faults.inject(Message::TransferProof, Fault::Drop, 1);
+        faults.clear();
+
+        assert_eq!(event_loop_faults.next(Message::TransferProof), None);
+    }
+
+    #[tokio::test]
+    async fn injector_drops_and_delays_requests() {
+        let faults = MessageFaults::new();
+        let mut injector = FaultInjector::new(faults.clone());
+
+        faults.inject(Message::CooperativeEarlyRefund, Fault::Drop, 1);
+        let mut dropped = None;
+        let sent = injector.intercept(Message::CooperativeEarlyRefund, 1, |request| {
+            dropped = Some(request)
+        });
+        assert_eq!(sent, None);
+        assert_eq!(dropped, Some(1));
+
+        faults.inject(
+            Message::SwapSetup,
+            Fault::Delay(Duration::from_millis(10)),
+            1,
+        );
+        assert_eq!(
+            injector.intercept(Message::SwapSetup, 2, |_| unreachable!()),
+            None
+        );
+        assert_eq!(
+            injector.intercept(Message::SwapSetup, 3, |_| unreachable!()),
+            Some(3)
+        );
+        assert_eq!(injector.next_due().await, 2);
+    }
+}
@@ -5,6 +5,7 @@ use libp2p::core::Multiaddr;
 use libp2p::swarm::dial_opts::{DialOpts, PeerCondition};
 use libp2p::swarm::{NetworkBehaviour, ToSwarm};
 use libp2p::PeerId;
+use std::collections::HashMap;
 use std::pin::Pin;
 use std::task::{Context, Poll};
 use std::time::Duration;
@@ -14,11 +15,17 @@ use void::Void;
 use crate::cli;
 
 /// A [`NetworkBehaviour`] that tracks whether we are connected to the given
-/// peer and attempts to re-establish a connection with an exponential backoff
-/// if we lose the connection.
+/// peers and attempts to re-establish a connection with an exponential backoff
+/// if we lose the connection to one of them.
 pub struct Behaviour {
-    /// The peer we are interested in.
-    peer: PeerId,
+    /// The peers we are interested in.
+    peers: HashMap<PeerId, Redial>,
+    interval: Duration,
+    max_interval: Duration,
+}
+
+/// The re-dial state of a single peer.
+struct Redial {
     /// If present, tracks for how long we need to sleep until we dial again.
     sleep: Option<Pin<Box<Sleep>>>,
     /// Tracks the current backoff state.
@@ -26,9 +33,19 @@ pub struct Behaviour {
 }
 
 impl Behaviour {
-    pub fn new(peer: PeerId, interval: Duration, max_interval: Duration) -> Self {
+    pub fn new(interval: Duration, max_interval: Duration) -> Self {
         Self {
-            peer,
+            peers: HashMap::new(),
+            interval,
+            max_interval,
+        }
+    }
+
+    /// Start re-dialling the peer whenever we lose the connection to it.
+    pub fn add_peer(&mut self, peer: PeerId) {
+        let (interval, max_interval) = (self.interval, self.max_interval);
+
+        self.peers.entry(peer).or_insert_with(|| Redial {
             sleep: None,
             backoff: ExponentialBackoff {
                 initial_interval: interval,
@@ -37,11 +54,18 @@ impl Behaviour {
                 max_elapsed_time: None, // We never give up on re-dialling
                 ..ExponentialBackoff::default()
             },
-        }
+        });
     }
 
-    pub fn until_next_redial(&self) -> Option<Duration> {
+    /// Stop re-dialling the peer.
+    pub fn remove_peer(&mut self, peer: &PeerId) {
+        self.peers.remove(peer);
+    }
+
+    pub fn until_next_redial(&self, peer: &PeerId) -> Option<Duration> {
         let until_next_redial = self
+            .peers
+            .get(peer)?
             .sleep
             .as_ref()?
             .deadline()
@@ -49,6 +73,15 @@ impl Behaviour {
 
         Some(until_next_redial)
     }
+
+    /// We are connected to the peer again. Reset the backoff state to start with the initial
+    /// interval again once we disconnect again.
+    fn connected(&mut self, peer: &PeerId) {
+        if let Some(redial) = self.peers.get_mut(peer) {
+            redial.backoff.reset();
+            redial.sleep = None;
+        }
+    }
 }
 
 impl NetworkBehaviour for Behaviour {
@@ -62,13 +95,9 @@ impl NetworkBehaviour for Behaviour {
         _local_addr: &Multiaddr,
         _remote_addr: &Multiaddr,
     ) -> Result<libp2p::swarm::THandler<Self>, libp2p::swarm::ConnectionDenied> {
-        // We establish an inbound connection to the peer we are interested in.
+        // We establish an inbound connection to a peer we are interested in.
         // We stop re-dialling.
-        // Reset the backoff state to start with the initial interval again once we disconnect again
-        if peer == self.peer {
-            self.backoff.reset();
-            self.sleep = None;
-        }
+        self.connected(&peer);
         Ok(Self::ConnectionHandler {})
     }
 
@@ -79,51 +108,62 @@ impl NetworkBehaviour for Behaviour {
         _addr: &Multiaddr,
         _role_override: libp2p::core::Endpoint,
     ) -> Result<libp2p::swarm::THandler<Self>, libp2p::swarm::ConnectionDenied> {
-        // We establish an outbound connection to the peer we are interested in.
+        // We establish an outbound connection to a peer we are interested in.
         // We stop re-dialling.
-        // Reset the backoff state to start with the initial interval again once we disconnect again
-        if peer == self.peer {
-            self.backoff.reset();
-            self.sleep = None;
-        }
+        self.connected(&peer);
         Ok(Self::ConnectionHandler {})
     }
 
     fn on_swarm_event(&mut self, event: libp2p::swarm::FromSwarm<'_>) {
-        let redial = match event {
-            libp2p::swarm::FromSwarm::ConnectionClosed(e) if e.peer_id == self.peer => true,
-            libp2p::swarm::FromSwarm::DialFailure(e) if e.peer_id == Some(self.peer) => true,
-            _ => false,
+        let peer = match event {
+            libp2p::swarm::FromSwarm::ConnectionClosed(e) => e.peer_id,
+            libp2p::swarm::FromSwarm::DialFailure(e) => match e.peer_id {
+                Some(peer) => peer,
+                None => return,
+            },
+            _ => return,
         };
 
-        if redial && self.sleep.is_none() {
-            self.sleep = Some(Box::pin(tokio::time::sleep(self.backoff.initial_interval)));
-            tracing::info!(seconds_until_next_redial = %self.until_next_redial().expect("We initialize the backoff without max_elapsed_time").as_secs(), "Waiting for next redial attempt");
+        let Some(redial) = self.peers.get_mut(&peer) else {
+            return;
+        };
+
+        if redial.sleep.is_none() {
+            redial.sleep = Some(Box::pin(tokio::time::sleep(
+                redial.backoff.initial_interval,
+            )));
+            tracing::info!(%peer, seconds_until_next_redial = %self.until_next_redial(&peer).expect("We initialize the backoff without max_elapsed_time").as_secs(), "Waiting for next redial attempt");
         }
     }
 
     fn poll(&mut self, cx: &mut Context<'_>) -> std::task::Poll<ToSwarm<Self::ToSwarm, Void>> {
-        let sleep = match self.sleep.as_mut() {
-            None => return Poll::Pending, // early exit if we shouldn't be re-dialling
-            Some(future) => future,
-        };
+        for (peer, redial) in self.peers.iter_mut() {
+            let sleep = match redial.sleep.as_mut() {
+                None => continue, // skip peers we shouldn't be re-dialling
+                Some(future) => future,
+            };
+
+            if sleep.poll_unpin(cx).is_pending() {
+                continue;
+            }
 
-        futures::ready!(sleep.poll_unpin(cx));
+            let next_dial_in = match redial.backoff.next_backoff() {
+                Some(next_dial_in) => next_dial_in,
+                None => {
+                    unreachable!("The backoff should never run out of attempts");
+                }
+            };
 
-        let next_dial_in = match self.backoff.next_backoff() {
-            Some(next_dial_in) => next_dial_in,
-            None => {
-                unreachable!("The backoff should never run out of attempts");
-            }
-        };
+            redial.sleep = Some(Box::pin(tokio::time::sleep(next_dial_in)));
 
-        self.sleep = Some(Box::pin(tokio::time::sleep(next_dial_in)));
+            return Poll::Ready(ToSwarm::Dial {
+                opts: DialOpts::peer_id(*peer)
+                    .condition(PeerCondition::Disconnected)
+                    .build(),
+            });
+        }
 
-        Poll::Ready(ToSwarm::Dial {
-            opts: DialOpts::peer_id(self.peer)
-                .condition(PeerCondition::Disconnected)
-                .build(),
-        })
+        Poll::Pending
     }
 
     fn on_connection_handler_event(
@@ -141,3 +181,62 @@ impl From<()> for cli::OutEvent {
         Self::Other
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libp2p::swarm::{ConnectionId, DialError, DialFailure, FromSwarm};
+    use std::collections::HashSet;
+
+    const INTERVAL: Duration = Duration::from_millis(10);
+
+    fn fail_dial(behaviour: &mut Behaviour, peer: PeerId) {
+        behaviour.on_swarm_event(FromSwarm::DialFailure(DialFailure {
+            peer_id: Some(peer),
+            error: &DialError::Aborted,
+            connection_id: ConnectionId::new_unchecked(0),
+        }));
+    }
+
+    /// The peer the behaviour dials next, `None` if it does not dial anyone for a while.
+    async fn next_dial(behaviour: &mut Behaviour) -> Option<PeerId> {
+        let event = tokio::time::timeout(
+            Duration::from_millis(500),
+            futures::future::poll_fn(|cx| behaviour.poll(cx)),
+        )
+        .await
+        .ok()?;
+
+        match event {
+            ToSwarm::Dial { opts } => opts.get_peer_id(),
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn redials_every_added_peer_until_it_is_removed() {
+        let mut behaviour = Behaviour::new(INTERVAL, INTERVAL);
+        let (alice, other_alice, stranger) = (PeerId::random(), PeerId::random(), PeerId::random());
+
+        behaviour.add_peer(alice);
+        behaviour.add_peer(other_alice);
+
+        fail_dial(&mut behaviour, alice);
+        fail_dial(&mut behaviour, other_alice);
+        fail_dial(&mut behaviour, stranger);
+
+        let mut dialled = HashSet::new();
+        for _ in 0..4 {
+            dialled.insert(next_dial(&mut behaviour).await.unwrap());
+        }
+        assert_eq!(dialled, HashSet::from([alice, other_alice]));
+
+        behaviour.remove_peer(&alice);
+        for _ in 0..2 {
+            assert_eq!(next_dial(&mut behaviour).await, Some(other_alice));
+        }
+
+        behaviour.remove_peer(&other_alice);
+        assert_eq!(next_dial(&mut behaviour).await, None);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::asb::LatestRate;
+use crate::asb::{LatestRate, Rate};
+use crate::common::metrics::METRICS;
 use crate::network::swap_setup;
 use crate::network::swap_setup::{
     protocol, BlockchainNetwork, SpotPriceError, SpotPriceRequest, SpotPriceResponse,
@@ -6,7 +7,7 @@ use crate::network::swap_setup::{
 use crate::protocol::alice::{State0, State3};
 use crate::protocol::{Message0, Message2, Message4};
 use crate::{asb, bitcoin, env, monero};
-use anyhow::{anyhow, Context, Result};
+use anyhow::{Context, Result};
 use futures::future::{BoxFuture, OptionFuture};
 use futures::AsyncWriteExt;
 use futures::FutureExt;
@@ -15,6 +16,7 @@ use libp2p::swarm::handler::ConnectionEvent;
 use libp2p::swarm::{ConnectionHandler, ConnectionId};
 use libp2p::swarm::{ConnectionHandlerEvent, NetworkBehaviour, SubstreamProtocol, ToSwarm};
 use libp2p::{Multiaddr, PeerId};
+use rust_decimal::Decimal;
 use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::task::Poll;
@@ -25,12 +27,14 @@ use uuid::Uuid;
 #[allow(clippy::large_enum_variant)]
 pub enum OutEvent {
     Initiated {
+        peer_id: PeerId,
         send_wallet_snapshot: bmrng::RequestReceiver<bitcoin::Amount, WalletSnapshot>,
     },
     Completed {
         peer_id: PeerId,
         swap_id: Uuid,
         state3: State3,
+        rate: Rate,
     },
     Error {
         peer_id: PeerId,
@@ -50,6 +54,9 @@ pub struct WalletSnapshot {
 
     redeem_fee: bitcoin::Amount,
     punish_fee: bitcoin::Amount,
+
+    /// Spread charged on top of the base spread, as determined by our pricing policy
+    additional_ask_spread: Decimal,
 }
 
 impl WalletSnapshot {
@@ -59,8 +66,8 @@ impl WalletSnapshot {
         external_redeem_address: &Option<bitcoin::Address>,
         transfer_amount: bitcoin::Amount,
     ) -> Result<Self> {
-        let unlocked_balance = monero_wallet.main_wallet().await.unlocked_balance().await;
-        let total_balance = monero_wallet.main_wallet().await.total_balance().await;
+        let unlocked_balance = monero_wallet.unlocked_balance().await;
+        let total_balance = monero_wallet.total_balance().await;
 
         tracing::info!(%unlocked_balance, %total_balance, "Capturing monero wallet snapshot");
 
@@ -85,30 +92,52 @@ impl WalletSnapshot {
             punish_address,
             redeem_fee,
             punish_fee,
+            additional_ask_spread: Decimal::ZERO,
         })
     }
+
+    pub fn unlocked_balance(&self) -> monero::Amount {
+        self.unlocked_balance
+    }
+
+    pub fn with_additional_ask_spread(self, additional_ask_spread: Decimal) -> Self {
+        Self {
+            additional_ask_spread,
+            ..self
+        }
+    }
 }
 
 impl From<OutEvent> for asb::OutEvent {
     fn from(event: OutEvent) -> Self {
         match event {
             OutEvent::Initiated {
+                peer_id,
                 send_wallet_snapshot,
             } => asb::OutEvent::SwapSetupInitiated {
+                peer_id,
                 send_wallet_snapshot,
             },
             OutEvent::Completed {
                 peer_id: bob_peer_id,
                 swap_id,
                 state3,
+                rate,
             } => asb::OutEvent::SwapSetupCompleted {
                 peer_id: bob_peer_id,
                 swap_id,
                 state3,
+                rate,
             },
-            OutEvent::Error { peer_id, error } => asb::OutEvent::Failure {
-                peer: peer_id,
-                error: anyhow!(error),
+            OutEvent::Error { peer_id, error } => match error.downcast::<Error>() {
+                Ok(error) => asb::OutEvent::SwapDeclined {
+                    peer: peer_id,
+                    error,
+                },
+                Err(error) => asb::OutEvent::SwapSetupFailed {
+                    peer: peer_id,
+                    error,
+                },
             },
         }
     }
@@ -204,17 +233,28 @@ where
         match event {
             HandlerOutEvent::Initiated(send_wallet_snapshot) => {
                 self.events.push_back(OutEvent::Initiated {
+                    peer_id,
                     send_wallet_snapshot,
                 })
             }
-            HandlerOutEvent::Completed(Ok((swap_id, state3))) => {
+            HandlerOutEvent::Completed(Ok((swap_id, state3, rate))) => {
+                METRICS.record_swap_setup("completed");
                 self.events.push_back(OutEvent::Completed {
                     peer_id,
                     swap_id,
                     state3,
+                    rate,
                 })
             }
             HandlerOutEvent::Completed(Err(error)) => {
+                // Errors we reject the swap with are reported by their variant, everything else
+                // is a failure while executing the protocol.
+                let outcome = error
+                    .downcast_ref::<Error>()
+                    .map(Error::metric_label)
+                    .unwrap_or("protocol_failed");
+                METRICS.record_swap_setup(outcome);
+
                 self.events.push_back(OutEvent::Error { peer_id, error })
             }
         }
@@ -281,7 +321,7 @@ impl<LR> Handler<LR> {
 #[derive(Debug)]
 pub enum HandlerOutEvent {
     Initiated(bmrng::RequestReceiver<bitcoin::Amount, WalletSnapshot>),
-    Completed(Result<(Uuid, State3)>),
+    Completed(Result<(Uuid, State3, Rate)>),
 }
 
 impl<LR> ConnectionHandler for Handler<LR>
@@ -372,8 +412,9 @@ where
                             });
                         }
 
-                        let rate =
-                            latest_rate.map_err(|e| Error::LatestRateFetchFailed(Box::new(e)))?;
+                        let rate = latest_rate
+                            .map_err(|e| Error::LatestRateFetchFailed(Box::new(e)))?
+                            .with_additional_spread(wallet_snapshot.additional_ask_spread);
                         let xmr = rate
                             .sell_quote(btc)
                             .map_err(Error::SellQuoteCalculationFailed)?;
@@ -393,7 +434,7 @@ where
                             });
                         }
 
-                        Ok(xmr)
+                        Ok((xmr, rate))
                     };
 
                     let result = validate.await;
@@ -405,7 +446,7 @@ where
                     .await
                     .context("Failed to write spot price response")?;
 
-                    let xmr = result?;
+                    let (xmr, rate) = result?;
 
                     let state0 = State0::new(
                         request.btc,
@@ -456,7 +497,7 @@ where
                         .await
                         .context("Failed to close substream after all messages were sent")?;
 
-                    Ok((swap_id, state3))
+                    Ok((swap_id, state3, rate))
                 });
 
                 let max_seconds = self.negotiation_timeout.as_secs();
@@ -521,9 +562,9 @@ where
 }
 
 impl SpotPriceResponse {
-    pub fn from_result_ref(result: &Result<monero::Amount, Error>) -> Self {
+    pub fn from_result_ref(result: &Result<(monero::Amount, Rate), Error>) -> Self {
         match result {
-            Ok(amount) => SpotPriceResponse::Xmr(*amount),
+            Ok((amount, _)) => SpotPriceResponse::Xmr(*amount),
             Err(error) => SpotPriceResponse::Error(error.to_error_response()),
         }
     }
@@ -560,6 +601,19 @@ pub enum Error {
 }
 
 impl Error {
+    /// The label we report this error with in the swap setup metrics.
+    pub fn metric_label(&self) -> &'static str {
+        match self {
+            Error::ResumeOnlyMode => "resume_only_mode",
+            Error::AmountBelowMinimum { .. } => "amount_below_minimum",
+            Error::AmountAboveMaximum { .. } => "amount_above_maximum",
+            Error::BalanceTooLow { .. } => "balance_too_low",
+            Error::LatestRateFetchFailed(_) => "latest_rate_fetch_failed",
+            Error::SellQuoteCalculationFailed(_) => "sell_quote_calculation_failed",
+            Error::BlockchainNetworkMismatch { .. } => "blockchain_network_mismatch",
+        }
+    }
+
     pub fn to_error_response(&self) -> SpotPriceError {
         match self {
             Error::ResumeOnlyMode => SpotPriceError::NoSwapsAccepted,
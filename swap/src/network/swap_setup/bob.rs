@@ -3,9 +3,9 @@ use crate::protocol::bob::{State0, State2};
 use crate::protocol::{Message1, Message3};
 use crate::{bitcoin, cli, env, monero};
 use anyhow::{Context, Result};
-use futures::future::{BoxFuture, OptionFuture};
-use futures::AsyncWriteExt;
-use futures::FutureExt;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use futures::{AsyncWriteExt, StreamExt};
 use libp2p::core::upgrade;
 use libp2p::swarm::{
     ConnectionDenied, ConnectionHandler, ConnectionHandlerEvent, ConnectionId, FromSwarm,
@@ -45,7 +45,10 @@ impl Behaviour {
 
 impl From<Completed> for cli::OutEvent {
     fn from(completed: Completed) -> Self {
-        cli::OutEvent::SwapSetupCompleted(Box::new(completed.0))
+        cli::OutEvent::SwapSetupCompleted {
+            swap_id: completed.swap_id,
+            result: Box::new(completed.result),
+        }
     }
 }
 
@@ -108,10 +111,12 @@ impl NetworkBehaviour for Behaviour {
     }
 }
 
-type OutboundStream = BoxFuture<'static, Result<State2, Error>>;
+type OutboundStream = BoxFuture<'static, (Uuid, Result<State2, Error>)>;
 
 pub struct Handler {
-    outbound_stream: OptionFuture<OutboundStream>,
+    /// The swap setups in progress on this connection, several swaps with the same peer can be
+    /// set up at the same time.
+    outbound_streams: FuturesUnordered<OutboundStream>,
     env_config: env::Config,
     timeout: Duration,
     new_swaps: VecDeque<NewSwap>,
@@ -123,7 +128,7 @@ impl Handler {
     fn new(env_config: env::Config, bitcoin_wallet: Arc<bitcoin::Wallet>) -> Self {
         Self {
             env_config,
-            outbound_stream: OptionFuture::from(None),
+            outbound_streams: FuturesUnordered::new(),
             timeout: Duration::from_secs(120),
             new_swaps: VecDeque::default(),
             bitcoin_wallet,
@@ -140,10 +145,14 @@ pub struct NewSwap {
     pub tx_refund_fee: bitcoin::Amount,
     pub tx_cancel_fee: bitcoin::Amount,
     pub bitcoin_refund_address: bitcoin::Address,
+    pub tx_lock_inputs: Option<Vec<bitcoin::OutPoint>>,
 }
 
 #[derive(Debug)]
-pub struct Completed(Result<State2>);
+pub struct Completed {
+    swap_id: Uuid,
+    result: Result<State2>,
+}
 
 impl ConnectionHandler for Handler {
     type FromBehaviour = NewSwap;
@@ -175,6 +184,7 @@ impl ConnectionHandler for Handler {
             libp2p::swarm::handler::ConnectionEvent::FullyNegotiatedOutbound(outbound) => {
                 let mut substream = outbound.protocol;
                 let new_swap_request = outbound.info;
+                let swap_id = new_swap_request.swap_id;
 
                 let bitcoin_wallet = self.bitcoin_wallet.clone();
                 let env_config = self.env_config;
@@ -217,6 +227,7 @@ impl ConnectionHandler for Handler {
                             new_swap_request.tx_refund_fee,
                             new_swap_request.tx_cancel_fee,
                             new_swap_request.tx_lock_fee,
+                            new_swap_request.tx_lock_inputs.clone(),
                         );
 
                         write_cbor_message(&mut substream, state0.next_message())
@@ -264,12 +275,16 @@ impl ConnectionHandler for Handler {
 
                 let max_seconds = self.timeout.as_secs();
 
-                self.outbound_stream = OptionFuture::from(Some(Box::pin(async move {
-                    protocol.await.map_err(|_| Error::Timeout {
-                        seconds: max_seconds,
-                    })?
-                })
-                    as OutboundStream));
+                self.outbound_streams.push(Box::pin(async move {
+                    let result = match protocol.await {
+                        Ok(result) => result,
+                        Err(_) => Err(Error::Timeout {
+                            seconds: max_seconds,
+                        }),
+                    };
+
+                    (swap_id, result)
+                }));
 
                 // Once the outbound stream is created, we keep the connection alive
                 self.keep_alive = true;
@@ -302,17 +317,16 @@ impl ConnectionHandler for Handler {
             });
         }
 
-        // Check if the outbound stream has completed
-        if let Poll::Ready(Some(result)) = self.outbound_stream.poll_unpin(cx) {
-            self.outbound_stream = None.into();
-
-            // Once the outbound stream is completed, we no longer keep the connection alive
-            self.keep_alive = false;
+        // Check if one of the outbound streams has completed
+        if let Poll::Ready(Some((swap_id, result))) = self.outbound_streams.poll_next_unpin(cx) {
+            // Once all outbound streams are completed, we no longer keep the connection alive
+            self.keep_alive = !self.outbound_streams.is_empty();
 
             // We notify the swarm that the swap setup is completed / failed
-            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Completed(
-                result.map_err(anyhow::Error::from),
-            )));
+            return Poll::Ready(ConnectionHandlerEvent::NotifyBehaviour(Completed {
+                swap_id,
+                result: result.map_err(anyhow::Error::from),
+            }));
         }
 
         Poll::Pending
@@ -1,3 +1,5 @@
+use crate::asb::config::PeerLimits;
+use crate::asb::onion::OnionService;
 use crate::asb::{LatestRate, RendezvousNode};
 use crate::libp2p_ext::MultiAddrExt;
 use crate::network::rendezvous::XmrBtcNamespace;
@@ -26,7 +28,9 @@ pub fn asb<LR>(
     maybe_tor_client: Option<Arc<TorClient<TokioRustlsRuntime>>>,
     register_hidden_service: bool,
     num_intro_points: u8,
-) -> Result<(Swarm<asb::Behaviour<LR>>, Vec<Multiaddr>)>
+    onion_only: bool,
+    peer_limits: &PeerLimits,
+) -> Result<(Swarm<asb::Behaviour<LR>>, Option<OnionService>)>
 where
     LR: LatestRate + Send + 'static + Debug + Clone,
 {
@@ -51,13 +55,15 @@ where
         env_config,
         (identity.clone(), namespace),
         rendezvous_nodes,
+        peer_limits,
     );
 
-    let (transport, onion_addresses) = asb::transport::new(
+    let (transport, onion_service) = asb::transport::new(
         &identity,
         maybe_tor_client,
         register_hidden_service,
         num_intro_points,
+        onion_only.then(|| seed.derive_onion_service_identity()),
     )?;
 
     let swarm = SwarmBuilder::with_existing_identity(identity)
@@ -67,7 +73,7 @@ where
         .with_swarm_config(|cfg| cfg.with_idle_connection_timeout(Duration::MAX))
         .build();
 
-    Ok((swarm, onion_addresses))
+    Ok((swarm, onion_service))
 }
 
 pub async fn cli<T>(
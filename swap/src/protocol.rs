@@ -1,3 +1,5 @@
+use crate::asb::Rate;
+use crate::database::{PeerSettings, PeerStatus};
 use crate::monero::MoneroAddressPool;
 use crate::protocol::alice::swap::is_complete as alice_is_complete;
 use crate::protocol::alice::AliceState;
@@ -12,11 +14,18 @@ use serde::{Deserialize, Serialize};
 use sha2::Sha256;
 use sigma_fun::ext::dl_secp256k1_ed25519_eq::{CrossCurveDLEQ, CrossCurveDLEQProof};
 use sigma_fun::HashTranscript;
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::fmt;
+use time::OffsetDateTime;
 use uuid::Uuid;
 
 pub mod alice;
 pub mod bob;
+pub mod export;
+#[cfg(test)]
+pub(crate) mod fixtures;
+pub mod timeline;
 
 pub static CROSS_CURVE_PROOF_SYSTEM: Lazy<
     CrossCurveDLEQ<HashTranscript<Sha256, rand_chacha::ChaCha20Rng>>,
@@ -92,6 +101,46 @@ impl State {
             State::Bob(state) => bob_is_complete(state),
         }
     }
+
+    /// All Bitcoin transactions of this swap that can be constructed from this state.
+    pub fn swap_transactions(&self) -> Result<Vec<bitcoin::SwapTransaction>> {
+        match self {
+            State::Alice(state) => Ok(state.swap_transactions()),
+            State::Bob(state) => state.swap_transactions(),
+        }
+    }
+
+    /// The Bitcoin transactions of this swap that we may have to get
+    /// confirmed, see [`crate::bitcoin::bump_transactions`].
+    pub fn transactions_to_confirm(&self) -> Result<Vec<bitcoin::SwapTransaction>> {
+        match self {
+            State::Alice(state) => Ok(state.transactions_to_confirm()),
+            State::Bob(state) => state.transactions_to_confirm(),
+        }
+    }
+}
+
+impl fmt::Display for State {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            State::Alice(state) => fmt::Display::fmt(state, f),
+            State::Bob(state) => fmt::Display::fmt(state, f),
+        }
+    }
+}
+
+/// A state of a swap together with the time it was entered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateTransition {
+    pub state: State,
+    pub entered_at: OffsetDateTime,
+}
+
+/// How often we could and could not fetch a quote from a peer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PeerReachability {
+    pub reachable: u32,
+    pub unreachable: u32,
 }
 
 impl From<AliceState> for State {
@@ -150,10 +199,30 @@ pub trait Database {
     async fn insert_address(&self, peer_id: PeerId, address: Multiaddr) -> Result<()>;
     async fn get_addresses(&self, peer_id: PeerId) -> Result<Vec<Multiaddr>>;
     async fn get_all_peer_addresses(&self) -> Result<Vec<(PeerId, Vec<Multiaddr>)>>;
+    /// Records whether we could fetch a quote from the peer.
+    async fn insert_peer_reachability(&self, peer_id: PeerId, reachable: bool) -> Result<()>;
+    async fn get_all_peer_reachability(&self) -> Result<Vec<(PeerId, PeerReachability)>>;
+    async fn insert_peer_status(&self, peer_id: PeerId, status: PeerStatus) -> Result<()>;
+    async fn insert_peer_label(&self, peer_id: PeerId, label: Option<String>) -> Result<()>;
+    async fn get_all_peer_settings(&self) -> Result<Vec<(PeerId, PeerSettings)>>;
+    /// Forgets the addresses and settings of the peer.
+    async fn remove_peer(&self, peer_id: PeerId) -> Result<()>;
     async fn get_swap_start_date(&self, swap_id: Uuid) -> Result<String>;
     async fn insert_latest_state(&self, swap_id: Uuid, state: State) -> Result<()>;
     async fn get_state(&self, swap_id: Uuid) -> Result<State>;
     async fn get_states(&self, swap_id: Uuid) -> Result<Vec<State>>;
+    /// All states of the swap in the order they were entered.
+    async fn get_state_transitions(&self, swap_id: Uuid) -> Result<Vec<StateTransition>>;
+    /// The states of all swaps entered between `from` and `to` (inclusive), oldest first.
+    async fn get_state_transitions_between(
+        &self,
+        from: OffsetDateTime,
+        to: OffsetDateTime,
+    ) -> Result<Vec<(Uuid, StateTransition)>>;
+    /// The states of every swap in the order they were entered.
+    ///
+    /// Swaps with a state which cannot be read are left out.
+    async fn get_all_state_transitions(&self) -> Result<HashMap<Uuid, Vec<StateTransition>>>;
     async fn all(&self) -> Result<Vec<(Uuid, State)>>;
     async fn insert_buffered_transfer_proof(
         &self,
@@ -164,4 +233,20 @@ pub trait Database {
         &self,
         swap_id: Uuid,
     ) -> Result<Option<monero::TransferProof>>;
+    async fn insert_buffered_encrypted_signature(
+        &self,
+        swap_id: Uuid,
+        encrypted_signature: bitcoin::EncryptedSignature,
+    ) -> Result<()>;
+    async fn get_buffered_encrypted_signature(
+        &self,
+        swap_id: Uuid,
+    ) -> Result<Option<bitcoin::EncryptedSignature>>;
+    /// Removes the buffered encrypted signature once it is part of the swap state.
+    async fn remove_buffered_encrypted_signature(&self, swap_id: Uuid) -> Result<()>;
+    async fn insert_swap_rate(&self, swap_id: Uuid, rate: Rate) -> Result<()>;
+    async fn get_swap_rate(&self, swap_id: Uuid) -> Result<Option<Rate>>;
+    /// Records that the swap is part of an order which was split across several makers.
+    async fn insert_swap_order(&self, swap_id: Uuid, order_id: Uuid) -> Result<()>;
+    async fn get_swap_order(&self, swap_id: Uuid) -> Result<Option<Uuid>>;
 }
@@ -1,6 +1,6 @@
 use crate::bitcoin::{
-    current_epoch, CancelTimelock, ExpiredTimelocks, PunishTimelock, Transaction, TxCancel,
-    TxEarlyRefund, TxPunish, TxRedeem, TxRefund, Txid,
+    current_epoch, CancelTimelock, ExpiredTimelocks, PunishTimelock, SwapTransaction, Transaction,
+    TxCancel, TxEarlyRefund, TxPunish, TxRedeem, TxRefund, Txid,
 };
 use crate::env::Config;
 use crate::monero::wallet::{TransferRequest, WatchRequest};
@@ -118,6 +118,66 @@ impl fmt::Display for AliceState {
     }
 }
 
+impl AliceState {
+    /// The parameters of the swap, `None` in the final states which no longer carry them.
+    pub fn state3(&self) -> Option<&State3> {
+        match self {
+            AliceState::Started { state3 }
+            | AliceState::BtcLockTransactionSeen { state3 }
+            | AliceState::BtcLocked { state3 }
+            | AliceState::BtcEarlyRefundable { state3 }
+            | AliceState::XmrLockTransactionSent { state3, .. }
+            | AliceState::XmrLocked { state3, .. }
+            | AliceState::XmrLockTransferProofSent { state3, .. }
+            | AliceState::EncSigLearned { state3, .. }
+            | AliceState::BtcRedeemTransactionPublished { state3, .. }
+            | AliceState::BtcCancelled { state3, .. }
+            | AliceState::BtcEarlyRefunded(state3)
+            | AliceState::BtcRefunded { state3, .. }
+            | AliceState::BtcPunishable { state3, .. }
+            | AliceState::CancelTimelockExpired { state3, .. }
+            | AliceState::BtcPunished { state3, .. } => Some(state3.as_ref()),
+            AliceState::BtcRedeemed | AliceState::XmrRefunded | AliceState::SafelyAborted => None,
+        }
+    }
+
+    /// All Bitcoin transactions of the swap, whether they have been published or not.
+    pub fn swap_transactions(&self) -> Vec<SwapTransaction> {
+        let Some(state3) = self.state3() else {
+            return vec![];
+        };
+
+        vec![
+            SwapTransaction::new("lock", &state3.tx_lock),
+            SwapTransaction::new("redeem", &state3.tx_redeem()),
+            SwapTransaction::new("early_refund", &state3.tx_early_refund()),
+            SwapTransaction::new("cancel", &state3.tx_cancel()),
+            SwapTransaction::new("refund", &state3.tx_refund()),
+            SwapTransaction::new("punish", &state3.tx_punish()),
+        ]
+    }
+
+    /// The Bitcoin transactions we may have to get confirmed in this state,
+    /// ordered such that each transaction only spends the ones before it.
+    pub fn transactions_to_confirm(&self) -> Vec<SwapTransaction> {
+        match self {
+            AliceState::BtcRedeemTransactionPublished { state3, .. } => {
+                vec![SwapTransaction::new("redeem", &state3.tx_redeem())]
+            }
+            AliceState::BtcCancelled { state3, .. }
+            | AliceState::CancelTimelockExpired { state3, .. }
+            | AliceState::BtcPunishable { state3, .. } => {
+                vec![SwapTransaction::new("cancel", &state3.tx_cancel())]
+            }
+            AliceState::BtcPunished { state3, .. } => vec![
+                SwapTransaction::new("cancel", &state3.tx_cancel()),
+                SwapTransaction::new("punish", &state3.tx_punish()),
+            ],
+            _ => vec![],
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct State0 {
     a: bitcoin::SecretKey,
@@ -434,7 +494,7 @@ pub struct State3 {
     #[serde(default)]
     tx_early_refund_sig_bob: Option<bitcoin::Signature>,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
-    tx_redeem_fee: bitcoin::Amount,
+    pub tx_redeem_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     pub tx_punish_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
@@ -589,24 +649,18 @@ impl State3 {
 
         tracing::info!("Refunding Monero");
 
-        tracing::debug!(%swap_id, "Opening temporary Monero wallet from keys");
-        let swap_wallet = monero_wallet
-            .swap_wallet(swap_id, spend_key, view_key, transfer_proof.tx_hash())
-            .await
-            .context(format!("Failed to open/create swap wallet `{}`", swap_id))?;
-
-        // Update blockheight to ensure that the wallet knows the funds are unlocked
-        tracing::debug!(%swap_id, "Updating temporary Monero wallet's blockheight");
-        let _ = swap_wallet
-            .blockchain_height()
-            .await
-            .context("Couldn't get Monero blockheight")?;
-
         tracing::debug!(%swap_id, "Sweeping Monero to redeem address");
-        let main_address = monero_wallet.main_wallet().await.main_address().await;
+        let receive_address = monero_wallet.swap_receive_address(swap_id).await;
 
-        swap_wallet
-            .sweep(&main_address)
+        monero_wallet
+            .sweep_swap_wallet(
+                swap_id,
+                spend_key,
+                view_key,
+                transfer_proof.tx_hash(),
+                &[receive_address],
+                &[100.0],
+            )
             .await
             .context("Failed to sweep Monero to redeem address")?;
 
@@ -659,7 +713,7 @@ impl State3 {
         }
     }
 
-    fn tx_punish(&self) -> TxPunish {
+    pub fn tx_punish(&self) -> TxPunish {
         bitcoin::TxPunish::new(
             &self.tx_cancel(),
             &self.punish_address,
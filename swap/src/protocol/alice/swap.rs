@@ -10,11 +10,13 @@ use crate::common::retry;
 use crate::env::Config;
 use crate::monero::TransferProof;
 use crate::protocol::alice::{AliceState, Swap};
+use crate::protocol::Database;
 use crate::{bitcoin, monero};
 use ::bitcoin::consensus::encode::serialize_hex;
 use anyhow::{bail, Context, Result};
 use tokio::select;
 use tokio::time::timeout;
+use tracing::{field, Span};
 use uuid::Uuid;
 
 pub async fn run<LR>(swap: Swap, rate_service: LR) -> Result<AliceState>
@@ -24,10 +26,20 @@ where
     run_until(swap, |_| false, rate_service).await
 }
 
-#[tracing::instrument(name = "swap", skip(swap,exit_early,rate_service), fields(id = %swap.swap_id), err)]
+/// Every message logged while running the swap is correlated by the fields of the `swap` span.
+#[tracing::instrument(
+    name = "swap",
+    skip(swap, exit_early, rate_service),
+    fields(
+        swap_id = %swap.swap_id,
+        peer_id = %swap.event_loop_handle.peer_id(),
+        state = %swap.state,
+    ),
+    err
+)]
 pub async fn run_until<LR>(
     mut swap: Swap,
-    exit_early: fn(&AliceState) -> bool,
+    exit_early: impl Fn(&AliceState) -> bool,
     rate_service: LR,
 ) -> Result<AliceState>
 where
@@ -40,6 +52,7 @@ where
             swap.swap_id,
             current_state,
             &mut swap.event_loop_handle,
+            swap.db.clone(),
             swap.bitcoin_wallet.as_ref(),
             swap.monero_wallet.clone(),
             &swap.env_config,
@@ -50,6 +63,8 @@ where
         swap.db
             .insert_latest_state(swap.swap_id, current_state.clone().into())
             .await?;
+
+        Span::current().record("state", field::display(&current_state));
     }
 
     Ok(current_state)
@@ -59,6 +74,7 @@ async fn next_state<LR>(
     swap_id: Uuid,
     state: AliceState,
     event_loop_handle: &mut EventLoopHandle,
+    db: Arc<dyn Database + Send + Sync>,
     bitcoin_wallet: &bitcoin::Wallet,
     monero_wallet: Arc<monero::Wallets>,
     env_config: &Config,
@@ -77,12 +93,19 @@ where
         AliceState::Started { state3 } => {
             let tx_lock_status = bitcoin_wallet.subscribe_to(state3.tx_lock.clone()).await;
 
-            match timeout(
-                env_config.bitcoin_lock_mempool_timeout,
-                tx_lock_status.wait_until_seen(),
-            )
-            .await
-            {
+            // Bob might change his mind before we lock the Monero
+            let result = select! {
+                result = timeout(
+                    env_config.bitcoin_lock_mempool_timeout,
+                    tx_lock_status.wait_until_seen(),
+                ) => result,
+                _ = event_loop_handle.accept_early_refund_request() => {
+                    tracing::info!("Bob requested an early refund, we will not lock the Monero");
+                    return Ok(AliceState::BtcEarlyRefundable { state3 });
+                }
+            };
+
+            match result {
                 Err(_) => {
                     tracing::info!(
                         minutes = %env_config.bitcoin_lock_mempool_timeout.as_secs_f64() / 60.0,
@@ -99,12 +122,18 @@ where
         AliceState::BtcLockTransactionSeen { state3 } => {
             let tx_lock_status = bitcoin_wallet.subscribe_to(state3.tx_lock.clone()).await;
 
-            match timeout(
-                env_config.bitcoin_lock_confirmed_timeout,
-                tx_lock_status.wait_until_final(),
-            )
-            .await
-            {
+            let result = select! {
+                result = timeout(
+                    env_config.bitcoin_lock_confirmed_timeout,
+                    tx_lock_status.wait_until_final(),
+                ) => result,
+                _ = event_loop_handle.accept_early_refund_request() => {
+                    tracing::info!("Bob requested an early refund, we will not lock the Monero");
+                    return Ok(AliceState::BtcEarlyRefundable { state3 });
+                }
+            };
+
+            match result {
                 Err(_) => {
                     tracing::info!(
                         confirmations_needed = %env_config.bitcoin_finality_confirmations,
@@ -130,6 +159,9 @@ where
                 .with_max_interval(Duration::from_secs(30))
                 .build();
 
+            // We only look at early refund requests between attempts, never while publishing
+            let event_loop_handle = &*event_loop_handle;
+
             let transfer_proof = backoff::future::retry_notify(
                 backoff,
                 || async {
@@ -144,7 +176,12 @@ where
                             .map_err(backoff::Error::transient)?,
                         ExpiredTimelocks::None { .. }
                     ) {
-                        return Ok(None);
+                        return Ok(MoneroLock::TimelockExpired);
+                    }
+
+                    // Bob asked us to refund his Bitcoin early, we have not locked anything yet
+                    if event_loop_handle.try_accept_early_refund_request() {
+                        return Ok(MoneroLock::EarlyRefundRequested);
                     }
 
                     // Record the current monero wallet block height so we don't have to scan from
@@ -159,11 +196,9 @@ where
                         .lock_xmr_transfer_request()
                         .address_and_amount(env_config.monero_network);
 
-                    // Lock the Monero
+                    // Lock the Monero, this waits for the offline signer if we have one
                     let receipt = monero_wallet
-                        .main_wallet()
-                        .await
-                        .transfer(&address, amount)
+                        .lock_transfer(swap_id, &address, amount)
                         .await
                         .map_err(|e| tracing::error!(err=%e, "Failed to lock Monero"))
                         .ok();
@@ -174,14 +209,14 @@ where
                         )));
                     };
 
-                    Ok(Some((
+                    Ok(MoneroLock::Locked {
                         monero_wallet_restore_blockheight,
-                        TransferProof::new(
+                        transfer_proof: TransferProof::new(
                             monero::TxHash(receipt.txid),
                             monero::PrivateKey::from_str(&receipt.tx_key)
                                 .expect("tx key to be valid private key"),
                         ),
-                    )))
+                    })
                 },
                 |e, wait_time: Duration| {
                     tracing::warn!(
@@ -196,17 +231,18 @@ where
 
             match transfer_proof {
                 // If the transfer was successful, we transition to the next state
-                Ok(Some((monero_wallet_restore_blockheight, transfer_proof))) => {
-                    AliceState::XmrLockTransactionSent {
-                        monero_wallet_restore_blockheight,
-                        transfer_proof,
-                        state3,
-                    }
-                }
+                Ok(MoneroLock::Locked {
+                    monero_wallet_restore_blockheight,
+                    transfer_proof,
+                }) => AliceState::XmrLockTransactionSent {
+                    monero_wallet_restore_blockheight,
+                    transfer_proof,
+                    state3,
+                },
                 // If we were not able to lock the Monero funds before the timelock expired,
                 // we can safely abort the swap because we did not lock any funds
                 // We do not do an early refund because Bob can refund himself (timelock expired)
-                Ok(None) => {
+                Ok(MoneroLock::TimelockExpired) => {
                     tracing::info!(
                         swap_id = %swap_id,
                         "We did not manage to lock the Monero funds before the timelock expired. Aborting swap."
@@ -214,6 +250,14 @@ where
 
                     AliceState::SafelyAborted
                 }
+                Ok(MoneroLock::EarlyRefundRequested) => {
+                    tracing::info!(
+                        swap_id = %swap_id,
+                        "Bob requested an early refund, we will not lock the Monero"
+                    );
+
+                    AliceState::BtcEarlyRefundable { state3 }
+                }
                 Err(e) => {
                     tracing::error!(
                         swap_id = %swap_id,
@@ -372,40 +416,57 @@ where
             let tx_lock_status_subscription =
                 bitcoin_wallet.subscribe_to(state3.tx_lock.clone()).await;
 
-            select! {
-                biased; // make sure the cancel timelock expiry future is polled first
-                result = tx_lock_status_subscription.wait_until_confirmed_with(state3.cancel_timelock) => {
-                    result?;
-                    AliceState::CancelTimelockExpired {
-                        monero_wallet_restore_blockheight,
-                        transfer_proof,
-                        state3,
-                    }
+            // Bob might have sent us the encrypted signature before we were waiting for it
+            // (e.g. while we were restarting). In that case the event loop buffered it in the database.
+            let buffered_enc_sig = db
+                .get_buffered_encrypted_signature(swap_id)
+                .await
+                .context("Failed to get buffered encrypted signature")?;
+
+            let enc_sig = match buffered_enc_sig {
+                Some(enc_sig) => {
+                    tracing::debug!("Found buffered encrypted signature");
+                    enc_sig
                 }
-                enc_sig = event_loop_handle.recv_encrypted_signature() => {
-                    // Fetch the status as early as possible to update the internal cache of our Electurm client
-                    // Prevents redundant network requests later on when we redeem the Bitcoin
-                    let tx_lock_status = bitcoin_wallet.status_of_script(&state3.tx_lock.clone()).await?;
-
-                    if tx_lock_status.is_confirmed_with(state3.cancel_timelock.half()) {
-                        tx_lock_status_subscription.wait_until_confirmed_with(state3.cancel_timelock).await?;
-
+                None => select! {
+                    biased; // make sure the cancel timelock expiry future is polled first
+                    result = tx_lock_status_subscription.wait_until_confirmed_with(state3.cancel_timelock) => {
+                        result?;
                         return Ok(AliceState::CancelTimelockExpired {
                             monero_wallet_restore_blockheight,
                             transfer_proof,
                             state3,
-                        })
+                        });
                     }
+                    enc_sig = event_loop_handle.recv_encrypted_signature() => enc_sig?,
+                },
+            };
 
-                    tracing::info!("Received encrypted signature");
+            // Fetch the status as early as possible to update the internal cache of our Electurm client
+            // Prevents redundant network requests later on when we redeem the Bitcoin
+            let tx_lock_status = bitcoin_wallet
+                .status_of_script(&state3.tx_lock.clone())
+                .await?;
 
-                    AliceState::EncSigLearned {
-                        monero_wallet_restore_blockheight,
-                        transfer_proof,
-                        encrypted_signature: Box::new(enc_sig?),
-                        state3,
-                    }
-                }
+            if tx_lock_status.is_confirmed_with(state3.cancel_timelock.half()) {
+                tx_lock_status_subscription
+                    .wait_until_confirmed_with(state3.cancel_timelock)
+                    .await?;
+
+                return Ok(AliceState::CancelTimelockExpired {
+                    monero_wallet_restore_blockheight,
+                    transfer_proof,
+                    state3,
+                });
+            }
+
+            tracing::info!("Received encrypted signature");
+
+            AliceState::EncSigLearned {
+                monero_wallet_restore_blockheight,
+                transfer_proof,
+                encrypted_signature: Box::new(enc_sig),
+                state3,
             }
         }
         AliceState::EncSigLearned {
@@ -414,6 +475,14 @@ where
             encrypted_signature,
             state3,
         } => {
+            // The encrypted signature is stored as part of this state now
+            if let Err(error) = db.remove_buffered_encrypted_signature(swap_id).await {
+                tracing::warn!(
+                    "Failed to remove the buffered encrypted signature: {:#}",
+                    error
+                );
+            }
+
             // Try to sign the redeem transaction, otherwise wait for the cancel timelock to expire
             let tx_redeem = match state3.signed_redeem_transaction(*encrypted_signature) {
                 Ok(tx_redeem) => tx_redeem,
@@ -647,6 +716,16 @@ where
     })
 }
 
+/// The outcome of trying to lock the Monero in [`AliceState::BtcLocked`].
+enum MoneroLock {
+    Locked {
+        monero_wallet_restore_blockheight: monero::BlockHeight,
+        transfer_proof: TransferProof,
+    },
+    TimelockExpired,
+    EarlyRefundRequested,
+}
+
 pub fn is_complete(state: &AliceState) -> bool {
     matches!(
         state,
@@ -40,12 +40,14 @@ impl Swap {
         bitcoin_change_address: bitcoin::Address,
         btc_amount: bitcoin::Amount,
         tx_lock_fee: bitcoin::Amount,
+        tx_lock_inputs: Option<Vec<bitcoin::OutPoint>>,
     ) -> Self {
         Self {
             state: BobState::Started {
                 btc_amount,
                 tx_lock_fee,
                 change_address: bitcoin_change_address,
+                tx_lock_inputs,
             },
             event_loop_handle,
             db,
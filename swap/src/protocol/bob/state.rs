@@ -1,8 +1,8 @@
 use crate::bitcoin::address_serde;
 use crate::bitcoin::wallet::{EstimateFeeRate, Subscription};
 use crate::bitcoin::{
-    self, current_epoch, CancelTimelock, ExpiredTimelocks, PunishTimelock, Transaction, TxCancel,
-    TxLock, Txid, Wallet,
+    self, current_epoch, CancelTimelock, ExpiredTimelocks, PunishTimelock, SwapTransaction,
+    Transaction, TxCancel, TxLock, Txid, Wallet,
 };
 use crate::monero::wallet::WatchRequest;
 use crate::monero::{self, MoneroAddressPool, TxHash};
@@ -30,12 +30,20 @@ pub enum BobState {
         tx_lock_fee: bitcoin::Amount,
         #[serde(with = "address_serde")]
         change_address: bitcoin::Address,
+        /// The UTXOs the user selected to fund the lock transaction.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        tx_lock_inputs: Option<Vec<bitcoin::OutPoint>>,
     },
     SwapSetupCompleted(State2),
     BtcLocked {
         state3: State3,
         monero_wallet_restore_blockheight: BlockHeight,
     },
+    /// We asked Alice to refund our Bitcoin early instead of locking the Monero.
+    BtcEarlyRefundRequested {
+        state3: State3,
+        monero_wallet_restore_blockheight: BlockHeight,
+    },
     XmrLockProofReceived {
         state: State3,
         lock_transfer_proof: TransferProof,
@@ -66,6 +74,7 @@ impl fmt::Display for BobState {
             BobState::Started { .. } => write!(f, "quote has been requested"),
             BobState::SwapSetupCompleted(..) => write!(f, "execution setup done"),
             BobState::BtcLocked { .. } => write!(f, "btc is locked"),
+            BobState::BtcEarlyRefundRequested { .. } => write!(f, "btc early refund is requested"),
             BobState::XmrLockProofReceived { .. } => {
                 write!(f, "XMR lock transaction transfer proof received")
             }
@@ -97,6 +106,7 @@ impl BobState {
             | BobState::SafelyAborted
             | BobState::SwapSetupCompleted(_) => None,
             BobState::BtcLocked { state3: state, .. }
+            | BobState::BtcEarlyRefundRequested { state3: state, .. }
             | BobState::XmrLockProofReceived { state, .. } => {
                 Some(state.expired_timelock(&bitcoin_wallet).await?)
             }
@@ -116,6 +126,79 @@ impl BobState {
             | BobState::XmrRedeemed { .. } => None,
         })
     }
+
+    /// All Bitcoin transactions of the swap we can construct in this state,
+    /// whether they have been published or not.
+    pub fn swap_transactions(&self) -> Result<Vec<SwapTransaction>> {
+        Ok(match self {
+            BobState::SwapSetupCompleted(state2) => {
+                vec![SwapTransaction::new("lock", &state2.tx_lock)]
+            }
+            BobState::BtcLocked { state3: state, .. }
+            | BobState::BtcEarlyRefundRequested { state3: state, .. }
+            | BobState::XmrLockProofReceived { state, .. } => vec![
+                SwapTransaction::new("lock", &state.tx_lock),
+                SwapTransaction::new("early_refund", &state.construct_tx_early_refund()),
+            ],
+            BobState::XmrLocked(state) | BobState::EncSigSent(state) => vec![
+                SwapTransaction::new("lock", &state.tx_lock),
+                SwapTransaction::new("early_refund", &state.construct_tx_early_refund()),
+            ],
+            BobState::BtcRedeemed(state) => vec![SwapTransaction::new("lock", &state.tx_lock)],
+            BobState::CancelTimelockExpired(state)
+            | BobState::BtcCancelled(state)
+            | BobState::BtcRefundPublished(state)
+            | BobState::BtcEarlyRefundPublished(state)
+            | BobState::BtcRefunded(state)
+            | BobState::BtcEarlyRefunded(state)
+            | BobState::BtcPunished { state, .. } => vec![
+                SwapTransaction::new("lock", &state.tx_lock),
+                SwapTransaction::new("cancel", &state.construct_tx_cancel()?),
+                SwapTransaction::new("refund", &state.construct_tx_refund()?),
+                SwapTransaction::new("early_refund", &state.construct_tx_early_refund()),
+            ],
+            BobState::Started { .. } | BobState::XmrRedeemed { .. } | BobState::SafelyAborted => {
+                vec![]
+            }
+        })
+    }
+
+    /// The Bitcoin transactions we may have to get confirmed in this state,
+    /// ordered such that each transaction only spends the ones before it.
+    pub fn transactions_to_confirm(&self) -> Result<Vec<SwapTransaction>> {
+        Ok(match self {
+            BobState::BtcLocked { state3: state, .. }
+            | BobState::BtcEarlyRefundRequested { state3: state, .. }
+            | BobState::XmrLockProofReceived { state, .. } => {
+                vec![SwapTransaction::new("lock", &state.tx_lock)]
+            }
+            BobState::XmrLocked(state) | BobState::EncSigSent(state) => {
+                vec![SwapTransaction::new("lock", &state.tx_lock)]
+            }
+            BobState::CancelTimelockExpired(state) | BobState::BtcCancelled(state) => {
+                vec![SwapTransaction::new(
+                    "cancel",
+                    &state.construct_tx_cancel()?,
+                )]
+            }
+            BobState::BtcRefundPublished(state) => vec![
+                SwapTransaction::new("cancel", &state.construct_tx_cancel()?),
+                SwapTransaction::new("refund", &state.construct_tx_refund()?),
+            ],
+            BobState::BtcEarlyRefundPublished(state) => vec![SwapTransaction::new(
+                "early_refund",
+                &state.construct_tx_early_refund(),
+            )],
+            BobState::Started { .. }
+            | BobState::SwapSetupCompleted(_)
+            | BobState::BtcRedeemed(_)
+            | BobState::BtcRefunded(_)
+            | BobState::BtcEarlyRefunded(_)
+            | BobState::XmrRedeemed { .. }
+            | BobState::BtcPunished { .. }
+            | BobState::SafelyAborted => vec![],
+        })
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -136,6 +219,7 @@ pub struct State0 {
     tx_refund_fee: bitcoin::Amount,
     tx_cancel_fee: bitcoin::Amount,
     tx_lock_fee: bitcoin::Amount,
+    tx_lock_inputs: Option<Vec<bitcoin::OutPoint>>,
 }
 
 impl State0 {
@@ -152,6 +236,7 @@ impl State0 {
         tx_refund_fee: bitcoin::Amount,
         tx_cancel_fee: bitcoin::Amount,
         tx_lock_fee: bitcoin::Amount,
+        tx_lock_inputs: Option<Vec<bitcoin::OutPoint>>,
     ) -> Self {
         let b = bitcoin::SecretKey::new_random(rng);
 
@@ -179,6 +264,7 @@ impl State0 {
             tx_refund_fee,
             tx_cancel_fee,
             tx_lock_fee,
+            tx_lock_inputs,
         }
     }
 
@@ -226,6 +312,7 @@ impl State0 {
             msg.A,
             self.b.public(),
             self.refund_address.clone(),
+            self.tx_lock_inputs.as_deref(),
         )
         .await?;
         let v = msg.v_a + self.v_b;
@@ -349,9 +436,9 @@ pub struct State2 {
     tx_refund_encsig: bitcoin::EncryptedSignature,
     min_monero_confirmations: u64,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
-    tx_redeem_fee: bitcoin::Amount,
+    pub tx_redeem_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
-    tx_punish_fee: bitcoin::Amount,
+    pub tx_punish_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     pub tx_refund_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
@@ -573,7 +660,7 @@ pub struct State4 {
     tx_cancel_sig_a: Signature,
     tx_refund_encsig: bitcoin::EncryptedSignature,
     monero_wallet_restore_blockheight: BlockHeight,
-    lock_transfer_proof: TransferProof,
+    pub lock_transfer_proof: TransferProof,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
     tx_redeem_fee: bitcoin::Amount,
     #[serde(with = "::bitcoin::amount::serde::as_sat")]
@@ -583,12 +670,15 @@ pub struct State4 {
 }
 
 impl State4 {
+    pub fn construct_tx_redeem(&self) -> bitcoin::TxRedeem {
+        bitcoin::TxRedeem::new(&self.tx_lock, &self.redeem_address, self.tx_redeem_fee)
+    }
+
     pub async fn check_for_tx_redeem(
         &self,
         bitcoin_wallet: &bitcoin::Wallet,
     ) -> Result<Option<State5>> {
-        let tx_redeem =
-            bitcoin::TxRedeem::new(&self.tx_lock, &self.redeem_address, self.tx_redeem_fee);
+        let tx_redeem = self.construct_tx_redeem();
         let tx_redeem_encsig = self.b.encsign(self.S_a_bitcoin, tx_redeem.digest());
 
         let tx_redeem_candidate = bitcoin_wallet.get_raw_transaction(tx_redeem.txid()).await?;
@@ -614,14 +704,12 @@ impl State4 {
     }
 
     pub fn tx_redeem_encsig(&self) -> bitcoin::EncryptedSignature {
-        let tx_redeem =
-            bitcoin::TxRedeem::new(&self.tx_lock, &self.redeem_address, self.tx_redeem_fee);
+        let tx_redeem = self.construct_tx_redeem();
         self.b.encsign(self.S_a_bitcoin, tx_redeem.digest())
     }
 
     pub async fn watch_for_redeem_btc(&self, bitcoin_wallet: &bitcoin::Wallet) -> Result<State5> {
-        let tx_redeem =
-            bitcoin::TxRedeem::new(&self.tx_lock, &self.redeem_address, self.tx_redeem_fee);
+        let tx_redeem = self.construct_tx_redeem();
 
         bitcoin_wallet
             .subscribe_to(tx_redeem.clone())
@@ -734,32 +822,24 @@ impl State5 {
 
         tracing::info!(%swap_id, "Redeeming Monero from extracted keys");
 
-        tracing::debug!(%swap_id, "Opening temporary Monero wallet");
+        tracing::debug!(%swap_id, receive_address=?monero_receive_pool, "Sweeping Monero to receive address");
 
-        let wallet = monero_wallet
-            .swap_wallet(
+        // Monero for the internal wallet goes to a subaddress dedicated to this swap
+        let receive_address = match monero_receive_pool
+            .iter()
+            .any(|address| address.address().is_none())
+        {
+            true => monero_wallet.swap_receive_address(swap_id).await,
+            false => monero_wallet.main_address().await,
+        };
+
+        let tx_hashes = monero_wallet
+            .sweep_swap_wallet(
                 swap_id,
                 spend_key,
                 view_key,
                 self.lock_transfer_proof.tx_hash(),
-            )
-            .await
-            .context("Failed to open Monero wallet")?;
-
-        // Update blockheight to ensure that the wallet knows the funds are unlocked
-        tracing::debug!(%swap_id, "Updating temporary Monero wallet's blockheight");
-        let _ = wallet
-            .blockchain_height()
-            .await
-            .context("Couldn't get Monero blockheight")?;
-
-        tracing::debug!(%swap_id, receive_address=?monero_receive_pool, "Sweeping Monero to receive address");
-
-        let main_address = monero_wallet.main_wallet().await.main_address().await;
-
-        let tx_hashes = wallet
-            .sweep_multi(
-                &monero_receive_pool.fill_empty_addresses(main_address),
+                &monero_receive_pool.fill_empty_addresses(receive_address),
                 &monero_receive_pool.percentages(),
             )
             .await
@@ -5,6 +5,7 @@ use crate::cli::api::tauri_bindings::{TauriEmitter, TauriHandle, TauriSwapProgre
 use crate::cli::EventLoopHandle;
 use crate::common::retry;
 use crate::monero::MoneroAddressPool;
+use crate::network::cooperative_early_refund;
 use crate::network::cooperative_xmr_redeem_after_punish::Response::{Fullfilled, Rejected};
 use crate::network::swap_setup::bob::NewSwap;
 use crate::protocol::bob::state::*;
@@ -14,6 +15,7 @@ use anyhow::{bail, Context as AnyContext, Result};
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::select;
+use tracing::{field, Span};
 use uuid::Uuid;
 
 const PRE_BTC_LOCK_APPROVAL_TIMEOUT_SECS: u64 = 60 * 3;
@@ -60,9 +62,20 @@ pub async fn run(swap: bob::Swap) -> Result<BobState> {
     run_until(swap, is_complete).await
 }
 
+/// Every message logged while running the swap is correlated by the fields of the `swap` span.
+#[tracing::instrument(
+    name = "swap",
+    skip(swap, is_target_state),
+    fields(
+        swap_id = %swap.id,
+        peer_id = %swap.event_loop_handle.peer_id(),
+        state = %swap.state,
+    ),
+    err
+)]
 pub async fn run_until(
     mut swap: bob::Swap,
-    is_target_state: fn(&BobState) -> bool,
+    is_target_state: impl Fn(&BobState) -> bool,
 ) -> Result<BobState> {
     let mut current_state = swap.state.clone();
 
@@ -89,6 +102,8 @@ pub async fn run_until(
         }
 
         current_state = next_state;
+
+        Span::current().record("state", field::display(&current_state));
     }
 
     Ok(current_state)
@@ -113,6 +128,7 @@ async fn next_state(
             btc_amount,
             change_address,
             tx_lock_fee,
+            tx_lock_inputs,
         } => {
             let tx_refund_fee = bitcoin_wallet
                 .estimate_fee(TxRefund::weight(), Some(btc_amount))
@@ -139,6 +155,7 @@ async fn next_state(
                     tx_refund_fee,
                     tx_cancel_fee,
                     bitcoin_refund_address: change_address,
+                    tx_lock_inputs,
                 })
                 .await?;
 
@@ -346,6 +363,49 @@ async fn next_state(
                 },
             }
         }
+        // We asked to get our Bitcoin back before Alice locks the Monero
+        BobState::BtcEarlyRefundRequested {
+            state3,
+            monero_wallet_restore_blockheight,
+        } => {
+            // Alice already locked the Monero, there is nothing to refund early anymore
+            if let Some(transfer_proof) = db
+                .get_buffered_transfer_proof(swap_id)
+                .await
+                .context("Failed to get buffered transfer proof")?
+            {
+                tracing::info!(txid = %transfer_proof.tx_hash(), "Alice already locked the Monero, continuing the swap instead of refunding early");
+
+                return Ok(BobState::XmrLockProofReceived {
+                    state: state3,
+                    lock_transfer_proof: transfer_proof,
+                    monero_wallet_restore_blockheight,
+                });
+            }
+
+            tracing::info!("Requesting Alice to refund our Bitcoin early");
+
+            // Either way we go back to waiting for Alice, which also watches for the early refund transaction
+            match event_loop_handle.request_cooperative_early_refund().await {
+                Ok(cooperative_early_refund::Response::Accepted { .. }) => {
+                    tracing::info!("Alice agreed to refund our Bitcoin early, waiting for her to publish the early refund transaction");
+                }
+                Ok(cooperative_early_refund::Response::Rejected { reason, .. }) => {
+                    tracing::warn!(%reason, "Alice rejected our request for an early refund, continuing the swap");
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        ?error,
+                        "Failed to request an early refund from Alice, continuing the swap"
+                    );
+                }
+            }
+
+            BobState::BtcLocked {
+                state3,
+                monero_wallet_restore_blockheight,
+            }
+        }
         BobState::XmrLockProofReceived {
             state,
             lock_transfer_proof,
@@ -0,0 +1,628 @@
+//! Export of the swap history for bookkeeping and tax reporting.
+//!
+//! A [`SwapRecord`] collects everything about a single swap that ends up in the
+//! books: the amounts and the effective price, the published Bitcoin transactions
+//! with their fees, the Monero lock transaction, the counterparty and when each
+//! state was entered. Both the CLI and the ASB render the records of their
+//! database as CSV or JSON.
+
+use crate::bitcoin::{self, SwapTransaction, Txid};
+use crate::monero::{self, TransferProof};
+use crate::protocol::alice::AliceState;
+use crate::protocol::bob::BobState;
+use crate::protocol::timeline::{self, TimelineEntry};
+use crate::protocol::{Database, State, StateTransition};
+use anyhow::{bail, Context, Result};
+use libp2p::PeerId;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+use time::{Date, OffsetDateTime, UtcOffset};
+use typeshare::typeshare;
+use uuid::Uuid;
+
+/// The kinds of Bitcoin transactions of a swap, in the order they appear in a CSV row.
+const BITCOIN_TRANSACTION_KINDS: [&str; 6] = [
+    "lock",
+    "redeem",
+    "early_refund",
+    "cancel",
+    "refund",
+    "punish",
+];
+
+#[typeshare]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => bail!("Unknown export format `{}`, expected `csv` or `json`", s),
+        }
+    }
+}
+
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExportFormat::Csv => write!(f, "csv"),
+            ExportFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+/// Only export swaps started within these days, both ends are inclusive and in UTC.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: Option<Date>,
+    pub to: Option<Date>,
+}
+
+impl DateRange {
+    /// Parse the bounds given as `YYYY-MM-DD`.
+    pub fn parse(from: Option<&str>, to: Option<&str>) -> Result<Self> {
+        let range = Self {
+            from: from.map(parse_date).transpose()?,
+            to: to.map(parse_date).transpose()?,
+        };
+
+        if let (Some(from), Some(to)) = (range.from, range.to) {
+            if from > to {
+                bail!(
+                    "The start of the date range ({}) is after its end ({})",
+                    from,
+                    to
+                );
+            }
+        }
+
+        Ok(range)
+    }
+
+    pub fn contains(&self, timestamp: OffsetDateTime) -> bool {
+        let date = timestamp.to_offset(UtcOffset::UTC).date();
+
+        self.from.is_none_or(|from| from <= date) && self.to.is_none_or(|to| date <= to)
+    }
+}
+
+pub fn parse_date(date: &str) -> Result<Date> {
+    let format = time::format_description::parse("[year]-[month]-[day]")?;
+
+    Date::parse(date, &format)
+        .with_context(|| format!("Invalid date `{}`, expected YYYY-MM-DD", date))
+}
+
+/// Everything about a single swap that is relevant for bookkeeping.
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SwapRecord {
+    #[typeshare(serialized_as = "string")]
+    pub swap_id: Uuid,
+    /// `None` if the peer id of the counterparty could not be read from the database.
+    #[typeshare(serialized_as = "Option<string>")]
+    pub counterparty_peer_id: Option<PeerId>,
+    /// When the swap was started, RFC 3339 in UTC.
+    pub start_date: String,
+    pub state: String,
+    pub completed: bool,
+    #[typeshare(serialized_as = "number")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat")]
+    pub btc_amount: bitcoin::Amount,
+    #[typeshare(serialized_as = "number")]
+    pub xmr_amount: monero::Amount,
+    /// The effective price in BTC per XMR.
+    #[typeshare(serialized_as = "number")]
+    pub price: Decimal,
+    /// The Bitcoin transactions of the swap which have been published.
+    pub bitcoin_transactions: Vec<BitcoinTransactionRecord>,
+    /// The hash of the Monero lock transaction, once it has been published.
+    pub xmr_lock_tx_hash: Option<String>,
+    pub transitions: Vec<TimelineEntry>,
+}
+
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BitcoinTransactionRecord {
+    pub kind: String,
+    #[typeshare(serialized_as = "string")]
+    pub txid: Txid,
+    /// `None` if the fee cannot be determined from the swap data.
+    #[typeshare(serialized_as = "Option<number>")]
+    #[serde(with = "::bitcoin::amount::serde::as_sat::opt")]
+    pub fee: Option<bitcoin::Amount>,
+}
+
+/// The records of all swaps in the database started within `range`, oldest first.
+///
+/// Swaps which were aborted before the amounts were agreed on are skipped, there
+/// is nothing to report for them.
+pub async fn swap_records(
+    db: &(dyn Database + Send + Sync),
+    range: DateRange,
+) -> Result<Vec<SwapRecord>> {
+    let mut records = Vec::new();
+    let now = OffsetDateTime::now_utc();
+
+    for (swap_id, _) in db.all().await? {
+        let transitions = db.get_state_transitions(swap_id).await?;
+
+        let started_at = match transitions.first() {
+            Some(first) if range.contains(first.entered_at) => first.entered_at,
+            _ => continue,
+        };
+
+        let counterparty = match db.get_peer_id(swap_id).await {
+            Ok(peer_id) => Some(peer_id),
+            Err(error) => {
+                tracing::warn!(
+                    %swap_id,
+                    "Failed to read the peer id of the counterparty, exporting the swap without it: {:#}",
+                    error
+                );
+                None
+            }
+        };
+
+        match SwapRecord::new(swap_id, counterparty, &transitions, now)
+            .with_context(|| format!("Failed to export swap {}", swap_id))?
+        {
+            Some(record) => records.push((started_at, record)),
+            None => tracing::debug!(%swap_id, "Swap was aborted before it was set up, skipping it"),
+        }
+    }
+
+    records.sort_by_key(|(started_at, _)| *started_at);
+
+    Ok(records.into_iter().map(|(_, record)| record).collect())
+}
+
+impl SwapRecord {
+    /// Build the record from all states of the swap in the order they were entered.
+    ///
+    /// Returns `None` if the swap never got to agree on the amounts.
+    pub fn new(
+        swap_id: Uuid,
+        counterparty_peer_id: Option<PeerId>,
+        transitions: &[StateTransition],
+        now: OffsetDateTime,
+    ) -> Result<Option<Self>> {
+        let (first, latest) = match (transitions.first(), transitions.last()) {
+            (Some(first), Some(latest)) => (first, latest),
+            _ => bail!("Swap has no states"),
+        };
+        let states = transitions.iter().map(|transition| &transition.state);
+
+        let details = match first.state {
+            State::Alice(_) => alice_details(states.filter_map(|state| match state {
+                State::Alice(state) => Some(state),
+                State::Bob(_) => None,
+            })),
+            State::Bob(_) => bob_details(states.filter_map(|state| match state {
+                State::Bob(state) => Some(state),
+                State::Alice(_) => None,
+            }))?,
+        };
+
+        let Some(details) = details else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            swap_id,
+            counterparty_peer_id,
+            start_date: timeline::format_timestamp(first.entered_at)?,
+            state: latest.state.to_string(),
+            completed: latest.state.swap_finished(),
+            btc_amount: details.btc_amount,
+            xmr_amount: details.xmr_amount,
+            price: price(details.btc_amount, details.xmr_amount)?,
+            bitcoin_transactions: details.bitcoin_transactions(),
+            xmr_lock_tx_hash: details.xmr_lock_tx_hash,
+            transitions: timeline::timeline(transitions, now)?,
+        }))
+    }
+}
+
+/// Render the records in `format`.
+pub fn render(records: &[SwapRecord], format: ExportFormat) -> Result<String> {
+    match format {
+        ExportFormat::Csv => Ok(to_csv(records)),
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(records)?),
+    }
+}
+
+/// One row per swap. Amounts and fees are given in BTC and XMR, transactions
+/// which have not been published and unknown counterparties are left empty.
+fn to_csv(records: &[SwapRecord]) -> String {
+    let mut header = vec![
+        "swap_id".to_string(),
+        "start_date".to_string(),
+        "state".to_string(),
+        "completed".to_string(),
+        "counterparty_peer_id".to_string(),
+        "btc_amount".to_string(),
+        "xmr_amount".to_string(),
+        "price_btc_per_xmr".to_string(),
+    ];
+    for kind in BITCOIN_TRANSACTION_KINDS {
+        header.push(format!("btc_{}_txid", kind));
+        header.push(format!("btc_{}_fee", kind));
+    }
+    header.push("xmr_lock_tx_hash".to_string());
+    header.push("transitions".to_string());
+
+    let mut csv = csv_row(&header);
+
+    for record in records {
+        let mut row = vec![
+            record.swap_id.to_string(),
+            record.start_date.clone(),
+            record.state.clone(),
+            record.completed.to_string(),
+            record
+                .counterparty_peer_id
+                .map(|peer_id| peer_id.to_string())
+                .unwrap_or_default(),
+            btc_decimal(record.btc_amount).to_string(),
+            xmr_decimal(record.xmr_amount).to_string(),
+            record.price.to_string(),
+        ];
+
+        for kind in BITCOIN_TRANSACTION_KINDS {
+            let transaction = record
+                .bitcoin_transactions
+                .iter()
+                .find(|transaction| transaction.kind == kind);
+
+            row.push(
+                transaction
+                    .map(|transaction| transaction.txid.to_string())
+                    .unwrap_or_default(),
+            );
+            row.push(
+                transaction
+                    .and_then(|transaction| transaction.fee)
+                    .map(|fee| btc_decimal(fee).to_string())
+                    .unwrap_or_default(),
+            );
+        }
+
+        row.push(record.xmr_lock_tx_hash.clone().unwrap_or_default());
+        row.push(
+            record
+                .transitions
+                .iter()
+                .map(|transition| format!("{} {}", transition.entered_at, transition.state))
+                .collect::<Vec<_>>()
+                .join("; "),
+        );
+
+        csv.push_str(&csv_row(&row));
+    }
+
+    csv
+}
+
+fn csv_row(fields: &[String]) -> String {
+    let mut row = fields
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",");
+    row.push('\n');
+    row
+}
+
+/// Quote the field if it contains a separator, a quote or a line break (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// What we learn about a swap from the states of one party.
+struct Details {
+    btc_amount: bitcoin::Amount,
+    xmr_amount: monero::Amount,
+    txids: HashMap<&'static str, Txid>,
+    fees: HashMap<&'static str, bitcoin::Amount>,
+    published: HashSet<&'static str>,
+    xmr_lock_tx_hash: Option<String>,
+}
+
+impl Details {
+    fn bitcoin_transactions(&self) -> Vec<BitcoinTransactionRecord> {
+        BITCOIN_TRANSACTION_KINDS
+            .iter()
+            .filter(|kind| self.published.contains(*kind))
+            .filter_map(|kind| {
+                Some(BitcoinTransactionRecord {
+                    kind: kind.to_string(),
+                    txid: *self.txids.get(kind)?,
+                    fee: self.fees.get(kind).copied(),
+                })
+            })
+            .collect()
+    }
+
+    fn add_transactions(&mut self, transactions: Vec<SwapTransaction>) {
+        for transaction in transactions {
+            self.txids
+                .entry(transaction.kind)
+                .or_insert(transaction.txid);
+        }
+    }
+}
+
+fn alice_details<'a>(states: impl Iterator<Item = &'a AliceState>) -> Option<Details> {
+    let mut details: Option<Details> = None;
+
+    for state in states {
+        if let Some(state3) = state.state3() {
+            let details = details.get_or_insert_with(|| Details {
+                btc_amount: state3.btc,
+                xmr_amount: state3.xmr,
+                txids: HashMap::new(),
+                fees: [
+                    ("redeem", state3.tx_redeem_fee),
+                    ("early_refund", state3.tx_refund_fee),
+                    ("cancel", state3.tx_cancel_fee),
+                    ("refund", state3.tx_refund_fee),
+                    ("punish", state3.tx_punish_fee),
+                ]
+                .into_iter()
+                .chain(state3.tx_lock.fee().ok().map(|fee| ("lock", fee)))
+                .collect(),
+                published: HashSet::new(),
+                xmr_lock_tx_hash: None,
+            });
+
+            details.add_transactions(state.swap_transactions());
+        }
+
+        if let Some(details) = details.as_mut() {
+            details.published.extend(alice_published(state));
+
+            if let Some(transfer_proof) = alice_transfer_proof(state) {
+                details.xmr_lock_tx_hash = Some(transfer_proof.tx_hash().to_string());
+            }
+        }
+    }
+
+    details
+}
+
+/// The Bitcoin transactions Alice knows to be published once she is in `state`.
+fn alice_published(state: &AliceState) -> &'static [&'static str] {
+    match state {
+        AliceState::Started { .. } | AliceState::SafelyAborted => &[],
+        AliceState::BtcLockTransactionSeen { .. }
+        | AliceState::BtcLocked { .. }
+        | AliceState::BtcEarlyRefundable { .. }
+        | AliceState::XmrLockTransactionSent { .. }
+        | AliceState::XmrLocked { .. }
+        | AliceState::XmrLockTransferProofSent { .. }
+        | AliceState::EncSigLearned { .. }
+        | AliceState::CancelTimelockExpired { .. } => &["lock"],
+        AliceState::BtcRedeemTransactionPublished { .. } | AliceState::BtcRedeemed => {
+            &["lock", "redeem"]
+        }
+        AliceState::BtcEarlyRefunded(_) => &["lock", "early_refund"],
+        AliceState::BtcCancelled { .. } | AliceState::BtcPunishable { .. } => &["lock", "cancel"],
+        AliceState::BtcRefunded { .. } | AliceState::XmrRefunded => &["lock", "cancel", "refund"],
+        AliceState::BtcPunished { .. } => &["lock", "cancel", "punish"],
+    }
+}
+
+fn alice_transfer_proof(state: &AliceState) -> Option<&TransferProof> {
+    match state {
+        AliceState::XmrLockTransactionSent { transfer_proof, .. }
+        | AliceState::XmrLocked { transfer_proof, .. }
+        | AliceState::XmrLockTransferProofSent { transfer_proof, .. }
+        | AliceState::EncSigLearned { transfer_proof, .. }
+        | AliceState::BtcRedeemTransactionPublished { transfer_proof, .. }
+        | AliceState::BtcCancelled { transfer_proof, .. }
+        | AliceState::BtcRefunded { transfer_proof, .. }
+        | AliceState::BtcPunishable { transfer_proof, .. }
+        | AliceState::CancelTimelockExpired { transfer_proof, .. }
+        | AliceState::BtcPunished { transfer_proof, .. } => Some(transfer_proof),
+        _ => None,
+    }
+}
+
+fn bob_details<'a>(states: impl Iterator<Item = &'a BobState>) -> Result<Option<Details>> {
+    let mut details: Option<Details> = None;
+
+    for state in states {
+        if let (None, BobState::SwapSetupCompleted(state2)) = (&details, state) {
+            details = Some(Details {
+                btc_amount: state2.tx_lock.lock_amount(),
+                xmr_amount: state2.xmr,
+                txids: HashMap::new(),
+                fees: [
+                    ("redeem", state2.tx_redeem_fee),
+                    ("early_refund", state2.tx_refund_fee),
+                    ("cancel", state2.tx_cancel_fee),
+                    ("refund", state2.tx_refund_fee),
+                    ("punish", state2.tx_punish_fee),
+                ]
+                .into_iter()
+                .chain(state2.tx_lock.fee().ok().map(|fee| ("lock", fee)))
+                .collect(),
+                published: HashSet::new(),
+                xmr_lock_tx_hash: None,
+            });
+        }
+
+        let Some(details) = details.as_mut() else {
+            continue;
+        };
+
+        details.add_transactions(state.swap_transactions()?);
+        details.published.extend(bob_published(state));
+
+        // Unlike Alice, we cannot construct the redeem transaction from every state
+        if let BobState::XmrLocked(state4) | BobState::EncSigSent(state4) = state {
+            details.add_transactions(vec![SwapTransaction::new(
+                "redeem",
+                &state4.construct_tx_redeem(),
+            )]);
+        }
+
+        let transfer_proof = match state {
+            BobState::XmrLockProofReceived {
+                lock_transfer_proof,
+                ..
+            } => Some(lock_transfer_proof),
+            BobState::XmrLocked(state4) | BobState::EncSigSent(state4) => {
+                Some(&state4.lock_transfer_proof)
+            }
+            BobState::BtcRedeemed(state5) => Some(&state5.lock_transfer_proof),
+            _ => None,
+        };
+        if let Some(transfer_proof) = transfer_proof {
+            details.xmr_lock_tx_hash = Some(transfer_proof.tx_hash().to_string());
+        }
+    }
+
+    Ok(details)
+}
+
+/// The Bitcoin transactions Bob knows to be published once he is in `state`.
+///
+/// Bob cannot construct the punish transaction, it is never part of his export.
+fn bob_published(state: &BobState) -> &'static [&'static str] {
+    match state {
+        BobState::Started { .. } | BobState::SwapSetupCompleted(_) | BobState::SafelyAborted => &[],
+        BobState::BtcLocked { .. }
+        | BobState::BtcEarlyRefundRequested { .. }
+        | BobState::XmrLockProofReceived { .. }
+        | BobState::XmrLocked(_)
+        | BobState::EncSigSent(_)
+        | BobState::CancelTimelockExpired(_) => &["lock"],
+        BobState::BtcRedeemed(_) | BobState::XmrRedeemed { .. } => &["lock", "redeem"],
+        BobState::BtcEarlyRefundPublished(_) | BobState::BtcEarlyRefunded(_) => {
+            &["lock", "early_refund"]
+        }
+        BobState::BtcCancelled(_) => &["lock", "cancel"],
+        BobState::BtcRefundPublished(_) | BobState::BtcRefunded(_) => &["lock", "cancel", "refund"],
+        BobState::BtcPunished { .. } => &["lock", "cancel", "punish"],
+    }
+}
+
+fn btc_decimal(amount: bitcoin::Amount) -> Decimal {
+    let mut decimal = Decimal::from(amount.to_sat());
+    decimal
+        .set_scale(8)
+        .expect("8 is smaller than max precision of 28");
+    decimal
+}
+
+fn xmr_decimal(amount: monero::Amount) -> Decimal {
+    let mut decimal = Decimal::from(amount.as_piconero());
+    decimal
+        .set_scale(12)
+        .expect("12 is smaller than max precision of 28");
+    decimal
+}
+
+/// The price in BTC per XMR.
+fn price(btc: bitcoin::Amount, xmr: monero::Amount) -> Result<Decimal> {
+    let price = btc_decimal(btc)
+        .checked_div(xmr_decimal(xmr))
+        .context("Cannot compute the price of a swap without Monero")?;
+
+    Ok(price.round_dp(12).normalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::format_description::well_known::Rfc3339;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("btc is locked"), "btc is locked");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn date_range_is_inclusive_and_in_utc() {
+        let range = DateRange::parse(Some("2025-07-01"), Some("2025-07-31")).unwrap();
+
+        let at = |timestamp: &str| OffsetDateTime::parse(timestamp, &Rfc3339).unwrap();
+
+        assert!(range.contains(at("2025-07-01T00:00:00Z")));
+        assert!(range.contains(at("2025-07-31T23:59:59Z")));
+        assert!(range.contains(at("2025-08-01T01:00:00+02:00")));
+        assert!(!range.contains(at("2025-06-30T23:59:59Z")));
+        assert!(!range.contains(at("2025-08-01T00:00:00Z")));
+
+        assert!(DateRange::default().contains(at("1970-01-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn invalid_date_ranges_are_rejected() {
+        assert!(DateRange::parse(Some("2025-07-32"), None).is_err());
+        assert!(DateRange::parse(Some("01.07.2025"), None).is_err());
+        assert!(DateRange::parse(Some("2025-08-01"), Some("2025-07-01")).is_err());
+    }
+
+    #[test]
+    fn price_is_btc_per_xmr() {
+        let price = price(
+            bitcoin::Amount::from_sat(1_000_000),
+            monero::Amount::from_piconero(2_000_000_000_000),
+        )
+        .unwrap();
+
+        assert_eq!(price.to_string(), "0.005");
+    }
+
+    #[test]
+    fn unknown_counterparty_is_left_empty_in_csv() {
+        let record = SwapRecord {
+            swap_id: Uuid::nil(),
+            counterparty_peer_id: None,
+            start_date: "2025-07-01T00:00:00Z".to_string(),
+            state: "btc is locked".to_string(),
+            completed: false,
+            btc_amount: bitcoin::Amount::from_sat(1_000_000),
+            xmr_amount: monero::Amount::from_piconero(2_000_000_000_000),
+            price: Decimal::new(5, 3),
+            bitcoin_transactions: Vec::new(),
+            xmr_lock_tx_hash: None,
+            transitions: Vec::new(),
+        };
+
+        let csv = to_csv(&[record]);
+        let row = csv.lines().nth(1).unwrap();
+
+        assert!(row.starts_with(
+            "00000000-0000-0000-0000-000000000000,2025-07-01T00:00:00Z,btc is locked,false,,0.01000000,2.000000000000,0.005,"
+        ));
+    }
+
+    #[test]
+    fn formats_parse_case_insensitively() {
+        assert_eq!(ExportFormat::from_str("csv").unwrap(), ExportFormat::Csv);
+        assert_eq!(ExportFormat::from_str("JSON").unwrap(), ExportFormat::Json);
+        assert!(ExportFormat::from_str("xlsx").is_err());
+    }
+}
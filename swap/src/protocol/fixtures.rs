@@ -0,0 +1,66 @@
+//! The states of a swap between Alice and Bob, for unit tests which need real states.
+
+use crate::bitcoin::TestWalletBuilder;
+use crate::env::{GetConfig, Regtest};
+use crate::protocol::{alice, bob};
+use crate::{bitcoin, monero};
+use rand::rngs::OsRng;
+use uuid::Uuid;
+
+/// Alice and Bob set up a swap and Bob locked the Bitcoin.
+pub async fn btc_locked() -> (alice::State3, bob::State3) {
+    let (alice_state3, bob_state2) = swap_setup_completed().await;
+    let (bob_state3, _) = bob_state2.lock_btc().await.unwrap();
+
+    (alice_state3, bob_state3)
+}
+
+/// Alice and Bob set up a swap, Bob has not locked the Bitcoin yet.
+pub async fn swap_setup_completed() -> (alice::State3, bob::State2) {
+    let alice_wallet = TestWalletBuilder::new(bitcoin::Amount::ONE_BTC.to_sat())
+        .build()
+        .await;
+    let bob_wallet = TestWalletBuilder::new(bitcoin::Amount::ONE_BTC.to_sat())
+        .build()
+        .await;
+    let fee = bitcoin::Amount::from_sat(1_000);
+    let btc_amount = bitcoin::Amount::from_sat(500_000);
+    let xmr_amount = monero::Amount::from_piconero(10_000);
+
+    let config = Regtest::get_config();
+    let alice_state0 = alice::State0::new(
+        btc_amount,
+        xmr_amount,
+        config,
+        alice_wallet.new_address().await.unwrap(),
+        alice_wallet.new_address().await.unwrap(),
+        fee,
+        fee,
+        &mut OsRng,
+    );
+    let bob_state0 = bob::State0::new(
+        Uuid::new_v4(),
+        &mut OsRng,
+        btc_amount,
+        xmr_amount,
+        config.bitcoin_cancel_timelock,
+        config.bitcoin_punish_timelock,
+        bob_wallet.new_address().await.unwrap(),
+        config.monero_finality_confirmations,
+        fee,
+        fee,
+        fee,
+        None,
+    );
+
+    let (_, alice_state1) = alice_state0.receive(bob_state0.next_message()).unwrap();
+    let bob_state1 = bob_state0
+        .receive(&bob_wallet, alice_state1.next_message())
+        .await
+        .unwrap();
+    let alice_state2 = alice_state1.receive(bob_state1.next_message()).unwrap();
+    let bob_state2 = bob_state1.receive(alice_state2.next_message()).unwrap();
+    let alice_state3 = alice_state2.receive(bob_state2.next_message()).unwrap();
+
+    (alice_state3, bob_state2)
+}
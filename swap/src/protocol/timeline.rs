@@ -0,0 +1,156 @@
+//! When a swap entered each of its states and how long it stayed there.
+
+use crate::protocol::{State, StateTransition};
+use anyhow::Result;
+use serde::Serialize;
+use time::format_description::well_known::Rfc3339;
+use time::{OffsetDateTime, UtcOffset};
+use typeshare::typeshare;
+
+#[typeshare]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimelineEntry {
+    pub state: String,
+    /// RFC 3339 in UTC.
+    pub entered_at: String,
+    /// How long the swap stayed in this state, in milliseconds.
+    ///
+    /// For the latest state this is the time until now, unless the swap is finished.
+    #[typeshare(serialized_as = "Option<number>")]
+    pub duration_ms: Option<u64>,
+}
+
+/// The timeline of a swap from all its states in the order they were entered.
+pub fn timeline(
+    transitions: &[StateTransition],
+    now: OffsetDateTime,
+) -> Result<Vec<TimelineEntry>> {
+    transitions
+        .iter()
+        .enumerate()
+        .map(|(index, transition)| {
+            let left_at = match transitions.get(index + 1) {
+                Some(next) => Some(next.entered_at),
+                None if transition.state.swap_finished() => None,
+                None => Some(now),
+            };
+
+            Ok(TimelineEntry {
+                state: transition.state.to_string(),
+                entered_at: format_timestamp(transition.entered_at)?,
+                duration_ms: left_at.map(|left_at| duration_ms(transition.entered_at, left_at)),
+            })
+        })
+        .collect()
+}
+
+/// When the swap first entered a state matching `predicate`.
+pub fn first_entered_at(
+    transitions: &[StateTransition],
+    predicate: impl Fn(&State) -> bool,
+) -> Option<OffsetDateTime> {
+    transitions
+        .iter()
+        .find(|transition| predicate(&transition.state))
+        .map(|transition| transition.entered_at)
+}
+
+/// The milliseconds between `from` and `to`, zero if the clock went backwards.
+pub fn duration_ms(from: OffsetDateTime, to: OffsetDateTime) -> u64 {
+    u64::try_from((to - from).whole_milliseconds()).unwrap_or(0)
+}
+
+pub fn format_timestamp(timestamp: OffsetDateTime) -> Result<String> {
+    Ok(timestamp.to_offset(UtcOffset::UTC).format(&Rfc3339)?)
+}
+
+/// Format a duration for humans, e.g. `1h 5m 12s`.
+pub fn format_duration_ms(duration_ms: u64) -> String {
+    if duration_ms < 1000 {
+        return format!("{}ms", duration_ms);
+    }
+
+    let seconds = duration_ms / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", seconds),
+        (0, _) => format!("{}m {}s", minutes, seconds),
+        _ => format!("{}h {}m {}s", hours, minutes, seconds),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitcoin;
+    use crate::protocol::alice::AliceState;
+    use crate::protocol::bob::BobState;
+    use ::bitcoin::address::NetworkUnchecked;
+
+    fn at(timestamp: &str) -> OffsetDateTime {
+        OffsetDateTime::parse(timestamp, &Rfc3339).unwrap()
+    }
+
+    fn transition(state: impl Into<State>, entered_at: &str) -> StateTransition {
+        StateTransition {
+            state: state.into(),
+            entered_at: at(entered_at),
+        }
+    }
+
+    #[test]
+    fn durations_run_until_the_next_state() {
+        let transitions = vec![
+            transition(BobState::SafelyAborted, "2025-07-22T14:00:00Z"),
+            transition(BobState::SafelyAborted, "2025-07-22T14:01:30.5Z"),
+        ];
+
+        let timeline = timeline(&transitions, at("2025-07-22T15:00:00Z")).unwrap();
+
+        assert_eq!(timeline[0].entered_at, "2025-07-22T14:00:00Z");
+        assert_eq!(timeline[0].duration_ms, Some(90_500));
+        assert_eq!(timeline[1].duration_ms, None);
+    }
+
+    #[test]
+    fn latest_state_lasts_until_now_unless_the_swap_is_finished() {
+        let started = BobState::Started {
+            btc_amount: bitcoin::Amount::from_sat(100_000),
+            tx_lock_fee: bitcoin::Amount::from_sat(1_000),
+            change_address: "tb1qyccwk4yun26708qg5h6g6we8kxln232wclxf5a"
+                .parse::<bitcoin::Address<NetworkUnchecked>>()
+                .unwrap()
+                .assume_checked(),
+            tx_lock_inputs: None,
+        };
+        let now = at("2025-07-22T14:05:00Z");
+
+        let ongoing = timeline(&[transition(started, "2025-07-22T16:00:00+02:00")], now).unwrap();
+        assert_eq!(ongoing[0].entered_at, "2025-07-22T14:00:00Z");
+        assert_eq!(ongoing[0].duration_ms, Some(300_000));
+
+        let finished = timeline(
+            &[transition(AliceState::XmrRefunded, "2025-07-22T14:00:00Z")],
+            now,
+        )
+        .unwrap();
+        assert_eq!(finished[0].duration_ms, None);
+    }
+
+    #[test]
+    fn clock_going_backwards_is_zero_duration() {
+        assert_eq!(
+            duration_ms(at("2025-07-22T14:00:01Z"), at("2025-07-22T14:00:00Z")),
+            0
+        );
+    }
+
+    #[test]
+    fn durations_are_formatted_for_humans() {
+        assert_eq!(format_duration_ms(250), "250ms");
+        assert_eq!(format_duration_ms(12_900), "12s");
+        assert_eq!(format_duration_ms(312_000), "5m 12s");
+        assert_eq!(format_duration_ms(3_912_000), "1h 5m 12s");
+    }
+}
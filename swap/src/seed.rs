@@ -1,10 +1,14 @@
-use crate::cli::api::tauri_bindings::{SeedChoice, TauriEmitter, TauriHandle};
+use crate::cli::api::tauri_bindings::{SeedChoice, TauriEmitter, TauriHandle, UnlockSeedDetails};
 use crate::fs::ensure_directory_exists;
 use ::bitcoin::bip32::Xpriv as ExtendedPrivKey;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
+use argon2::{Algorithm, Argon2, Params, Version};
 use bitcoin::hashes::{sha256, Hash, HashEngine};
 use bitcoin::secp256k1::constants::SECRET_KEY_SIZE;
 use bitcoin::secp256k1::{self, SecretKey};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use dialoguer::Password;
 use libp2p::identity;
 use monero_seed::{Language, Seed as MoneroSeed};
 use pem::{encode, Pem};
@@ -14,10 +18,21 @@ use std::fmt;
 use std::fs::{self, File};
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
+use tor_hscrypto::pk::HsIdKeypair;
+use tor_llcrypto::pk::ed25519;
 use zeroize::Zeroizing;
 
 pub const SEED_LENGTH: usize = 32;
 
+/// The PEM tag of a seed file which is encrypted with a passphrase.
+const ENCRYPTED_SEED_TAG: &str = "ENCRYPTED SEED";
+
+/// How often the user may enter a wrong passphrase before we give up.
+const MAX_UNLOCK_ATTEMPTS: u32 = 3;
+
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+
 #[derive(Clone, Eq, PartialEq)]
 pub struct Seed([u8; SEED_LENGTH]);
 
@@ -63,6 +78,17 @@ impl Seed {
         identity::Keypair::ed25519_from_bytes(bytes).expect("we always pass 32 bytes")
     }
 
+    /// The identity of the onion service the asb listens on, which determines its onion address.
+    pub fn derive_onion_service_identity(&self) -> HsIdKeypair {
+        let bytes = self
+            .derive(b"NETWORK")
+            .derive(b"ONION_SERVICE_IDENTITY")
+            .bytes();
+        let keypair = ed25519::Keypair::from_bytes(&bytes);
+
+        HsIdKeypair::from(ed25519::ExpandedKeypair::from(&keypair))
+    }
+
     pub async fn from_file_or_generate(
         data_dir: &Path,
         tauri_handle: Option<TauriHandle>,
@@ -71,7 +97,10 @@ impl Seed {
         let file_path = Path::new(&file_path_buf);
 
         if file_path.exists() {
-            return Self::from_file(file_path).context("Couldn't get seed from file");
+            return match Self::read_file(file_path).context("Couldn't get seed from file")? {
+                SeedFile::Plain(seed) => Ok(seed),
+                SeedFile::Encrypted(encrypted) => encrypted.unlock(tauri_handle).await,
+            };
         }
 
         tracing::debug!("No seed file found, creating at {}", file_path.display());
@@ -136,17 +165,69 @@ impl Seed {
         self.0
     }
 
+    /// Encrypts the plaintext seed file in `data_dir` with a passphrase the user enters
+    /// on the terminal. Returns the path of the seed file.
+    pub fn encrypt_file(data_dir: &Path) -> Result<PathBuf> {
+        let file_path = data_dir.join("seed.pem");
+
+        let seed = match Self::from_file(&file_path) {
+            Ok(seed) => seed,
+            Err(Error::Encrypted) => {
+                bail!("The seed file {} is already encrypted", file_path.display())
+            }
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!("Couldn't read the seed file {}", file_path.display())
+                })
+            }
+        };
+
+        let passphrase = Zeroizing::new(
+            Password::new()
+                .with_prompt("Enter a passphrase for the seed file")
+                .with_confirmation("Repeat the passphrase", "The passphrases do not match")
+                .interact()
+                .context("Failed to read the passphrase from the terminal")?,
+        );
+
+        let encrypted = EncryptedSeed::encrypt(&seed, &passphrase)?;
+        encrypted.write_to(file_path.clone())?;
+
+        // Make sure we can unlock what we wrote before the plaintext seed is gone for good
+        let written = match Self::read_file(&file_path)? {
+            SeedFile::Encrypted(written) => written,
+            SeedFile::Plain(_) => bail!("The seed file was not encrypted"),
+        };
+        if written.decrypt(&passphrase)? != seed {
+            bail!("The encrypted seed file does not contain the seed");
+        }
+
+        Ok(file_path)
+    }
+
     fn from_file<D>(seed_file: D) -> Result<Self, Error>
     where
         D: AsRef<OsStr>,
     {
-        let file = Path::new(&seed_file);
+        match Self::read_file(Path::new(&seed_file))? {
+            SeedFile::Plain(seed) => Ok(seed),
+            SeedFile::Encrypted(_) => Err(Error::Encrypted),
+        }
+    }
+
+    fn read_file(file: &Path) -> Result<SeedFile, Error> {
         let contents = fs::read_to_string(file)?;
         let pem = pem::parse(contents)?;
 
         tracing::debug!("Reading in seed from {}", file.display());
 
-        Self::from_pem(pem)
+        if pem.tag() == ENCRYPTED_SEED_TAG {
+            return Ok(SeedFile::Encrypted(EncryptedSeed::from_bytes(
+                pem.contents(),
+            )?));
+        }
+
+        Ok(SeedFile::Plain(Self::from_pem(pem)?))
     }
 
     fn from_pem(pem: pem::Pem) -> Result<Self, Error> {
@@ -178,6 +259,200 @@ impl Seed {
     }
 }
 
+/// The contents of a seed file, which may be encrypted with a passphrase.
+enum SeedFile {
+    Plain(Seed),
+    Encrypted(EncryptedSeed),
+}
+
+/// A seed encrypted with a key derived from a passphrase.
+///
+/// The key is derived with Argon2id and the seed is encrypted with
+/// XChaCha20-Poly1305. The version, the Argon2 parameters and the salt are
+/// authenticated as associated data, so decryption fails if any part of the file
+/// was changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EncryptedSeed {
+    argon2_params: [u32; 3],
+    salt: [u8; SALT_LENGTH],
+    nonce: [u8; NONCE_LENGTH],
+    ciphertext: Vec<u8>,
+}
+
+impl EncryptedSeed {
+    const VERSION: u8 = 1;
+    const HEADER_LENGTH: usize = 1 + 3 * 4 + SALT_LENGTH;
+    /// The seed followed by the Poly1305 tag.
+    const CIPHERTEXT_LENGTH: usize = SEED_LENGTH + 16;
+    const LENGTH: usize = Self::HEADER_LENGTH + NONCE_LENGTH + Self::CIPHERTEXT_LENGTH;
+
+    /// Memory cost (64 MiB), number of passes and parallelism of Argon2id for new seed files.
+    const ARGON2_PARAMS: [u32; 3] = [64 * 1024, 3, 1];
+
+    fn encrypt(seed: &Seed, passphrase: &str) -> Result<Self, Error> {
+        Self::encrypt_with_params(seed, passphrase, Self::ARGON2_PARAMS)
+    }
+
+    fn encrypt_with_params(
+        seed: &Seed,
+        passphrase: &str,
+        argon2_params: [u32; 3],
+    ) -> Result<Self, Error> {
+        let mut encrypted = Self {
+            argon2_params,
+            salt: Default::default(),
+            nonce: Default::default(),
+            ciphertext: Vec::new(),
+        };
+        rand::thread_rng().fill_bytes(&mut encrypted.salt);
+        rand::thread_rng().fill_bytes(&mut encrypted.nonce);
+
+        let key = encrypted.derive_key(passphrase)?;
+        encrypted.ciphertext = XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+            .encrypt(
+                XNonce::from_slice(&encrypted.nonce),
+                Payload {
+                    msg: &seed.bytes(),
+                    aad: &encrypted.header(),
+                },
+            )
+            .map_err(|_| Error::Encryption)?;
+
+        Ok(encrypted)
+    }
+
+    fn decrypt(&self, passphrase: &str) -> Result<Seed, Error> {
+        let key = self.derive_key(passphrase)?;
+        let plaintext = Zeroizing::new(
+            XChaCha20Poly1305::new(Key::from_slice(&key[..]))
+                .decrypt(
+                    XNonce::from_slice(&self.nonce),
+                    Payload {
+                        msg: &self.ciphertext,
+                        aad: &self.header(),
+                    },
+                )
+                .map_err(|_| Error::WrongPassphrase)?,
+        );
+
+        let bytes: [u8; SEED_LENGTH] = plaintext
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::IncorrectLength(plaintext.len()))?;
+
+        Ok(Seed(bytes))
+    }
+
+    /// Asks the user for the passphrase, through the GUI if there is one and on the
+    /// terminal otherwise, until the seed is unlocked.
+    async fn unlock(&self, tauri_handle: Option<TauriHandle>) -> Result<Seed> {
+        for attempt in 1..=MAX_UNLOCK_ATTEMPTS {
+            let passphrase = match &tauri_handle {
+                Some(tauri_handle) => {
+                    let details = UnlockSeedDetails {
+                        wrong_passphrase: attempt > 1,
+                    };
+                    Zeroizing::new(tauri_handle.request_seed_passphrase(details).await?.passphrase)
+                }
+                None => Zeroizing::new(
+                    Password::new()
+                        .with_prompt("Enter the passphrase of the seed file")
+                        .interact()
+                        .context("Failed to read the passphrase of the encrypted seed file from the terminal")?,
+                ),
+            };
+
+            match self.decrypt(&passphrase) {
+                Ok(seed) => return Ok(seed),
+                Err(Error::WrongPassphrase) => {
+                    tracing::warn!(attempt, "Wrong passphrase for the seed file");
+                }
+                Err(error) => return Err(error.into()),
+            }
+        }
+
+        bail!(
+            "Failed to unlock the seed file, the passphrase was wrong {} times",
+            MAX_UNLOCK_ATTEMPTS
+        )
+    }
+
+    fn derive_key(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>, Error> {
+        let [m_cost, t_cost, p_cost] = self.argon2_params;
+        let params =
+            Params::new(m_cost, t_cost, p_cost, Some(32)).map_err(|e| Error::Kdf(e.to_string()))?;
+
+        let mut key = Zeroizing::new([0u8; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &self.salt, &mut key[..])
+            .map_err(|e| Error::Kdf(e.to_string()))?;
+
+        Ok(key)
+    }
+
+    /// Everything in front of the nonce, authenticated as associated data.
+    fn header(&self) -> Vec<u8> {
+        let mut header = Vec::with_capacity(Self::HEADER_LENGTH);
+        header.push(Self::VERSION);
+        for param in self.argon2_params {
+            header.extend_from_slice(&param.to_le_bytes());
+        }
+        header.extend_from_slice(&self.salt);
+
+        header
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header();
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend_from_slice(&self.ciphertext);
+
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != Self::LENGTH {
+            return Err(Error::IncorrectEncryptedLength(bytes.len()));
+        }
+
+        if bytes[0] != Self::VERSION {
+            return Err(Error::UnsupportedVersion(bytes[0]));
+        }
+
+        let param = |index: usize| {
+            let offset = 1 + index * 4;
+            u32::from_le_bytes(bytes[offset..offset + 4].try_into().expect("4 bytes"))
+        };
+        let (salt, rest) = bytes[Self::HEADER_LENGTH - SALT_LENGTH..].split_at(SALT_LENGTH);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+
+        Ok(Self {
+            argon2_params: [param(0), param(1), param(2)],
+            salt: salt.try_into().expect("salt length was checked"),
+            nonce: nonce.try_into().expect("nonce length was checked"),
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    /// Replaces the seed file with this encrypted seed.
+    ///
+    /// We write to a temporary file first such that the seed file is never only
+    /// partially written.
+    fn write_to(&self, seed_file: PathBuf) -> Result<(), Error> {
+        ensure_directory_exists(&seed_file)?;
+
+        let pem = Pem::new(ENCRYPTED_SEED_TAG, self.to_bytes());
+        let temp_file = seed_file.with_extension("pem.tmp");
+
+        let mut file = File::create(&temp_file)?;
+        file.write_all(encode(&pem).as_bytes())?;
+        file.sync_all()?;
+        fs::rename(temp_file, seed_file)?;
+
+        Ok(())
+    }
+}
+
 impl fmt::Debug for Seed {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Seed([*****])")
@@ -210,6 +485,21 @@ pub enum Error {
     Rand(#[from] rand::Error),
     #[error("no default path")]
     NoDefaultPath,
+    #[error("the seed file is encrypted")]
+    Encrypted,
+    #[error("wrong passphrase or the seed file was tampered with")]
+    WrongPassphrase,
+    #[error(
+        "expected {} bytes of encrypted seed, got {0} bytes",
+        EncryptedSeed::LENGTH
+    )]
+    IncorrectEncryptedLength(usize),
+    #[error("unsupported version {0} of the encrypted seed file")]
+    UnsupportedVersion(u8),
+    #[error("key derivation: {0}")]
+    Kdf(String),
+    #[error("failed to encrypt the seed")]
+    Encryption,
 }
 
 #[cfg(test)]
@@ -222,6 +512,20 @@ mod tests {
         let _ = Seed::random().unwrap();
     }
 
+    #[test]
+    fn onion_service_identity_is_derived_deterministically() {
+        use tor_hscrypto::pk::{HsId, HsIdKey};
+
+        let onion_address =
+            |seed: &Seed| HsId::from(HsIdKey::from(&seed.derive_onion_service_identity()));
+
+        let seed = Seed::from(*b"this string is exactly 32 bytes!");
+        let other_seed = Seed::from(*b"this string is also 32 bytes ...");
+
+        assert_eq!(onion_address(&seed), onion_address(&seed.clone()));
+        assert_ne!(onion_address(&seed), onion_address(&other_seed));
+    }
+
     #[test]
     fn seed_byte_string_must_be_32_bytes_long() {
         let _seed = Seed::from(*b"this string is exactly 32 bytes!");
@@ -286,6 +590,58 @@ dWWSQ0nRGt2hOPDO+35NKhQEjBQxPh/v7n0CAwEAAQJBAOGaBAyuw0ICyENy5NsO
         }
     }
 
+    /// Cheap Argon2 parameters such that the tests run fast.
+    const TEST_ARGON2_PARAMS: [u32; 3] = [64, 1, 1];
+
+    #[test]
+    fn encrypted_seed_can_only_be_decrypted_with_its_passphrase() {
+        let seed = Seed::random().unwrap();
+        let encrypted =
+            EncryptedSeed::encrypt_with_params(&seed, "passphrase", TEST_ARGON2_PARAMS).unwrap();
+
+        assert_eq!(encrypted.decrypt("passphrase").unwrap(), seed);
+        assert!(matches!(
+            encrypted.decrypt("wrong passphrase"),
+            Err(Error::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn decryption_fails_if_the_header_was_tampered_with() {
+        let seed = Seed::random().unwrap();
+        let encrypted =
+            EncryptedSeed::encrypt_with_params(&seed, "passphrase", TEST_ARGON2_PARAMS).unwrap();
+
+        let mut bytes = encrypted.to_bytes();
+        // Increase the number of Argon2 passes
+        bytes[5] += 1;
+        let tampered = EncryptedSeed::from_bytes(&bytes).unwrap();
+
+        assert!(matches!(
+            tampered.decrypt("passphrase"),
+            Err(Error::WrongPassphrase)
+        ));
+    }
+
+    #[test]
+    fn encrypted_seed_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let seed_file = dir.path().join("seed.pem");
+
+        let seed = Seed::random().unwrap();
+        EncryptedSeed::encrypt_with_params(&seed, "passphrase", TEST_ARGON2_PARAMS)
+            .unwrap()
+            .write_to(seed_file.clone())
+            .unwrap();
+
+        assert!(matches!(Seed::from_file(&seed_file), Err(Error::Encrypted)));
+
+        let SeedFile::Encrypted(encrypted) = Seed::read_file(&seed_file).unwrap() else {
+            panic!("seed file should be encrypted");
+        };
+        assert_eq!(encrypted.decrypt("passphrase").unwrap(), seed);
+    }
+
     #[test]
     fn round_trip_through_file_write_read() {
         let tmpfile = temp_dir().join("seed.pem");
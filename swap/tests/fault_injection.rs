@@ -0,0 +1,112 @@
+pub mod harness;
+
+use harness::alice_run_until::{
+    is_btc_locked as alice_is_btc_locked, is_encsig_learned, is_xmr_lock_transaction_sent,
+    is_xmr_lock_transfer_proof_sent,
+};
+use harness::bob_run_until::{
+    is_btc_locked, is_encsig_sent, is_lock_proof_received, is_xmr_locked,
+};
+use harness::scenario::{Action, Outcome, Scenario};
+use harness::{FastCancelConfig, FastPunishConfig, SlowCancelConfig, SlowMoneroLockConfig};
+use std::time::Duration;
+use swap::network::fault_injection::{Fault, Message};
+
+/// Every row runs a swap on its own simulated blockchains, see [`harness::scenario`].
+macro_rules! scenarios {
+    ($($name:ident: $config:expr => $scenario:expr;)*) => {
+        $(
+            #[tokio::test]
+            async fn $name() {
+                harness::scenario::test($config, $scenario).await
+            }
+        )*
+    };
+}
+
+const DELAY: Duration = Duration::from_secs(10);
+
+scenarios! {
+    alice_restarts_after_btc_locked: SlowCancelConfig =>
+        Scenario::new("Alice restarts after the Bitcoin is locked")
+            .alice(alice_is_btc_locked, Action::Restart);
+    alice_restarts_after_xmr_lock_transaction_sent: SlowCancelConfig =>
+        Scenario::new("Alice restarts after publishing the Monero lock transaction")
+            .alice(is_xmr_lock_transaction_sent, Action::Restart);
+    alice_restarts_after_transfer_proof_sent: SlowCancelConfig =>
+        Scenario::new("Alice restarts after sending the transfer proof")
+            .alice(is_xmr_lock_transfer_proof_sent, Action::Restart);
+    alice_restarts_after_encsig_learned: SlowCancelConfig =>
+        Scenario::new("Alice restarts after learning the encrypted signature")
+            .alice(is_encsig_learned, Action::Restart);
+    bob_restarts_after_btc_locked: SlowCancelConfig =>
+        Scenario::new("Bob restarts after locking the Bitcoin")
+            .bob(is_btc_locked, Action::Restart);
+    bob_restarts_after_lock_proof_received: SlowCancelConfig =>
+        Scenario::new("Bob restarts after receiving the transfer proof")
+            .bob(is_lock_proof_received, Action::Restart);
+    bob_restarts_after_xmr_locked: SlowCancelConfig =>
+        Scenario::new("Bob restarts after the Monero is locked")
+            .bob(is_xmr_locked, Action::Restart);
+    bob_restarts_after_encsig_sent: SlowCancelConfig =>
+        Scenario::new("Bob restarts after sending the encrypted signature")
+            .bob(is_encsig_sent, Action::Restart);
+    both_restart_after_xmr_locked: SlowCancelConfig =>
+        Scenario::new("Alice and Bob restart after the Monero is locked")
+            .alice(is_xmr_lock_transfer_proof_sent, Action::Restart)
+            .bob(is_xmr_locked, Action::Restart);
+    swap_setup_is_lost: SlowCancelConfig =>
+        Scenario::new("The swap setup request is lost")
+            .message_fault(Message::SwapSetup, Fault::Drop, 2);
+    swap_setup_is_delayed: SlowCancelConfig =>
+        Scenario::new("The swap setup request is delayed")
+            .message_fault(Message::SwapSetup, Fault::Delay(DELAY), 1);
+    transfer_proof_is_lost: SlowCancelConfig =>
+        Scenario::new("The transfer proof is lost")
+            .message_fault(Message::TransferProof, Fault::Drop, 3);
+    transfer_proof_is_delayed: SlowCancelConfig =>
+        Scenario::new("The transfer proof is delayed")
+            .message_fault(Message::TransferProof, Fault::Delay(DELAY), 1);
+    encsig_is_lost: SlowCancelConfig =>
+        Scenario::new("The encrypted signature is lost")
+            .message_fault(Message::EncryptedSignature, Fault::Drop, 3);
+    encsig_is_delayed: SlowCancelConfig =>
+        Scenario::new("The encrypted signature is delayed")
+            .message_fault(Message::EncryptedSignature, Fault::Delay(DELAY), 1);
+    encsig_is_lost_while_alice_restarts: SlowCancelConfig =>
+        Scenario::new("The encrypted signature is lost while Alice restarts")
+            .message_fault(Message::EncryptedSignature, Fault::Drop, 2)
+            .alice(is_xmr_lock_transfer_proof_sent, Action::Restart);
+    bitcoin_stalls_after_btc_locked: SlowCancelConfig =>
+        Scenario::new("No Bitcoin blocks are mined after the Bitcoin is locked")
+            .bob(is_btc_locked, Action::StallBitcoin(DELAY));
+    monero_stalls_after_xmr_lock_transaction_sent: SlowCancelConfig =>
+        Scenario::new("No Monero blocks are mined after the Monero lock transaction is published")
+            .alice(is_xmr_lock_transaction_sent, Action::StallMonero(DELAY));
+    encsig_never_arrives: FastCancelConfig =>
+        Scenario::new("The encrypted signature never arrives")
+            .message_fault(Message::EncryptedSignature, Fault::Drop, u32::MAX)
+            .expect(Outcome::Refunded);
+    early_refund_while_alice_cannot_lock_xmr: SlowMoneroLockConfig =>
+        Scenario::new("Alice cannot lock the Monero, Bob asks for an early refund")
+            .alice(alice_is_btc_locked, Action::SweepAliceMonero)
+            .bob(is_btc_locked, Action::RequestEarlyRefund)
+            .expect(Outcome::EarlyRefunded);
+    early_refund_request_is_lost: SlowMoneroLockConfig =>
+        Scenario::new("The early refund request is lost")
+            .alice(alice_is_btc_locked, Action::SweepAliceMonero)
+            .bob(is_btc_locked, Action::RequestEarlyRefund)
+            .message_fault(Message::CooperativeEarlyRefund, Fault::Drop, 2)
+            .expect(Outcome::EarlyRefunded);
+    early_refund_request_is_delayed: SlowMoneroLockConfig =>
+        Scenario::new("The early refund request is delayed")
+            .alice(alice_is_btc_locked, Action::SweepAliceMonero)
+            .bob(is_btc_locked, Action::RequestEarlyRefund)
+            .message_fault(Message::CooperativeEarlyRefund, Fault::Delay(DELAY), 1)
+            .expect(Outcome::EarlyRefunded);
+    bob_offline_until_punished: FastPunishConfig =>
+        Scenario::new("Bob is offline until Alice punishes him, then redeems cooperatively")
+            .bob(is_btc_locked, Action::RestartOnceOtherPartyDone)
+            .message_fault(Message::CooperativeXmrRedeem, Fault::Drop, 1)
+            .expect(Outcome::PunishedAndCooperativelyRedeemed);
+}
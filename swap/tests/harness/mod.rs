@@ -1,5 +1,6 @@
 mod bitcoind;
 mod electrs;
+pub mod scenario;
 
 use anyhow::{bail, Context, Result};
 use async_trait::async_trait;
@@ -17,7 +18,8 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
-use swap::asb::FixedRate;
+use swap::asb::config::PeerLimits;
+use swap::asb::{FixedRate, PricingPolicy};
 use swap::bitcoin::{CancelTimelock, PunishTimelock};
 use swap::cli::api;
 use swap::database::{AccessMode, SqliteDatabase};
@@ -25,6 +27,7 @@ use swap::env::{Config, GetConfig};
 use swap::fs::ensure_directory_exists;
 use swap::monero::wallet::no_listener;
 use swap::monero::Wallets;
+use swap::network::fault_injection::MessageFaults;
 use swap::network::rendezvous::XmrBtcNamespace;
 use swap::network::swarm;
 use swap::protocol::alice::{AliceState, Swap};
@@ -39,6 +42,7 @@ use tokio::sync::mpsc;
 use tokio::sync::mpsc::Receiver;
 use tokio::task::JoinHandle;
 use tokio::time::{interval, timeout};
+use tokio_util::task::AbortOnDropHandle;
 use url::Url;
 use uuid::Uuid;
 
@@ -89,6 +93,8 @@ where
         .parse()
         .expect("failed to parse Alice's address");
 
+    let message_faults = MessageFaults::new();
+
     let (alice_handle, alice_swap_handle) = start_alice(
         &alice_seed,
         alice_db_path.clone(),
@@ -96,6 +102,7 @@ where
         env_config,
         alice_bitcoin_wallet.clone(),
         alice_monero_wallet.clone(),
+        message_faults.clone(),
     )
     .await;
 
@@ -123,6 +130,7 @@ where
         alice_address: alice_listen_address.clone(),
         alice_peer_id: alice_handle.peer_id,
         env_config,
+        message_faults: message_faults.clone(),
     };
 
     monero.start_miner().await.unwrap();
@@ -143,7 +151,117 @@ where
         bob_starting_balances,
         bob_bitcoin_wallet,
         bob_monero_wallet,
-        monerod_container_id: containers._monerod_container.id().to_string(),
+        monerod_container_id: Some(containers._monerod_container.id().to_string()),
+        simulated: None,
+        message_faults,
+    };
+
+    testfn(test).await.unwrap()
+}
+
+/// Like [`setup_test`] but on in-process [simulated](SimulatedChains) blockchains
+/// instead of Docker containers.
+pub async fn setup_simulated_test<T, F, C>(_config: C, testfn: T)
+where
+    T: Fn(TestContext) -> F,
+    F: Future<Output = Result<()>>,
+    C: GetConfig,
+{
+    // Several simulated tests can run in the same binary, only the first one sets the subscriber
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter("info,swap=debug")
+        .with_test_writer()
+        .try_init();
+
+    let env_config = C::get_config();
+
+    let bitcoin_blockchain =
+        bitcoin::simulated::Blockchain::new(env_config.bitcoin_network).unwrap();
+    let monero_blockchain = monero::simulated::Blockchain::new();
+
+    let btc_amount = bitcoin::Amount::from_sat(1_000_000);
+    let xmr_amount = monero::Amount::from_monero(btc_amount.to_btc() / FixedRate::RATE).unwrap();
+
+    let alice_starting_balances =
+        StartingBalances::new(bitcoin::Amount::ZERO, xmr_amount, Some(10));
+
+    let alice_seed = Seed::random().unwrap();
+    let alice_db_path = NamedTempFile::new().unwrap().path().to_path_buf();
+    let (alice_bitcoin_wallet, alice_monero_wallet) = init_simulated_wallets(
+        &bitcoin_blockchain,
+        &monero_blockchain,
+        alice_starting_balances.clone(),
+        &alice_seed,
+        env_config,
+    )
+    .await;
+
+    let alice_listen_port = get_port().expect("Failed to find a free port");
+    let alice_listen_address: Multiaddr = format!("/ip4/127.0.0.1/tcp/{}", alice_listen_port)
+        .parse()
+        .expect("failed to parse Alice's address");
+
+    let message_faults = MessageFaults::new();
+
+    let (alice_handle, alice_swap_handle) = start_alice(
+        &alice_seed,
+        alice_db_path.clone(),
+        alice_listen_address.clone(),
+        env_config,
+        alice_bitcoin_wallet.clone(),
+        alice_monero_wallet.clone(),
+        message_faults.clone(),
+    )
+    .await;
+
+    let bob_seed = Seed::random().unwrap();
+    let bob_starting_balances = StartingBalances::new(btc_amount * 10, monero::Amount::ZERO, None);
+    let (bob_bitcoin_wallet, bob_monero_wallet) = init_simulated_wallets(
+        &bitcoin_blockchain,
+        &monero_blockchain,
+        bob_starting_balances.clone(),
+        &bob_seed,
+        env_config,
+    )
+    .await;
+
+    let bob_params = BobParams {
+        seed: Seed::random().unwrap(),
+        db_path: NamedTempFile::new().unwrap().path().to_path_buf(),
+        bitcoin_wallet: bob_bitcoin_wallet.clone(),
+        monero_wallet: bob_monero_wallet.clone(),
+        alice_address: alice_listen_address.clone(),
+        alice_peer_id: alice_handle.peer_id,
+        env_config,
+        message_faults: message_faults.clone(),
+    };
+
+    let simulated = SimulatedChains {
+        _bitcoin_miner: bitcoin_blockchain.start_mining(Duration::from_secs(1)),
+        _monero_miner: monero_blockchain.start_mining(Duration::from_millis(250)),
+        bitcoin: bitcoin_blockchain,
+        monero: monero_blockchain,
+    };
+
+    let test = TestContext {
+        env_config,
+        btc_amount,
+        xmr_amount,
+        alice_seed,
+        alice_db_path,
+        alice_listen_address,
+        alice_starting_balances,
+        alice_bitcoin_wallet,
+        alice_monero_wallet,
+        alice_swap_handle,
+        alice_handle,
+        bob_params,
+        bob_starting_balances,
+        bob_bitcoin_wallet,
+        bob_monero_wallet,
+        monerod_container_id: None,
+        simulated: Some(simulated),
+        message_faults,
     };
 
     testfn(test).await.unwrap()
@@ -233,6 +351,7 @@ async fn start_alice(
     env_config: Config,
     bitcoin_wallet: Arc<bitcoin::Wallet>,
     monero_wallet: Arc<monero::Wallets>,
+    message_faults: MessageFaults,
 ) -> (AliceApplicationHandle, Receiver<alice::Swap>) {
     if let Some(parent_dir) = db_path.parent() {
         ensure_directory_exists(parent_dir).unwrap();
@@ -263,6 +382,8 @@ async fn start_alice(
         None,
         false,
         1,
+        false,
+        &PeerLimits::default(),
     )
     .unwrap();
     swarm.listen_on(listen_address).unwrap();
@@ -274,11 +395,13 @@ async fn start_alice(
         monero_wallet,
         db,
         FixedRate::default(),
+        PricingPolicy::default(),
         min_buy,
         max_buy,
         None,
     )
     .unwrap();
+    let event_loop = event_loop.with_message_faults(message_faults);
 
     let peer_id = event_loop.peer_id();
     let handle = tokio::spawn(event_loop.run());
@@ -305,6 +428,7 @@ async fn init_test_wallets(
     let monero_daemon = Daemon {
         address: format!("http://127.0.0.1:{}", monerod_port),
         ssl: false,
+        proxy: None,
     };
 
     let wallets = Wallets::new(
@@ -318,7 +442,7 @@ async fn init_test_wallets(
     .await
     .unwrap();
 
-    let xmr_wallet = wallets.main_wallet().await;
+    let xmr_wallet = wallets.main_wallet().await.unwrap();
     tracing::info!(
         address = %xmr_wallet.main_address().await,
         "Initialized monero wallet"
@@ -398,6 +522,49 @@ async fn init_test_wallets(
     (Arc::new(btc_wallet), Arc::new(wallets))
 }
 
+async fn init_simulated_wallets(
+    bitcoin_blockchain: &bitcoin::simulated::Blockchain,
+    monero_blockchain: &monero::simulated::Blockchain,
+    starting_balances: StartingBalances,
+    seed: &Seed,
+    env_config: Config,
+) -> (Arc<bitcoin::Wallet>, Arc<monero::Wallets>) {
+    let wallets = Wallets::simulated(monero_blockchain.wallet(env_config.monero_network));
+    let monero_address = wallets.main_address().await;
+
+    for amount in starting_balances.xmr_outputs {
+        monero_blockchain.fund(&monero_address, amount.into());
+    }
+
+    let btc_wallet = swap::bitcoin::wallet::WalletBuilder::default()
+        .seed(seed.clone())
+        .network(env_config.bitcoin_network)
+        .electrum_rpc_urls(vec![bitcoin_blockchain.electrum_url()])
+        .persister(swap::bitcoin::wallet::PersisterConfig::InMemorySqlite)
+        .finality_confirmations(1_u32)
+        .target_block(1_u32)
+        .sync_interval(Duration::from_secs(1))
+        .build()
+        .await
+        .expect("could not init btc wallet");
+
+    if starting_balances.btc != bitcoin::Amount::ZERO {
+        bitcoin_blockchain.fund(
+            &btc_wallet.new_address().await.unwrap(),
+            starting_balances.btc,
+        );
+
+        assert_eventual_balance(&btc_wallet, Ordering::Equal, starting_balances.btc)
+            .await
+            .expect("could not sync btc starting balance");
+    }
+
+    (Arc::new(btc_wallet), Arc::new(wallets))
+}
+
+/// Nobody can spend what is sent here.
+const MONERO_BURN_ADDRESS: &str = "49LEH26DJGuCyr8xzRAzWPUryzp7bpccC7Hie1DiwyfJEyUKvMFAethRLybDYrFdU1eHaMkKQpUPebY4WT3cSjEvThmpjPa";
+
 const MONERO_WALLET_NAME_BOB: &str = "bob";
 const MONERO_WALLET_NAME_ALICE: &str = "alice";
 const BITCOIN_TEST_WALLET_NAME: &str = "testwallet";
@@ -456,6 +623,7 @@ pub struct BobParams {
     alice_address: Multiaddr,
     alice_peer_id: PeerId,
     env_config: Config,
+    message_faults: MessageFaults,
 }
 
 impl BobParams {
@@ -470,96 +638,135 @@ impl BobParams {
     pub async fn get_change_receive_addresses(&self) -> (bitcoin::Address, monero::Address) {
         (
             self.bitcoin_wallet.new_address().await.unwrap(),
-            self.monero_wallet.main_wallet().await.main_address().await,
+            self.monero_wallet.main_address().await,
         )
     }
 
     pub async fn new_swap_from_db(&self, swap_id: Uuid) -> Result<(bob::Swap, cli::EventLoop)> {
-        if let Some(parent_dir) = self.db_path.parent() {
-            ensure_directory_exists(parent_dir)?;
-        }
-        if !self.db_path.exists() {
-            tokio::fs::File::create(&self.db_path).await?;
+        let db = self.open_db().await?;
+        let event_loop = self.new_eventloop(db.clone()).await?;
+        let swap = self
+            .swap_from_db_on(&event_loop.service(), db, swap_id)
+            .await?;
+
+        Ok((swap, event_loop))
+    }
+
+    pub async fn new_swap(
+        &self,
+        btc_amount: bitcoin::Amount,
+    ) -> Result<(bob::Swap, cli::EventLoop)> {
+        let db = self.open_db().await?;
+        let event_loop = self.new_eventloop(db.clone()).await?;
+        let swap = self
+            .new_swap_on(&event_loop.service(), db, btc_amount)
+            .await?;
+
+        Ok((swap, event_loop))
+    }
+
+    /// Like [`BobParams::new_swap`] but all swaps run on the same event loop.
+    pub async fn new_swaps(
+        &self,
+        btc_amount: bitcoin::Amount,
+        count: usize,
+    ) -> Result<(Vec<bob::Swap>, cli::EventLoop)> {
+        let db = self.open_db().await?;
+        let event_loop = self.new_eventloop(db.clone()).await?;
+
+        let mut swaps = Vec::new();
+        for _ in 0..count {
+            swaps.push(
+                self.new_swap_on(&event_loop.service(), db.clone(), btc_amount)
+                    .await?,
+            );
         }
-        let db = Arc::new(SqliteDatabase::open(&self.db_path, AccessMode::ReadWrite).await?);
 
-        let (event_loop, handle) = self.new_eventloop(swap_id, db.clone()).await?;
+        Ok((swaps, event_loop))
+    }
 
-        let swap = bob::Swap::from_db(
-            db.clone(),
+    pub async fn swap_from_db_on(
+        &self,
+        event_loop: &cli::EventLoopService,
+        db: Arc<SqliteDatabase>,
+        swap_id: Uuid,
+    ) -> Result<bob::Swap> {
+        let handle = event_loop.new_handle(
+            swap_id,
+            self.alice_peer_id,
+            vec![self.alice_address.clone()],
+        )?;
+
+        bob::Swap::from_db(
+            db,
             swap_id,
             self.bitcoin_wallet.clone(),
             self.monero_wallet.clone(),
             self.env_config,
             handle,
-            self.monero_wallet
-                .main_wallet()
-                .await
-                .main_address()
-                .await
-                .into(),
+            self.monero_wallet.main_address().await.into(),
         )
-        .await?;
-
-        Ok((swap, event_loop))
+        .await
     }
 
-    pub async fn new_swap(
+    async fn new_swap_on(
         &self,
+        event_loop: &cli::EventLoopService,
+        db: Arc<SqliteDatabase>,
         btc_amount: bitcoin::Amount,
-    ) -> Result<(bob::Swap, cli::EventLoop)> {
+    ) -> Result<bob::Swap> {
         let swap_id = Uuid::new_v4();
 
-        if let Some(parent_dir) = self.db_path.parent() {
-            ensure_directory_exists(parent_dir)?;
-        }
-        if !self.db_path.exists() {
-            tokio::fs::File::create(&self.db_path).await?;
-        }
-        let db = Arc::new(SqliteDatabase::open(&self.db_path, AccessMode::ReadWrite).await?);
-
-        let (event_loop, handle) = self.new_eventloop(swap_id, db.clone()).await?;
+        let handle = event_loop.new_handle(
+            swap_id,
+            self.alice_peer_id,
+            vec![self.alice_address.clone()],
+        )?;
 
         db.insert_peer_id(swap_id, self.alice_peer_id).await?;
 
-        let swap = bob::Swap::new(
+        Ok(bob::Swap::new(
             db,
             swap_id,
             self.bitcoin_wallet.clone(),
             self.monero_wallet.clone(),
             self.env_config,
             handle,
-            self.monero_wallet
-                .main_wallet()
-                .await
-                .main_address()
-                .await
-                .into(),
+            self.monero_wallet.main_address().await.into(),
             self.bitcoin_wallet.new_address().await?,
             btc_amount,
             bitcoin::Amount::from_sat(1000), // Fixed fee of 1000 satoshis for now
-        );
+            None,
+        ))
+    }
 
-        Ok((swap, event_loop))
+    pub async fn open_db(&self) -> Result<Arc<SqliteDatabase>> {
+        if let Some(parent_dir) = self.db_path.parent() {
+            ensure_directory_exists(parent_dir)?;
+        }
+        if !self.db_path.exists() {
+            tokio::fs::File::create(&self.db_path).await?;
+        }
+
+        Ok(Arc::new(
+            SqliteDatabase::open(&self.db_path, AccessMode::ReadWrite).await?,
+        ))
     }
 
     pub async fn new_eventloop(
         &self,
-        swap_id: Uuid,
         db: Arc<dyn Database + Send + Sync>,
-    ) -> Result<(cli::EventLoop, cli::EventLoopHandle)> {
+    ) -> Result<cli::EventLoop> {
         let identity = self.seed.derive_libp2p_identity();
 
         let behaviour = cli::Behaviour::new(
-            self.alice_peer_id,
             self.env_config,
             self.bitcoin_wallet.clone(),
             (identity.clone(), XmrBtcNamespace::Testnet),
         );
-        let mut swarm = swarm::cli(identity.clone(), None, behaviour).await?;
-        swarm.add_peer_address(self.alice_peer_id, self.alice_address.clone());
+        let swarm = swarm::cli(identity.clone(), None, behaviour).await?;
 
-        cli::EventLoop::new(swap_id, swarm, self.alice_peer_id, db.clone())
+        Ok(cli::EventLoop::new(swarm, db).with_message_faults(self.message_faults.clone()))
     }
 }
 
@@ -603,8 +810,25 @@ pub struct TestContext {
     bob_bitcoin_wallet: Arc<bitcoin::Wallet>,
     bob_monero_wallet: Arc<monero::Wallets>,
 
-    // Store the container ID as String instead of reference
-    monerod_container_id: String,
+    // Store the container ID as String instead of reference, `None` on simulated blockchains
+    monerod_container_id: Option<String>,
+
+    /// Set if the test runs on simulated blockchains, see [`setup_simulated_test`].
+    pub simulated: Option<SimulatedChains>,
+
+    /// Faults injected into the messages Alice and Bob send each other.
+    pub message_faults: MessageFaults,
+}
+
+/// The blockchains of a test started with [`setup_simulated_test`].
+///
+/// Blocks are mined in the background, tests can mine more, reorg or evict
+/// transactions to exercise edge cases.
+pub struct SimulatedChains {
+    pub bitcoin: bitcoin::simulated::Blockchain,
+    pub monero: monero::simulated::Blockchain,
+    _bitcoin_miner: AbortOnDropHandle<()>,
+    _monero_miner: AbortOnDropHandle<()>,
 }
 
 impl TestContext {
@@ -629,6 +853,7 @@ impl TestContext {
             self.env_config,
             self.alice_bitcoin_wallet.clone(),
             self.alice_monero_wallet.clone(),
+            self.message_faults.clone(),
         )
         .await;
 
@@ -668,6 +893,40 @@ impl TestContext {
         (swap, BobApplicationHandle(join_handle))
     }
 
+    /// Like [`TestContext::bob_swap`] but the swaps share one event loop, like the swaps started
+    /// through the API do.
+    pub async fn bob_swaps(
+        &mut self,
+        count: usize,
+    ) -> (Vec<bob::Swap>, cli::EventLoopService, BobApplicationHandle) {
+        let (swaps, event_loop) = self
+            .bob_params
+            .new_swaps(self.btc_amount, count)
+            .await
+            .unwrap();
+
+        self.bob_bitcoin_wallet.sync().await.unwrap();
+
+        let service = event_loop.service();
+        let join_handle = tokio::spawn(event_loop.run());
+
+        (swaps, service, BobApplicationHandle(join_handle))
+    }
+
+    /// Resume Bob's swap on an event loop which is already running.
+    pub async fn resume_bob_on(
+        &self,
+        event_loop: &cli::EventLoopService,
+        swap_id: Uuid,
+    ) -> bob::Swap {
+        let db = self.bob_params.open_db().await.unwrap();
+
+        self.bob_params
+            .swap_from_db_on(event_loop, db, swap_id)
+            .await
+            .unwrap()
+    }
+
     pub async fn assert_alice_redeemed(&mut self, state: AliceState) {
         assert!(matches!(state, AliceState::BtcRedeemed));
 
@@ -680,7 +939,7 @@ impl TestContext {
         .unwrap();
 
         assert_eventual_balance(
-            &*self.alice_monero_wallet.main_wallet().await,
+            self.alice_monero_wallet.as_ref(),
             Ordering::Less,
             self.alice_redeemed_xmr_balance(),
         )
@@ -701,7 +960,7 @@ impl TestContext {
 
         // Alice pays fees - comparison does not take exact lock fee into account
         assert_eventual_balance(
-            &*self.alice_monero_wallet.main_wallet().await,
+            self.alice_monero_wallet.as_ref(),
             Ordering::Greater,
             self.alice_refunded_xmr_balance(),
         )
@@ -725,7 +984,7 @@ impl TestContext {
         .unwrap();
 
         assert_eventual_balance(
-            &*self.alice_monero_wallet.main_wallet().await,
+            self.alice_monero_wallet.as_ref(),
             Ordering::Less,
             self.alice_punished_xmr_balance(),
         )
@@ -733,6 +992,19 @@ impl TestContext {
         .unwrap();
     }
 
+    pub async fn assert_alice_early_refunded(&self, state: AliceState) {
+        assert!(matches!(state, AliceState::BtcEarlyRefunded(_)));
+
+        // Alice never gets any Bitcoin and might not have had any Monero left to lock
+        assert_eventual_balance(
+            self.alice_bitcoin_wallet.as_ref(),
+            Ordering::Equal,
+            self.alice_starting_balances.btc,
+        )
+        .await
+        .unwrap();
+    }
+
     pub async fn assert_bob_redeemed(&self, state: BobState) {
         assert_eventual_balance(
             self.bob_bitcoin_wallet.as_ref(),
@@ -743,7 +1015,7 @@ impl TestContext {
         .unwrap();
 
         assert_eventual_balance(
-            &*self.bob_monero_wallet.main_wallet().await,
+            self.bob_monero_wallet.as_ref(),
             Ordering::Greater,
             self.bob_redeemed_xmr_balance(),
         )
@@ -776,7 +1048,35 @@ impl TestContext {
         assert!(bob_cancelled_and_refunded);
 
         assert_eventual_balance(
-            &*self.bob_monero_wallet.main_wallet().await,
+            self.bob_monero_wallet.as_ref(),
+            Ordering::Equal,
+            self.bob_refunded_xmr_balance(),
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn assert_bob_early_refunded(&self, state: BobState) {
+        let (lock_tx_id, early_refund_fee) = match state {
+            BobState::BtcEarlyRefunded(state6) => (state6.tx_lock_id(), state6.tx_refund_fee),
+            _ => panic!("Bob is not in btc early refunded state: {:?}", state),
+        };
+        let lock_tx_bitcoin_fee = self
+            .bob_bitcoin_wallet
+            .transaction_fee(lock_tx_id)
+            .await
+            .unwrap();
+
+        assert_eventual_balance(
+            self.bob_bitcoin_wallet.as_ref(),
+            Ordering::Equal,
+            self.bob_starting_balances.btc - lock_tx_bitcoin_fee - early_refund_fee,
+        )
+        .await
+        .unwrap();
+
+        assert_eventual_balance(
+            self.bob_monero_wallet.as_ref(),
             Ordering::Equal,
             self.bob_refunded_xmr_balance(),
         )
@@ -794,7 +1094,7 @@ impl TestContext {
         .unwrap();
 
         assert_eventual_balance(
-            &*self.bob_monero_wallet.main_wallet().await,
+            self.bob_monero_wallet.as_ref(),
             Ordering::Equal,
             self.bob_punished_xmr_balance(),
         )
@@ -886,11 +1186,16 @@ impl TestContext {
     }
 
     pub async fn stop_alice_monero_wallet_rpc(&self) {
+        let monerod_container_id = self
+            .monerod_container_id
+            .as_ref()
+            .expect("Killing monerod requires the Docker test setup");
+
         tracing::info!("Killing monerod container");
 
         // Use Docker CLI to forcefully kill the container
         let output = tokio::process::Command::new("docker")
-            .args(["kill", &self.monerod_container_id])
+            .args(["kill", monerod_container_id])
             .output()
             .await
             .expect("Failed to execute docker kill command");
@@ -898,21 +1203,35 @@ impl TestContext {
         if output.status.success() {
             tracing::info!(
                 "Successfully killed monerod container: {}",
-                &self.monerod_container_id
+                monerod_container_id
             );
         } else {
             let stderr = String::from_utf8_lossy(&output.stderr);
             tracing::error!(
                 "Failed to kill monerod container {}: {}",
-                &self.monerod_container_id,
+                monerod_container_id,
                 stderr
             );
         }
     }
 
     pub async fn empty_alice_monero_wallet(&self) {
-        let burn_address = monero::Address::from_str("49LEH26DJGuCyr8xzRAzWPUryzp7bpccC7Hie1DiwyfJEyUKvMFAethRLybDYrFdU1eHaMkKQpUPebY4WT3cSjEvThmpjPa").unwrap();
-        let wallet = self.alice_monero_wallet.main_wallet().await;
+        let burn_address = monero::Address::from_str(MONERO_BURN_ADDRESS).unwrap();
+
+        if let Some(chains) = &self.simulated {
+            // Only unlocked funds can be swept
+            chains.monero.mine(monero::simulated::UNLOCK_CONFIRMATIONS);
+
+            let alice_address = self.alice_monero_wallet.main_address().await;
+            chains
+                .monero
+                .sweep(&alice_address, &[burn_address], &[100.0])
+                .expect("Failed to empty alice monero wallet to burn address");
+
+            return;
+        }
+
+        let wallet = self.alice_monero_wallet.main_wallet().await.unwrap();
 
         wallet
             .sweep(&burn_address)
@@ -921,10 +1240,13 @@ impl TestContext {
     }
 
     pub async fn assert_alice_monero_wallet_empty(&self) {
-        let wallet = self.alice_monero_wallet.main_wallet().await;
-        assert_eventual_balance(&*wallet, Ordering::Equal, monero::Amount::ZERO)
-            .await
-            .unwrap();
+        assert_eventual_balance(
+            self.alice_monero_wallet.as_ref(),
+            Ordering::Equal,
+            monero::Amount::ZERO,
+        )
+        .await
+        .unwrap();
     }
 }
 
@@ -984,7 +1306,7 @@ trait Wallet {
     fn get_balance(&self) -> impl Future<Output = Result<Self::Amount>>;
 }
 
-impl Wallet for monero::Wallet {
+impl Wallet for monero::Wallets {
     type Amount = monero::Amount;
 
     async fn refresh(&self) -> Result<()> {
@@ -1086,10 +1408,18 @@ struct Containers<'a> {
 pub mod alice_run_until {
     use swap::protocol::alice::AliceState;
 
+    pub fn is_btc_locked(state: &AliceState) -> bool {
+        matches!(state, AliceState::BtcLocked { .. })
+    }
+
     pub fn is_xmr_lock_transaction_sent(state: &AliceState) -> bool {
         matches!(state, AliceState::XmrLockTransactionSent { .. })
     }
 
+    pub fn is_xmr_lock_transfer_proof_sent(state: &AliceState) -> bool {
+        matches!(state, AliceState::XmrLockTransferProofSent { .. })
+    }
+
     pub fn is_encsig_learned(state: &AliceState) -> bool {
         matches!(state, AliceState::EncSigLearned { .. })
     }
@@ -1130,6 +1460,19 @@ impl GetConfig for SlowCancelConfig {
     }
 }
 
+/// Alice keeps trying to lock the Monero for longer than a test runs.
+pub struct SlowMoneroLockConfig;
+
+impl GetConfig for SlowMoneroLockConfig {
+    fn get_config() -> Config {
+        Config {
+            bitcoin_cancel_timelock: CancelTimelock::new(180),
+            monero_lock_retry_timeout: Duration::from_secs(10 * 60),
+            ..env::Regtest::get_config()
+        }
+    }
+}
+
 pub struct FastCancelConfig;
 
 impl GetConfig for FastCancelConfig {
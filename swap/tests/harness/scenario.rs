@@ -0,0 +1,401 @@
+//! Table driven fault injection on [simulated](super::SimulatedChains) blockchains.
+//!
+//! A [`Scenario`] describes what goes wrong while Alice and Bob run a single swap:
+//! a party crashing after a state transition, messages getting lost or delayed,
+//! a chain not producing blocks for a while, Bob changing his mind. [`run`]
+//! drives the swap through the scenario and checks that
+//!
+//! - both parties end in a terminal state (see `is_complete` of Alice and Bob),
+//! - the states agree on the outcome and it is the expected one,
+//! - no party loses funds, the balances match the outcome.
+
+use super::{setup_simulated_test, TestContext, MONERO_BURN_ADDRESS};
+use anyhow::{bail, Context, Result};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use swap::asb::FixedRate;
+use swap::bitcoin;
+use swap::env::GetConfig;
+use swap::monero;
+use swap::network::fault_injection::{Fault, Message};
+use swap::protocol::alice::AliceState;
+use swap::protocol::bob::BobState;
+use swap::protocol::{alice, bob, Database};
+use tokio::task::JoinHandle;
+
+/// Fail the scenario if the swap did not finish within this time.
+const SCENARIO_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// What happens once a party reaches a state.
+#[derive(Debug, Clone, Copy)]
+pub enum Action {
+    /// The party crashes and is restarted from its database right away.
+    Restart,
+    /// The party crashes and is only restarted once the other party finished its swap.
+    RestartOnceOtherPartyDone,
+    /// The background miner of the Bitcoin blockchain stops for the given duration.
+    StallBitcoin(Duration),
+    /// The background miner of the Monero blockchain stops for the given duration.
+    StallMonero(Duration),
+    /// Bob crashes and asks Alice to refund his Bitcoin early once he is restarted.
+    RequestEarlyRefund,
+    /// All of Alice's Monero is swept away, she cannot lock it anymore.
+    SweepAliceMonero,
+}
+
+/// How the swap ended, derived from the final states of both parties.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Alice redeemed the Bitcoin, Bob redeemed the Monero.
+    Redeemed,
+    /// Bob refunded the Bitcoin, Alice refunded the Monero.
+    Refunded,
+    /// Alice punished Bob, Bob did not get the Monero.
+    Punished,
+    /// Alice punished Bob, who then redeemed the Monero with her cooperation.
+    PunishedAndCooperativelyRedeemed,
+    /// Alice agreed to refund Bob's Bitcoin before she locked the Monero.
+    EarlyRefunded,
+}
+
+#[derive(Clone)]
+pub struct Scenario {
+    name: &'static str,
+    message_faults: Vec<(Message, Fault, u32)>,
+    alice: Vec<(fn(&AliceState) -> bool, Action)>,
+    bob: Vec<(fn(&BobState) -> bool, Action)>,
+    expected: Outcome,
+}
+
+impl Scenario {
+    /// A scenario in which nothing goes wrong and the swap is expected to be redeemed.
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            message_faults: Vec::new(),
+            alice: Vec::new(),
+            bob: Vec::new(),
+            expected: Outcome::Redeemed,
+        }
+    }
+
+    /// Apply `fault` to the first `times` messages of kind `message`.
+    pub fn message_fault(mut self, message: Message, fault: Fault, times: u32) -> Self {
+        self.message_faults.push((message, fault, times));
+        self
+    }
+
+    /// Perform `action` the first time Alice reaches a state matching `at`.
+    pub fn alice(mut self, at: fn(&AliceState) -> bool, action: Action) -> Self {
+        self.alice.push((at, action));
+        self
+    }
+
+    /// Perform `action` the first time Bob reaches a state matching `at`.
+    pub fn bob(mut self, at: fn(&BobState) -> bool, action: Action) -> Self {
+        self.bob.push((at, action));
+        self
+    }
+
+    pub fn expect(mut self, outcome: Outcome) -> Self {
+        self.expected = outcome;
+        self
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name)
+    }
+}
+
+/// Run `scenario` on freshly set up simulated blockchains with the given config.
+pub async fn test<C: GetConfig>(config: C, scenario: Scenario) {
+    setup_simulated_test(config, |mut ctx| {
+        let scenario = scenario.clone();
+
+        async move { run(&mut ctx, scenario).await }
+    })
+    .await
+}
+
+/// Run a swap between Alice and Bob through `scenario` and check the invariants.
+pub async fn run(ctx: &mut TestContext, scenario: Scenario) -> Result<()> {
+    tracing::info!(%scenario, "Running scenario");
+
+    let swap = run_swap(ctx, &scenario);
+    let (alice_state, bob_state) = tokio::time::timeout(SCENARIO_TIMEOUT, swap)
+        .await
+        .with_context(|| format!("Scenario `{}` did not finish in time", scenario))??;
+
+    tracing::info!(%scenario, %alice_state, %bob_state, "Swap finished, checking invariants");
+
+    let outcome = match (&alice_state, &bob_state) {
+        (AliceState::BtcRedeemed, BobState::XmrRedeemed { .. }) => {
+            ctx.assert_alice_redeemed(alice_state.clone()).await;
+            ctx.assert_bob_redeemed(bob_state.clone()).await;
+            Outcome::Redeemed
+        }
+        (AliceState::XmrRefunded, BobState::BtcRefunded(..)) => {
+            ctx.assert_alice_refunded(alice_state.clone()).await;
+            ctx.assert_bob_refunded(bob_state.clone()).await;
+            Outcome::Refunded
+        }
+        (AliceState::BtcPunished { .. }, BobState::BtcPunished { .. }) => {
+            ctx.assert_alice_punished(alice_state.clone()).await;
+            ctx.assert_bob_punished(bob_state.clone()).await;
+            Outcome::Punished
+        }
+        (AliceState::BtcPunished { .. }, BobState::XmrRedeemed { .. }) => {
+            ctx.assert_alice_punished(alice_state.clone()).await;
+            ctx.assert_bob_redeemed(bob_state.clone()).await;
+            Outcome::PunishedAndCooperativelyRedeemed
+        }
+        (AliceState::BtcEarlyRefunded(_), BobState::BtcEarlyRefunded(_)) => {
+            ctx.assert_alice_early_refunded(alice_state.clone()).await;
+            ctx.assert_bob_early_refunded(bob_state.clone()).await;
+            Outcome::EarlyRefunded
+        }
+        _ => bail!(
+            "Scenario `{}` ended in inconsistent states: Alice is {}, Bob is {}",
+            scenario,
+            alice_state,
+            bob_state
+        ),
+    };
+
+    if outcome != scenario.expected {
+        bail!(
+            "Scenario `{}` ended with {:?} but {:?} was expected",
+            scenario,
+            outcome,
+            scenario.expected
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_swap(ctx: &mut TestContext, scenario: &Scenario) -> Result<(AliceState, BobState)> {
+    let chains = ctx
+        .simulated
+        .as_ref()
+        .context("Scenarios only run on simulated blockchains")?;
+    let bitcoin = chains.bitcoin.clone();
+    let monero = chains.monero.clone();
+    let alice_monero_address = ctx.alice_monero_wallet.main_address().await;
+
+    for (message, fault, times) in &scenario.message_faults {
+        ctx.message_faults.inject(*message, *fault, *times);
+    }
+
+    let alice_triggers = Triggers::new(
+        scenario.alice.clone(),
+        &bitcoin,
+        &monero,
+        alice_monero_address,
+    );
+    let bob_triggers = Triggers::new(
+        scenario.bob.clone(),
+        &bitcoin,
+        &monero,
+        alice_monero_address,
+    );
+
+    let (bob_swap, mut bob_handle) = ctx.bob_swap().await;
+    let swap_id = bob_swap.id;
+    let mut bob = spawn_bob(bob_swap, bob_triggers.clone());
+
+    let alice_swap = ctx.alice_next_swap().await;
+    let mut alice = spawn_alice(alice_swap, alice_triggers.clone());
+
+    let mut alice_state = None;
+    let mut bob_state = None;
+    let mut alice_waiting_for_bob = false;
+    let mut bob_waiting_for_alice = false;
+
+    while alice_state.is_none() || bob_state.is_none() {
+        tokio::select! {
+            state = &mut alice, if alice_state.is_none() && !alice_waiting_for_bob => {
+                let state = state.context("Alice's swap panicked")??;
+
+                match alice_triggers.take_restart() {
+                    None => alice_state = Some(state),
+                    Some(Action::RestartOnceOtherPartyDone) if bob_state.is_none() => {
+                        tracing::info!(%state, "Alice crashed, restarting her once Bob is done");
+                        ctx.alice_handle.abort();
+                        alice_waiting_for_bob = true;
+                    }
+                    Some(_) => {
+                        tracing::info!(%state, "Alice crashed, restarting her");
+                        ctx.restart_alice().await;
+                        alice = spawn_alice(ctx.alice_next_swap().await, alice_triggers.clone());
+                    }
+                }
+            }
+            state = &mut bob, if bob_state.is_none() && !bob_waiting_for_alice => {
+                let state = state.context("Bob's swap panicked")??;
+
+                match bob_triggers.take_restart() {
+                    None => bob_state = Some(state),
+                    Some(Action::RestartOnceOtherPartyDone) if alice_state.is_none() => {
+                        tracing::info!(%state, "Bob crashed, restarting him once Alice is done");
+                        bob_handle.abort();
+                        bob_waiting_for_alice = true;
+                    }
+                    Some(Action::RequestEarlyRefund) => {
+                        tracing::info!(%state, "Bob crashed, restarting him with an early refund request");
+                        let (mut swap, handle) = ctx.stop_and_resume_bob_from_db(bob_handle, swap_id).await;
+                        request_early_refund(&mut swap).await?;
+                        bob_handle = handle;
+                        bob = spawn_bob(swap, bob_triggers.clone());
+                    }
+                    Some(_) => {
+                        tracing::info!(%state, "Bob crashed, restarting him");
+                        let (swap, handle) = ctx.stop_and_resume_bob_from_db(bob_handle, swap_id).await;
+                        bob_handle = handle;
+                        bob = spawn_bob(swap, bob_triggers.clone());
+                    }
+                }
+            }
+            else => bail!("Alice and Bob are both waiting for the other one to finish"),
+        }
+
+        if alice_waiting_for_bob && bob_state.is_some() {
+            tracing::info!("Bob is done, restarting Alice");
+            alice_waiting_for_bob = false;
+            ctx.restart_alice().await;
+            alice = spawn_alice(ctx.alice_next_swap().await, alice_triggers.clone());
+        }
+
+        if bob_waiting_for_alice && alice_state.is_some() {
+            tracing::info!("Alice is done, restarting Bob");
+            bob_waiting_for_alice = false;
+            let (swap, handle) = ctx.stop_and_resume_bob_from_db(bob_handle, swap_id).await;
+            bob_handle = handle;
+            bob = spawn_bob(swap, bob_triggers.clone());
+        }
+    }
+
+    Ok((
+        alice_state.expect("loop only ends once Alice is done"),
+        bob_state.expect("loop only ends once Bob is done"),
+    ))
+}
+
+/// Does what `swap early-refund` does for a swap that was stopped while waiting for Alice.
+pub async fn request_early_refund(swap: &mut bob::Swap) -> Result<()> {
+    let BobState::BtcLocked {
+        state3,
+        monero_wallet_restore_blockheight,
+    } = swap.state.clone()
+    else {
+        bail!(
+            "Early refunds can only be requested in BtcLocked, not {}",
+            swap.state
+        );
+    };
+
+    swap.state = BobState::BtcEarlyRefundRequested {
+        state3,
+        monero_wallet_restore_blockheight,
+    };
+    swap.db
+        .insert_latest_state(swap.id, swap.state.clone().into())
+        .await
+}
+
+fn spawn_alice(
+    swap: alice::Swap,
+    triggers: Arc<Triggers<AliceState>>,
+) -> JoinHandle<Result<AliceState>> {
+    tokio::spawn(alice::run_until(
+        swap,
+        move |state| triggers.on_transition(state),
+        FixedRate::default(),
+    ))
+}
+
+fn spawn_bob(swap: bob::Swap, triggers: Arc<Triggers<BobState>>) -> JoinHandle<Result<BobState>> {
+    tokio::spawn(bob::run_until(swap, move |state| {
+        bob::swap::is_complete(state) || triggers.on_transition(state)
+    }))
+}
+
+/// The actions of one party which have not been performed yet.
+struct Triggers<S> {
+    pending: Mutex<Vec<(fn(&S) -> bool, Action)>>,
+    /// The restart requested by the last transition, if any.
+    restart: Mutex<Option<Action>>,
+    bitcoin: bitcoin::simulated::Blockchain,
+    monero: monero::simulated::Blockchain,
+    alice_monero_address: monero::Address,
+}
+
+impl<S> Triggers<S> {
+    fn new(
+        actions: Vec<(fn(&S) -> bool, Action)>,
+        bitcoin: &bitcoin::simulated::Blockchain,
+        monero: &monero::simulated::Blockchain,
+        alice_monero_address: monero::Address,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            pending: Mutex::new(actions),
+            restart: Mutex::new(None),
+            bitcoin: bitcoin.clone(),
+            monero: monero.clone(),
+            alice_monero_address,
+        })
+    }
+
+    /// Called with every state the party reaches, returns whether it has to stop.
+    ///
+    /// Every action is performed at most once, such that a restarted party does not
+    /// crash again in the state it resumes from.
+    fn on_transition(&self, state: &S) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+
+        let mut restart = None;
+        pending.retain(|(at, action)| {
+            if !at(state) {
+                return true;
+            }
+
+            match action {
+                Action::Restart
+                | Action::RestartOnceOtherPartyDone
+                | Action::RequestEarlyRefund => restart = Some(*action),
+                Action::StallBitcoin(duration) => self.bitcoin.stall(*duration),
+                Action::StallMonero(duration) => self.monero.stall(*duration),
+                Action::SweepAliceMonero => self.sweep_alice_monero(),
+            }
+
+            false
+        });
+
+        if restart.is_none() {
+            return false;
+        }
+
+        *self.restart.lock().unwrap() = restart;
+
+        true
+    }
+
+    fn sweep_alice_monero(&self) {
+        let burn_address = monero::Address::from_str(MONERO_BURN_ADDRESS).unwrap();
+
+        // Only unlocked funds can be swept
+        self.monero.mine(monero::simulated::UNLOCK_CONFIRMATIONS);
+        self.monero
+            .sweep(&self.alice_monero_address, &[burn_address], &[100.0])
+            .expect("Failed to sweep Alice's Monero");
+    }
+
+    /// The restart requested when the party stopped, `None` if it stopped because it is done.
+    fn take_restart(&self) -> Option<Action> {
+        self.restart.lock().unwrap().take()
+    }
+}
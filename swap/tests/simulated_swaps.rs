@@ -0,0 +1,312 @@
+pub mod harness;
+
+use harness::alice_run_until::{
+    is_btc_locked as alice_is_btc_locked, is_encsig_learned, is_xmr_lock_transaction_sent,
+    is_xmr_lock_transfer_proof_sent,
+};
+use harness::bob_run_until::{is_btc_locked, is_encsig_sent, is_xmr_locked};
+use harness::scenario::request_early_refund;
+use harness::{FastCancelConfig, FastPunishConfig, SlowCancelConfig, SlowMoneroLockConfig};
+use std::time::Duration;
+use swap::asb::FixedRate;
+use swap::protocol::alice::AliceState;
+use swap::protocol::bob::BobState;
+use swap::protocol::{alice, bob, Database};
+use tokio::join;
+use tokio::time::timeout;
+
+#[tokio::test]
+async fn simulated_happy_path() {
+    harness::setup_simulated_test(SlowCancelConfig, |mut ctx| async move {
+        let (bob_swap, _) = ctx.bob_swap().await;
+        let bob_swap = tokio::spawn(bob::run(bob_swap));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run(alice_swap, FixedRate::default()));
+
+        let (bob_state, alice_state) = join!(bob_swap, alice_swap);
+
+        ctx.assert_alice_redeemed(alice_state??).await;
+        ctx.assert_bob_redeemed(bob_state??).await;
+
+        Ok(())
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn simulated_bob_restarts_after_xmr_is_locked() {
+    harness::setup_simulated_test(SlowCancelConfig, |mut ctx| async move {
+        let (bob_swap, bob_join_handle) = ctx.bob_swap().await;
+        let bob_swap_id = bob_swap.id;
+        let bob_swap = tokio::spawn(bob::run_until(bob_swap, is_xmr_locked));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run(alice_swap, FixedRate::default()));
+
+        let bob_state = bob_swap.await??;
+        assert!(matches!(bob_state, BobState::XmrLocked { .. }));
+
+        let (bob_swap, _) = ctx
+            .stop_and_resume_bob_from_db(bob_join_handle, bob_swap_id)
+            .await;
+        assert!(matches!(bob_swap.state, BobState::XmrLocked { .. }));
+
+        let bob_state = bob::run(bob_swap).await?;
+        ctx.assert_bob_redeemed(bob_state).await;
+
+        let alice_state = alice_swap.await??;
+        ctx.assert_alice_redeemed(alice_state).await;
+
+        Ok(())
+    })
+    .await;
+}
+
+/// Bob sends the encrypted signature while Alice's swap is not running, only her event loop.
+/// It buffers the signature, the resumed swap learns it from there and removes it once used.
+#[tokio::test]
+async fn simulated_encsig_buffered_while_alice_swap_is_not_running() {
+    harness::setup_simulated_test(SlowCancelConfig, |mut ctx| async move {
+        let (bob_swap, bob_handle) = ctx.bob_swap().await;
+        let swap_id = bob_swap.id;
+        let bob_swap = tokio::spawn(bob::run_until(bob_swap, is_encsig_sent));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_db = alice_swap.db.clone();
+        let alice_state = alice::run_until(
+            alice_swap,
+            is_xmr_lock_transfer_proof_sent,
+            FixedRate::default(),
+        )
+        .await?;
+        assert!(matches!(
+            alice_state,
+            AliceState::XmrLockTransferProofSent { .. }
+        ));
+
+        // The event loop acknowledges the buffered signature, Bob does not send it again
+        let bob_state = bob_swap.await??;
+        assert!(matches!(bob_state, BobState::EncSigSent(..)));
+        assert!(alice_db
+            .get_buffered_encrypted_signature(swap_id)
+            .await?
+            .is_some());
+
+        ctx.restart_alice().await;
+        let alice_swap = ctx.alice_next_swap().await;
+        assert!(matches!(
+            alice_swap.state,
+            AliceState::XmrLockTransferProofSent { .. }
+        ));
+        let alice_state =
+            alice::run_until(alice_swap, is_encsig_learned, FixedRate::default()).await?;
+        assert!(matches!(alice_state, AliceState::EncSigLearned { .. }));
+
+        ctx.restart_alice().await;
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_state = alice::run(alice_swap, FixedRate::default()).await?;
+        ctx.assert_alice_redeemed(alice_state).await;
+        assert!(alice_db
+            .get_buffered_encrypted_signature(swap_id)
+            .await?
+            .is_none());
+
+        let (bob_swap, _) = ctx.stop_and_resume_bob_from_db(bob_handle, swap_id).await;
+        let bob_state = bob::run(bob_swap).await?;
+        ctx.assert_bob_redeemed(bob_state).await;
+
+        Ok(())
+    })
+    .await;
+}
+
+/// Two swaps with the same maker run on one event loop at the same time. The second swap only
+/// starts once the first one locked its Bitcoin, so they cannot spend the same coins.
+#[tokio::test]
+async fn simulated_concurrent_swaps_on_one_event_loop() {
+    harness::setup_simulated_test(SlowCancelConfig, |mut ctx| async move {
+        let (bob_swaps, event_loop, _bob_join_handle) = ctx.bob_swaps(2).await;
+        let mut bob_swaps = bob_swaps.into_iter();
+        let (bob_swap_1, bob_swap_2) = (bob_swaps.next().unwrap(), bob_swaps.next().unwrap());
+
+        let bob_swap_1_id = bob_swap_1.id;
+        let bob_swap_1 = tokio::spawn(bob::run_until(bob_swap_1, is_btc_locked));
+
+        let alice_swap_1 = ctx.alice_next_swap().await;
+        let alice_swap_1 = tokio::spawn(alice::run(alice_swap_1, FixedRate::default()));
+
+        let bob_state_1 = bob_swap_1.await??;
+        assert!(matches!(bob_state_1, BobState::BtcLocked { .. }));
+
+        // The event loop keeps running for the second swap while the first one is resumed
+        let bob_swap_1 = ctx.resume_bob_on(&event_loop, bob_swap_1_id).await;
+        let bob_swap_1 = tokio::spawn(bob::run(bob_swap_1));
+        let bob_swap_2 = tokio::spawn(bob::run(bob_swap_2));
+
+        let alice_swap_2 = ctx.alice_next_swap().await;
+        let alice_swap_2 = tokio::spawn(alice::run(alice_swap_2, FixedRate::default()));
+
+        let (bob_state_1, bob_state_2, alice_state_1, alice_state_2) =
+            join!(bob_swap_1, bob_swap_2, alice_swap_1, alice_swap_2);
+
+        assert!(matches!(bob_state_1??, BobState::XmrRedeemed { .. }));
+        assert!(matches!(bob_state_2??, BobState::XmrRedeemed { .. }));
+        assert!(matches!(alice_state_1??, AliceState::BtcRedeemed));
+        assert!(matches!(alice_state_2??, AliceState::BtcRedeemed));
+
+        Ok(())
+    })
+    .await;
+}
+
+/// Alice stops after locking the Monero, Bob refunds. Alice refunds once she is back.
+#[tokio::test]
+async fn simulated_refund() {
+    harness::setup_simulated_test(FastCancelConfig, |mut ctx| async move {
+        let (bob_swap, _) = ctx.bob_swap().await;
+        let bob_swap = tokio::spawn(bob::run(bob_swap));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run_until(
+            alice_swap,
+            is_xmr_lock_transaction_sent,
+            FixedRate::default(),
+        ));
+
+        let bob_state = bob_swap.await??;
+        ctx.assert_bob_refunded(bob_state).await;
+
+        let alice_state = alice_swap.await??;
+        assert!(matches!(
+            alice_state,
+            AliceState::XmrLockTransactionSent { .. }
+        ));
+
+        ctx.restart_alice().await;
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_state = alice::run(alice_swap, FixedRate::default()).await?;
+        ctx.assert_alice_refunded(alice_state).await;
+
+        Ok(())
+    })
+    .await;
+}
+
+/// Bob stops after locking the Bitcoin, Alice punishes him.
+#[tokio::test]
+async fn simulated_punish() {
+    harness::setup_simulated_test(FastPunishConfig, |mut ctx| async move {
+        let (bob_swap, _) = ctx.bob_swap().await;
+        let bob_swap = tokio::spawn(bob::run_until(bob_swap, is_btc_locked));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run(alice_swap, FixedRate::default()));
+
+        let bob_state = bob_swap.await??;
+        assert!(matches!(bob_state, BobState::BtcLocked { .. }));
+
+        let alice_state = alice_swap.await??;
+        ctx.assert_alice_punished(alice_state).await;
+
+        Ok(())
+    })
+    .await;
+}
+
+/// Alice cannot lock the Monero and keeps retrying, Bob asks her to refund his Bitcoin early.
+#[tokio::test]
+async fn simulated_early_refund_requested_while_alice_retries_locking_xmr() {
+    harness::setup_simulated_test(SlowMoneroLockConfig, |mut ctx| async move {
+        let (bob_swap, bob_handle) = ctx.bob_swap().await;
+        let bob_swap_id = bob_swap.id;
+        let bob_swap = tokio::spawn(bob::run_until(bob_swap, is_btc_locked));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run_until(
+            alice_swap,
+            alice_is_btc_locked,
+            FixedRate::default(),
+        ));
+
+        let alice_state = alice_swap.await??;
+        assert!(matches!(alice_state, AliceState::BtcLocked { .. }));
+        let bob_state = bob_swap.await??;
+        assert!(matches!(bob_state, BobState::BtcLocked { .. }));
+
+        // Alice cannot lock the Monero anymore and retries until the request arrives
+        ctx.empty_alice_monero_wallet().await;
+        ctx.assert_alice_monero_wallet_empty().await;
+
+        ctx.restart_alice().await;
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run(alice_swap, FixedRate::default()));
+
+        let (mut bob_swap, _) = ctx
+            .stop_and_resume_bob_from_db(bob_handle, bob_swap_id)
+            .await;
+        request_early_refund(&mut bob_swap).await?;
+        let bob_swap = tokio::spawn(bob::run(bob_swap));
+
+        // Alice only gives up on locking the Monero by herself after minutes
+        let (alice_state, bob_state) = timeout(Duration::from_secs(120), async {
+            join!(alice_swap, bob_swap)
+        })
+        .await?;
+
+        assert!(matches!(alice_state??, AliceState::BtcEarlyRefunded(_)));
+        assert!(matches!(bob_state??, BobState::BtcEarlyRefunded(_)));
+
+        Ok(())
+    })
+    .await;
+}
+
+/// Bob asks for an early refund after Alice locked the Monero, she rejects and the swap continues.
+#[tokio::test]
+async fn simulated_early_refund_rejected_after_xmr_locked() {
+    harness::setup_simulated_test(SlowCancelConfig, |mut ctx| async move {
+        let (bob_swap, bob_handle) = ctx.bob_swap().await;
+        let bob_swap_id = bob_swap.id;
+        let bob_swap = tokio::spawn(bob::run_until(bob_swap, is_btc_locked));
+
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run_until(
+            alice_swap,
+            is_xmr_lock_transaction_sent,
+            FixedRate::default(),
+        ));
+
+        let bob_state = bob_swap.await??;
+        assert!(matches!(bob_state, BobState::BtcLocked { .. }));
+        let alice_state = alice_swap.await??;
+        assert!(matches!(
+            alice_state,
+            AliceState::XmrLockTransactionSent { .. }
+        ));
+
+        // Alice has not sent the transfer proof yet, Bob does not know that the Monero is locked
+        let (mut bob_swap, bob_handle) = ctx
+            .stop_and_resume_bob_from_db(bob_handle, bob_swap_id)
+            .await;
+        request_early_refund(&mut bob_swap).await?;
+        let bob_state = bob::run_until(bob_swap, is_btc_locked).await?;
+        assert!(matches!(bob_state, BobState::BtcLocked { .. }));
+
+        ctx.restart_alice().await;
+        let alice_swap = ctx.alice_next_swap().await;
+        let alice_swap = tokio::spawn(alice::run(alice_swap, FixedRate::default()));
+
+        let (bob_swap, _) = ctx
+            .stop_and_resume_bob_from_db(bob_handle, bob_swap_id)
+            .await;
+        let bob_state = bob::run(bob_swap).await?;
+        ctx.assert_bob_redeemed(bob_state).await;
+
+        let alice_state = alice_swap.await??;
+        ctx.assert_alice_redeemed(alice_state).await;
+
+        Ok(())
+    })
+    .await;
+}